@@ -0,0 +1,15 @@
+-- Saved payout addresses; an entry must be verified before it can receive claim payouts
+CREATE TABLE IF NOT EXISTS address_book_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_address VARCHAR(255) NOT NULL,
+    label VARCHAR(64) NOT NULL,
+    address VARCHAR(255) NOT NULL,
+    verification_nonce VARCHAR(64) NOT NULL,
+    verification_memo VARCHAR(28) NOT NULL UNIQUE,
+    verification_method VARCHAR(20) CHECK (verification_method IN ('signature', 'memo_payment')),
+    verified_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (owner_address, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_book_entries_owner ON address_book_entries(owner_address);
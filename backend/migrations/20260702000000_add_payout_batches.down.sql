@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS payouts_pending_crypto_idx;
+DROP INDEX IF EXISTS payouts_batch_id_idx;
+
+ALTER TABLE payouts
+    DROP COLUMN IF EXISTS updated_at,
+    DROP COLUMN IF EXISTS failure_reason,
+    DROP COLUMN IF EXISTS attempts,
+    DROP COLUMN IF EXISTS batch_id;
+
+DROP TABLE IF EXISTS payout_batches;
@@ -0,0 +1,24 @@
+-- On-chain transfer batches for crypto payouts
+CREATE TABLE payout_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    token_address TEXT NOT NULL,
+    transfer_count INTEGER NOT NULL CHECK (transfer_count > 0),
+    status TEXT NOT NULL DEFAULT 'submitting'
+        CHECK (status IN ('submitting', 'confirmed', 'partial', 'failed')),
+    tx_hash TEXT,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX payout_batches_status_idx ON payout_batches (status);
+
+ALTER TABLE payouts
+    ADD COLUMN batch_id UUID REFERENCES payout_batches (id) ON DELETE SET NULL,
+    ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN failure_reason TEXT,
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX payouts_batch_id_idx ON payouts (batch_id);
+CREATE INDEX payouts_pending_crypto_idx ON payouts (created_at)
+    WHERE payout_type = 'crypto' AND status = 'pending';
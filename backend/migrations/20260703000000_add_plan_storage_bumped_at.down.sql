@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_plans_storage_bump_due;
+ALTER TABLE plans DROP COLUMN IF EXISTS storage_bumped_at;
@@ -0,0 +1,6 @@
+-- Tracks the last on-chain TTL extension so plans are bumped before Soroban archives them
+ALTER TABLE plans ADD COLUMN IF NOT EXISTS storage_bumped_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_plans_storage_bump_due
+    ON plans (COALESCE(storage_bumped_at, created_at))
+    WHERE status <> 'PAID_OUT';
@@ -0,0 +1,41 @@
+-- In-app notifications addressed to a wallet
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_address TEXT NOT NULL,
+    notification_type TEXT NOT NULL,
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    is_read BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX notifications_user_address_created_at_idx ON notifications (user_address, created_at DESC);
+
+-- Fiat off-ramp transfers of claim payouts through a SEP-24/SEP-31 anchor
+CREATE TABLE withdrawals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    payout_id UUID NOT NULL REFERENCES payouts (id) ON DELETE RESTRICT,
+    beneficiary_address TEXT NOT NULL,
+    protocol TEXT NOT NULL CHECK (protocol IN ('sep24', 'sep31')),
+    asset_code TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    anchor_transaction_id TEXT UNIQUE,
+    interactive_url TEXT,
+    status TEXT NOT NULL DEFAULT 'incomplete',
+    status_message TEXT,
+    amount_out TEXT,
+    withdraw_anchor_account TEXT,
+    withdraw_memo TEXT,
+    stellar_transaction_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX withdrawals_beneficiary_address_idx ON withdrawals (beneficiary_address);
+CREATE INDEX withdrawals_open_idx ON withdrawals (updated_at)
+    WHERE status NOT IN ('completed', 'refunded', 'expired', 'error', 'no_market', 'too_small', 'too_large');
+-- At most one live (or completed) withdrawal per payout
+CREATE UNIQUE INDEX withdrawals_payout_open_idx ON withdrawals (payout_id)
+    WHERE status NOT IN ('refunded', 'expired', 'error', 'no_market', 'too_small', 'too_large');
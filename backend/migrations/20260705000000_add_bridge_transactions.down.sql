@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS bridge_attestations;
+DROP TABLE IF EXISTS bridge_transactions;
@@ -0,0 +1,38 @@
+-- Inbound cross-chain bridge transfers and the attestations that advance them
+CREATE TABLE IF NOT EXISTS bridge_transactions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_address TEXT NOT NULL,
+    source_chain TEXT NOT NULL,
+    source_tx_hash TEXT,
+    destination_address TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'locked', 'minted', 'failed')),
+    lock_tx_hash TEXT,
+    mint_tx_hash TEXT,
+    failure_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_at TIMESTAMPTZ,
+    minted_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS bridge_transactions_user_address_idx
+    ON bridge_transactions (user_address, created_at DESC);
+CREATE INDEX IF NOT EXISTS bridge_transactions_open_idx
+    ON bridge_transactions (status, updated_at)
+    WHERE status IN ('pending', 'locked');
+
+CREATE TABLE IF NOT EXISTS bridge_attestations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    bridge_transaction_id UUID NOT NULL REFERENCES bridge_transactions (id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    attester_address TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS bridge_attestations_transaction_idx
+    ON bridge_attestations (bridge_transaction_id);
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS asset_price_history;
+
+ALTER TABLE plans
+    DROP COLUMN IF EXISTS installment_interval_days,
+    DROP COLUMN IF EXISTS installment_count;
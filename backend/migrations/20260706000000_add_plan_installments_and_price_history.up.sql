@@ -0,0 +1,15 @@
+-- Installment distribution settings and token price history for payout projections
+ALTER TABLE plans
+    ADD COLUMN IF NOT EXISTS installment_count INTEGER NOT NULL DEFAULT 1
+        CHECK (installment_count BETWEEN 1 AND 600),
+    ADD COLUMN IF NOT EXISTS installment_interval_days INTEGER NOT NULL DEFAULT 30
+        CHECK (installment_interval_days > 0);
+
+CREATE TABLE IF NOT EXISTS asset_price_history (
+    token_address TEXT NOT NULL,
+    -- Price of one whole token; amounts are stored in base units.
+    price_usd NUMERIC(38, 10) NOT NULL CHECK (price_usd > 0),
+    decimals SMALLINT NOT NULL DEFAULT 7 CHECK (decimals BETWEEN 0 AND 38),
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (token_address, recorded_at)
+);
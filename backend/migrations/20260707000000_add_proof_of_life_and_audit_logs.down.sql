@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS proof_of_life;
+DROP TABLE IF EXISTS audit_logs;
@@ -0,0 +1,31 @@
+-- Append-only record of administrative and automated actions
+CREATE TABLE audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX audit_logs_subject_created_at_idx ON audit_logs (subject, created_at DESC);
+CREATE INDEX audit_logs_action_created_at_idx ON audit_logs (action, created_at DESC);
+
+-- Backend proof-of-life check-ins and their escalation state per wallet
+CREATE TABLE proof_of_life (
+    user_address TEXT PRIMARY KEY,
+    email TEXT,
+    secondary_email TEXT,
+    interval_days INTEGER NOT NULL DEFAULT 30 CHECK (interval_days BETWEEN 1 AND 3650),
+    last_check_in_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    stage TEXT NOT NULL DEFAULT 'active'
+        CHECK (stage IN ('active', 'reminded', 'contact_notified', 'escalated')),
+    stage_changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    paused_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX proof_of_life_open_stage_idx
+    ON proof_of_life (stage, stage_changed_at)
+    WHERE stage <> 'escalated';
@@ -0,0 +1,20 @@
+-- People to alert when an owner stops checking in or a plan becomes claimable
+CREATE TABLE emergency_contacts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_address TEXT NOT NULL,
+    name TEXT NOT NULL,
+    relationship TEXT,
+    email TEXT,
+    phone TEXT,
+    email_verified_at TIMESTAMPTZ,
+    phone_verified_at TIMESTAMPTZ,
+    email_code_hash TEXT,
+    phone_code_hash TEXT,
+    code_expires_at TIMESTAMPTZ,
+    last_alerted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT emergency_contacts_channel_required CHECK (email IS NOT NULL OR phone IS NOT NULL)
+);
+
+CREATE INDEX emergency_contacts_user_address_idx ON emergency_contacts (user_address);
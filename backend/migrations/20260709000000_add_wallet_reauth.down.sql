@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS wallet_challenges;
+DROP TABLE IF EXISTS wallet_reauth_settings;
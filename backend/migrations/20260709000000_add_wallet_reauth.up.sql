@@ -0,0 +1,23 @@
+-- Wallet signature re-authentication for sensitive actions
+CREATE TABLE wallet_reauth_settings (
+    wallet_address TEXT PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE wallet_challenges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    wallet_address TEXT NOT NULL,
+    action TEXT NOT NULL,
+    plan_id UUID,
+    amount NUMERIC,
+    nonce TEXT NOT NULL,
+    message TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT wallet_challenges_action_check
+        CHECK (action IN ('claim', 'deactivate_plan', 'disable_reauth'))
+);
+
+CREATE INDEX wallet_challenges_wallet_address_idx ON wallet_challenges (wallet_address, created_at);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS pending_changes;
+DROP TABLE IF EXISTS platform_settings;
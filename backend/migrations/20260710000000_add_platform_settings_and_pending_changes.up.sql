@@ -0,0 +1,27 @@
+-- Runtime platform settings changed through maker-checker review
+CREATE TABLE platform_settings (
+    key TEXT PRIMARY KEY,
+    value JSONB NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE pending_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    setting_key TEXT NOT NULL,
+    changes JSONB NOT NULL,
+    base_version INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    proposed_by TEXT NOT NULL,
+    proposed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    reviewed_by TEXT,
+    reviewed_at TIMESTAMPTZ,
+    review_note TEXT,
+    CONSTRAINT pending_changes_status_check
+        CHECK (status IN ('pending', 'applied', 'rejected', 'expired', 'superseded'))
+);
+
+CREATE INDEX pending_changes_status_idx ON pending_changes (status, expires_at);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS notifications_unemailed_idx;
+ALTER TABLE notifications DROP COLUMN IF EXISTS emailed_at;
+DROP TABLE IF EXISTS notification_preferences;
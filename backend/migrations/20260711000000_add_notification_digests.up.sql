@@ -0,0 +1,16 @@
+-- Email delivery preferences and digest tracking for notifications
+CREATE TABLE notification_preferences (
+    user_address TEXT PRIMARY KEY,
+    email TEXT,
+    digest_frequency TEXT NOT NULL DEFAULT 'immediate',
+    last_digest_sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT notification_preferences_frequency_check
+        CHECK (digest_frequency IN ('immediate', 'hourly', 'daily'))
+);
+
+ALTER TABLE notifications ADD COLUMN emailed_at TIMESTAMPTZ;
+
+CREATE INDEX notifications_unemailed_idx ON notifications (user_address, created_at)
+    WHERE emailed_at IS NULL;
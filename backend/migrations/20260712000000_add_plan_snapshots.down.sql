@@ -0,0 +1,6 @@
+DROP TRIGGER IF EXISTS beneficiaries_snapshot ON beneficiaries;
+DROP TRIGGER IF EXISTS plans_snapshot ON plans;
+DROP TABLE IF EXISTS plan_snapshots;
+DROP FUNCTION IF EXISTS reject_plan_snapshot_rewrite();
+DROP FUNCTION IF EXISTS record_plan_snapshot();
+DROP FUNCTION IF EXISTS plan_state(UUID);
@@ -0,0 +1,117 @@
+-- Append-only history of plan state, one row per transaction that changed a plan
+CREATE TABLE plan_snapshots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- No foreign key: history outlives the plan row
+    plan_id UUID NOT NULL,
+    txid BIGINT NOT NULL DEFAULT txid_current(),
+    change_kind TEXT NOT NULL,
+    -- Plan row plus its beneficiaries; NULL once the plan is deleted
+    state JSONB,
+    captured_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp(),
+    CONSTRAINT plan_snapshots_plan_txid_unique UNIQUE (plan_id, txid),
+    CONSTRAINT plan_snapshots_change_kind_check
+        CHECK (change_kind IN ('baseline', 'created', 'updated', 'deleted'))
+);
+
+CREATE INDEX plan_snapshots_plan_captured_idx ON plan_snapshots (plan_id, captured_at DESC);
+
+CREATE OR REPLACE FUNCTION plan_state(target_plan_id UUID)
+RETURNS JSONB AS $$
+    SELECT to_jsonb(p)
+        -- Amounts as strings so NUMERIC(78, _) survives JSON parsing
+        || jsonb_build_object(
+            'amount', p.amount::text,
+            'accrued_yield', p.accrued_yield::text,
+            'beneficiaries', COALESCE(
+                (SELECT jsonb_agg(to_jsonb(b) - 'plan_id' ORDER BY b.wallet_address)
+                 FROM beneficiaries b
+                 WHERE b.plan_id = p.id),
+                '[]'::jsonb
+            )
+        )
+    FROM plans p
+    WHERE p.id = target_plan_id;
+$$ LANGUAGE sql STABLE;
+
+-- Runs at commit, so a plan created together with its beneficiaries is
+-- captured once, in its final state.
+CREATE OR REPLACE FUNCTION record_plan_snapshot()
+RETURNS TRIGGER AS $$
+DECLARE
+    target_plan_id UUID;
+    current_state JSONB;
+    previous_state JSONB;
+    has_history BOOLEAN;
+BEGIN
+    IF TG_TABLE_NAME = 'plans' THEN
+        target_plan_id := COALESCE(NEW.id, OLD.id);
+    ELSE
+        target_plan_id := COALESCE(NEW.plan_id, OLD.plan_id);
+    END IF;
+
+    current_state := plan_state(target_plan_id);
+
+    SELECT state, TRUE
+    INTO previous_state, has_history
+    FROM plan_snapshots
+    WHERE plan_id = target_plan_id AND txid <> txid_current()
+    ORDER BY captured_at DESC
+    LIMIT 1;
+
+    IF has_history AND previous_state IS NOT DISTINCT FROM current_state THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO plan_snapshots (plan_id, change_kind, state)
+    VALUES (
+        target_plan_id,
+        CASE
+            WHEN current_state IS NULL THEN 'deleted'
+            WHEN has_history THEN 'updated'
+            ELSE 'created'
+        END,
+        current_state
+    )
+    ON CONFLICT (plan_id, txid) DO UPDATE
+    SET state = EXCLUDED.state,
+        change_kind = CASE
+            WHEN EXCLUDED.state IS NULL THEN 'deleted'
+            ELSE plan_snapshots.change_kind
+        END,
+        captured_at = EXCLUDED.captured_at;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER plans_snapshot
+    AFTER INSERT OR UPDATE OR DELETE ON plans
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW
+    EXECUTE FUNCTION record_plan_snapshot();
+
+CREATE CONSTRAINT TRIGGER beneficiaries_snapshot
+    AFTER INSERT OR UPDATE OR DELETE ON beneficiaries
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW
+    EXECUTE FUNCTION record_plan_snapshot();
+
+-- Rows may only be changed by the transaction that wrote them
+CREATE OR REPLACE FUNCTION reject_plan_snapshot_rewrite()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.txid <> txid_current() THEN
+        RAISE EXCEPTION 'plan_snapshots is append-only';
+    END IF;
+    RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER plan_snapshots_append_only
+    BEFORE UPDATE OR DELETE ON plan_snapshots
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_plan_snapshot_rewrite();
+
+-- Existing plans start with their current state
+INSERT INTO plan_snapshots (plan_id, change_kind, state)
+SELECT id, 'baseline', plan_state(id) FROM plans;
@@ -0,0 +1,37 @@
+-- Admin-defined reports, run on demand or on a cron schedule
+CREATE TABLE report_definitions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    entity TEXT NOT NULL,
+    columns TEXT[] NOT NULL DEFAULT '{}',
+    filters JSONB NOT NULL DEFAULT '[]',
+    aggregation JSONB,
+    -- Cron expression (UTC); NULL for on-demand only
+    schedule TEXT,
+    recipients TEXT[] NOT NULL DEFAULT '{}',
+    next_run_at TIMESTAMPTZ,
+    last_run_at TIMESTAMPTZ,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT report_definitions_entity_check
+        CHECK (entity IN ('plans', 'claims', 'kyc', 'fees'))
+);
+
+CREATE INDEX report_definitions_due_idx ON report_definitions (next_run_at)
+    WHERE schedule IS NOT NULL;
+
+CREATE TABLE report_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    report_id UUID NOT NULL REFERENCES report_definitions (id) ON DELETE CASCADE,
+    triggered_by TEXT NOT NULL,
+    status TEXT NOT NULL,
+    row_count INTEGER,
+    emailed_to TEXT[] NOT NULL DEFAULT '{}',
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+    CONSTRAINT report_runs_status_check CHECK (status IN ('succeeded', 'failed'))
+);
+
+CREATE INDEX report_runs_report_started_idx ON report_runs (report_id, started_at DESC);
@@ -0,0 +1,22 @@
+-- Beneficiary claims waiting out the cooling-off window before payout
+CREATE TABLE claim_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES plans (id) ON DELETE CASCADE,
+    requested_by TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    execute_after TIMESTAMPTZ NOT NULL,
+    cancelled_by TEXT,
+    cancel_reason TEXT,
+    failure_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    CONSTRAINT claim_requests_status_check
+        CHECK (status IN ('pending', 'executed', 'cancelled', 'failed'))
+);
+
+-- At most one claim may be waiting on a plan at a time
+CREATE UNIQUE INDEX claim_requests_one_pending_idx ON claim_requests (plan_id)
+    WHERE status = 'pending';
+
+CREATE INDEX claim_requests_due_idx ON claim_requests (execute_after)
+    WHERE status = 'pending';
@@ -0,0 +1,16 @@
+ALTER TABLE notifications ADD COLUMN emailed_at TIMESTAMPTZ;
+
+UPDATE notifications n
+SET emailed_at = d.sent_at
+FROM notification_deliveries d
+WHERE d.notification_id = n.id AND d.channel = 'email' AND d.status = 'sent';
+
+CREATE INDEX notifications_unemailed_idx ON notifications (user_address, created_at)
+    WHERE emailed_at IS NULL;
+
+DROP INDEX IF EXISTS notifications_user_address_status_idx;
+DROP TABLE IF EXISTS notification_deliveries;
+
+ALTER TABLE notifications
+    DROP CONSTRAINT IF EXISTS notifications_status_check,
+    DROP COLUMN IF EXISTS status;
@@ -0,0 +1,56 @@
+-- Per-channel delivery tracking for notifications
+ALTER TABLE notifications
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'sent',
+    ADD CONSTRAINT notifications_status_check
+        CHECK (status IN ('queued', 'sent', 'failed', 'read'));
+
+CREATE TABLE notification_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    notification_id UUID NOT NULL REFERENCES notifications (id) ON DELETE CASCADE,
+    channel TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT notification_deliveries_channel_check CHECK (channel IN ('email')),
+    CONSTRAINT notification_deliveries_status_check
+        CHECK (status IN ('queued', 'sent', 'failed')),
+    CONSTRAINT notification_deliveries_unique UNIQUE (notification_id, channel)
+);
+
+CREATE INDEX notification_deliveries_queued_idx ON notification_deliveries (next_attempt_at)
+    WHERE status = 'queued';
+CREATE INDEX notification_deliveries_failed_idx ON notification_deliveries (updated_at DESC)
+    WHERE status = 'failed';
+
+-- Carry over email state tracked by notifications.emailed_at
+INSERT INTO notification_deliveries (notification_id, channel, status, attempts, sent_at)
+SELECT n.id, 'email', 'sent', 1, n.emailed_at
+FROM notifications n
+WHERE n.emailed_at IS NOT NULL;
+
+INSERT INTO notification_deliveries (notification_id, channel)
+SELECT n.id, 'email'
+FROM notifications n
+JOIN notification_preferences p ON p.user_address = n.user_address
+WHERE n.emailed_at IS NULL
+  AND p.email IS NOT NULL
+  AND n.created_at >= p.created_at;
+
+UPDATE notifications n
+SET status = CASE
+    WHEN n.is_read THEN 'read'
+    WHEN EXISTS (
+        SELECT 1 FROM notification_deliveries d
+        WHERE d.notification_id = n.id AND d.status = 'queued'
+    ) THEN 'queued'
+    ELSE 'sent'
+END;
+
+CREATE INDEX notifications_user_address_status_idx ON notifications (user_address, status);
+
+DROP INDEX notifications_unemailed_idx;
+ALTER TABLE notifications DROP COLUMN emailed_at;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS dead_letters;
+
+ALTER TABLE notification_deliveries DROP COLUMN IF EXISTS attempt_history;
+ALTER TABLE payouts DROP COLUMN IF EXISTS attempt_history;
@@ -0,0 +1,54 @@
+-- Jobs that exhausted their retries, kept for inspection and requeue
+ALTER TABLE payouts ADD COLUMN attempt_history JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE notification_deliveries ADD COLUMN attempt_history JSONB NOT NULL DEFAULT '[]';
+
+CREATE TABLE dead_letters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    worker TEXT NOT NULL,
+    job_id UUID NOT NULL,
+    payload JSONB NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    attempt_history JSONB NOT NULL DEFAULT '[]',
+    status TEXT NOT NULL DEFAULT 'pending',
+    resolved_by TEXT,
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT dead_letters_worker_check
+        CHECK (worker IN ('payout_batcher', 'notification_digest')),
+    CONSTRAINT dead_letters_status_check
+        CHECK (status IN ('pending', 'requeued', 'discarded'))
+);
+
+-- A job has at most one open entry; it gets a new one if it fails again after a requeue
+CREATE UNIQUE INDEX dead_letters_pending_job_idx ON dead_letters (worker, job_id)
+    WHERE status = 'pending';
+CREATE INDEX dead_letters_created_at_idx ON dead_letters (created_at DESC);
+
+-- Carry over jobs that had already failed permanently
+INSERT INTO dead_letters (worker, job_id, payload, error, attempts)
+SELECT 'payout_batcher',
+       p.id,
+       jsonb_build_object(
+           'plan_id', p.plan_id,
+           'beneficiary_address', p.beneficiary_address,
+           'amount', p.amount::text
+       ),
+       COALESCE(p.failure_reason, 'unknown'),
+       p.attempts
+FROM payouts p
+WHERE p.payout_type = 'crypto' AND p.status = 'failed' AND p.attempts > 0;
+
+INSERT INTO dead_letters (worker, job_id, payload, error, attempts)
+SELECT 'notification_digest',
+       d.id,
+       jsonb_build_object(
+           'notification_id', d.notification_id,
+           'channel', d.channel,
+           'user_address', n.user_address
+       ),
+       COALESCE(d.last_error, 'unknown'),
+       d.attempts
+FROM notification_deliveries d
+JOIN notifications n ON n.id = d.notification_id
+WHERE d.status = 'failed';
@@ -0,0 +1,11 @@
+-- Self-managed profile details per wallet
+CREATE TABLE user_profiles (
+    user_address TEXT PRIMARY KEY,
+    display_name TEXT,
+    phone TEXT,
+    country TEXT,
+    preferred_language TEXT NOT NULL DEFAULT 'en',
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- Admin overrides and extra languages for the built-in notification templates
+CREATE TABLE notification_templates (
+    template_key TEXT NOT NULL,
+    language TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (template_key, language)
+);
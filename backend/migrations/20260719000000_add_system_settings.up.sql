@@ -0,0 +1,8 @@
+-- Admin overrides for operational parameters (TTLs, cooling-off, escalation
+-- windows). Keys without a row fall back to the built-in/env defaults.
+CREATE TABLE system_settings (
+    key TEXT PRIMARY KEY,
+    value BIGINT NOT NULL,
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS horizon_cursors;
+DROP TABLE IF EXISTS lending_events;
+ALTER TABLE plans
+    DROP COLUMN IF EXISTS funded_at,
+    DROP COLUMN IF EXISTS funded_amount;
@@ -0,0 +1,29 @@
+-- Deposits detected on the platform's deposit accounts
+ALTER TABLE plans
+    ADD COLUMN funded_amount NUMERIC(78, 0) NOT NULL DEFAULT 0,
+    ADD COLUMN funded_at TIMESTAMPTZ;
+
+CREATE TABLE lending_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_type TEXT NOT NULL CHECK (event_type IN ('deposit')),
+    -- NULL when the memo did not name a known plan
+    plan_id UUID REFERENCES plans (id) ON DELETE SET NULL,
+    user_address TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    transaction_hash TEXT NOT NULL,
+    -- Horizon operation id, so replayed pages are not recorded twice
+    external_id TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT lending_events_external_unique UNIQUE (event_type, external_id)
+);
+
+CREATE INDEX lending_events_plan_id_idx ON lending_events (plan_id, created_at);
+
+-- Last Horizon paging token processed for each watched account
+CREATE TABLE horizon_cursors (
+    account TEXT PRIMARY KEY,
+    cursor TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
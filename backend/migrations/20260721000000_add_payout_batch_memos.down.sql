@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS payout_batches_open_idx;
+DROP INDEX IF EXISTS payout_batches_memo_idx;
+ALTER TABLE payout_batches DROP COLUMN IF EXISTS memo;
+
+UPDATE payout_batches SET status = 'failed' WHERE status IN ('unconfirmed', 'expired');
+ALTER TABLE payout_batches DROP CONSTRAINT IF EXISTS payout_batches_status_check;
+ALTER TABLE payout_batches ADD CONSTRAINT payout_batches_status_check
+    CHECK (status IN ('submitting', 'confirmed', 'partial', 'failed'));
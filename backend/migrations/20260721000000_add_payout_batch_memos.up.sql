@@ -0,0 +1,11 @@
+-- Exactly-once payouts: each batch's transaction carries the batch memo so it
+-- can be found on-chain when its submission outcome is unknown
+ALTER TABLE payout_batches DROP CONSTRAINT IF EXISTS payout_batches_status_check;
+ALTER TABLE payout_batches ADD CONSTRAINT payout_batches_status_check
+    CHECK (status IN ('submitting', 'unconfirmed', 'confirmed', 'partial', 'failed', 'expired'));
+
+ALTER TABLE payout_batches ADD COLUMN memo TEXT;
+
+CREATE UNIQUE INDEX payout_batches_memo_idx ON payout_batches (memo) WHERE memo IS NOT NULL;
+CREATE INDEX payout_batches_open_idx ON payout_batches (created_at)
+    WHERE status IN ('submitting', 'unconfirmed');
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS http_audit (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    category VARCHAR(32) NOT NULL CHECK (category IN ('claim', 'kyc', 'admin_config')),
+    method VARCHAR(10) NOT NULL,
+    route VARCHAR(255) NOT NULL,
+    path VARCHAR(1024) NOT NULL,
+    actor VARCHAR(255),
+    status INTEGER NOT NULL,
+    request_body JSONB,
+    response_body JSONB,
+    duration_ms BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS http_audit_category_created_idx ON http_audit (category, created_at DESC);
+CREATE INDEX IF NOT EXISTS http_audit_actor_idx ON http_audit (actor);
+CREATE INDEX IF NOT EXISTS http_audit_created_idx ON http_audit (created_at);
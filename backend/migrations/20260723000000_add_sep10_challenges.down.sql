@@ -0,0 +1 @@
+DROP TABLE IF EXISTS sep10_challenges;
@@ -0,0 +1,10 @@
+-- SEP-10 challenges already exchanged for a token, kept until they expire.
+CREATE TABLE IF NOT EXISTS sep10_challenges (
+    transaction_hash VARCHAR(64) PRIMARY KEY,
+    account VARCHAR(56) NOT NULL,
+    client_domain VARCHAR(255),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS sep10_challenges_expires_idx ON sep10_challenges (expires_at);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS plan_owner_approvals;
+DROP TABLE IF EXISTS plan_co_owners;
@@ -0,0 +1,38 @@
+-- Joint plans: owners besides plans.owner_address, invited by the primary owner
+CREATE TABLE plan_co_owners (
+    plan_id UUID NOT NULL REFERENCES plans (id) ON DELETE CASCADE,
+    owner_address TEXT NOT NULL,
+    invited_by TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'invited',
+    -- Unix seconds of the co-owner's last ping; set on acceptance
+    last_ping BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    responded_at TIMESTAMPTZ,
+    PRIMARY KEY (plan_id, owner_address),
+    CONSTRAINT plan_co_owners_status_check
+        CHECK (status IN ('invited', 'accepted', 'declined'))
+);
+
+CREATE INDEX plan_co_owners_owner_idx ON plan_co_owners (owner_address);
+
+-- Amendments and deactivations of joint plans waiting on every owner's approval
+CREATE TABLE plan_owner_approvals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES plans (id) ON DELETE CASCADE,
+    action TEXT NOT NULL,
+    changes JSONB NOT NULL DEFAULT '{}'::jsonb,
+    proposed_by TEXT NOT NULL,
+    approved_by TEXT[] NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    CONSTRAINT plan_owner_approvals_action_check
+        CHECK (action IN ('amend', 'deactivate')),
+    CONSTRAINT plan_owner_approvals_status_check
+        CHECK (status IN ('pending', 'applied', 'rejected'))
+);
+
+-- At most one proposal of each kind may be waiting on a plan at a time
+CREATE UNIQUE INDEX plan_owner_approvals_one_pending_idx
+    ON plan_owner_approvals (plan_id, action)
+    WHERE status = 'pending';
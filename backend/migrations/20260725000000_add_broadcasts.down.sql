@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS notifications_broadcast_id_idx;
+DROP TABLE IF EXISTS broadcasts;
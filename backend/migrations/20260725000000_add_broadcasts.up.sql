@@ -0,0 +1,25 @@
+-- Admin announcements sent as notifications to a segment of users
+CREATE TABLE broadcasts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    segment TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'scheduled',
+    scheduled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ,
+    recipient_count INTEGER,
+    created_by TEXT NOT NULL,
+    cancelled_by TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT broadcasts_segment_check
+        CHECK (segment IN ('all', 'kyc_approved', 'due_plans')),
+    CONSTRAINT broadcasts_status_check
+        CHECK (status IN ('scheduled', 'sent', 'cancelled'))
+);
+
+CREATE INDEX broadcasts_due_idx ON broadcasts (scheduled_at)
+    WHERE status = 'scheduled';
+
+-- Delivery stats are counted from the notifications each broadcast created
+CREATE INDEX notifications_broadcast_id_idx ON notifications ((metadata->>'broadcast_id'))
+    WHERE notification_type = 'broadcast';
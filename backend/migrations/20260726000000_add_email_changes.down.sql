@@ -0,0 +1,6 @@
+DELETE FROM wallet_challenges WHERE action = 'change_email';
+ALTER TABLE wallet_challenges DROP CONSTRAINT wallet_challenges_action_check;
+ALTER TABLE wallet_challenges ADD CONSTRAINT wallet_challenges_action_check
+    CHECK (action IN ('claim', 'deactivate_plan', 'disable_reauth'));
+
+DROP TABLE IF EXISTS email_changes;
@@ -0,0 +1,28 @@
+-- Email changes confirmed from both the current and the new address
+CREATE TABLE email_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_address TEXT NOT NULL,
+    old_email TEXT NOT NULL,
+    -- NULL removes the email once the current address confirms
+    new_email TEXT,
+    old_token_hash TEXT NOT NULL,
+    new_token_hash TEXT,
+    old_confirmed_at TIMESTAMPTZ,
+    new_confirmed_at TIMESTAMPTZ,
+    status TEXT NOT NULL DEFAULT 'pending',
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    CONSTRAINT email_changes_status_check
+        CHECK (status IN ('pending', 'completed', 'cancelled'))
+);
+
+CREATE UNIQUE INDEX email_changes_one_pending_idx ON email_changes (user_address)
+    WHERE status = 'pending';
+CREATE UNIQUE INDEX email_changes_old_token_idx ON email_changes (old_token_hash);
+CREATE UNIQUE INDEX email_changes_new_token_idx ON email_changes (new_token_hash);
+
+-- Email changes are confirmed with a wallet signature
+ALTER TABLE wallet_challenges DROP CONSTRAINT wallet_challenges_action_check;
+ALTER TABLE wallet_challenges ADD CONSTRAINT wallet_challenges_action_check
+    CHECK (action IN ('claim', 'deactivate_plan', 'disable_reauth', 'change_email'));
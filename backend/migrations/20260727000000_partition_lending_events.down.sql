@@ -0,0 +1,32 @@
+-- Rows in archived partitions are not brought back; restore them first
+DROP TABLE IF EXISTS lending_event_archives;
+
+ALTER TABLE lending_events RENAME TO lending_events_partitioned;
+ALTER INDEX lending_events_pkey RENAME TO lending_events_partitioned_pkey;
+ALTER INDEX lending_events_plan_id_idx RENAME TO lending_events_partitioned_plan_id_idx;
+
+CREATE TABLE lending_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_type TEXT NOT NULL CHECK (event_type IN ('deposit')),
+    plan_id UUID REFERENCES plans (id) ON DELETE SET NULL,
+    user_address TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    transaction_hash TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT lending_events_external_unique UNIQUE (event_type, external_id)
+);
+
+CREATE INDEX lending_events_plan_id_idx ON lending_events (plan_id, created_at);
+
+INSERT INTO lending_events
+    (id, event_type, plan_id, user_address, asset, amount, transaction_hash, external_id,
+     metadata, created_at)
+SELECT id, event_type, plan_id, user_address, asset, amount, transaction_hash, external_id,
+       metadata, created_at
+FROM lending_events_partitioned;
+
+DROP TABLE lending_events_partitioned;
+DROP TABLE IF EXISTS lending_event_keys;
@@ -0,0 +1,85 @@
+-- Monthly partitions of lending_events so old months can be archived and dropped
+ALTER TABLE lending_events RENAME TO lending_events_old;
+ALTER INDEX lending_events_pkey RENAME TO lending_events_old_pkey;
+ALTER INDEX lending_events_plan_id_idx RENAME TO lending_events_old_plan_id_idx;
+ALTER TABLE lending_events_old
+    RENAME CONSTRAINT lending_events_external_unique TO lending_events_old_external_unique;
+
+CREATE TABLE lending_events (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    event_type TEXT NOT NULL CHECK (event_type IN ('deposit')),
+    -- NULL when the memo did not name a known plan
+    plan_id UUID REFERENCES plans (id) ON DELETE SET NULL,
+    user_address TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    transaction_hash TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (id, created_at)
+) PARTITION BY RANGE (created_at);
+
+CREATE INDEX lending_events_plan_id_idx ON lending_events (plan_id, created_at);
+
+-- Catches rows outside the monthly partitions the archiver keeps ahead of time
+CREATE TABLE lending_events_default PARTITION OF lending_events DEFAULT;
+
+-- Partitions cannot enforce uniqueness across months, so each Horizon
+-- operation is claimed here first; keys outlive archived partitions
+CREATE TABLE lending_event_keys (
+    event_type TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_type, external_id)
+);
+
+DO $$
+DECLARE
+    month TIMESTAMP;
+BEGIN
+    month := date_trunc('month', COALESCE(
+        (SELECT MIN(created_at) FROM lending_events_old), NOW()) AT TIME ZONE 'UTC');
+    WHILE month <= date_trunc('month', NOW() AT TIME ZONE 'UTC') + INTERVAL '1 month' LOOP
+        EXECUTE format(
+            'CREATE TABLE %I PARTITION OF lending_events FOR VALUES FROM (%L) TO (%L)',
+            'lending_events_p' || to_char(month, 'YYYY_MM'),
+            month AT TIME ZONE 'UTC',
+            (month + INTERVAL '1 month') AT TIME ZONE 'UTC'
+        );
+        month := month + INTERVAL '1 month';
+    END LOOP;
+END $$;
+
+INSERT INTO lending_events
+    (id, event_type, plan_id, user_address, asset, amount, transaction_hash, external_id,
+     metadata, created_at)
+SELECT id, event_type, plan_id, user_address, asset, amount, transaction_hash, external_id,
+       metadata, created_at
+FROM lending_events_old;
+
+INSERT INTO lending_event_keys (event_type, external_id, created_at)
+SELECT event_type, external_id, created_at FROM lending_events_old;
+
+DROP TABLE lending_events_old;
+
+-- Monthly partitions exported to the archive store and dropped
+CREATE TABLE lending_event_archives (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    partition_name TEXT NOT NULL UNIQUE,
+    range_start TIMESTAMPTZ NOT NULL,
+    range_end TIMESTAMPTZ NOT NULL,
+    row_count BIGINT NOT NULL,
+    -- Path of the gzipped CSV relative to LENDING_ARCHIVE_DIR
+    object_key TEXT NOT NULL,
+    byte_size BIGINT NOT NULL,
+    sha256 TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'archived',
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    restored_at TIMESTAMPTZ,
+    restored_by TEXT,
+    CONSTRAINT lending_event_archives_status_check
+        CHECK (status IN ('archived', 'restored'))
+);
+
+CREATE INDEX lending_event_archives_range_idx ON lending_event_archives (range_start, range_end);
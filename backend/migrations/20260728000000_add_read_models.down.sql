@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS read_model_refreshes;
+DROP MATERIALIZED VIEW IF EXISTS fees_collected_daily;
+DROP MATERIALIZED VIEW IF EXISTS plans_by_status_daily;
@@ -0,0 +1,35 @@
+-- Pre-aggregated dashboard read models, refreshed by the read model worker
+
+-- Plans created each day, by their current status
+CREATE MATERIALIZED VIEW plans_by_status_daily AS
+SELECT date_trunc('day', created_at AT TIME ZONE 'UTC')::date AS day,
+       status,
+       COUNT(*) AS plan_count,
+       SUM(amount) AS total_amount
+FROM plans
+GROUP BY 1, 2;
+
+CREATE UNIQUE INDEX plans_by_status_daily_key ON plans_by_status_daily (day, status);
+
+-- Completed payouts each day, by plan token; fees are estimated at read time
+CREATE MATERIALIZED VIEW fees_collected_daily AS
+SELECT date_trunc('day', py.updated_at AT TIME ZONE 'UTC')::date AS day,
+       p.token_address,
+       COUNT(*) AS payout_count,
+       SUM(py.amount) AS gross_amount
+FROM payouts py
+JOIN plans p ON p.id = py.plan_id
+WHERE py.status = 'completed'
+GROUP BY 1, 2;
+
+CREATE UNIQUE INDEX fees_collected_daily_key ON fees_collected_daily (day, token_address);
+
+-- When each view was last refreshed
+CREATE TABLE read_model_refreshes (
+    view_name TEXT PRIMARY KEY,
+    refreshed_at TIMESTAMPTZ NOT NULL,
+    duration_ms BIGINT NOT NULL
+);
+
+INSERT INTO read_model_refreshes (view_name, refreshed_at, duration_ms)
+VALUES ('plans_by_status_daily', NOW(), 0), ('fees_collected_daily', NOW(), 0);
@@ -0,0 +1,22 @@
+-- Emailed links that let a beneficiary without an account start a claim
+CREATE TABLE claim_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES plans (id) ON DELETE CASCADE,
+    beneficiary_id UUID NOT NULL REFERENCES beneficiaries (id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    invited_by TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    -- Set once the beneficiary proves control of the plan's beneficiary wallet
+    wallet_linked_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    CONSTRAINT claim_invitations_status_check
+        CHECK (status IN ('pending', 'completed', 'revoked'))
+);
+
+CREATE UNIQUE INDEX claim_invitations_one_pending_idx ON claim_invitations (beneficiary_id)
+    WHERE status = 'pending';
+CREATE UNIQUE INDEX claim_invitations_token_idx ON claim_invitations (token_hash);
+CREATE INDEX claim_invitations_plan_id_idx ON claim_invitations (plan_id);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS plan_co_owners_accepted_idx;
+DROP INDEX IF EXISTS beneficiaries_wallet_plan_idx;
+CREATE INDEX plans_owner_address_idx ON plans (owner_address);
+DROP INDEX IF EXISTS plans_owner_created_idx;
@@ -0,0 +1,12 @@
+-- Indexes for the plan listing, claim and re-auth paths exercised by the load test
+
+-- Owner listings filter by owner and sort newest first
+CREATE INDEX plans_owner_created_idx ON plans (owner_address, created_at DESC);
+DROP INDEX plans_owner_address_idx;
+
+-- Beneficiary listings, claim and challenge checks look beneficiaries up by wallet
+CREATE INDEX beneficiaries_wallet_plan_idx ON beneficiaries (wallet_address, plan_id);
+
+-- Owner listings include plans co-owned by the caller
+CREATE INDEX plan_co_owners_accepted_idx ON plan_co_owners (owner_address, plan_id)
+    WHERE status = 'accepted';
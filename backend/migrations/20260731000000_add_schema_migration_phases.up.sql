@@ -0,0 +1,9 @@
+-- Which phase applied each migration. A release that finds a post-deploy
+-- migration it was not built with refuses to boot.
+CREATE TABLE schema_migration_phases (
+    version BIGINT PRIMARY KEY,
+    phase TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT schema_migration_phases_phase_check
+        CHECK (phase IN ('pre_deploy', 'post_deploy'))
+);
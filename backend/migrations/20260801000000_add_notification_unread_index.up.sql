@@ -0,0 +1,3 @@
+-- Unread counters and mark-all-read only touch unread rows; keep them off the full history
+CREATE INDEX notifications_unread_idx ON notifications (user_address, notification_type)
+    WHERE is_read = false;
@@ -0,0 +1,17 @@
+DROP TABLE IF EXISTS claim_fraud_assessments;
+DROP INDEX IF EXISTS claim_requests_in_review_idx;
+
+-- Flagged claims must not pay out unreviewed once the queue is gone
+UPDATE claim_requests
+SET status = 'cancelled', cancelled_by = 'system', cancel_reason = 'Fraud review removed', resolved_at = NOW()
+WHERE status = 'in_review';
+
+DROP INDEX IF EXISTS claim_requests_one_pending_idx;
+CREATE UNIQUE INDEX claim_requests_one_pending_idx ON claim_requests (plan_id)
+    WHERE status = 'pending';
+
+ALTER TABLE claim_requests DROP CONSTRAINT claim_requests_status_check;
+ALTER TABLE claim_requests ADD CONSTRAINT claim_requests_status_check
+    CHECK (status IN ('pending', 'executed', 'cancelled', 'failed'));
+
+DROP TABLE IF EXISTS wallet_devices;
@@ -0,0 +1,32 @@
+-- Client addresses and user agents each wallet has signed requests from
+CREATE TABLE wallet_devices (
+    wallet_address TEXT NOT NULL,
+    ip_address TEXT NOT NULL,
+    user_agent_hash TEXT NOT NULL,
+    first_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, ip_address, user_agent_hash)
+);
+
+-- Claims scoring at or above the review threshold wait for an admin
+ALTER TABLE claim_requests DROP CONSTRAINT claim_requests_status_check;
+ALTER TABLE claim_requests ADD CONSTRAINT claim_requests_status_check
+    CHECK (status IN ('pending', 'in_review', 'executed', 'cancelled', 'failed'));
+
+DROP INDEX claim_requests_one_pending_idx;
+CREATE UNIQUE INDEX claim_requests_one_pending_idx ON claim_requests (plan_id)
+    WHERE status IN ('pending', 'in_review');
+
+CREATE INDEX claim_requests_in_review_idx ON claim_requests (created_at)
+    WHERE status = 'in_review';
+
+-- Fraud signals evaluated when each claim was requested
+CREATE TABLE claim_fraud_assessments (
+    claim_id UUID PRIMARY KEY REFERENCES claim_requests (id) ON DELETE CASCADE,
+    score INTEGER NOT NULL,
+    signals JSONB NOT NULL DEFAULT '[]'::jsonb,
+    ip_address TEXT,
+    reviewed_by TEXT,
+    reviewed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
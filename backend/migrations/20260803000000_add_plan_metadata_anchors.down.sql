@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS plan_metadata_anchors;
+DROP TABLE IF EXISTS plan_metadata;
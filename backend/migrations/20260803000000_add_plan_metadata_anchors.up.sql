@@ -0,0 +1,21 @@
+-- Latest canonical document per plan and the hash last anchored on-chain
+CREATE TABLE plan_metadata (
+    plan_id UUID PRIMARY KEY REFERENCES plans(id) ON DELETE CASCADE,
+    metadata_hash VARCHAR(64) NOT NULL,
+    document JSONB NOT NULL,
+    checked_at TIMESTAMPTZ NOT NULL,
+    anchored_hash VARCHAR(64),
+    last_error TEXT
+);
+
+-- Every anchored document, kept after the plan is gone for dispute resolution
+CREATE TABLE plan_metadata_anchors (
+    id BIGSERIAL PRIMARY KEY,
+    plan_id UUID NOT NULL,
+    metadata_hash VARCHAR(64) NOT NULL,
+    document JSONB NOT NULL,
+    tx_hash VARCHAR(128) NOT NULL,
+    anchored_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX plan_metadata_anchors_plan_idx ON plan_metadata_anchors (plan_id, anchored_at DESC);
@@ -0,0 +1,19 @@
+-- Runtime switches for soft-launched features. Flags without a row are off.
+CREATE TABLE feature_flags (
+    key TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    enabled BOOLEAN NOT NULL DEFAULT false,
+    -- Share of users, by a stable hash of flag and user, who get the feature
+    rollout_percent SMALLINT NOT NULL DEFAULT 0,
+    -- Users who get the feature regardless of the rollout
+    allowlist TEXT[] NOT NULL DEFAULT '{}',
+    -- APP_ENV values the flag applies in; empty means every environment
+    environments TEXT[] NOT NULL DEFAULT '{}',
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT feature_flags_rollout_percent_check CHECK (rollout_percent BETWEEN 0 AND 100)
+);
+
+-- Installment plans were already live before flags existed
+INSERT INTO feature_flags (key, description, enabled, rollout_percent, updated_by)
+VALUES ('installment_plans', 'Plans paid out in more than one installment', true, 100, 'system');
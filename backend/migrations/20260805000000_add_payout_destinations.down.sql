@@ -0,0 +1,8 @@
+-- Unpaid parts of a split fall back to the beneficiary's own wallet
+DROP INDEX IF EXISTS payouts_split_group_idx;
+
+ALTER TABLE payouts
+    DROP COLUMN IF EXISTS split_group,
+    DROP COLUMN IF EXISTS destination_address;
+
+DROP TABLE IF EXISTS payout_destinations;
@@ -0,0 +1,17 @@
+-- Wallets a beneficiary's crypto payouts are split across, in basis points
+CREATE TABLE payout_destinations (
+    beneficiary_address TEXT NOT NULL,
+    destination_address TEXT NOT NULL,
+    share_bps INTEGER NOT NULL CHECK (share_bps > 0 AND share_bps <= 10000),
+    label TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (beneficiary_address, destination_address)
+);
+
+-- NULL destination pays the beneficiary's own wallet. Parts of one split
+-- share a split_group so the batcher submits them in one transaction.
+ALTER TABLE payouts
+    ADD COLUMN destination_address TEXT,
+    ADD COLUMN split_group UUID;
+
+CREATE INDEX payouts_split_group_idx ON payouts (split_group) WHERE split_group IS NOT NULL;
@@ -0,0 +1,24 @@
+-- Corrections of whitelisted user and plan fields, approved by a second admin
+CREATE TABLE data_corrections (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    field TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    before_value JSONB NOT NULL,
+    after_value JSONB NOT NULL,
+    reason_code TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    proposed_by TEXT NOT NULL,
+    proposed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    reviewed_by TEXT,
+    reviewed_at TIMESTAMPTZ,
+    review_note TEXT,
+    CONSTRAINT data_corrections_reason_code_check
+        CHECK (reason_code IN ('data_entry_error', 'user_request', 'system_defect', 'compliance')),
+    CONSTRAINT data_corrections_status_check
+        CHECK (status IN ('pending', 'applied', 'rejected', 'expired', 'superseded'))
+);
+
+CREATE INDEX data_corrections_status_idx ON data_corrections (status, expires_at);
+CREATE INDEX data_corrections_record_idx ON data_corrections (field, record_id);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS claim_expiry_reminders;
+ALTER TABLE plans DROP COLUMN IF EXISTS escheated_at;
+ALTER TABLE plans DROP COLUMN IF EXISTS fallback_address;
@@ -0,0 +1,11 @@
+-- Where a plan goes if no beneficiary claims it within the claim window
+ALTER TABLE plans ADD COLUMN fallback_address TEXT;
+ALTER TABLE plans ADD COLUMN escheated_at TIMESTAMPTZ;
+
+-- Claim window reminders already sent, so each goes out once per plan
+CREATE TABLE claim_expiry_reminders (
+    plan_id UUID NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    days_before INTEGER NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (plan_id, days_before)
+);
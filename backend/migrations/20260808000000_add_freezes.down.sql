@@ -0,0 +1,9 @@
+ALTER TABLE plans DROP COLUMN IF EXISTS freeze_note;
+ALTER TABLE plans DROP COLUMN IF EXISTS freeze_reason_code;
+ALTER TABLE plans DROP COLUMN IF EXISTS frozen_by;
+ALTER TABLE plans DROP COLUMN IF EXISTS frozen_at;
+
+ALTER TABLE users DROP COLUMN IF EXISTS freeze_note;
+ALTER TABLE users DROP COLUMN IF EXISTS freeze_reason_code;
+ALTER TABLE users DROP COLUMN IF EXISTS frozen_by;
+ALTER TABLE users DROP COLUMN IF EXISTS frozen_at;
@@ -0,0 +1,11 @@
+-- Admin freezes: a frozen user cannot sign in, and a frozen plan cannot be
+-- claimed, amended or paid out until an admin unfreezes it
+ALTER TABLE users ADD COLUMN frozen_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN frozen_by TEXT;
+ALTER TABLE users ADD COLUMN freeze_reason_code TEXT;
+ALTER TABLE users ADD COLUMN freeze_note TEXT;
+
+ALTER TABLE plans ADD COLUMN frozen_at TIMESTAMPTZ;
+ALTER TABLE plans ADD COLUMN frozen_by TEXT;
+ALTER TABLE plans ADD COLUMN freeze_reason_code TEXT;
+ALTER TABLE plans ADD COLUMN freeze_note TEXT;
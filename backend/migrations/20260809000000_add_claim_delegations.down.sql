@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS claim_documents;
+ALTER TABLE claim_requests DROP COLUMN IF EXISTS filed_by;
+DROP TABLE IF EXISTS claim_delegations;
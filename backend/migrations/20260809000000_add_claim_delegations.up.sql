@@ -0,0 +1,43 @@
+-- Access a beneficiary grants an executor to act on their claim
+CREATE TABLE claim_delegations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    beneficiary_address TEXT NOT NULL,
+    executor_address TEXT NOT NULL,
+    scopes TEXT[] NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    revoked_by TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT claim_delegations_scopes_check
+        CHECK (cardinality(scopes) > 0
+               AND scopes <@ ARRAY['file_claim', 'upload_documents']::TEXT[]),
+    CONSTRAINT claim_delegations_distinct_check
+        CHECK (beneficiary_address <> executor_address)
+);
+
+CREATE UNIQUE INDEX claim_delegations_one_active_idx
+    ON claim_delegations (plan_id, beneficiary_address, executor_address)
+    WHERE revoked_at IS NULL;
+CREATE INDEX claim_delegations_executor_idx ON claim_delegations (executor_address);
+
+-- Set when a claim was filed by an executor for the beneficiary in requested_by
+ALTER TABLE claim_requests ADD COLUMN filed_by TEXT;
+
+-- Supporting documents for a claim, such as a death certificate
+CREATE TABLE claim_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    beneficiary_address TEXT NOT NULL,
+    uploaded_by TEXT NOT NULL,
+    document_type TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    sha256 TEXT NOT NULL,
+    -- Base64 content, sealed with the field cipher
+    content TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX claim_documents_plan_idx ON claim_documents (plan_id, created_at);
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS http_audit_correlation_id_idx;
+DROP INDEX IF EXISTS audit_logs_correlation_id_idx;
+
+ALTER TABLE payouts DROP COLUMN IF EXISTS correlation_id;
+ALTER TABLE claim_requests DROP COLUMN IF EXISTS correlation_id;
+ALTER TABLE http_audit DROP COLUMN IF EXISTS correlation_id;
+ALTER TABLE audit_logs DROP COLUMN IF EXISTS correlation_id;
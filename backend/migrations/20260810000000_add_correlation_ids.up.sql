@@ -0,0 +1,11 @@
+-- Correlation id of the request (or worker run) that wrote the row, so an
+-- operation can be traced across the audit trail, claims and payouts.
+ALTER TABLE audit_logs ADD COLUMN correlation_id TEXT;
+ALTER TABLE http_audit ADD COLUMN correlation_id TEXT;
+ALTER TABLE claim_requests ADD COLUMN correlation_id TEXT;
+ALTER TABLE payouts ADD COLUMN correlation_id TEXT;
+
+CREATE INDEX audit_logs_correlation_id_idx ON audit_logs (correlation_id)
+    WHERE correlation_id IS NOT NULL;
+CREATE INDEX http_audit_correlation_id_idx ON http_audit (correlation_id)
+    WHERE correlation_id IS NOT NULL;
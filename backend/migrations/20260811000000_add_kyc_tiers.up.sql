@@ -0,0 +1,4 @@
+-- KYC tier a user has been verified to; each tier's plan, loan and claim
+-- limits are system settings.
+ALTER TABLE users ADD COLUMN kyc_tier TEXT NOT NULL DEFAULT 'basic'
+    CHECK (kyc_tier IN ('basic', 'verified', 'enhanced'));
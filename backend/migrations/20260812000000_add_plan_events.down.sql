@@ -0,0 +1,13 @@
+DROP TRIGGER IF EXISTS plan_events_append_only ON plan_events;
+DROP TRIGGER IF EXISTS beneficiaries_event ON beneficiaries;
+DROP TRIGGER IF EXISTS plans_event ON plans;
+
+DROP FUNCTION IF EXISTS reject_plan_event_rewrite();
+DROP FUNCTION IF EXISTS record_beneficiary_event();
+DROP FUNCTION IF EXISTS record_plan_event();
+DROP FUNCTION IF EXISTS append_plan_event(UUID, TEXT, JSONB);
+DROP FUNCTION IF EXISTS jsonb_changes(JSONB, JSONB);
+DROP FUNCTION IF EXISTS beneficiary_event_row(beneficiaries);
+DROP FUNCTION IF EXISTS plan_event_row(plans);
+
+DROP TABLE IF EXISTS plan_events;
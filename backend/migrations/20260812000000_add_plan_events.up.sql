@@ -0,0 +1,148 @@
+-- Domain events of the plan aggregate, appended by triggers on `plans` and
+-- `beneficiaries` so every write path records them. The two tables are
+-- the projection of this stream and can be rebuilt from it with
+-- `inheritx-cli rebuild-plans`.
+CREATE TABLE plan_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- No foreign key: events outlive the plan row
+    plan_id UUID NOT NULL,
+    -- Position in the plan's stream, from 1
+    sequence BIGINT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    txid BIGINT NOT NULL DEFAULT txid_current(),
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp(),
+    CONSTRAINT plan_events_plan_sequence_unique UNIQUE (plan_id, sequence),
+    CONSTRAINT plan_events_event_type_check CHECK (event_type IN (
+        'PlanCreated', 'PlanUpdated', 'Claimed', 'PlanDeleted',
+        'BeneficiaryAdded', 'AllocationChanged', 'BeneficiaryUpdated', 'BeneficiaryRemoved'))
+);
+
+CREATE INDEX plan_events_occurred_idx ON plan_events (occurred_at);
+
+-- Row encodings used in event payloads. NUMERIC columns are strings so
+-- they survive JSON parsing; the generated deadline is left out.
+CREATE OR REPLACE FUNCTION plan_event_row(p plans)
+RETURNS JSONB AS $$
+    SELECT (to_jsonb(p) - 'inactivity_deadline_at')
+        || jsonb_build_object(
+            'amount', p.amount::text,
+            'accrued_yield', p.accrued_yield::text,
+            'funded_amount', p.funded_amount::text
+        );
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION beneficiary_event_row(b beneficiaries)
+RETURNS JSONB AS $$
+    SELECT to_jsonb(b) - 'plan_id';
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Keys of `new_row` whose values differ from `old_row`.
+CREATE OR REPLACE FUNCTION jsonb_changes(old_row JSONB, new_row JSONB)
+RETURNS JSONB AS $$
+    SELECT COALESCE(jsonb_object_agg(n.key, n.value), '{}'::jsonb)
+    FROM jsonb_each(new_row) n
+    WHERE old_row -> n.key IS DISTINCT FROM n.value;
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION append_plan_event(target_plan_id UUID, kind TEXT, body JSONB)
+RETURNS VOID AS $$
+BEGIN
+    -- Serializes appends to one stream so sequences have no gaps
+    PERFORM pg_advisory_xact_lock(837, hashtext(target_plan_id::text));
+    INSERT INTO plan_events (plan_id, sequence, event_type, payload)
+    SELECT target_plan_id, COALESCE(MAX(sequence), 0) + 1, kind, body
+    FROM plan_events
+    WHERE plan_id = target_plan_id;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_plan_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    changes JSONB;
+BEGIN
+    -- A rebuild writes the projection from the events; nothing new happened
+    IF current_setting('inheritx.replaying', true) = 'on' THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        PERFORM append_plan_event(NEW.id, 'PlanCreated', plan_event_row(NEW));
+    ELSIF TG_OP = 'DELETE' THEN
+        PERFORM append_plan_event(OLD.id, 'PlanDeleted', '{}'::jsonb);
+    ELSE
+        changes := jsonb_changes(plan_event_row(OLD), plan_event_row(NEW));
+        IF changes <> '{}'::jsonb THEN
+            PERFORM append_plan_event(
+                NEW.id,
+                CASE WHEN NEW.status = 'PAID_OUT' AND OLD.status <> 'PAID_OUT'
+                     THEN 'Claimed' ELSE 'PlanUpdated' END,
+                jsonb_build_object('changes', changes)
+            );
+        END IF;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_beneficiary_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    changes JSONB;
+BEGIN
+    IF current_setting('inheritx.replaying', true) = 'on' THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        PERFORM append_plan_event(NEW.plan_id, 'BeneficiaryAdded', beneficiary_event_row(NEW));
+    ELSIF TG_OP = 'DELETE' THEN
+        PERFORM append_plan_event(OLD.plan_id, 'BeneficiaryRemoved', jsonb_build_object('id', OLD.id));
+    ELSE
+        changes := jsonb_changes(beneficiary_event_row(OLD), beneficiary_event_row(NEW));
+        IF changes <> '{}'::jsonb THEN
+            PERFORM append_plan_event(
+                NEW.plan_id,
+                CASE WHEN changes ? 'allocation_bps'
+                     THEN 'AllocationChanged' ELSE 'BeneficiaryUpdated' END,
+                jsonb_build_object('id', NEW.id, 'changes', changes)
+            );
+        END IF;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER plans_event
+    AFTER INSERT OR UPDATE OR DELETE ON plans
+    FOR EACH ROW
+    EXECUTE FUNCTION record_plan_event();
+
+CREATE TRIGGER beneficiaries_event
+    AFTER INSERT OR UPDATE OR DELETE ON beneficiaries
+    FOR EACH ROW
+    EXECUTE FUNCTION record_beneficiary_event();
+
+CREATE OR REPLACE FUNCTION reject_plan_event_rewrite()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'plan_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER plan_events_append_only
+    BEFORE UPDATE OR DELETE ON plan_events
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_plan_event_rewrite();
+
+-- Existing plans start their streams with their current state
+INSERT INTO plan_events (plan_id, sequence, event_type, payload)
+SELECT p.id, 1, 'PlanCreated', plan_event_row(p) FROM plans p;
+
+INSERT INTO plan_events (plan_id, sequence, event_type, payload)
+SELECT b.plan_id,
+       1 + ROW_NUMBER() OVER (PARTITION BY b.plan_id ORDER BY b.wallet_address),
+       'BeneficiaryAdded',
+       beneficiary_event_row(b)
+FROM beneficiaries b;
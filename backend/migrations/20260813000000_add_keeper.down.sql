@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS keeper_runs;
+DROP TABLE IF EXISTS keeper_jobs;
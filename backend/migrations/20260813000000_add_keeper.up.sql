@@ -0,0 +1,46 @@
+-- Permissionless contract calls the keeper owes, one per plan and due time.
+-- A plan whose inactivity deadline moves (the owner pinged) gets new jobs.
+CREATE TABLE keeper_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    task TEXT NOT NULL,
+    plan_id UUID NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    owner_address TEXT NOT NULL,
+    due_at TIMESTAMPTZ NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    fee_stroops BIGINT,
+    tx_hash TEXT,
+    submitted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT keeper_jobs_task_check
+        CHECK (task IN ('claim', 'trigger_payout', 'escheat')),
+    CONSTRAINT keeper_jobs_status_check
+        CHECK (status IN ('pending', 'submitted', 'failed', 'cancelled')),
+    CONSTRAINT keeper_jobs_plan_due_key UNIQUE (task, plan_id, due_at)
+);
+
+CREATE INDEX keeper_jobs_pending_idx ON keeper_jobs (next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX keeper_jobs_submitted_at_idx ON keeper_jobs (submitted_at)
+    WHERE submitted_at IS NOT NULL;
+
+-- One row per keeper sweep; a row without finished_at is a sweep that died.
+CREATE TABLE keeper_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+    missed_runs INTEGER NOT NULL DEFAULT 0,
+    discovered INTEGER NOT NULL DEFAULT 0,
+    submitted INTEGER NOT NULL DEFAULT 0,
+    not_due INTEGER NOT NULL DEFAULT 0,
+    cancelled INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0,
+    deferred INTEGER NOT NULL DEFAULT 0,
+    fees_stroops BIGINT NOT NULL DEFAULT 0,
+    error TEXT
+);
+
+CREATE INDEX keeper_runs_started_at_idx ON keeper_runs (started_at DESC);
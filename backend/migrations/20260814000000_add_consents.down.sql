@@ -0,0 +1,13 @@
+DELETE FROM http_audit WHERE category = 'consent';
+ALTER TABLE http_audit DROP CONSTRAINT http_audit_category_check;
+ALTER TABLE http_audit ADD CONSTRAINT http_audit_category_check
+    CHECK (category IN ('claim', 'kyc', 'admin_config'));
+
+ALTER TABLE broadcasts DROP COLUMN IF EXISTS marketing;
+
+DROP VIEW IF EXISTS active_consents;
+DROP VIEW IF EXISTS current_consent_documents;
+DROP TRIGGER IF EXISTS user_consents_append_only ON user_consents;
+DROP FUNCTION IF EXISTS reject_user_consent_rewrite();
+DROP TABLE IF EXISTS user_consents;
+DROP TABLE IF EXISTS consent_documents;
@@ -0,0 +1,71 @@
+-- Versioned documents users consent to. The latest published version of a
+-- kind is the current one; consent to an older version no longer counts.
+CREATE TABLE consent_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL,
+    version TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT,
+    published_by TEXT NOT NULL,
+    published_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT consent_documents_kind_check
+        CHECK (kind IN ('terms_of_service', 'privacy_policy', 'marketing')),
+    CONSTRAINT consent_documents_kind_version_key UNIQUE (kind, version)
+);
+
+CREATE INDEX consent_documents_current_idx ON consent_documents (kind, published_at DESC);
+
+-- Every grant and withdrawal, in order. A user's latest entry per kind is
+-- their decision.
+CREATE TABLE user_consents (
+    id BIGSERIAL PRIMARY KEY,
+    user_address TEXT NOT NULL,
+    document_id UUID NOT NULL REFERENCES consent_documents(id),
+    kind TEXT NOT NULL,
+    granted BOOLEAN NOT NULL,
+    correlation_id TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX user_consents_user_kind_idx ON user_consents (user_address, kind, id DESC);
+
+CREATE OR REPLACE FUNCTION reject_user_consent_rewrite()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'user_consents is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER user_consents_append_only
+    BEFORE UPDATE OR DELETE ON user_consents
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_user_consent_rewrite();
+
+CREATE VIEW current_consent_documents AS
+SELECT DISTINCT ON (kind) id, kind, version, title, url, published_by, published_at
+FROM consent_documents
+ORDER BY kind, published_at DESC, id;
+
+-- Users whose latest decision grants the current document of a kind
+CREATE VIEW active_consents AS
+SELECT latest.user_address, latest.kind, latest.document_id, d.version,
+       latest.recorded_at AS granted_at
+FROM (
+    SELECT DISTINCT ON (user_address, kind) user_address, kind, document_id, granted, recorded_at
+    FROM user_consents
+    ORDER BY user_address, kind, id DESC
+) latest
+JOIN current_consent_documents d ON d.id = latest.document_id
+WHERE latest.granted;
+
+INSERT INTO consent_documents (kind, version, title, published_by) VALUES
+    ('terms_of_service', '1', 'Terms of Service', 'system'),
+    ('privacy_policy', '1', 'Privacy Policy', 'system'),
+    ('marketing', '1', 'Marketing communications', 'system');
+
+-- Marketing broadcasts are only emailed to users with marketing consent
+ALTER TABLE broadcasts ADD COLUMN marketing BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE http_audit DROP CONSTRAINT http_audit_category_check;
+ALTER TABLE http_audit ADD CONSTRAINT http_audit_category_check
+    CHECK (category IN ('claim', 'kyc', 'admin_config', 'consent'));
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS audit_logs_actor_created_at_idx;
@@ -0,0 +1,2 @@
+-- Security events are read back per user from the audit trail
+CREATE INDEX audit_logs_actor_created_at_idx ON audit_logs (actor, created_at DESC);
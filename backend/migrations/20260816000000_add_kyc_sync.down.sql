@@ -0,0 +1,14 @@
+DROP TRIGGER IF EXISTS users_queue_kyc_sync ON users;
+DROP FUNCTION IF EXISTS queue_kyc_sync();
+DROP FUNCTION IF EXISTS kyc_onchain_tier(kyc_status, TEXT);
+DROP INDEX IF EXISTS users_kyc_sync_due_idx;
+
+ALTER TABLE users
+    DROP CONSTRAINT IF EXISTS users_kyc_sync_status_check,
+    DROP COLUMN IF EXISTS kyc_synced_at,
+    DROP COLUMN IF EXISTS kyc_sync_tx_hash,
+    DROP COLUMN IF EXISTS kyc_sync_error,
+    DROP COLUMN IF EXISTS kyc_sync_next_attempt_at,
+    DROP COLUMN IF EXISTS kyc_sync_attempts,
+    DROP COLUMN IF EXISTS kyc_synced_tier,
+    DROP COLUMN IF EXISTS kyc_sync_status;
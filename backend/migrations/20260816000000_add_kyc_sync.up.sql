@@ -0,0 +1,54 @@
+-- Tracks whether each user's KYC standing has been pushed to the
+-- inheritance contract. The contract holds a tier per address: the user's
+-- tier once approved, `basic` otherwise.
+ALTER TABLE users
+    ADD COLUMN kyc_sync_status TEXT NOT NULL DEFAULT 'synced',
+    ADD COLUMN kyc_synced_tier TEXT NOT NULL DEFAULT 'basic',
+    ADD COLUMN kyc_sync_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN kyc_sync_next_attempt_at TIMESTAMPTZ,
+    ADD COLUMN kyc_sync_error TEXT,
+    ADD COLUMN kyc_sync_tx_hash TEXT,
+    ADD COLUMN kyc_synced_at TIMESTAMPTZ,
+    ADD CONSTRAINT users_kyc_sync_status_check
+        CHECK (kyc_sync_status IN ('synced', 'pending_push', 'failed'));
+
+CREATE INDEX users_kyc_sync_due_idx ON users (kyc_sync_next_attempt_at)
+    WHERE kyc_sync_status = 'pending_push';
+
+CREATE OR REPLACE FUNCTION kyc_onchain_tier(status kyc_status, tier TEXT)
+RETURNS TEXT AS $$
+    SELECT CASE WHEN status = 'approved' THEN tier ELSE 'basic' END;
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Queues a push whenever the tier the contract should hold changes, and
+-- drops a queued one when it changes back to what was last pushed.
+CREATE OR REPLACE FUNCTION queue_kyc_sync()
+RETURNS TRIGGER AS $$
+DECLARE
+    target TEXT := kyc_onchain_tier(NEW.kyc_status, NEW.kyc_tier);
+BEGIN
+    IF TG_OP = 'UPDATE' AND target = kyc_onchain_tier(OLD.kyc_status, OLD.kyc_tier) THEN
+        RETURN NEW;
+    END IF;
+    IF target = NEW.kyc_synced_tier THEN
+        NEW.kyc_sync_status := 'synced';
+        NEW.kyc_sync_next_attempt_at := NULL;
+    ELSE
+        NEW.kyc_sync_status := 'pending_push';
+        NEW.kyc_sync_next_attempt_at := NOW();
+    END IF;
+    NEW.kyc_sync_attempts := 0;
+    NEW.kyc_sync_error := NULL;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_queue_kyc_sync
+    BEFORE INSERT OR UPDATE OF kyc_status, kyc_tier ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION queue_kyc_sync();
+
+UPDATE users
+SET kyc_sync_status = 'pending_push', kyc_sync_next_attempt_at = NOW()
+WHERE kyc_onchain_tier(kyc_status, kyc_tier) <> kyc_synced_tier;
+
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS notification_template_versions;
+ALTER TABLE notification_templates DROP COLUMN IF EXISTS version;
@@ -0,0 +1,19 @@
+-- Every uploaded copy of a notification template override, so earlier
+-- wording can be reviewed and restored
+ALTER TABLE notification_templates ADD COLUMN version INT NOT NULL DEFAULT 1;
+
+CREATE TABLE notification_template_versions (
+    template_key TEXT NOT NULL,
+    language TEXT NOT NULL,
+    version INT NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (template_key, language, version)
+);
+
+INSERT INTO notification_template_versions
+    (template_key, language, version, subject, body, created_by, created_at)
+SELECT template_key, language, version, subject, body, updated_by, updated_at
+FROM notification_templates;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS saved_plan_filters;
+DROP TABLE IF EXISTS plan_tags;
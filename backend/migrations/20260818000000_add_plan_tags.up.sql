@@ -0,0 +1,21 @@
+-- Labels plan owners put on their plans, and each user's saved plan filters
+CREATE TABLE plan_tags (
+    plan_id UUID NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    added_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (plan_id, tag)
+);
+
+CREATE INDEX plan_tags_tag_idx ON plan_tags (tag, plan_id);
+
+CREATE TABLE saved_plan_filters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_address TEXT NOT NULL,
+    name TEXT NOT NULL,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    beneficiary TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_address, name)
+);
@@ -0,0 +1,13 @@
+DROP TRIGGER IF EXISTS payouts_ledger ON payouts;
+DROP TRIGGER IF EXISTS plans_yield_ledger ON plans;
+DROP TRIGGER IF EXISTS lending_events_ledger ON lending_events;
+DROP FUNCTION IF EXISTS ledger_record_payout();
+DROP FUNCTION IF EXISTS ledger_record_yield();
+DROP FUNCTION IF EXISTS ledger_record_deposit();
+DROP TABLE IF EXISTS ledger_entries;
+DROP TABLE IF EXISTS ledger_journals;
+DROP TABLE IF EXISTS ledger_accounts;
+DROP FUNCTION IF EXISTS reject_ledger_rewrite();
+DROP FUNCTION IF EXISTS ledger_check_balanced();
+DROP FUNCTION IF EXISTS ledger_post(TEXT, TEXT, UUID, UUID, TEXT, TEXT, TEXT, NUMERIC, TIMESTAMPTZ);
+DROP FUNCTION IF EXISTS ledger_account_id(TEXT, TEXT);
@@ -0,0 +1,228 @@
+-- Double-entry ledger of the platform's funds, kept on an accrual and a
+-- cash basis. Triggers on `lending_events`, `plans` and `payouts` post a
+-- balanced journal for every financial event, so every write path is
+-- covered. Amounts are in the asset's base units.
+CREATE TABLE ledger_accounts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('asset', 'liability', 'expense')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT ledger_accounts_code_asset_unique UNIQUE (code, asset)
+);
+
+CREATE TABLE ledger_journals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_type TEXT NOT NULL CHECK (event_type IN (
+        'deposit', 'yield_accrued', 'payout_created', 'payout_completed',
+        'payout_failed', 'payout_requeued')),
+    basis TEXT NOT NULL CHECK (basis IN ('accrual', 'cash')),
+    -- The lending event, plan or payout the journal was posted for
+    source_id UUID NOT NULL,
+    plan_id UUID,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 4) NOT NULL CHECK (amount > 0),
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp()
+);
+
+-- Restoring an archived month of lending events must not post it again
+CREATE UNIQUE INDEX ledger_journals_deposit_unique
+    ON ledger_journals (basis, source_id) WHERE event_type = 'deposit';
+CREATE INDEX ledger_journals_source_idx ON ledger_journals (source_id);
+
+CREATE TABLE ledger_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    journal_id UUID NOT NULL REFERENCES ledger_journals (id),
+    account_id UUID NOT NULL REFERENCES ledger_accounts (id),
+    basis TEXT NOT NULL,
+    debit NUMERIC(78, 4) NOT NULL DEFAULT 0,
+    credit NUMERIC(78, 4) NOT NULL DEFAULT 0,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    CONSTRAINT ledger_entries_one_side CHECK (
+        debit >= 0 AND credit >= 0 AND (debit = 0) <> (credit = 0))
+);
+
+CREATE INDEX ledger_entries_account_idx ON ledger_entries (account_id, basis, occurred_at);
+CREATE INDEX ledger_entries_journal_idx ON ledger_entries (journal_id);
+
+CREATE OR REPLACE FUNCTION ledger_account_id(account_code TEXT, account_asset TEXT)
+RETURNS UUID AS $$
+DECLARE
+    account UUID;
+BEGIN
+    INSERT INTO ledger_accounts (code, asset, kind)
+    VALUES (account_code, account_asset, CASE account_code
+        WHEN 'custody' THEN 'asset'
+        WHEN 'yield_expense' THEN 'expense'
+        ELSE 'liability' END)
+    ON CONFLICT (code, asset) DO NOTHING;
+    SELECT id INTO account FROM ledger_accounts WHERE code = account_code AND asset = account_asset;
+    RETURN account;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Posts `amount` from `credit_code` to `debit_code` as one journal.
+CREATE OR REPLACE FUNCTION ledger_post(
+    kind TEXT, post_basis TEXT, source UUID, target_plan_id UUID, post_asset TEXT,
+    debit_code TEXT, credit_code TEXT, post_amount NUMERIC,
+    at TIMESTAMPTZ DEFAULT clock_timestamp())
+RETURNS VOID AS $$
+DECLARE
+    journal UUID;
+BEGIN
+    IF post_amount IS NULL OR post_amount <= 0 THEN
+        RETURN;
+    END IF;
+    INSERT INTO ledger_journals (event_type, basis, source_id, plan_id, asset, amount, occurred_at)
+    VALUES (kind, post_basis, source, target_plan_id, post_asset, post_amount, at)
+    ON CONFLICT DO NOTHING
+    RETURNING id INTO journal;
+    IF journal IS NULL THEN
+        RETURN;
+    END IF;
+    INSERT INTO ledger_entries (journal_id, account_id, basis, debit, credit, occurred_at)
+    VALUES (journal, ledger_account_id(debit_code, post_asset), post_basis, post_amount, 0, at),
+           (journal, ledger_account_id(credit_code, post_asset), post_basis, 0, post_amount, at);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION ledger_check_balanced()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF (SELECT SUM(debit) <> SUM(credit) FROM ledger_entries WHERE journal_id = NEW.journal_id) THEN
+        RAISE EXCEPTION 'ledger journal % does not balance', NEW.journal_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER ledger_entries_balanced
+    AFTER INSERT ON ledger_entries
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW
+    EXECUTE FUNCTION ledger_check_balanced();
+
+CREATE OR REPLACE FUNCTION reject_ledger_rewrite()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'the ledger is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER ledger_journals_append_only
+    BEFORE UPDATE OR DELETE ON ledger_journals
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_ledger_rewrite();
+
+CREATE TRIGGER ledger_entries_append_only
+    BEFORE UPDATE OR DELETE ON ledger_entries
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_ledger_rewrite();
+
+-- Deposits are cash in on both bases, owed to the plan or, when the memo
+-- named no plan, held as unallocated.
+CREATE OR REPLACE FUNCTION ledger_record_deposit()
+RETURNS TRIGGER AS $$
+DECLARE
+    owed TEXT := CASE WHEN NEW.plan_id IS NULL THEN 'unallocated_deposits' ELSE 'plan_liability' END;
+BEGIN
+    PERFORM ledger_post('deposit', 'accrual', NEW.id, NEW.plan_id, NEW.asset, 'custody', owed, NEW.amount, NEW.created_at);
+    PERFORM ledger_post('deposit', 'cash', NEW.id, NEW.plan_id, NEW.asset, 'custody', owed, NEW.amount, NEW.created_at);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lending_events_ledger
+    AFTER INSERT ON lending_events
+    FOR EACH ROW
+    WHEN (NEW.event_type = 'deposit')
+    EXECUTE FUNCTION ledger_record_deposit();
+
+-- Yield is an expense as it accrues; on a cash basis it is only seen in
+-- the payouts that include it.
+CREATE OR REPLACE FUNCTION ledger_record_yield()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF current_setting('inheritx.replaying', true) = 'on' THEN
+        RETURN NULL;
+    END IF;
+    PERFORM ledger_post('yield_accrued', 'accrual', NEW.id, NEW.id, NEW.token_address,
+        'yield_expense', 'plan_liability', NEW.accrued_yield - OLD.accrued_yield);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER plans_yield_ledger
+    AFTER UPDATE OF accrued_yield ON plans
+    FOR EACH ROW
+    WHEN (NEW.accrued_yield > OLD.accrued_yield)
+    EXECUTE FUNCTION ledger_record_yield();
+
+-- On an accrual basis a payout is owed once it is created and paid from
+-- custody when it completes; a payout that finally fails goes back to
+-- the plan. On a cash basis only the completed transfer is posted.
+CREATE OR REPLACE FUNCTION ledger_record_payout()
+RETURNS TRIGGER AS $$
+DECLARE
+    token TEXT;
+    old_status payout_status := CASE WHEN TG_OP = 'UPDATE' THEN OLD.status END;
+BEGIN
+    IF old_status IS NOT DISTINCT FROM NEW.status AND TG_OP = 'UPDATE' THEN
+        RETURN NULL;
+    END IF;
+    SELECT token_address INTO token FROM plans WHERE id = NEW.plan_id;
+    IF TG_OP = 'INSERT' THEN
+        PERFORM ledger_post('payout_created', 'accrual', NEW.id, NEW.plan_id, token,
+            'plan_liability', 'payouts_payable', NEW.amount);
+    ELSIF old_status = 'failed' THEN
+        PERFORM ledger_post('payout_requeued', 'accrual', NEW.id, NEW.plan_id, token,
+            'plan_liability', 'payouts_payable', NEW.amount);
+    END IF;
+    IF NEW.status = 'completed' THEN
+        PERFORM ledger_post('payout_completed', 'accrual', NEW.id, NEW.plan_id, token,
+            'payouts_payable', 'custody', NEW.amount);
+        PERFORM ledger_post('payout_completed', 'cash', NEW.id, NEW.plan_id, token,
+            'plan_liability', 'custody', NEW.amount);
+    ELSIF NEW.status = 'failed' THEN
+        PERFORM ledger_post('payout_failed', 'accrual', NEW.id, NEW.plan_id, token,
+            'payouts_payable', 'plan_liability', NEW.amount);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER payouts_ledger
+    AFTER INSERT OR UPDATE OF status ON payouts
+    FOR EACH ROW
+    EXECUTE FUNCTION ledger_record_payout();
+
+-- Existing deposits, payouts and accrued yield open the ledger
+SELECT ledger_post('deposit', basis, e.id, e.plan_id, e.asset, 'custody',
+                   CASE WHEN e.plan_id IS NULL THEN 'unallocated_deposits' ELSE 'plan_liability' END,
+                   e.amount, e.created_at)
+FROM lending_events e, (VALUES ('accrual'), ('cash')) AS b (basis)
+WHERE e.event_type = 'deposit'
+ORDER BY e.created_at;
+
+SELECT ledger_post('yield_accrued', 'accrual', p.id, p.id, p.token_address,
+                   'yield_expense', 'plan_liability', p.accrued_yield)
+FROM plans p
+WHERE p.accrued_yield > 0;
+
+SELECT ledger_post('payout_created', 'accrual', pay.id, pay.plan_id, p.token_address,
+                   'plan_liability', 'payouts_payable', pay.amount, pay.created_at)
+FROM payouts pay JOIN plans p ON p.id = pay.plan_id
+ORDER BY pay.created_at;
+
+SELECT ledger_post('payout_completed', basis, pay.id, pay.plan_id, p.token_address,
+                   CASE basis WHEN 'accrual' THEN 'payouts_payable' ELSE 'plan_liability' END,
+                   'custody', pay.amount, pay.updated_at)
+FROM payouts pay JOIN plans p ON p.id = pay.plan_id, (VALUES ('accrual'), ('cash')) AS b (basis)
+WHERE pay.status = 'completed'
+ORDER BY pay.updated_at;
+
+SELECT ledger_post('payout_failed', 'accrual', pay.id, pay.plan_id, p.token_address,
+                   'payouts_payable', 'plan_liability', pay.amount, pay.updated_at)
+FROM payouts pay JOIN plans p ON p.id = pay.plan_id
+WHERE pay.status = 'failed'
+ORDER BY pay.updated_at;
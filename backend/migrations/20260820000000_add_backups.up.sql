@@ -0,0 +1,22 @@
+-- Logical backups of the critical tables, uploaded to object storage and
+-- verified by restoring them into a scratch schema.
+CREATE TABLE backups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    status TEXT NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'uploaded', 'verified', 'failed')),
+    tables TEXT[] NOT NULL,
+    -- Rows per table in the dump, compared again after the test restore
+    row_counts JSONB NOT NULL DEFAULT '{}',
+    object_key TEXT,
+    byte_size BIGINT,
+    -- Of the encrypted object as uploaded
+    sha256 TEXT,
+    key_id TEXT NOT NULL,
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    verified_at TIMESTAMPTZ
+);
+
+CREATE INDEX backups_started_at_idx ON backups (started_at DESC);
+CREATE INDEX backups_verified_idx ON backups (started_at DESC) WHERE status = 'verified';
@@ -0,0 +1,8 @@
+ALTER TABLE payouts
+    DROP CONSTRAINT IF EXISTS payouts_conversion_complete,
+    DROP COLUMN IF EXISTS conversion_rate,
+    DROP COLUMN IF EXISTS min_converted_amount,
+    DROP COLUMN IF EXISTS converted_amount,
+    DROP COLUMN IF EXISTS payout_asset;
+
+DROP TABLE IF EXISTS payout_asset_preferences;
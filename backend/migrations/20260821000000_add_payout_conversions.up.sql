@@ -0,0 +1,23 @@
+-- Token a beneficiary wants their crypto payouts converted to, and how far
+-- below the quote the delivered amount may fall
+CREATE TABLE payout_asset_preferences (
+    beneficiary_address TEXT PRIMARY KEY,
+    asset TEXT NOT NULL,
+    max_slippage_bps INTEGER NOT NULL DEFAULT 100
+        CHECK (max_slippage_bps BETWEEN 0 AND 1000),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- A converted payout keeps `amount` in the plan's token and records the
+-- quoted amount of `payout_asset` the destination is sent. The transfer
+-- fails rather than deliver less than `min_converted_amount`.
+ALTER TABLE payouts
+    ADD COLUMN payout_asset TEXT,
+    ADD COLUMN converted_amount NUMERIC(78, 0),
+    ADD COLUMN min_converted_amount NUMERIC(78, 0),
+    -- Base units of `payout_asset` per base unit of the plan's token
+    ADD COLUMN conversion_rate NUMERIC(38, 18),
+    ADD CONSTRAINT payouts_conversion_complete CHECK (
+        (payout_asset IS NULL) = (converted_amount IS NULL)
+        AND (payout_asset IS NULL) = (min_converted_amount IS NULL)
+        AND (payout_asset IS NULL) = (conversion_rate IS NULL));
@@ -0,0 +1,21 @@
+-- One row per token per balance monitor run: the funded plan balances the
+-- database expects the inheritance contract to hold against what it holds
+-- on-chain.
+CREATE TABLE balance_checks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    run_id UUID NOT NULL,
+    asset TEXT NOT NULL,
+    expected NUMERIC(78, 0) NOT NULL,
+    -- NULL when the on-chain balance could not be read
+    on_chain NUMERIC(78, 0),
+    difference NUMERIC(78, 0),
+    tolerance NUMERIC(78, 0) NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('balanced', 'mismatched', 'unreadable')),
+    error TEXT,
+    -- Plans holding the asset, largest first, recorded for mismatches
+    entities JSONB NOT NULL DEFAULT '[]',
+    alerted BOOLEAN NOT NULL DEFAULT FALSE,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX balance_checks_asset_idx ON balance_checks (asset, checked_at DESC);
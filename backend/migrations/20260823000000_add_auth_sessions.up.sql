@@ -0,0 +1,16 @@
+-- SEP-10 sessions, keyed by the token's jti, so tokens stop working after
+-- a period of inactivity and not only when they expire.
+CREATE TABLE auth_sessions (
+    jti TEXT PRIMARY KEY,
+    account TEXT NOT NULL,
+    last_active_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX auth_sessions_expires_at_idx ON auth_sessions (expires_at);
+
+-- Stale sessions step up by signing a wallet challenge
+ALTER TABLE wallet_challenges DROP CONSTRAINT wallet_challenges_action_check;
+ALTER TABLE wallet_challenges ADD CONSTRAINT wallet_challenges_action_check
+    CHECK (action IN ('claim', 'deactivate_plan', 'disable_reauth', 'change_email', 'step_up'));
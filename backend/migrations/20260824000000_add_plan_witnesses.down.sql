@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS plan_attestations;
+DROP TABLE IF EXISTS plan_witnesses;
+DROP TABLE IF EXISTS plan_witness_policies;
@@ -0,0 +1,38 @@
+-- Witnesses an owner registers to attest their death or incapacity, and how
+-- many must attest before the plan becomes claimable.
+CREATE TABLE plan_witness_policies (
+    plan_id UUID PRIMARY KEY REFERENCES plans(id) ON DELETE CASCADE,
+    threshold INTEGER NOT NULL CHECK (threshold > 0),
+    quorum_reached_at TIMESTAMPTZ,
+    -- Hash over the attestations that met the threshold, and its anchoring
+    attestation_hash TEXT,
+    anchored_hash TEXT,
+    anchor_tx_hash TEXT,
+    last_error TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE plan_witnesses (
+    plan_id UUID NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    -- Stellar public key the witness signs attestations with
+    witness_address TEXT NOT NULL,
+    name TEXT NOT NULL,
+    role TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (plan_id, witness_address)
+);
+
+CREATE TABLE plan_attestations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES plans(id) ON DELETE CASCADE,
+    witness_address TEXT NOT NULL,
+    event TEXT NOT NULL CHECK (event IN ('death', 'incapacity')),
+    occurred_on DATE NOT NULL,
+    signature TEXT NOT NULL,
+    attestation_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (plan_id, witness_address)
+);
+
+CREATE INDEX plan_witness_policies_unanchored_idx ON plan_witness_policies (quorum_reached_at)
+    WHERE attestation_hash IS DISTINCT FROM anchored_hash;
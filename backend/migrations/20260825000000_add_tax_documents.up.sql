@@ -0,0 +1,16 @@
+-- Annual tax statements, one per wallet and year. The summary is kept as
+-- generated so a statement reads the same every time it is downloaded;
+-- regenerating a year replaces it.
+CREATE TABLE tax_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_address TEXT NOT NULL,
+    tax_year INTEGER NOT NULL,
+    jurisdiction TEXT,
+    language TEXT NOT NULL,
+    summary JSONB NOT NULL,
+    generated_by TEXT NOT NULL,
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT tax_documents_user_year_unique UNIQUE (user_address, tax_year)
+);
+
+CREATE INDEX tax_documents_year_idx ON tax_documents (tax_year);
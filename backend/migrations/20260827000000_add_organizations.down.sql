@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS plans_organization_idx;
+DROP INDEX IF EXISTS users_organization_idx;
+ALTER TABLE plans DROP COLUMN IF EXISTS organization_id;
+ALTER TABLE users DROP COLUMN IF EXISTS organization_id;
+DROP TABLE IF EXISTS organization_admins;
+DROP TABLE IF EXISTS organizations;
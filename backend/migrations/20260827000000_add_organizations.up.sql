@@ -0,0 +1,33 @@
+-- White-label organizations: estate-planning firms running InheritX under
+-- their own brand. Users and plans without an organization belong to the
+-- platform itself.
+CREATE TABLE organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    branding JSONB NOT NULL DEFAULT '{}',
+    -- Overrides the platform fee schedule for the organization's plans
+    payout_fee_bps INTEGER CHECK (payout_fee_bps BETWEEN 0 AND 10000),
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Admins limited to one organization, keyed by admin JWT subject
+CREATE TABLE organization_admins (
+    admin_id TEXT PRIMARY KEY,
+    organization_id UUID NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'admin')),
+    added_by TEXT NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX organization_admins_org_idx ON organization_admins (organization_id);
+
+ALTER TABLE users ADD COLUMN organization_id UUID REFERENCES organizations (id);
+ALTER TABLE plans ADD COLUMN organization_id UUID REFERENCES organizations (id);
+
+CREATE INDEX users_organization_idx ON users (organization_id)
+    WHERE organization_id IS NOT NULL;
+CREATE INDEX plans_organization_idx ON plans (organization_id)
+    WHERE organization_id IS NOT NULL;
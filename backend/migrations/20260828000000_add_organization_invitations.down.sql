@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS organization_invitations;
+DROP INDEX IF EXISTS organization_admins_one_owner_idx;
+ALTER TABLE organization_admins DROP CONSTRAINT organization_admins_role_check;
+UPDATE organization_admins SET role = 'admin' WHERE role IN ('manager', 'agent');
+ALTER TABLE organization_admins ADD CONSTRAINT organization_admins_role_check
+    CHECK (role IN ('owner', 'admin'));
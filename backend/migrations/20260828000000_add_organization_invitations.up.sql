@@ -0,0 +1,28 @@
+-- Organization admin roles: the owner manages admins, managers work on the
+-- organization's plans and users, agents only read them
+UPDATE organization_admins SET role = 'manager' WHERE role = 'admin';
+ALTER TABLE organization_admins DROP CONSTRAINT organization_admins_role_check;
+ALTER TABLE organization_admins ADD CONSTRAINT organization_admins_role_check
+    CHECK (role IN ('owner', 'manager', 'agent'));
+
+CREATE UNIQUE INDEX organization_admins_one_owner_idx
+    ON organization_admins (organization_id) WHERE role = 'owner';
+
+-- Email invitations to become an organization admin; only the SHA-256 of
+-- the one-time token is stored
+CREATE TABLE organization_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id UUID NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('manager', 'agent')),
+    token_hash TEXT NOT NULL UNIQUE,
+    invited_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX organization_invitations_pending_idx
+    ON organization_invitations (organization_id, email)
+    WHERE accepted_at IS NULL AND revoked_at IS NULL;
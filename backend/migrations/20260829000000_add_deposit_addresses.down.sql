@@ -0,0 +1,7 @@
+ALTER TABLE ledger_journals DROP CONSTRAINT ledger_journals_event_type_check;
+ALTER TABLE ledger_journals ADD CONSTRAINT ledger_journals_event_type_check CHECK (event_type IN (
+    'deposit', 'yield_accrued', 'payout_created', 'payout_completed',
+    'payout_failed', 'payout_requeued'));
+
+DROP TABLE IF EXISTS quarantined_deposits;
+DROP TABLE IF EXISTS plan_deposit_addresses;
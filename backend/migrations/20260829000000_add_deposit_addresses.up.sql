@@ -0,0 +1,41 @@
+-- Muxed deposit addresses: a plan can be issued a 64-bit id which, with a
+-- deposit account, forms an M... address that routes payments to the plan
+-- without a memo
+CREATE TABLE plan_deposit_addresses (
+    plan_id UUID PRIMARY KEY REFERENCES plans (id) ON DELETE CASCADE,
+    muxed_id BIGSERIAL NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Deposits the watcher could not route to exactly one plan. They are
+-- recorded in lending_events without a plan and wait here for an admin to
+-- assign them to a plan or dismiss them
+CREATE TABLE quarantined_deposits (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    lending_event_id UUID NOT NULL UNIQUE,
+    reason TEXT NOT NULL CHECK (reason IN (
+        'missing_reference', 'unknown_reference', 'conflicting_reference')),
+    account TEXT NOT NULL,
+    memo TEXT,
+    -- Muxed ids are unsigned 64-bit, so they are kept as text
+    muxed_id TEXT,
+    from_address TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'assigned', 'dismissed')),
+    plan_id UUID REFERENCES plans (id) ON DELETE SET NULL,
+    resolved_by TEXT,
+    resolution_note TEXT,
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX quarantined_deposits_status_idx ON quarantined_deposits (status, created_at DESC);
+
+-- Assigning a quarantined deposit moves it from unallocated_deposits to
+-- the plan
+ALTER TABLE ledger_journals DROP CONSTRAINT ledger_journals_event_type_check;
+ALTER TABLE ledger_journals ADD CONSTRAINT ledger_journals_event_type_check CHECK (event_type IN (
+    'deposit', 'deposit_assigned', 'yield_accrued', 'payout_created', 'payout_completed',
+    'payout_failed', 'payout_requeued'));
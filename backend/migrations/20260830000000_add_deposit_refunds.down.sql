@@ -0,0 +1,13 @@
+ALTER TABLE ledger_journals DROP CONSTRAINT ledger_journals_event_type_check;
+ALTER TABLE ledger_journals ADD CONSTRAINT ledger_journals_event_type_check CHECK (event_type IN (
+    'deposit', 'deposit_assigned', 'yield_accrued', 'payout_created', 'payout_completed',
+    'payout_failed', 'payout_requeued'));
+
+DROP TABLE IF EXISTS deposit_refunds;
+
+ALTER TABLE quarantined_deposits DROP CONSTRAINT quarantined_deposits_status_check;
+ALTER TABLE quarantined_deposits ADD CONSTRAINT quarantined_deposits_status_check CHECK (status IN (
+    'open', 'assigned', 'dismissed'));
+ALTER TABLE quarantined_deposits DROP CONSTRAINT quarantined_deposits_reason_check;
+ALTER TABLE quarantined_deposits ADD CONSTRAINT quarantined_deposits_reason_check CHECK (reason IN (
+    'missing_reference', 'unknown_reference', 'conflicting_reference'));
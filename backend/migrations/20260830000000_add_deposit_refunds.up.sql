@@ -0,0 +1,52 @@
+-- Payments in an asset other than the deposit asset are quarantined too,
+-- and a quarantined deposit can be refunded to its sender
+ALTER TABLE quarantined_deposits DROP CONSTRAINT quarantined_deposits_reason_check;
+ALTER TABLE quarantined_deposits ADD CONSTRAINT quarantined_deposits_reason_check CHECK (reason IN (
+    'missing_reference', 'unknown_reference', 'conflicting_reference', 'wrong_asset'));
+ALTER TABLE quarantined_deposits DROP CONSTRAINT quarantined_deposits_status_check;
+ALTER TABLE quarantined_deposits ADD CONSTRAINT quarantined_deposits_status_check CHECK (status IN (
+    'open', 'assigned', 'dismissed', 'refunding', 'refunded'));
+
+-- Deposits sent back to the address they came from: the part of a plan's
+-- deposits above the plan amount, or a quarantined deposit. A refund is
+-- requested, approved or rejected by an admin, and approved refunds are
+-- sent by the refund worker
+CREATE TABLE deposit_refunds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- The deposit being returned
+    lending_event_id UUID NOT NULL,
+    reason TEXT NOT NULL CHECK (reason IN ('overpayment', 'quarantined')),
+    plan_id UUID REFERENCES plans (id) ON DELETE SET NULL,
+    quarantine_id UUID REFERENCES quarantined_deposits (id),
+    destination TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    status TEXT NOT NULL DEFAULT 'requested' CHECK (status IN (
+        'requested', 'approved', 'rejected', 'submitting', 'completed', 'failed')),
+    requested_by TEXT NOT NULL,
+    request_note TEXT,
+    reviewed_by TEXT,
+    review_note TEXT,
+    reviewed_at TIMESTAMPTZ,
+    -- Memo the transfer is sent with, to find it on-chain if the
+    -- submission outcome is lost
+    memo TEXT,
+    tx_hash TEXT,
+    failure_reason TEXT,
+    submitted_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX deposit_refunds_status_idx ON deposit_refunds (status, created_at DESC);
+CREATE INDEX deposit_refunds_plan_idx ON deposit_refunds (plan_id, created_at DESC);
+CREATE UNIQUE INDEX deposit_refunds_active_quarantine_unique
+    ON deposit_refunds (quarantine_id) WHERE status NOT IN ('rejected', 'failed');
+
+-- A completed refund leaves custody, settling what was owed to the plan
+-- or held as unallocated
+ALTER TABLE ledger_journals DROP CONSTRAINT ledger_journals_event_type_check;
+ALTER TABLE ledger_journals ADD CONSTRAINT ledger_journals_event_type_check CHECK (event_type IN (
+    'deposit', 'deposit_assigned', 'deposit_refunded', 'yield_accrued', 'payout_created',
+    'payout_completed', 'payout_failed', 'payout_requeued'));
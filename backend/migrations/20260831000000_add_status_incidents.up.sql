@@ -0,0 +1,18 @@
+-- Incidents and maintenance shown on the public status page. An incident
+-- raises the status of the components it names until it is resolved
+CREATE TABLE status_incidents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    impact TEXT NOT NULL CHECK (impact IN ('maintenance', 'degraded', 'major_outage')),
+    components TEXT[] NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL DEFAULT 'investigating' CHECK (status IN (
+        'investigating', 'identified', 'monitoring', 'resolved')),
+    created_by TEXT NOT NULL,
+    updated_by TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX status_incidents_started_idx ON status_incidents (started_at DESC);
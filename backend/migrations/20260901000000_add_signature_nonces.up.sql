@@ -0,0 +1,10 @@
+-- Nonces of timestamped request signatures, kept until the signature
+-- leaves the step-up window so it cannot be replayed inside it
+CREATE TABLE signature_nonces (
+    public_key TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (public_key, nonce)
+);
+
+CREATE INDEX signature_nonces_expires_idx ON signature_nonces (expires_at);
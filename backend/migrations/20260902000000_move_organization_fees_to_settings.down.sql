@@ -0,0 +1,9 @@
+ALTER TABLE organizations
+    ADD COLUMN payout_fee_bps INTEGER CHECK (payout_fee_bps BETWEEN 0 AND 10000);
+
+UPDATE organizations o
+SET payout_fee_bps = (s.value ->> o.id::text)::int
+FROM platform_settings s
+WHERE s.key = 'organization_fees';
+
+DELETE FROM platform_settings WHERE key = 'organization_fees';
@@ -0,0 +1,10 @@
+-- Organization fee overrides become the organization_fees platform setting,
+-- changed through pending_changes review like the platform fee schedule
+INSERT INTO platform_settings (key, value, updated_by)
+SELECT 'organization_fees', jsonb_object_agg(id::text, payout_fee_bps), 'migration'
+FROM organizations
+WHERE payout_fee_bps IS NOT NULL
+HAVING COUNT(*) > 0
+ON CONFLICT (key) DO NOTHING;
+
+ALTER TABLE organizations DROP COLUMN payout_fee_bps;
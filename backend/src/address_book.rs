@@ -0,0 +1,389 @@
+//! Saved payout addresses with proof of control.
+//!
+//! An address must be verified before it can receive claim payouts, either by
+//! signing the issued challenge with the address key or by sending a dust
+//! payment carrying the verification memo.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::{verify_wallet_signature, UserContext};
+
+const MAX_LABEL_LEN: usize = 64;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AddressBookEntry {
+    pub id: Uuid,
+    pub owner_address: String,
+    pub label: String,
+    pub address: String,
+    #[serde(skip_serializing)]
+    pub verification_nonce: String,
+    pub verification_memo: String,
+    pub verification_method: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AddressBookEntryResponse {
+    #[serde(flatten)]
+    pub entry: AddressBookEntry,
+    /// Message the address key must sign to verify by signature.
+    pub challenge: Option<String>,
+}
+
+impl From<AddressBookEntry> for AddressBookEntryResponse {
+    fn from(entry: AddressBookEntry) -> Self {
+        let challenge = entry
+            .verified_at
+            .is_none()
+            .then(|| challenge_message(&entry.address, &entry.verification_nonce));
+        Self { entry, challenge }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAddressRequest {
+    pub label: String,
+    pub address: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyAddressRequest {
+    /// Hex-encoded ed25519 signature over the entry's challenge.
+    pub signature: String,
+}
+
+/// Message a user signs with the saved address key to prove control.
+pub fn challenge_message(address: &str, nonce: &str) -> String {
+    format!("InheritX address verification\naddress: {address}\nnonce: {nonce}")
+}
+
+fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Stellar text memos are limited to 28 bytes.
+fn memo_for_nonce(nonce: &str) -> String {
+    format!("ixv-{}", &nonce[..16])
+}
+
+pub fn is_valid_stellar_address(address: &str) -> bool {
+    stellar_strkey::ed25519::PublicKey::from_string(address).is_ok()
+}
+
+/// Returns true when `owner` has verified `address` as a payout destination.
+pub async fn is_verified_destination<'e, E>(
+    executor: E,
+    owner: &str,
+    address: &str,
+) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM address_book_entries
+            WHERE owner_address = $1 AND address = $2 AND verified_at IS NOT NULL
+        )
+        "#,
+    )
+    .bind(owner)
+    .bind(address)
+    .fetch_one(executor)
+    .await
+}
+
+/// Marks the entry carrying `memo` as verified by an observed dust payment
+/// from `source_address`. Returns whether an entry was updated.
+pub async fn confirm_memo_payment(
+    db: &PgPool,
+    memo: &str,
+    source_address: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE address_book_entries
+        SET verified_at = NOW(), verification_method = 'memo_payment'
+        WHERE verification_memo = $1 AND address = $2 AND verified_at IS NULL
+        "#,
+    )
+    .bind(memo)
+    .bind(source_address)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Handler: List Address Book
+pub async fn list_addresses(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, AddressBookEntry>(
+        r#"
+        SELECT id, owner_address, label, address, verification_nonce, verification_memo,
+               verification_method, verified_at, created_at
+        FROM address_book_entries
+        WHERE owner_address = $1
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(&owner)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => {
+            let entries: Vec<AddressBookEntryResponse> = rows.into_iter().map(Into::into).collect();
+            (StatusCode::OK, Json(entries)).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to list address book entries");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: Save Address
+pub async fn create_address(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<CreateAddressRequest>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+
+    let label = payload.label.trim();
+    if label.is_empty() || label.len() > MAX_LABEL_LEN {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("Label must be between 1 and {MAX_LABEL_LEN} characters")
+            })),
+        )
+            .into_response();
+    }
+
+    let address = payload.address.trim();
+    if !is_valid_stellar_address(address) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Address must be a valid Stellar account (G...)" })),
+        )
+            .into_response();
+    }
+
+    let nonce = generate_nonce();
+    let memo = memo_for_nonce(&nonce);
+
+    let result = sqlx::query_as::<_, AddressBookEntry>(
+        r#"
+        INSERT INTO address_book_entries
+            (owner_address, label, address, verification_nonce, verification_memo)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, owner_address, label, address, verification_nonce, verification_memo,
+                  verification_method, verified_at, created_at
+        "#,
+    )
+    .bind(&owner)
+    .bind(label)
+    .bind(address)
+    .bind(&nonce)
+    .bind(&memo)
+    .fetch_one(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(entry) => (
+            StatusCode::CREATED,
+            Json(AddressBookEntryResponse::from(entry)),
+        )
+            .into_response(),
+        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "Address is already in the address book" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to save address book entry");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to save address" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: Verify Address by Signature
+pub async fn verify_address(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<VerifyAddressRequest>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+
+    let entry = match sqlx::query_as::<_, AddressBookEntry>(
+        r#"
+        SELECT id, owner_address, label, address, verification_nonce, verification_memo,
+               verification_method, verified_at, created_at
+        FROM address_book_entries
+        WHERE id = $1 AND owner_address = $2
+        "#,
+    )
+    .bind(id)
+    .bind(&owner)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(entry)) => entry,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Address book entry not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to load address book entry");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    if entry.verified_at.is_some() {
+        return (StatusCode::OK, Json(AddressBookEntryResponse::from(entry))).into_response();
+    }
+
+    let message = challenge_message(&entry.address, &entry.verification_nonce);
+    if !verify_wallet_signature(&entry.address, message.as_bytes(), &payload.signature) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "error": "Signature does not match the saved address" })),
+        )
+            .into_response();
+    }
+
+    match sqlx::query_as::<_, AddressBookEntry>(
+        r#"
+        UPDATE address_book_entries
+        SET verified_at = NOW(), verification_method = 'signature'
+        WHERE id = $1
+        RETURNING id, owner_address, label, address, verification_nonce, verification_memo,
+                  verification_method, verified_at, created_at
+        "#,
+    )
+    .bind(entry.id)
+    .fetch_one(&state.db_pool)
+    .await
+    {
+        Ok(entry) => {
+            info!(entry_id = %entry.id, address = %entry.address, "Address verified by signature");
+            (StatusCode::OK, Json(AddressBookEntryResponse::from(entry))).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to mark address as verified");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to verify address" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: Remove Address
+pub async fn delete_address(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query("DELETE FROM address_book_entries WHERE id = $1 AND owner_address = $2")
+        .bind(id)
+        .bind(&owner)
+        .execute(&state.db_pool)
+        .await
+    {
+        Ok(result) if result.rows_affected() > 0 => StatusCode::NO_CONTENT.into_response(),
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Address book entry not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to delete address book entry");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to delete address" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memo_fits_stellar_text_memo_limit() {
+        let memo = memo_for_nonce(&generate_nonce());
+        assert!(memo.len() <= 28);
+        assert!(memo.starts_with("ixv-"));
+    }
+
+    #[test]
+    fn challenge_binds_address_and_nonce() {
+        let message = challenge_message("GABC", "deadbeef");
+        assert!(message.contains("address: GABC"));
+        assert!(message.contains("nonce: deadbeef"));
+    }
+
+    #[test]
+    fn rejects_non_stellar_addresses() {
+        assert!(!is_valid_stellar_address("not-an-address"));
+        assert!(is_valid_stellar_address(
+            &stellar_strkey::ed25519::PublicKey([1u8; 32]).to_string()
+        ));
+    }
+}
@@ -0,0 +1,343 @@
+//! Network restrictions for the admin API.
+//!
+//! When `ADMIN_ALLOWED_CIDRS` and/or `ADMIN_ALLOWED_COUNTRIES` are set,
+//! requests to `/api/admin/*` must come from an allowed range and country
+//! before JWT auth is attempted. Blocked attempts are written to
+//! `audit_logs`. An operator locked out by the restrictions can send the
+//! break-glass token in `X-Break-Glass-Token`; the admin JWT is still
+//! required and every use of the token is audited.
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ipnet::IpNet;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::config::Config;
+
+pub const BREAK_GLASS_HEADER: &str = "x-break-glass-token";
+
+/// Resolves a client address to a country.
+pub trait GeoIpLookup: Send + Sync {
+    /// ISO 3166-1 alpha-2 code (upper case) for `ip`, if known.
+    fn country(&self, ip: IpAddr) -> Option<String>;
+}
+
+/// GeoIP table loaded from `cidr,country` CSV rows. The most specific
+/// matching range wins.
+#[derive(Debug, Default)]
+pub struct CidrCountryTable {
+    ranges: Vec<(IpNet, String)>,
+}
+
+impl CidrCountryTable {
+    pub fn from_reader(reader: impl Read) -> Result<Self, String> {
+        let mut csv = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .comment(Some(b'#'))
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        let mut ranges = Vec::new();
+        for (line, record) in csv.records().enumerate() {
+            let record = record.map_err(|e| format!("line {}: {e}", line + 1))?;
+            let (Some(range), Some(country)) = (record.get(0), record.get(1)) else {
+                return Err(format!("line {}: expected cidr,country", line + 1));
+            };
+            let range: IpNet = range
+                .parse()
+                .map_err(|e| format!("line {}: {e}", line + 1))?;
+            ranges.push((range, country.to_ascii_uppercase()));
+        }
+        ranges.sort_by_key(|(range, _)| std::cmp::Reverse(range.prefix_len()));
+        Ok(Self { ranges })
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::from_reader(file).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+impl GeoIpLookup for CidrCountryTable {
+    fn country(&self, ip: IpAddr) -> Option<String> {
+        self.ranges
+            .iter()
+            .find(|(range, _)| range.contains(&ip))
+            .map(|(_, country)| country.clone())
+    }
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Denial {
+    pub reason: &'static str,
+    pub country: Option<String>,
+}
+
+#[derive(Default)]
+pub struct AdminAccessPolicy {
+    allowed_cidrs: Vec<IpNet>,
+    allowed_countries: Vec<String>,
+    trusted_proxies: Vec<IpNet>,
+    break_glass_sha256: Option<Vec<u8>>,
+    geoip: Option<Arc<dyn GeoIpLookup>>,
+}
+
+impl AdminAccessPolicy {
+    /// Builds the policy from config, loading `ADMIN_GEOIP_CSV` if set.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let geoip = match &config.admin_geoip_csv {
+            Some(path) => {
+                Some(Arc::new(CidrCountryTable::from_path(path)?) as Arc<dyn GeoIpLookup>)
+            }
+            None => None,
+        };
+        Ok(Self::with_geoip(config, geoip))
+    }
+
+    pub fn with_geoip(config: &Config, geoip: Option<Arc<dyn GeoIpLookup>>) -> Self {
+        Self {
+            allowed_cidrs: config.admin_allowed_cidrs.clone(),
+            allowed_countries: config.admin_allowed_countries.clone(),
+            trusted_proxies: config.admin_trusted_proxies.clone(),
+            break_glass_sha256: config
+                .admin_break_glass_token_sha256
+                .as_deref()
+                .and_then(|digest| hex::decode(digest).ok()),
+            geoip,
+        }
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        !self.allowed_cidrs.is_empty() || !self.allowed_countries.is_empty()
+    }
+
+    /// The client address: the peer, or when the peer is a trusted proxy,
+    /// the nearest `X-Forwarded-For` hop that is not one.
+    pub fn client_ip(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        let peer = peer?;
+        if !self.is_trusted_proxy(peer) {
+            return Some(peer);
+        }
+        let forwarded: Vec<IpAddr> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|hop| hop.trim().parse::<IpAddr>())
+            .collect::<Result<_, _>>()
+            .ok()?;
+        Some(
+            forwarded
+                .into_iter()
+                .rev()
+                .find(|hop| !self.is_trusted_proxy(*hop))
+                .unwrap_or(peer),
+        )
+    }
+
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|range| range.contains(&ip))
+    }
+
+    /// Checks `ip` against the configured ranges and countries. An unknown
+    /// address is refused whenever a restriction is configured.
+    pub fn check(&self, ip: Option<IpAddr>) -> Result<(), Denial> {
+        if !self.is_restricted() {
+            return Ok(());
+        }
+        let Some(ip) = ip else {
+            return Err(Denial {
+                reason: "unknown_client_address",
+                country: None,
+            });
+        };
+
+        let country = self.geoip.as_ref().and_then(|geoip| geoip.country(ip));
+        if !self.allowed_cidrs.is_empty() && !self.allowed_cidrs.iter().any(|r| r.contains(&ip)) {
+            return Err(Denial {
+                reason: "address_not_allowed",
+                country,
+            });
+        }
+        if !self.allowed_countries.is_empty()
+            && !country
+                .as_ref()
+                .is_some_and(|c| self.allowed_countries.contains(c))
+        {
+            return Err(Denial {
+                reason: "country_not_allowed",
+                country,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether `token` hashes to the configured break-glass digest.
+    pub fn break_glass_matches(&self, token: &str) -> bool {
+        let Some(expected) = &self.break_glass_sha256 else {
+            return false;
+        };
+        let actual = Sha256::digest(token.as_bytes());
+        expected.len() == actual.len()
+            && expected
+                .iter()
+                .zip(actual.iter())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+/// Refuses admin requests from outside the configured network policy.
+pub async fn admin_access_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let policy = &state.admin_access;
+    if !policy.is_restricted() {
+        return next.run(req).await;
+    }
+
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ci| ci.0.ip());
+    let ip = policy.client_ip(peer, req.headers());
+    let Err(denial) = policy.check(ip) else {
+        return next.run(req).await;
+    };
+
+    let path = req.uri().path().to_string();
+    let ip_text = ip.map(|ip| ip.to_string());
+    let break_glass = req
+        .headers()
+        .get(BREAK_GLASS_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|token| policy.break_glass_matches(token));
+
+    let (action, actor) = if break_glass {
+        ("admin_access.break_glass", "break-glass")
+    } else {
+        ("admin_access.blocked", "anonymous")
+    };
+    if let Err(e) = record_audit(
+        &state.db_pool,
+        actor,
+        action,
+        &path,
+        serde_json::json!({
+            "ip": ip_text,
+            "country": denial.country,
+            "reason": denial.reason,
+            "method": req.method().as_str(),
+        }),
+    )
+    .await
+    {
+        error!(error = %e, "Failed to audit admin access decision");
+        if break_glass {
+            // Break-glass use must never go unrecorded.
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "Audit log unavailable" })),
+            )
+                .into_response();
+        }
+    }
+
+    if break_glass {
+        warn!(ip = ?ip_text, path = %path, "Admin network restriction bypassed with break-glass token");
+        return next.run(req).await;
+    }
+
+    warn!(ip = ?ip_text, path = %path, reason = denial.reason, "Blocked admin request");
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({ "error": "Admin access is not allowed from this network" })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn policy(cidrs: &[&str], countries: &[&str]) -> AdminAccessPolicy {
+        let mut config = Config::for_tests();
+        config.admin_allowed_cidrs = cidrs.iter().map(|c| c.parse().unwrap()).collect();
+        config.admin_allowed_countries = countries.iter().map(|c| c.to_string()).collect();
+        config.admin_trusted_proxies = vec!["10.0.0.0/8".parse().unwrap()];
+        config.admin_break_glass_token_sha256 = Some(hex::encode(Sha256::digest(b"open-sesame")));
+        let table = CidrCountryTable::from_reader(
+            "# cidr,country\n203.0.113.0/24,ng\n203.0.113.128/25,GB\n".as_bytes(),
+        )
+        .unwrap();
+        AdminAccessPolicy::with_geoip(&config, Some(Arc::new(table)))
+    }
+
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn unrestricted_policy_allows_everything() {
+        assert!(policy(&[], &[]).check(None).is_ok());
+    }
+
+    #[test]
+    fn checks_ranges_and_countries() {
+        let p = policy(&["203.0.113.0/24"], &["NG"]);
+        assert!(p.check(ip("203.0.113.7")).is_ok());
+        assert_eq!(
+            p.check(ip("203.0.113.200")).unwrap_err(),
+            Denial {
+                reason: "country_not_allowed",
+                country: Some("GB".into())
+            }
+        );
+        assert_eq!(
+            p.check(ip("198.51.100.1")).unwrap_err().reason,
+            "address_not_allowed"
+        );
+        assert_eq!(p.check(None).unwrap_err().reason, "unknown_client_address");
+    }
+
+    #[test]
+    fn resolves_client_behind_trusted_proxy() {
+        let p = policy(&["203.0.113.0/24"], &[]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("198.51.100.9, 203.0.113.7, 10.1.2.3"),
+        );
+        assert_eq!(p.client_ip(ip("10.0.0.1"), &headers), ip("203.0.113.7"));
+        // Untrusted peers cannot spoof their address with the header.
+        assert_eq!(
+            p.client_ip(ip("198.51.100.1"), &headers),
+            ip("198.51.100.1")
+        );
+    }
+
+    #[test]
+    fn matches_break_glass_token() {
+        let p = policy(&["203.0.113.0/24"], &[]);
+        assert!(p.break_glass_matches("open-sesame"));
+        assert!(!p.break_glass_matches("open-sesame "));
+        assert!(!AdminAccessPolicy::default().break_glass_matches("open-sesame"));
+    }
+}
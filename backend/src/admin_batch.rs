@@ -0,0 +1,576 @@
+//! Batch admin operations for KYC review and plan status management.
+//!
+//! Each batch runs in one transaction with a savepoint per item, so a bad
+//! item is reported without discarding the rest. With `all_or_nothing` set,
+//! any failed item rolls back the whole batch instead. Every applied item is
+//! audit logged, followed by one summary entry for the batch.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, PgPool, Postgres, Transaction};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::emergency_contacts::ContactAlert;
+use crate::kyc_webhook::notify_kyc_status;
+use crate::notifications::create_notification;
+use crate::ws::KycUpdateEvent;
+
+pub const MAX_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KycBatchAction {
+    Approve,
+    Reject,
+}
+
+impl KycBatchAction {
+    fn target_status(self) -> &'static str {
+        match self {
+            Self::Approve => "approved",
+            Self::Reject => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PlanBatchStatus {
+    /// Reinstates a plan and restarts its inactivity timer.
+    Active,
+    Claimable,
+}
+
+impl PlanBatchStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Active => "ACTIVE",
+            Self::Claimable => "CLAIMABLE",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KycBatchRequest {
+    pub action: KycBatchAction,
+    pub user_ids: Vec<Uuid>,
+    pub reason: String,
+    #[serde(default)]
+    pub all_or_nothing: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlanBatchStatusRequest {
+    pub plan_ids: Vec<Uuid>,
+    pub status: PlanBatchStatus,
+    pub reason: String,
+    #[serde(default)]
+    pub all_or_nothing: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemOutcome {
+    Updated,
+    Unchanged,
+    NotFound,
+    Rejected,
+    Failed,
+}
+
+impl ItemOutcome {
+    fn is_failure(self) -> bool {
+        matches!(self, Self::NotFound | Self::Rejected | Self::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemResult {
+    pub id: Uuid,
+    pub outcome: ItemOutcome,
+    pub previous_status: Option<String>,
+    pub error: Option<String>,
+}
+
+impl BatchItemResult {
+    fn new(id: Uuid, outcome: ItemOutcome, previous_status: Option<String>) -> Self {
+        Self {
+            id,
+            outcome,
+            previous_status,
+            error: None,
+        }
+    }
+
+    fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSummary {
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+}
+
+impl BatchSummary {
+    fn of(results: &[BatchItemResult]) -> Self {
+        let count = |outcome: ItemOutcome| results.iter().filter(|r| r.outcome == outcome).count();
+        Self {
+            updated: count(ItemOutcome::Updated),
+            unchanged: count(ItemOutcome::Unchanged),
+            failed: results.iter().filter(|r| r.outcome.is_failure()).count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResponse {
+    pub batch_id: Uuid,
+    /// False when `all_or_nothing` rolled the batch back.
+    pub committed: bool,
+    pub summary: BatchSummary,
+    pub results: Vec<BatchItemResult>,
+}
+
+/// Validates the shared fields of a batch and returns its ids with
+/// duplicates removed, preserving order.
+fn prepare_batch(ids: &[Uuid], reason: &str) -> Result<Vec<Uuid>, &'static str> {
+    if reason.trim().is_empty() {
+        return Err("A reason is required for batch operations");
+    }
+    if ids.is_empty() {
+        return Err("At least one id is required");
+    }
+    if ids.len() > MAX_BATCH_SIZE {
+        return Err("Batches are limited to 500 items");
+    }
+    let mut seen = HashSet::with_capacity(ids.len());
+    Ok(ids.iter().copied().filter(|id| seen.insert(*id)).collect())
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+/// Commits the batch with its summary audit entry, or rolls it back. A
+/// rolled back batch still gets a summary entry, written outside the
+/// transaction.
+async fn finish_batch(
+    db: &PgPool,
+    mut tx: Transaction<'_, Postgres>,
+    commit: bool,
+    actor: &str,
+    action: &str,
+    batch_id: Uuid,
+    details: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    if commit {
+        record_audit(&mut *tx, actor, action, &batch_id.to_string(), details).await?;
+        tx.commit().await?;
+    } else {
+        tx.rollback().await?;
+        record_audit(db, actor, action, &batch_id.to_string(), details).await?;
+    }
+    Ok(())
+}
+
+fn batch_response(batch_id: Uuid, committed: bool, results: Vec<BatchItemResult>) -> BatchResponse {
+    BatchResponse {
+        batch_id,
+        committed,
+        summary: BatchSummary::of(&results),
+        results,
+    }
+}
+
+// Handler: Batch KYC Review
+pub async fn batch_update_kyc(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(payload): Json<KycBatchRequest>,
+) -> impl IntoResponse {
+    let user_ids = match prepare_batch(&payload.user_ids, &payload.reason) {
+        Ok(ids) => ids,
+        Err(message) => return bad_request(message),
+    };
+    let reason = payload.reason.trim();
+    let target = payload.action.target_status();
+    let batch_id = Uuid::new_v4();
+
+    let result: Result<(bool, Vec<BatchItemResult>, Vec<String>), sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let mut results = Vec::with_capacity(user_ids.len());
+        let mut updated_wallets = Vec::new();
+
+        for user_id in &user_ids {
+            let mut item = tx.begin().await?;
+            let applied: Result<BatchItemResult, sqlx::Error> = async {
+                let Some((wallet_address, current)) = sqlx::query_as::<_, (String, String)>(
+                    "SELECT wallet_address, kyc_status::text FROM users WHERE id = $1 FOR UPDATE",
+                )
+                .bind(user_id)
+                .fetch_optional(&mut *item)
+                .await?
+                else {
+                    return Ok(BatchItemResult::new(*user_id, ItemOutcome::NotFound, None));
+                };
+                if current == target {
+                    return Ok(BatchItemResult::new(
+                        *user_id,
+                        ItemOutcome::Unchanged,
+                        Some(current),
+                    ));
+                }
+
+                sqlx::query("UPDATE users SET kyc_status = $2::kyc_status WHERE id = $1")
+                    .bind(user_id)
+                    .bind(target)
+                    .execute(&mut *item)
+                    .await?;
+                record_audit(
+                    &mut *item,
+                    &admin.user_id,
+                    "kyc.batch_item",
+                    &wallet_address,
+                    serde_json::json!({
+                        "batch_id": batch_id,
+                        "user_id": user_id,
+                        "from": current,
+                        "to": target,
+                        "reason": reason,
+                    }),
+                )
+                .await?;
+                notify_kyc_status(&mut item, &wallet_address, target).await?;
+                updated_wallets.push(wallet_address);
+                Ok(BatchItemResult::new(
+                    *user_id,
+                    ItemOutcome::Updated,
+                    Some(current),
+                ))
+            }
+            .await;
+
+            match applied {
+                Ok(result) => {
+                    item.commit().await?;
+                    results.push(result);
+                }
+                Err(e) => {
+                    warn!(batch_id = %batch_id, user_id = %user_id, error = %e, "KYC batch item failed");
+                    item.rollback().await?;
+                    results.push(
+                        BatchItemResult::new(*user_id, ItemOutcome::Failed, None)
+                            .with_error("Database update failed"),
+                    );
+                }
+            }
+        }
+
+        let summary = BatchSummary::of(&results);
+        let committed = !(payload.all_or_nothing && summary.failed > 0);
+        finish_batch(
+            &state.db_pool,
+            tx,
+            committed,
+            &admin.user_id,
+            "kyc.batch",
+            batch_id,
+            serde_json::json!({
+                "committed": committed,
+                "summary": summary,
+                "action": payload.action,
+                "reason": reason,
+                "requested": user_ids.len(),
+            }),
+        )
+        .await?;
+        Ok((committed, results, updated_wallets))
+    }
+    .await;
+
+    match result {
+        Ok((committed, results, updated_wallets)) => {
+            if committed {
+                for wallet_address in updated_wallets {
+                    // No subscribers is not an error.
+                    let _ = state.kyc_tx.send(KycUpdateEvent {
+                        wallet_address,
+                        kyc_status: target.to_string(),
+                        event_type: "admin_batch".to_string(),
+                    });
+                }
+            }
+            info!(batch_id = %batch_id, admin = %admin.user_id, committed, "KYC batch processed");
+            let status = if committed {
+                StatusCode::OK
+            } else {
+                StatusCode::CONFLICT
+            };
+            (status, Json(batch_response(batch_id, committed, results))).into_response()
+        }
+        Err(e) => {
+            error!(batch_id = %batch_id, error = %e, "Failed to process KYC batch");
+            database_error()
+        }
+    }
+}
+
+struct ClaimablePlan {
+    plan_id: Uuid,
+    owner_address: String,
+}
+
+// Handler: Batch Plan Status
+pub async fn batch_update_plan_status(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(payload): Json<PlanBatchStatusRequest>,
+) -> impl IntoResponse {
+    let plan_ids = match prepare_batch(&payload.plan_ids, &payload.reason) {
+        Ok(ids) => ids,
+        Err(message) => return bad_request(message),
+    };
+    let reason = payload.reason.trim();
+    let target = payload.status;
+    let batch_id = Uuid::new_v4();
+
+    type Touched = Vec<(String, Vec<String>)>;
+    let result: Result<(bool, Vec<BatchItemResult>, Touched, Vec<ClaimablePlan>), sqlx::Error> =
+        async {
+            let mut tx = state.db_pool.begin().await?;
+            let mut results = Vec::with_capacity(plan_ids.len());
+            let mut touched: Touched = Vec::new();
+            let mut claimable = Vec::new();
+
+            for plan_id in &plan_ids {
+                let mut item = tx.begin().await?;
+                let applied: Result<BatchItemResult, sqlx::Error> = async {
+                    let Some((owner_address, current, is_active)) =
+                        sqlx::query_as::<_, (String, String, bool)>(
+                            "SELECT owner_address, status, is_active FROM plans WHERE id = $1 FOR UPDATE",
+                        )
+                        .bind(plan_id)
+                        .fetch_optional(&mut *item)
+                        .await?
+                    else {
+                        return Ok(BatchItemResult::new(*plan_id, ItemOutcome::NotFound, None));
+                    };
+                    if !is_active {
+                        return Ok(BatchItemResult::new(
+                            *plan_id,
+                            ItemOutcome::Rejected,
+                            Some(current),
+                        )
+                        .with_error("Plan has already been paid out"));
+                    }
+                    if current == target.as_str() {
+                        return Ok(BatchItemResult::new(
+                            *plan_id,
+                            ItemOutcome::Unchanged,
+                            Some(current),
+                        ));
+                    }
+
+                    match target {
+                        PlanBatchStatus::Active => {
+                            sqlx::query("UPDATE plans SET status = $2, last_ping = $3 WHERE id = $1")
+                                .bind(plan_id)
+                                .bind(target.as_str())
+                                .bind(Utc::now().timestamp())
+                                .execute(&mut *item)
+                                .await?;
+                        }
+                        PlanBatchStatus::Claimable => {
+                            sqlx::query("UPDATE plans SET status = $2 WHERE id = $1")
+                                .bind(plan_id)
+                                .bind(target.as_str())
+                                .execute(&mut *item)
+                                .await?;
+                        }
+                    }
+
+                    let beneficiaries: Vec<String> = sqlx::query_scalar(
+                        "SELECT wallet_address FROM beneficiaries WHERE plan_id = $1",
+                    )
+                    .bind(plan_id)
+                    .fetch_all(&mut *item)
+                    .await?;
+                    if target == PlanBatchStatus::Claimable {
+                        for beneficiary in &beneficiaries {
+                            create_notification(
+                                &mut *item,
+                                beneficiary,
+                                "plan_claimable",
+                                "An inheritance plan is ready to claim",
+                                "An administrator has marked the plan claimable.",
+                                serde_json::json!({ "plan_id": plan_id }),
+                            )
+                            .await?;
+                        }
+                    }
+
+                    record_audit(
+                        &mut *item,
+                        &admin.user_id,
+                        "plan.batch_status_item",
+                        &plan_id.to_string(),
+                        serde_json::json!({
+                            "batch_id": batch_id,
+                            "owner_address": owner_address,
+                            "from": current,
+                            "to": target.as_str(),
+                            "reason": reason,
+                        }),
+                    )
+                    .await?;
+
+                    if target == PlanBatchStatus::Claimable {
+                        claimable.push(ClaimablePlan {
+                            plan_id: *plan_id,
+                            owner_address: owner_address.clone(),
+                        });
+                    }
+                    touched.push((owner_address, beneficiaries));
+                    Ok(BatchItemResult::new(
+                        *plan_id,
+                        ItemOutcome::Updated,
+                        Some(current),
+                    ))
+                }
+                .await;
+
+                match applied {
+                    Ok(result) => {
+                        item.commit().await?;
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        warn!(batch_id = %batch_id, plan_id = %plan_id, error = %e, "Plan batch item failed");
+                        item.rollback().await?;
+                        results.push(
+                            BatchItemResult::new(*plan_id, ItemOutcome::Failed, None)
+                                .with_error("Database update failed"),
+                        );
+                    }
+                }
+            }
+
+            let summary = BatchSummary::of(&results);
+            let committed = !(payload.all_or_nothing && summary.failed > 0);
+            finish_batch(
+                &state.db_pool,
+                tx,
+                committed,
+                &admin.user_id,
+                "plan.batch_status",
+                batch_id,
+                serde_json::json!({
+                    "committed": committed,
+                    "summary": summary,
+                    "status": target.as_str(),
+                    "reason": reason,
+                    "requested": plan_ids.len(),
+                }),
+            )
+            .await?;
+            Ok((committed, results, touched, claimable))
+        }
+        .await;
+
+    match result {
+        Ok((committed, results, touched, claimable)) => {
+            if committed {
+                for (owner_address, beneficiaries) in &touched {
+                    if let Err(err) = state
+                        .plan_cache
+                        .invalidate_queries(owner_address, beneficiaries)
+                        .await
+                    {
+                        warn!(owner_address = %owner_address, error = %err, "Failed to invalidate plan cache after batch update");
+                    }
+                }
+                for plan in claimable {
+                    let alert = ContactAlert::PlanClaimable {
+                        plan_id: plan.plan_id,
+                    };
+                    if let Err(e) = state
+                        .contacts
+                        .alert(&state.db_pool, &plan.owner_address, alert)
+                        .await
+                    {
+                        warn!(plan_id = %plan.plan_id, error = %e, "Failed to alert emergency contacts");
+                    }
+                }
+            }
+            info!(batch_id = %batch_id, admin = %admin.user_id, committed, "Plan status batch processed");
+            let status = if committed {
+                StatusCode::OK
+            } else {
+                StatusCode::CONFLICT
+            };
+            (status, Json(batch_response(batch_id, committed, results))).into_response()
+        }
+        Err(e) => {
+            error!(batch_id = %batch_id, error = %e, "Failed to process plan status batch");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepare_batch_dedupes_in_order() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        assert_eq!(prepare_batch(&[a, b, a], "cleanup").unwrap(), vec![a, b]);
+    }
+
+    #[test]
+    fn prepare_batch_rejects_bad_input() {
+        let id = Uuid::new_v4();
+        assert!(prepare_batch(&[id], "  ").is_err());
+        assert!(prepare_batch(&[], "cleanup").is_err());
+        assert!(prepare_batch(&vec![id; MAX_BATCH_SIZE + 1], "cleanup").is_err());
+    }
+
+    #[test]
+    fn summary_counts_failures() {
+        let results = vec![
+            BatchItemResult::new(Uuid::new_v4(), ItemOutcome::Updated, None),
+            BatchItemResult::new(Uuid::new_v4(), ItemOutcome::Unchanged, None),
+            BatchItemResult::new(Uuid::new_v4(), ItemOutcome::NotFound, None),
+            BatchItemResult::new(Uuid::new_v4(), ItemOutcome::Rejected, None),
+        ];
+        let summary = BatchSummary::of(&results);
+        assert_eq!(
+            (summary.updated, summary.unchanged, summary.failed),
+            (1, 1, 2)
+        );
+    }
+}
@@ -4,13 +4,13 @@ use crate::middleware::{
 };
 use axum::http::{HeaderValue, Method};
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::header::HeaderName,
     http::StatusCode,
-    middleware::from_fn,
+    middleware::{from_fn, from_fn_with_state},
     response::IntoResponse,
-    routing::{get, post},
-    Json, Router,
+    routing::{delete, get, patch, post, put},
+    Extension, Json, Router,
 };
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
@@ -20,11 +20,143 @@ use tower_http::cors::CorsLayer;
 use tracing::error;
 use uuid::Uuid;
 
-use crate::auth::signature_auth_middleware;
+use crate::address_book::{create_address, delete_address, list_addresses, verify_address};
+use crate::admin_access::{admin_access_middleware, AdminAccessPolicy};
+use crate::admin_batch::{batch_update_kyc, batch_update_plan_status};
+use crate::auth::{jwt_auth_middleware, signature_auth_middleware, UserContext};
+use crate::backups::{get_backup_status, list_backups};
+use crate::balance_monitor::get_balance_checks;
+use crate::bridge::{
+    get_bridge_transfer, initiate_bridge_transfer, list_bridge_transfers, submit_bridge_attestation,
+};
+use crate::broadcasts::{
+    cancel_broadcast, create_broadcast, get_broadcast, list_broadcasts, preview_broadcast,
+};
 use crate::cache::PlanCache;
+use crate::chain::rpc::SorobanRpcClient;
+use crate::check_in::{get_check_in, override_check_in, record_check_in, update_check_in_settings};
+use crate::claim_delegations::{grant_delegation, list_delegations, revoke_delegation, Claimant};
+use crate::claim_documents::{get_claim_document, list_claim_documents, upload_claim_document};
+use crate::claim_eligibility::get_claim_eligibility;
+use crate::claim_expiry::{get_fallback_beneficiary, set_fallback_beneficiary};
+use crate::claim_fraud::{
+    approve_claim, client_device_middleware, list_review_queue, ClientDevice,
+};
+use crate::claim_portal::{
+    complete_claim_start, invite_beneficiary, link_claim_wallet, revoke_beneficiary_invitation,
+    start_claim,
+};
+use crate::claim_requests::{self, admin_cancel_claim, cancel_claim, get_claim, request_claim};
+use crate::config::Config;
+use crate::consents::{
+    self, get_consent_report, get_my_consents, get_user_consents, list_consent_documents,
+    publish_consent_document, record_my_consents, ConsentKind,
+};
+use crate::cost_breakdown::get_plan_cost_breakdown;
+use crate::data_corrections::{
+    approve_correction, list_corrections, propose_correction, reject_correction,
+};
+use crate::dead_letters::{
+    discard_dead_letter, get_dead_letter, list_dead_letters, requeue_dead_letter,
+};
+use crate::deposit_quarantine::{
+    assign_quarantined_deposit, dismiss_quarantined_deposit, list_quarantined_deposits,
+};
+use crate::deposit_refunds::{
+    approve_deposit_refund, list_deposit_refunds, list_plan_refunds, refund_quarantined_deposit,
+    reject_deposit_refund, request_overpayment_refund,
+};
+use crate::deposits::{get_deposit_address, get_plan_deposits, issue_deposit_address};
+use crate::economics::simulate_economics;
+use crate::email_changes::{
+    cancel_email_change, confirm_email_change, get_email_change, request_email_change,
+};
+use crate::emergency_contacts::{
+    create_contact, delete_contact, list_contacts, update_contact, verify_contact, ContactNotifier,
+};
+use crate::exports::{export_claims_csv, export_plans_csv};
+use crate::feature_flags::{
+    delete_feature_flag, list_feature_flags, update_feature_flag, FeatureFlagCache, FeatureKey,
+    InstallmentPlans,
+};
+use crate::field_crypto::{FieldCipher, SensitiveField};
+use crate::freezes::{freeze_plan, freeze_user, refuse_frozen_plan, unfreeze_plan, unfreeze_user};
+use crate::graphql::graphql_handler;
+use crate::http_audit::{http_audit_middleware, search_http_audit};
+use crate::jobs::{get_job_runs, list_jobs, pause_job, resume_job, trigger_job, JobRegistry};
+use crate::keeper::get_keeper_status;
+use crate::kyc_sync::{get_kyc_sync_drift, get_user_kyc_sync, resync_user_kyc};
+use crate::kyc_tiers::{self, get_my_limits, set_user_kyc_tier};
 use crate::kyc_webhook::kyc_webhook_handler;
+use crate::ledger::{get_ledger_account_history, get_ledger_accounts, get_trial_balance};
+use crate::lending_archive::{list_archives, restore_archive};
+use crate::mailer::Mailer;
 use crate::metrics::{latency_middleware, metrics_handler};
+use crate::money::Money;
+use crate::notification_digest::{get_notification_preferences, update_notification_preferences};
+use crate::notifications::{
+    get_notification, list_deliveries, list_notifications, mark_all_notifications_read,
+    mark_notification_read, mark_notifications_read, retry_delivery, unread_notification_count,
+};
+use crate::offramp::{list_withdrawals, start_withdrawal, AnchorClient};
+use crate::organization_members::{
+    accept_invitation, invite_admin, list_invitations, remove_organization_admin,
+    revoke_invitation, set_organization_admin, transfer_ownership,
+};
+use crate::organizations::{
+    assign_organization_user, create_organization, get_organization, get_organization_branding,
+    list_organizations, organization_scope_middleware, remove_organization_user,
+    update_organization,
+};
+use crate::pagination::{split_page, with_next_cursor, Cursor, NEXT_CURSOR_HEADER};
+use crate::payout_conversion::{
+    self, clear_payout_asset, get_payout_asset, set_payout_asset, PayoutConversion,
+};
+use crate::payout_destinations::{self, get_payout_destinations, replace_payout_destinations};
+use crate::pending_changes::{
+    approve_change, list_pending_changes, list_settings, propose_change, reject_change,
+};
+use crate::plan_events::admin_get_plan_events;
+use crate::plan_history::{
+    admin_get_plan_as_of, admin_get_plan_history, get_plan_as_of, get_plan_history,
+};
+use crate::plan_metadata::{verify_plan_metadata, verify_submitted_plan_metadata};
+use crate::plan_owners::{
+    self, accept_co_ownership, amend_plan, approve_plan_change, decline_co_ownership,
+    invite_co_owner, list_approvals, list_co_owners, ping_co_owner, reject_plan_change,
+};
+use crate::plan_tags::{
+    self, add_plan_tags, delete_plan_filter, get_my_plan_tags, list_plan_filters, remove_plan_tag,
+    save_plan_filter,
+};
+use crate::plan_validation::validate_plan;
+use crate::projection::get_plan_projection;
+use crate::read_models::{get_fees_collected_daily, get_plans_by_status_daily};
+use crate::reports::{
+    create_report, delete_report, list_report_runs, list_reports, run_report_now, update_report,
+};
+use crate::security_events::get_my_security_events;
+use crate::sep10::{get_challenge, get_stellar_toml, post_challenge, step_up};
+use crate::simulation::simulate_contract_call;
+use crate::status_page::{
+    create_status_incident, get_status, list_status_incidents, update_status_incident, StatusPage,
+};
 use crate::stellar_anchor::AnchorRegistry;
+use crate::system_settings::{
+    list_system_settings, reset_system_setting, update_system_setting, SystemSettingsCache,
+};
+use crate::tax_documents::{download_tax_document, generate_tax_documents, get_my_tax_documents};
+use crate::telemetry;
+use crate::templates::{
+    delete_template, list_template_versions, list_templates, preview_template,
+    restore_template_version, upsert_template,
+};
+use crate::trustlines::get_payout_readiness;
+use crate::user_profiles::{get_profile, update_profile};
+use crate::wallet_reauth::{
+    self, create_challenge, update_reauth_settings, ReauthAction, WalletConfirmation,
+};
+use crate::witnesses::{get_plan_witnesses, set_plan_witnesses, submit_attestation};
 use crate::ws::{ws_handler, KycUpdateEvent};
 use crate::yield_calculator;
 
@@ -40,28 +172,65 @@ pub struct PlanBeneficiary {
 pub struct Plan {
     pub owner: String,
     pub token: String,
-    pub amount: f64,
+    pub amount: Decimal,
     pub beneficiaries: Vec<PlanBeneficiary>,
     pub last_ping: i64,
     pub grace_period: u64,
     pub earn_yield: bool,
     pub yield_rate_bps: u32,
     pub is_active: bool,
+    /// Number of equal installments the claim is paid in; 1 is a lump sum.
+    #[serde(default = "default_installment_count")]
+    pub installment_count: u32,
+    #[serde(default = "default_installment_interval_days")]
+    pub installment_interval_days: u32,
+}
+
+fn default_installment_count() -> u32 {
+    1
+}
+
+fn default_installment_interval_days() -> u32 {
+    30
 }
 
 pub struct AppState {
     pub anchor: Arc<AnchorRegistry>,
     pub db_pool: sqlx::PgPool,
     pub kyc_tx: tokio::sync::broadcast::Sender<KycUpdateEvent>,
-    pub kyc_webhook_secret: Option<String>,
+    pub config: Arc<Config>,
     pub apy_config: yield_calculator::ApyConfig,
     pub plan_cache: PlanCache,
+    pub offramp: Arc<AnchorClient>,
+    pub contacts: Arc<ContactNotifier>,
+    pub mailer: Arc<Mailer>,
+    pub soroban_rpc: Arc<SorobanRpcClient>,
+    pub admin_access: Arc<AdminAccessPolicy>,
+    pub field_cipher: Arc<FieldCipher>,
+    pub system_settings: Arc<SystemSettingsCache>,
+    pub feature_flags: Arc<FeatureFlagCache>,
+    pub jobs: Arc<JobRegistry>,
+    pub status_page: Arc<StatusPage>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PlanQuery {
     pub owner: Option<String>,
     pub beneficiary: Option<String>,
+    /// Comma-separated tags; only plans with every one are listed.
+    pub tag: Option<String>,
+}
+
+const DEFAULT_PLAN_PAGE_SIZE: i64 = 50;
+const MAX_PLAN_PAGE_SIZE: i64 = 200;
+
+/// Opt-in paging of `GET /api/plans`. Without either field every matching
+/// plan is returned, as before.
+#[derive(Debug, Deserialize)]
+pub struct PlanPageQuery {
+    pub limit: Option<i64>,
+    /// `X-Next-Cursor` of the previous page.
+    pub cursor: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -81,6 +250,15 @@ pub struct PingResponse {
 #[derive(Deserialize)]
 pub struct PayoutRequest {
     pub owner: String,
+    /// Required when the caller has wallet re-auth enabled.
+    #[serde(default)]
+    pub confirmation: Option<WalletConfirmation>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct DeactivatePlanRequest {
+    #[serde(default)]
+    pub confirmation: Option<WalletConfirmation>,
 }
 
 #[derive(Deserialize)]
@@ -90,20 +268,63 @@ pub struct AnchorQuery {
     pub page_size: Option<i64>,
 }
 
-#[derive(Debug, Serialize, sqlx::FromRow)]
+#[derive(Debug, sqlx::FromRow)]
 pub struct PayoutRow {
     pub id: Uuid,
     pub plan_id: Uuid,
     pub beneficiary_address: String,
-    pub amount: String,
+    pub destination_address: Option<String>,
+    pub amount: Decimal,
+    /// The plan's token, which `amount` is in.
+    pub token_address: String,
+    pub payout_asset: Option<String>,
+    pub converted_amount: Option<Decimal>,
+    pub payout_type: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PayoutResponse {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub beneficiary_address: String,
+    /// Wallet paid when the beneficiary split their payout; `None` pays
+    /// `beneficiary_address`.
+    pub destination_address: Option<String>,
+    pub amount: Money,
+    /// Token the payout is converted to; `None` pays the plan's token.
+    pub payout_asset: Option<String>,
+    pub converted_amount: Option<Money>,
     pub payout_type: String,
     pub status: String,
     pub created_at: DateTime<Utc>,
 }
 
+impl From<PayoutRow> for PayoutResponse {
+    fn from(row: PayoutRow) -> Self {
+        let converted_amount = row
+            .converted_amount
+            .zip(row.payout_asset.clone())
+            .map(|(amount, asset)| Money::new(amount, asset));
+        Self {
+            id: row.id,
+            plan_id: row.plan_id,
+            beneficiary_address: row.beneficiary_address,
+            destination_address: row.destination_address,
+            amount: Money::new(row.amount, row.token_address),
+            payout_asset: row.payout_asset,
+            converted_amount,
+            payout_type: row.payout_type,
+            status: row.status,
+            created_at: row.created_at,
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct PayoutStatusResponse {
-    pub data: Vec<PayoutRow>,
+    pub data: Vec<PayoutResponse>,
     pub page: i64,
     pub page_size: i64,
     pub total: i64,
@@ -122,39 +343,479 @@ pub fn create_router(state: Arc<AppState>) -> Router {
                 .parse::<HeaderValue>()
                 .unwrap(),
         )
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
         .allow_headers([
             axum::http::header::CONTENT_TYPE,
             axum::http::header::AUTHORIZATION,
+            telemetry::REQUEST_ID_HEADER,
+        ])
+        .expose_headers([
+            telemetry::REQUEST_ID_HEADER,
+            HeaderName::from_static(NEXT_CURSOR_HEADER),
         ])
         .max_age(std::time::Duration::from_secs(3600));
 
-    // Rate limiter: 100 requests per IP per 60 seconds
+    // Rate limiter: 100 requests per IP per 60 seconds unless overridden
     let store = RateLimitStore::new();
-    let config = Arc::new(RateLimitConfig::default());
+    let config = Arc::new(RateLimitConfig::from_env());
 
     // User routes requiring signature verification
     let user_routes = Router::new()
         .route("/api/plans", post(create_plan))
+        .route("/api/plans/validate", post(validate_plan))
         .route("/api/plans/ping", post(ping_plan))
         .route("/api/plans/payout", post(trigger_payout))
-        .route_layer(from_fn(signature_auth_middleware));
+        .route("/api/plans/{id}/deactivate", post(deactivate_plan))
+        .route("/api/plans/{id}/amendments", post(amend_plan))
+        .route(
+            "/api/plans/{id}/co-owners",
+            get(list_co_owners).post(invite_co_owner),
+        )
+        .route(
+            "/api/plans/{id}/co-owners/accept",
+            post(accept_co_ownership),
+        )
+        .route(
+            "/api/plans/{id}/co-owners/decline",
+            post(decline_co_ownership),
+        )
+        .route("/api/plans/{id}/co-owners/ping", post(ping_co_owner))
+        .route("/api/plans/{id}/approvals", get(list_approvals))
+        .route("/api/plans/{id}/tags", post(add_plan_tags))
+        .route("/api/plans/{id}/tags/{tag}", delete(remove_plan_tag))
+        .route(
+            "/api/plans/{id}/witnesses",
+            get(get_plan_witnesses).put(set_plan_witnesses),
+        )
+        .route(
+            "/api/plans/{id}/approvals/{approval_id}/approve",
+            post(approve_plan_change),
+        )
+        .route(
+            "/api/plans/{id}/approvals/{approval_id}/reject",
+            post(reject_plan_change),
+        )
+        .route("/api/reauth/challenges", post(create_challenge))
+        .route("/api/auth/step-up", post(step_up))
+        .route("/api/chain/simulate", post(simulate_contract_call))
+        .route("/api/users/me", get(get_profile).patch(update_profile))
+        .route("/api/users/me/wallet-reauth", put(update_reauth_settings))
+        .route("/api/users/me/plans/export.csv", get(export_plans_csv))
+        .route("/api/users/me/claims/export.csv", get(export_claims_csv))
+        .route("/api/users/me/limits", get(get_my_limits))
+        .route("/api/users/me/tax-documents", get(get_my_tax_documents))
+        .route("/api/users/me/plan-tags", get(get_my_plan_tags))
+        .route(
+            "/api/users/me/plan-filters",
+            get(list_plan_filters).post(save_plan_filter),
+        )
+        .route(
+            "/api/users/me/plan-filters/{id}",
+            delete(delete_plan_filter),
+        )
+        .route("/api/users/me/security-events", get(get_my_security_events))
+        .route(
+            "/api/users/me/consents",
+            get(get_my_consents).post(record_my_consents),
+        )
+        .route("/api/kyc/submit", post(submit_kyc))
+        .route("/api/kyc/upload", post(upload_kyc_document))
+        .route(
+            "/api/users/me/payout-destinations",
+            get(get_payout_destinations).put(replace_payout_destinations),
+        )
+        .route(
+            "/api/users/me/payout-asset",
+            get(get_payout_asset)
+                .put(set_payout_asset)
+                .delete(clear_payout_asset),
+        )
+        .route(
+            "/api/address-book",
+            get(list_addresses).post(create_address),
+        )
+        .route("/api/address-book/{id}", delete(delete_address))
+        .route("/api/address-book/{id}/verify", post(verify_address))
+        .route(
+            "/api/offramp/withdrawals",
+            get(list_withdrawals).post(start_withdrawal),
+        )
+        .route("/api/notifications", get(list_notifications))
+        .route(
+            "/api/notifications/unread-count",
+            get(unread_notification_count),
+        )
+        .route(
+            "/api/notifications/mark-all-read",
+            post(mark_all_notifications_read),
+        )
+        .route(
+            "/api/notifications/mark-read",
+            post(mark_notifications_read),
+        )
+        .route("/api/notifications/{id}", get(get_notification))
+        .route("/api/notifications/{id}/read", post(mark_notification_read))
+        .route(
+            "/api/users/me/notification-preferences",
+            get(get_notification_preferences).put(update_notification_preferences),
+        )
+        .route(
+            "/api/users/me/email-change",
+            get(get_email_change)
+                .post(request_email_change)
+                .delete(cancel_email_change),
+        )
+        .route(
+            "/api/bridge/transfers",
+            get(list_bridge_transfers).post(initiate_bridge_transfer),
+        )
+        .route("/api/bridge/transfers/{id}", get(get_bridge_transfer))
+        .route(
+            "/api/users/me/check-in",
+            get(get_check_in).post(record_check_in),
+        )
+        .route(
+            "/api/users/me/check-in/settings",
+            put(update_check_in_settings),
+        )
+        .route(
+            "/api/emergency-contacts",
+            get(list_contacts).post(create_contact),
+        )
+        .route(
+            "/api/emergency-contacts/{id}",
+            put(update_contact).delete(delete_contact),
+        )
+        .route("/api/emergency-contacts/{id}/verify", post(verify_contact))
+        .route(
+            "/api/plans/{id}/claim-eligibility",
+            get(get_claim_eligibility),
+        )
+        .route("/api/plans/{id}/claim", get(get_claim).post(request_claim))
+        .route("/api/plans/{id}/claim/cancel", post(cancel_claim))
+        .route(
+            "/api/plans/{id}/claim/documents",
+            get(list_claim_documents).post(upload_claim_document),
+        )
+        .route(
+            "/api/plans/{id}/claim/documents/{document_id}",
+            get(get_claim_document),
+        )
+        .route(
+            "/api/plans/{id}/delegations",
+            get(list_delegations).post(grant_delegation),
+        )
+        .route(
+            "/api/plans/{id}/delegations/{delegation_id}",
+            delete(revoke_delegation),
+        )
+        .route(
+            "/api/plans/{id}/fallback-beneficiary",
+            get(get_fallback_beneficiary).put(set_fallback_beneficiary),
+        )
+        .route(
+            "/api/plans/{id}/beneficiaries/{beneficiary_id}/claim-invitation",
+            post(invite_beneficiary).delete(revoke_beneficiary_invitation),
+        )
+        .route("/api/plans/{id}/deposits", get(get_plan_deposits))
+        .route(
+            "/api/plans/{id}/deposit-address",
+            get(get_deposit_address).post(issue_deposit_address),
+        )
+        .route(
+            "/api/plans/{id}/refunds",
+            get(list_plan_refunds).post(request_overpayment_refund),
+        )
+        .route(
+            "/api/plans/{id}/payout-readiness",
+            get(get_payout_readiness),
+        )
+        .route("/api/plans/{id}/history", get(get_plan_history))
+        .route("/api/plans/{id}/as-of", get(get_plan_as_of))
+        .route("/api/graphql", post(graphql_handler))
+        .route_layer(from_fn_with_state(state.clone(), client_device_middleware))
+        .route_layer(from_fn_with_state(state.clone(), http_audit_middleware))
+        .route_layer(from_fn_with_state(state.clone(), signature_auth_middleware));
+
+    // Admin routes requiring an admin JWT
+    let admin_routes = Router::new()
+        .route("/api/admin/kyc/batch", post(batch_update_kyc))
+        .route("/api/admin/settings", get(list_settings))
+        .route(
+            "/api/admin/pending-changes",
+            get(list_pending_changes).post(propose_change),
+        )
+        .route(
+            "/api/admin/pending-changes/{id}/approve",
+            post(approve_change),
+        )
+        .route(
+            "/api/admin/pending-changes/{id}/reject",
+            post(reject_change),
+        )
+        .route(
+            "/api/admin/corrections",
+            get(list_corrections).post(propose_correction),
+        )
+        .route(
+            "/api/admin/corrections/{id}/approve",
+            post(approve_correction),
+        )
+        .route(
+            "/api/admin/corrections/{id}/reject",
+            post(reject_correction),
+        )
+        .route(
+            "/api/admin/plans/batch-status",
+            post(batch_update_plan_status),
+        )
+        .route("/api/admin/claims/review", get(list_review_queue))
+        .route("/api/admin/claims/{id}/approve", post(approve_claim))
+        .route("/api/admin/claims/{id}/cancel", post(admin_cancel_claim))
+        .route("/api/admin/plans/{id}/history", get(admin_get_plan_history))
+        .route("/api/admin/plans/{id}/events", get(admin_get_plan_events))
+        .route("/api/admin/plans/{id}/as-of", get(admin_get_plan_as_of))
+        .route(
+            "/api/admin/plans/{id}/metadata",
+            get(verify_plan_metadata).post(verify_submitted_plan_metadata),
+        )
+        .route("/api/admin/reports", get(list_reports).post(create_report))
+        .route(
+            "/api/admin/reports/{id}",
+            put(update_report).delete(delete_report),
+        )
+        .route("/api/admin/reports/{id}/run", post(run_report_now))
+        .route("/api/admin/reports/{id}/runs", get(list_report_runs))
+        .route(
+            "/api/admin/broadcasts",
+            get(list_broadcasts).post(create_broadcast),
+        )
+        .route("/api/admin/broadcasts/preview", post(preview_broadcast))
+        .route("/api/admin/broadcasts/{id}", get(get_broadcast))
+        .route("/api/admin/broadcasts/{id}/cancel", post(cancel_broadcast))
+        .route("/api/admin/lending-archives", get(list_archives))
+        .route(
+            "/api/admin/dashboard/plans-by-status",
+            get(get_plans_by_status_daily),
+        )
+        .route(
+            "/api/admin/dashboard/fees-collected",
+            get(get_fees_collected_daily),
+        )
+        .route(
+            "/api/admin/lending-archives/{id}/restore",
+            post(restore_archive),
+        )
+        .route(
+            "/api/admin/check-ins/{address}/override",
+            post(override_check_in),
+        )
+        .route("/api/admin/notification-deliveries", get(list_deliveries))
+        .route(
+            "/api/admin/notification-deliveries/{id}/retry",
+            post(retry_delivery),
+        )
+        .route("/api/admin/system-settings", get(list_system_settings))
+        .route(
+            "/api/admin/system-settings/{key}",
+            put(update_system_setting).delete(reset_system_setting),
+        )
+        .route("/api/admin/feature-flags", get(list_feature_flags))
+        .route(
+            "/api/admin/feature-flags/{key}",
+            put(update_feature_flag).delete(delete_feature_flag),
+        )
+        .route("/api/admin/notification-templates", get(list_templates))
+        .route(
+            "/api/admin/notification-templates/{key}/{language}",
+            put(upsert_template).delete(delete_template),
+        )
+        .route(
+            "/api/admin/notification-templates/{key}/{language}/versions",
+            get(list_template_versions),
+        )
+        .route(
+            "/api/admin/notification-templates/{key}/{language}/versions/{version}/restore",
+            post(restore_template_version),
+        )
+        .route(
+            "/api/admin/notification-templates/{key}/{language}/preview",
+            post(preview_template),
+        )
+        .route("/api/admin/dead-letters", get(list_dead_letters))
+        .route("/api/admin/dead-letters/{id}", get(get_dead_letter))
+        .route(
+            "/api/admin/dead-letters/{id}/requeue",
+            post(requeue_dead_letter),
+        )
+        .route(
+            "/api/admin/dead-letters/{id}/discard",
+            post(discard_dead_letter),
+        )
+        .route("/api/admin/keeper", get(get_keeper_status))
+        .route("/api/admin/users/{id}/freeze", post(freeze_user))
+        .route("/api/admin/users/{id}/unfreeze", post(unfreeze_user))
+        .route("/api/admin/users/{id}/kyc-tier", put(set_user_kyc_tier))
+        .route(
+            "/api/admin/users/{id}/kyc-sync",
+            get(get_user_kyc_sync).post(resync_user_kyc),
+        )
+        .route("/api/admin/dashboard/kyc-sync", get(get_kyc_sync_drift))
+        .route("/api/admin/backups", get(list_backups))
+        .route("/api/admin/backups/status", get(get_backup_status))
+        .route("/api/admin/balance-checks", get(get_balance_checks))
+        .route("/api/admin/simulate/economics", post(simulate_economics))
+        .route("/api/admin/ledger/accounts", get(get_ledger_accounts))
+        .route(
+            "/api/admin/ledger/accounts/{id}/history",
+            get(get_ledger_account_history),
+        )
+        .route("/api/admin/ledger/trial-balance", get(get_trial_balance))
+        .route(
+            "/api/admin/deposits/quarantine",
+            get(list_quarantined_deposits),
+        )
+        .route(
+            "/api/admin/deposits/quarantine/{id}/assign",
+            post(assign_quarantined_deposit),
+        )
+        .route(
+            "/api/admin/deposits/quarantine/{id}/dismiss",
+            post(dismiss_quarantined_deposit),
+        )
+        .route(
+            "/api/admin/deposits/quarantine/{id}/refund",
+            post(refund_quarantined_deposit),
+        )
+        .route("/api/admin/refunds", get(list_deposit_refunds))
+        .route(
+            "/api/admin/refunds/{id}/approve",
+            post(approve_deposit_refund),
+        )
+        .route(
+            "/api/admin/refunds/{id}/reject",
+            post(reject_deposit_refund),
+        )
+        .route(
+            "/api/admin/status/incidents",
+            get(list_status_incidents).post(create_status_incident),
+        )
+        .route(
+            "/api/admin/status/incidents/{id}",
+            patch(update_status_incident),
+        )
+        .route(
+            "/api/admin/tax-documents/generate",
+            post(generate_tax_documents),
+        )
+        .route("/api/admin/users/{id}/consents", get(get_user_consents))
+        .route(
+            "/api/admin/consent-documents",
+            post(publish_consent_document),
+        )
+        .route("/api/admin/consents/report", get(get_consent_report))
+        .route("/api/admin/plans/{id}/freeze", post(freeze_plan))
+        .route("/api/admin/plans/{id}/unfreeze", post(unfreeze_plan))
+        .route("/api/admin/http-audit", get(search_http_audit))
+        .route("/api/admin/jobs", get(list_jobs))
+        .route("/api/admin/jobs/{name}/runs", get(get_job_runs))
+        .route("/api/admin/jobs/{name}/trigger", post(trigger_job))
+        .route("/api/admin/jobs/{name}/pause", post(pause_job))
+        .route("/api/admin/jobs/{name}/resume", post(resume_job))
+        .route("/api/admin/graphql", post(graphql_handler))
+        .route(
+            "/api/admin/organizations",
+            get(list_organizations).post(create_organization),
+        )
+        .route(
+            "/api/admin/organizations/{id}",
+            get(get_organization).patch(update_organization),
+        )
+        .route(
+            "/api/admin/organizations/{id}/admins/{admin_id}",
+            put(set_organization_admin).delete(remove_organization_admin),
+        )
+        .route(
+            "/api/admin/organizations/{id}/transfer-ownership",
+            post(transfer_ownership),
+        )
+        .route(
+            "/api/admin/organizations/{id}/invitations",
+            get(list_invitations).post(invite_admin),
+        )
+        .route(
+            "/api/admin/organizations/{id}/invitations/{invitation_id}",
+            delete(revoke_invitation),
+        )
+        .route(
+            "/api/admin/organizations/{id}/users/{user}",
+            put(assign_organization_user).delete(remove_organization_user),
+        )
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            organization_scope_middleware,
+        ))
+        .route_layer(from_fn_with_state(state.clone(), http_audit_middleware))
+        .route_layer(from_fn_with_state(state.clone(), jwt_auth_middleware))
+        .route_layer(from_fn_with_state(state.clone(), admin_access_middleware));
 
     // Public or admin routes
     let public_routes = Router::new()
+        .route("/auth", get(get_challenge).post(post_challenge))
+        .route("/.well-known/stellar.toml", get(get_stellar_toml))
         .route("/api/plans", get(get_plans))
+        .route("/api/plans/{id}/projection", get(get_plan_projection))
+        .route(
+            "/api/plans/{id}/cost-breakdown",
+            get(get_plan_cost_breakdown),
+        )
         .route("/api/anchor/payout-status", get(get_anchor_payouts))
         .route("/api/kyc/webhook", post(kyc_webhook_handler))
+        .route("/api/email-change/confirm", post(confirm_email_change))
+        .route("/api/claims/start/{token}", get(start_claim))
+        .route("/api/claims/start/{token}/wallet", post(link_claim_wallet))
+        .route(
+            "/api/claims/start/{token}/complete",
+            post(complete_claim_start),
+        )
+        .route("/api/bridge/attestations", post(submit_bridge_attestation))
+        .route("/api/plans/{id}/attestations", post(submit_attestation))
+        .route(
+            "/api/tax-documents/{id}/download",
+            get(download_tax_document),
+        )
+        .route("/api/organizations/{slug}", get(get_organization_branding))
+        .route(
+            "/api/organization-invitations/accept",
+            post(accept_invitation),
+        )
         .route("/api/kyc/status", get(get_kyc_status))
-        .route("/api/kyc/submit", post(submit_kyc))
-        .route("/api/kyc/upload", post(upload_kyc_document))
+        .route("/api/consent-documents", get(list_consent_documents))
         .route("/api/kyc/required", get(is_kyc_required))
         .route("/api/kyc/requirements", get(get_kyc_requirements))
-        .route("/ws/kyc", get(ws_handler));
+        .route("/ws/kyc", get(ws_handler))
+        .route_layer(from_fn_with_state(state.clone(), http_audit_middleware));
+
+    // Public status page, polled by monitors, with its own tighter limit
+    let (status_store, status_config) = state.status_page.limiter();
+    let status_routes = Router::new()
+        .route("/api/status", get(get_status))
+        .route_layer(axum::middleware::from_fn(move |req, next| {
+            rate_limit_middleware(req, next, status_store.clone(), status_config.clone())
+        }));
 
     Router::new()
         .merge(user_routes)
+        .merge(admin_routes)
         .merge(public_routes)
+        .merge(status_routes)
         .layer(axum::middleware::from_fn(move |req, next| {
             rate_limit_middleware(req, next, store.clone(), config.clone())
         }))
@@ -166,6 +827,7 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/metrics", get(metrics_handler))
         .layer(from_fn(latency_middleware))
         .layer(cors)
+        .layer(from_fn(telemetry::correlation_middleware))
         .with_state(state)
 }
 
@@ -200,7 +862,7 @@ pub struct PlanResponse {
     pub id: uuid::Uuid,
     pub owner_address: String,
     pub token_address: String,
-    pub amount: rust_decimal::Decimal,
+    pub amount: Money,
     pub grace_period: i64,
     pub grace_period_seconds: i64,
     pub earn_yield: bool,
@@ -208,9 +870,14 @@ pub struct PlanResponse {
     pub is_active: bool,
     pub status: String,
     pub yield_rate_bps: i32,
-    pub accrued_yield: f64,
+    pub accrued_yield: Money,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub beneficiaries: Vec<BeneficiaryResponse>,
+    /// Accepted co-owners of a joint plan, besides `owner_address`.
+    #[serde(default)]
+    pub co_owners: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -223,9 +890,9 @@ pub struct BeneficiaryResponse {
 }
 
 /// Compute the accrued yield for a plan based on elapsed time since last_ping.
-fn compute_accrued_yield(amount: &Decimal, yield_rate_bps: i32, last_ping: i64) -> f64 {
+fn compute_accrued_yield(amount: Decimal, yield_rate_bps: i32, last_ping: i64) -> Option<Decimal> {
     if yield_rate_bps == 0 || last_ping == 0 {
-        return 0.0;
+        return Some(Decimal::ZERO);
     }
 
     let now = std::time::SystemTime::now()
@@ -234,27 +901,28 @@ fn compute_accrued_yield(amount: &Decimal, yield_rate_bps: i32, last_ping: i64)
         .as_secs() as i64;
 
     let elapsed_secs = (now - last_ping).max(0) as u64;
-    let amount_f64 = amount.to_string().parse::<f64>().unwrap_or(0.0);
-
-    yield_calculator::calculate_yield(amount_f64, yield_rate_bps as u32, elapsed_secs)
+    yield_calculator::calculate_yield(amount, yield_rate_bps.max(0) as u32, elapsed_secs)
 }
 
-fn compute_projected_accrued_yield(row: &PlanRow) -> f64 {
-    let persisted = row.accrued_yield.to_string().parse::<f64>().unwrap_or(0.0);
-
+/// Persisted yield plus yield accrued since the last ping, or `None` if
+/// the accrual overflows.
+pub(crate) fn compute_projected_accrued_yield(row: &PlanRow) -> Option<Decimal> {
     if !row.earn_yield {
-        return persisted;
+        return Some(row.accrued_yield);
     }
 
-    persisted + compute_accrued_yield(&row.amount, row.yield_rate_bps, row.last_ping)
+    compute_accrued_yield(row.amount, row.yield_rate_bps, row.last_ping)?
+        .checked_add(row.accrued_yield)
 }
 
-/// Load beneficiaries for a given plan.
-async fn load_beneficiaries(
+/// Shown instead of fiat payout details outside the plan owner's views.
+const REDACTED_ANCHOR_INFO: &str = "[redacted]";
+
+async fn beneficiary_rows(
     pool: &sqlx::PgPool,
     plan_id: uuid::Uuid,
-) -> Result<Vec<BeneficiaryResponse>, sqlx::Error> {
-    let rows = sqlx::query_as::<_, BeneficiaryRow>(
+) -> Result<Vec<BeneficiaryRow>, sqlx::Error> {
+    sqlx::query_as::<_, BeneficiaryRow>(
         r#"
         SELECT id, plan_id, wallet_address, allocation_bps, fiat_anchor_info
         FROM beneficiaries
@@ -263,29 +931,67 @@ async fn load_beneficiaries(
     )
     .bind(plan_id)
     .fetch_all(pool)
-    .await?;
+    .await
+}
+
+/// Load beneficiaries for a given plan.
+pub(crate) async fn load_beneficiaries(
+    pool: &sqlx::PgPool,
+    cipher: &FieldCipher,
+    plan_id: uuid::Uuid,
+) -> Result<Vec<BeneficiaryResponse>, sqlx::Error> {
+    beneficiary_rows(pool, plan_id)
+        .await?
+        .into_iter()
+        .map(|r| {
+            Ok(BeneficiaryResponse {
+                id: r.id,
+                plan_id: r.plan_id,
+                wallet_address: r.wallet_address,
+                allocation_bps: r.allocation_bps,
+                fiat_anchor_info: cipher
+                    .decrypt_column(SensitiveField::BeneficiaryAnchorInfo, &r.fiat_anchor_info)?,
+            })
+        })
+        .collect()
+}
 
-    Ok(rows
+/// Load beneficiaries for the public plan listing. Fiat payout details are
+/// never decrypted there; a fiat beneficiary shows [`REDACTED_ANCHOR_INFO`].
+async fn load_public_beneficiaries(
+    pool: &sqlx::PgPool,
+    plan_id: uuid::Uuid,
+) -> Result<Vec<BeneficiaryResponse>, sqlx::Error> {
+    Ok(beneficiary_rows(pool, plan_id)
+        .await?
         .into_iter()
         .map(|r| BeneficiaryResponse {
             id: r.id,
             plan_id: r.plan_id,
             wallet_address: r.wallet_address,
             allocation_bps: r.allocation_bps,
-            fiat_anchor_info: r.fiat_anchor_info,
+            fiat_anchor_info: if r.fiat_anchor_info.is_empty() {
+                String::new()
+            } else {
+                REDACTED_ANCHOR_INFO.to_string()
+            },
         })
         .collect())
 }
 
 // Helper: convert PlanRow + beneficiaries into PlanResponse with yield
-fn plan_row_to_response(row: PlanRow, beneficiaries: Vec<BeneficiaryResponse>) -> PlanResponse {
-    let accrued_yield = compute_projected_accrued_yield(&row);
+pub(crate) fn plan_row_to_response(
+    row: PlanRow,
+    beneficiaries: Vec<BeneficiaryResponse>,
+) -> PlanResponse {
+    let accrued_yield = compute_projected_accrued_yield(&row).unwrap_or(row.accrued_yield);
+    let accrued_yield = Money::new(accrued_yield, &row.token_address);
 
     PlanResponse {
         id: row.id,
         owner_address: row.owner_address,
+        amount: Money::new(row.amount, &row.token_address),
         token_address: row.token_address,
-        amount: row.amount,
         grace_period: row.grace_period,
         grace_period_seconds: row.grace_period_seconds,
         earn_yield: row.earn_yield,
@@ -296,6 +1002,8 @@ fn plan_row_to_response(row: PlanRow, beneficiaries: Vec<BeneficiaryResponse>) -
         accrued_yield,
         created_at: row.created_at,
         beneficiaries,
+        co_owners: Vec::new(),
+        tags: Vec::new(),
     }
 }
 
@@ -359,7 +1067,7 @@ fn add_plan_cache_headers(
     }
 }
 
-async fn invalidate_plan_cache(
+pub(crate) async fn invalidate_plan_cache(
     cache: &PlanCache,
     owner_address: &str,
     beneficiary_addresses: &[String],
@@ -397,7 +1105,7 @@ async fn create_plan(
         )
             .into_response();
     }
-    if payload.amount < 0.0 {
+    if payload.amount < Decimal::ZERO {
         return (
             StatusCode::BAD_REQUEST,
             Json(serde_json::json!({ "error": "Amount must be non-negative" })),
@@ -418,6 +1126,27 @@ async fn create_plan(
         )
             .into_response();
     }
+    if !(1..=600).contains(&payload.installment_count) || payload.installment_interval_days == 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "installment_count must be between 1 and 600 and installment_interval_days greater than zero"
+            })),
+        )
+            .into_response();
+    }
+    if payload.installment_count > 1
+        && !state
+            .feature_flags
+            .is_enabled(InstallmentPlans::KEY, Some(payload.owner.as_str()))
+            .await
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Installment payouts are not available yet" })),
+        )
+            .into_response();
+    }
     let mut total_bps = 0;
     for b in &payload.beneficiaries {
         if b.address.trim().is_empty() {
@@ -444,17 +1173,7 @@ async fn create_plan(
         ).into_response();
     }
 
-    // Convert amount to rust_decimal::Decimal
-    let amount_dec = match rust_decimal::Decimal::from_f64_retain(payload.amount) {
-        Some(d) => d.normalize(),
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "Invalid amount representation" })),
-            )
-                .into_response()
-        }
-    };
+    let amount_dec = payload.amount.normalize();
 
     // 2. Transaction Execution
     let mut tx = match state.db_pool.begin().await {
@@ -465,6 +1184,20 @@ async fn create_plan(
         ).into_response(),
     };
 
+    let settings = state.system_settings.get().await;
+    match kyc_tiers::check_plan_amount(&mut tx, &settings, &payload.owner, amount_dec).await {
+        Ok(Ok(())) => {}
+        Ok(Err(exceeded)) => return exceeded.into_response(),
+        Err(e) => {
+            error!(owner = %payload.owner, error = %e, "Failed to check KYC tier limit");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    }
+
     let plan_row = match sqlx::query_as::<_, PlanRow>(
         r#"
         INSERT INTO plans (
@@ -478,8 +1211,14 @@ async fn create_plan(
             accrued_yield,
             last_ping,
             is_active,
-            status
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            status,
+            installment_count,
+            installment_interval_days,
+            organization_id
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
+            (SELECT organization_id FROM users WHERE wallet_address = $1)
+        )
         RETURNING id, owner_address, token_address, amount, grace_period, grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, accrued_yield, created_at
         "#
     )
@@ -494,6 +1233,8 @@ async fn create_plan(
     .bind(payload.last_ping)
     .bind(payload.is_active)
     .bind("ACTIVE")
+    .bind(payload.installment_count as i32)
+    .bind(payload.installment_interval_days as i32)
     .fetch_one(&mut *tx)
     .await {
         Ok(row) => row,
@@ -507,6 +1248,20 @@ async fn create_plan(
 
     let mut inserted_beneficiaries = Vec::new();
     for b in &payload.beneficiaries {
+        let stored_anchor_info = match state
+            .field_cipher
+            .encrypt(SensitiveField::BeneficiaryAnchorInfo, &b.fiat_anchor_info)
+        {
+            Ok(value) => value,
+            Err(e) => {
+                error!(error = %e, "Failed to encrypt beneficiary anchor info");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": "Failed to save beneficiary" })),
+                )
+                    .into_response();
+            }
+        };
         let beneficiary_row = match sqlx::query_as::<_, BeneficiaryRow>(
             r#"
             INSERT INTO beneficiaries (
@@ -521,7 +1276,7 @@ async fn create_plan(
         .bind(plan_row.id)
         .bind(&b.address)
         .bind(b.allocation_bps as i32)
-        .bind(&b.fiat_anchor_info)
+        .bind(&stored_anchor_info)
         .fetch_one(&mut *tx)
         .await
         {
@@ -539,7 +1294,7 @@ async fn create_plan(
             plan_id: beneficiary_row.plan_id,
             wallet_address: beneficiary_row.wallet_address,
             allocation_bps: beneficiary_row.allocation_bps,
-            fiat_anchor_info: beneficiary_row.fiat_anchor_info,
+            fiat_anchor_info: b.fiat_anchor_info.clone(),
         });
     }
 
@@ -559,8 +1314,9 @@ async fn create_plan(
     let response = PlanResponse {
         id: plan_row.id,
         owner_address: plan_row.owner_address,
+        amount: Money::new(plan_row.amount, &plan_row.token_address),
+        accrued_yield: Money::zero(&plan_row.token_address), // No yield accrued at creation
         token_address: plan_row.token_address,
-        amount: plan_row.amount,
         grace_period: plan_row.grace_period,
         grace_period_seconds: plan_row.grace_period_seconds,
         earn_yield: plan_row.earn_yield,
@@ -568,9 +1324,10 @@ async fn create_plan(
         is_active: plan_row.is_active,
         status: plan_row.status,
         yield_rate_bps: plan_row.yield_rate_bps,
-        accrued_yield: 0.0, // No yield accrued at creation
         created_at: plan_row.created_at,
         beneficiaries: inserted_beneficiaries,
+        co_owners: Vec::new(),
+        tags: Vec::new(),
     };
 
     (StatusCode::CREATED, Json(response)).into_response()
@@ -581,16 +1338,48 @@ async fn create_plan(
 async fn get_plans(
     State(state): State<Arc<AppState>>,
     Query(query): Query<PlanQuery>,
+    Query(page): Query<PlanPageQuery>,
 ) -> impl IntoResponse {
+    let tags = match plan_tags::parse_tag_filter(query.tag.as_deref()) {
+        Ok(tags) => tags,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": message })),
+            )
+                .into_response();
+        }
+    };
+    let cursor = match Cursor::parse(page.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": message })),
+            )
+                .into_response();
+        }
+    };
+    // Pages are read from PostgreSQL; the cache holds whole lists only.
+    let paged = page.limit.is_some() || cursor.is_some();
+    let limit = page
+        .limit
+        .unwrap_or(DEFAULT_PLAN_PAGE_SIZE)
+        .clamp(1, MAX_PLAN_PAGE_SIZE);
+    let fetch_limit = paged.then_some(limit + 1);
+    let after_created_at = cursor.map(|c| c.created_at);
+    let after_id = cursor.map(|c| c.id);
+    let use_cache = state.plan_cache.is_enabled() && !paged;
+
     let total_started = std::time::Instant::now();
     let cache_lookup_started = std::time::Instant::now();
-    let mut cache_status = if state.plan_cache.is_enabled() {
+    let mut cache_status = if use_cache {
         PlanCacheStatus::Miss
     } else {
         PlanCacheStatus::Bypass
     };
 
-    if state.plan_cache.is_enabled() {
+    if use_cache {
         match state.plan_cache.get_plans(&query).await {
             Ok(Some(plans)) => {
                 let mut response = (StatusCode::OK, Json(plans)).into_response();
@@ -624,11 +1413,22 @@ async fn get_plans(
                        grace_period_seconds, earn_yield, last_ping, is_active,
                        status, yield_rate_bps, accrued_yield, created_at
                 FROM plans
-                WHERE owner_address = $1
-                ORDER BY created_at DESC
+                WHERE (owner_address = $1
+                       OR id IN (SELECT plan_id FROM plan_co_owners
+                                 WHERE owner_address = $1 AND status = 'accepted'))
+                  AND ($2::text[] IS NULL
+                       OR (SELECT COUNT(*) FROM plan_tags t
+                           WHERE t.plan_id = plans.id AND t.tag = ANY($2)) = cardinality($2))
+                  AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4::uuid))
+                ORDER BY created_at DESC, id DESC
+                LIMIT $5
                 "#,
             )
             .bind(owner)
+            .bind(&tags)
+            .bind(after_created_at)
+            .bind(after_id)
+            .bind(fetch_limit)
             .fetch_all(&state.db_pool)
             .await
             {
@@ -654,10 +1454,19 @@ async fn get_plans(
                 FROM plans p
                 INNER JOIN beneficiaries b ON b.plan_id = p.id
                 WHERE b.wallet_address = $1
-                ORDER BY p.created_at DESC
+                  AND ($2::text[] IS NULL
+                       OR (SELECT COUNT(*) FROM plan_tags t
+                           WHERE t.plan_id = p.id AND t.tag = ANY($2)) = cardinality($2))
+                  AND ($3::timestamptz IS NULL OR (p.created_at, p.id) < ($3, $4::uuid))
+                ORDER BY p.created_at DESC, p.id DESC
+                LIMIT $5
                 "#,
             )
             .bind(beneficiary)
+            .bind(&tags)
+            .bind(after_created_at)
+            .bind(after_id)
+            .bind(fetch_limit)
             .fetch_all(&state.db_pool)
             .await
             {
@@ -682,12 +1491,24 @@ async fn get_plans(
                        p.last_ping, p.is_active, p.status, p.yield_rate_bps, p.accrued_yield, p.created_at
                 FROM plans p
                 INNER JOIN beneficiaries b ON b.plan_id = p.id
-                WHERE p.owner_address = $1 AND b.wallet_address = $2
-                ORDER BY p.created_at DESC
+                WHERE (p.owner_address = $1
+                       OR p.id IN (SELECT plan_id FROM plan_co_owners
+                                   WHERE owner_address = $1 AND status = 'accepted'))
+                  AND b.wallet_address = $2
+                  AND ($3::text[] IS NULL
+                       OR (SELECT COUNT(*) FROM plan_tags t
+                           WHERE t.plan_id = p.id AND t.tag = ANY($3)) = cardinality($3))
+                  AND ($4::timestamptz IS NULL OR (p.created_at, p.id) < ($4, $5::uuid))
+                ORDER BY p.created_at DESC, p.id DESC
+                LIMIT $6
                 "#,
             )
             .bind(owner)
             .bind(beneficiary)
+            .bind(&tags)
+            .bind(after_created_at)
+            .bind(after_id)
+            .bind(fetch_limit)
             .fetch_all(&state.db_pool)
             .await
             {
@@ -711,9 +1532,18 @@ async fn get_plans(
                        grace_period_seconds, earn_yield, last_ping, is_active,
                        status, yield_rate_bps, accrued_yield, created_at
                 FROM plans
-                ORDER BY created_at DESC
+                WHERE ($1::text[] IS NULL
+                       OR (SELECT COUNT(*) FROM plan_tags t
+                           WHERE t.plan_id = plans.id AND t.tag = ANY($1)) = cardinality($1))
+                  AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3::uuid))
+                ORDER BY created_at DESC, id DESC
+                LIMIT $4
                 "#,
             )
+            .bind(&tags)
+            .bind(after_created_at)
+            .bind(after_id)
+            .bind(fetch_limit)
             .fetch_all(&state.db_pool)
             .await
             {
@@ -731,10 +1561,15 @@ async fn get_plans(
         }
     };
 
+    let (rows, next) = match fetch_limit {
+        Some(_) => split_page(rows, limit, |row| Cursor::new(row.created_at, row.id)),
+        None => (rows, None),
+    };
+
     // Convert each plan row to a response with beneficiaries and yield
     let mut responses = Vec::with_capacity(rows.len());
     for row in rows {
-        let beneficiaries = match load_beneficiaries(&state.db_pool, row.id).await {
+        let beneficiaries = match load_public_beneficiaries(&state.db_pool, row.id).await {
             Ok(b) => b,
             Err(e) => {
                 return (
@@ -745,12 +1580,44 @@ async fn get_plans(
             }
         };
 
-        responses.push(plan_row_to_response(row, beneficiaries));
+        let co_owners = match plan_owners::co_owners(&state.db_pool, row.id).await {
+            Ok(co_owners) => co_owners,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(
+                        serde_json::json!({ "error": format!("Failed to load co-owners: {}", e) }),
+                    ),
+                )
+                    .into_response();
+            }
+        };
+
+        let tags = match plan_tags::tags_for(&state.db_pool, row.id).await {
+            Ok(tags) => tags,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": format!("Failed to load tags: {}", e) })),
+                )
+                    .into_response();
+            }
+        };
+
+        let mut response = plan_row_to_response(row, beneficiaries);
+        response.co_owners = co_owners;
+        response.tags = tags;
+        responses.push(response);
     }
 
     let db_query_ms = db_query_started.elapsed().as_millis();
 
-    if state.plan_cache.is_enabled() && responses.iter().any(|plan| plan.is_active) {
+    // Cache entries are invalidated per owner address, so lists containing
+    // joint plans are not cached: a co-owner's view would go stale.
+    if use_cache
+        && responses.iter().any(|plan| plan.is_active)
+        && responses.iter().all(|plan| plan.co_owners.is_empty())
+    {
         if let Err(err) = state.plan_cache.set_plans(&query, &responses).await {
             error!(error = %err, "Failed to populate plan cache");
         }
@@ -764,7 +1631,7 @@ async fn get_plans(
         db_query_ms,
         total_started.elapsed().as_millis(),
     );
-    response
+    with_next_cursor(response, next)
 }
 
 /// Verify the ping signature using ed25519.
@@ -824,11 +1691,22 @@ async fn ping_plan(
 
     let mut new_accrued_yield: rust_decimal::Decimal = plan.accrued_yield;
     if plan.earn_yield && elapsed > 0 {
-        let amount_f64 = plan.amount.to_string().parse::<f64>().unwrap_or(0.0);
-        let yield_val =
-            yield_calculator::calculate_yield(amount_f64, plan.yield_rate_bps as u32, elapsed);
-        if let Some(yield_dec) = rust_decimal::Decimal::from_f64_retain(yield_val) {
-            new_accrued_yield += yield_dec;
+        let accrued = yield_calculator::calculate_yield(
+            plan.amount,
+            plan.yield_rate_bps.max(0) as u32,
+            elapsed,
+        )
+        .and_then(|y| y.checked_add(new_accrued_yield));
+        match accrued {
+            Some(total) => new_accrued_yield = total,
+            None => {
+                error!(plan_id = %plan.id, "Accrued yield overflowed");
+                return (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(serde_json::json!({ "error": "Accrued yield is out of range" })),
+                )
+                    .into_response();
+            }
         }
     }
 
@@ -882,9 +1760,21 @@ async fn ping_plan(
 // submitting fiat payouts to AnchorRegistry, and marking the plan inactive
 async fn trigger_payout(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    device: Option<Extension<ClientDevice>>,
     Json(payload): Json<PayoutRequest>,
 ) -> impl IntoResponse {
-    // 1. Begin database transaction
+    // 1. Paying out is a claim: the beneficiary's wallet must have signed
+    // recently, as for claim requests
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = user.require_recently_verified() {
+        return e.into_response();
+    }
+
+    // 2. Begin database transaction
     let mut tx = match state.db_pool.begin().await {
         Ok(tx) => tx,
         Err(e) => {
@@ -896,7 +1786,7 @@ async fn trigger_payout(
         }
     };
 
-    // 2. Fetch the active plan for the owner
+    // 3. Fetch the active plan for the owner
     let plan = match sqlx::query_as::<_, PlanRow>(
         "SELECT id, owner_address, token_address, amount, grace_period, grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, accrued_yield, created_at FROM plans WHERE owner_address = $1 AND is_active = true FOR UPDATE",
     )
@@ -920,116 +1810,492 @@ async fn trigger_payout(
         }
     };
 
-    // 3. Verify if the grace period has elapsed
-    let now = chrono::Utc::now().timestamp();
-    let deadline = plan.last_ping + plan.grace_period_seconds;
-    if now < deadline {
+    // 4. Frozen plans wait for an admin to unfreeze them
+    if let Err(response) = refuse_frozen_plan(&mut tx, plan.id).await {
+        return response;
+    }
+
+    // 5. Immediate payouts are only allowed without a cooling-off window;
+    // otherwise claims go through POST /api/plans/{id}/claim
+    if state.system_settings.get().await.claim_cooling_off() > chrono::Duration::zero() {
         return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "Grace period has not elapsed" })),
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Payouts require a claim request and cooling-off period",
+                "claim_url": format!("/api/plans/{}/claim", plan.id),
+            })),
         )
             .into_response();
     }
 
-    // 4. Compute final locked amount + yield
-    let accrued_yield_f64 = compute_projected_accrued_yield(&plan);
-    let accrued_yield_dec = match Decimal::from_f64_retain(accrued_yield_f64) {
-        Some(d) => d.normalize(),
-        None => Decimal::ZERO,
-    };
-    let total_payout_dec = plan.amount + accrued_yield_dec;
-
-    // 5. Load beneficiaries for the plan
-    let beneficiaries_rows = match sqlx::query_as::<_, BeneficiaryRow>(
-        r#"
-        SELECT id, plan_id, wallet_address, allocation_bps, fiat_anchor_info
-        FROM beneficiaries
-        WHERE plan_id = $1
-        "#,
+    // 6. Only a beneficiary can claim, confirming with their wallet if they
+    // have re-auth enabled
+    let is_beneficiary: Result<bool, sqlx::Error> = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM beneficiaries WHERE plan_id = $1 AND wallet_address = $2)",
     )
     .bind(plan.id)
-    .fetch_all(&mut *tx)
-    .await
-    {
-        Ok(rows) => rows,
+    .bind(&caller)
+    .fetch_one(&mut *tx)
+    .await;
+    match is_beneficiary {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": "Only a beneficiary of this plan can request a claim" })),
+            )
+                .into_response();
+        }
         Err(e) => {
             error!(plan_id = %plan.id, error = %e, "Failed to load beneficiaries");
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(
-                    serde_json::json!({ "error": format!("Failed to load beneficiaries: {}", e) }),
-                ),
+                Json(serde_json::json!({ "error": format!("Database error: {}", e) })),
             )
                 .into_response();
         }
-    };
-
-    let n = beneficiaries_rows.len();
-    if n == 0 {
+    }
+    if let Err(e) = wallet_reauth::enforce(
+        &mut tx,
+        &caller,
+        ReauthAction::Claim,
+        Some(plan.id),
+        payload.confirmation.as_ref(),
+    )
+    .await
+    {
+        return e.into_response();
+    }
+
+    // 7. Check the plan is claimable by this beneficiary, as for claim requests
+    let now = chrono::Utc::now().timestamp();
+    let deadline = match claim_requests::check_eligibility(&state, &mut tx, &plan, &caller).await {
+        Ok(Ok(deadline)) => deadline,
+        Ok(Err(ineligible)) => return ineligible.into_response(),
+        Err(e) => {
+            error!(plan_id = %plan.id, error = %e, "Failed to check claim eligibility");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": format!("Database error: {}", e) })),
+            )
+                .into_response();
+        }
+    };
+
+    // 8. Record the claim like POST /api/plans/{id}/claim; one the fraud
+    // checks flag waits in the review queue instead of paying out
+    let claimant = Claimant {
+        beneficiary: caller,
+        executor: None,
+        delegation_id: None,
+    };
+    let device = device.as_ref().map(|Extension(d)| d);
+    let claim =
+        match claim_requests::file_claim(&state, &mut tx, &plan, &claimant, device, deadline).await
+        {
+            Ok(Some(claim)) => claim,
+            Ok(None) => {
+                return (
+                    StatusCode::CONFLICT,
+                    Json(
+                        serde_json::json!({ "error": "A claim is already pending for this plan" }),
+                    ),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                error!(plan_id = %plan.id, error = %e, "Failed to record claim");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": format!("Database error: {}", e) })),
+                )
+                    .into_response();
+            }
+        };
+    if claim.status == "in_review" {
+        if let Err(e) = tx.commit().await {
+            error!(error = %e, "Failed to commit database transaction");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": format!("Failed to commit database transaction: {}", e) })),
+            ).into_response();
+        }
+        return (StatusCode::ACCEPTED, Json(claim)).into_response();
+    }
+
+    // 9. Record payouts, mark the plan paid out and the claim executed
+    let (payout_rows, beneficiary_addresses) = match pay_out_plan(&state, &mut tx, &plan, now).await
+    {
+        Ok(result) => result,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) =
+        claim_requests::mark_executed(&mut tx, &claim, &plan, &beneficiary_addresses).await
+    {
+        error!(claim_id = %claim.id, error = %e, "Failed to mark claim executed");
         return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "Plan has no beneficiaries" })),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Database error: {}", e) })),
         )
             .into_response();
     }
 
-    // 6. Iterate over beneficiaries and insert payout records
-    let mut remaining = total_payout_dec;
-    let mut payout_rows = Vec::with_capacity(n);
+    // 10. Commit transaction
+    if let Err(e) = tx.commit().await {
+        error!(error = %e, "Failed to commit database transaction");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Failed to commit database transaction: {}", e) })),
+        ).into_response();
+    }
 
-    for (i, b) in beneficiaries_rows.iter().enumerate() {
-        let share = if i == n - 1 {
-            remaining
-        } else {
-            let amount =
-                (total_payout_dec * Decimal::from(b.allocation_bps)) / Decimal::from(10000);
-            let amount = amount.floor();
-            remaining -= amount;
-            amount
-        };
+    // 11. Invalidate cache
+    invalidate_plan_cache(
+        &state.plan_cache,
+        &plan.owner_address,
+        &beneficiary_addresses,
+    )
+    .await;
+
+    (
+        StatusCode::OK,
+        Json(
+            payout_rows
+                .into_iter()
+                .map(PayoutResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+    )
+        .into_response()
+}
+
+/// Why [`pay_out_plan`] could not record a payout; rendered as the HTTP
+/// error the payout endpoints return.
+#[derive(Debug)]
+pub(crate) struct PayoutError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl PayoutError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for PayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl IntoResponse for PayoutError {
+    fn into_response(self) -> axum::response::Response {
+        (
+            self.status,
+            Json(serde_json::json!({ "error": self.message })),
+        )
+            .into_response()
+    }
+}
+
+/// What a plan pays out now: its balance plus the yield accrued to date.
+/// `None` if that is out of range.
+pub(crate) fn payout_total(plan: &PlanRow) -> Option<Decimal> {
+    plan.amount
+        .checked_add(compute_projected_accrued_yield(plan)?.normalize())
+}
 
+/// Splits `total` by each beneficiary's allocation in basis points. Shares
+/// are rounded down and the last beneficiary takes the remainder.
+pub(crate) fn split_payout(total: Decimal, allocations: &[i32]) -> Vec<Decimal> {
+    let mut remaining = total;
+    allocations
+        .iter()
+        .enumerate()
+        .map(|(i, &bps)| {
+            if i == allocations.len() - 1 {
+                return remaining;
+            }
+            let share = (total * Decimal::from(bps) / Decimal::from(10000)).floor();
+            remaining -= share;
+            share
+        })
+        .collect()
+}
+
+/// Refuses the payout unless `address` is a verified entry in the plan
+/// owner's address book.
+async fn ensure_verified_destination(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    plan: &PlanRow,
+    address: &str,
+) -> Result<(), PayoutError> {
+    match crate::address_book::is_verified_destination(&mut **tx, &plan.owner_address, address)
+        .await
+    {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(PayoutError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "Payout address {} has not been verified in the owner's address book",
+                address
+            ),
+        )),
+        Err(e) => {
+            error!(plan_id = %plan.id, error = %e, "Failed to check payout address verification");
+            Err(PayoutError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Database error: {}", e),
+            ))
+        }
+    }
+}
+
+/// Splits a plan's balance plus yield across its beneficiaries, records a
+/// payout per beneficiary, hands fiat payouts to the anchor and marks the
+/// plan paid out. The caller owns the transaction and must hold the plan
+/// row lock. Returns the payout rows and the beneficiary addresses whose
+/// cached plans should be invalidated after commit.
+pub(crate) async fn pay_out_plan(
+    state: &AppState,
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    plan: &PlanRow,
+    now: i64,
+) -> Result<(Vec<PayoutRow>, Vec<String>), PayoutError> {
+    // Compute final locked amount + yield
+    let out_of_range = || {
+        PayoutError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Plan payout is out of range",
+        )
+    };
+    let total_payout_dec = payout_total(plan).ok_or_else(out_of_range)?;
+
+    // Load beneficiaries for the plan
+    let beneficiaries_rows = sqlx::query_as::<_, BeneficiaryRow>(
+        r#"
+        SELECT id, plan_id, wallet_address, allocation_bps, fiat_anchor_info
+        FROM beneficiaries
+        WHERE plan_id = $1
+        ORDER BY id
+        "#,
+    )
+    .bind(plan.id)
+    .fetch_all(&mut **tx)
+    .await
+    .and_then(|rows| {
+        rows.into_iter()
+            .map(|mut row| {
+                row.fiat_anchor_info = state
+                    .field_cipher
+                    .decrypt_column(SensitiveField::BeneficiaryAnchorInfo, &row.fiat_anchor_info)?;
+                Ok(row)
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+    })
+    .map_err(|e| {
+        error!(plan_id = %plan.id, error = %e, "Failed to load beneficiaries");
+        PayoutError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to load beneficiaries: {}", e),
+        )
+    })?;
+
+    let n = beneficiaries_rows.len();
+    if n == 0 {
+        return Err(PayoutError::new(
+            StatusCode::BAD_REQUEST,
+            "Plan has no beneficiaries",
+        ));
+    }
+
+    // Iterate over beneficiaries and insert payout records
+    let allocations: Vec<i32> = beneficiaries_rows
+        .iter()
+        .map(|b| b.allocation_bps)
+        .collect();
+    let shares = split_payout(total_payout_dec, &allocations);
+    let mut payout_rows = Vec::with_capacity(n);
+
+    for (b, share) in beneficiaries_rows.iter().zip(shares) {
         if share <= Decimal::ZERO {
             continue;
         }
 
         let is_fiat = !b.fiat_anchor_info.trim().is_empty();
+        if !is_fiat && state.config.require_verified_payout_addresses {
+            ensure_verified_destination(tx, plan, &b.wallet_address).await?;
+        }
         let payout_type_str = if is_fiat { "fiat" } else { "crypto" };
-        let payout_status_str = "processing";
+        // Crypto transfers are queued for the payout batcher; fiat goes straight to the anchor.
+        let payout_status_str = if is_fiat { "processing" } else { "pending" };
 
-        let payout_row = match sqlx::query_as::<_, PayoutRow>(
-            r#"
-            INSERT INTO payouts (plan_id, beneficiary_address, amount, payout_type, status)
-            VALUES ($1, $2, $3, $4::payout_type, $5::payout_status)
-            RETURNING id, plan_id, beneficiary_address, amount::text, payout_type::text, status::text, created_at
-            "#,
-        )
-        .bind(plan.id)
-        .bind(&b.wallet_address)
-        .bind(share)
-        .bind(payout_type_str)
-        .bind(payout_status_str)
-        .fetch_one(&mut *tx)
-        .await {
-            Ok(row) => row,
-            Err(e) => {
-                error!(plan_id = %plan.id, beneficiary = %b.wallet_address, error = %e, "Failed to insert payout record");
-                return (
+        let destinations = if is_fiat {
+            Vec::new()
+        } else {
+            payout_destinations::load_destinations(tx, &b.wallet_address)
+                .await
+                .map_err(|e| {
+                    error!(plan_id = %plan.id, beneficiary = %b.wallet_address, error = %e, "Failed to load payout destinations");
+                    PayoutError::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to load payout destinations: {}", e),
+                    )
+                })?
+        };
+        // Every split wallet is paid directly, so each must be verified too.
+        if state.config.require_verified_payout_addresses {
+            for destination in &destinations {
+                ensure_verified_destination(tx, plan, &destination.destination_address).await?;
+            }
+        }
+        let (parts, split_group) = if destinations.is_empty() {
+            (vec![(None, share)], None)
+        } else {
+            let shares: Vec<i32> = destinations.iter().map(|d| d.share_bps).collect();
+            let parts = destinations
+                .into_iter()
+                .map(|d| Some(d.destination_address))
+                .zip(payout_destinations::split_amount(share, &shares))
+                .filter(|(_, amount)| *amount > Decimal::ZERO)
+                .collect();
+            (parts, Some(Uuid::new_v4()))
+        };
+
+        let conversion = if is_fiat {
+            PayoutConversion::None
+        } else {
+            payout_conversion::conversion_for(
+                tx,
+                &b.wallet_address,
+                &plan.token_address,
+                std::time::Duration::from_secs(state.config.payout_quote_max_age_secs),
+            )
+            .await
+            .map_err(|e| {
+                error!(plan_id = %plan.id, beneficiary = %b.wallet_address, error = %e, "Failed to quote payout conversion");
+                PayoutError::new(
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({ "error": format!("Failed to insert payout record: {}", e) })),
-                ).into_response();
+                    format!("Failed to quote payout conversion: {}", e),
+                )
+            })?
+        };
+        let (quote, unavailable) = match conversion {
+            PayoutConversion::None => (None, None),
+            PayoutConversion::Quoted(quote) => {
+                match parts
+                    .iter()
+                    .map(|(_, amount)| quote.convert(*amount))
+                    .collect::<Option<Vec<_>>>()
+                {
+                    Some(converted) => (Some((quote, converted)), None),
+                    None => (
+                        None,
+                        Some(serde_json::json!({
+                            "asset": quote.asset,
+                            "reason": "amount_too_small",
+                        })),
+                    ),
+                }
             }
+            PayoutConversion::Unavailable { asset, reason } => (
+                None,
+                Some(serde_json::json!({ "asset": asset, "reason": reason })),
+            ),
         };
 
+        let mut converted_total = Decimal::ZERO;
+        for (i, (destination, amount)) in parts.into_iter().enumerate() {
+            let converted = quote
+                .as_ref()
+                .map(|(quote, converted)| (quote, converted[i]));
+            converted_total += converted.map_or(Decimal::ZERO, |(_, c)| c.amount);
+            let payout_row = sqlx::query_as::<_, PayoutRow>(
+                r#"
+                INSERT INTO payouts
+                    (plan_id, beneficiary_address, destination_address, split_group, amount,
+                     payout_type, status, correlation_id, payout_asset, converted_amount,
+                     min_converted_amount, conversion_rate)
+                VALUES ($1, $2, $3, $4, $5, $6::payout_type, $7::payout_status, $8, $9, $10,
+                        $11, $12)
+                RETURNING id, plan_id, beneficiary_address, destination_address, amount,
+                          (SELECT token_address FROM plans WHERE plans.id = payouts.plan_id) AS token_address,
+                          payout_asset, converted_amount, payout_type::text,
+                          status::text, created_at
+                "#,
+            )
+            .bind(plan.id)
+            .bind(&b.wallet_address)
+            .bind(&destination)
+            .bind(split_group)
+            .bind(amount)
+            .bind(payout_type_str)
+            .bind(payout_status_str)
+            .bind(telemetry::correlation_id())
+            .bind(converted.map(|(quote, _)| &quote.asset))
+            .bind(converted.map(|(_, c)| c.amount))
+            .bind(converted.map(|(_, c)| c.min_amount))
+            .bind(converted.map(|(quote, _)| quote.rate))
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| {
+                error!(plan_id = %plan.id, beneficiary = %b.wallet_address, error = %e, "Failed to insert payout record");
+                PayoutError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to insert payout record: {}", e),
+                )
+            })?;
+            payout_rows.push(payout_row);
+        }
+
+        let conversion_audit = match (&quote, unavailable) {
+            (Some((quote, _)), _) => Some((
+                "payout.converted",
+                serde_json::json!({
+                    "payout_asset": quote.asset,
+                    "converted_amount": converted_total.to_string(),
+                    "rate": quote.rate.to_string(),
+                    "max_slippage_bps": quote.max_slippage_bps,
+                    "quoted_at": quote.quoted_at,
+                }),
+            )),
+            (None, Some(details)) => Some(("payout.conversion_unavailable", details)),
+            (None, None) => None,
+        };
+        if let Some((action, mut details)) = conversion_audit {
+            details["beneficiary_address"] = serde_json::json!(b.wallet_address);
+            details["token_address"] = serde_json::json!(plan.token_address);
+            details["amount"] = serde_json::json!(share.to_string());
+            crate::audit::record_audit(
+                &mut **tx,
+                crate::audit::SYSTEM_ACTOR,
+                action,
+                &plan.id.to_string(),
+                details,
+            )
+            .await
+            .map_err(|e| {
+                error!(plan_id = %plan.id, error = %e, "Failed to record payout conversion");
+                PayoutError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to record payout conversion: {}", e),
+                )
+            })?;
+        }
+
         // Initiate payout distribution
         if is_fiat {
             let (beneficiary_name, fiat_currency, bank_name, account_number) =
                 parse_fiat_anchor_info(&b.fiat_anchor_info, &b.wallet_address);
-            let token_amount_f64 = share.to_string().parse::<f64>().unwrap_or(0.0);
             let req = crate::stellar_anchor::AnchorPayoutRequest {
                 beneficiary_address: b.wallet_address.clone(),
                 beneficiary_name,
                 token: plan.token_address.clone(),
-                token_amount: token_amount_f64,
+                token_amount: share,
                 fiat_currency,
                 bank_name,
                 account_number,
@@ -1040,51 +2306,178 @@ async fn trigger_payout(
                 plan_id = %plan.id,
                 beneficiary = %b.wallet_address,
                 amount = %share,
-                "Initiated on-chain crypto distribution"
+                split = split_group.is_some(),
+                "Queued on-chain crypto distribution"
             );
         }
-
-        payout_rows.push(payout_row);
     }
 
-    // 7. Mark the plan as inactive
-    if let Err(e) = sqlx::query(
+    // Mark the plan as inactive
+    sqlx::query(
         "UPDATE plans SET is_active = false, status = 'PAID_OUT', accrued_yield = $1, last_ping = $2 WHERE id = $3"
     )
-    .bind(accrued_yield_dec)
+    .bind(total_payout_dec - plan.amount)
     .bind(now)
     .bind(plan.id)
-    .execute(&mut *tx)
-    .await {
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| {
         error!(plan_id = %plan.id, error = %e, "Failed to mark plan as inactive");
-        return (
+        PayoutError::new(
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": format!("Failed to mark plan as inactive: {}", e) })),
-        ).into_response();
+            format!("Failed to mark plan as inactive: {}", e),
+        )
+    })?;
+
+    let beneficiary_addresses = beneficiaries_rows
+        .into_iter()
+        .map(|b| b.wallet_address)
+        .collect();
+    Ok((payout_rows, beneficiary_addresses))
+}
+
+// Handler: Deactivate Plan
+async fn deactivate_plan(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    payload: Option<Json<DeactivatePlanRequest>>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+
+    let mut tx = match state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!(error = %e, "Failed to begin database transaction");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    let plan = match sqlx::query_as::<_, PlanRow>(
+        "SELECT id, owner_address, token_address, amount, grace_period, grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, accrued_yield, created_at FROM plans WHERE id = $1 AND is_active = true AND (owner_address = $2 OR id IN (SELECT plan_id FROM plan_co_owners WHERE owner_address = $2 AND status = 'accepted')) FOR UPDATE",
+    )
+    .bind(plan_id)
+    .bind(&owner)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(Some(plan)) => plan,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Active plan not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Database error fetching plan");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Err(response) = refuse_frozen_plan(&mut tx, plan.id).await {
+        return response;
     }
 
-    // 8. Commit transaction
-    if let Err(e) = tx.commit().await {
-        error!(error = %e, "Failed to commit database transaction");
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": format!("Failed to commit database transaction: {}", e) })),
-        ).into_response();
+    if let Err(e) = wallet_reauth::enforce(
+        &mut tx,
+        &owner,
+        ReauthAction::DeactivatePlan,
+        Some(plan.id),
+        payload.confirmation.as_ref(),
+    )
+    .await
+    {
+        return e.into_response();
+    }
+
+    // Joint plans are only deactivated once every owner has asked to.
+    match plan_owners::co_owners(&mut *tx, plan.id).await {
+        Ok(co_owners) if co_owners.is_empty() => {}
+        Ok(_) => {
+            return plan_owners::propose(
+                &state,
+                tx,
+                plan,
+                &owner,
+                plan_owners::OwnerAction::Deactivate,
+                serde_json::json!({}),
+            )
+            .await;
+        }
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load co-owners");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
     }
 
-    // 9. Invalidate cache
-    let beneficiary_addresses: Vec<String> = beneficiaries_rows
+    let result: Result<PlanRow, sqlx::Error> = async {
+        let updated = sqlx::query_as::<_, PlanRow>(
+            "UPDATE plans SET is_active = false, status = 'DEACTIVATED' WHERE id = $1 RETURNING id, owner_address, token_address, amount, grace_period, grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, accrued_yield, created_at",
+        )
+        .bind(plan.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        crate::audit::record_audit(
+            &mut *tx,
+            &owner,
+            "plan.deactivate",
+            &plan.id.to_string(),
+            serde_json::json!({ "previous_status": plan.status }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(updated)
+    }
+    .await;
+
+    let updated = match result {
+        Ok(updated) => updated,
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to deactivate plan");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    let beneficiaries = match load_beneficiaries(&state.db_pool, &state.field_cipher, plan.id).await
+    {
+        Ok(beneficiaries) => beneficiaries,
+        Err(err) => {
+            error!(plan_id = %plan.id, error = %err, "Failed to load beneficiaries");
+            Vec::new()
+        }
+    };
+    let beneficiary_addresses: Vec<String> = beneficiaries
         .iter()
         .map(|b| b.wallet_address.clone())
         .collect();
-    invalidate_plan_cache(
-        &state.plan_cache,
-        &plan.owner_address,
-        &beneficiary_addresses,
-    )
-    .await;
+    invalidate_plan_cache(&state.plan_cache, &owner, &beneficiary_addresses).await;
 
-    (StatusCode::OK, Json(payout_rows)).into_response()
+    (
+        StatusCode::OK,
+        Json(plan_row_to_response(updated, beneficiaries)),
+    )
+        .into_response()
 }
 
 fn parse_fiat_anchor_info(info: &str, wallet_address: &str) -> (String, String, String, String) {
@@ -1191,16 +2584,21 @@ async fn get_anchor_payouts(
     let rows: Vec<PayoutRow> = match sqlx::query_as::<_, PayoutRow>(
         r#"
         SELECT
-            id,
-            plan_id,
-            beneficiary_address,
-            amount::text      AS amount,
-            payout_type::text AS payout_type,
-            status::text      AS status,
-            created_at
-        FROM payouts
-        WHERE ($1::text IS NULL OR beneficiary_address = $1)
-        ORDER BY created_at DESC
+            po.id,
+            po.plan_id,
+            po.beneficiary_address,
+            po.destination_address,
+            po.amount,
+            p.token_address,
+            po.payout_asset,
+            po.converted_amount,
+            po.payout_type::text AS payout_type,
+            po.status::text      AS status,
+            po.created_at
+        FROM payouts po
+        JOIN plans p ON p.id = po.plan_id
+        WHERE ($1::text IS NULL OR po.beneficiary_address = $1)
+        ORDER BY po.created_at DESC
         LIMIT $2 OFFSET $3
         "#,
     )
@@ -1226,7 +2624,7 @@ async fn get_anchor_payouts(
     (
         StatusCode::OK,
         Json(PayoutStatusResponse {
-            data: rows,
+            data: rows.into_iter().map(PayoutResponse::from).collect(),
             page,
             page_size,
             total,
@@ -1297,7 +2695,16 @@ async fn get_kyc_status() -> impl IntoResponse {
 }
 
 // Submit KYC verification data
-async fn submit_kyc(Json(_payload): Json<KYCSubmitRequest>) -> impl IntoResponse {
+async fn submit_kyc(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(_payload): Json<KYCSubmitRequest>,
+) -> impl IntoResponse {
+    let wallet_address = match require_privacy_consent(&state, &user).await {
+        Ok(address) => address,
+        Err(response) => return response,
+    };
+
     // In a real implementation, this would:
     // 1. Validate the request
     // 2. Submit to third-party KYC provider
@@ -1305,7 +2712,7 @@ async fn submit_kyc(Json(_payload): Json<KYCSubmitRequest>) -> impl IntoResponse
     // 4. Return reference ID
 
     let response = KYCStatusResponse {
-        wallet_address: "GDTEST123".to_string(),
+        wallet_address,
         kyc_status: "submitted".to_string(),
         submitted_at: Some(Utc::now()),
         approved_at: None,
@@ -1314,11 +2721,18 @@ async fn submit_kyc(Json(_payload): Json<KYCSubmitRequest>) -> impl IntoResponse
         provider_reference: Some("ref-001".to_string()),
     };
 
-    (StatusCode::OK, Json(response))
+    (StatusCode::OK, Json(response)).into_response()
 }
 
 // Upload KYC document
-async fn upload_kyc_document() -> impl IntoResponse {
+async fn upload_kyc_document(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    if let Err(response) = require_privacy_consent(&state, &user).await {
+        return response;
+    }
+
     // In a real implementation, this would:
     // 1. Receive multipart form data with file and document_type
     // 2. Validate file (size, type)
@@ -1331,7 +2745,32 @@ async fn upload_kyc_document() -> impl IntoResponse {
         url: "https://example.com/documents/doc-001".to_string(),
     };
 
-    (StatusCode::OK, Json(response))
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// KYC data is personal data, so it is only taken from users who have
+/// accepted the current privacy policy.
+async fn require_privacy_consent(
+    state: &AppState,
+    user: &UserContext,
+) -> Result<String, axum::response::Response> {
+    let wallet_address = user
+        .require_wallet_address()
+        .map_err(|e| e.into_response())?;
+    match consents::require_consent(&state.db_pool, &wallet_address, ConsentKind::PrivacyPolicy)
+        .await
+    {
+        Ok(Ok(())) => Ok(wallet_address),
+        Ok(Err(required)) => Err(required.into_response()),
+        Err(e) => {
+            error!(user = %wallet_address, error = %e, "Failed to check privacy consent");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response())
+        }
+    }
 }
 
 // Check if KYC is required
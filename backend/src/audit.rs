@@ -0,0 +1,35 @@
+//! Append-only audit trail for administrative and automated actions.
+
+use uuid::Uuid;
+
+/// Actor recorded for actions taken by background workers.
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// Appends an audit log entry. `subject` identifies what was acted on (a
+/// wallet address, plan id, ...). The entry is tagged with the current
+/// correlation id, when there is one.
+pub async fn record_audit<'e, E>(
+    executor: E,
+    actor: &str,
+    action: &str,
+    subject: &str,
+    details: serde_json::Value,
+) -> Result<Uuid, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO audit_logs (actor, action, subject, details, correlation_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(actor)
+    .bind(action)
+    .bind(subject)
+    .bind(details)
+    .bind(crate::telemetry::correlation_id())
+    .fetch_one(executor)
+    .await
+}
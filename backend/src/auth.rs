@@ -1,26 +1,81 @@
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    extract::State,
+    http::{header, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use thiserror::Error;
+use tracing::error;
+
+use crate::api::AppState;
+use crate::freezes::user_frozen;
+
+/// Role of admin JWTs issued to organization members. Unlike `admin`
+/// tokens, they grant nothing once the member leaves the organization.
+pub const ORGANIZATION_MEMBER_ROLE: &str = "organization";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub role: String,
     pub exp: usize,
+    pub iat: usize,
+    /// Session id; the token's activity is tracked in `auth_sessions`.
+    pub jti: String,
+}
+
+/// How recently the caller proved control of their wallet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthLevel {
+    #[default]
+    Standard,
+    /// Signed by the wallet within the step-up window: a signed request
+    /// carrying a fresh timestamp and unused nonce, or a session that signed
+    /// in or stepped up recently.
+    RecentlyVerified,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserContext {
     pub user_id: String,
     pub role: String,
+    #[serde(default)]
+    pub auth_level: AuthLevel,
+}
+
+impl UserContext {
+    /// Stellar `G...` address of a signature-authenticated user.
+    pub fn wallet_address(&self) -> Option<String> {
+        let bytes: [u8; 32] = hex::decode(self.user_id.trim_start_matches("0x"))
+            .ok()?
+            .try_into()
+            .ok()?;
+        Some(stellar_strkey::ed25519::PublicKey(bytes).to_string())
+    }
+
+    /// Like [`UserContext::wallet_address`], for handlers that only serve
+    /// signature-authenticated wallets.
+    pub fn require_wallet_address(&self) -> Result<String, AuthError> {
+        self.wallet_address().ok_or(AuthError::Unauthorized)
+    }
+
+    /// For sensitive actions: sessions that have not signed with the
+    /// wallet within the step-up window are sent to step up first.
+    pub fn require_recently_verified(&self) -> Result<(), AuthError> {
+        if self.auth_level >= AuthLevel::RecentlyVerified {
+            Ok(())
+        } else {
+            Err(AuthError::StepUpRequired)
+        }
+    }
 }
 
 impl axum::extract::FromRequestParts<()> for UserContext {
@@ -47,16 +102,47 @@ pub enum AuthError {
     InvalidToken,
     #[error("Token expired")]
     TokenExpired,
+    #[error("Session expired after inactivity; sign in again")]
+    SessionExpired,
+    #[error("Recent wallet verification required")]
+    StepUpRequired,
     #[error("Invalid signature")]
     InvalidSignature,
     #[error("Unauthorized")]
     Unauthorized,
+    #[error("Account is frozen pending review")]
+    AccountFrozen,
+    #[error("Authentication is temporarily unavailable")]
+    Unavailable,
 }
 
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
+        if let AuthError::StepUpRequired = self {
+            // RFC 9470: tells the client to re-authenticate, here by signing
+            // a `step_up` wallet challenge.
+            let body = serde_json::json!({
+                "error": self.to_string(),
+                "step_up": {
+                    "challenge": "/api/reauth/challenges",
+                    "action": "step_up",
+                    "verify": "/api/auth/step-up",
+                },
+            });
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(
+                    header::WWW_AUTHENTICATE,
+                    r#"Bearer error="insufficient_user_authentication""#,
+                )],
+                Json(body),
+            )
+                .into_response();
+        }
         let status = match self {
             AuthError::TokenExpired => StatusCode::UNAUTHORIZED,
+            AuthError::AccountFrozen => StatusCode::FORBIDDEN,
+            AuthError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
             _ => StatusCode::UNAUTHORIZED,
         };
         let body = serde_json::json!({ "error": self.to_string() });
@@ -64,7 +150,57 @@ impl IntoResponse for AuthError {
     }
 }
 
+/// Signs an HS256 token accepted by `jwt_auth_middleware`.
+pub fn issue_token(
+    secret: &str,
+    subject: &str,
+    role: &str,
+    ttl: std::time::Duration,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let iat = chrono::Utc::now().timestamp() as usize;
+    let claims = Claims {
+        sub: subject.to_string(),
+        role: role.to_string(),
+        exp: iat + ttl.as_secs() as usize,
+        iat,
+        jti: uuid::Uuid::new_v4().to_string(),
+    };
+
+    encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(secret.as_ref()),
+    )
+}
+
+/// Generates a random 256-bit secret, hex encoded, suitable for `JWT_SECRET`.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Verifies a hex-encoded ed25519 signature over `message` made by the key
+/// behind a Stellar `G...` address.
+pub fn verify_wallet_signature(address: &str, message: &[u8], signature_hex: &str) -> bool {
+    let Ok(public_key) = stellar_strkey::ed25519::PublicKey::from_string(address.trim()) else {
+        return false;
+    };
+    let Ok(verifying_key) = VerifyingKey::from_bytes(&public_key.0) else {
+        return false;
+    };
+    let Ok(signature_bytes) = hex::decode(signature_hex.trim().trim_start_matches("0x")) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(&signature_bytes) else {
+        return false;
+    };
+
+    verifying_key.verify(message, &signature).is_ok()
+}
+
 pub async fn jwt_auth_middleware(
+    State(state): State<Arc<AppState>>,
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, AuthError> {
@@ -86,23 +222,32 @@ pub async fn jwt_auth_middleware(
         return Err(AuthError::MissingToken);
     }
 
-    let secret = std::env::var("JWT_SECRET").map_err(|_| AuthError::InvalidToken)?;
-
     let validation = Validation::new(Algorithm::HS256);
     let token_data = decode::<Claims>(
         token,
-        &DecodingKey::from_secret(secret.as_ref()),
+        &DecodingKey::from_secret(state.config.jwt_secret.as_ref()),
         &validation,
     )
     .map_err(|_| AuthError::InvalidToken)?;
 
-    if token_data.claims.role != "admin" {
+    if token_data.claims.role != "admin" && token_data.claims.role != ORGANIZATION_MEMBER_ROLE {
         return Err(AuthError::Unauthorized);
     }
 
+    let idle_timeout = state.system_settings.get().await.session_idle_timeout();
+    match touch_admin_session(&state.db_pool, &token_data.claims, idle_timeout).await {
+        Ok(true) => {}
+        Ok(false) => return Err(AuthError::SessionExpired),
+        Err(e) => {
+            error!(error = %e, "Failed to check session activity");
+            return Err(AuthError::Unavailable);
+        }
+    }
+
     let user_context = UserContext {
         user_id: token_data.claims.sub,
         role: token_data.claims.role,
+        auth_level: AuthLevel::Standard,
     };
 
     req.extensions_mut().insert(user_context);
@@ -110,10 +255,64 @@ pub async fn jwt_auth_middleware(
     Ok(next.run(req).await)
 }
 
+/// Marks an admin token's session active, opening it on the token's first
+/// use. False when the session has been idle longer than `idle_timeout`, or
+/// the token was first used more than `idle_timeout` after it was issued.
+async fn touch_admin_session(
+    db: &sqlx::PgPool,
+    claims: &Claims,
+    idle_timeout: chrono::Duration,
+) -> Result<bool, sqlx::Error> {
+    let idle_secs = idle_timeout.num_seconds() as f64;
+    let touched = sqlx::query(
+        r#"
+        UPDATE auth_sessions SET last_active_at = NOW()
+        WHERE jti = $1 AND account = $2
+          AND last_active_at > NOW() - make_interval(secs => $3)
+        "#,
+    )
+    .bind(&claims.jti)
+    .bind(&claims.sub)
+    .bind(idle_secs)
+    .execute(db)
+    .await?;
+    if touched.rows_affected() > 0 {
+        return Ok(true);
+    }
+    let opened = sqlx::query(
+        r#"
+        WITH purged AS (DELETE FROM auth_sessions WHERE expires_at < NOW())
+        INSERT INTO auth_sessions (jti, account, expires_at)
+        SELECT $1, $2, to_timestamp($4)
+        WHERE to_timestamp($5) > NOW() - make_interval(secs => $3)
+        ON CONFLICT (jti) DO NOTHING
+        "#,
+    )
+    .bind(&claims.jti)
+    .bind(&claims.sub)
+    .bind(idle_secs)
+    .bind(claims.exp as f64)
+    .bind(claims.iat as f64)
+    .execute(db)
+    .await?;
+    Ok(opened.rows_affected() > 0)
+}
+
+/// Authenticates wallet routes by an ed25519 signature over the body, or by
+/// a SEP-10 token in `Authorization: Bearer` when no signature is sent.
+/// Only signatures bound to a fresh timestamp and nonce count as recent
+/// verification; a bare body signature can be replayed.
 pub async fn signature_auth_middleware(
+    State(state): State<Arc<AppState>>,
     req: Request<Body>,
     next: Next,
 ) -> Result<Response, AuthError> {
+    if !req.headers().contains_key("X-Signature") {
+        if let Some(token) = bearer_token(req.headers()) {
+            return sep10_auth(&state, token.to_string(), req, next).await;
+        }
+    }
+
     let (parts, body) = req.into_parts();
 
     let public_key_hex = parts
@@ -157,14 +356,27 @@ pub async fn signature_auth_middleware(
     let body_str =
         String::from_utf8(body_bytes.to_vec()).map_err(|_| AuthError::InvalidSignature)?;
 
+    let freshness = SignatureFreshness::from_headers(&parts.headers)?;
+    let message = match &freshness {
+        Some(fresh) => fresh.signed_message(&body_str),
+        None => body_str.clone(),
+    };
+
     verifying_key
-        .verify(body_str.as_bytes(), &signature)
+        .verify(message.as_bytes(), &signature)
         .map_err(|_| AuthError::InvalidSignature)?;
 
+    let auth_level = match freshness {
+        Some(fresh) => fresh.auth_level(&state, public_key_hex).await?,
+        None => AuthLevel::Standard,
+    };
+
     let user_context = UserContext {
         user_id: public_key_hex.to_string(),
         role: "user".to_string(),
+        auth_level,
     };
+    refuse_frozen(&state, &user_context).await?;
 
     let mut new_req = Request::from_parts(parts, Body::from(body_str));
     new_req.extensions_mut().insert(user_context);
@@ -172,5 +384,208 @@ pub async fn signature_auth_middleware(
     Ok(next.run(new_req).await)
 }
 
+/// Tolerance for wallet clocks running ahead of the server.
+const SIGNATURE_CLOCK_SKEW_SECS: i64 = 60;
+
+/// `X-Signature-Timestamp` and `X-Signature-Nonce` of a signed request.
+/// The signature then covers `"{timestamp}\n{nonce}\n{body}"`, which makes
+/// the request usable once and only within the step-up window.
+struct SignatureFreshness {
+    timestamp: i64,
+    nonce: String,
+}
+
+impl SignatureFreshness {
+    fn from_headers(headers: &axum::http::HeaderMap) -> Result<Option<Self>, AuthError> {
+        let header = |name| {
+            headers
+                .get(name)
+                .map(|v| v.to_str().map_err(|_| AuthError::InvalidHeaderFormat))
+                .transpose()
+        };
+        match (
+            header("X-Signature-Timestamp")?,
+            header("X-Signature-Nonce")?,
+        ) {
+            (None, None) => Ok(None),
+            (Some(timestamp), Some(nonce)) if !nonce.is_empty() && nonce.len() <= 128 => {
+                Ok(Some(Self {
+                    timestamp: timestamp
+                        .trim()
+                        .parse()
+                        .map_err(|_| AuthError::InvalidHeaderFormat)?,
+                    nonce: nonce.to_string(),
+                }))
+            }
+            _ => Err(AuthError::InvalidHeaderFormat),
+        }
+    }
+
+    fn signed_message(&self, body: &str) -> String {
+        format!("{}\n{}\n{}", self.timestamp, self.nonce, body)
+    }
+
+    /// Recently verified while the signature is inside the step-up window,
+    /// consuming its nonce; a replayed nonce is refused.
+    async fn auth_level(&self, state: &AppState, public_key: &str) -> Result<AuthLevel, AuthError> {
+        let window = state.system_settings.get().await.step_up_window();
+        let age = chrono::Utc::now().timestamp() - self.timestamp;
+        if !(-SIGNATURE_CLOCK_SKEW_SECS..=window.num_seconds()).contains(&age) {
+            return Ok(AuthLevel::Standard);
+        }
+
+        let consumed = sqlx::query(
+            r#"
+            WITH purged AS (DELETE FROM signature_nonces WHERE expires_at < NOW())
+            INSERT INTO signature_nonces (public_key, nonce, expires_at)
+            VALUES ($1, $2, to_timestamp($3))
+            ON CONFLICT (public_key, nonce) DO NOTHING
+            "#,
+        )
+        .bind(public_key.trim_start_matches("0x").to_lowercase())
+        .bind(&self.nonce)
+        .bind((self.timestamp + window.num_seconds() + SIGNATURE_CLOCK_SKEW_SECS) as f64)
+        .execute(&state.db_pool)
+        .await;
+        match consumed {
+            Ok(result) if result.rows_affected() == 0 => Err(AuthError::InvalidSignature),
+            Ok(_) => Ok(AuthLevel::RecentlyVerified),
+            Err(e) => {
+                error!(error = %e, "Failed to record signature nonce");
+                Err(AuthError::Unavailable)
+            }
+        }
+    }
+}
+
+pub(crate) fn bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+    headers
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+async fn sep10_auth(
+    state: &AppState,
+    token: String,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, AuthError> {
+    let claims =
+        crate::sep10::verify_claims(&state.config, &token).ok_or(AuthError::InvalidToken)?;
+    let public_key = stellar_strkey::ed25519::PublicKey::from_string(&claims.sub)
+        .map_err(|_| AuthError::InvalidToken)?;
+
+    let settings = state.system_settings.get().await;
+    match crate::sep10::touch_session(&state.db_pool, &claims, settings.session_idle_timeout())
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return Err(AuthError::SessionExpired),
+        Err(e) => {
+            error!(error = %e, "Failed to check session activity");
+            return Err(AuthError::Unavailable);
+        }
+    }
+
+    let user_context = UserContext {
+        user_id: hex::encode(public_key.0),
+        role: "user".to_string(),
+        auth_level: claims.auth_level_at(chrono::Utc::now(), settings.step_up_window()),
+    };
+    refuse_frozen(state, &user_context).await?;
+
+    req.extensions_mut().insert(user_context);
+
+    Ok(next.run(req).await)
+}
+
+/// Stops a wallet frozen by an admin from authenticating.
+async fn refuse_frozen(state: &AppState, user: &UserContext) -> Result<(), AuthError> {
+    let Some(address) = user.wallet_address() else {
+        return Ok(());
+    };
+    match user_frozen(&state.db_pool, &address).await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(AuthError::AccountFrozen),
+        Err(e) => {
+            error!(error = %e, "Failed to check account freeze");
+            Err(AuthError::Unavailable)
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issued_token_round_trips_claims() {
+        let secret = generate_secret();
+        let token = issue_token(
+            &secret,
+            "ops@inheritx",
+            "admin",
+            std::time::Duration::from_secs(60),
+        )
+        .unwrap();
+
+        let decoded = decode::<Claims>(
+            &token,
+            &DecodingKey::from_secret(secret.as_ref()),
+            &Validation::new(Algorithm::HS256),
+        )
+        .unwrap();
+
+        assert_eq!(decoded.claims.sub, "ops@inheritx");
+        assert_eq!(decoded.claims.role, "admin");
+    }
+
+    #[test]
+    fn wallet_signature_verification_uses_stellar_address() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let address =
+            stellar_strkey::ed25519::PublicKey(signing_key.verifying_key().to_bytes()).to_string();
+        let signature = hex::encode(signing_key.sign(b"challenge").to_bytes());
+
+        assert!(verify_wallet_signature(&address, b"challenge", &signature));
+        assert!(!verify_wallet_signature(&address, b"other", &signature));
+        assert!(!verify_wallet_signature(
+            "GNOTANADDRESS",
+            b"challenge",
+            &signature
+        ));
+    }
+
+    #[test]
+    fn user_context_derives_wallet_address_from_public_key() {
+        let context = UserContext {
+            user_id: format!("0x{}", hex::encode([7u8; 32])),
+            role: "user".to_string(),
+            auth_level: AuthLevel::Standard,
+        };
+
+        let address = context.wallet_address().unwrap();
+        assert!(address.starts_with('G'));
+        assert_eq!(
+            stellar_strkey::ed25519::PublicKey::from_string(&address)
+                .unwrap()
+                .0,
+            [7u8; 32]
+        );
+    }
+
+    #[test]
+    fn generated_secrets_are_unique_and_hex() {
+        let a = generate_secret();
+        let b = generate_secret();
+
+        assert_eq!(a.len(), 64);
+        assert!(hex::decode(&a).is_ok());
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,500 @@
+//! Reconciles the inheritance contract's token balances with the books.
+//!
+//! Every run sums `funded_amount` over active plans per token and reads the
+//! contract's balance of that token by simulating the token's `balance`
+//! call, with `BALANCE_MONITOR_SOURCE_ACCOUNT` as the source. A token is
+//! mismatched when the two differ by more than its tolerance:
+//! `BALANCE_MONITOR_TOLERANCE_BPS` of the expected balance, and never less
+//! than `BALANCE_MONITOR_TOLERANCE_UNITS`. Each result is stored in
+//! `balance_checks` and published as `inheritx_balance_discrepancy`. When a
+//! token turns mismatched, the plans holding it are attached to an alert
+//! that is logged, emailed to `BALANCE_MONITOR_ALERT_EMAILS` and posted to
+//! `BALANCE_MONITOR_WEBHOOK_URL`.
+//!
+//! The platform keeps no loan book, so only funded plans make up the
+//! expected balance.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::types::Json as SqlJson;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::http_client::{HttpClient, HttpPolicy};
+use crate::jobs::JobRegistry;
+use crate::metrics::{BALANCE_CHECKS, BALANCE_DISCREPANCY};
+use crate::simulation::{build_envelope, summarize, ContractArg};
+
+const DEFAULT_INTERVAL_SECS: u64 = 15 * 60;
+const DEFAULT_TOLERANCE_BPS: u32 = 10;
+const BALANCE_MONITOR_LOCK_KEY: i64 = 840;
+/// Plans attached to an alert, largest first.
+const MAX_ENTITIES: i64 = 50;
+const RECENT_ALERTS: i64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStatus {
+    Balanced,
+    Mismatched,
+    /// The on-chain balance could not be read.
+    Unreadable,
+}
+
+impl BalanceStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Balanced => "balanced",
+            Self::Mismatched => "mismatched",
+            Self::Unreadable => "unreadable",
+        }
+    }
+}
+
+/// Largest difference accepted for a token expected to hold `expected`.
+pub fn tolerance(expected: Decimal, bps: u32, units: Decimal) -> Decimal {
+    (expected.abs() * Decimal::from(bps) / Decimal::from(10_000))
+        .trunc()
+        .max(units)
+}
+
+pub fn classify(expected: Decimal, on_chain: Decimal, tolerance: Decimal) -> BalanceStatus {
+    if (on_chain - expected).abs() > tolerance {
+        BalanceStatus::Mismatched
+    } else {
+        BalanceStatus::Balanced
+    }
+}
+
+/// Alerts are raised when a token turns mismatched, not on every run it
+/// stays so. `previous` is the last status that was not `unreadable`, so a
+/// flaky RPC does not repeat an alert.
+pub fn should_alert(previous: Option<&str>, current: BalanceStatus) -> bool {
+    current == BalanceStatus::Mismatched && previous != Some(BalanceStatus::Mismatched.as_str())
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone)]
+pub struct BalanceMonitorConfig {
+    pub interval: Duration,
+    /// Funded account the `balance` calls are simulated from; the monitor
+    /// does not run without it.
+    pub source_account: Option<String>,
+    pub tolerance_bps: u32,
+    /// Smallest tolerance, in token units.
+    pub tolerance_units: Decimal,
+    /// Addresses emailed about mismatches; alerts are only logged when
+    /// empty.
+    pub alert_recipients: Vec<String>,
+    pub webhook_url: Option<String>,
+}
+
+impl BalanceMonitorConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("BALANCE_MONITOR_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            source_account: std::env::var("BALANCE_MONITOR_SOURCE_ACCOUNT")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            tolerance_bps: parse_env("BALANCE_MONITOR_TOLERANCE_BPS", DEFAULT_TOLERANCE_BPS)
+                .min(10_000),
+            tolerance_units: Decimal::from(parse_env("BALANCE_MONITOR_TOLERANCE_UNITS", 0u64)),
+            alert_recipients: std::env::var("BALANCE_MONITOR_ALERT_EMAILS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            webhook_url: std::env::var("BALANCE_MONITOR_WEBHOOK_URL")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+/// A plan holding funds of a mismatched token.
+#[derive(Debug, Clone, Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct BalanceEntity {
+    pub plan_id: Uuid,
+    pub owner_address: String,
+    pub amount: Decimal,
+    pub funded_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BalanceCheck {
+    pub id: Uuid,
+    pub run_id: Uuid,
+    pub asset: String,
+    pub expected: Decimal,
+    pub on_chain: Option<Decimal>,
+    /// On-chain less expected.
+    pub difference: Option<Decimal>,
+    pub tolerance: Decimal,
+    pub status: String,
+    pub error: Option<String>,
+    pub entities: SqlJson<Vec<BalanceEntity>>,
+    pub alerted: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+pub struct BalanceMonitorService {
+    state: Arc<AppState>,
+    contract_id: String,
+    source_account: String,
+    config: BalanceMonitorConfig,
+    webhook: HttpClient,
+}
+
+impl BalanceMonitorService {
+    pub fn new(
+        state: Arc<AppState>,
+        contract_id: String,
+        source_account: String,
+        config: BalanceMonitorConfig,
+    ) -> Self {
+        Self {
+            state,
+            contract_id,
+            source_account,
+            config,
+            webhook: HttpClient::new(
+                "balance_monitor_webhook",
+                HttpPolicy {
+                    timeout: Duration::from_secs(10),
+                    ..HttpPolicy::default()
+                },
+            ),
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("balance_monitor", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker
+                    .run_once()
+                    .await
+                    .map(|checks| checks.map_or(0, |checks| checks.len()))
+            }
+        });
+    }
+
+    /// Checks every token held by an active plan. Returns the stored
+    /// checks, or `None` when another instance holds the lock.
+    pub async fn run_once(&self) -> Result<Option<Vec<BalanceCheck>>, sqlx::Error> {
+        let mut tx = self.state.db_pool.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(BALANCE_MONITOR_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Balance monitor lock is held by another worker; skipping run");
+            tx.commit().await?;
+            return Ok(None);
+        }
+
+        let expected: Vec<(String, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT token_address, SUM(funded_amount)
+            FROM plans
+            WHERE is_active
+            GROUP BY token_address
+            ORDER BY token_address
+            "#,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let run_id = Uuid::new_v4();
+        let mut checks = Vec::with_capacity(expected.len());
+        for (asset, expected) in expected {
+            let tolerance = tolerance(
+                expected,
+                self.config.tolerance_bps,
+                self.config.tolerance_units,
+            );
+            let (on_chain, status, read_error) = match self.on_chain_balance(&asset).await {
+                Ok(on_chain) => (
+                    Some(on_chain),
+                    classify(expected, on_chain, tolerance),
+                    None,
+                ),
+                Err(e) => {
+                    warn!(asset = %asset, error = %e, "Could not read on-chain balance");
+                    (None, BalanceStatus::Unreadable, Some(e))
+                }
+            };
+            let difference = on_chain.map(|on_chain| on_chain - expected);
+
+            let previous: Option<String> = sqlx::query_scalar(
+                r#"
+                SELECT status FROM balance_checks
+                WHERE asset = $1 AND status <> 'unreadable'
+                ORDER BY checked_at DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(&asset)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let entities = if status == BalanceStatus::Mismatched {
+                sqlx::query_as::<_, BalanceEntity>(
+                    r#"
+                    SELECT id AS plan_id, owner_address, amount, funded_amount
+                    FROM plans
+                    WHERE is_active AND token_address = $1 AND funded_amount > 0
+                    ORDER BY funded_amount DESC, id
+                    LIMIT $2
+                    "#,
+                )
+                .bind(&asset)
+                .bind(MAX_ENTITIES)
+                .fetch_all(&mut *tx)
+                .await?
+            } else {
+                Vec::new()
+            };
+            let alerted = should_alert(previous.as_deref(), status);
+
+            let check = sqlx::query_as::<_, BalanceCheck>(
+                r#"
+                INSERT INTO balance_checks
+                    (run_id, asset, expected, on_chain, difference, tolerance, status, error,
+                     entities, alerted)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING id, run_id, asset, expected, on_chain, difference, tolerance, status,
+                          error, entities, alerted, checked_at
+                "#,
+            )
+            .bind(run_id)
+            .bind(&asset)
+            .bind(expected)
+            .bind(on_chain)
+            .bind(difference)
+            .bind(tolerance)
+            .bind(status.as_str())
+            .bind(&read_error)
+            .bind(SqlJson(&entities))
+            .bind(alerted)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            BALANCE_CHECKS.with_label_values(&[status.as_str()]).inc();
+            if let Some(difference) = difference {
+                BALANCE_DISCREPANCY
+                    .with_label_values(&[asset.as_str()])
+                    .set(f64::try_from(difference).unwrap_or(f64::NAN));
+            }
+            if status == BalanceStatus::Balanced
+                && previous.as_deref() == Some(BalanceStatus::Mismatched.as_str())
+            {
+                info!(asset = %asset, "On-chain balance is back within tolerance");
+            }
+            checks.push(check);
+        }
+
+        tx.commit().await?;
+
+        for check in checks.iter().filter(|c| c.alerted) {
+            self.alert(check).await;
+        }
+        Ok(Some(checks))
+    }
+
+    /// The inheritance contract's balance of `token`, read by simulating
+    /// the token's `balance` call.
+    async fn on_chain_balance(&self, token: &str) -> Result<Decimal, String> {
+        let envelope = build_envelope(
+            &self.source_account,
+            token,
+            "balance",
+            &[ContractArg::Address(self.contract_id.clone())],
+        )?;
+        let response = self
+            .state
+            .soroban_rpc
+            .simulate_transaction(&envelope)
+            .await
+            .map_err(|e| format!("Simulation failed: {e}"))?;
+        let result = summarize(&response, None);
+        if let Some(error) = result.error {
+            return Err(error.detail);
+        }
+        result
+            .return_value
+            .as_ref()
+            .and_then(|value| value.as_str())
+            .and_then(|value| Decimal::from_str(value).ok())
+            .ok_or_else(|| "balance did not return an integer".to_string())
+    }
+
+    async fn alert(&self, check: &BalanceCheck) {
+        error!(
+            asset = %check.asset,
+            expected = %check.expected,
+            on_chain = ?check.on_chain,
+            tolerance = %check.tolerance,
+            plans = check.entities.len(),
+            "On-chain balance does not match funded plans"
+        );
+
+        let on_chain = check.on_chain.unwrap_or_default();
+        let subject = format!("InheritX balance mismatch: {}", check.asset);
+        let mut body = format!(
+            "The inheritance contract holds {on_chain} of {} but funded plans account for {} \
+             (difference {}, tolerance {}).\n\nPlans holding it, largest first:\n\n",
+            check.asset,
+            check.expected,
+            on_chain - check.expected,
+            check.tolerance,
+        );
+        for entity in check.entities.iter() {
+            body.push_str(&format!(
+                "- plan {} (owner {}): funded {} of {}\n",
+                entity.plan_id, entity.owner_address, entity.funded_amount, entity.amount
+            ));
+        }
+        body.push_str("\nReview the latest checks at GET /api/admin/balance-checks.\n");
+
+        for recipient in &self.config.alert_recipients {
+            if let Err(e) = self.state.mailer.send(recipient, &subject, &body).await {
+                warn!(recipient = %recipient, error = %e, "Failed to email balance mismatch alert");
+            }
+        }
+
+        if let Some(url) = &self.config.webhook_url {
+            let payload = serde_json::json!({
+                "event": "balance_mismatch",
+                "check": check,
+            });
+            match self
+                .webhook
+                .send(self.webhook.post(url.as_str()).json(&payload))
+                .await
+            {
+                Ok(response) if !response.status().is_success() => {
+                    warn!(status = %response.status(), "Balance mismatch webhook was refused");
+                }
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "Failed to post balance mismatch webhook"),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BalanceChecksResponse {
+    /// The latest check of every token.
+    latest: Vec<BalanceCheck>,
+    recent_alerts: Vec<BalanceCheck>,
+}
+
+const CHECK_COLUMNS: &str = "id, run_id, asset, expected, on_chain, difference, tolerance, \
+                             status, error, entities, alerted, checked_at";
+
+// Handler: Balance Checks (admin)
+pub async fn get_balance_checks(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let result: Result<BalanceChecksResponse, sqlx::Error> = async {
+        let latest = sqlx::query_as::<_, BalanceCheck>(&format!(
+            "SELECT DISTINCT ON (asset) {CHECK_COLUMNS} FROM balance_checks \
+             ORDER BY asset, checked_at DESC"
+        ))
+        .fetch_all(&state.db_pool)
+        .await?;
+        let recent_alerts = sqlx::query_as::<_, BalanceCheck>(&format!(
+            "SELECT {CHECK_COLUMNS} FROM balance_checks WHERE alerted \
+             ORDER BY checked_at DESC LIMIT $1"
+        ))
+        .bind(RECENT_ALERTS)
+        .fetch_all(&state.db_pool)
+        .await?;
+        Ok(BalanceChecksResponse {
+            latest,
+            recent_alerts,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(checks) => (StatusCode::OK, Json(checks)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to read balance checks");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerance_is_a_share_of_expected_with_a_floor() {
+        assert_eq!(
+            tolerance(Decimal::from(1_000_000), 10, Decimal::ZERO),
+            Decimal::from(1_000)
+        );
+        assert_eq!(
+            tolerance(Decimal::from(1_000_000), 10, Decimal::from(5_000)),
+            Decimal::from(5_000)
+        );
+        assert_eq!(
+            tolerance(Decimal::from(999), 10, Decimal::ZERO),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn mismatch_is_beyond_tolerance_either_way() {
+        let expected = Decimal::from(1_000);
+        let tol = Decimal::from(10);
+        assert_eq!(
+            classify(expected, Decimal::from(1_010), tol),
+            BalanceStatus::Balanced
+        );
+        assert_eq!(
+            classify(expected, Decimal::from(990), tol),
+            BalanceStatus::Balanced
+        );
+        assert_eq!(
+            classify(expected, Decimal::from(1_011), tol),
+            BalanceStatus::Mismatched
+        );
+        assert_eq!(
+            classify(expected, Decimal::from(989), tol),
+            BalanceStatus::Mismatched
+        );
+    }
+
+    #[test]
+    fn alerts_only_when_turning_mismatched() {
+        assert!(should_alert(None, BalanceStatus::Mismatched));
+        assert!(should_alert(Some("balanced"), BalanceStatus::Mismatched));
+        assert!(!should_alert(Some("mismatched"), BalanceStatus::Mismatched));
+        assert!(!should_alert(None, BalanceStatus::Unreadable));
+        assert!(!should_alert(Some("mismatched"), BalanceStatus::Balanced));
+    }
+}
@@ -0,0 +1,322 @@
+//! Operations CLI for the InheritX backend.
+//!
+//! Every command goes through the same library code the HTTP server uses, so
+//! ops teams can administer an environment without direct database access.
+
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use inheritx_backend::chain::signer::LocalKeystoreSigner;
+use inheritx_backend::db::MigrationPhase;
+use inheritx_backend::field_crypto::{self, EncryptionKey, FieldCipher};
+use inheritx_backend::{
+    auth, kyc_webhook, plan_events, BackupConfig, BackupService, Config, DbManager,
+    InactivityWatchdogConfig, InactivityWatchdogService, PlanCache,
+};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(name = "inheritx-cli", about = "InheritX backend administration tool")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Issue an admin JWT for the given subject.
+    CreateAdmin {
+        /// Identifier recorded as the token subject (e.g. an email address).
+        subject: String,
+        /// Token lifetime in hours.
+        #[arg(long, default_value_t = 24)]
+        ttl_hours: u64,
+        /// Issue a token for an organization admin, which stops working
+        /// when they leave the organization.
+        #[arg(long)]
+        organization_member: bool,
+    },
+    /// Generate a new JWT signing secret to roll out as `JWT_SECRET`.
+    RotateJwtSecret,
+    /// Apply or revert database migrations.
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// Run one inactivity watchdog sweep, marking overdue plans claimable.
+    Reconcile,
+    /// Replay plan events and compare them with the `plans` and
+    /// `beneficiaries` tables.
+    RebuildPlans {
+        /// Only this plan; every plan with events otherwise.
+        #[arg(long)]
+        plan_id: Option<Uuid>,
+        /// Rewrite drifted plans from their events instead of only reporting them.
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Re-apply a KYC webhook previously recorded in `kyc_webhook_logs`.
+    ReplayWebhook {
+        /// Id of the `kyc_webhook_logs` row to replay.
+        log_id: Uuid,
+    },
+    /// Generate a new field encryption key to prepend to `FIELD_ENCRYPTION_KEYS`.
+    RotateFieldKey {
+        /// Identifier stored alongside every value sealed with the key.
+        #[arg(long)]
+        id: String,
+    },
+    /// Re-encrypt sensitive fields that are plaintext or under an old key.
+    ReencryptFields {
+        #[arg(long, default_value_t = 500)]
+        batch_size: i64,
+    },
+    /// Generate a transaction signing key in a keystore sealed with the
+    /// field encryption keys.
+    CreateSignerKey {
+        /// Key identifier logged with every signature.
+        #[arg(long)]
+        id: String,
+        /// Keystore file to write; must not exist.
+        #[arg(long)]
+        out: std::path::PathBuf,
+    },
+    /// Take, upload and verify a backup now.
+    Backup,
+    /// Restore the latest verified backup taken at or before a time into a
+    /// new schema.
+    RestoreBackup {
+        /// RFC 3339 time to restore to; the latest backup by default.
+        #[arg(long)]
+        as_of: Option<chrono::DateTime<chrono::Utc>>,
+        /// Schema to create; `restore_<backup time>` by default.
+        #[arg(long)]
+        schema: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateAction {
+    /// Apply all pending migrations of a phase.
+    Run {
+        /// `pre-deploy` before a release starts, `post-deploy` once every
+        /// instance runs it.
+        #[arg(long, value_enum, default_value_t = Phase::PreDeploy)]
+        phase: Phase,
+    },
+    /// Show pending migrations and whether this release can run against the schema.
+    Status,
+    /// Revert the most recent reversible migrations.
+    Rollback {
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Phase {
+    PreDeploy,
+    PostDeploy,
+}
+
+impl From<Phase> for MigrationPhase {
+    fn from(phase: Phase) -> Self {
+        match phase {
+            Phase::PreDeploy => Self::PreDeploy,
+            Phase::PostDeploy => Self::PostDeploy,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::CreateAdmin {
+            subject,
+            ttl_hours,
+            organization_member,
+        } => {
+            let config = Config::load()?;
+            let role = if organization_member {
+                auth::ORGANIZATION_MEMBER_ROLE
+            } else {
+                "admin"
+            };
+            let token = auth::issue_token(
+                &config.jwt_secret,
+                &subject,
+                role,
+                Duration::from_secs(ttl_hours * 3600),
+            )?;
+            println!("{token}");
+        }
+        Command::RotateJwtSecret => {
+            println!("{}", auth::generate_secret());
+            eprintln!("Set this value as JWT_SECRET and restart the backend; existing tokens will be rejected.");
+        }
+        Command::Migrate { action } => {
+            let config = Config::load()?;
+            let pool = DbManager::create_pool(&config.database_url).await?;
+            match action {
+                MigrateAction::Run {
+                    phase: Phase::PreDeploy,
+                } => {
+                    DbManager::run_migrations(&pool).await?;
+                    println!("Pre-deploy migrations applied");
+                }
+                MigrateAction::Run {
+                    phase: Phase::PostDeploy,
+                } => {
+                    // Contracting the schema under an older release would break it.
+                    DbManager::check_schema_compatibility(&pool).await?;
+                    DbManager::run_phase(&pool, MigrationPhase::PostDeploy).await?;
+                    println!("Post-deploy migrations applied");
+                }
+                MigrateAction::Status => {
+                    let status = DbManager::schema_status(&pool).await?;
+                    println!("pending pre-deploy:  {:?}", status.pending_pre_deploy);
+                    println!("pending post-deploy: {:?}", status.pending_post_deploy);
+                    println!("newer pre-deploy:    {:?}", status.newer_pre_deploy);
+                    println!("newer post-deploy:   {:?}", status.newer_post_deploy);
+                    match status.check() {
+                        Ok(()) => println!("compatible: yes"),
+                        Err(e) => println!("compatible: no ({e})"),
+                    }
+                }
+                MigrateAction::Rollback { steps } => {
+                    DbManager::rollback_migrations(&pool, steps).await?;
+                    println!("Rolled back {steps} migration(s)");
+                }
+            }
+        }
+        Command::Reconcile => {
+            let config = Config::load()?;
+            let pool = DbManager::create_pool(&config.database_url).await?;
+            let plan_cache =
+                PlanCache::from_redis_url(config.redis_url.as_deref(), config.plan_cache_ttl_secs)
+                    .unwrap_or_else(|_| PlanCache::disabled());
+            let watchdog = InactivityWatchdogService::new(
+                pool,
+                plan_cache,
+                std::sync::Arc::new(inheritx_backend::emergency_contacts::ContactNotifier::new(
+                    std::sync::Arc::new(inheritx_backend::mailer::Mailer::new(
+                        inheritx_backend::mailer::MailerConfig::from_env(),
+                    )),
+                    std::sync::Arc::new(inheritx_backend::sms::SmsClient::new(
+                        inheritx_backend::sms::SmsConfig::from_env(),
+                    )),
+                )),
+                InactivityWatchdogConfig::from_env(),
+            );
+            let count = watchdog.run_once().await?;
+            println!("Marked {count} plan(s) as claimable");
+        }
+        Command::RebuildPlans { plan_id, apply } => {
+            let config = Config::load()?;
+            let pool = DbManager::create_pool(&config.database_url).await?;
+            let report = plan_events::rebuild(&pool, plan_id, apply).await?;
+            println!("Replayed {} plan(s)", report.plans_checked);
+            for id in &report.drifted {
+                println!("drifted: {id}");
+            }
+            for id in &report.deleted_in_stream {
+                println!("deleted in events but still stored: {id}");
+            }
+            if apply {
+                println!("Rebuilt {} plan(s) from their events", report.repaired);
+            } else if !report.drifted.is_empty() {
+                eprintln!("Run again with --apply to rewrite the drifted plans from their events.");
+            }
+        }
+        Command::ReplayWebhook { log_id } => {
+            let config = Config::load()?;
+            let pool = DbManager::create_pool(&config.database_url).await?;
+            let raw_payload = kyc_webhook::load_logged_payload(&pool, log_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("No webhook log with id {log_id}"))?;
+            let payload: kyc_webhook::KycWebhookPayload =
+                serde_json::from_value(raw_payload.clone())?;
+
+            // No WebSocket subscribers exist in this process; the broadcast is a no-op.
+            let (kyc_tx, _) = tokio::sync::broadcast::channel(1);
+            kyc_webhook::process_kyc_event(&pool, &kyc_tx, &payload, &raw_payload).await?;
+            println!(
+                "Replayed webhook {log_id}: {} is now {}",
+                payload.wallet_address,
+                payload.status.as_db_str()
+            );
+        }
+        Command::RotateFieldKey { id } => {
+            println!("{}", EncryptionKey::generate(&id));
+            eprintln!("Prepend this value to FIELD_ENCRYPTION_KEYS, keep the old keys after it, restart, then run reencrypt-fields.");
+        }
+        Command::ReencryptFields { batch_size } => {
+            let config = Config::load()?;
+            if config.field_encryption_keys.is_empty() {
+                anyhow::bail!("FIELD_ENCRYPTION_KEYS is not set");
+            }
+            let pool = DbManager::create_pool(&config.database_url).await?;
+            let cipher = FieldCipher::from_keys(&config.field_encryption_keys);
+            let count = field_crypto::reencrypt_beneficiaries(&pool, &cipher, batch_size).await?;
+            println!("Re-encrypted {count} beneficiary record(s)");
+        }
+        Command::CreateSignerKey { id, out } => {
+            let config = Config::load()?;
+            let cipher = FieldCipher::from_keys(&config.field_encryption_keys);
+            let keystore = LocalKeystoreSigner::generate(&id, &cipher)?;
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&out)
+                .and_then(|mut file| {
+                    std::io::Write::write_all(
+                        &mut file,
+                        serde_json::to_string_pretty(&keystore)?.as_bytes(),
+                    )
+                })?;
+            println!("{}", keystore.public_key);
+            eprintln!("Wrote {}. Add this account as a signer on-chain before pointing SIGNER_KEYSTORE_PATH at it.", out.display());
+        }
+        Command::Backup => {
+            let backups = backup_service().await?;
+            match backups.backup_now().await? {
+                Some(backup) => match backup.error {
+                    None => println!(
+                        "Backup {} verified: {}",
+                        backup.id,
+                        backup.object_key.unwrap_or_default()
+                    ),
+                    Some(error) => anyhow::bail!("Backup {} failed: {error}", backup.id),
+                },
+                None => anyhow::bail!("Another backup is running"),
+            }
+        }
+        Command::RestoreBackup { as_of, schema } => {
+            let backups = backup_service().await?;
+            let restored = backups
+                .restore(as_of.unwrap_or_else(chrono::Utc::now), schema)
+                .await?;
+            println!(
+                "Restored backup {} taken at {} into schema {}",
+                restored.backup_id, restored.taken_at, restored.schema
+            );
+            for (table, rows) in &restored.row_counts {
+                println!("{table}: {rows} row(s)");
+            }
+            eprintln!("Compare or copy rows back into public, then drop the schema.");
+        }
+    }
+
+    Ok(())
+}
+
+async fn backup_service() -> anyhow::Result<BackupService> {
+    let config = Config::load()?;
+    let pool = DbManager::create_pool(&config.database_url).await?;
+    BackupService::new(pool, config.database_url.clone(), BackupConfig::from_env()).ok_or_else(
+        || anyhow::anyhow!("Set BACKUP_S3_ENDPOINT, BACKUP_S3_BUCKET and BACKUP_ENCRYPTION_KEYS"),
+    )
+}
@@ -0,0 +1,554 @@
+//! Load-test harness for the plan listing, claim and wallet re-auth paths.
+//!
+//! `seed` writes owners with plan portfolios and heirs with claimable plans
+//! and re-auth turned on into the database at `DATABASE_URL`, and saves the
+//! heirs' signing keys to a fixtures file. `run` drives a running server at
+//! a fixed request rate from those fixtures, writes a JSON report and exits
+//! non-zero when the budget in `loadtest/budget.toml` is not met.
+
+#[path = "../../tests/factory/mod.rs"]
+mod factory;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use ed25519_dalek::{Signer, SigningKey};
+use factory::{PlanFactory, UserFactory};
+use inheritx_backend::db::MigrationPhase;
+use inheritx_backend::{Config, DbManager};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(name = "loadtest", about = "Load-test an InheritX backend")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Seed plans, heirs and re-auth settings for a load test.
+    Seed {
+        /// Plan owners to create; plan listing requests rotate through them.
+        #[arg(long, default_value_t = 20)]
+        owners: usize,
+        /// Active plans created for each owner.
+        #[arg(long, default_value_t = 10)]
+        plans_per_owner: usize,
+        /// Heirs to create, each named on its own claimable plan.
+        #[arg(long, default_value_t = 200)]
+        heirs: usize,
+        #[arg(long, default_value = "loadtest/fixtures.json")]
+        fixtures: PathBuf,
+    },
+    /// Run the scenarios against a server and check the budget.
+    Run {
+        #[arg(long, default_value = "http://localhost:3001")]
+        base_url: String,
+        #[arg(long, default_value = "loadtest/budget.toml")]
+        budget: PathBuf,
+        #[arg(long, default_value = "loadtest/fixtures.json")]
+        fixtures: PathBuf,
+        #[arg(long, default_value = "loadtest/report.json")]
+        report: PathBuf,
+        /// Overrides `duration_secs` from the budget, e.g. for a smoke run.
+        #[arg(long)]
+        duration_secs: Option<u64>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixtures {
+    owners: Vec<String>,
+    heirs: Vec<HeirFixture>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HeirFixture {
+    /// Hex-encoded ed25519 secret key.
+    secret_key: String,
+    plan_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct Budget {
+    target_rps: u32,
+    duration_secs: u64,
+    concurrency: usize,
+    min_rps_ratio: f64,
+    scenarios: BTreeMap<String, ScenarioBudget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScenarioBudget {
+    weight: u32,
+    p95_ms: f64,
+    p99_ms: f64,
+    max_error_rate: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scenario {
+    PlanList,
+    ReauthChallenge,
+    ClaimSubmit,
+}
+
+impl Scenario {
+    const ALL: [Self; 3] = [Self::PlanList, Self::ReauthChallenge, Self::ClaimSubmit];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::PlanList => "plan_list",
+            Self::ReauthChallenge => "reauth_challenge",
+            Self::ClaimSubmit => "claim_submit",
+        }
+    }
+}
+
+struct Sample {
+    scenario: Scenario,
+    latency: Duration,
+    ok: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ScenarioReport {
+    requests: usize,
+    errors: usize,
+    error_rate: f64,
+    rps: f64,
+    p50_ms: f64,
+    p95_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    target_rps: u32,
+    achieved_rps: f64,
+    duration_secs: f64,
+    scenarios: BTreeMap<&'static str, ScenarioReport>,
+    violations: Vec<String>,
+    passed: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    match Cli::parse().command {
+        Command::Seed {
+            owners,
+            plans_per_owner,
+            heirs,
+            fixtures,
+        } => seed(owners, plans_per_owner, heirs, &fixtures).await,
+        Command::Run {
+            base_url,
+            budget,
+            fixtures,
+            report,
+            duration_secs,
+        } => {
+            let mut budget: Budget = toml::from_str(
+                &std::fs::read_to_string(&budget)
+                    .with_context(|| format!("reading {}", budget.display()))?,
+            )?;
+            if let Some(secs) = duration_secs {
+                budget.duration_secs = secs;
+            }
+            let fixtures: Fixtures = serde_json::from_str(
+                &std::fs::read_to_string(&fixtures)
+                    .with_context(|| format!("reading {}", fixtures.display()))?,
+            )?;
+            let result = run(&base_url, &budget, fixtures).await?;
+            std::fs::write(&report, serde_json::to_string_pretty(&result)?)?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+            if !result.passed {
+                anyhow::bail!(
+                    "performance budget not met: {}",
+                    result.violations.join("; ")
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn seed(
+    owners: usize,
+    plans_per_owner: usize,
+    heirs: usize,
+    fixtures_path: &Path,
+) -> anyhow::Result<()> {
+    if owners == 0 {
+        anyhow::bail!("--owners must be at least 1");
+    }
+    let config = Config::load()?;
+    if config.environment.is_deployed() {
+        anyhow::bail!(
+            "refusing to seed a {} database",
+            config.environment.as_str()
+        );
+    }
+    let pool = DbManager::create_pool(&config.database_url).await?;
+    DbManager::run_migrations(&pool).await?;
+    DbManager::run_phase(&pool, MigrationPhase::PostDeploy).await?;
+
+    let mut fixtures = Fixtures {
+        owners: Vec::with_capacity(owners),
+        heirs: Vec::with_capacity(heirs),
+    };
+    for _ in 0..owners {
+        let owner = UserFactory::new()
+            .kyc_status("approved")
+            .insert(&pool)
+            .await?;
+        for _ in 0..plans_per_owner {
+            PlanFactory::new()
+                .owner(&owner.wallet_address)
+                .insert(&pool)
+                .await?;
+        }
+        fixtures.owners.push(owner.wallet_address);
+    }
+
+    for i in 0..heirs {
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let address =
+            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
+        UserFactory::new()
+            .wallet_address(&address)
+            .kyc_status("approved")
+            .insert(&pool)
+            .await?;
+        let plan = PlanFactory::new()
+            .owner(&fixtures.owners[i % owners])
+            .beneficiary(&address, 10_000)
+            .claimable()
+            .insert(&pool)
+            .await?;
+        sqlx::query(
+            "INSERT INTO wallet_reauth_settings (wallet_address, enabled) VALUES ($1, true)",
+        )
+        .bind(&address)
+        .execute(&pool)
+        .await?;
+        fixtures.heirs.push(HeirFixture {
+            secret_key: hex::encode(key.to_bytes()),
+            plan_id: plan.id(),
+        });
+    }
+
+    std::fs::write(fixtures_path, serde_json::to_string_pretty(&fixtures)?)?;
+    println!(
+        "seeded {} owners with {} plans each and {} heirs; fixtures in {}",
+        owners,
+        plans_per_owner,
+        heirs,
+        fixtures_path.display()
+    );
+    Ok(())
+}
+
+struct Heir {
+    key: SigningKey,
+    plan_id: Uuid,
+}
+
+struct Driver {
+    client: reqwest::Client,
+    base_url: String,
+    owners: Vec<String>,
+    heirs: Vec<Heir>,
+    next_owner: AtomicUsize,
+    next_heir: AtomicUsize,
+}
+
+impl Driver {
+    fn owner(&self) -> &str {
+        &self.owners[self.next_owner.fetch_add(1, Ordering::Relaxed) % self.owners.len()]
+    }
+
+    fn heir(&self) -> &Heir {
+        &self.heirs[self.next_heir.fetch_add(1, Ordering::Relaxed) % self.heirs.len()]
+    }
+
+    /// Sends `body` with the signature headers the wallet routes expect.
+    async fn signed_post(
+        &self,
+        key: &SigningKey,
+        path: &str,
+        body: serde_json::Value,
+    ) -> reqwest::Result<reqwest::Response> {
+        let body = body.to_string();
+        let signature = key.sign(body.as_bytes());
+        self.client
+            .post(format!("{}{path}", self.base_url))
+            .header("Content-Type", "application/json")
+            .header("X-Public-Key", hex::encode(key.verifying_key().to_bytes()))
+            .header("X-Signature", hex::encode(signature.to_bytes()))
+            .body(body)
+            .send()
+            .await
+    }
+
+    async fn plan_list(&self) -> bool {
+        let response = self
+            .client
+            .get(format!("{}/api/plans", self.base_url))
+            .query(&[("owner", self.owner())])
+            .send()
+            .await;
+        matches!(response, Ok(r) if r.status() == reqwest::StatusCode::OK)
+    }
+
+    /// Requests a claim challenge and returns it signed.
+    async fn challenge(&self, heir: &Heir) -> Option<serde_json::Value> {
+        let response = self
+            .signed_post(
+                &heir.key,
+                "/api/reauth/challenges",
+                serde_json::json!({ "action": "claim", "plan_id": heir.plan_id }),
+            )
+            .await
+            .ok()?;
+        if response.status() != reqwest::StatusCode::CREATED {
+            return None;
+        }
+        let challenge: serde_json::Value = response.json().await.ok()?;
+        let message = challenge["message"].as_str()?;
+        Some(serde_json::json!({
+            "challenge_id": challenge["challenge_id"],
+            "signature": hex::encode(heir.key.sign(message.as_bytes()).to_bytes()),
+        }))
+    }
+
+    async fn claim(&self, heir: &Heir, confirmation: serde_json::Value) -> bool {
+        let response = self
+            .signed_post(
+                &heir.key,
+                &format!("/api/plans/{}/claim", heir.plan_id),
+                serde_json::json!({ "confirmation": confirmation }),
+            )
+            .await;
+        matches!(
+            response,
+            Ok(r) if matches!(r.status(), reqwest::StatusCode::ACCEPTED | reqwest::StatusCode::CONFLICT)
+        )
+    }
+
+    async fn iterate(&self, scenario: Scenario, samples: &mpsc::UnboundedSender<Sample>) {
+        let record = |scenario, started: Instant, ok| {
+            let _ = samples.send(Sample {
+                scenario,
+                latency: started.elapsed(),
+                ok,
+            });
+        };
+
+        match scenario {
+            Scenario::PlanList => {
+                let started = Instant::now();
+                let ok = self.plan_list().await;
+                record(Scenario::PlanList, started, ok);
+            }
+            Scenario::ReauthChallenge => {
+                let started = Instant::now();
+                let ok = self.challenge(self.heir()).await.is_some();
+                record(Scenario::ReauthChallenge, started, ok);
+            }
+            Scenario::ClaimSubmit => {
+                let heir = self.heir();
+                let started = Instant::now();
+                let confirmation = self.challenge(heir).await;
+                record(Scenario::ReauthChallenge, started, confirmation.is_some());
+                let Some(confirmation) = confirmation else {
+                    record(Scenario::ClaimSubmit, Instant::now(), false);
+                    return;
+                };
+                let started = Instant::now();
+                let ok = self.claim(heir, confirmation).await;
+                record(Scenario::ClaimSubmit, started, ok);
+            }
+        }
+    }
+}
+
+/// Scenario for the `n`th iteration, spread by the budget weights.
+fn pick(schedule: &[Scenario], n: usize) -> Scenario {
+    schedule[n % schedule.len()]
+}
+
+fn schedule(budget: &Budget) -> Vec<Scenario> {
+    Scenario::ALL
+        .into_iter()
+        .flat_map(|scenario| {
+            let weight = budget
+                .scenarios
+                .get(scenario.as_str())
+                .map_or(0, |s| s.weight);
+            std::iter::repeat_n(scenario, weight as usize)
+        })
+        .collect()
+}
+
+async fn run(base_url: &str, budget: &Budget, fixtures: Fixtures) -> anyhow::Result<Report> {
+    if fixtures.owners.is_empty() || fixtures.heirs.is_empty() {
+        anyhow::bail!("fixtures need at least one owner and one heir; run `loadtest seed` first");
+    }
+    let schedule = schedule(budget);
+    if schedule.is_empty() || budget.target_rps == 0 {
+        anyhow::bail!("budget needs a target_rps and at least one weighted scenario");
+    }
+
+    let heirs = fixtures
+        .heirs
+        .into_iter()
+        .map(|heir| {
+            let bytes: [u8; 32] = hex::decode(&heir.secret_key)?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("secret keys must be 32 bytes"))?;
+            Ok(Heir {
+                key: SigningKey::from_bytes(&bytes),
+                plan_id: heir.plan_id,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let driver = Arc::new(Driver {
+        client: reqwest::Client::builder()
+            .pool_max_idle_per_host(budget.concurrency)
+            .timeout(Duration::from_secs(10))
+            .build()?,
+        base_url: base_url.trim_end_matches('/').to_string(),
+        owners: fixtures.owners,
+        heirs,
+        next_owner: AtomicUsize::new(0),
+        next_heir: AtomicUsize::new(0),
+    });
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let collector = tokio::spawn(async move {
+        let mut samples = Vec::new();
+        while let Some(sample) = rx.recv().await {
+            samples.push(sample);
+        }
+        samples
+    });
+
+    // Open model: one iteration per tick whether or not earlier ones have
+    // finished, bounded by `concurrency` requests in flight.
+    let in_flight = Arc::new(Semaphore::new(budget.concurrency.max(1)));
+    let mut ticker =
+        tokio::time::interval(Duration::from_secs_f64(1.0 / f64::from(budget.target_rps)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Burst);
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(budget.duration_secs);
+    let mut iteration = 0;
+    while Instant::now() < deadline {
+        ticker.tick().await;
+        let permit = in_flight.clone().acquire_owned().await?;
+        let driver = driver.clone();
+        let tx = tx.clone();
+        let scenario = pick(&schedule, iteration);
+        iteration += 1;
+        tokio::spawn(async move {
+            driver.iterate(scenario, &tx).await;
+            drop(permit);
+        });
+    }
+    let elapsed = started.elapsed();
+    drop(tx);
+    let samples = collector.await?;
+
+    Ok(evaluate(budget, &samples, elapsed))
+}
+
+fn percentile_ms(sorted: &[Duration], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1].as_secs_f64() * 1000.0
+}
+
+fn evaluate(budget: &Budget, samples: &[Sample], elapsed: Duration) -> Report {
+    let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+    let mut scenarios = BTreeMap::new();
+    let mut violations = Vec::new();
+
+    for scenario in Scenario::ALL {
+        let mut latencies: Vec<Duration> = samples
+            .iter()
+            .filter(|s| s.scenario == scenario)
+            .map(|s| s.latency)
+            .collect();
+        latencies.sort();
+        let errors = samples
+            .iter()
+            .filter(|s| s.scenario == scenario && !s.ok)
+            .count();
+        let requests = latencies.len();
+        let report = ScenarioReport {
+            requests,
+            errors,
+            error_rate: if requests == 0 {
+                0.0
+            } else {
+                errors as f64 / requests as f64
+            },
+            rps: requests as f64 / secs,
+            p50_ms: percentile_ms(&latencies, 50.0),
+            p95_ms: percentile_ms(&latencies, 95.0),
+            p99_ms: percentile_ms(&latencies, 99.0),
+            max_ms: latencies.last().map_or(0.0, |l| l.as_secs_f64() * 1000.0),
+        };
+
+        if let Some(limits) = budget.scenarios.get(scenario.as_str()) {
+            let name = scenario.as_str();
+            if report.p95_ms > limits.p95_ms {
+                violations.push(format!(
+                    "{name} p95 {:.1}ms over {}ms",
+                    report.p95_ms, limits.p95_ms
+                ));
+            }
+            if report.p99_ms > limits.p99_ms {
+                violations.push(format!(
+                    "{name} p99 {:.1}ms over {}ms",
+                    report.p99_ms, limits.p99_ms
+                ));
+            }
+            if report.error_rate > limits.max_error_rate {
+                violations.push(format!(
+                    "{name} error rate {:.3} over {}",
+                    report.error_rate, limits.max_error_rate
+                ));
+            }
+        }
+        scenarios.insert(scenario.as_str(), report);
+    }
+
+    let achieved_rps = samples.len() as f64 / secs;
+    let floor = f64::from(budget.target_rps) * budget.min_rps_ratio;
+    if achieved_rps < floor {
+        violations.push(format!("achieved {achieved_rps:.1} rps, below {floor:.1}"));
+    }
+
+    Report {
+        target_rps: budget.target_rps,
+        achieved_rps,
+        duration_secs: secs,
+        scenarios,
+        passed: violations.is_empty(),
+        violations,
+    }
+}
@@ -0,0 +1,96 @@
+//! Fills a local database with demo data: owners with plans in every state,
+//! pending claims and notifications. Uses the same builders as the
+//! integration tests (`tests/factory`).
+
+#[path = "../../tests/factory/mod.rs"]
+mod factory;
+
+use clap::Parser;
+use factory::{
+    wallet_address, AdminFactory, ClaimFactory, NotificationFactory, PlanFactory, UserFactory,
+};
+use inheritx_backend::db::MigrationPhase;
+use inheritx_backend::field_crypto::{FieldCipher, SensitiveField};
+use inheritx_backend::{Config, DbManager};
+
+#[derive(Parser)]
+#[command(name = "seed", about = "Seed a local InheritX database with demo data")]
+struct Args {
+    /// Number of plan owners to create; each gets an active and a claimable plan.
+    #[arg(long, default_value_t = 5)]
+    owners: usize,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let args = Args::parse();
+    let config = Config::load()?;
+    if config.environment.is_deployed() {
+        anyhow::bail!(
+            "refusing to seed a {} database",
+            config.environment.as_str()
+        );
+    }
+
+    let pool = DbManager::create_pool(&config.database_url).await?;
+    DbManager::run_migrations(&pool).await?;
+    DbManager::run_phase(&pool, MigrationPhase::PostDeploy).await?;
+    let cipher = FieldCipher::from_keys(&config.field_encryption_keys);
+    let anchor_info = cipher.encrypt(
+        SensitiveField::BeneficiaryAnchorInfo,
+        r#"{"account_name":"Demo Heir","bank":"Demo Bank","account_number":"0123456789"}"#,
+    )?;
+
+    for i in 0..args.owners {
+        let owner = UserFactory::new()
+            .kyc_status(if i % 2 == 0 { "approved" } else { "pending" })
+            .insert(&pool)
+            .await?;
+        let heir = UserFactory::new()
+            .kyc_status("approved")
+            .insert(&pool)
+            .await?;
+
+        let active = PlanFactory::new()
+            .owner(&owner.wallet_address)
+            .earning_yield(500)
+            .beneficiary(&heir.wallet_address, 6_000)
+            .fiat_beneficiary(&wallet_address(), 4_000, &anchor_info)
+            .insert(&pool)
+            .await?;
+        let claimable = PlanFactory::new()
+            .owner(&owner.wallet_address)
+            .beneficiary(&heir.wallet_address, 10_000)
+            .claimable()
+            .insert(&pool)
+            .await?;
+        ClaimFactory::new(claimable.id(), &heir.wallet_address)
+            .insert(&pool)
+            .await?;
+
+        NotificationFactory::new(&heir.wallet_address)
+            .notification_type("plan_claimable")
+            .title("A plan is ready to claim")
+            .message("An inheritance plan naming you is now claimable.")
+            .metadata(serde_json::json!({ "plan_id": claimable.id() }))
+            .insert(&pool)
+            .await?;
+
+        println!(
+            "owner {} -> plans {} (active), {} (claimable, claim pending by {})",
+            owner.wallet_address,
+            active.id(),
+            claimable.id(),
+            heir.wallet_address
+        );
+    }
+
+    println!(
+        "admin token: {}",
+        AdminFactory::new()
+            .subject("seed-admin")
+            .token(&config.jwt_secret)
+    );
+    Ok(())
+}
@@ -0,0 +1,590 @@
+//! Lifecycle of inbound cross-chain bridge transfers.
+//!
+//! A user registers a transfer from another chain; the bridge relayer then
+//! posts signed attestations as the funds are locked on the source chain and
+//! minted on Stellar (`pending → locked → minted`, or `failed`). Transfers
+//! that stall are failed by [`BridgeTimeoutService`].
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::address_book::is_valid_stellar_address;
+use crate::api::AppState;
+use crate::auth::{verify_wallet_signature, UserContext};
+use crate::jobs::JobRegistry;
+use crate::money::Money;
+use crate::notifications::create_notification;
+
+pub const SUPPORTED_SOURCE_CHAINS: &[&str] = &["ethereum", "polygon", "arbitrum", "base", "bsc"];
+
+const DEFAULT_INTERVAL_SECS: u64 = 5 * 60;
+const DEFAULT_LOCK_TIMEOUT_SECS: u64 = 2 * 60 * 60;
+const DEFAULT_MINT_TIMEOUT_SECS: u64 = 24 * 60 * 60;
+const BRIDGE_TIMEOUT_LOCK_KEY: i64 = 824;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BridgeStatus {
+    Pending,
+    Locked,
+    Minted,
+    Failed,
+}
+
+impl BridgeStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Locked => "locked",
+            Self::Minted => "minted",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "locked" => Some(Self::Locked),
+            "minted" => Some(Self::Minted),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    /// Whether a transfer may move from `self` to `next`.
+    pub fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Locked)
+                | (Self::Locked, Self::Minted)
+                | (Self::Pending, Self::Failed)
+                | (Self::Locked, Self::Failed)
+        )
+    }
+}
+
+/// Message the bridge attester signs for a status change.
+pub fn attestation_message(transfer_id: Uuid, status: BridgeStatus, tx_hash: &str) -> String {
+    format!(
+        "InheritX bridge attestation\ntransfer: {transfer_id}\nstatus: {}\ntx: {tx_hash}",
+        status.as_str()
+    )
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BridgeTransaction {
+    pub id: Uuid,
+    pub user_address: String,
+    pub source_chain: String,
+    pub source_tx_hash: Option<String>,
+    pub destination_address: String,
+    pub asset: String,
+    /// Serialized as [`Money`] through [`BridgeTransferView`].
+    #[serde(skip_serializing)]
+    pub amount: Decimal,
+    pub status: String,
+    pub lock_tx_hash: Option<String>,
+    pub mint_tx_hash: Option<String>,
+    pub failure_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub minted_at: Option<DateTime<Utc>>,
+}
+
+/// A bridge transfer as returned to clients, with its amount tagged by asset.
+#[derive(Debug, Serialize)]
+pub struct BridgeTransferView {
+    #[serde(flatten)]
+    pub transfer: BridgeTransaction,
+    pub amount: Money,
+}
+
+impl From<BridgeTransaction> for BridgeTransferView {
+    fn from(transfer: BridgeTransaction) -> Self {
+        Self {
+            amount: Money::new(transfer.amount, &transfer.asset),
+            transfer,
+        }
+    }
+}
+
+const BRIDGE_COLUMNS: &str =
+    "id, user_address, source_chain, source_tx_hash, destination_address, \
+     asset, amount, status, lock_tx_hash, mint_tx_hash, failure_reason, created_at, updated_at, \
+     locked_at, minted_at";
+
+#[derive(Debug, Deserialize)]
+pub struct InitiateBridgeRequest {
+    pub source_chain: String,
+    pub source_tx_hash: Option<String>,
+    pub destination_address: String,
+    pub asset: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BridgeAttestation {
+    pub transfer_id: Uuid,
+    pub status: BridgeStatus,
+    /// Source-chain lock tx for `locked`, Stellar mint tx for `minted`.
+    pub tx_hash: String,
+    pub reason: Option<String>,
+    /// Hex-encoded ed25519 signature over [`attestation_message`].
+    pub signature: String,
+}
+
+fn validate_initiate(payload: &InitiateBridgeRequest) -> Result<(), String> {
+    if !SUPPORTED_SOURCE_CHAINS.contains(&payload.source_chain.as_str()) {
+        return Err(format!(
+            "Unsupported source chain; expected one of {}",
+            SUPPORTED_SOURCE_CHAINS.join(", ")
+        ));
+    }
+    if !is_valid_stellar_address(&payload.destination_address) {
+        return Err("Destination must be a valid Stellar account (G...)".to_string());
+    }
+    if payload.asset.trim().is_empty() {
+        return Err("Asset is required".to_string());
+    }
+    if payload.amount <= Decimal::ZERO || payload.amount.fract() != Decimal::ZERO {
+        return Err("Amount must be a positive integer in base units".to_string());
+    }
+    Ok(())
+}
+
+// Handler: Initiate Bridge Transfer
+pub async fn initiate_bridge_transfer(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<InitiateBridgeRequest>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    if let Err(message) = validate_initiate(&payload) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": message })),
+        )
+            .into_response();
+    }
+
+    match sqlx::query_as::<_, BridgeTransaction>(&format!(
+        r#"
+        INSERT INTO bridge_transactions
+            (user_address, source_chain, source_tx_hash, destination_address, asset, amount)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {BRIDGE_COLUMNS}
+        "#
+    ))
+    .bind(&user_address)
+    .bind(&payload.source_chain)
+    .bind(&payload.source_tx_hash)
+    .bind(&payload.destination_address)
+    .bind(payload.asset.trim())
+    .bind(payload.amount)
+    .fetch_one(&state.db_pool)
+    .await
+    {
+        Ok(row) => {
+            info!(transfer_id = %row.id, source_chain = %row.source_chain, "Bridge transfer initiated");
+            (StatusCode::CREATED, Json(BridgeTransferView::from(row))).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to record bridge transfer");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to record bridge transfer" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: List Bridge Transfers
+pub async fn list_bridge_transfers(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, BridgeTransaction>(&format!(
+        "SELECT {BRIDGE_COLUMNS} FROM bridge_transactions WHERE user_address = $1 ORDER BY created_at DESC"
+    ))
+    .bind(&user_address)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(rows.into_iter().map(BridgeTransferView::from).collect::<Vec<_>>()),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list bridge transfers");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: Get Bridge Transfer
+pub async fn get_bridge_transfer(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, BridgeTransaction>(&format!(
+        "SELECT {BRIDGE_COLUMNS} FROM bridge_transactions WHERE id = $1 AND user_address = $2"
+    ))
+    .bind(id)
+    .bind(&user_address)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(row)) => (StatusCode::OK, Json(BridgeTransferView::from(row))).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Bridge transfer not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to load bridge transfer");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: Bridge Attestation (called by the bridge relayer)
+pub async fn submit_bridge_attestation(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<BridgeAttestation>,
+) -> impl IntoResponse {
+    let Some(attester) = state.config.bridge_attester_address.as_deref() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Bridge attestations are not enabled" })),
+        )
+            .into_response();
+    };
+
+    let message = attestation_message(payload.transfer_id, payload.status, &payload.tx_hash);
+    if !verify_wallet_signature(attester, message.as_bytes(), &payload.signature) {
+        warn!(transfer_id = %payload.transfer_id, "Rejected bridge attestation with invalid signature");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "error": "Invalid attestation signature" })),
+        )
+            .into_response();
+    }
+
+    let result = apply_transition(
+        &state.db_pool,
+        payload.transfer_id,
+        payload.status,
+        Some(&payload.tx_hash),
+        payload.reason.as_deref(),
+        Some((attester, &payload.signature)),
+    )
+    .await;
+
+    match result {
+        Ok(Some(row)) => (StatusCode::OK, Json(BridgeTransferView::from(row))).into_response(),
+        Ok(None) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": format!("Transfer cannot move to {}", payload.status.as_str())
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(transfer_id = %payload.transfer_id, error = %e, "Failed to apply bridge attestation");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to apply attestation" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Moves a transfer to `next` if the transition is allowed, recording the
+/// attestation (when given) and notifying the user. Returns `None` when the
+/// transfer does not exist or cannot make that transition.
+async fn apply_transition(
+    db: &PgPool,
+    transfer_id: Uuid,
+    next: BridgeStatus,
+    tx_hash: Option<&str>,
+    reason: Option<&str>,
+    attestation: Option<(&str, &str)>,
+) -> Result<Option<BridgeTransaction>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let current: Option<String> =
+        sqlx::query_scalar("SELECT status FROM bridge_transactions WHERE id = $1 FOR UPDATE")
+            .bind(transfer_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+    let Some(current) = current.as_deref().and_then(BridgeStatus::parse) else {
+        return Ok(None);
+    };
+    if !current.can_transition_to(next) {
+        return Ok(None);
+    }
+
+    let row = sqlx::query_as::<_, BridgeTransaction>(&format!(
+        r#"
+        UPDATE bridge_transactions
+        SET status = $2,
+            lock_tx_hash = CASE WHEN $2 = 'locked' THEN $3 ELSE lock_tx_hash END,
+            locked_at = CASE WHEN $2 = 'locked' THEN NOW() ELSE locked_at END,
+            mint_tx_hash = CASE WHEN $2 = 'minted' THEN $3 ELSE mint_tx_hash END,
+            minted_at = CASE WHEN $2 = 'minted' THEN NOW() ELSE minted_at END,
+            failure_reason = CASE WHEN $2 = 'failed' THEN $4 ELSE failure_reason END,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING {BRIDGE_COLUMNS}
+        "#
+    ))
+    .bind(transfer_id)
+    .bind(next.as_str())
+    .bind(tx_hash)
+    .bind(reason)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if let Some((attester, signature)) = attestation {
+        sqlx::query(
+            r#"
+            INSERT INTO bridge_attestations
+                (bridge_transaction_id, status, tx_hash, attester_address, signature)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(transfer_id)
+        .bind(next.as_str())
+        .bind(tx_hash.unwrap_or_default())
+        .bind(attester)
+        .bind(signature)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    create_notification(
+        &mut *tx,
+        &row.user_address,
+        "bridge_status",
+        "Bridge transfer update",
+        &format!(
+            "Your {} transfer from {} is now {}.",
+            row.asset,
+            row.source_chain,
+            next.as_str()
+        ),
+        serde_json::json!({ "transfer_id": row.id, "status": next.as_str() }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    info!(transfer_id = %transfer_id, from = current.as_str(), to = next.as_str(), "Bridge transfer status changed");
+    Ok(Some(row))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeTimeoutConfig {
+    pub interval: Duration,
+    /// How long a transfer may wait for its source-chain lock.
+    pub lock_timeout: Duration,
+    /// How long a locked transfer may wait for its Stellar mint.
+    pub mint_timeout: Duration,
+}
+
+impl BridgeTimeoutConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("BRIDGE_WORKER_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let lock_timeout_secs = parse_env("BRIDGE_LOCK_TIMEOUT_SECS", DEFAULT_LOCK_TIMEOUT_SECS);
+        let mint_timeout_secs = parse_env("BRIDGE_MINT_TIMEOUT_SECS", DEFAULT_MINT_TIMEOUT_SECS);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            lock_timeout: Duration::from_secs(lock_timeout_secs.max(1)),
+            mint_timeout: Duration::from_secs(mint_timeout_secs.max(1)),
+        }
+    }
+}
+
+/// Fails bridge transfers that have not progressed within their timeout.
+pub struct BridgeTimeoutService {
+    db: PgPool,
+    config: BridgeTimeoutConfig,
+}
+
+impl BridgeTimeoutService {
+    pub fn new(db: PgPool, config: BridgeTimeoutConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("bridge_timeouts", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
+                        warn!("Bridge worker failed {count} stalled transfer(s)");
+                    }
+                })
+            }
+        });
+    }
+
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(BRIDGE_TIMEOUT_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Bridge worker lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let stalled: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, status
+            FROM bridge_transactions
+            WHERE (status = 'pending' AND created_at <= NOW() - ($1 * INTERVAL '1 second'))
+               OR (status = 'locked' AND locked_at <= NOW() - ($2 * INTERVAL '1 second'))
+            ORDER BY updated_at ASC
+            LIMIT 500
+            "#,
+        )
+        .bind(self.config.lock_timeout.as_secs() as f64)
+        .bind(self.config.mint_timeout.as_secs() as f64)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let mut failed = 0;
+        for (id, status) in stalled {
+            let reason = match status.as_str() {
+                "pending" => "Source-chain lock was not observed in time",
+                _ => "Stellar mint was not observed in time",
+            };
+            if apply_transition(&self.db, id, BridgeStatus::Failed, None, Some(reason), None)
+                .await?
+                .is_some()
+            {
+                failed += 1;
+            }
+        }
+
+        Ok(failed)
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_forward_transitions_are_allowed() {
+        use BridgeStatus::*;
+
+        assert!(Pending.can_transition_to(Locked));
+        assert!(Locked.can_transition_to(Minted));
+        assert!(Pending.can_transition_to(Failed));
+        assert!(Locked.can_transition_to(Failed));
+
+        assert!(!Pending.can_transition_to(Minted));
+        assert!(!Minted.can_transition_to(Failed));
+        assert!(!Failed.can_transition_to(Locked));
+        assert!(!Locked.can_transition_to(Locked));
+    }
+
+    #[test]
+    fn attestation_signature_round_trips() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let attester =
+            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
+        let transfer_id = Uuid::new_v4();
+
+        let message = attestation_message(transfer_id, BridgeStatus::Locked, "0xabc");
+        let signature = hex::encode(key.sign(message.as_bytes()).to_bytes());
+
+        assert!(verify_wallet_signature(
+            &attester,
+            message.as_bytes(),
+            &signature
+        ));
+
+        let forged = attestation_message(transfer_id, BridgeStatus::Minted, "0xabc");
+        assert!(!verify_wallet_signature(
+            &attester,
+            forged.as_bytes(),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_chains_and_fractional_amounts() {
+        let mut request = InitiateBridgeRequest {
+            source_chain: "ethereum".to_string(),
+            source_tx_hash: None,
+            destination_address: stellar_strkey::ed25519::PublicKey([9u8; 32]).to_string(),
+            asset: "USDC".to_string(),
+            amount: Decimal::from(1_000),
+        };
+        assert!(validate_initiate(&request).is_ok());
+
+        request.source_chain = "dogechain".to_string();
+        assert!(validate_initiate(&request).is_err());
+
+        request.source_chain = "polygon".to_string();
+        request.amount = Decimal::new(15, 1);
+        assert!(validate_initiate(&request).is_err());
+    }
+}
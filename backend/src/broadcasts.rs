@@ -0,0 +1,683 @@
+//! Admin announcements broadcast to all users or a segment of them.
+//!
+//! A broadcast is stored as `scheduled` and sent by
+//! [`BroadcastSenderService`] once its `scheduled_at` passes: every wallet
+//! in the segment gets an in-app notification, and wallets with an email
+//! on file get it through the usual delivery queue. Marketing broadcasts
+//! are only emailed to wallets with active marketing consent. Delivery
+//! stats are counted from the notifications a broadcast created.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::consents::{active_consent_sql, ConsentKind};
+use crate::jobs::JobRegistry;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_BATCH_SIZE: i64 = 5;
+const BROADCAST_LOCK_KEY: i64 = 831;
+const MAX_TITLE_CHARS: usize = 200;
+const MAX_MESSAGE_CHARS: usize = 5000;
+const PREVIEW_SAMPLE_SIZE: i64 = 10;
+/// Plans whose inactivity deadline falls within this many days are "due".
+const DUE_PLAN_WINDOW_DAYS: i64 = 7;
+
+const BROADCAST_COLUMNS: &str = "b.id, b.title, b.message, b.segment, b.marketing, b.status, \
+     b.scheduled_at, b.sent_at, b.recipient_count, b.created_by, b.cancelled_by, b.created_at";
+
+/// Counts the notifications a broadcast created, joined as `s`.
+const STATS_JOIN: &str = r#"
+    LEFT JOIN LATERAL (
+        SELECT COUNT(*) AS delivered,
+               COUNT(*) FILTER (WHERE n.is_read) AS read,
+               COUNT(d.id) FILTER (WHERE d.status = 'queued') AS email_queued,
+               COUNT(d.id) FILTER (WHERE d.status = 'sent') AS email_sent,
+               COUNT(d.id) FILTER (WHERE d.status = 'failed') AS email_failed
+        FROM notifications n
+        LEFT JOIN notification_deliveries d
+               ON d.notification_id = n.id AND d.channel = 'email'
+        WHERE n.notification_type = 'broadcast'
+          AND n.metadata->>'broadcast_id' = b.id::text
+    ) s ON TRUE
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BroadcastSegment {
+    /// Every wallet known to the platform: users, plan owners and
+    /// beneficiaries.
+    All,
+    KycApproved,
+    /// Owners and co-owners of active plans whose inactivity deadline has
+    /// passed or falls within the next week.
+    DuePlans,
+}
+
+impl BroadcastSegment {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::KycApproved => "kyc_approved",
+            Self::DuePlans => "due_plans",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [Self::All, Self::KycApproved, Self::DuePlans]
+            .into_iter()
+            .find(|segment| segment.as_str() == value)
+    }
+
+    /// A query selecting the segment's distinct wallet addresses as
+    /// `address`.
+    fn recipients_sql(self) -> String {
+        match self {
+            Self::All => r#"
+                SELECT wallet_address AS address FROM users
+                UNION SELECT owner_address FROM plans
+                UNION SELECT wallet_address FROM beneficiaries
+            "#
+            .to_string(),
+            Self::KycApproved => r#"
+                SELECT wallet_address AS address FROM users WHERE kyc_status = 'approved'
+            "#
+            .to_string(),
+            Self::DuePlans => format!(
+                r#"
+                SELECT p.owner_address AS address FROM plans p
+                WHERE p.is_active = true
+                  AND p.last_ping + p.grace_period_seconds
+                      <= EXTRACT(EPOCH FROM NOW())::BIGINT + {window}
+                UNION
+                SELECT o.owner_address FROM plan_co_owners o
+                JOIN plans p ON p.id = o.plan_id
+                WHERE o.status = 'accepted'
+                  AND p.is_active = true
+                  AND p.last_ping + p.grace_period_seconds
+                      <= EXTRACT(EPOCH FROM NOW())::BIGINT + {window}
+                "#,
+                window = DUE_PLAN_WINDOW_DAYS * 86400
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BroadcastStats {
+    /// In-app notifications created.
+    pub delivered: i64,
+    pub read: i64,
+    pub email_queued: i64,
+    pub email_sent: i64,
+    pub email_failed: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Broadcast {
+    pub id: Uuid,
+    pub title: String,
+    pub message: String,
+    pub segment: String,
+    pub marketing: bool,
+    /// `scheduled`, `sent` or `cancelled`.
+    pub status: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Wallets in the segment when the broadcast was sent.
+    pub recipient_count: Option<i32>,
+    pub created_by: String,
+    pub cancelled_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub stats: BroadcastStats,
+}
+
+#[derive(sqlx::FromRow)]
+struct BroadcastRow {
+    id: Uuid,
+    title: String,
+    message: String,
+    segment: String,
+    marketing: bool,
+    status: String,
+    scheduled_at: DateTime<Utc>,
+    sent_at: Option<DateTime<Utc>>,
+    recipient_count: Option<i32>,
+    created_by: String,
+    cancelled_by: Option<String>,
+    created_at: DateTime<Utc>,
+    delivered: Option<i64>,
+    read: Option<i64>,
+    email_queued: Option<i64>,
+    email_sent: Option<i64>,
+    email_failed: Option<i64>,
+}
+
+impl From<BroadcastRow> for Broadcast {
+    fn from(row: BroadcastRow) -> Self {
+        Self {
+            id: row.id,
+            title: row.title,
+            message: row.message,
+            segment: row.segment,
+            marketing: row.marketing,
+            status: row.status,
+            scheduled_at: row.scheduled_at,
+            sent_at: row.sent_at,
+            recipient_count: row.recipient_count,
+            created_by: row.created_by,
+            cancelled_by: row.cancelled_by,
+            created_at: row.created_at,
+            stats: BroadcastStats {
+                delivered: row.delivered.unwrap_or_default(),
+                read: row.read.unwrap_or_default(),
+                email_queued: row.email_queued.unwrap_or_default(),
+                email_sent: row.email_sent.unwrap_or_default(),
+                email_failed: row.email_failed.unwrap_or_default(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BroadcastRequest {
+    pub title: String,
+    pub message: String,
+    pub segment: BroadcastSegment,
+    /// Promotional content, emailed only to wallets that consented to
+    /// marketing.
+    #[serde(default)]
+    pub marketing: bool,
+    /// Sent on the sender's next run when omitted.
+    #[serde(default)]
+    pub scheduled_at: Option<DateTime<Utc>>,
+}
+
+impl BroadcastRequest {
+    fn validate(&mut self) -> Result<(), String> {
+        self.title = self.title.trim().to_string();
+        self.message = self.message.trim().to_string();
+        if self.title.is_empty() || self.title.chars().count() > MAX_TITLE_CHARS {
+            return Err(format!(
+                "title must be between 1 and {MAX_TITLE_CHARS} characters"
+            ));
+        }
+        if self.message.is_empty() || self.message.chars().count() > MAX_MESSAGE_CHARS {
+            return Err(format!(
+                "message must be between 1 and {MAX_MESSAGE_CHARS} characters"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BroadcastPreview {
+    pub title: String,
+    pub message: String,
+    pub segment: BroadcastSegment,
+    pub marketing: bool,
+    /// Wallets the broadcast would reach if sent now.
+    pub recipient_count: i64,
+    pub sample_recipients: Vec<String>,
+    /// Of those, wallets that would also be emailed.
+    pub email_recipient_count: i64,
+}
+
+fn error_response(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+async fn load_broadcast(db: &PgPool, id: Uuid) -> Result<Option<Broadcast>, sqlx::Error> {
+    let row = sqlx::query_as::<_, BroadcastRow>(&format!(
+        "SELECT {BROADCAST_COLUMNS}, s.* FROM broadcasts b {STATS_JOIN} WHERE b.id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+    Ok(row.map(Broadcast::from))
+}
+
+/// SQL condition that recipient `r` is emailed a broadcast, given its
+/// preferences joined as `p`.
+fn emailed_sql(marketing: bool) -> String {
+    if marketing {
+        format!(
+            "p.email IS NOT NULL AND {}",
+            active_consent_sql("r.address", ConsentKind::Marketing)
+        )
+    } else {
+        "p.email IS NOT NULL".to_string()
+    }
+}
+
+/// Notifies every wallet in the broadcast's segment, queueing email for
+/// those with an address on file. Returns the number of wallets reached.
+async fn deliver(
+    conn: &mut PgConnection,
+    id: Uuid,
+    title: &str,
+    message: &str,
+    segment: BroadcastSegment,
+    marketing: bool,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        r#"
+        WITH recipients AS ({recipients}),
+        inserted AS (
+            INSERT INTO notifications (user_address, notification_type, title, message, metadata, status)
+            SELECT r.address, 'broadcast', $2, $3,
+                   jsonb_build_object('broadcast_id', $1::text),
+                   CASE WHEN {emailed} THEN 'queued' ELSE 'sent' END
+            FROM recipients r
+            LEFT JOIN notification_preferences p ON p.user_address = r.address
+            WHERE r.address IS NOT NULL
+            RETURNING id, status
+        ),
+        queued AS (
+            INSERT INTO notification_deliveries (notification_id, channel)
+            SELECT id, 'email' FROM inserted WHERE status = 'queued'
+        )
+        SELECT COUNT(*) FROM inserted
+        "#,
+        recipients = segment.recipients_sql(),
+        emailed = emailed_sql(marketing)
+    ))
+    .bind(id)
+    .bind(title)
+    .bind(message)
+    .fetch_one(conn)
+    .await
+}
+
+// Handler: List Broadcasts
+pub async fn list_broadcasts(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match sqlx::query_as::<_, BroadcastRow>(&format!(
+        "SELECT {BROADCAST_COLUMNS}, s.* FROM broadcasts b {STATS_JOIN} ORDER BY b.created_at DESC LIMIT 100"
+    ))
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(rows.into_iter().map(Broadcast::from).collect::<Vec<_>>()),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list broadcasts");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get Broadcast
+pub async fn get_broadcast(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match load_broadcast(&state.db_pool, id).await {
+        Ok(Some(broadcast)) => (StatusCode::OK, Json(broadcast)).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Broadcast not found"),
+        Err(e) => {
+            error!(broadcast_id = %id, error = %e, "Failed to load broadcast");
+            database_error()
+        }
+    }
+}
+
+// Handler: Preview Broadcast
+pub async fn preview_broadcast(
+    State(state): State<Arc<AppState>>,
+    Json(mut payload): Json<BroadcastRequest>,
+) -> impl IntoResponse {
+    if let Err(message) = payload.validate() {
+        return error_response(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let recipients = payload.segment.recipients_sql();
+    let emailed = emailed_sql(payload.marketing);
+    let result: Result<BroadcastPreview, sqlx::Error> = async {
+        let (recipient_count, email_recipient_count): (i64, i64) = sqlx::query_as(&format!(
+            r#"
+            WITH recipients AS ({recipients})
+            SELECT COUNT(*), COUNT(*) FILTER (WHERE {emailed})
+            FROM recipients r
+            LEFT JOIN notification_preferences p ON p.user_address = r.address
+            WHERE r.address IS NOT NULL
+            "#
+        ))
+        .fetch_one(&state.db_pool)
+        .await?;
+        let sample_recipients: Vec<String> = sqlx::query_scalar(&format!(
+            r#"
+            WITH recipients AS ({recipients})
+            SELECT address FROM recipients WHERE address IS NOT NULL ORDER BY address LIMIT $1
+            "#
+        ))
+        .bind(PREVIEW_SAMPLE_SIZE)
+        .fetch_all(&state.db_pool)
+        .await?;
+
+        Ok(BroadcastPreview {
+            title: payload.title.clone(),
+            message: payload.message.clone(),
+            segment: payload.segment,
+            marketing: payload.marketing,
+            recipient_count,
+            sample_recipients,
+            email_recipient_count,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(preview) => (StatusCode::OK, Json(preview)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to preview broadcast");
+            database_error()
+        }
+    }
+}
+
+// Handler: Create Broadcast
+pub async fn create_broadcast(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(mut payload): Json<BroadcastRequest>,
+) -> impl IntoResponse {
+    if let Err(message) = payload.validate() {
+        return error_response(StatusCode::BAD_REQUEST, &message);
+    }
+    let scheduled_at = payload.scheduled_at.unwrap_or_else(Utc::now);
+
+    let result: Result<Uuid, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO broadcasts (title, message, segment, marketing, scheduled_at, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(&payload.title)
+        .bind(&payload.message)
+        .bind(payload.segment.as_str())
+        .bind(payload.marketing)
+        .bind(scheduled_at)
+        .bind(&admin.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "broadcast.created",
+            &id.to_string(),
+            serde_json::json!({
+                "title": payload.title,
+                "segment": payload.segment.as_str(),
+                "marketing": payload.marketing,
+                "scheduled_at": scheduled_at,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+    .await;
+
+    match result {
+        Ok(id) => match load_broadcast(&state.db_pool, id).await {
+            Ok(Some(broadcast)) => (StatusCode::CREATED, Json(broadcast)).into_response(),
+            Ok(None) => database_error(),
+            Err(e) => {
+                error!(broadcast_id = %id, error = %e, "Failed to load broadcast");
+                database_error()
+            }
+        },
+        Err(e) => {
+            error!(error = %e, "Failed to create broadcast");
+            database_error()
+        }
+    }
+}
+
+// Handler: Cancel Broadcast
+pub async fn cancel_broadcast(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let result: Result<bool, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let cancelled = sqlx::query(
+            "UPDATE broadcasts SET status = 'cancelled', cancelled_by = $2 WHERE id = $1 AND status = 'scheduled'",
+        )
+        .bind(id)
+        .bind(&admin.user_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+        if cancelled {
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "broadcast.cancelled",
+                &id.to_string(),
+                serde_json::json!({}),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(cancelled)
+    }
+    .await;
+
+    match result {
+        Ok(true) => get_broadcast(State(state), Path(id)).await.into_response(),
+        Ok(false) => error_response(StatusCode::NOT_FOUND, "Scheduled broadcast not found"),
+        Err(e) => {
+            error!(broadcast_id = %id, error = %e, "Failed to cancel broadcast");
+            database_error()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BroadcastSenderConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+}
+
+impl BroadcastSenderConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("BROADCAST_SENDER_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("BROADCAST_SENDER_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+/// Sends broadcasts whose scheduled time has passed.
+pub struct BroadcastSenderService {
+    db: PgPool,
+    config: BroadcastSenderConfig,
+}
+
+#[derive(sqlx::FromRow)]
+struct DueBroadcast {
+    id: Uuid,
+    title: String,
+    message: String,
+    segment: String,
+    marketing: bool,
+}
+
+impl BroadcastSenderService {
+    pub fn new(db: PgPool, config: BroadcastSenderConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("broadcast_sender", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&sent| {
+                    if sent > 0 {
+                        info!(broadcasts = sent, "Scheduled broadcasts sent");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Sends every due broadcast, each in its own transaction so that a
+    /// broadcast is either fully delivered or left scheduled. Returns the
+    /// number sent.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut lock_tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(BROADCAST_LOCK_KEY)
+            .fetch_one(&mut *lock_tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Broadcast sender lock is held by another worker; skipping run");
+            lock_tx.commit().await?;
+            return Ok(0);
+        }
+
+        let due = sqlx::query_as::<_, DueBroadcast>(
+            r#"
+            SELECT id, title, message, segment, marketing FROM broadcasts
+            WHERE status = 'scheduled' AND scheduled_at <= NOW()
+            ORDER BY scheduled_at
+            LIMIT $1
+            "#,
+        )
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *lock_tx)
+        .await?;
+
+        let mut sent = 0;
+        for broadcast in &due {
+            let Some(segment) = BroadcastSegment::parse(&broadcast.segment) else {
+                warn!(broadcast_id = %broadcast.id, segment = %broadcast.segment, "Unknown broadcast segment");
+                continue;
+            };
+
+            let mut tx = self.db.begin().await?;
+            let still_scheduled: bool = sqlx::query_scalar(
+                "SELECT status = 'scheduled' FROM broadcasts WHERE id = $1 FOR UPDATE",
+            )
+            .bind(broadcast.id)
+            .fetch_one(&mut *tx)
+            .await?;
+            if !still_scheduled {
+                continue;
+            }
+
+            let recipients = deliver(
+                &mut tx,
+                broadcast.id,
+                &broadcast.title,
+                &broadcast.message,
+                segment,
+                broadcast.marketing,
+            )
+            .await?;
+            sqlx::query(
+                "UPDATE broadcasts SET status = 'sent', sent_at = NOW(), recipient_count = $2 WHERE id = $1",
+            )
+            .bind(broadcast.id)
+            .bind(i32::try_from(recipients).unwrap_or(i32::MAX))
+            .execute(&mut *tx)
+            .await?;
+            record_audit(
+                &mut *tx,
+                SYSTEM_ACTOR,
+                "broadcast.sent",
+                &broadcast.id.to_string(),
+                serde_json::json!({ "recipients": recipients }),
+            )
+            .await?;
+            tx.commit().await?;
+
+            info!(broadcast_id = %broadcast.id, recipients, "Broadcast sent");
+            sent += 1;
+        }
+
+        lock_tx.commit().await?;
+        Ok(sent)
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(title: &str, message: &str) -> BroadcastRequest {
+        BroadcastRequest {
+            title: title.to_string(),
+            message: message.to_string(),
+            segment: BroadcastSegment::All,
+            marketing: false,
+            scheduled_at: None,
+        }
+    }
+
+    #[test]
+    fn segments_round_trip_and_reject_unknown_names() {
+        for segment in [
+            BroadcastSegment::All,
+            BroadcastSegment::KycApproved,
+            BroadcastSegment::DuePlans,
+        ] {
+            assert_eq!(BroadcastSegment::parse(segment.as_str()), Some(segment));
+        }
+        assert_eq!(BroadcastSegment::parse("active_borrowers"), None);
+        assert!(serde_json::from_str::<BroadcastSegment>(r#""active_borrowers""#).is_err());
+    }
+
+    #[test]
+    fn request_text_is_trimmed_and_bounded() {
+        let mut ok = request("  Maintenance  ", " Tonight at 02:00 UTC ");
+        assert!(ok.validate().is_ok());
+        assert_eq!(ok.title, "Maintenance");
+        assert_eq!(ok.message, "Tonight at 02:00 UTC");
+
+        assert!(request("   ", "body").validate().is_err());
+        assert!(request("title", &"x".repeat(MAX_MESSAGE_CHARS + 1))
+            .validate()
+            .is_err());
+    }
+}
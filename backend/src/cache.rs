@@ -215,9 +215,14 @@ impl PlanCache {
 
 pub(crate) fn cache_key(query: &PlanQuery) -> String {
     format!(
-        "{CACHE_NAMESPACE}:query:owner={}:beneficiary={}",
+        "{CACHE_NAMESPACE}:query:owner={}:beneficiary={}:tag={}",
         normalize_optional_filter(query.owner.as_deref()),
         normalize_optional_filter(query.beneficiary.as_deref()),
+        crate::plan_tags::parse_tag_filter(query.tag.as_deref())
+            .ok()
+            .flatten()
+            .map(|tags| tags.join(","))
+            .unwrap_or_else(|| "all".to_string()),
     )
 }
 
@@ -272,6 +277,7 @@ fn owner_beneficiary_index_key(owner: &str, beneficiary: &str) -> String {
 mod tests {
     use super::*;
     use crate::api::{BeneficiaryResponse, PlanResponse};
+    use crate::money::Money;
     use chrono::Utc;
     use rust_decimal::Decimal;
     use uuid::Uuid;
@@ -281,7 +287,7 @@ mod tests {
             id: Uuid::new_v4(),
             owner_address: owner.to_string(),
             token_address: "USDC".to_string(),
-            amount: Decimal::from(1000),
+            amount: Money::new(Decimal::from(1000), "USDC"),
             grace_period: 3600,
             grace_period_seconds: 3600,
             earn_yield: true,
@@ -289,7 +295,7 @@ mod tests {
             is_active: true,
             status: "ACTIVE".to_string(),
             yield_rate_bps: 500,
-            accrued_yield: 42.5,
+            accrued_yield: Money::new(Decimal::new(425, 1), "USDC"),
             created_at: Utc::now(),
             beneficiaries: vec![BeneficiaryResponse {
                 id: Uuid::new_v4(),
@@ -298,6 +304,8 @@ mod tests {
                 allocation_bps: 10_000,
                 fiat_anchor_info: "bank-usd".to_string(),
             }],
+            co_owners: Vec::new(),
+            tags: Vec::new(),
         }
     }
 
@@ -307,6 +315,7 @@ mod tests {
         let query = PlanQuery {
             owner: Some("GOWNER".to_string()),
             beneficiary: Some("GBENEFICIARY".to_string()),
+            tag: None,
         };
         let plans = vec![sample_plan("GOWNER", "GBENEFICIARY")];
 
@@ -326,18 +335,22 @@ mod tests {
             PlanQuery {
                 owner: None,
                 beneficiary: None,
+                tag: None,
             },
             PlanQuery {
                 owner: Some("GOWNER".to_string()),
                 beneficiary: None,
+                tag: None,
             },
             PlanQuery {
                 owner: None,
                 beneficiary: Some("GBENEFICIARY".to_string()),
+                tag: None,
             },
             PlanQuery {
                 owner: Some("GOWNER".to_string()),
                 beneficiary: Some("GBENEFICIARY".to_string()),
+                tag: None,
             },
         ];
 
@@ -360,11 +373,12 @@ mod tests {
         let query = PlanQuery {
             owner: Some("  GOwner ".to_string()),
             beneficiary: Some(" GBeneficiary ".to_string()),
+            tag: None,
         };
 
         assert_eq!(
             cache_key(&query),
-            "plans:v1:query:owner=gowner:beneficiary=gbeneficiary"
+            "plans:v1:query:owner=gowner:beneficiary=gbeneficiary:tag=all"
         );
     }
 }
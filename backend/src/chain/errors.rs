@@ -0,0 +1,181 @@
+//! Named mappings for the numeric error codes our contracts return.
+//!
+//! A failing contract call surfaces as `Error(Contract, #N)`. The
+//! inheritance contract's codes come from the shared `inheritx-types`
+//! crate; the token codes mirror `mock-token` and must be kept in sync.
+//! Clients get the snake_case `name`, which stays stable even if the
+//! wording of `message` changes.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub use inheritx_types::InheritanceError;
+
+/// Which contract interface a code belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractInterface {
+    Inheritance,
+    Token,
+}
+
+/// `mock-token` `ContractError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    NegativeAmount = 1,
+    InsufficientBalance = 2,
+    Overflow = 3,
+    ExceedsMaxSupply = 4,
+}
+
+impl TokenError {
+    pub const ALL: [Self; 4] = [
+        Self::NegativeAmount,
+        Self::InsufficientBalance,
+        Self::Overflow,
+        Self::ExceedsMaxSupply,
+    ];
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| *e as u32 == code)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NegativeAmount => "negative_amount",
+            Self::InsufficientBalance => "insufficient_balance",
+            Self::Overflow => "overflow",
+            Self::ExceedsMaxSupply => "exceeds_max_supply",
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::NegativeAmount => "Token amounts must not be negative",
+            Self::InsufficientBalance => "The token balance is too low for this transfer",
+            Self::Overflow => "The token balance would overflow",
+            Self::ExceedsMaxSupply => "Minting would exceed the token's maximum supply",
+        }
+    }
+}
+
+/// A contract error decoded for one interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractError {
+    Inheritance(InheritanceError),
+    Token(TokenError),
+}
+
+impl ContractError {
+    /// Returns `None` for codes the interface does not define.
+    pub fn decode(interface: ContractInterface, code: u32) -> Option<Self> {
+        match interface {
+            ContractInterface::Inheritance => {
+                InheritanceError::from_code(code).map(Self::Inheritance)
+            }
+            ContractInterface::Token => TokenError::from_code(code).map(Self::Token),
+        }
+    }
+
+    pub fn interface(self) -> ContractInterface {
+        match self {
+            Self::Inheritance(_) => ContractInterface::Inheritance,
+            Self::Token(_) => ContractInterface::Token,
+        }
+    }
+
+    pub fn code(self) -> u32 {
+        match self {
+            Self::Inheritance(e) => e as u32,
+            Self::Token(e) => e as u32,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Inheritance(e) => e.name(),
+            Self::Token(e) => e.name(),
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Inheritance(e) => e.message(),
+            Self::Token(e) => e.message(),
+        }
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (#{}): {}", self.name(), self.code(), self.message())
+    }
+}
+
+/// Client-facing form of a [`ContractError`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContractErrorInfo {
+    pub interface: ContractInterface,
+    pub code: u32,
+    pub name: &'static str,
+    pub message: &'static str,
+}
+
+impl From<ContractError> for ContractErrorInfo {
+    fn from(error: ContractError) -> Self {
+        Self {
+            interface: error.interface(),
+            code: error.code(),
+            name: error.name(),
+            message: error.message(),
+        }
+    }
+}
+
+/// Extracts the contract error code from a host error message such as
+/// `HostError: Error(Contract, #7)`.
+pub fn contract_code(message: &str) -> Option<u32> {
+    let start = message.find("Error(Contract, #")? + "Error(Contract, #".len();
+    let digits: String = message[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for error in InheritanceError::ALL {
+            assert_eq!(InheritanceError::from_code(error as u32), Some(error));
+        }
+        for error in TokenError::ALL {
+            assert_eq!(TokenError::from_code(error as u32), Some(error));
+        }
+        assert_eq!(
+            ContractError::decode(ContractInterface::Inheritance, 0),
+            None
+        );
+        assert_eq!(ContractError::decode(ContractInterface::Token, 5), None);
+    }
+
+    #[test]
+    fn same_code_decodes_per_interface() {
+        let inheritance = ContractError::decode(ContractInterface::Inheritance, 2).unwrap();
+        let token = ContractError::decode(ContractInterface::Token, 2).unwrap();
+        assert_eq!(inheritance.name(), "plan_not_found");
+        assert_eq!(token.name(), "insufficient_balance");
+    }
+
+    #[test]
+    fn extracts_code_from_host_error() {
+        assert_eq!(
+            contract_code("HostError: Error(Contract, #10)\n\nEvent log ..."),
+            Some(10)
+        );
+        assert_eq!(contract_code("HostError: Error(Auth, InvalidAction)"), None);
+    }
+}
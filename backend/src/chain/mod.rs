@@ -0,0 +1,16 @@
+//! Soroban/Stellar transaction plumbing shared by background workers.
+
+pub mod errors;
+pub mod rpc;
+pub mod signer;
+pub mod tx_service;
+
+/// Plan, guardian and fee types as the contracts declare them.
+pub use inheritx_types as types;
+
+pub use errors::{ContractError, ContractErrorInfo, ContractInterface};
+pub use signer::{KeyRing, Signer, SignerError, SigningOperation, SigningPolicy};
+pub use tx_service::{
+    BatchReceipt, ContractInvocation, Conversion, SimulatedTxService, TokenTransfer,
+    TransferOutcome, TxError, TxService, TX_VALIDITY,
+};
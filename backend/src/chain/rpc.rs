@@ -0,0 +1,178 @@
+//! Minimal Soroban RPC JSON-RPC client.
+//!
+//! Only the calls the backend needs are implemented. Without
+//! `SOROBAN_RPC_URL` the client reports itself unconfigured and callers
+//! respond with 503 instead of guessing.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::http_client::{HttpClient, HttpError, HttpPolicy};
+
+#[derive(Debug, Clone, Default)]
+pub struct SorobanRpcConfig {
+    pub url: Option<String>,
+}
+
+impl SorobanRpcConfig {
+    pub fn from_env() -> Self {
+        Self {
+            url: std::env::var("SOROBAN_RPC_URL")
+                .ok()
+                .filter(|v| !v.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RpcError {
+    #[error("Soroban RPC is not configured")]
+    NotConfigured,
+    #[error("RPC request failed: {0}")]
+    Http(#[from] HttpError),
+    #[error("RPC returned error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("RPC response had no result")]
+    EmptyResult,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateCost {
+    #[serde(default)]
+    pub cpu_insns: String,
+    #[serde(default)]
+    pub mem_bytes: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimulateHostFunctionResult {
+    #[serde(default)]
+    pub auth: Vec<String>,
+    pub xdr: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimulateStateChange {
+    #[serde(rename = "type")]
+    pub change_type: String,
+    pub key: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransactionResponse {
+    pub latest_ledger: u32,
+    pub min_resource_fee: Option<String>,
+    pub cost: Option<SimulateCost>,
+    #[serde(default)]
+    pub results: Vec<SimulateHostFunctionResult>,
+    pub transaction_data: Option<String>,
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub restore_preamble: Option<serde_json::Value>,
+    #[serde(default)]
+    pub state_changes: Vec<SimulateStateChange>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RpcRequest<'a, P: Serialize> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<P>,
+}
+
+#[derive(Deserialize)]
+struct RpcErrorBody {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcErrorBody>,
+}
+
+pub struct SorobanRpcClient {
+    http: HttpClient,
+    config: SorobanRpcConfig,
+}
+
+impl SorobanRpcClient {
+    pub fn new(config: SorobanRpcConfig) -> Self {
+        let http = HttpClient::new(
+            "soroban_rpc",
+            HttpPolicy {
+                timeout: Duration::from_secs(15),
+                retry_unsafe_methods: true,
+                ..HttpPolicy::default()
+            },
+        );
+        Self { http, config }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.config.url.is_some()
+    }
+
+    async fn call<P: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: Option<P>,
+    ) -> Result<T, RpcError> {
+        let url = self.config.url.as_deref().ok_or(RpcError::NotConfigured)?;
+        let request = self.http.post(url).json(&RpcRequest {
+            jsonrpc: "2.0",
+            id: 1,
+            method,
+            params,
+        });
+        let response: RpcResponse<T> = self
+            .http
+            .send(request)
+            .await?
+            .error_for_status()
+            .map_err(HttpError::from)?
+            .json()
+            .await
+            .map_err(HttpError::from)?;
+
+        if let Some(error) = response.error {
+            return Err(RpcError::Rpc {
+                code: error.code,
+                message: error.message,
+            });
+        }
+        response.result.ok_or(RpcError::EmptyResult)
+    }
+
+    /// Runs `simulateTransaction` for a base64 `TransactionEnvelope`.
+    pub async fn simulate_transaction(
+        &self,
+        envelope_xdr: &str,
+    ) -> Result<SimulateTransactionResponse, RpcError> {
+        self.call(
+            "simulateTransaction",
+            Some(serde_json::json!({ "transaction": envelope_xdr })),
+        )
+        .await
+    }
+
+    /// Runs `getHealth` and returns the server's reported status
+    /// (`healthy` when it is keeping up with the network).
+    pub async fn get_health(&self) -> Result<String, RpcError> {
+        #[derive(Deserialize)]
+        struct Health {
+            status: String,
+        }
+        let health: Health = self.call("getHealth", None::<()>).await?;
+        Ok(health.status)
+    }
+}
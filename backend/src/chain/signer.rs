@@ -0,0 +1,712 @@
+//! Keys that sign the backend's Stellar transactions.
+//!
+//! A [`Signer`] holds one ed25519 key: a seed in a local keystore sealed
+//! with the field encryption keys, a key in AWS KMS, or a key behind a
+//! remote signing service. [`KeyRing`] picks the key for each operation:
+//! the hot key by default, and the hardware-backed key where
+//! [`SigningPolicy`] requires it, such as payouts above a threshold.
+//! Rotating swaps the hot key without a restart. Remote signatures are
+//! checked against the configured public key before they are used.
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier, VerifyingKey};
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+use crate::field_crypto::{FieldCipher, SensitiveField};
+use crate::http_client::{HttpClient, HttpPolicy};
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("signer is not configured: {0}")]
+    NotConfigured(String),
+    #[error("keystore error: {0}")]
+    Keystore(String),
+    #[error("signing backend unavailable: {0}")]
+    Unavailable(String),
+    #[error("signing backend returned an invalid signature")]
+    InvalidSignature,
+    #[error("{0} requires a hardware-backed key, and none is configured")]
+    PolicyViolation(&'static str),
+}
+
+pub type SignFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, SignerError>> + Send + 'a>>;
+
+pub trait Signer: Send + Sync {
+    /// Operator-facing name of the key, logged with every signature.
+    fn key_id(&self) -> &str;
+
+    /// Stellar account (`G...`) of the key.
+    fn public_key(&self) -> &str;
+
+    /// Whether the private key never leaves an HSM or KMS.
+    fn is_hardware_backed(&self) -> bool;
+
+    /// Signs `payload` (for transactions, the transaction hash).
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> SignFuture<'a, [u8; 64]>;
+}
+
+fn verifying_key(public_key: &str) -> Result<VerifyingKey, SignerError> {
+    let key = stellar_strkey::ed25519::PublicKey::from_string(public_key.trim())
+        .map_err(|_| SignerError::NotConfigured(format!("invalid public key {public_key}")))?;
+    VerifyingKey::from_bytes(&key.0)
+        .map_err(|_| SignerError::NotConfigured(format!("invalid public key {public_key}")))
+}
+
+/// Checks a signature from a remote backend against the expected key.
+fn checked_signature(
+    key: &VerifyingKey,
+    payload: &[u8],
+    bytes: &[u8],
+) -> Result<[u8; 64], SignerError> {
+    let signature = Signature::from_slice(bytes).map_err(|_| SignerError::InvalidSignature)?;
+    key.verify(payload, &signature)
+        .map_err(|_| SignerError::InvalidSignature)?;
+    Ok(signature.to_bytes())
+}
+
+/// On-disk form of a local key. The seed is sealed with the field
+/// encryption keys, so the file alone does not reveal it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreFile {
+    pub key_id: String,
+    pub public_key: String,
+    pub sealed_seed: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct LocalKeystoreSigner {
+    key_id: String,
+    public_key: String,
+    key: SigningKey,
+}
+
+impl LocalKeystoreSigner {
+    /// Generates a key and its keystore entry. Refuses to run without a
+    /// field encryption key, which would leave the seed in plaintext.
+    pub fn generate(key_id: &str, cipher: &FieldCipher) -> Result<KeystoreFile, SignerError> {
+        if cipher.current_key_id().is_none() {
+            return Err(SignerError::NotConfigured(
+                "FIELD_ENCRYPTION_KEYS must be set to seal a signing key".to_string(),
+            ));
+        }
+        let key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let sealed_seed = cipher
+            .encrypt(SensitiveField::SignerSeed, &hex::encode(key.to_bytes()))
+            .map_err(|e| SignerError::Keystore(e.to_string()))?;
+        Ok(KeystoreFile {
+            key_id: key_id.to_string(),
+            public_key: stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes())
+                .to_string(),
+            sealed_seed,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn open(file: &KeystoreFile, cipher: &FieldCipher) -> Result<Self, SignerError> {
+        if !file.sealed_seed.starts_with("enc:") {
+            return Err(SignerError::Keystore(format!(
+                "keystore {} holds an unsealed seed",
+                file.key_id
+            )));
+        }
+        let seed = cipher
+            .decrypt(SensitiveField::SignerSeed, &file.sealed_seed)
+            .map_err(|e| SignerError::Keystore(e.to_string()))?;
+        let bytes: [u8; 32] = hex::decode(seed.trim())
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| {
+                SignerError::Keystore(format!("keystore {} is malformed", file.key_id))
+            })?;
+        let key = SigningKey::from_bytes(&bytes);
+        if key.verifying_key() != verifying_key(&file.public_key)? {
+            return Err(SignerError::Keystore(format!(
+                "keystore {} seed does not match its public key",
+                file.key_id
+            )));
+        }
+        Ok(Self {
+            key_id: file.key_id.clone(),
+            public_key: file.public_key.clone(),
+            key,
+        })
+    }
+
+    pub fn load(path: &Path, cipher: &FieldCipher) -> Result<Self, SignerError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| SignerError::Keystore(format!("{}: {e}", path.display())))?;
+        let file: KeystoreFile = serde_json::from_str(&contents)
+            .map_err(|e| SignerError::Keystore(format!("{}: {e}", path.display())))?;
+        Self::open(&file, cipher)
+    }
+}
+
+impl Signer for LocalKeystoreSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn is_hardware_backed(&self) -> bool {
+        false
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> SignFuture<'a, [u8; 64]> {
+        Box::pin(async move { Ok(self.key.sign(payload).to_bytes()) })
+    }
+}
+
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// The standard `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+    /// optional `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: std::env::var("AWS_SESSION_TOKEN")
+                .ok()
+                .filter(|t| !t.is_empty()),
+        })
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// AWS Signature Version 4 signing key for one day, region and service.
+fn sigv4_signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{secret}").as_bytes(), date);
+    let k_region = hmac_sha256(&k_date, region);
+    let k_service = hmac_sha256(&k_region, service);
+    hmac_sha256(&k_service, "aws4_request")
+}
+
+/// `Authorization` header value for a POST to `/` with `headers`, which
+/// must be lowercase, sorted by name and include `host` and `x-amz-date`.
+fn sigv4_authorization(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    headers: &[(&str, String)],
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> String {
+    let date = now.format("%Y%m%d").to_string();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+        hex::encode(Sha256::digest(body))
+    );
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{scope}\n{}",
+        now.format("%Y%m%dT%H%M%SZ"),
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signature = hex::encode(hmac_sha256(
+        &sigv4_signing_key(&credentials.secret_access_key, &date, region, service),
+        &string_to_sign,
+    ));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        credentials.access_key_id
+    )
+}
+
+/// An `ECC_NIST_EDWARDS25519` key in AWS KMS.
+pub struct AwsKmsSigner {
+    key_id: String,
+    kms_key_id: String,
+    region: String,
+    public_key: String,
+    verifying_key: VerifyingKey,
+    credentials: AwsCredentials,
+    http: HttpClient,
+}
+
+#[derive(Deserialize)]
+struct KmsSignResponse {
+    #[serde(rename = "Signature")]
+    signature: String,
+}
+
+impl AwsKmsSigner {
+    pub fn new(
+        key_id: &str,
+        kms_key_id: &str,
+        region: &str,
+        public_key: &str,
+        credentials: AwsCredentials,
+    ) -> Result<Self, SignerError> {
+        Ok(Self {
+            key_id: key_id.to_string(),
+            kms_key_id: kms_key_id.to_string(),
+            region: region.to_string(),
+            public_key: public_key.to_string(),
+            verifying_key: verifying_key(public_key)?,
+            credentials,
+            http: HttpClient::new(
+                "kms_signer",
+                HttpPolicy {
+                    retry_unsafe_methods: true,
+                    ..HttpPolicy::default()
+                },
+            ),
+        })
+    }
+}
+
+impl Signer for AwsKmsSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn is_hardware_backed(&self) -> bool {
+        true
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> SignFuture<'a, [u8; 64]> {
+        Box::pin(async move {
+            let host = format!("kms.{}.amazonaws.com", self.region);
+            let body = serde_json::json!({
+                "KeyId": self.kms_key_id,
+                "Message": base64::engine::general_purpose::STANDARD.encode(payload),
+                "MessageType": "RAW",
+                "SigningAlgorithm": "ED25519_SHA_512",
+            })
+            .to_string();
+            let now = Utc::now();
+            let mut headers = vec![
+                ("content-type", "application/x-amz-json-1.1".to_string()),
+                ("host", host.clone()),
+                ("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string()),
+            ];
+            if let Some(token) = &self.credentials.session_token {
+                headers.push(("x-amz-security-token", token.clone()));
+            }
+            headers.push(("x-amz-target", "TrentService.Sign".to_string()));
+            let authorization = sigv4_authorization(
+                &self.credentials,
+                &self.region,
+                "kms",
+                &headers,
+                body.as_bytes(),
+                now,
+            );
+
+            let mut request = self
+                .http
+                .post(format!("https://{host}/"))
+                .header("Authorization", authorization);
+            for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+                request = request.header(*name, value);
+            }
+            let response = self
+                .http
+                .send(request.body(body))
+                .await
+                .map_err(|e| SignerError::Unavailable(e.to_string()))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let detail = response.text().await.unwrap_or_default();
+                return Err(SignerError::Unavailable(format!(
+                    "KMS returned {status}: {detail}"
+                )));
+            }
+            let signed: KmsSignResponse = response
+                .json()
+                .await
+                .map_err(|e| SignerError::Unavailable(e.to_string()))?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(signed.signature)
+                .map_err(|_| SignerError::InvalidSignature)?;
+            checked_signature(&self.verifying_key, payload, &bytes)
+        })
+    }
+}
+
+/// A signing service reached over HTTPS. It receives
+/// `{"key_id", "payload"}` (payload base64) with a bearer token and answers
+/// `{"signature"}` (hex).
+pub struct RemoteSigner {
+    key_id: String,
+    url: String,
+    token: String,
+    public_key: String,
+    verifying_key: VerifyingKey,
+    http: HttpClient,
+}
+
+#[derive(Deserialize)]
+struct RemoteSignResponse {
+    signature: String,
+}
+
+impl RemoteSigner {
+    pub fn new(
+        key_id: &str,
+        url: &str,
+        token: &str,
+        public_key: &str,
+    ) -> Result<Self, SignerError> {
+        Ok(Self {
+            key_id: key_id.to_string(),
+            url: url.to_string(),
+            token: token.to_string(),
+            public_key: public_key.to_string(),
+            verifying_key: verifying_key(public_key)?,
+            http: HttpClient::new(
+                "remote_signer",
+                HttpPolicy {
+                    retry_unsafe_methods: true,
+                    ..HttpPolicy::default()
+                },
+            ),
+        })
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn is_hardware_backed(&self) -> bool {
+        true
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> SignFuture<'a, [u8; 64]> {
+        Box::pin(async move {
+            let request =
+                self.http
+                    .post(&self.url)
+                    .bearer_auth(&self.token)
+                    .json(&serde_json::json!({
+                        "key_id": self.key_id,
+                        "payload": base64::engine::general_purpose::STANDARD.encode(payload),
+                    }));
+            let response = self
+                .http
+                .send(request)
+                .await
+                .map_err(|e| SignerError::Unavailable(e.to_string()))?;
+            if !response.status().is_success() {
+                return Err(SignerError::Unavailable(format!(
+                    "remote signer returned {}",
+                    response.status()
+                )));
+            }
+            let signed: RemoteSignResponse = response
+                .json()
+                .await
+                .map_err(|e| SignerError::Unavailable(e.to_string()))?;
+            let bytes = hex::decode(signed.signature.trim_start_matches("0x"))
+                .map_err(|_| SignerError::InvalidSignature)?;
+            checked_signature(&self.verifying_key, payload, &bytes)
+        })
+    }
+}
+
+/// What a signature authorizes, for [`SigningPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SigningOperation {
+    /// A payout transaction; `amount` is its largest transfer in base units.
+    Payout {
+        amount: Decimal,
+    },
+    ContractInvocation,
+}
+
+impl SigningOperation {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Payout { .. } => "payout",
+            Self::ContractInvocation => "contract invocation",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SigningPolicy {
+    /// Payouts with a transfer above this many base units need the
+    /// hardware-backed key.
+    pub hsm_payout_threshold: Option<Decimal>,
+    /// Contract invocations always need the hardware-backed key.
+    pub hsm_contract_invocations: bool,
+}
+
+impl SigningPolicy {
+    pub fn requires_hardware(&self, operation: SigningOperation) -> bool {
+        match operation {
+            SigningOperation::Payout { amount } => self
+                .hsm_payout_threshold
+                .is_some_and(|threshold| amount > threshold),
+            SigningOperation::ContractInvocation => self.hsm_contract_invocations,
+        }
+    }
+}
+
+/// The hot key, the optional hardware-backed key and the policy choosing
+/// between them.
+pub struct KeyRing {
+    hot: RwLock<Arc<dyn Signer>>,
+    hardware: Option<Arc<dyn Signer>>,
+    policy: SigningPolicy,
+}
+
+impl KeyRing {
+    pub fn new(
+        hot: Arc<dyn Signer>,
+        hardware: Option<Arc<dyn Signer>>,
+        policy: SigningPolicy,
+    ) -> Self {
+        Self {
+            hot: RwLock::new(hot),
+            hardware,
+            policy,
+        }
+    }
+
+    /// Key for `operation`. A hardware-backed hot key satisfies the policy
+    /// on its own.
+    pub fn signer_for(&self, operation: SigningOperation) -> Result<Arc<dyn Signer>, SignerError> {
+        let hot = self.hot.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if !self.policy.requires_hardware(operation) || hot.is_hardware_backed() {
+            return Ok(hot);
+        }
+        self.hardware
+            .clone()
+            .ok_or(SignerError::PolicyViolation(operation.as_str()))
+    }
+
+    /// Makes `signer` the hot key; signatures already in progress finish
+    /// with the old one. Returns the retired key.
+    pub fn rotate(&self, signer: Arc<dyn Signer>) -> Arc<dyn Signer> {
+        std::mem::replace(
+            &mut *self.hot.write().unwrap_or_else(|e| e.into_inner()),
+            signer,
+        )
+    }
+
+    pub fn policy(&self) -> &SigningPolicy {
+        &self.policy
+    }
+
+    /// Builds the key ring from `SIGNER_*` (hot key) and `HSM_SIGNER_*`
+    /// (hardware-backed key). `None` when no hot key is configured.
+    pub fn from_env(cipher: &FieldCipher) -> Result<Option<Self>, SignerError> {
+        let Some(hot) = signer_from_env("SIGNER", cipher)? else {
+            return Ok(None);
+        };
+        let hardware = signer_from_env("HSM_SIGNER", cipher)?;
+        if hardware.as_ref().is_some_and(|s| !s.is_hardware_backed()) {
+            return Err(SignerError::NotConfigured(
+                "HSM_SIGNER_BACKEND must be aws_kms or remote".to_string(),
+            ));
+        }
+        let policy = SigningPolicy {
+            hsm_payout_threshold: env("SIGNER_HSM_PAYOUT_THRESHOLD")
+                .map(|v| {
+                    Decimal::from_str(&v).map_err(|_| {
+                        SignerError::NotConfigured(format!(
+                            "SIGNER_HSM_PAYOUT_THRESHOLD is not a number: {v}"
+                        ))
+                    })
+                })
+                .transpose()?,
+            hsm_contract_invocations: env("SIGNER_HSM_CONTRACT_INVOCATIONS")
+                .is_some_and(|v| v == "true"),
+        };
+        Ok(Some(Self::new(hot, hardware, policy)))
+    }
+}
+
+impl std::fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hot = self.hot.read().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("KeyRing")
+            .field("hot", &hot.key_id())
+            .field("hardware", &self.hardware.as_ref().map(|s| s.key_id()))
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+fn env(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn required(key: String) -> Result<String, SignerError> {
+    env(&key).ok_or(SignerError::NotConfigured(format!("{key} is not set")))
+}
+
+/// One signer from `{prefix}_BACKEND` (`local`, `aws_kms` or `remote`) and
+/// its settings. `None` when the backend is unset.
+fn signer_from_env(
+    prefix: &str,
+    cipher: &FieldCipher,
+) -> Result<Option<Arc<dyn Signer>>, SignerError> {
+    let Some(backend) = env(&format!("{prefix}_BACKEND")) else {
+        return Ok(None);
+    };
+    let signer: Arc<dyn Signer> = match backend.as_str() {
+        "local" => Arc::new(LocalKeystoreSigner::load(
+            Path::new(&required(format!("{prefix}_KEYSTORE_PATH"))?),
+            cipher,
+        )?),
+        "aws_kms" => Arc::new(AwsKmsSigner::new(
+            &required(format!("{prefix}_KEY_ID"))?,
+            &required(format!("{prefix}_KMS_KEY_ID"))?,
+            &required(format!("{prefix}_KMS_REGION"))?,
+            &required(format!("{prefix}_PUBLIC_KEY"))?,
+            AwsCredentials::from_env().ok_or(SignerError::NotConfigured(
+                "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are not set".to_string(),
+            ))?,
+        )?),
+        "remote" => Arc::new(RemoteSigner::new(
+            &required(format!("{prefix}_KEY_ID"))?,
+            &required(format!("{prefix}_REMOTE_URL"))?,
+            &required(format!("{prefix}_REMOTE_TOKEN"))?,
+            &required(format!("{prefix}_PUBLIC_KEY"))?,
+        )?),
+        other => {
+            return Err(SignerError::NotConfigured(format!(
+                "unknown {prefix}_BACKEND {other}"
+            )))
+        }
+    };
+    Ok(Some(signer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field_crypto::EncryptionKey;
+
+    fn cipher() -> FieldCipher {
+        FieldCipher::from_keys(&[EncryptionKey::generate("v1")])
+    }
+
+    #[tokio::test]
+    async fn keystore_round_trips_and_signs() {
+        let cipher = cipher();
+        let file = LocalKeystoreSigner::generate("hot-1", &cipher).unwrap();
+        assert!(file.sealed_seed.starts_with("enc:v1:"));
+
+        let signer = LocalKeystoreSigner::open(&file, &cipher).unwrap();
+        let signature = signer.sign(b"tx hash").await.unwrap();
+        let key = verifying_key(signer.public_key()).unwrap();
+        assert!(checked_signature(&key, b"tx hash", &signature).is_ok());
+        assert!(checked_signature(&key, b"other", &signature).is_err());
+
+        assert!(LocalKeystoreSigner::open(&file, &self::cipher()).is_err());
+        assert!(LocalKeystoreSigner::generate("hot-2", &FieldCipher::disabled()).is_err());
+    }
+
+    #[test]
+    fn large_payouts_need_the_hardware_key() {
+        let cipher = cipher();
+        let hot: Arc<dyn Signer> = Arc::new(
+            LocalKeystoreSigner::open(
+                &LocalKeystoreSigner::generate("hot-1", &cipher).unwrap(),
+                &cipher,
+            )
+            .unwrap(),
+        );
+        let policy = SigningPolicy {
+            hsm_payout_threshold: Some(Decimal::from(1_000)),
+            hsm_contract_invocations: false,
+        };
+        let ring = KeyRing::new(hot, None, policy.clone());
+
+        let small = SigningOperation::Payout {
+            amount: Decimal::from(1_000),
+        };
+        let large = SigningOperation::Payout {
+            amount: Decimal::from(1_001),
+        };
+        assert_eq!(ring.signer_for(small).unwrap().key_id(), "hot-1");
+        assert!(matches!(
+            ring.signer_for(large),
+            Err(SignerError::PolicyViolation("payout"))
+        ));
+
+        let hsm: Arc<dyn Signer> = Arc::new(
+            RemoteSigner::new(
+                "hsm-1",
+                "https://signer.invalid/sign",
+                "token",
+                &LocalKeystoreSigner::generate("hsm-1", &cipher)
+                    .unwrap()
+                    .public_key,
+            )
+            .unwrap(),
+        );
+        let ring = KeyRing::new(ring.signer_for(small).unwrap(), Some(hsm), policy);
+        assert_eq!(ring.signer_for(large).unwrap().key_id(), "hsm-1");
+
+        let next: Arc<dyn Signer> = Arc::new(
+            LocalKeystoreSigner::open(
+                &LocalKeystoreSigner::generate("hot-2", &cipher).unwrap(),
+                &cipher,
+            )
+            .unwrap(),
+        );
+        assert_eq!(ring.rotate(next).key_id(), "hot-1");
+        assert_eq!(ring.signer_for(small).unwrap().key_id(), "hot-2");
+    }
+
+    #[test]
+    fn sigv4_matches_the_aws_reference_signing_key() {
+        // From the AWS "derive a signing key" example.
+        let key = sigv4_signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use rand::RngCore;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::info;
+use uuid::Uuid;
+
+use super::errors::{contract_code, ContractError, ContractInterface};
+use super::signer::{KeyRing, SigningOperation};
+
+/// Longest a submitted transaction stays valid. Implementations set its time
+/// bounds to at most this, so a transaction the indexer has not seen once
+/// this has passed can no longer land.
+pub const TX_VALIDITY: Duration = Duration::from_secs(5 * 60);
+
+/// A single token transfer to be included in a submitted transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenTransfer {
+    /// Caller-side identifier (e.g. the payout id) echoed back in the receipt.
+    pub reference: Uuid,
+    pub token: String,
+    pub destination: String,
+    pub amount: Decimal,
+    /// Text memo required by the destination (e.g. an anchor deposit memo).
+    /// Transfers submitted together share one transaction and must carry
+    /// the same memo.
+    pub memo: Option<String>,
+    /// Correlation id of the request that created the payout. The memo is
+    /// taken by the batch or the anchor, so this is recorded alongside the
+    /// submission instead.
+    pub correlation_id: Option<String>,
+    /// Token the destination receives instead of `token`, swapped on the
+    /// way (a path payment).
+    pub conversion: Option<Conversion>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    pub token: String,
+    /// The transfer fails rather than deliver less than this.
+    pub min_amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferOutcome {
+    Succeeded,
+    Failed(String),
+}
+
+/// Result of a submitted batch; `outcomes` is index-aligned with the
+/// transfers that were submitted.
+#[derive(Debug, Clone)]
+pub struct BatchReceipt {
+    pub tx_hash: String,
+    pub outcomes: Vec<TransferOutcome>,
+}
+
+/// A single contract entrypoint call. Arguments are passed as their string
+/// form (addresses as strkeys, integers in decimal).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractInvocation {
+    pub contract_id: String,
+    pub function: String,
+    pub args: Vec<String>,
+    /// Text memo the transaction is sent with, normally the current
+    /// operation's correlation memo (see [`crate::telemetry::current_memo`]).
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum TxError {
+    #[error("transaction rejected: {0}")]
+    Rejected(String),
+    /// The submission may or may not have landed (e.g. a timeout after the
+    /// transaction was sent).
+    #[error("network unavailable: {0}")]
+    Unavailable(String),
+    /// The contract returned one of its declared error codes.
+    #[error("contract error {0}")]
+    Contract(ContractError),
+}
+
+impl TxError {
+    /// Classifies a failed submission from its host error message, decoding
+    /// contract error codes for `interface` where possible.
+    pub fn from_host_error(interface: ContractInterface, message: &str) -> Self {
+        contract_code(message)
+            .and_then(|code| ContractError::decode(interface, code))
+            .map(Self::Contract)
+            .unwrap_or_else(|| Self::Rejected(message.to_string()))
+    }
+}
+
+pub type TxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, TxError>> + Send + 'a>>;
+
+/// Submits token transfers on-chain.
+///
+/// Implementations group all transfers of one call into a single transaction
+/// and report a per-transfer outcome so callers can handle partial failure.
+pub trait TxService: Send + Sync {
+    fn submit_transfers<'a>(&'a self, transfers: &'a [TokenTransfer])
+        -> TxFuture<'a, BatchReceipt>;
+
+    /// Submits one contract invocation and returns the transaction hash.
+    fn invoke_contract<'a>(&'a self, invocation: &'a ContractInvocation) -> TxFuture<'a, String>;
+
+    /// Looks up a landed transfer transaction by its memo through the
+    /// network indexer. `None` means no such transaction has landed yet.
+    fn find_transfers_by_memo<'a>(&'a self, memo: &'a str) -> TxFuture<'a, Option<BatchReceipt>>;
+}
+
+fn random_tx_hash() -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut hash);
+    hash
+}
+
+/// Logs transfers and reports them as succeeded without touching the network.
+/// Contributors: Replace with an RPC-backed implementation that signs and submits.
+#[derive(Debug, Default)]
+pub struct SimulatedTxService {
+    /// Receipts of memo-tagged submissions, standing in for the indexer.
+    landed: Mutex<HashMap<String, BatchReceipt>>,
+    /// Signs each transaction hash with the key the policy picks.
+    keys: Option<Arc<KeyRing>>,
+}
+
+impl SimulatedTxService {
+    pub fn with_keys(keys: Arc<KeyRing>) -> Self {
+        Self {
+            keys: Some(keys),
+            ..Self::default()
+        }
+    }
+
+    /// Signs `tx_hash` for `operation`, or refuses when the policy needs a
+    /// key that is not configured. Without a key ring nothing is signed.
+    async fn sign(&self, tx_hash: &[u8; 32], operation: SigningOperation) -> Result<(), TxError> {
+        let Some(keys) = &self.keys else {
+            return Ok(());
+        };
+        let signer = keys
+            .signer_for(operation)
+            .map_err(|e| TxError::Rejected(e.to_string()))?;
+        signer.sign(tx_hash).await.map_err(|e| match e {
+            super::signer::SignerError::Unavailable(_) => TxError::Unavailable(e.to_string()),
+            _ => TxError::Rejected(e.to_string()),
+        })?;
+        info!(
+            tx_hash = %hex::encode(tx_hash),
+            key_id = signer.key_id(),
+            signer = signer.public_key(),
+            "Signed transaction"
+        );
+        Ok(())
+    }
+}
+
+impl TxService for SimulatedTxService {
+    fn submit_transfers<'a>(
+        &'a self,
+        transfers: &'a [TokenTransfer],
+    ) -> TxFuture<'a, BatchReceipt> {
+        Box::pin(async move {
+            let hash = random_tx_hash();
+            let amount = transfers.iter().map(|t| t.amount).max().unwrap_or_default();
+            self.sign(&hash, SigningOperation::Payout { amount })
+                .await?;
+            let tx_hash = hex::encode(hash);
+
+            for transfer in transfers {
+                info!(
+                    tx_hash = %tx_hash,
+                    reference = %transfer.reference,
+                    token = %transfer.token,
+                    destination = %transfer.destination,
+                    amount = %transfer.amount,
+                    memo = ?transfer.memo,
+                    correlation_id = ?transfer.correlation_id,
+                    conversion = ?transfer.conversion,
+                    "Simulated token transfer"
+                );
+            }
+
+            let receipt = BatchReceipt {
+                tx_hash,
+                outcomes: vec![TransferOutcome::Succeeded; transfers.len()],
+            };
+            if let Some(memo) = transfers.first().and_then(|t| t.memo.clone()) {
+                self.landed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(memo, receipt.clone());
+            }
+            Ok(receipt)
+        })
+    }
+
+    fn invoke_contract<'a>(&'a self, invocation: &'a ContractInvocation) -> TxFuture<'a, String> {
+        Box::pin(async move {
+            let hash = random_tx_hash();
+            self.sign(&hash, SigningOperation::ContractInvocation)
+                .await?;
+            let tx_hash = hex::encode(hash);
+            info!(
+                tx_hash = %tx_hash,
+                contract_id = %invocation.contract_id,
+                function = %invocation.function,
+                args = ?invocation.args,
+                memo = ?invocation.memo,
+                "Simulated contract invocation"
+            );
+            Ok(tx_hash)
+        })
+    }
+
+    fn find_transfers_by_memo<'a>(&'a self, memo: &'a str) -> TxFuture<'a, Option<BatchReceipt>> {
+        Box::pin(async move {
+            Ok(self
+                .landed
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(memo)
+                .cloned())
+        })
+    }
+}
@@ -0,0 +1,856 @@
+//! Backend proof-of-life check-ins.
+//!
+//! Complements the on-chain dead-man switch. Each wallet checks in every
+//! `interval_days`; once a check-in is overdue the escalation worker moves it
+//! through `active → reminded → contact_notified → escalated`, emailing the
+//! owner, then their secondary contact, and finally marking the owner's plans
+//! claimable. The grace periods between stages are system settings. Every
+//! step, check-in and admin override is audit logged.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::cache::PlanCache;
+use crate::emergency_contacts::{ContactAlert, ContactNotifier};
+use crate::jobs::JobRegistry;
+use crate::mailer::{is_plausible_email, Mailer};
+use crate::notifications::create_notification;
+use crate::system_settings::SystemSettingsCache;
+
+const DEFAULT_SWEEP_INTERVAL_SECS: u64 = 60 * 60;
+pub(crate) const DEFAULT_CONTACT_AFTER_DAYS: i64 = 7;
+pub(crate) const DEFAULT_ESCALATE_AFTER_DAYS: i64 = 7;
+const DEFAULT_BATCH_SIZE: i64 = 200;
+const MAX_INTERVAL_DAYS: i32 = 3650;
+const CHECK_IN_LOCK_KEY: i64 = 825;
+const CLAIMABLE_STATUS: &str = "CLAIMABLE";
+
+const RECORD_COLUMNS: &str = "user_address, email, secondary_email, interval_days, \
+     last_check_in_at, stage, stage_changed_at, paused_until, created_at, updated_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckInStage {
+    Active,
+    Reminded,
+    ContactNotified,
+    Escalated,
+}
+
+impl CheckInStage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Reminded => "reminded",
+            Self::ContactNotified => "contact_notified",
+            Self::Escalated => "escalated",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(Self::Active),
+            "reminded" => Some(Self::Reminded),
+            "contact_notified" => Some(Self::ContactNotified),
+            "escalated" => Some(Self::Escalated),
+            _ => None,
+        }
+    }
+}
+
+/// How long each escalation stage waits before moving on.
+#[derive(Debug, Clone, Copy)]
+pub struct EscalationPolicy {
+    /// Time after the reminder before the secondary contact is notified.
+    pub contact_after: chrono::Duration,
+    /// Time after notifying the contact before inheritance is triggered.
+    pub escalate_after: chrono::Duration,
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self {
+            contact_after: chrono::Duration::days(DEFAULT_CONTACT_AFTER_DAYS),
+            escalate_after: chrono::Duration::days(DEFAULT_ESCALATE_AFTER_DAYS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CheckInRecord {
+    pub user_address: String,
+    pub email: Option<String>,
+    pub secondary_email: Option<String>,
+    pub interval_days: i32,
+    pub last_check_in_at: DateTime<Utc>,
+    pub stage: String,
+    pub stage_changed_at: DateTime<Utc>,
+    pub paused_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CheckInRecord {
+    /// When the next check-in is due; a pause pushes the deadline out.
+    pub fn next_check_in_due(&self) -> DateTime<Utc> {
+        let base = match self.paused_until {
+            Some(paused_until) if paused_until > self.last_check_in_at => paused_until,
+            _ => self.last_check_in_at,
+        };
+        base + chrono::Duration::days(i64::from(self.interval_days))
+    }
+
+    /// The stage this record should move to at `now`, if any.
+    pub fn due_stage(&self, now: DateTime<Utc>, policy: &EscalationPolicy) -> Option<CheckInStage> {
+        if self.paused_until.is_some_and(|until| until > now) {
+            return None;
+        }
+
+        match CheckInStage::parse(&self.stage)? {
+            CheckInStage::Active if now >= self.next_check_in_due() => Some(CheckInStage::Reminded),
+            CheckInStage::Reminded if now >= self.stage_changed_at + policy.contact_after => {
+                Some(CheckInStage::ContactNotified)
+            }
+            CheckInStage::ContactNotified
+                if now >= self.stage_changed_at + policy.escalate_after =>
+            {
+                Some(CheckInStage::Escalated)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckInStatus {
+    #[serde(flatten)]
+    pub record: CheckInRecord,
+    pub next_check_in_due: DateTime<Utc>,
+}
+
+impl From<CheckInRecord> for CheckInStatus {
+    fn from(record: CheckInRecord) -> Self {
+        let next_check_in_due = record.next_check_in_due();
+        Self {
+            record,
+            next_check_in_due,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckInSettingsRequest {
+    pub email: Option<String>,
+    pub secondary_email: Option<String>,
+    pub interval_days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverrideAction {
+    /// Record a check-in on the user's behalf and clear any escalation.
+    Reset,
+    /// Suspend escalation until `paused_until`.
+    Pause,
+    /// Trigger the inheritance flow immediately.
+    Escalate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckInOverrideRequest {
+    pub action: OverrideAction,
+    pub reason: String,
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+/// Loads the caller's record, creating it with defaults on first use.
+async fn load_or_create<'e, E>(
+    executor: E,
+    user_address: &str,
+) -> Result<CheckInRecord, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, CheckInRecord>(&format!(
+        r#"
+        INSERT INTO proof_of_life (user_address)
+        VALUES ($1)
+        ON CONFLICT (user_address) DO UPDATE SET user_address = EXCLUDED.user_address
+        RETURNING {RECORD_COLUMNS}
+        "#
+    ))
+    .bind(user_address)
+    .fetch_one(executor)
+    .await
+}
+
+// Handler: Get Check-in Status
+pub async fn get_check_in(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match load_or_create(&state.db_pool, &user_address).await {
+        Ok(record) => (StatusCode::OK, Json(CheckInStatus::from(record))).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to load check-in status");
+            database_error()
+        }
+    }
+}
+
+// Handler: Update Check-in Settings
+pub async fn update_check_in_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<CheckInSettingsRequest>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    for email in [&payload.email, &payload.secondary_email]
+        .into_iter()
+        .flatten()
+    {
+        if !is_plausible_email(email.trim()) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": format!("Invalid email address: {email}") })),
+            )
+                .into_response();
+        }
+    }
+    if let Some(days) = payload.interval_days {
+        if !(1..=MAX_INTERVAL_DAYS).contains(&days) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("interval_days must be between 1 and {MAX_INTERVAL_DAYS}")
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    let result = sqlx::query_as::<_, CheckInRecord>(&format!(
+        r#"
+        INSERT INTO proof_of_life (user_address, email, secondary_email, interval_days)
+        VALUES ($1, $2, $3, COALESCE($4, 30))
+        ON CONFLICT (user_address) DO UPDATE
+        SET email = COALESCE($2, proof_of_life.email),
+            secondary_email = COALESCE($3, proof_of_life.secondary_email),
+            interval_days = COALESCE($4, proof_of_life.interval_days),
+            updated_at = NOW()
+        RETURNING {RECORD_COLUMNS}
+        "#
+    ))
+    .bind(&user_address)
+    .bind(payload.email.as_deref().map(str::trim))
+    .bind(payload.secondary_email.as_deref().map(str::trim))
+    .bind(payload.interval_days)
+    .fetch_one(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(record) => (StatusCode::OK, Json(CheckInStatus::from(record))).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to update check-in settings");
+            database_error()
+        }
+    }
+}
+
+// Handler: Record Check-in
+pub async fn record_check_in(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<CheckInRecord>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let current = load_or_create(&mut *tx, &user_address).await?;
+        if current.stage == CheckInStage::Escalated.as_str() {
+            return Ok(None);
+        }
+
+        let record = mark_checked_in(&mut tx, &user_address).await?;
+        record_audit(
+            &mut *tx,
+            &user_address,
+            "check_in.recorded",
+            &user_address,
+            serde_json::json!({ "previous_stage": current.stage }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(record))
+    }
+    .await;
+
+    match result {
+        Ok(Some(record)) => (StatusCode::OK, Json(CheckInStatus::from(record))).into_response(),
+        Ok(None) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Inheritance has already been triggered for this wallet; contact support"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to record check-in");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Check-in Override
+pub async fn override_check_in(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(user_address): Path<String>,
+    Json(payload): Json<CheckInOverrideRequest>,
+) -> impl IntoResponse {
+    if payload.reason.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "A reason is required for overrides" })),
+        )
+            .into_response();
+    }
+    let paused_until = match (payload.action, payload.paused_until) {
+        (OverrideAction::Pause, Some(until)) if until > Utc::now() => Some(until),
+        (OverrideAction::Pause, _) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "paused_until must be a future timestamp" })),
+            )
+                .into_response();
+        }
+        _ => None,
+    };
+
+    let result: Result<(CheckInRecord, Vec<Claimable>), sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let current = load_or_create(&mut *tx, &user_address).await?;
+
+        let (record, claimable) = match payload.action {
+            OverrideAction::Reset => (mark_checked_in(&mut tx, &user_address).await?, Vec::new()),
+            OverrideAction::Pause => {
+                let record = sqlx::query_as::<_, CheckInRecord>(&format!(
+                    r#"
+                    UPDATE proof_of_life
+                    SET paused_until = $2,
+                        stage = CASE WHEN stage = 'escalated' THEN stage ELSE 'active' END,
+                        stage_changed_at = NOW(),
+                        updated_at = NOW()
+                    WHERE user_address = $1
+                    RETURNING {RECORD_COLUMNS}
+                    "#
+                ))
+                .bind(&user_address)
+                .bind(paused_until)
+                .fetch_one(&mut *tx)
+                .await?;
+                (record, Vec::new())
+            }
+            OverrideAction::Escalate => escalate(&mut tx, &user_address).await?,
+        };
+
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "check_in.override",
+            &user_address,
+            serde_json::json!({
+                "action": payload.action,
+                "reason": payload.reason.trim(),
+                "previous_stage": current.stage,
+                "paused_until": paused_until,
+                "plans_claimable": claimable.iter().map(|c| c.plan_id).collect::<Vec<_>>(),
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok((record, claimable))
+    }
+    .await;
+
+    match result {
+        Ok((record, claimable)) => {
+            invalidate_claimable(&state.plan_cache, &user_address, &claimable).await;
+            alert_claimable(&state.contacts, &state.db_pool, &user_address, &claimable).await;
+            info!(user_address = %user_address, admin = %admin.user_id, "Check-in override applied");
+            (StatusCode::OK, Json(CheckInStatus::from(record))).into_response()
+        }
+        Err(e) => {
+            error!(user_address = %user_address, error = %e, "Failed to apply check-in override");
+            database_error()
+        }
+    }
+}
+
+async fn mark_checked_in(
+    tx: &mut Transaction<'_, Postgres>,
+    user_address: &str,
+) -> Result<CheckInRecord, sqlx::Error> {
+    sqlx::query_as::<_, CheckInRecord>(&format!(
+        r#"
+        UPDATE proof_of_life
+        SET last_check_in_at = NOW(),
+            stage = 'active',
+            stage_changed_at = NOW(),
+            paused_until = NULL,
+            updated_at = NOW()
+        WHERE user_address = $1
+        RETURNING {RECORD_COLUMNS}
+        "#
+    ))
+    .bind(user_address)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// A plan made claimable by an escalation, with the beneficiaries to notify.
+#[derive(Debug)]
+struct Claimable {
+    plan_id: Uuid,
+    beneficiaries: Vec<String>,
+}
+
+/// Marks the owner's record escalated and their active plans claimable,
+/// notifying each beneficiary.
+async fn escalate(
+    tx: &mut Transaction<'_, Postgres>,
+    user_address: &str,
+) -> Result<(CheckInRecord, Vec<Claimable>), sqlx::Error> {
+    let record = sqlx::query_as::<_, CheckInRecord>(&format!(
+        r#"
+        UPDATE proof_of_life
+        SET stage = 'escalated', stage_changed_at = NOW(), paused_until = NULL, updated_at = NOW()
+        WHERE user_address = $1
+        RETURNING {RECORD_COLUMNS}
+        "#
+    ))
+    .bind(user_address)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    let plan_ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE plans
+        SET status = $2
+        WHERE owner_address = $1
+          AND COALESCE(is_active, true) = true
+          AND status <> $2
+        RETURNING id
+        "#,
+    )
+    .bind(user_address)
+    .bind(CLAIMABLE_STATUS)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut claimable = Vec::with_capacity(plan_ids.len());
+    for plan_id in plan_ids {
+        let beneficiaries: Vec<String> =
+            sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+                .bind(plan_id)
+                .fetch_all(&mut **tx)
+                .await?;
+
+        for beneficiary in &beneficiaries {
+            create_notification(
+                &mut **tx,
+                beneficiary,
+                "plan_claimable",
+                "An inheritance plan is ready to claim",
+                "The plan owner missed their proof-of-life check-ins and the plan is now claimable.",
+                serde_json::json!({ "plan_id": plan_id }),
+            )
+            .await?;
+        }
+        claimable.push(Claimable {
+            plan_id,
+            beneficiaries,
+        });
+    }
+
+    Ok((record, claimable))
+}
+
+async fn invalidate_claimable(cache: &PlanCache, owner_address: &str, claimable: &[Claimable]) {
+    for plan in claimable {
+        if let Err(err) = cache
+            .invalidate_queries(owner_address, &plan.beneficiaries)
+            .await
+        {
+            warn!(plan_id = %plan.plan_id, error = %err, "Failed to invalidate plan cache after escalation");
+        }
+    }
+}
+
+async fn alert_claimable(
+    contacts: &ContactNotifier,
+    db: &PgPool,
+    owner_address: &str,
+    claimable: &[Claimable],
+) {
+    for plan in claimable {
+        let alert = ContactAlert::PlanClaimable {
+            plan_id: plan.plan_id,
+        };
+        if let Err(e) = contacts.alert(db, owner_address, alert).await {
+            warn!(plan_id = %plan.plan_id, error = %e, "Failed to alert emergency contacts");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CheckInEscalationConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+}
+
+impl CheckInEscalationConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("CHECK_IN_SWEEP_INTERVAL_SECS", DEFAULT_SWEEP_INTERVAL_SECS);
+        let batch_size = parse_env("CHECK_IN_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EscalationSummary {
+    pub reminded: usize,
+    pub contacts_notified: usize,
+    pub escalated: usize,
+}
+
+/// Email queued during a sweep and sent once its transaction commits.
+struct PendingEmail {
+    to: String,
+    subject: String,
+    body: String,
+}
+
+pub struct CheckInEscalationService {
+    db: PgPool,
+    mailer: Arc<Mailer>,
+    contacts: Arc<ContactNotifier>,
+    plan_cache: PlanCache,
+    settings: Arc<SystemSettingsCache>,
+    config: CheckInEscalationConfig,
+}
+
+impl CheckInEscalationService {
+    pub fn new(
+        db: PgPool,
+        mailer: Arc<Mailer>,
+        contacts: Arc<ContactNotifier>,
+        plan_cache: PlanCache,
+        settings: Arc<SystemSettingsCache>,
+        config: CheckInEscalationConfig,
+    ) -> Self {
+        Self {
+            db,
+            mailer,
+            contacts,
+            plan_cache,
+            settings,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("check_in_escalation", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|summary| {
+                    if *summary != EscalationSummary::default() {
+                        info!(
+                            reminded = summary.reminded,
+                            contacts_notified = summary.contacts_notified,
+                            escalated = summary.escalated,
+                            "Check-in escalation sweep finished"
+                        );
+                    }
+                })
+            }
+        });
+    }
+
+    pub async fn run_once(&self) -> Result<EscalationSummary, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(CHECK_IN_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Check-in escalation lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(EscalationSummary::default());
+        }
+
+        let overdue = sqlx::query_as::<_, CheckInRecord>(&format!(
+            r#"
+            SELECT {RECORD_COLUMNS}
+            FROM proof_of_life
+            WHERE stage <> 'escalated'
+              AND (paused_until IS NULL OR paused_until <= NOW())
+              AND last_check_in_at + (interval_days * INTERVAL '1 day') <= NOW()
+            ORDER BY last_check_in_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#
+        ))
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let now = Utc::now();
+        let policy = self.settings.get().await.escalation_policy();
+        let mut summary = EscalationSummary::default();
+        let mut emails = Vec::new();
+        let mut escalated = Vec::new();
+        let mut overdue_alerts = Vec::new();
+
+        for record in overdue {
+            let Some(next) = record.due_stage(now, &policy) else {
+                continue;
+            };
+
+            match next {
+                CheckInStage::Reminded | CheckInStage::ContactNotified => {
+                    sqlx::query(
+                        r#"
+                        UPDATE proof_of_life
+                        SET stage = $2, stage_changed_at = NOW(), updated_at = NOW()
+                        WHERE user_address = $1
+                        "#,
+                    )
+                    .bind(&record.user_address)
+                    .bind(next.as_str())
+                    .execute(&mut *tx)
+                    .await?;
+
+                    create_notification(
+                        &mut *tx,
+                        &record.user_address,
+                        "check_in_overdue",
+                        "Proof-of-life check-in overdue",
+                        "Check in to confirm you are still active, or your inheritance plans will be escalated.",
+                        serde_json::json!({ "stage": next.as_str() }),
+                    )
+                    .await?;
+
+                    if let Some(email) = self.email_for(&record, next, &policy) {
+                        emails.push(email);
+                    }
+                    if next == CheckInStage::Reminded {
+                        summary.reminded += 1;
+                    } else {
+                        summary.contacts_notified += 1;
+                        overdue_alerts.push((record.user_address.clone(), record.last_check_in_at));
+                    }
+                }
+                CheckInStage::Escalated => {
+                    let (_, claimable) = escalate(&mut tx, &record.user_address).await?;
+                    summary.escalated += 1;
+                    escalated.push((record.user_address.clone(), claimable));
+                }
+                CheckInStage::Active => continue,
+            }
+
+            record_audit(
+                &mut *tx,
+                SYSTEM_ACTOR,
+                &format!("check_in.{}", next.as_str()),
+                &record.user_address,
+                serde_json::json!({
+                    "previous_stage": record.stage,
+                    "last_check_in_at": record.last_check_in_at,
+                }),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        for (owner, claimable) in &escalated {
+            warn!(user_address = %owner, plans = claimable.len(), "Missed check-ins escalated to inheritance");
+            invalidate_claimable(&self.plan_cache, owner, claimable).await;
+            alert_claimable(&self.contacts, &self.db, owner, claimable).await;
+        }
+        for (owner, last_check_in_at) in overdue_alerts {
+            let alert = ContactAlert::CheckInOverdue { last_check_in_at };
+            if let Err(e) = self.contacts.alert(&self.db, &owner, alert).await {
+                warn!(user_address = %owner, error = %e, "Failed to alert emergency contacts");
+            }
+        }
+        for email in emails {
+            if let Err(e) = self
+                .mailer
+                .send(&email.to, &email.subject, &email.body)
+                .await
+            {
+                warn!(to = %email.to, error = %e, "Failed to send check-in email");
+            }
+        }
+
+        Ok(summary)
+    }
+
+    fn email_for(
+        &self,
+        record: &CheckInRecord,
+        stage: CheckInStage,
+        policy: &EscalationPolicy,
+    ) -> Option<PendingEmail> {
+        match stage {
+            CheckInStage::Reminded => Some(PendingEmail {
+                to: record.email.clone()?,
+                subject: "Please check in with InheritX".to_string(),
+                body: format!(
+                    "Your proof-of-life check-in was due on {}. Please sign in and check in. \
+                     If we do not hear from you within {} day(s), your secondary contact will be notified.",
+                    record.next_check_in_due().format("%Y-%m-%d"),
+                    policy.contact_after.num_days()
+                ),
+            }),
+            CheckInStage::ContactNotified => Some(PendingEmail {
+                to: record.secondary_email.clone()?,
+                subject: "An InheritX user has not checked in".to_string(),
+                body: format!(
+                    "You are listed as the secondary contact for wallet {}, which has not checked in \
+                     since {}. If you can reach them, please ask them to check in. Their inheritance \
+                     plans will become claimable in {} day(s).",
+                    record.user_address,
+                    record.last_check_in_at.format("%Y-%m-%d"),
+                    policy.escalate_after.num_days()
+                ),
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn record(
+        stage: CheckInStage,
+        last_check_in_days_ago: i64,
+        stage_days_ago: i64,
+    ) -> CheckInRecord {
+        let now = now();
+        CheckInRecord {
+            user_address: "GOWNER".to_string(),
+            email: Some("owner@example.com".to_string()),
+            secondary_email: None,
+            interval_days: 30,
+            last_check_in_at: now - chrono::Duration::days(last_check_in_days_ago),
+            stage: stage.as_str().to_string(),
+            stage_changed_at: now - chrono::Duration::days(stage_days_ago),
+            paused_until: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn escalation_advances_one_stage_at_a_time() {
+        let policy = EscalationPolicy::default();
+
+        assert_eq!(
+            record(CheckInStage::Active, 29, 29).due_stage(now(), &policy),
+            None
+        );
+        assert_eq!(
+            record(CheckInStage::Active, 30, 30).due_stage(now(), &policy),
+            Some(CheckInStage::Reminded)
+        );
+        assert_eq!(
+            record(CheckInStage::Reminded, 35, 5).due_stage(now(), &policy),
+            None
+        );
+        assert_eq!(
+            record(CheckInStage::Reminded, 37, 7).due_stage(now(), &policy),
+            Some(CheckInStage::ContactNotified)
+        );
+        assert_eq!(
+            record(CheckInStage::ContactNotified, 44, 7).due_stage(now(), &policy),
+            Some(CheckInStage::Escalated)
+        );
+        assert_eq!(
+            record(CheckInStage::Escalated, 90, 30).due_stage(now(), &policy),
+            None
+        );
+    }
+
+    #[test]
+    fn pause_holds_escalation_and_extends_deadline() {
+        let policy = EscalationPolicy::default();
+        let mut paused = record(CheckInStage::Active, 60, 60);
+
+        paused.paused_until = Some(now() + chrono::Duration::days(1));
+        assert_eq!(paused.due_stage(now(), &policy), None);
+
+        paused.paused_until = Some(now() - chrono::Duration::days(1));
+        assert_eq!(
+            paused.next_check_in_due(),
+            now() + chrono::Duration::days(29)
+        );
+        assert_eq!(paused.due_stage(now(), &policy), None);
+    }
+}
@@ -0,0 +1,408 @@
+//! Delegated claim filing by estate executors.
+//!
+//! A beneficiary can let another wallet, usually an executor or lawyer, act
+//! on their claim for one plan. Each grant lists its [`DelegationScope`]s,
+//! expires at a set time and can be revoked by either party. Payout
+//! destinations are never delegable: they are keyed by the signing wallet,
+//! so an executor only ever changes their own. Delegated actions are audit
+//! logged with the executor as the actor and the beneficiary in the
+//! details, and a claim filed this way records both.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::freezes::user_frozen;
+use crate::notifications::create_notification;
+
+/// Longest a grant may run before the beneficiary has to renew it.
+pub const MAX_DELEGATION_DAYS: i64 = 365;
+
+const DELEGATION_COLUMNS: &str = "id, plan_id, beneficiary_address, executor_address, scopes, \
+     expires_at, revoked_at, revoked_by, created_at, \
+     (revoked_at IS NULL AND expires_at > NOW()) AS active";
+
+/// What an executor may do for the beneficiary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DelegationScope {
+    /// Request a claim with `POST /api/plans/{id}/claim`.
+    FileClaim,
+    /// Upload supporting documents for the claim.
+    UploadDocuments,
+}
+
+impl DelegationScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FileClaim => "file_claim",
+            Self::UploadDocuments => "upload_documents",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClaimDelegation {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub beneficiary_address: String,
+    pub executor_address: String,
+    pub scopes: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Not revoked and not yet expired.
+    pub active: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrantDelegationRequest {
+    pub executor_address: String,
+    pub scopes: Vec<DelegationScope>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The beneficiary an action is for and, when delegated, who performed it.
+#[derive(Debug, Clone)]
+pub struct Claimant {
+    pub beneficiary: String,
+    pub executor: Option<String>,
+    pub delegation_id: Option<Uuid>,
+}
+
+impl Claimant {
+    /// Audit details naming both parties of a delegated action.
+    pub fn audit_details(&self, mut details: serde_json::Value) -> serde_json::Value {
+        if let (Some(executor), Some(fields)) = (&self.executor, details.as_object_mut()) {
+            fields.insert("on_behalf_of".into(), self.beneficiary.clone().into());
+            fields.insert("executor".into(), executor.clone().into());
+            fields.insert(
+                "delegation_id".into(),
+                serde_json::json!(self.delegation_id),
+            );
+        }
+        details
+    }
+}
+
+/// Resolves who `caller` is acting for. Without `on_behalf_of` the caller
+/// acts for themselves; otherwise they need an active grant with `scope`
+/// from that beneficiary on this plan. The inner error is why not.
+pub async fn claimant(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    caller: &str,
+    on_behalf_of: Option<&str>,
+    scope: DelegationScope,
+) -> Result<Result<Claimant, &'static str>, sqlx::Error> {
+    let beneficiary = match on_behalf_of.map(str::trim) {
+        Some(beneficiary) if beneficiary != caller => beneficiary,
+        _ => {
+            return Ok(Ok(Claimant {
+                beneficiary: caller.to_string(),
+                executor: None,
+                delegation_id: None,
+            }))
+        }
+    };
+
+    let delegation_id: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM claim_delegations
+        WHERE plan_id = $1 AND beneficiary_address = $2 AND executor_address = $3
+          AND revoked_at IS NULL AND expires_at > NOW()
+          AND $4 = ANY(scopes)
+        "#,
+    )
+    .bind(plan_id)
+    .bind(beneficiary)
+    .bind(caller)
+    .bind(scope.as_str())
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(delegation_id) = delegation_id else {
+        return Ok(Err(
+            "No active delegation from this beneficiary allows this",
+        ));
+    };
+    if user_frozen(&mut *conn, beneficiary).await? {
+        return Ok(Err("The beneficiary's account is frozen pending review"));
+    }
+
+    Ok(Ok(Claimant {
+        beneficiary: beneficiary.to_string(),
+        executor: Some(caller.to_string()),
+        delegation_id: Some(delegation_id),
+    }))
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+// Handler: Grant Claim Delegation
+pub async fn grant_delegation(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<GrantDelegationRequest>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let executor = payload.executor_address.trim().to_string();
+    if stellar_strkey::ed25519::PublicKey::from_string(&executor).is_err() {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Executor must be a Stellar account address (G...)",
+        );
+    }
+    if executor == caller {
+        return refused(StatusCode::BAD_REQUEST, "You cannot delegate to yourself");
+    }
+    let mut scopes: Vec<&str> = payload.scopes.iter().map(|s| s.as_str()).collect();
+    scopes.sort_unstable();
+    scopes.dedup();
+    if scopes.is_empty() {
+        return refused(StatusCode::BAD_REQUEST, "At least one scope is required");
+    }
+    let now = Utc::now();
+    if payload.expires_at <= now {
+        return refused(StatusCode::BAD_REQUEST, "expires_at must be in the future");
+    }
+    if payload.expires_at > now + Duration::days(MAX_DELEGATION_DAYS) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Delegations can run for at most 365 days",
+        );
+    }
+
+    let result: Result<Outcome<ClaimDelegation>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let is_beneficiary: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM plans p JOIN beneficiaries b ON b.plan_id = p.id
+                WHERE p.id = $1 AND p.is_active = true AND b.wallet_address = $2)
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&caller)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !is_beneficiary {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "No active plan found",
+            ));
+        }
+
+        // A new grant to the same executor replaces the previous one.
+        let replaced: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE claim_delegations SET revoked_at = NOW(), revoked_by = $2
+            WHERE plan_id = $1 AND beneficiary_address = $2 AND executor_address = $3
+              AND revoked_at IS NULL
+            RETURNING id
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&caller)
+        .bind(&executor)
+        .fetch_all(&mut *tx)
+        .await?;
+        let delegation = sqlx::query_as::<_, ClaimDelegation>(&format!(
+            r#"
+            INSERT INTO claim_delegations
+                (plan_id, beneficiary_address, executor_address, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {DELEGATION_COLUMNS}
+            "#
+        ))
+        .bind(plan_id)
+        .bind(&caller)
+        .bind(&executor)
+        .bind(&scopes)
+        .bind(payload.expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        record_audit(
+            &mut *tx,
+            &caller,
+            "claim_delegation.granted",
+            &delegation.id.to_string(),
+            serde_json::json!({
+                "plan_id": plan_id,
+                "beneficiary": caller,
+                "executor": executor,
+                "scopes": scopes,
+                "expires_at": delegation.expires_at,
+                "replaced": replaced,
+            }),
+        )
+        .await?;
+        create_notification(
+            &mut *tx,
+            &executor,
+            "claim_delegation_granted",
+            "You can act on a claim",
+            &format!(
+                "A beneficiary gave you access to their claim on a plan until {}.",
+                delegation.expires_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            serde_json::json!({
+                "plan_id": plan_id,
+                "delegation_id": delegation.id,
+                "beneficiary": caller,
+                "scopes": scopes,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(delegation))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(delegation)) => {
+            info!(delegation_id = %delegation.id, plan_id = %plan_id, "Claim delegation granted");
+            (StatusCode::CREATED, Json(delegation)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to grant claim delegation");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Claim Delegations
+pub async fn list_delegations(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, ClaimDelegation>(&format!(
+        r#"
+        SELECT {DELEGATION_COLUMNS} FROM claim_delegations
+        WHERE plan_id = $1 AND (beneficiary_address = $2 OR executor_address = $2)
+        ORDER BY created_at DESC
+        LIMIT 100
+        "#
+    ))
+    .bind(plan_id)
+    .bind(&caller)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(delegations) => (StatusCode::OK, Json(delegations)).into_response(),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to list claim delegations");
+            database_error()
+        }
+    }
+}
+
+// Handler: Revoke Claim Delegation
+pub async fn revoke_delegation(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path((plan_id, delegation_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<ClaimDelegation>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let revoked = sqlx::query_as::<_, ClaimDelegation>(&format!(
+            r#"
+            UPDATE claim_delegations SET revoked_at = NOW(), revoked_by = $3
+            WHERE id = $1 AND plan_id = $2 AND revoked_at IS NULL
+              AND (beneficiary_address = $3 OR executor_address = $3)
+            RETURNING {DELEGATION_COLUMNS}
+            "#
+        ))
+        .bind(delegation_id)
+        .bind(plan_id)
+        .bind(&caller)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(revoked) = revoked else {
+            return Ok(None);
+        };
+
+        record_audit(
+            &mut *tx,
+            &caller,
+            "claim_delegation.revoked",
+            &revoked.id.to_string(),
+            serde_json::json!({
+                "plan_id": plan_id,
+                "beneficiary": revoked.beneficiary_address,
+                "executor": revoked.executor_address,
+            }),
+        )
+        .await?;
+        let other = if caller == revoked.beneficiary_address {
+            &revoked.executor_address
+        } else {
+            &revoked.beneficiary_address
+        };
+        create_notification(
+            &mut *tx,
+            other,
+            "claim_delegation_revoked",
+            "Claim access revoked",
+            "Access to act on a claim for a plan has been revoked.",
+            serde_json::json!({
+                "plan_id": plan_id,
+                "delegation_id": revoked.id,
+                "revoked_by": caller,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(revoked))
+    }
+    .await;
+
+    match result {
+        Ok(Some(revoked)) => (StatusCode::OK, Json(revoked)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Active delegation not found"),
+        Err(e) => {
+            error!(delegation_id = %delegation_id, error = %e, "Failed to revoke claim delegation");
+            database_error()
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Supporting documents for a claim, such as a death certificate or
+//! letters of administration.
+//!
+//! A beneficiary uploads documents for their own claim, or an executor does
+//! so for them under an `upload_documents` delegation (see
+//! [`crate::claim_delegations`]). Documents travel as base64 in a signed
+//! JSON body and are stored sealed with the field cipher. Plan owners,
+//! beneficiaries and delegated executors can list them and download them.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::claim_delegations::{self, DelegationScope};
+use crate::field_crypto::SensitiveField;
+
+/// Largest document accepted, before base64 encoding.
+pub const MAX_DOCUMENT_BYTES: usize = 1024 * 1024;
+const MAX_FILE_NAME_LEN: usize = 255;
+const ALLOWED_CONTENT_TYPES: [&str; 3] = ["application/pdf", "image/jpeg", "image/png"];
+
+const DOCUMENT_COLUMNS: &str = "id, plan_id, beneficiary_address, uploaded_by, document_type, \
+     file_name, content_type, size_bytes, sha256, created_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentType {
+    DeathCertificate,
+    GrantOfProbate,
+    LettersOfAdministration,
+    Identity,
+    Other,
+}
+
+impl DocumentType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DeathCertificate => "death_certificate",
+            Self::GrantOfProbate => "grant_of_probate",
+            Self::LettersOfAdministration => "letters_of_administration",
+            Self::Identity => "identity",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClaimDocument {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    /// Beneficiary whose claim the document supports.
+    pub beneficiary_address: String,
+    /// The beneficiary, or the executor who uploaded it for them.
+    pub uploaded_by: String,
+    pub document_type: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i32,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClaimDocumentContent {
+    #[serde(flatten)]
+    pub document: ClaimDocument,
+    /// Base64 encoded file.
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadDocumentRequest {
+    pub document_type: DocumentType,
+    pub file_name: String,
+    pub content_type: String,
+    /// Base64 encoded file.
+    pub content: String,
+    /// Beneficiary to upload for, when acting as their executor.
+    #[serde(default)]
+    pub on_behalf_of: Option<String>,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// Owners, co-owners, beneficiaries and active executors may read a plan's
+/// claim documents.
+async fn can_read(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    caller: &str,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM plans p
+            WHERE p.id = $1
+              AND (p.owner_address = $2
+                   OR EXISTS (SELECT 1 FROM plan_co_owners o
+                              WHERE o.plan_id = p.id AND o.owner_address = $2
+                                AND o.status = 'accepted')
+                   OR EXISTS (SELECT 1 FROM beneficiaries b
+                              WHERE b.plan_id = p.id AND b.wallet_address = $2)
+                   OR EXISTS (SELECT 1 FROM claim_delegations d
+                              WHERE d.plan_id = p.id AND d.executor_address = $2
+                                AND d.revoked_at IS NULL AND d.expires_at > NOW())))
+        "#,
+    )
+    .bind(plan_id)
+    .bind(caller)
+    .fetch_one(conn)
+    .await
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+// Handler: Upload Claim Document
+pub async fn upload_claim_document(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<UploadDocumentRequest>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let file_name = payload.file_name.trim().to_string();
+    if file_name.is_empty() || file_name.len() > MAX_FILE_NAME_LEN {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "file_name must be 1-255 characters",
+        );
+    }
+    let content_type = payload.content_type.trim().to_ascii_lowercase();
+    if !ALLOWED_CONTENT_TYPES.contains(&content_type.as_str()) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Documents must be PDF, JPEG or PNG",
+        );
+    }
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(payload.content.trim()) else {
+        return refused(StatusCode::BAD_REQUEST, "content must be base64 encoded");
+    };
+    if bytes.is_empty() {
+        return refused(StatusCode::BAD_REQUEST, "Document is empty");
+    }
+    if bytes.len() > MAX_DOCUMENT_BYTES {
+        return refused(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Documents can be at most 1 MB",
+        );
+    }
+    let sha256 = hex::encode(Sha256::digest(&bytes));
+    let sealed = match state
+        .field_cipher
+        .encrypt(SensitiveField::ClaimDocument, payload.content.trim())
+    {
+        Ok(sealed) => sealed,
+        Err(e) => {
+            error!(error = %e, "Failed to encrypt claim document");
+            return database_error();
+        }
+    };
+
+    let result: Result<Outcome<ClaimDocument>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let claimant = match claim_delegations::claimant(
+            &mut tx,
+            plan_id,
+            &caller,
+            payload.on_behalf_of.as_deref(),
+            DelegationScope::UploadDocuments,
+        )
+        .await?
+        {
+            Ok(claimant) => claimant,
+            Err(message) => return Ok(Outcome::Refused(StatusCode::FORBIDDEN, message)),
+        };
+        let is_beneficiary: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM plans p JOIN beneficiaries b ON b.plan_id = p.id
+                WHERE p.id = $1 AND p.is_active = true AND b.wallet_address = $2)
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&claimant.beneficiary)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !is_beneficiary {
+            return Ok(Outcome::Refused(
+                StatusCode::FORBIDDEN,
+                "Only a beneficiary of this plan can upload claim documents",
+            ));
+        }
+
+        let document = sqlx::query_as::<_, ClaimDocument>(&format!(
+            r#"
+            INSERT INTO claim_documents
+                (plan_id, beneficiary_address, uploaded_by, document_type, file_name,
+                 content_type, size_bytes, sha256, content)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {DOCUMENT_COLUMNS}
+            "#
+        ))
+        .bind(plan_id)
+        .bind(&claimant.beneficiary)
+        .bind(&caller)
+        .bind(payload.document_type.as_str())
+        .bind(&file_name)
+        .bind(&content_type)
+        .bind(bytes.len() as i32)
+        .bind(&sha256)
+        .bind(&sealed)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &caller,
+            "claim_document.uploaded",
+            &document.id.to_string(),
+            claimant.audit_details(serde_json::json!({
+                "plan_id": plan_id,
+                "document_type": document.document_type,
+                "sha256": document.sha256,
+                "size_bytes": document.size_bytes,
+            })),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(document))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(document)) => {
+            info!(document_id = %document.id, plan_id = %plan_id, "Claim document uploaded");
+            (StatusCode::CREATED, Json(document)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to upload claim document");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Claim Documents
+pub async fn list_claim_documents(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<Vec<ClaimDocument>>, sqlx::Error> = async {
+        let mut conn = state.db_pool.acquire().await?;
+        if !can_read(&mut conn, plan_id, &caller).await? {
+            return Ok(None);
+        }
+        let documents = sqlx::query_as::<_, ClaimDocument>(&format!(
+            "SELECT {DOCUMENT_COLUMNS} FROM claim_documents WHERE plan_id = $1 ORDER BY created_at"
+        ))
+        .bind(plan_id)
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(Some(documents))
+    }
+    .await;
+
+    match result {
+        Ok(Some(documents)) => (StatusCode::OK, Json(documents)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to list claim documents");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get Claim Document
+pub async fn get_claim_document(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path((plan_id, document_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<ClaimDocumentContent>, sqlx::Error> = async {
+        let mut conn = state.db_pool.acquire().await?;
+        if !can_read(&mut conn, plan_id, &caller).await? {
+            return Ok(None);
+        }
+        let Some(document) = sqlx::query_as::<_, ClaimDocument>(&format!(
+            "SELECT {DOCUMENT_COLUMNS} FROM claim_documents WHERE id = $1 AND plan_id = $2"
+        ))
+        .bind(document_id)
+        .bind(plan_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let sealed: String =
+            sqlx::query_scalar("SELECT content FROM claim_documents WHERE id = $1")
+                .bind(document_id)
+                .fetch_one(&mut *conn)
+                .await?;
+        let content = state
+            .field_cipher
+            .decrypt_column(SensitiveField::ClaimDocument, &sealed)?;
+        Ok(Some(ClaimDocumentContent { document, content }))
+    }
+    .await;
+
+    match result {
+        Ok(Some(document)) => (StatusCode::OK, Json(document)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Document not found"),
+        Err(e) => {
+            error!(document_id = %document_id, error = %e, "Failed to load claim document");
+            database_error()
+        }
+    }
+}
@@ -0,0 +1,410 @@
+//! Evaluates whether a plan can be claimed yet.
+//!
+//! Combines the plan's inactivity deadline with the owner's proof-of-life
+//! check-ins and emergency contacts: a current check-in means the owner is
+//! alive, and an owner with verified contacts is only claimable once those
+//! contacts have been alerted. Joint plans must satisfy this for every owner.
+//! A plan frozen by an admin is never claimable until it is unfrozen.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct EligibilityPlanRow {
+    owner_address: String,
+    is_active: bool,
+    status: String,
+    last_ping: i64,
+    grace_period_seconds: i64,
+    frozen: bool,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct ContactRow {
+    name: String,
+    relationship: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+    email_verified_at: Option<DateTime<Utc>>,
+    phone_verified_at: Option<DateTime<Utc>>,
+    last_alerted_at: Option<DateTime<Utc>>,
+}
+
+/// Contact details shown to claimants, with email and phone masked.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactSummary {
+    pub name: String,
+    pub relationship: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub last_alerted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimEligibility {
+    pub plan_id: Uuid,
+    pub eligible: bool,
+    pub reasons: Vec<String>,
+    pub inactivity_deadline_at: DateTime<Utc>,
+    /// Claims are refused from this time on and the plan is escheated.
+    pub claim_expires_at: DateTime<Utc>,
+    pub check_in_stage: Option<String>,
+    pub next_check_in_due: Option<DateTime<Utc>>,
+    pub emergency_contacts: Vec<ContactSummary>,
+}
+
+/// Facts the evaluation depends on.
+#[derive(Debug, Clone)]
+pub struct EligibilityInput {
+    pub now: DateTime<Utc>,
+    pub is_active: bool,
+    pub marked_claimable: bool,
+    /// Frozen by an admin, see [`crate::freezes`].
+    pub frozen: bool,
+    pub inactivity_deadline_at: DateTime<Utc>,
+    pub claim_expires_at: DateTime<Utc>,
+    pub next_check_in_due: Option<DateTime<Utc>>,
+    /// Owner's last recorded activity (ping or check-in).
+    pub last_activity_at: DateTime<Utc>,
+    pub verified_contacts: usize,
+    pub last_contact_alert_at: Option<DateTime<Utc>>,
+}
+
+/// Returns whether the plan is claimable and, if not, why.
+pub fn evaluate(input: &EligibilityInput) -> (bool, Vec<String>) {
+    let mut reasons = Vec::new();
+
+    if !input.is_active {
+        reasons.push("Plan has already been paid out".to_string());
+    }
+    if input.frozen {
+        reasons.push("This plan is frozen pending review".to_string());
+    }
+    if !input.marked_claimable && input.now < input.inactivity_deadline_at {
+        reasons.push("The owner's inactivity deadline has not passed".to_string());
+    }
+    if input.is_active && input.now >= input.claim_expires_at {
+        reasons.push("The claim window for this plan has closed".to_string());
+    }
+    if input.next_check_in_due.is_some_and(|due| due > input.now) {
+        reasons.push("The owner's proof-of-life check-in is current".to_string());
+    }
+    if input.verified_contacts > 0
+        && input
+            .last_contact_alert_at
+            .is_none_or(|alerted| alerted < input.last_activity_at)
+    {
+        reasons.push("The owner's emergency contacts have not been alerted yet".to_string());
+    }
+
+    (reasons.is_empty(), reasons)
+}
+
+pub(crate) fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first = local.chars().next().unwrap_or('*');
+            format!("{first}***@{domain}")
+        }
+        None => "***".to_string(),
+    }
+}
+
+pub(crate) fn mask_phone(phone: &str) -> String {
+    let visible = phone.len().saturating_sub(4);
+    format!(
+        "{}{}",
+        "*".repeat(visible),
+        phone.get(visible..).unwrap_or_default()
+    )
+}
+
+/// The owner's proof-of-life check-in (stage, last check-in, next due) and
+/// verified emergency contacts.
+async fn owner_conditions(
+    db: &sqlx::PgPool,
+    owner: &str,
+) -> Result<
+    (
+        Option<(String, DateTime<Utc>, DateTime<Utc>)>,
+        Vec<ContactRow>,
+    ),
+    sqlx::Error,
+> {
+    let check_in = sqlx::query_as(
+        r#"
+        SELECT stage,
+               last_check_in_at,
+               GREATEST(last_check_in_at, COALESCE(paused_until, last_check_in_at))
+                   + (interval_days * INTERVAL '1 day')
+        FROM proof_of_life
+        WHERE user_address = $1
+        "#,
+    )
+    .bind(owner)
+    .fetch_optional(db)
+    .await?;
+
+    let contacts = sqlx::query_as::<_, ContactRow>(
+        r#"
+        SELECT name, relationship, email, phone,
+               email_verified_at, phone_verified_at, last_alerted_at
+        FROM emergency_contacts
+        WHERE user_address = $1
+          AND (email_verified_at IS NOT NULL OR phone_verified_at IS NOT NULL)
+        ORDER BY created_at
+        "#,
+    )
+    .bind(owner)
+    .fetch_all(db)
+    .await?;
+
+    Ok((check_in, contacts))
+}
+
+// Handler: Claim Eligibility
+pub async fn get_claim_eligibility(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<ClaimEligibility>, sqlx::Error> = async {
+        let Some(plan) = sqlx::query_as::<_, EligibilityPlanRow>(
+            r#"
+            SELECT owner_address, is_active, status, last_ping, grace_period_seconds,
+                   frozen_at IS NOT NULL AS frozen
+            FROM plans
+            WHERE id = $1
+              AND (owner_address = $2
+                   OR EXISTS (SELECT 1 FROM plan_co_owners o
+                              WHERE o.plan_id = plans.id AND o.owner_address = $2
+                                AND o.status = 'accepted')
+                   OR EXISTS (SELECT 1 FROM beneficiaries b
+                              WHERE b.plan_id = plans.id AND b.wallet_address = $2))
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&caller)
+        .fetch_optional(&state.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let co_owners: Vec<(String, Option<i64>)> = sqlx::query_as(
+            r#"
+            SELECT owner_address, last_ping
+            FROM plan_co_owners
+            WHERE plan_id = $1 AND status = 'accepted'
+            ORDER BY created_at
+            "#,
+        )
+        .bind(plan_id)
+        .fetch_all(&state.db_pool)
+        .await?;
+
+        let now = Utc::now();
+        let at = |secs: i64| Utc.timestamp_opt(secs, 0).single().unwrap_or(now);
+        // Joint plans run their grace period from the latest ping of any owner.
+        let latest_ping = co_owners
+            .iter()
+            .filter_map(|(_, ping)| *ping)
+            .fold(plan.last_ping, i64::max);
+        let inactivity_deadline_at =
+            at(latest_ping) + chrono::Duration::seconds(plan.grace_period_seconds);
+        let claim_expires_at =
+            inactivity_deadline_at + state.system_settings.get().await.claim_window();
+
+        let mut owners = vec![(plan.owner_address.clone(), plan.last_ping)];
+        owners.extend(
+            co_owners
+                .into_iter()
+                .map(|(address, ping)| (address, ping.unwrap_or(plan.last_ping))),
+        );
+
+        // Every owner's check-in and contacts must allow the claim.
+        let mut eligible = true;
+        let mut reasons: Vec<String> = Vec::new();
+        let mut primary_check_in = None;
+        let mut emergency_contacts = Vec::new();
+        for (index, (owner, owner_ping)) in owners.iter().enumerate() {
+            let (check_in, contacts) = owner_conditions(&state.db_pool, owner).await?;
+            let last_ping = at(*owner_ping);
+            let last_activity_at = match &check_in {
+                Some((_, checked_in_at, _)) => last_ping.max(*checked_in_at),
+                None => last_ping,
+            };
+
+            let input = EligibilityInput {
+                now,
+                is_active: plan.is_active,
+                marked_claimable: plan.status == "CLAIMABLE",
+                frozen: plan.frozen,
+                inactivity_deadline_at,
+                claim_expires_at,
+                next_check_in_due: check_in
+                    .as_ref()
+                    .filter(|(stage, _, _)| stage == "active")
+                    .map(|(_, _, due)| *due),
+                last_activity_at,
+                verified_contacts: contacts.len(),
+                last_contact_alert_at: contacts.iter().filter_map(|c| c.last_alerted_at).max(),
+            };
+            let (owner_eligible, owner_reasons) = evaluate(&input);
+            eligible &= owner_eligible;
+            for reason in owner_reasons {
+                if !reasons.contains(&reason) {
+                    reasons.push(reason);
+                }
+            }
+
+            if index == 0 {
+                primary_check_in = check_in;
+            }
+            emergency_contacts.extend(contacts.into_iter().map(|c| {
+                ContactSummary {
+                    name: c.name,
+                    relationship: c.relationship,
+                    email: c
+                        .email
+                        .filter(|_| c.email_verified_at.is_some())
+                        .map(|e| mask_email(&e)),
+                    phone: c
+                        .phone
+                        .filter(|_| c.phone_verified_at.is_some())
+                        .map(|p| mask_phone(&p)),
+                    last_alerted_at: c.last_alerted_at,
+                }
+            }));
+        }
+
+        Ok(Some(ClaimEligibility {
+            plan_id,
+            eligible,
+            reasons,
+            inactivity_deadline_at,
+            claim_expires_at,
+            check_in_stage: primary_check_in.as_ref().map(|(stage, _, _)| stage.clone()),
+            next_check_in_due: primary_check_in.map(|(_, _, due)| due),
+            emergency_contacts,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Some(eligibility)) => (StatusCode::OK, Json(eligibility)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Plan not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to evaluate claim eligibility");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> EligibilityInput {
+        let now = Utc.with_ymd_and_hms(2026, 6, 1, 0, 0, 0).unwrap();
+        EligibilityInput {
+            now,
+            is_active: true,
+            marked_claimable: false,
+            frozen: false,
+            inactivity_deadline_at: now - chrono::Duration::days(1),
+            claim_expires_at: now + chrono::Duration::days(364),
+            next_check_in_due: None,
+            last_activity_at: now - chrono::Duration::days(100),
+            verified_contacts: 0,
+            last_contact_alert_at: None,
+        }
+    }
+
+    #[test]
+    fn eligible_once_deadline_passes_without_contacts() {
+        assert_eq!(evaluate(&input()), (true, Vec::new()));
+    }
+
+    #[test]
+    fn verified_contacts_must_be_alerted_after_last_activity() {
+        let mut pending = input();
+        pending.verified_contacts = 2;
+        assert!(!evaluate(&pending).0);
+
+        pending.last_contact_alert_at = Some(pending.last_activity_at - chrono::Duration::days(1));
+        assert!(!evaluate(&pending).0);
+
+        pending.last_contact_alert_at = Some(pending.now - chrono::Duration::days(2));
+        assert!(evaluate(&pending).0);
+    }
+
+    #[test]
+    fn current_check_in_blocks_claim() {
+        let mut alive = input();
+        alive.next_check_in_due = Some(alive.now + chrono::Duration::days(5));
+
+        let (eligible, reasons) = evaluate(&alive);
+        assert!(!eligible);
+        assert_eq!(reasons.len(), 1);
+    }
+
+    #[test]
+    fn closed_claim_window_blocks_claim() {
+        let mut expired = input();
+        expired.claim_expires_at = expired.now;
+
+        assert_eq!(
+            evaluate(&expired),
+            (
+                false,
+                vec!["The claim window for this plan has closed".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn frozen_plan_blocks_claim() {
+        let mut frozen = input();
+        frozen.frozen = true;
+
+        assert_eq!(
+            evaluate(&frozen),
+            (
+                false,
+                vec!["This plan is frozen pending review".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn contact_details_are_masked() {
+        assert_eq!(mask_email("ada@example.com"), "a***@example.com");
+        assert_eq!(mask_phone("+15550100123"), "********0123");
+    }
+}
@@ -0,0 +1,600 @@
+//! Claim windows and escheatment of unclaimed plans.
+//!
+//! Beneficiaries have `claim_window_days` (a system setting, defaulting to
+//! `CLAIM_WINDOW_DAYS`) after a plan's inactivity deadline to request a
+//! claim; later requests are refused. [`ClaimExpiryService`] reminds them
+//! as the window closes and, once it has closed with no claim pending,
+//! escheats the plan: its balance is queued as a payout to the fallback
+//! beneficiary the owner named, or to `ESCHEAT_TREASURY_ADDRESS` without
+//! one. Plans with neither are left in place. The inheritance contract's
+//! `escheat` entrypoint applies the same rules to funds held on-chain.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::{compute_projected_accrued_yield, invalidate_plan_cache, AppState, PlanRow};
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::jobs::JobRegistry;
+use crate::notifications::create_localized_notification;
+use crate::plan_owners;
+use crate::telemetry;
+use crate::templates::TemplateKey;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60 * 60;
+const DEFAULT_BATCH_SIZE: i64 = 50;
+const DEFAULT_REMINDER_DAYS: &str = "30,7,1";
+const CLAIM_EXPIRY_LOCK_KEY: i64 = 835;
+const DAY_SECS: i64 = 86_400;
+
+const PLAN_COLUMNS: &str = "id, owner_address, token_address, amount, grace_period, \
+     grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, \
+     accrued_yield, created_at";
+
+#[derive(Debug, Clone)]
+pub struct ClaimExpiryConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+    /// Days before the window closes that beneficiaries are reminded,
+    /// largest first.
+    pub reminder_days: Vec<i32>,
+}
+
+impl ClaimExpiryConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("CLAIM_EXPIRY_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("CLAIM_EXPIRY_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+        let reminder_days = std::env::var("CLAIM_EXPIRY_REMINDER_DAYS")
+            .unwrap_or_else(|_| DEFAULT_REMINDER_DAYS.to_string());
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+            reminder_days: parse_reminder_days(&reminder_days),
+        }
+    }
+}
+
+/// Parses a comma-separated list of positive day counts, largest first.
+pub fn parse_reminder_days(value: &str) -> Vec<i32> {
+    let mut days: Vec<i32> = value
+        .split(',')
+        .filter_map(|d| d.trim().parse().ok())
+        .filter(|d| *d > 0)
+        .collect();
+    days.sort_unstable_by(|a, b| b.cmp(a));
+    days.dedup();
+    days
+}
+
+/// Reminders due at `now` for a window closing at `expires_at`, with the
+/// one to send: the closest to expiry. Earlier ones that were missed are
+/// only recorded, so beneficiaries get one message per sweep.
+pub fn due_reminders(reminder_days: &[i32], expires_at: i64, now: i64) -> (Vec<i32>, Option<i32>) {
+    if now >= expires_at {
+        return (Vec::new(), None);
+    }
+    let due: Vec<i32> = reminder_days
+        .iter()
+        .copied()
+        .filter(|days| now >= expires_at - i64::from(*days) * DAY_SECS)
+        .collect();
+    let send = due.iter().copied().min();
+    (due, send)
+}
+
+/// Work done by one sweep.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExpirySweep {
+    pub reminded: usize,
+    pub escheated: usize,
+}
+
+enum Action {
+    Reminded,
+    Escheated,
+    Nothing,
+}
+
+pub struct ClaimExpiryService {
+    state: Arc<AppState>,
+    config: ClaimExpiryConfig,
+}
+
+impl ClaimExpiryService {
+    pub fn new(state: Arc<AppState>, config: ClaimExpiryConfig) -> Self {
+        Self { state, config }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("claim_expiry", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|sweep| {
+                    if *sweep != ExpirySweep::default() {
+                        info!(
+                            reminded = sweep.reminded,
+                            escheated = sweep.escheated,
+                            "Claim expiry sweep finished"
+                        );
+                    }
+                })
+            }
+        });
+    }
+
+    /// Sends due reminders and escheats plans whose window has closed.
+    /// Each plan is handled in its own transaction.
+    pub async fn run_once(&self) -> Result<ExpirySweep, sqlx::Error> {
+        let mut lock_tx = self.state.db_pool.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(CLAIM_EXPIRY_LOCK_KEY)
+            .fetch_one(&mut *lock_tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Claim expiry lock is held by another worker; skipping sweep");
+            lock_tx.commit().await?;
+            return Ok(ExpirySweep::default());
+        }
+
+        let window = self
+            .state
+            .system_settings
+            .get()
+            .await
+            .claim_window()
+            .num_seconds();
+        let earliest_reminder =
+            i64::from(self.config.reminder_days.first().copied().unwrap_or(0)) * DAY_SECS;
+        let now = Utc::now().timestamp();
+
+        let due: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            WITH candidates AS (
+                SELECT p.id, p.fallback_address,
+                       GREATEST(p.last_ping, COALESCE(MAX(o.last_ping), p.last_ping))
+                           + p.grace_period_seconds + $1 AS expires_at
+                FROM plans p
+                LEFT JOIN plan_co_owners o ON o.plan_id = p.id AND o.status = 'accepted'
+                WHERE p.is_active = true
+                  AND p.frozen_at IS NULL
+                  AND p.inactivity_deadline_at
+                      <= NOW() - ($1 - $2)::double precision * INTERVAL '1 second'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM claim_requests c
+                      WHERE c.plan_id = p.id AND c.status IN ('pending', 'in_review'))
+                GROUP BY p.id
+            )
+            SELECT c.id FROM candidates c
+            WHERE (c.expires_at <= $3 AND (c.fallback_address IS NOT NULL OR $4))
+               OR (c.expires_at > $3 AND EXISTS (
+                   SELECT 1 FROM unnest($5::int[]) AS d(days)
+                   WHERE c.expires_at - d.days * 86400 <= $3
+                     AND NOT EXISTS (
+                         SELECT 1 FROM claim_expiry_reminders r
+                         WHERE r.plan_id = c.id AND r.days_before = d.days)))
+            ORDER BY c.expires_at
+            LIMIT $6
+            "#,
+        )
+        .bind(window)
+        .bind(earliest_reminder)
+        .bind(now)
+        .bind(self.state.config.escheat_treasury_address.is_some())
+        .bind(&self.config.reminder_days)
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *lock_tx)
+        .await?;
+
+        let mut sweep = ExpirySweep::default();
+        for plan_id in due {
+            match self.process(plan_id, window).await {
+                Ok(Action::Reminded) => sweep.reminded += 1,
+                Ok(Action::Escheated) => sweep.escheated += 1,
+                Ok(Action::Nothing) => {}
+                Err(e) => warn!(plan_id = %plan_id, error = %e, "Failed to process claim expiry"),
+            }
+        }
+
+        lock_tx.commit().await?;
+        Ok(sweep)
+    }
+
+    async fn process(&self, plan_id: Uuid, window: i64) -> Result<Action, sqlx::Error> {
+        let mut tx = self.state.db_pool.begin().await?;
+
+        let plan = sqlx::query_as::<_, PlanRow>(&format!(
+            "SELECT {PLAN_COLUMNS} FROM plans WHERE id = $1 AND is_active = true AND frozen_at IS NULL FOR UPDATE"
+        ))
+        .bind(plan_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(plan) = plan else {
+            return Ok(Action::Nothing);
+        };
+        let claim_pending: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM claim_requests WHERE plan_id = $1 AND status IN ('pending', 'in_review'))",
+        )
+        .bind(plan.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if claim_pending {
+            return Ok(Action::Nothing);
+        }
+
+        let now = Utc::now().timestamp();
+        let expires_at = plan_owners::inactivity_deadline(&mut *tx, &plan).await? + window;
+        let beneficiaries: Vec<String> =
+            sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+                .bind(plan.id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let action = if now >= expires_at {
+            self.escheat(&mut tx, &plan, &beneficiaries, expires_at)
+                .await?
+        } else {
+            self.remind(&mut tx, &plan, &beneficiaries, expires_at, now)
+                .await?
+        };
+
+        tx.commit().await?;
+        if matches!(action, Action::Escheated) {
+            invalidate_plan_cache(&self.state.plan_cache, &plan.owner_address, &beneficiaries)
+                .await;
+        }
+        Ok(action)
+    }
+
+    async fn remind(
+        &self,
+        conn: &mut PgConnection,
+        plan: &PlanRow,
+        beneficiaries: &[String],
+        expires_at: i64,
+        now: i64,
+    ) -> Result<Action, sqlx::Error> {
+        let (due, send) = due_reminders(&self.config.reminder_days, expires_at, now);
+        let Some(send) = send else {
+            return Ok(Action::Nothing);
+        };
+
+        let recorded: Vec<i32> = sqlx::query_scalar(
+            r#"
+            INSERT INTO claim_expiry_reminders (plan_id, days_before)
+            SELECT $1, days FROM unnest($2::int[]) AS d(days)
+            ON CONFLICT DO NOTHING
+            RETURNING days_before
+            "#,
+        )
+        .bind(plan.id)
+        .bind(&due)
+        .fetch_all(&mut *conn)
+        .await?;
+        if !recorded.contains(&send) {
+            return Ok(Action::Nothing);
+        }
+
+        let expires = timestamp(expires_at);
+        let days = ((expires_at - now + DAY_SECS - 1) / DAY_SECS).to_string();
+        notify(
+            conn,
+            beneficiaries,
+            "claim_expiring",
+            TemplateKey::ClaimExpiring,
+            &[("expires_at", &expires.to_rfc3339()), ("days", &days)],
+            serde_json::json!({ "plan_id": plan.id, "expires_at": expires }),
+        )
+        .await?;
+        info!(plan_id = %plan.id, days_before = send, "Sent claim window reminder");
+        Ok(Action::Reminded)
+    }
+
+    async fn escheat(
+        &self,
+        conn: &mut PgConnection,
+        plan: &PlanRow,
+        beneficiaries: &[String],
+        expires_at: i64,
+    ) -> Result<Action, sqlx::Error> {
+        let fallback: Option<String> =
+            sqlx::query_scalar("SELECT fallback_address FROM plans WHERE id = $1")
+                .bind(plan.id)
+                .fetch_one(&mut *conn)
+                .await?;
+        let (recipient, recipient_kind) =
+            match (fallback, self.state.config.escheat_treasury_address.clone()) {
+                (Some(fallback), _) => (fallback, "fallback"),
+                (None, Some(treasury)) => (treasury, "treasury"),
+                (None, None) => return Ok(Action::Nothing),
+            };
+
+        let Some((accrued_yield, amount)) =
+            compute_projected_accrued_yield(plan).and_then(|accrued| {
+                let accrued = accrued.normalize();
+                Some((accrued, plan.amount.checked_add(accrued)?))
+            })
+        else {
+            warn!(plan_id = %plan.id, "Escheat amount is out of range; leaving the plan");
+            return Ok(Action::Nothing);
+        };
+
+        let payout_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO payouts
+                (plan_id, beneficiary_address, amount, payout_type, status, correlation_id)
+            VALUES ($1, $2, $3, 'crypto', 'pending', $4)
+            RETURNING id
+            "#,
+        )
+        .bind(plan.id)
+        .bind(&recipient)
+        .bind(amount)
+        .bind(telemetry::correlation_id())
+        .fetch_one(&mut *conn)
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE plans
+            SET is_active = false, status = 'ESCHEATED', escheated_at = NOW(), accrued_yield = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(plan.id)
+        .bind(accrued_yield)
+        .execute(&mut *conn)
+        .await?;
+
+        let mut recipients = beneficiaries.to_vec();
+        recipients.push(recipient.clone());
+        notify(
+            conn,
+            &recipients,
+            "plan_escheated",
+            TemplateKey::PlanEscheated,
+            &[],
+            serde_json::json!({ "plan_id": plan.id, "payout_id": payout_id }),
+        )
+        .await?;
+        record_audit(
+            &mut *conn,
+            SYSTEM_ACTOR,
+            "plan.escheated",
+            &plan.id.to_string(),
+            serde_json::json!({
+                "recipient": recipient,
+                "recipient_kind": recipient_kind,
+                "amount": amount,
+                "token": plan.token_address,
+                "claim_window_closed_at": timestamp(expires_at),
+                "payout_id": payout_id,
+            }),
+        )
+        .await?;
+
+        info!(plan_id = %plan.id, recipient_kind, "Escheated unclaimed plan");
+        Ok(Action::Escheated)
+    }
+}
+
+/// Sends an in-app notification to each address once.
+async fn notify(
+    conn: &mut PgConnection,
+    addresses: &[String],
+    notification_type: &str,
+    key: TemplateKey,
+    vars: &[(&str, &str)],
+    metadata: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    let mut notified: Vec<&str> = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        if notified.contains(&address.as_str()) {
+            continue;
+        }
+        create_localized_notification(
+            &mut *conn,
+            address,
+            notification_type,
+            key,
+            vars,
+            metadata.clone(),
+        )
+        .await?;
+        notified.push(address);
+    }
+    Ok(())
+}
+
+fn timestamp(secs: i64) -> chrono::DateTime<Utc> {
+    Utc.timestamp_opt(secs, 0).single().unwrap_or_else(Utc::now)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FallbackBeneficiaryRequest {
+    /// Stellar account (`G...`), or null to send unclaimed funds to the
+    /// treasury.
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FallbackBeneficiary {
+    pub plan_id: Uuid,
+    pub fallback_address: Option<String>,
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: Get Fallback Beneficiary
+pub async fn get_fallback_beneficiary(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_scalar::<_, Option<String>>(
+        r#"
+        SELECT fallback_address FROM plans
+        WHERE id = $1
+          AND (owner_address = $2
+               OR id IN (SELECT plan_id FROM plan_co_owners
+                         WHERE owner_address = $2 AND status = 'accepted'))
+        "#,
+    )
+    .bind(plan_id)
+    .bind(&caller)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(fallback_address)) => (
+            StatusCode::OK,
+            Json(FallbackBeneficiary {
+                plan_id,
+                fallback_address,
+            }),
+        )
+            .into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load fallback beneficiary");
+            database_error()
+        }
+    }
+}
+
+// Handler: Set Fallback Beneficiary
+pub async fn set_fallback_beneficiary(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<FallbackBeneficiaryRequest>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let address = payload
+        .address
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
+    if address
+        .as_deref()
+        .is_some_and(|a| stellar_strkey::ed25519::PublicKey::from_string(a).is_err())
+    {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Fallback beneficiary must be a Stellar account address (G...)",
+        );
+    }
+
+    let result: Result<Option<FallbackBeneficiary>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let previous: Option<Option<String>> = sqlx::query_scalar(
+            r#"
+            SELECT fallback_address FROM plans
+            WHERE id = $1 AND is_active = true
+              AND (owner_address = $2
+                   OR id IN (SELECT plan_id FROM plan_co_owners
+                             WHERE owner_address = $2 AND status = 'accepted'))
+            FOR UPDATE
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&caller)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(previous) = previous else {
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE plans SET fallback_address = $2 WHERE id = $1")
+            .bind(plan_id)
+            .bind(&address)
+            .execute(&mut *tx)
+            .await?;
+        record_audit(
+            &mut *tx,
+            &caller,
+            "plan.fallback_beneficiary_updated",
+            &plan_id.to_string(),
+            serde_json::json!({ "from": previous, "to": address }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(FallbackBeneficiary {
+            plan_id,
+            fallback_address: address,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Some(fallback)) => (StatusCode::OK, Json(fallback)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "No active plan found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to set fallback beneficiary");
+            database_error()
+        }
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reminder_days_are_positive_and_largest_first() {
+        assert_eq!(parse_reminder_days("1, 30,7,7,-2,x"), vec![30, 7, 1]);
+        assert!(parse_reminder_days("").is_empty());
+    }
+
+    #[test]
+    fn only_the_closest_due_reminder_is_sent() {
+        let days = [30, 7, 1];
+        let expires_at = 100 * DAY_SECS;
+
+        assert_eq!(
+            due_reminders(&days, expires_at, expires_at - 31 * DAY_SECS),
+            (vec![], None)
+        );
+        assert_eq!(
+            due_reminders(&days, expires_at, expires_at - 30 * DAY_SECS),
+            (vec![30], Some(30))
+        );
+        assert_eq!(
+            due_reminders(&days, expires_at, expires_at - 3 * DAY_SECS),
+            (vec![30, 7], Some(7))
+        );
+        assert_eq!(due_reminders(&days, expires_at, expires_at), (vec![], None));
+    }
+}
@@ -0,0 +1,410 @@
+//! Fraud signals evaluated when a beneficiary requests a claim.
+//!
+//! Each signal that fires adds its weight to the claim's score. A claim
+//! scoring at least `claim_review_score_threshold` is recorded as
+//! `in_review` instead of `pending`, so the claim executor leaves it alone
+//! until an admin approves it (it then waits out the rest of its cooling-off
+//! period) or cancels it. The signals and score are kept with the claim for
+//! the reviewer.
+//!
+//! Device signals compare the claim request with the client addresses and
+//! user agents [`client_device_middleware`] has recorded for the wallet.
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Path, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::claim_requests::{prefixed_columns, ClaimRequest, CLAIM_COLUMNS};
+
+/// A client address or user agent first seen this recently is new.
+const NEW_DEVICE_SECS: i64 = 24 * 60 * 60;
+/// Account changes this recent count against a claim.
+const RECENT_CHANGE_SECS: i64 = 72 * 60 * 60;
+/// A claim this soon after the plan became claimable is unusually fast.
+const FAST_CLAIM_SECS: i64 = 60 * 60;
+/// Window for counting claims paying out to the same wallet.
+const VELOCITY_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+/// Other owners' plans claimed to one wallet within the window before it
+/// is suspicious.
+const MAX_OTHER_OWNERS_PER_WALLET: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FraudSignal {
+    /// The request came from an address the wallet has not used before.
+    NewIp,
+    /// The request came from a user agent the wallet has not used before.
+    NewDevice,
+    /// The wallet's email changed recently.
+    RecentEmailChange,
+    /// The wallet turned off re-authentication recently.
+    ReauthDisabled,
+    /// The claim was filed within the hour after the plan became claimable.
+    FastClaim,
+    /// Plans of several other owners were claimed to this wallet recently.
+    SharedPayoutWallet,
+}
+
+impl FraudSignal {
+    pub fn weight(self) -> u32 {
+        match self {
+            Self::NewIp => 15,
+            Self::NewDevice => 15,
+            Self::RecentEmailChange => 30,
+            Self::ReauthDisabled => 30,
+            Self::FastClaim => 20,
+            Self::SharedPayoutWallet => 40,
+        }
+    }
+}
+
+pub fn score(signals: &[FraudSignal]) -> u32 {
+    signals.iter().map(|s| s.weight()).sum()
+}
+
+/// Where a signed request came from, attached to the request by
+/// [`client_device_middleware`].
+#[derive(Debug, Clone)]
+pub struct ClientDevice {
+    pub ip: Option<String>,
+    /// SHA-256 of the `User-Agent` header, hex.
+    pub user_agent_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FraudAssessment {
+    pub score: u32,
+    pub signals: Vec<FraudSignal>,
+}
+
+/// Records the client of each signed wallet request and attaches it as a
+/// [`ClientDevice`]. Repeat sightings update `last_seen_at` at most hourly.
+pub async fn client_device_middleware(
+    State(state): State<Arc<AppState>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let wallet = req
+        .extensions()
+        .get::<UserContext>()
+        .and_then(|user| user.wallet_address());
+    if let Some(wallet) = wallet {
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ci| ci.0.ip());
+        let user_agent = req
+            .headers()
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let device = ClientDevice {
+            ip: state
+                .admin_access
+                .client_ip(peer, req.headers())
+                .map(|ip| ip.to_string()),
+            user_agent_hash: hex::encode(Sha256::digest(user_agent.as_bytes())),
+        };
+        if let Err(e) = record_sighting(&state.db_pool, &wallet, &device).await {
+            warn!(error = %e, "Failed to record wallet device");
+        }
+        req.extensions_mut().insert(device);
+    }
+    next.run(req).await
+}
+
+pub(crate) async fn record_sighting(
+    pool: &sqlx::PgPool,
+    wallet: &str,
+    device: &ClientDevice,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO wallet_devices (wallet_address, ip_address, user_agent_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (wallet_address, ip_address, user_agent_hash) DO UPDATE
+        SET last_seen_at = NOW()
+        WHERE wallet_devices.last_seen_at < NOW() - INTERVAL '1 hour'
+        "#,
+    )
+    .bind(wallet)
+    .bind(device.ip.as_deref().unwrap_or("unknown"))
+    .bind(&device.user_agent_hash)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+/// Evaluates the signals for `claimant` claiming a plan of `owner` that
+/// became claimable `secs_since_claimable` seconds ago.
+pub async fn assess_claim(
+    conn: &mut PgConnection,
+    claimant: &str,
+    owner: &str,
+    device: Option<&ClientDevice>,
+    secs_since_claimable: i64,
+) -> Result<FraudAssessment, sqlx::Error> {
+    let mut signals = Vec::new();
+
+    if let Some(device) = device {
+        let (known_ip, known_agent): (bool, bool) = sqlx::query_as(
+            r#"
+            SELECT
+                COALESCE(BOOL_OR(ip_address = $2), false),
+                COALESCE(BOOL_OR(user_agent_hash = $3), false)
+            FROM wallet_devices
+            WHERE wallet_address = $1
+              AND first_seen_at < NOW() - ($4 * INTERVAL '1 second')
+            "#,
+        )
+        .bind(claimant)
+        .bind(device.ip.as_deref())
+        .bind(&device.user_agent_hash)
+        .bind(NEW_DEVICE_SECS)
+        .fetch_one(&mut *conn)
+        .await?;
+        if device.ip.is_some() && !known_ip {
+            signals.push(FraudSignal::NewIp);
+        }
+        if !known_agent {
+            signals.push(FraudSignal::NewDevice);
+        }
+    }
+
+    let (email_changed, reauth_disabled): (bool, bool) = sqlx::query_as(
+        r#"
+        SELECT
+            EXISTS (
+                SELECT 1 FROM email_changes
+                WHERE user_address = $1 AND status = 'completed'
+                  AND resolved_at > NOW() - ($2 * INTERVAL '1 second')
+            ),
+            EXISTS (
+                SELECT 1 FROM wallet_reauth_settings
+                WHERE wallet_address = $1 AND enabled = false
+                  AND updated_at > NOW() - ($2 * INTERVAL '1 second')
+            )
+        "#,
+    )
+    .bind(claimant)
+    .bind(RECENT_CHANGE_SECS)
+    .fetch_one(&mut *conn)
+    .await?;
+    if email_changed {
+        signals.push(FraudSignal::RecentEmailChange);
+    }
+    if reauth_disabled {
+        signals.push(FraudSignal::ReauthDisabled);
+    }
+
+    if secs_since_claimable < FAST_CLAIM_SECS {
+        signals.push(FraudSignal::FastClaim);
+    }
+
+    let other_owners: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(DISTINCT p.owner_address)
+        FROM claim_requests c
+        JOIN plans p ON p.id = c.plan_id
+        JOIN beneficiaries b ON b.plan_id = p.id AND b.wallet_address = $1
+        WHERE c.created_at > NOW() - ($3 * INTERVAL '1 second')
+          AND c.status <> 'cancelled'
+          AND p.owner_address <> $2
+        "#,
+    )
+    .bind(claimant)
+    .bind(owner)
+    .bind(VELOCITY_WINDOW_SECS)
+    .fetch_one(&mut *conn)
+    .await?;
+    if other_owners > MAX_OTHER_OWNERS_PER_WALLET {
+        signals.push(FraudSignal::SharedPayoutWallet);
+    }
+
+    Ok(FraudAssessment {
+        score: score(&signals),
+        signals,
+    })
+}
+
+pub async fn record_assessment(
+    conn: &mut PgConnection,
+    claim_id: Uuid,
+    assessment: &FraudAssessment,
+    device: Option<&ClientDevice>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO claim_fraud_assessments (claim_id, score, signals, ip_address)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(claim_id)
+    .bind(assessment.score as i32)
+    .bind(serde_json::json!(assessment.signals))
+    .bind(device.and_then(|d| d.ip.as_deref()))
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Marks a claim's assessment as reviewed by `admin`.
+pub async fn mark_reviewed(
+    conn: &mut PgConnection,
+    claim_id: Uuid,
+    admin: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE claim_fraud_assessments SET reviewed_by = $2, reviewed_at = NOW()
+        WHERE claim_id = $1 AND reviewed_at IS NULL
+        "#,
+    )
+    .bind(claim_id)
+    .bind(admin)
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ReviewItem {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub claim: ClaimRequest,
+    pub score: i32,
+    pub signals: serde_json::Value,
+    pub ip_address: Option<String>,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: Admin Claim Review Queue
+pub async fn list_review_queue(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match sqlx::query_as::<_, ReviewItem>(&format!(
+        r#"
+        SELECT {}, a.score, a.signals, a.ip_address
+        FROM claim_requests c
+        JOIN claim_fraud_assessments a ON a.claim_id = c.id
+        WHERE c.status = 'in_review'
+        ORDER BY c.created_at
+        "#,
+        prefixed_columns("c")
+    ))
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(items) => (StatusCode::OK, Json(items)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list claims in review");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Approve Claim in review
+pub async fn approve_claim(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(claim_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let result: Result<Option<ClaimRequest>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        // The claim still waits out whatever is left of its cooling-off period.
+        let Some(claim) = sqlx::query_as::<_, ClaimRequest>(&format!(
+            r#"
+            UPDATE claim_requests
+            SET status = 'pending', execute_after = GREATEST(execute_after, NOW())
+            WHERE id = $1 AND status = 'in_review'
+            RETURNING {CLAIM_COLUMNS}
+            "#
+        ))
+        .bind(claim_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        mark_reviewed(&mut tx, claim.id, &admin.user_id).await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "claim.review_approved",
+            &claim.id.to_string(),
+            serde_json::json!({ "plan_id": claim.plan_id, "execute_after": claim.execute_after }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(claim))
+    }
+    .await;
+
+    match result {
+        Ok(Some(claim)) => (StatusCode::OK, Json(claim)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Claim in review not found"),
+        Err(e) => {
+            error!(claim_id = %claim_id, error = %e, "Failed to approve claim");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_signal_alone_stays_below_the_default_threshold() {
+        let all = [
+            FraudSignal::NewIp,
+            FraudSignal::NewDevice,
+            FraudSignal::RecentEmailChange,
+            FraudSignal::ReauthDisabled,
+            FraudSignal::FastClaim,
+            FraudSignal::SharedPayoutWallet,
+        ];
+        assert!(all.iter().all(|s| s.weight() < 60));
+
+        // A first-time wallet claiming the moment the plan opens.
+        assert_eq!(
+            score(&[
+                FraudSignal::NewIp,
+                FraudSignal::NewDevice,
+                FraudSignal::FastClaim
+            ]),
+            50
+        );
+        assert_eq!(
+            score(&[FraudSignal::NewDevice, FraudSignal::RecentEmailChange]),
+            45
+        );
+        assert_eq!(
+            score(&[
+                FraudSignal::RecentEmailChange,
+                FraudSignal::SharedPayoutWallet
+            ]),
+            70
+        );
+    }
+}
@@ -0,0 +1,632 @@
+//! Claim portal for beneficiaries who do not have an account yet.
+//!
+//! A plan owner sends a beneficiary a claim link by email. The link carries
+//! a one-time token, and `GET /api/claims/start/{token}` shows just enough
+//! of the plan to continue: which wallet the plan pays and what is left to
+//! do. The beneficiary proves control of that wallet by signing
+//! [`link_message`], completes KYC through the usual provider flow, and
+//! finishing the invitation turns the wallet into a full account with the
+//! invited email on file. Links expire after [`CLAIM_INVITATION_TTL_DAYS`].
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::{verify_wallet_signature, UserContext};
+use crate::mailer::is_plausible_email;
+use crate::notifications::create_notification;
+
+pub const CLAIM_INVITATION_TTL_DAYS: i64 = 30;
+
+const INVITATION_COLUMNS: &str = "id, plan_id, beneficiary_id, email, invited_by, status, \
+     wallet_linked_at, expires_at, created_at, resolved_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClaimInvitation {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub beneficiary_id: Uuid,
+    pub email: String,
+    pub invited_by: String,
+    /// `pending`, `completed` or `revoked`.
+    pub status: String,
+    pub wallet_linked_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// A pending invitation with the plan details the portal may show.
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct StartRow {
+    id: Uuid,
+    plan_id: Uuid,
+    email: String,
+    wallet_linked_at: Option<DateTime<Utc>>,
+    expires_at: DateTime<Utc>,
+    beneficiary_wallet: String,
+    allocation_bps: i32,
+    token_address: String,
+    plan_is_active: bool,
+    kyc_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NextStep {
+    /// Sign [`link_message`] with the beneficiary wallet.
+    LinkWallet,
+    /// Finish KYC with the provider; the webhook records the result.
+    CompleteKyc,
+    /// KYC was rejected; the beneficiary has to contact support.
+    ContactSupport,
+    /// Everything is in place; `POST .../complete` creates the account.
+    Finish,
+}
+
+impl NextStep {
+    fn for_progress(wallet_linked: bool, kyc_status: Option<&str>) -> Self {
+        match (wallet_linked, kyc_status) {
+            (false, _) => Self::LinkWallet,
+            (true, Some("approved")) => Self::Finish,
+            (true, Some("rejected")) => Self::ContactSupport,
+            (true, _) => Self::CompleteKyc,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClaimStart {
+    pub invitation_id: Uuid,
+    pub plan_id: Uuid,
+    pub token_address: String,
+    pub plan_is_active: bool,
+    pub allocation_bps: i32,
+    /// The wallet the plan pays out to, shortened. The beneficiary has to
+    /// link this wallet; a different one needs the owner to update the plan.
+    pub beneficiary_wallet: String,
+    /// Message to sign with the beneficiary wallet to link it.
+    pub link_message: String,
+    pub wallet_linked: bool,
+    pub kyc_status: Option<String>,
+    pub next_step: NextStep,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<StartRow> for ClaimStart {
+    fn from(row: StartRow) -> Self {
+        let wallet_linked = row.wallet_linked_at.is_some();
+        Self {
+            invitation_id: row.id,
+            plan_id: row.plan_id,
+            token_address: row.token_address,
+            plan_is_active: row.plan_is_active,
+            allocation_bps: row.allocation_bps,
+            beneficiary_wallet: mask_address(&row.beneficiary_wallet),
+            link_message: link_message(row.id),
+            wallet_linked,
+            next_step: NextStep::for_progress(wallet_linked, row.kyc_status.as_deref()),
+            kyc_status: row.kyc_status,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InviteBeneficiaryRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkWalletRequest {
+    pub wallet_address: String,
+    /// Hex ed25519 signature over the invitation's `link_message`.
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompletedClaimStart {
+    pub invitation_id: Uuid,
+    pub plan_id: Uuid,
+    pub wallet_address: String,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn invalid_link() -> Response {
+    refused(
+        StatusCode::NOT_FOUND,
+        "Invalid, expired or already used claim link",
+    )
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn claim_link(base_url: &str, token: &str) -> String {
+    format!("{}/{token}", base_url.trim_end_matches('/'))
+}
+
+/// The message a beneficiary signs to link their wallet to `invitation_id`.
+pub fn link_message(invitation_id: Uuid) -> String {
+    format!("InheritX claim {invitation_id}")
+}
+
+/// `GABC…WXYZ`, enough for the beneficiary to recognise their wallet.
+fn mask_address(address: &str) -> String {
+    let chars: Vec<char> = address.chars().collect();
+    if chars.len() <= 8 {
+        return address.to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}…{tail}")
+}
+
+async fn load_start(
+    conn: &mut PgConnection,
+    token_hash: &str,
+) -> Result<Option<StartRow>, sqlx::Error> {
+    sqlx::query_as::<_, StartRow>(
+        r#"
+        SELECT i.id, i.plan_id, i.email, i.wallet_linked_at, i.expires_at,
+               b.wallet_address AS beneficiary_wallet, b.allocation_bps,
+               p.token_address, p.is_active AS plan_is_active,
+               u.kyc_status::text AS kyc_status
+        FROM claim_invitations i
+        JOIN beneficiaries b ON b.id = i.beneficiary_id
+        JOIN plans p ON p.id = i.plan_id
+        LEFT JOIN users u ON u.wallet_address = b.wallet_address
+        WHERE i.token_hash = $1
+          AND i.status = 'pending'
+          AND i.expires_at > NOW()
+        FOR UPDATE OF i
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(conn)
+    .await
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+// Handler: Invite Beneficiary to Claim
+pub async fn invite_beneficiary(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path((plan_id, beneficiary_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<InviteBeneficiaryRequest>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let email = payload.email.trim().to_string();
+    if !is_plausible_email(&email) {
+        return refused(StatusCode::BAD_REQUEST, "Invalid email address");
+    }
+
+    let token = generate_token();
+
+    let result: Result<Outcome<ClaimInvitation>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+
+        let is_beneficiary: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM beneficiaries b
+                JOIN plans p ON p.id = b.plan_id
+                WHERE b.id = $2 AND p.id = $1
+                  AND (p.owner_address = $3
+                       OR EXISTS (SELECT 1 FROM plan_co_owners o
+                                  WHERE o.plan_id = p.id AND o.owner_address = $3
+                                    AND o.status = 'accepted'))
+            )
+            "#,
+        )
+        .bind(plan_id)
+        .bind(beneficiary_id)
+        .bind(&owner)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !is_beneficiary {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Beneficiary not found on this plan",
+            ));
+        }
+
+        // A new link replaces the previous one, so a mistyped email can be
+        // corrected by inviting again.
+        sqlx::query(
+            r#"
+            UPDATE claim_invitations SET status = 'revoked', resolved_at = NOW()
+            WHERE beneficiary_id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(beneficiary_id)
+        .execute(&mut *tx)
+        .await?;
+        let invitation = sqlx::query_as::<_, ClaimInvitation>(&format!(
+            r#"
+            INSERT INTO claim_invitations
+                (plan_id, beneficiary_id, email, token_hash, invited_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {INVITATION_COLUMNS}
+            "#
+        ))
+        .bind(plan_id)
+        .bind(beneficiary_id)
+        .bind(&email)
+        .bind(hash_token(&token))
+        .bind(&owner)
+        .bind(Utc::now() + Duration::days(CLAIM_INVITATION_TTL_DAYS))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        record_audit(
+            &mut *tx,
+            &owner,
+            "claim_invitation.sent",
+            &invitation.id.to_string(),
+            serde_json::json!({ "plan_id": plan_id, "beneficiary_id": beneficiary_id }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(invitation))
+    }
+    .await;
+
+    let invitation = match result {
+        Ok(Outcome::Done(invitation)) => invitation,
+        Ok(Outcome::Refused(status, message)) => return refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to invite beneficiary");
+            return database_error();
+        }
+    };
+
+    let body = format!(
+        "You are a beneficiary of an inheritance plan on InheritX. To start your claim, open \
+         this link within {CLAIM_INVITATION_TTL_DAYS} days:\n\n{}\n\n\
+         You will be asked to connect the wallet named in the plan and to verify your \
+         identity. If you were not expecting this, you can ignore this email.",
+        claim_link(&state.config.claim_portal_url, &token)
+    );
+    if let Err(e) = state
+        .mailer
+        .send(&invitation.email, "Start your InheritX claim", &body)
+        .await
+    {
+        warn!(invitation_id = %invitation.id, error = %e, "Failed to send claim invitation");
+    }
+
+    info!(plan_id = %plan_id, invitation_id = %invitation.id, "Beneficiary invited to claim");
+    (StatusCode::CREATED, Json(invitation)).into_response()
+}
+
+// Handler: Revoke Beneficiary Claim Invitation
+pub async fn revoke_beneficiary_invitation(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path((plan_id, beneficiary_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<bool, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let revoked: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE claim_invitations i SET status = 'revoked', resolved_at = NOW()
+            FROM plans p
+            WHERE p.id = i.plan_id
+              AND i.plan_id = $1 AND i.beneficiary_id = $2 AND i.status = 'pending'
+              AND (p.owner_address = $3
+                   OR EXISTS (SELECT 1 FROM plan_co_owners o
+                              WHERE o.plan_id = p.id AND o.owner_address = $3
+                                AND o.status = 'accepted'))
+            RETURNING i.id
+            "#,
+        )
+        .bind(plan_id)
+        .bind(beneficiary_id)
+        .bind(&owner)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(id) = revoked {
+            record_audit(
+                &mut *tx,
+                &owner,
+                "claim_invitation.revoked",
+                &id.to_string(),
+                serde_json::json!({ "plan_id": plan_id, "beneficiary_id": beneficiary_id }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(revoked.is_some())
+    }
+    .await;
+
+    match result {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => refused(StatusCode::NOT_FOUND, "No pending claim invitation"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to revoke claim invitation");
+            database_error()
+        }
+    }
+}
+
+// Handler: Start Claim
+pub async fn start_claim(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    let token_hash = hash_token(token.trim());
+
+    let result = async {
+        let mut conn = state.db_pool.acquire().await?;
+        load_start(&mut conn, &token_hash).await
+    }
+    .await;
+
+    match result {
+        Ok(Some(row)) => (StatusCode::OK, Json(ClaimStart::from(row))).into_response(),
+        Ok(None) => invalid_link(),
+        Err(e) => {
+            error!(error = %e, "Failed to load claim invitation");
+            database_error()
+        }
+    }
+}
+
+// Handler: Link Claim Wallet
+pub async fn link_claim_wallet(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    Json(payload): Json<LinkWalletRequest>,
+) -> impl IntoResponse {
+    let token_hash = hash_token(token.trim());
+    let wallet_address = payload.wallet_address.trim().to_string();
+
+    let result: Result<Option<Outcome<StartRow>>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(mut row) = load_start(&mut tx, &token_hash).await? else {
+            return Ok(None);
+        };
+        if row.beneficiary_wallet != wallet_address {
+            return Ok(Some(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "This is not the wallet named in the plan; ask the plan owner to update it",
+            )));
+        }
+        if !verify_wallet_signature(
+            &wallet_address,
+            link_message(row.id).as_bytes(),
+            &payload.signature,
+        ) {
+            return Ok(Some(Outcome::Refused(
+                StatusCode::UNAUTHORIZED,
+                "Invalid wallet signature",
+            )));
+        }
+
+        row.kyc_status = sqlx::query_scalar(
+            r#"
+            INSERT INTO users (wallet_address) VALUES ($1)
+            ON CONFLICT (wallet_address) DO UPDATE SET wallet_address = EXCLUDED.wallet_address
+            RETURNING kyc_status::text
+            "#,
+        )
+        .bind(&wallet_address)
+        .fetch_one(&mut *tx)
+        .await?;
+        row.wallet_linked_at = sqlx::query_scalar(
+            "UPDATE claim_invitations SET wallet_linked_at = COALESCE(wallet_linked_at, NOW()) WHERE id = $1 RETURNING wallet_linked_at",
+        )
+        .bind(row.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        record_audit(
+            &mut *tx,
+            &wallet_address,
+            "claim_invitation.wallet_linked",
+            &row.id.to_string(),
+            serde_json::json!({ "plan_id": row.plan_id }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(Outcome::Done(row)))
+    }
+    .await;
+
+    match result {
+        Ok(Some(Outcome::Done(row))) => {
+            (StatusCode::OK, Json(ClaimStart::from(row))).into_response()
+        }
+        Ok(Some(Outcome::Refused(status, message))) => refused(status, message),
+        Ok(None) => invalid_link(),
+        Err(e) => {
+            error!(error = %e, "Failed to link claim wallet");
+            database_error()
+        }
+    }
+}
+
+// Handler: Complete Claim Start
+pub async fn complete_claim_start(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    let token_hash = hash_token(token.trim());
+
+    let result: Result<Option<Outcome<CompletedClaimStart>>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(row) = load_start(&mut tx, &token_hash).await? else {
+            return Ok(None);
+        };
+        match NextStep::for_progress(row.wallet_linked_at.is_some(), row.kyc_status.as_deref()) {
+            NextStep::Finish => {}
+            NextStep::LinkWallet => {
+                return Ok(Some(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "Link the beneficiary wallet first",
+                )))
+            }
+            NextStep::CompleteKyc | NextStep::ContactSupport => {
+                return Ok(Some(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "KYC must be approved before the claim can continue",
+                )))
+            }
+        }
+        let wallet_address = row.beneficiary_wallet;
+
+        // An email the wallet already set is left alone; changing it goes
+        // through the confirmed email change flow.
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_address, email)
+            VALUES ($1, $2)
+            ON CONFLICT (user_address)
+            DO UPDATE SET email = COALESCE(notification_preferences.email, EXCLUDED.email),
+                          updated_at = NOW()
+            "#,
+        )
+        .bind(&wallet_address)
+        .bind(&row.email)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO user_profiles (user_address) VALUES ($1) ON CONFLICT (user_address) DO NOTHING",
+        )
+        .bind(&wallet_address)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE claim_invitations SET status = 'completed', resolved_at = NOW() WHERE id = $1",
+        )
+        .bind(row.id)
+        .execute(&mut *tx)
+        .await?;
+
+        record_audit(
+            &mut *tx,
+            &wallet_address,
+            "claim_invitation.completed",
+            &row.id.to_string(),
+            serde_json::json!({ "plan_id": row.plan_id }),
+        )
+        .await?;
+        create_notification(
+            &mut *tx,
+            &wallet_address,
+            "claim_account_ready",
+            "Your InheritX account is ready",
+            "Sign in with your wallet to follow the plan and request your claim when it \
+             becomes claimable.",
+            serde_json::json!({ "plan_id": row.plan_id, "invitation_id": row.id }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(Outcome::Done(CompletedClaimStart {
+            invitation_id: row.id,
+            plan_id: row.plan_id,
+            wallet_address,
+        })))
+    }
+    .await;
+
+    match result {
+        Ok(Some(Outcome::Done(completed))) => {
+            info!(
+                invitation_id = %completed.invitation_id,
+                plan_id = %completed.plan_id,
+                "Beneficiary claim account created"
+            );
+            (StatusCode::OK, Json(completed)).into_response()
+        }
+        Ok(Some(Outcome::Refused(status, message))) => refused(status, message),
+        Ok(None) => invalid_link(),
+        Err(e) => {
+            error!(error = %e, "Failed to complete claim start");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_step_follows_wallet_then_kyc() {
+        assert_eq!(NextStep::for_progress(false, None), NextStep::LinkWallet);
+        assert_eq!(
+            NextStep::for_progress(false, Some("approved")),
+            NextStep::LinkWallet
+        );
+        assert_eq!(
+            NextStep::for_progress(true, Some("pending")),
+            NextStep::CompleteKyc
+        );
+        assert_eq!(
+            NextStep::for_progress(true, Some("submitted")),
+            NextStep::CompleteKyc
+        );
+        assert_eq!(
+            NextStep::for_progress(true, Some("rejected")),
+            NextStep::ContactSupport
+        );
+        assert_eq!(
+            NextStep::for_progress(true, Some("approved")),
+            NextStep::Finish
+        );
+    }
+
+    #[test]
+    fn links_and_masked_addresses() {
+        assert_eq!(
+            claim_link("https://app.example.com/claim/", "abc"),
+            "https://app.example.com/claim/abc"
+        );
+        assert_eq!(mask_address("GABCDEFGHIJKLMNOPQRSTUVWXYZ"), "GABC…WXYZ");
+        assert_eq!(mask_address("GSHORT"), "GSHORT");
+        assert_eq!(hash_token("abc"), hash_token("abc"));
+        assert_ne!(generate_token(), generate_token());
+    }
+}
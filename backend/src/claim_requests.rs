@@ -0,0 +1,889 @@
+//! Two-phase claims: a beneficiary requests a payout, and it only executes
+//! once the cooling-off period has passed without the owner or an admin
+//! cancelling it. This gives a live owner whose beneficiary account was
+//! compromised a window to stop the payout. The period is the
+//! `claim_cooling_off_hours` system setting, which defaults to
+//! `CLAIM_COOLING_OFF_HOURS`.
+//!
+//! Requests are executed by [`ClaimExecutorService`] through the same
+//! payout path as `POST /api/plans/payout`, which without a cooling-off
+//! period files a claim through [`file_claim`] and executes it at once.
+//! Requests that [`crate::claim_fraud`] scores as suspicious wait
+//! `in_review` for an admin before their cooling-off period counts down to
+//! execution, whichever endpoint filed them.
+//!
+//! An executor with a `file_claim` delegation (see
+//! [`crate::claim_delegations`]) can request a claim for the beneficiary by
+//! passing `on_behalf_of`; the claim records both in `requested_by` and
+//! `filed_by`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::{
+    invalidate_plan_cache, pay_out_plan, payout_total, split_payout, AppState, PlanRow,
+};
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::claim_delegations::{self, Claimant, DelegationScope};
+use crate::claim_fraud::{self, ClientDevice};
+use crate::freezes;
+use crate::jobs::JobRegistry;
+use crate::kyc_tiers::{self, LimitExceeded};
+use crate::notifications::create_localized_notification;
+use crate::plan_owners;
+use crate::system_settings::SystemSettings;
+use crate::telemetry;
+use crate::templates::TemplateKey;
+use crate::trustlines::{self, TrustlineIssue};
+use crate::wallet_reauth::{self, ReauthAction, WalletConfirmation};
+use crate::witnesses;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_BATCH_SIZE: i64 = 20;
+const CLAIM_EXECUTOR_LOCK_KEY: i64 = 828;
+
+pub(crate) const CLAIM_COLUMNS: &str =
+    "id, plan_id, requested_by, filed_by, status, execute_after, cancelled_by, \
+     cancel_reason, failure_reason, correlation_id, created_at, resolved_at";
+
+const PLAN_COLUMNS: &str = "id, owner_address, token_address, amount, grace_period, \
+     grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, \
+     accrued_yield, created_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClaimRequest {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    /// Beneficiary the claim pays.
+    pub requested_by: String,
+    /// Executor who filed the claim for the beneficiary, if delegated.
+    pub filed_by: Option<String>,
+    /// `pending`, `in_review`, `executed`, `cancelled` or `failed`.
+    pub status: String,
+    pub execute_after: DateTime<Utc>,
+    pub cancelled_by: Option<String>,
+    pub cancel_reason: Option<String>,
+    pub failure_reason: Option<String>,
+    /// `X-Request-Id` of the request that filed the claim; execution runs
+    /// under the same id.
+    pub correlation_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RequestClaimBody {
+    /// Required when the caller has wallet re-auth enabled.
+    #[serde(default)]
+    pub confirmation: Option<WalletConfirmation>,
+    /// Beneficiary to claim for, when acting as their executor.
+    #[serde(default)]
+    pub on_behalf_of: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CancelClaimBody {
+    pub reason: Option<String>,
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+async fn beneficiary_addresses(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+        .bind(plan_id)
+        .fetch_all(conn)
+        .await
+}
+
+/// Sends an in-app notification to each address, once per address, in
+/// each recipient's language.
+async fn notify_all(
+    conn: &mut PgConnection,
+    addresses: &[String],
+    notification_type: &str,
+    key: TemplateKey,
+    vars: &[(&str, &str)],
+    claim: &ClaimRequest,
+) -> Result<(), sqlx::Error> {
+    let metadata = serde_json::json!({
+        "plan_id": claim.plan_id,
+        "claim_id": claim.id,
+        "execute_after": claim.execute_after,
+    });
+    let mut notified: Vec<&str> = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        if notified.contains(&address.as_str()) {
+            continue;
+        }
+        create_localized_notification(
+            &mut *conn,
+            address,
+            notification_type,
+            key,
+            vars,
+            metadata.clone(),
+        )
+        .await?;
+        notified.push(address);
+    }
+    Ok(())
+}
+
+/// Cancels the pending or in-review claim on `plan_id` (or the claim
+/// `claim_id`, for admins) and tells the owner and beneficiaries.
+async fn cancel_pending(
+    state: &AppState,
+    plan_id: Option<Uuid>,
+    claim_id: Option<Uuid>,
+    owner: Option<&str>,
+    actor: &str,
+    reason: Option<String>,
+) -> Result<Option<ClaimRequest>, sqlx::Error> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let claim = sqlx::query_as::<_, ClaimRequest>(&format!(
+        r#"
+        UPDATE claim_requests c
+        SET status = 'cancelled', cancelled_by = $4, cancel_reason = $5, resolved_at = NOW()
+        FROM plans p
+        WHERE p.id = c.plan_id
+          AND c.status IN ('pending', 'in_review')
+          AND ($1::uuid IS NULL OR c.plan_id = $1)
+          AND ($2::uuid IS NULL OR c.id = $2)
+          AND ($3::text IS NULL OR p.owner_address = $3
+               OR p.id IN (SELECT plan_id FROM plan_co_owners
+                           WHERE owner_address = $3 AND status = 'accepted'))
+        RETURNING {}
+        "#,
+        prefixed_columns("c")
+    ))
+    .bind(plan_id)
+    .bind(claim_id)
+    .bind(owner)
+    .bind(actor)
+    .bind(&reason)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(claim) = claim else {
+        tx.commit().await?;
+        return Ok(None);
+    };
+    if owner.is_none() {
+        claim_fraud::mark_reviewed(&mut tx, claim.id, actor).await?;
+    }
+
+    let owner_address: String = sqlx::query_scalar("SELECT owner_address FROM plans WHERE id = $1")
+        .bind(claim.plan_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let mut recipients = beneficiary_addresses(&mut tx, claim.plan_id).await?;
+    recipients.push(owner_address);
+    notify_all(
+        &mut tx,
+        &recipients,
+        "claim_cancelled",
+        TemplateKey::ClaimCancelled,
+        &[],
+        &claim,
+    )
+    .await?;
+    record_audit(
+        &mut *tx,
+        actor,
+        "claim.cancelled",
+        &claim.id.to_string(),
+        serde_json::json!({ "plan_id": claim.plan_id, "reason": reason }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(claim))
+}
+
+pub(crate) fn prefixed_columns(alias: &str) -> String {
+    CLAIM_COLUMNS
+        .split(", ")
+        .map(|column| format!("{alias}.{}", column.trim()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn clean_reason(body: Option<Json<CancelClaimBody>>) -> Option<String> {
+    body.and_then(|Json(b)| b.reason)
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty())
+}
+
+/// Why a beneficiary cannot claim a plan right now.
+#[derive(Debug)]
+pub(crate) enum Ineligible {
+    GracePeriod,
+    WindowClosed,
+    Trustline(TrustlineIssue),
+    OverLimit(LimitExceeded),
+}
+
+impl IntoResponse for Ineligible {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::GracePeriod => refused(StatusCode::BAD_REQUEST, "Grace period has not elapsed"),
+            Self::WindowClosed => refused(
+                StatusCode::GONE,
+                "The claim window for this plan has closed",
+            ),
+            Self::Trustline(issue) => refused(StatusCode::UNPROCESSABLE_ENTITY, &issue.to_string()),
+            Self::OverLimit(exceeded) => exceeded.into_response(),
+        }
+    }
+}
+
+/// Checks that `beneficiary` may claim the locked `plan` now: every owner's
+/// grace period has passed or its witnesses reached quorum, the claim
+/// window is still open, the beneficiary can receive the token and their
+/// share is within their KYC tier's claim payout limit. Both claim
+/// endpoints go through this. Returns when the plan became claimable.
+pub(crate) async fn check_eligibility(
+    state: &AppState,
+    conn: &mut PgConnection,
+    plan: &PlanRow,
+    beneficiary: &str,
+) -> Result<Result<i64, Ineligible>, sqlx::Error> {
+    let now = Utc::now().timestamp();
+    let settings = state.system_settings.get().await;
+    let deadline = plan_owners::inactivity_deadline(&mut *conn, plan).await?;
+    let claimable_at = witnesses::claimable_at(&mut *conn, plan, deadline).await?;
+    if now < claimable_at {
+        return Ok(Err(Ineligible::GracePeriod));
+    }
+    if now >= deadline + settings.claim_window().num_seconds() {
+        return Ok(Err(Ineligible::WindowClosed));
+    }
+
+    if let Some(issue) = trustlines::beneficiary_issue(
+        state,
+        &mut *conn,
+        plan.id,
+        &plan.token_address,
+        plan.amount,
+        beneficiary,
+    )
+    .await?
+    {
+        return Ok(Err(Ineligible::Trustline(issue)));
+    }
+
+    if let Err(exceeded) = check_payout_limits(&mut *conn, &settings, plan).await? {
+        return Ok(Err(Ineligible::OverLimit(exceeded)));
+    }
+    Ok(Ok(claimable_at))
+}
+
+/// Refuses a payout of `plan` that would pay any beneficiary, principal
+/// plus yield, more than their KYC tier's claim payout limit. Shares are
+/// split as [`pay_out_plan`] splits them.
+async fn check_payout_limits(
+    conn: &mut PgConnection,
+    settings: &SystemSettings,
+    plan: &PlanRow,
+) -> Result<Result<(), LimitExceeded>, sqlx::Error> {
+    // Left for pay_out_plan to refuse.
+    let Some(total) = payout_total(plan) else {
+        return Ok(Ok(()));
+    };
+    let beneficiaries: Vec<(String, i32)> = sqlx::query_as(
+        "SELECT wallet_address, allocation_bps FROM beneficiaries WHERE plan_id = $1 ORDER BY id",
+    )
+    .bind(plan.id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let allocations: Vec<i32> = beneficiaries.iter().map(|(_, bps)| *bps).collect();
+    for ((beneficiary, _), share) in beneficiaries.iter().zip(split_payout(total, &allocations)) {
+        if let Err(exceeded) =
+            kyc_tiers::check_claim_payout(&mut *conn, settings, beneficiary, share).await?
+        {
+            return Ok(Err(exceeded));
+        }
+    }
+    Ok(Ok(()))
+}
+
+/// Records a claim for `claimant` on the locked `plan`, which became
+/// claimable at `deadline`. The claim is scored by [`claim_fraud`] and held
+/// `in_review` above the threshold; otherwise it is `pending` until the
+/// cooling-off period ends. Notifies the parties and audits the request
+/// without committing. Returns `None` if the plan already has a claim
+/// pending.
+pub(crate) async fn file_claim(
+    state: &AppState,
+    tx: &mut PgConnection,
+    plan: &PlanRow,
+    claimant: &Claimant,
+    device: Option<&ClientDevice>,
+    deadline: i64,
+) -> Result<Option<ClaimRequest>, sqlx::Error> {
+    let pending: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM claim_requests WHERE plan_id = $1 AND status IN ('pending', 'in_review'))",
+    )
+    .bind(plan.id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if pending {
+        return Ok(None);
+    }
+
+    let now = Utc::now();
+    let assessment = claim_fraud::assess_claim(
+        &mut *tx,
+        &claimant.beneficiary,
+        &plan.owner_address,
+        device,
+        now.timestamp() - deadline,
+    )
+    .await?;
+    let status = if assessment.score >= state.config.claim_review_score_threshold {
+        "in_review"
+    } else {
+        "pending"
+    };
+
+    let execute_after = now + state.system_settings.get().await.claim_cooling_off();
+    let claim = sqlx::query_as::<_, ClaimRequest>(&format!(
+        r#"
+        INSERT INTO claim_requests
+            (plan_id, requested_by, filed_by, execute_after, status, correlation_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {CLAIM_COLUMNS}
+        "#
+    ))
+    .bind(plan.id)
+    .bind(&claimant.beneficiary)
+    .bind(&claimant.executor)
+    .bind(execute_after)
+    .bind(status)
+    .bind(telemetry::correlation_id())
+    .fetch_one(&mut *tx)
+    .await?;
+    claim_fraud::record_assessment(&mut *tx, claim.id, &assessment, device).await?;
+    if claim.status == "in_review" {
+        warn!(
+            claim_id = %claim.id,
+            score = assessment.score,
+            signals = ?assessment.signals,
+            "Claim held for fraud review"
+        );
+    }
+
+    let mut recipients = beneficiary_addresses(&mut *tx, plan.id).await?;
+    recipients.push(plan.owner_address.clone());
+    recipients.extend(claimant.executor.clone());
+    notify_all(
+        &mut *tx,
+        &recipients,
+        "claim_requested",
+        TemplateKey::ClaimRequested,
+        &[("execute_after", &claim.execute_after.to_rfc3339())],
+        &claim,
+    )
+    .await?;
+    record_audit(
+        &mut *tx,
+        claimant
+            .executor
+            .as_deref()
+            .unwrap_or(&claimant.beneficiary),
+        "claim.requested",
+        &claim.id.to_string(),
+        claimant.audit_details(serde_json::json!({
+            "plan_id": plan.id,
+            "execute_after": claim.execute_after,
+            "status": claim.status,
+            "fraud_score": assessment.score,
+        })),
+    )
+    .await?;
+    Ok(Some(claim))
+}
+
+/// Marks `claim` executed once its plan has been paid out, and tells the
+/// owner and `beneficiaries`. Does not commit.
+pub(crate) async fn mark_executed(
+    conn: &mut PgConnection,
+    claim: &ClaimRequest,
+    plan: &PlanRow,
+    beneficiaries: &[String],
+) -> Result<ClaimRequest, sqlx::Error> {
+    let claim = sqlx::query_as::<_, ClaimRequest>(&format!(
+        r#"
+        UPDATE claim_requests SET status = 'executed', resolved_at = NOW()
+        WHERE id = $1
+        RETURNING {CLAIM_COLUMNS}
+        "#
+    ))
+    .bind(claim.id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let mut recipients = beneficiaries.to_vec();
+    recipients.push(plan.owner_address.clone());
+    notify_all(
+        &mut *conn,
+        &recipients,
+        "claim_executed",
+        TemplateKey::ClaimExecuted,
+        &[],
+        &claim,
+    )
+    .await?;
+    record_audit(
+        &mut *conn,
+        SYSTEM_ACTOR,
+        "claim.executed",
+        &claim.id.to_string(),
+        serde_json::json!({ "plan_id": plan.id }),
+    )
+    .await?;
+    Ok(claim)
+}
+
+// Handler: Request Claim
+pub async fn request_claim(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    device: Option<Extension<ClientDevice>>,
+    payload: Option<Json<RequestClaimBody>>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = user.require_recently_verified() {
+        return e.into_response();
+    }
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+
+    let mut tx = match state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!(error = %e, "Failed to begin database transaction");
+            return database_error();
+        }
+    };
+
+    let plan = match sqlx::query_as::<_, PlanRow>(&format!(
+        "SELECT {PLAN_COLUMNS} FROM plans WHERE id = $1 AND is_active = true FOR UPDATE"
+    ))
+    .bind(plan_id)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return refused(StatusCode::NOT_FOUND, "No active plan found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan for claim");
+            return database_error();
+        }
+    };
+    if let Err(response) = freezes::refuse_frozen_plan(&mut tx, plan.id).await {
+        return response;
+    }
+
+    let beneficiaries = match beneficiary_addresses(&mut tx, plan.id).await {
+        Ok(addresses) => addresses,
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load beneficiaries");
+            return database_error();
+        }
+    };
+    let claimant = match claim_delegations::claimant(
+        &mut tx,
+        plan.id,
+        &caller,
+        payload.on_behalf_of.as_deref(),
+        DelegationScope::FileClaim,
+    )
+    .await
+    {
+        Ok(Ok(claimant)) => claimant,
+        Ok(Err(message)) => return refused(StatusCode::FORBIDDEN, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to check claim delegation");
+            return database_error();
+        }
+    };
+    if !beneficiaries.contains(&claimant.beneficiary) {
+        return refused(
+            StatusCode::FORBIDDEN,
+            "Only a beneficiary of this plan can request a claim",
+        );
+    }
+
+    if let Err(e) = wallet_reauth::enforce(
+        &mut tx,
+        &caller,
+        ReauthAction::Claim,
+        Some(plan.id),
+        payload.confirmation.as_ref(),
+    )
+    .await
+    {
+        return e.into_response();
+    }
+
+    let deadline = match check_eligibility(&state, &mut tx, &plan, &claimant.beneficiary).await {
+        Ok(Ok(deadline)) => deadline,
+        Ok(Err(ineligible)) => return ineligible.into_response(),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to check claim eligibility");
+            return database_error();
+        }
+    };
+
+    let device = device.as_ref().map(|Extension(d)| d);
+    let result: Result<Outcome<ClaimRequest>, sqlx::Error> = async {
+        let Some(claim) = file_claim(&state, &mut tx, &plan, &claimant, device, deadline).await?
+        else {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "A claim is already pending for this plan",
+            ));
+        };
+        tx.commit().await?;
+        Ok(Outcome::Done(claim))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(claim)) => (StatusCode::ACCEPTED, Json(claim)).into_response(),
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to request claim");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get Claim
+pub async fn get_claim(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result = sqlx::query_as::<_, ClaimRequest>(&format!(
+        r#"
+        SELECT {} FROM claim_requests c
+        JOIN plans p ON p.id = c.plan_id
+        WHERE c.plan_id = $1
+          AND (p.owner_address = $2
+               OR EXISTS (SELECT 1 FROM beneficiaries b
+                          WHERE b.plan_id = p.id AND b.wallet_address = $2)
+               OR EXISTS (SELECT 1 FROM claim_delegations d
+                          WHERE d.plan_id = p.id AND d.executor_address = $2
+                            AND d.revoked_at IS NULL AND d.expires_at > NOW()))
+        ORDER BY c.created_at DESC
+        LIMIT 1
+        "#,
+        prefixed_columns("c")
+    ))
+    .bind(plan_id)
+    .bind(&caller)
+    .fetch_optional(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(Some(claim)) => (StatusCode::OK, Json(claim)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "No claim found for this plan"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load claim");
+            database_error()
+        }
+    }
+}
+
+// Handler: Cancel Claim
+pub async fn cancel_claim(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    payload: Option<Json<CancelClaimBody>>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let reason = clean_reason(payload);
+
+    match cancel_pending(&state, Some(plan_id), None, Some(&owner), &owner, reason).await {
+        Ok(Some(claim)) => (StatusCode::OK, Json(claim)).into_response(),
+        Ok(None) => refused(
+            StatusCode::NOT_FOUND,
+            "No pending claim found for this plan",
+        ),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to cancel claim");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Cancel Claim
+pub async fn admin_cancel_claim(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(claim_id): Path<Uuid>,
+    payload: Option<Json<CancelClaimBody>>,
+) -> impl IntoResponse {
+    let reason = clean_reason(payload);
+
+    match cancel_pending(&state, None, Some(claim_id), None, &admin.user_id, reason).await {
+        Ok(Some(claim)) => (StatusCode::OK, Json(claim)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Pending claim not found"),
+        Err(e) => {
+            error!(claim_id = %claim_id, error = %e, "Failed to cancel claim");
+            database_error()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClaimExecutorConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+}
+
+impl ClaimExecutorConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("CLAIM_EXECUTOR_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("CLAIM_EXECUTOR_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+/// Pays out claim requests whose cooling-off window has passed.
+pub struct ClaimExecutorService {
+    state: Arc<AppState>,
+    config: ClaimExecutorConfig,
+}
+
+impl ClaimExecutorService {
+    pub fn new(state: Arc<AppState>, config: ClaimExecutorConfig) -> Self {
+        Self { state, config }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("claim_executor", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&claims| {
+                    if claims > 0 {
+                        info!(claims, "Matured claim requests processed");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Executes every pending claim past its cooling-off window. Each claim
+    /// is paid out in its own transaction so one failure does not hold up
+    /// the rest. Returns the number of claims processed.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut lock_tx = self.state.db_pool.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(CLAIM_EXECUTOR_LOCK_KEY)
+            .fetch_one(&mut *lock_tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Claim executor lock is held by another worker; skipping run");
+            lock_tx.commit().await?;
+            return Ok(0);
+        }
+
+        let due = sqlx::query_as::<_, ClaimRequest>(&format!(
+            r#"
+            SELECT {CLAIM_COLUMNS} FROM claim_requests
+            WHERE status = 'pending' AND execute_after <= NOW()
+              AND plan_id NOT IN (SELECT id FROM plans WHERE frozen_at IS NOT NULL)
+            ORDER BY execute_after
+            LIMIT $1
+            "#
+        ))
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *lock_tx)
+        .await?;
+
+        for claim in &due {
+            telemetry::with_correlation_id(claim.correlation_id.clone(), async {
+                if let Err(failure) = execute_claim(&self.state, &self.state.db_pool, claim).await?
+                {
+                    warn!(claim_id = %claim.id, reason = %failure, "Claim request failed");
+                    mark_failed(&self.state.db_pool, claim, &failure).await?;
+                }
+                Ok::<_, sqlx::Error>(())
+            })
+            .await?;
+        }
+
+        lock_tx.commit().await?;
+        Ok(due.len())
+    }
+}
+
+/// Pays out one matured claim. The inner error is a reason the claim
+/// cannot be paid, in which case nothing has been written.
+async fn execute_claim(
+    state: &AppState,
+    db: &PgPool,
+    claim: &ClaimRequest,
+) -> Result<Result<(), String>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let still_pending: bool = sqlx::query_scalar(
+        "SELECT status = 'pending' FROM claim_requests WHERE id = $1 FOR UPDATE",
+    )
+    .bind(claim.id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if !still_pending {
+        return Ok(Ok(()));
+    }
+
+    let plan = sqlx::query_as::<_, PlanRow>(&format!(
+        "SELECT {PLAN_COLUMNS} FROM plans WHERE id = $1 FOR UPDATE"
+    ))
+    .bind(claim.plan_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(plan) = plan.filter(|p| p.is_active) else {
+        return Ok(Err("Plan is no longer active".to_string()));
+    };
+    // Frozen after it was picked up; it stays pending until unfrozen.
+    if freezes::plan_frozen(&mut *tx, plan.id).await? {
+        return Ok(Ok(()));
+    }
+
+    let now = Utc::now().timestamp();
+    let deadline = plan_owners::inactivity_deadline(&mut *tx, &plan).await?;
+    if now < witnesses::claimable_at(&mut tx, &plan, deadline).await? {
+        return Ok(Err(
+            "Owner checked in during the cooling-off period".to_string()
+        ));
+    }
+
+    // Yield accrued or a tier lowered since the claim was filed.
+    let settings = state.system_settings.get().await;
+    if let Err(exceeded) = check_payout_limits(&mut tx, &settings, &plan).await? {
+        return Ok(Err(exceeded.to_string()));
+    }
+
+    let (_, beneficiaries) = match pay_out_plan(state, &mut tx, &plan, now).await {
+        Ok(result) => result,
+        Err(e) => return Ok(Err(e.message)),
+    };
+
+    let claim = mark_executed(&mut tx, claim, &plan, &beneficiaries).await?;
+
+    tx.commit().await?;
+    invalidate_plan_cache(&state.plan_cache, &plan.owner_address, &beneficiaries).await;
+    info!(claim_id = %claim.id, plan_id = %plan.id, "Claim request executed");
+    Ok(Ok(()))
+}
+
+async fn mark_failed(db: &PgPool, claim: &ClaimRequest, reason: &str) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let failed = sqlx::query_as::<_, ClaimRequest>(&format!(
+        r#"
+        UPDATE claim_requests
+        SET status = 'failed', failure_reason = $2, resolved_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        RETURNING {CLAIM_COLUMNS}
+        "#
+    ))
+    .bind(claim.id)
+    .bind(reason)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if let Some(failed) = failed {
+        notify_all(
+            &mut tx,
+            std::slice::from_ref(&failed.requested_by),
+            "claim_failed",
+            TemplateKey::ClaimFailed,
+            &[("reason", reason)],
+            &failed,
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            SYSTEM_ACTOR,
+            "claim.failed",
+            &failed.id.to_string(),
+            serde_json::json!({ "plan_id": failed.plan_id, "reason": reason }),
+        )
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_every_claim_column() {
+        let columns = prefixed_columns("c");
+        assert!(columns.starts_with("c.id, c.plan_id, c.requested_by"));
+        assert!(columns.ends_with("c.created_at, c.resolved_at"));
+        assert_eq!(
+            columns.matches("c.").count(),
+            CLAIM_COLUMNS.split(", ").count()
+        );
+    }
+}
@@ -0,0 +1,662 @@
+//! Consent to the terms of service, the privacy policy and marketing.
+//!
+//! Each kind of consent has versioned documents. Publishing a new version
+//! makes it current, and consent given to an older version stops counting
+//! until the user accepts the new one. Every grant and withdrawal is kept
+//! in `user_consents` with the request's correlation id, which ties it to
+//! the captured HTTP request. KYC submissions need privacy policy consent,
+//! and marketing broadcasts are only emailed to users with marketing
+//! consent.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::telemetry;
+
+const MAX_VERSION_LEN: usize = 32;
+const MAX_TITLE_LEN: usize = 200;
+const MAX_URL_LEN: usize = 2048;
+const HISTORY_LIMIT: i64 = 500;
+
+const DOCUMENT_COLUMNS: &str = "id, kind, version, title, url, published_by, published_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentKind {
+    TermsOfService,
+    PrivacyPolicy,
+    Marketing,
+}
+
+impl ConsentKind {
+    pub const ALL: [Self; 3] = [Self::TermsOfService, Self::PrivacyPolicy, Self::Marketing];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TermsOfService => "terms_of_service",
+            Self::PrivacyPolicy => "privacy_policy",
+            Self::Marketing => "marketing",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ConsentDocument {
+    pub id: Uuid,
+    pub kind: String,
+    pub version: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub published_by: String,
+    pub published_at: DateTime<Utc>,
+}
+
+/// A user's standing for one kind of consent.
+#[derive(Debug, Serialize)]
+pub struct ConsentStatus {
+    pub kind: ConsentKind,
+    /// The current document; `None` when none is published.
+    pub document: Option<ConsentDocument>,
+    /// Granted, and to the current document.
+    pub active: bool,
+    /// The user's latest decision, if they made one.
+    pub granted: Option<bool>,
+    /// Version the latest decision was made on.
+    pub version: Option<String>,
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsentDecision {
+    pub kind: ConsentKind,
+    /// Required to grant, and must be the current version.
+    #[serde(default)]
+    pub version: Option<String>,
+    pub granted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordConsentsRequest {
+    pub consents: Vec<ConsentDecision>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublishDocumentRequest {
+    pub kind: ConsentKind,
+    pub version: String,
+    pub title: String,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsentReportQuery {
+    /// Report the position at this time; defaults to now.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ConsentReportRow {
+    pub kind: String,
+    /// Version current at `as_of`.
+    pub version: String,
+    pub published_at: DateTime<Utc>,
+    /// Users whose latest decision grants this version.
+    pub active: i64,
+    /// Users whose latest grant is for an earlier version.
+    pub outdated: i64,
+    /// Users whose latest decision withdraws consent.
+    pub withdrawn: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsentReport {
+    pub as_of: DateTime<Utc>,
+    pub users_total: i64,
+    pub kinds: Vec<ConsentReportRow>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ConsentRecord {
+    pub id: i64,
+    pub user_address: String,
+    pub kind: String,
+    pub version: String,
+    pub title: String,
+    pub granted: bool,
+    pub correlation_id: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// An operation refused because the caller has not consented to the
+/// current version of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsentRequired {
+    pub kind: ConsentKind,
+    pub version: String,
+}
+
+impl IntoResponse for ConsentRequired {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": format!(
+                    "Consent to version {} of the {} is required",
+                    self.version,
+                    self.kind.as_str().replace('_', " ")
+                ),
+                "consent_required": self.kind,
+                "version": self.version,
+            })),
+        )
+            .into_response()
+    }
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// SQL condition that the wallet in `address` (a column or expression) has
+/// active consent of `kind`.
+pub fn active_consent_sql(address: &str, kind: ConsentKind) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM active_consents ac WHERE ac.user_address = {address} AND ac.kind = '{}')",
+        kind.as_str()
+    )
+}
+
+/// Refuses `wallet` unless it has consented to the current `kind`
+/// document. Nothing is required while no document of the kind is
+/// published.
+pub async fn require_consent<'e, E>(
+    executor: E,
+    wallet: &str,
+    kind: ConsentKind,
+) -> Result<Result<(), ConsentRequired>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let current: Option<(String, bool)> = sqlx::query_as(
+        r#"
+        SELECT d.version,
+               EXISTS (SELECT 1 FROM active_consents a
+                       WHERE a.user_address = $1 AND a.kind = d.kind)
+        FROM current_consent_documents d
+        WHERE d.kind = $2
+        "#,
+    )
+    .bind(wallet)
+    .bind(kind.as_str())
+    .fetch_optional(executor)
+    .await?;
+    Ok(match current {
+        Some((version, false)) => Err(ConsentRequired { kind, version }),
+        _ => Ok(()),
+    })
+}
+
+async fn consent_statuses(
+    conn: &mut sqlx::PgConnection,
+    wallet: &str,
+) -> Result<Vec<ConsentStatus>, sqlx::Error> {
+    let documents = sqlx::query_as::<_, ConsentDocument>(&format!(
+        "SELECT {DOCUMENT_COLUMNS} FROM current_consent_documents"
+    ))
+    .fetch_all(&mut *conn)
+    .await?;
+    let latest: Vec<(String, Uuid, String, bool, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (c.kind) c.kind, c.document_id, d.version, c.granted, c.recorded_at
+        FROM user_consents c JOIN consent_documents d ON d.id = c.document_id
+        WHERE c.user_address = $1
+        ORDER BY c.kind, c.id DESC
+        "#,
+    )
+    .bind(wallet)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(ConsentKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let document = documents.iter().find(|d| d.kind == kind.as_str()).cloned();
+            let decision = latest.iter().find(|(k, ..)| k == kind.as_str());
+            let active = match (&document, decision) {
+                (Some(document), Some((_, document_id, _, granted, _))) => {
+                    *granted && *document_id == document.id
+                }
+                _ => false,
+            };
+            ConsentStatus {
+                kind,
+                document,
+                active,
+                granted: decision.map(|(_, _, _, granted, _)| *granted),
+                version: decision.map(|(_, _, version, ..)| version.clone()),
+                recorded_at: decision.map(|(.., at)| *at),
+            }
+        })
+        .collect())
+}
+
+// Handler: List Consent Documents
+pub async fn list_consent_documents(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match sqlx::query_as::<_, ConsentDocument>(&format!(
+        "SELECT {DOCUMENT_COLUMNS} FROM current_consent_documents ORDER BY kind"
+    ))
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(documents) => (StatusCode::OK, Json(documents)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list consent documents");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get My Consents
+pub async fn get_my_consents(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Vec<ConsentStatus>, sqlx::Error> = async {
+        let mut conn = state.db_pool.acquire().await?;
+        consent_statuses(&mut conn, &caller).await
+    }
+    .await;
+
+    match result {
+        Ok(statuses) => (StatusCode::OK, Json(statuses)).into_response(),
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to load consents");
+            database_error()
+        }
+    }
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+// Handler: Record My Consents
+pub async fn record_my_consents(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<RecordConsentsRequest>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if payload.consents.is_empty() {
+        return refused(StatusCode::BAD_REQUEST, "consents must not be empty");
+    }
+    let mut kinds = HashSet::new();
+    if !payload.consents.iter().all(|c| kinds.insert(c.kind)) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Each kind of consent may appear only once",
+        );
+    }
+    if payload
+        .consents
+        .iter()
+        .any(|c| c.granted && c.version.as_deref().is_none_or(|v| v.trim().is_empty()))
+    {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "version is required to give consent",
+        );
+    }
+
+    let result: Result<Outcome<Vec<ConsentStatus>>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        // Serialises a user's decisions so the latest entry is well defined.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('user_consents:' || $1))")
+            .bind(&caller)
+            .execute(&mut *tx)
+            .await?;
+
+        for decision in &payload.consents {
+            let Some(document) = sqlx::query_as::<_, ConsentDocument>(&format!(
+                "SELECT {DOCUMENT_COLUMNS} FROM current_consent_documents WHERE kind = $1"
+            ))
+            .bind(decision.kind.as_str())
+            .fetch_optional(&mut *tx)
+            .await?
+            else {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "No document of that kind has been published",
+                ));
+            };
+            if decision.granted
+                && decision.version.as_deref().map(str::trim) != Some(&document.version)
+            {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "Consent can only be given to the current version of a document",
+                ));
+            }
+
+            let unchanged: bool = sqlx::query_scalar(
+                r#"
+                SELECT COALESCE((
+                    SELECT document_id = $3 AND granted = $4 FROM user_consents
+                    WHERE user_address = $1 AND kind = $2
+                    ORDER BY id DESC LIMIT 1), false)
+                "#,
+            )
+            .bind(&caller)
+            .bind(decision.kind.as_str())
+            .bind(document.id)
+            .bind(decision.granted)
+            .fetch_one(&mut *tx)
+            .await?;
+            if unchanged {
+                continue;
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO user_consents (user_address, document_id, kind, granted, correlation_id)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(&caller)
+            .bind(document.id)
+            .bind(decision.kind.as_str())
+            .bind(decision.granted)
+            .bind(telemetry::correlation_id())
+            .execute(&mut *tx)
+            .await?;
+            record_audit(
+                &mut *tx,
+                &caller,
+                if decision.granted {
+                    "consent.granted"
+                } else {
+                    "consent.withdrawn"
+                },
+                &caller,
+                serde_json::json!({
+                    "kind": decision.kind,
+                    "version": document.version,
+                }),
+            )
+            .await?;
+        }
+
+        let statuses = consent_statuses(&mut tx, &caller).await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(statuses))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(statuses)) => (StatusCode::OK, Json(statuses)).into_response(),
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to record consents");
+            database_error()
+        }
+    }
+}
+
+// Handler: Publish Consent Document (admin)
+pub async fn publish_consent_document(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(payload): Json<PublishDocumentRequest>,
+) -> impl IntoResponse {
+    let version = payload.version.trim().to_string();
+    let title = payload.title.trim().to_string();
+    let url = payload
+        .url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty());
+    if version.is_empty() || version.len() > MAX_VERSION_LEN {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "version must be between 1 and 32 characters",
+        );
+    }
+    if title.is_empty() || title.chars().count() > MAX_TITLE_LEN {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "title must be between 1 and 200 characters",
+        );
+    }
+    if url
+        .as_ref()
+        .is_some_and(|u| !u.starts_with("https://") || u.len() > MAX_URL_LEN)
+    {
+        return refused(StatusCode::BAD_REQUEST, "url must be an https URL");
+    }
+
+    let result: Result<Outcome<ConsentDocument>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(document) = sqlx::query_as::<_, ConsentDocument>(&format!(
+            r#"
+            INSERT INTO consent_documents (kind, version, title, url, published_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (kind, version) DO NOTHING
+            RETURNING {DOCUMENT_COLUMNS}
+            "#
+        ))
+        .bind(payload.kind.as_str())
+        .bind(&version)
+        .bind(&title)
+        .bind(&url)
+        .bind(&admin.user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "That version has already been published",
+            ));
+        };
+
+        // Users who had consented to the previous version are asked to
+        // review the new one.
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (user_address, notification_type, title, message, metadata)
+            SELECT latest.user_address, 'consent_update', $2, $3,
+                   jsonb_build_object('kind', $1::text, 'version', $4::text)
+            FROM (
+                SELECT DISTINCT ON (user_address) user_address, granted
+                FROM user_consents WHERE kind = $1
+                ORDER BY user_address, id DESC
+            ) latest
+            WHERE latest.granted
+            "#,
+        )
+        .bind(payload.kind.as_str())
+        .bind(format!("{title} updated"))
+        .bind(format!(
+            "We have published version {version} of the {title}. Please review and accept it to keep using the services that depend on it."
+        ))
+        .bind(&version)
+        .execute(&mut *tx)
+        .await?;
+
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "consent_document.published",
+            &document.id.to_string(),
+            serde_json::json!({
+                "kind": payload.kind,
+                "version": version,
+                "title": title,
+                "url": url,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(document))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(document)) => {
+            info!(
+                kind = %document.kind,
+                version = %document.version,
+                "Consent document published"
+            );
+            (StatusCode::CREATED, Json(document)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(error = %e, "Failed to publish consent document");
+            database_error()
+        }
+    }
+}
+
+// Handler: Consent Report (admin)
+pub async fn get_consent_report(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ConsentReportQuery>,
+) -> impl IntoResponse {
+    let as_of = query.as_of.unwrap_or_else(Utc::now);
+
+    let result: Result<ConsentReport, sqlx::Error> = async {
+        let kinds = sqlx::query_as::<_, ConsentReportRow>(
+            r#"
+            WITH docs AS (
+                SELECT DISTINCT ON (kind) id, kind, version, published_at
+                FROM consent_documents
+                WHERE published_at <= $1
+                ORDER BY kind, published_at DESC, id
+            ),
+            latest AS (
+                SELECT DISTINCT ON (user_address, kind) user_address, kind, document_id, granted
+                FROM user_consents
+                WHERE recorded_at <= $1
+                ORDER BY user_address, kind, id DESC
+            )
+            SELECT d.kind, d.version, d.published_at,
+                   COUNT(l.user_address) FILTER (WHERE l.granted AND l.document_id = d.id) AS active,
+                   COUNT(l.user_address) FILTER (WHERE l.granted AND l.document_id <> d.id) AS outdated,
+                   COUNT(l.user_address) FILTER (WHERE NOT l.granted) AS withdrawn
+            FROM docs d
+            LEFT JOIN latest l ON l.kind = d.kind
+            GROUP BY d.kind, d.version, d.published_at
+            ORDER BY d.kind
+            "#,
+        )
+        .bind(as_of)
+        .fetch_all(&state.db_pool)
+        .await?;
+        let users_total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE created_at <= $1")
+                .bind(as_of)
+                .fetch_one(&state.db_pool)
+                .await?;
+        Ok(ConsentReport {
+            as_of,
+            users_total,
+            kinds,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to build consent report");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get User Consents (admin)
+pub async fn get_user_consents(
+    State(state): State<Arc<AppState>>,
+    Path(user): Path<String>,
+) -> impl IntoResponse {
+    let user = user.trim().to_string();
+    match sqlx::query_as::<_, ConsentRecord>(
+        r#"
+        SELECT c.id, c.user_address, c.kind, d.version, d.title, c.granted,
+               c.correlation_id, c.recorded_at
+        FROM user_consents c
+        JOIN consent_documents d ON d.id = c.document_id
+        WHERE c.user_address = $1
+           OR c.user_address = (SELECT wallet_address FROM users WHERE id::text = $1)
+        ORDER BY c.id DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(&user)
+    .bind(HISTORY_LIMIT)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(records) => (StatusCode::OK, Json(records)).into_response(),
+        Err(e) => {
+            error!(user = %user, error = %e, "Failed to load consent history");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_round_trip() {
+        for kind in ConsentKind::ALL {
+            assert_eq!(ConsentKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(ConsentKind::parse("cookies"), None);
+    }
+
+    #[test]
+    fn consent_required_names_the_document() {
+        let required = ConsentRequired {
+            kind: ConsentKind::PrivacyPolicy,
+            version: "2".to_string(),
+        };
+        assert_eq!(required.into_response().status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            active_consent_sql("r.address", ConsentKind::Marketing),
+            "EXISTS (SELECT 1 FROM active_consents ac WHERE ac.user_address = r.address AND ac.kind = 'marketing')"
+        );
+    }
+}
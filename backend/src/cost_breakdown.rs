@@ -0,0 +1,290 @@
+//! Itemized cost of a plan, so clients never redo the money math.
+//!
+//! `to_date` prices a payout made now: yield credited plus yield accrued
+//! since the last ping, split across beneficiaries and charged the platform
+//! fee per share, as at claim time. It uses checked decimal arithmetic and
+//! refuses to answer rather than overflow. `projected` totals the payout
+//! schedule from [`crate::projection`], so it matches the projection
+//! endpoint. Plans carry no late fees or interest charges; yield is paid to
+//! the beneficiaries, not charged.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::money::Money;
+use crate::organizations;
+use crate::projection::{build_schedule, ScheduleInput};
+
+const BPS_DENOMINATOR: i64 = 10_000;
+/// Same year length as `yield_calculator::calculate_yield`.
+const SECONDS_PER_YEAR: i64 = 31_557_600;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct CostPlanRow {
+    id: Uuid,
+    token_address: String,
+    amount: Decimal,
+    earn_yield: bool,
+    yield_rate_bps: i32,
+    accrued_yield: Decimal,
+    last_ping: i64,
+    grace_period_seconds: i64,
+    is_active: bool,
+    installment_count: i32,
+    installment_interval_days: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostItem {
+    /// `platform_fee`; plans have no other charges.
+    pub kind: &'static str,
+    pub rate_bps: u32,
+    pub to_date: Money,
+    pub projected: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostToDate {
+    pub as_of: DateTime<Utc>,
+    pub principal: Money,
+    pub yield_accrued: Money,
+    pub gross: Money,
+    pub total_cost: Money,
+    pub net: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectedCost {
+    /// When the last installment is scheduled.
+    pub final_payout_at: DateTime<Utc>,
+    pub gross: Money,
+    pub yield_accrued: Money,
+    pub total_cost: Money,
+    pub net: Money,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanCostBreakdown {
+    pub plan_id: Uuid,
+    pub token_address: String,
+    pub items: Vec<CostItem>,
+    pub to_date: CostToDate,
+    pub projected: ProjectedCost,
+}
+
+/// Simple interest on `balance` in whole base units, in exact decimal
+/// arithmetic. `None` on overflow.
+pub fn checked_yield(balance: Decimal, rate_bps: u32, elapsed_secs: i64) -> Option<Decimal> {
+    if rate_bps == 0 || elapsed_secs <= 0 {
+        return Some(Decimal::ZERO);
+    }
+    balance
+        .checked_mul(Decimal::from(rate_bps))?
+        .checked_mul(Decimal::from(elapsed_secs))?
+        .checked_div(Decimal::from(BPS_DENOMINATOR * SECONDS_PER_YEAR))
+        .map(|y| y.floor())
+}
+
+/// `fee_bps` of `amount`, rounded down to whole base units.
+pub fn checked_fee(amount: Decimal, fee_bps: u32) -> Option<Decimal> {
+    amount
+        .checked_mul(Decimal::from(fee_bps))?
+        .checked_div(Decimal::from(BPS_DENOMINATOR))
+        .map(|fee| fee.floor())
+}
+
+/// Fee on `gross` charged per beneficiary share, with the last share taking
+/// the rounding remainder, as at claim time.
+pub fn checked_fee_by_share(
+    gross: Decimal,
+    allocations_bps: &[u32],
+    fee_bps: u32,
+) -> Option<Decimal> {
+    let mut allocated = Decimal::ZERO;
+    let mut fee = Decimal::ZERO;
+    for (i, bps) in allocations_bps.iter().enumerate() {
+        let share = if i + 1 == allocations_bps.len() {
+            gross.checked_sub(allocated)?
+        } else {
+            checked_fee(gross, *bps)?
+        };
+        allocated = allocated.checked_add(share)?;
+        fee = fee.checked_add(checked_fee(share, fee_bps)?)?;
+    }
+    Some(fee)
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: Plan Cost Breakdown
+pub async fn get_plan_cost_breakdown(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let loaded = async {
+        let plan = sqlx::query_as::<_, CostPlanRow>(
+            r#"
+            SELECT id, token_address, amount, earn_yield, yield_rate_bps, accrued_yield,
+                   last_ping, grace_period_seconds, is_active,
+                   installment_count, installment_interval_days
+            FROM plans
+            WHERE id = $1
+            "#,
+        )
+        .bind(plan_id)
+        .fetch_optional(&state.db_pool)
+        .await?;
+        let allocations: Vec<i32> = sqlx::query_scalar(
+            "SELECT allocation_bps FROM beneficiaries WHERE plan_id = $1 ORDER BY wallet_address",
+        )
+        .bind(plan_id)
+        .fetch_all(&state.db_pool)
+        .await?;
+        let fees = organizations::plan_fee_schedule(&state.db_pool, &state.config, plan_id).await?;
+        Ok::<_, sqlx::Error>((plan, allocations, fees.payout_fee_bps))
+    }
+    .await;
+
+    let (plan, allocations, fee_bps) = match loaded {
+        Ok((Some(plan), allocations, fee_bps)) => (plan, allocations, fee_bps),
+        Ok((None, _, _)) => return refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan for cost breakdown");
+            return database_error();
+        }
+    };
+    if !plan.is_active {
+        return refused(StatusCode::CONFLICT, "Plan has already been paid out");
+    }
+
+    let now = Utc::now();
+    let allocations: Vec<u32> = allocations
+        .into_iter()
+        .map(|bps| bps.max(0) as u32)
+        .collect();
+    let yield_rate_bps = if plan.earn_yield {
+        plan.yield_rate_bps.max(0) as u32
+    } else {
+        0
+    };
+    let credited_yield = plan.accrued_yield.floor();
+    let money = |amount: Decimal| Money::new(amount, &plan.token_address);
+
+    let to_date = (|| {
+        let balance = plan.amount.checked_add(credited_yield)?;
+        let accruing = checked_yield(balance, yield_rate_bps, now.timestamp() - plan.last_ping)?;
+        let yield_accrued = credited_yield.checked_add(accruing)?;
+        let gross = plan.amount.checked_add(yield_accrued)?;
+        let total_cost = checked_fee_by_share(gross, &allocations, fee_bps)?;
+        Some(CostToDate {
+            as_of: now,
+            principal: money(plan.amount),
+            yield_accrued: money(yield_accrued),
+            gross: money(gross),
+            total_cost: money(total_cost),
+            net: money(gross.checked_sub(total_cost)?),
+        })
+    })();
+    let Some(to_date) = to_date else {
+        return refused(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Plan amounts are too large to price",
+        );
+    };
+
+    let accrual_start = Utc.timestamp_opt(plan.last_ping, 0).single().unwrap_or(now);
+    let input = ScheduleInput {
+        currency: plan.token_address.clone(),
+        principal: plan.amount,
+        accrued_yield: credited_yield,
+        yield_rate_bps,
+        accrual_start,
+        first_payout_at: (accrual_start + chrono::Duration::seconds(plan.grace_period_seconds))
+            .max(now),
+        installment_count: plan.installment_count.max(1) as u32,
+        interval_days: plan.installment_interval_days.max(1) as u32,
+        fee_bps,
+        beneficiaries: allocations
+            .iter()
+            .map(|bps| (String::new(), *bps))
+            .collect(),
+    };
+    let installments = build_schedule(&input, None);
+    let projected = ProjectedCost {
+        final_payout_at: installments
+            .last()
+            .map_or(input.first_payout_at, |i| i.scheduled_at),
+        gross: money(installments.iter().map(|i| i.gross_amount.amount).sum()),
+        yield_accrued: money(
+            credited_yield
+                + installments
+                    .iter()
+                    .map(|i| i.yield_amount.amount)
+                    .sum::<Decimal>(),
+        ),
+        total_cost: money(installments.iter().map(|i| i.fee_amount.amount).sum()),
+        net: money(installments.iter().map(|i| i.net_amount.amount).sum()),
+    };
+
+    let breakdown = PlanCostBreakdown {
+        plan_id: plan.id,
+        token_address: plan.token_address,
+        items: vec![CostItem {
+            kind: "platform_fee",
+            rate_bps: fee_bps,
+            to_date: to_date.total_cost.clone(),
+            projected: projected.total_cost.clone(),
+        }],
+        to_date,
+        projected,
+    };
+    (StatusCode::OK, Json(breakdown)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yield_and_fees_round_down_to_base_units() {
+        // 5% a year on 1,000,000 for half a year.
+        assert_eq!(
+            checked_yield(Decimal::from(1_000_000), 500, SECONDS_PER_YEAR / 2),
+            Some(Decimal::from(25_000))
+        );
+        assert_eq!(
+            checked_yield(Decimal::from(1_000), 500, 0),
+            Some(Decimal::ZERO)
+        );
+        assert_eq!(checked_fee(Decimal::from(999), 100), Some(Decimal::from(9)));
+
+        // 1,001 split 1/3 and 2/3: shares 333 and 668, a 1% fee of 3 + 6.
+        assert_eq!(
+            checked_fee_by_share(Decimal::from(1_001), &[3_333, 6_667], 100),
+            Some(Decimal::from(9))
+        );
+    }
+
+    #[test]
+    fn overflow_is_reported_instead_of_panicking() {
+        assert_eq!(checked_fee(Decimal::MAX, 10_000), None);
+        assert_eq!(checked_yield(Decimal::MAX, 500, SECONDS_PER_YEAR), None);
+    }
+}
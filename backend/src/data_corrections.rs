@@ -0,0 +1,678 @@
+//! Maker-checker corrections of user and plan data by support staff.
+//!
+//! Only the fields in [`CorrectionField`] can be corrected. One admin
+//! proposes a new value with a reason code, which snapshots the current
+//! value, and a different admin must approve it. Approval re-reads the
+//! field and refuses if it no longer matches the snapshot, so a correction
+//! never overwrites a change it did not see. Applied corrections keep both
+//! values, are audit logged and tell the affected user. Proposals expire
+//! after `PENDING_CHANGE_TTL_HOURS`, like settings changes.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::notifications::create_notification;
+
+const CORRECTION_COLUMNS: &str = "id, field, record_id, before_value, after_value, reason_code, \
+     reason, status, proposed_by, proposed_at, expires_at, reviewed_by, reviewed_at, review_note";
+const MAX_TEXT_LEN: usize = 254;
+
+/// Fields support may correct, and the record each is keyed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionField {
+    /// `notification_preferences.email`, by user address.
+    NotificationEmail,
+    /// `emergency_contacts.email`, by contact id. Resets verification.
+    EmergencyContactEmail,
+    /// `user_profiles.display_name`, by user address.
+    DisplayName,
+    /// `users.kyc_status`, by wallet address.
+    KycStatus,
+    /// `plans.status` of a live plan, by plan id; `ACTIVE` or `CLAIMABLE`.
+    PlanStatus,
+}
+
+impl CorrectionField {
+    pub const ALL: [Self; 5] = [
+        Self::NotificationEmail,
+        Self::EmergencyContactEmail,
+        Self::DisplayName,
+        Self::KycStatus,
+        Self::PlanStatus,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotificationEmail => "notification_email",
+            Self::EmergencyContactEmail => "emergency_contact_email",
+            Self::DisplayName => "display_name",
+            Self::KycStatus => "kyc_status",
+            Self::PlanStatus => "plan_status",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.as_str() == value)
+    }
+
+    fn keyed_by_id(self) -> bool {
+        matches!(self, Self::EmergencyContactEmail | Self::PlanStatus)
+    }
+
+    /// Checks a proposed value; `null` clears optional text fields.
+    pub fn validate(self, value: &Value) -> Result<(), String> {
+        let text = match value {
+            Value::Null if matches!(self, Self::NotificationEmail | Self::DisplayName) => {
+                return Ok(())
+            }
+            Value::String(text) => text.as_str(),
+            _ => return Err(format!("{} must be a string", self.as_str())),
+        };
+        let ok = match self {
+            Self::NotificationEmail | Self::EmergencyContactEmail => {
+                text.len() <= MAX_TEXT_LEN
+                    && text
+                        .split_once('@')
+                        .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+            }
+            Self::DisplayName => !text.trim().is_empty() && text.chars().count() <= MAX_TEXT_LEN,
+            Self::KycStatus => ["pending", "submitted", "approved", "rejected"].contains(&text),
+            Self::PlanStatus => ["ACTIVE", "CLAIMABLE"].contains(&text),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("Invalid value for {}", self.as_str()))
+        }
+    }
+
+    /// Current value and the user to tell about a correction, or `None`
+    /// when the record does not exist.
+    async fn read(
+        self,
+        conn: &mut PgConnection,
+        record_id: &str,
+    ) -> Result<Option<(Value, String)>, sqlx::Error> {
+        let row: Option<(Option<String>, String)> = match self {
+            Self::NotificationEmail => {
+                sqlx::query_as(
+                    "SELECT email, user_address FROM notification_preferences WHERE user_address = $1 FOR UPDATE",
+                )
+                .bind(record_id)
+                .fetch_optional(&mut *conn)
+                .await?
+            }
+            Self::EmergencyContactEmail => {
+                sqlx::query_as(
+                    "SELECT email, user_address FROM emergency_contacts WHERE id = $1::uuid FOR UPDATE",
+                )
+                .bind(record_id)
+                .fetch_optional(&mut *conn)
+                .await?
+            }
+            Self::DisplayName => {
+                sqlx::query_as(
+                    "SELECT display_name, user_address FROM user_profiles WHERE user_address = $1 FOR UPDATE",
+                )
+                .bind(record_id)
+                .fetch_optional(&mut *conn)
+                .await?
+            }
+            Self::KycStatus => {
+                sqlx::query_as(
+                    "SELECT kyc_status::text, wallet_address FROM users WHERE wallet_address = $1 FOR UPDATE",
+                )
+                .bind(record_id)
+                .fetch_optional(&mut *conn)
+                .await?
+            }
+            Self::PlanStatus => {
+                sqlx::query_as(
+                    r#"
+                    SELECT status::text, owner_address FROM plans
+                    WHERE id = $1::uuid AND is_active = true
+                    FOR UPDATE
+                    "#,
+                )
+                .bind(record_id)
+                .fetch_optional(&mut *conn)
+                .await?
+            }
+        };
+        Ok(row.map(|(value, user)| (value.map_or(Value::Null, Value::String), user)))
+    }
+
+    async fn write(
+        self,
+        conn: &mut PgConnection,
+        record_id: &str,
+        value: &Value,
+    ) -> Result<(), sqlx::Error> {
+        let text = value.as_str();
+        let sql = match self {
+            Self::NotificationEmail => {
+                "UPDATE notification_preferences SET email = $2, updated_at = NOW() WHERE user_address = $1"
+            }
+            Self::EmergencyContactEmail => {
+                r#"
+                UPDATE emergency_contacts
+                SET email = $2, email_verified_at = NULL, email_code_hash = NULL, updated_at = NOW()
+                WHERE id = $1::uuid
+                "#
+            }
+            Self::DisplayName => {
+                "UPDATE user_profiles SET display_name = $2, updated_at = NOW() WHERE user_address = $1"
+            }
+            Self::KycStatus => {
+                "UPDATE users SET kyc_status = $2::kyc_status WHERE wallet_address = $1"
+            }
+            Self::PlanStatus => "UPDATE plans SET status = $2 WHERE id = $1::uuid",
+        };
+        sqlx::query(sql)
+            .bind(record_id)
+            .bind(text)
+            .execute(&mut *conn)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Why a correction is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasonCode {
+    DataEntryError,
+    UserRequest,
+    SystemDefect,
+    Compliance,
+}
+
+impl ReasonCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DataEntryError => "data_entry_error",
+            Self::UserRequest => "user_request",
+            Self::SystemDefect => "system_defect",
+            Self::Compliance => "compliance",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DataCorrection {
+    pub id: Uuid,
+    pub field: String,
+    pub record_id: String,
+    pub before_value: Value,
+    pub after_value: Value,
+    pub reason_code: String,
+    pub reason: String,
+    pub status: String,
+    pub proposed_by: String,
+    pub proposed_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProposeCorrectionRequest {
+    pub field: CorrectionField,
+    pub record_id: String,
+    pub value: Value,
+    pub reason_code: ReasonCode,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ReviewCorrectionRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorrectionQuery {
+    pub status: Option<String>,
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// Marks pending corrections past their expiry as expired.
+pub async fn expire_stale(db: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let expired: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        UPDATE data_corrections
+        SET status = 'expired'
+        WHERE status = 'pending' AND expires_at <= NOW()
+        RETURNING id, field
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for (id, field) in &expired {
+        record_audit(
+            &mut *tx,
+            SYSTEM_ACTOR,
+            "data_correction.expired",
+            &id.to_string(),
+            serde_json::json!({ "field": field }),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(expired.len() as u64)
+}
+
+// Handler: Propose Data Correction
+pub async fn propose_correction(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(payload): Json<ProposeCorrectionRequest>,
+) -> impl IntoResponse {
+    let reason = payload.reason.trim();
+    if reason.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "A reason is required");
+    }
+    let record_id = payload.record_id.trim();
+    if payload.field.keyed_by_id() && Uuid::parse_str(record_id).is_err() {
+        return error_response(StatusCode::BAD_REQUEST, "record_id must be a UUID");
+    }
+    if let Err(message) = payload.field.validate(&payload.value) {
+        return error_response(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let result: Result<Result<DataCorrection, (StatusCode, &'static str)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some((current, _)) = payload.field.read(&mut tx, record_id).await? else {
+            return Ok(Err((StatusCode::NOT_FOUND, "Record not found")));
+        };
+        if current == payload.value {
+            return Ok(Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The correction does not change the value",
+            )));
+        }
+
+        let expires_at =
+            Utc::now() + Duration::hours(i64::from(state.config.pending_change_ttl_hours));
+        let correction = sqlx::query_as::<_, DataCorrection>(&format!(
+            r#"
+            INSERT INTO data_corrections
+                (field, record_id, before_value, after_value, reason_code, reason,
+                 proposed_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {CORRECTION_COLUMNS}
+            "#
+        ))
+        .bind(payload.field.as_str())
+        .bind(record_id)
+        .bind(&current)
+        .bind(&payload.value)
+        .bind(payload.reason_code.as_str())
+        .bind(reason)
+        .bind(&admin.user_id)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "data_correction.proposed",
+            &correction.id.to_string(),
+            serde_json::json!({
+                "field": correction.field,
+                "record_id": correction.record_id,
+                "reason_code": correction.reason_code,
+                "reason": reason,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Ok(correction))
+    }
+    .await;
+
+    match result {
+        Ok(Ok(correction)) => {
+            info!(correction_id = %correction.id, admin = %admin.user_id, "Data correction proposed");
+            (StatusCode::CREATED, Json(correction)).into_response()
+        }
+        Ok(Err((status, message))) => error_response(status, message),
+        Err(e) => {
+            error!(error = %e, "Failed to propose data correction");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Data Corrections
+pub async fn list_corrections(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CorrectionQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = expire_stale(&state.db_pool).await {
+        error!(error = %e, "Failed to expire stale data corrections");
+        return database_error();
+    }
+
+    let status = query.status.unwrap_or_else(|| "pending".to_string());
+    match sqlx::query_as::<_, DataCorrection>(&format!(
+        "SELECT {CORRECTION_COLUMNS} FROM data_corrections WHERE status = $1 ORDER BY proposed_at DESC LIMIT 200"
+    ))
+    .bind(&status)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(corrections) => (StatusCode::OK, Json(corrections)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list data corrections");
+            database_error()
+        }
+    }
+}
+
+enum ReviewOutcome {
+    Reviewed(Box<DataCorrection>),
+    Refused(StatusCode, &'static str),
+}
+
+/// Loads and locks a pending correction, refusing reviews by the proposer
+/// and of corrections that are no longer pending. An expired correction is
+/// marked as such and committed before refusing.
+async fn lock_for_review(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    correction_id: Uuid,
+    reviewer: &str,
+) -> Result<Result<DataCorrection, ReviewOutcome>, sqlx::Error> {
+    let Some(correction) = sqlx::query_as::<_, DataCorrection>(&format!(
+        "SELECT {CORRECTION_COLUMNS} FROM data_corrections WHERE id = $1 FOR UPDATE"
+    ))
+    .bind(correction_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    else {
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::NOT_FOUND,
+            "Correction not found",
+        )));
+    };
+    if correction.status != "pending" {
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::CONFLICT,
+            "Correction has already been reviewed",
+        )));
+    }
+    if correction.expires_at <= Utc::now() {
+        mark_reviewed(tx, correction_id, "expired", SYSTEM_ACTOR, None).await?;
+        record_audit(
+            &mut **tx,
+            SYSTEM_ACTOR,
+            "data_correction.expired",
+            &correction_id.to_string(),
+            serde_json::json!({ "field": correction.field }),
+        )
+        .await?;
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::CONFLICT,
+            "Correction has expired",
+        )));
+    }
+    if correction.proposed_by == reviewer {
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::FORBIDDEN,
+            "Corrections must be approved by a different admin",
+        )));
+    }
+    Ok(Ok(correction))
+}
+
+async fn mark_reviewed(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    correction_id: Uuid,
+    status: &str,
+    reviewer: &str,
+    note: Option<&str>,
+) -> Result<DataCorrection, sqlx::Error> {
+    sqlx::query_as::<_, DataCorrection>(&format!(
+        r#"
+        UPDATE data_corrections
+        SET status = $2, reviewed_by = $3, reviewed_at = NOW(), review_note = $4
+        WHERE id = $1
+        RETURNING {CORRECTION_COLUMNS}
+        "#
+    ))
+    .bind(correction_id)
+    .bind(status)
+    .bind(reviewer)
+    .bind(note)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+fn review_response(result: Result<ReviewOutcome, sqlx::Error>, correction_id: Uuid) -> Response {
+    match result {
+        Ok(ReviewOutcome::Reviewed(correction)) => {
+            (StatusCode::OK, Json(correction)).into_response()
+        }
+        Ok(ReviewOutcome::Refused(status, message)) => error_response(status, message),
+        Err(e) => {
+            error!(correction_id = %correction_id, error = %e, "Failed to review data correction");
+            database_error()
+        }
+    }
+}
+
+fn clean_note(payload: Option<Json<ReviewCorrectionRequest>>) -> Option<String> {
+    payload
+        .and_then(|Json(p)| p.note)
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+}
+
+// Handler: Approve Data Correction
+pub async fn approve_correction(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(correction_id): Path<Uuid>,
+    payload: Option<Json<ReviewCorrectionRequest>>,
+) -> impl IntoResponse {
+    let note = clean_note(payload);
+
+    let result: Result<ReviewOutcome, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let correction = match lock_for_review(&mut tx, correction_id, &admin.user_id).await? {
+            Ok(correction) => correction,
+            Err(refused) => {
+                tx.commit().await?;
+                return Ok(refused);
+            }
+        };
+        let Some(field) = CorrectionField::parse(&correction.field) else {
+            return Ok(ReviewOutcome::Refused(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Unknown field",
+            ));
+        };
+
+        let current = field.read(&mut tx, &correction.record_id).await?;
+        let user_address = match current {
+            Some((value, user_address)) if value == correction.before_value => user_address,
+            current => {
+                mark_reviewed(
+                    &mut tx,
+                    correction_id,
+                    "superseded",
+                    &admin.user_id,
+                    Some("Value changed after this correction was proposed"),
+                )
+                .await?;
+                record_audit(
+                    &mut *tx,
+                    &admin.user_id,
+                    "data_correction.superseded",
+                    &correction_id.to_string(),
+                    serde_json::json!({
+                        "field": correction.field,
+                        "record_id": correction.record_id,
+                        "record_exists": current.is_some(),
+                    }),
+                )
+                .await?;
+                tx.commit().await?;
+                return Ok(ReviewOutcome::Refused(
+                    StatusCode::CONFLICT,
+                    "Value changed after this correction was proposed; propose it again",
+                ));
+            }
+        };
+
+        field
+            .write(&mut tx, &correction.record_id, &correction.after_value)
+            .await?;
+        let applied = mark_reviewed(
+            &mut tx,
+            correction_id,
+            "applied",
+            &admin.user_id,
+            note.as_deref(),
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "data_correction.applied",
+            &correction_id.to_string(),
+            serde_json::json!({
+                "field": applied.field,
+                "record_id": applied.record_id,
+                "proposed_by": applied.proposed_by,
+                "reason_code": applied.reason_code,
+                "before": applied.before_value,
+                "after": applied.after_value,
+                "note": note,
+            }),
+        )
+        .await?;
+        create_notification(
+            &mut *tx,
+            &user_address,
+            "data_correction",
+            "Your details were corrected",
+            &format!(
+                "Our support team corrected your {}. Contact support if this looks wrong.",
+                applied.field.replace('_', " ")
+            ),
+            serde_json::json!({
+                "correction_id": applied.id,
+                "field": applied.field,
+                "reason_code": applied.reason_code,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        info!(correction_id = %correction_id, admin = %admin.user_id, "Data correction applied");
+        Ok(ReviewOutcome::Reviewed(Box::new(applied)))
+    }
+    .await;
+
+    review_response(result, correction_id)
+}
+
+// Handler: Reject Data Correction
+pub async fn reject_correction(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(correction_id): Path<Uuid>,
+    payload: Option<Json<ReviewCorrectionRequest>>,
+) -> impl IntoResponse {
+    let Some(note) = clean_note(payload) else {
+        return error_response(StatusCode::BAD_REQUEST, "A note is required to reject");
+    };
+
+    let result: Result<ReviewOutcome, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if let Err(refused) = lock_for_review(&mut tx, correction_id, &admin.user_id).await? {
+            tx.commit().await?;
+            return Ok(refused);
+        }
+        let rejected = mark_reviewed(
+            &mut tx,
+            correction_id,
+            "rejected",
+            &admin.user_id,
+            Some(&note),
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "data_correction.rejected",
+            &correction_id.to_string(),
+            serde_json::json!({
+                "field": rejected.field,
+                "record_id": rejected.record_id,
+                "proposed_by": rejected.proposed_by,
+                "note": note,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        info!(correction_id = %correction_id, admin = %admin.user_id, "Data correction rejected");
+        Ok(ReviewOutcome::Reviewed(Box::new(rejected)))
+    }
+    .await;
+
+    review_response(result, correction_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn only_whitelisted_values_are_accepted() {
+        assert!(CorrectionField::NotificationEmail
+            .validate(&json!("heir@example.com"))
+            .is_ok());
+        assert!(CorrectionField::NotificationEmail
+            .validate(&Value::Null)
+            .is_ok());
+        assert!(CorrectionField::EmergencyContactEmail
+            .validate(&Value::Null)
+            .is_err());
+        assert!(CorrectionField::EmergencyContactEmail
+            .validate(&json!("not-an-email"))
+            .is_err());
+        assert!(CorrectionField::PlanStatus
+            .validate(&json!("CLAIMABLE"))
+            .is_ok());
+        assert!(CorrectionField::PlanStatus
+            .validate(&json!("PAID_OUT"))
+            .is_err());
+        assert!(CorrectionField::KycStatus.validate(&json!(1)).is_err());
+        assert_eq!(CorrectionField::parse("owner_address"), None);
+    }
+}
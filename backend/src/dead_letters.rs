@@ -0,0 +1,537 @@
+//! Dead letter queue for background jobs that ran out of retries.
+//!
+//! When the payout batcher gives up on a payout, or the digest worker on an
+//! email delivery, it records the job's payload, last error and attempt
+//! history in `dead_letters`. Admins can inspect entries, requeue them (the
+//! job is reset and picked up on the worker's next run) or discard them.
+//! The monitor worker publishes the number of pending entries and alerts
+//! when it crosses `DEAD_LETTER_ALERT_THRESHOLD`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::jobs::JobRegistry;
+use crate::mailer::Mailer;
+use crate::metrics::DEAD_LETTER_DEPTH;
+use crate::notifications::requeue_failed_delivery;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_ALERT_THRESHOLD: i64 = 10;
+
+const DEAD_LETTER_COLUMNS: &str = "id, worker, job_id, payload, error, attempts, attempt_history, \
+     status, resolved_by, resolved_at, created_at";
+
+pub const DEAD_LETTER_STATUSES: [&str; 3] = ["pending", "requeued", "discarded"];
+
+/// Background worker whose jobs can end up in the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Worker {
+    PayoutBatcher,
+    NotificationDigest,
+}
+
+impl Worker {
+    pub const ALL: [Self; 2] = [Self::PayoutBatcher, Self::NotificationDigest];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PayoutBatcher => "payout_batcher",
+            Self::NotificationDigest => "notification_digest",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "payout_batcher" => Some(Self::PayoutBatcher),
+            "notification_digest" => Some(Self::NotificationDigest),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DeadLetter {
+    pub id: Uuid,
+    pub worker: String,
+    /// The payout or notification delivery that failed.
+    pub job_id: Uuid,
+    pub payload: serde_json::Value,
+    pub error: String,
+    pub attempts: i32,
+    /// `{"attempt", "error", "at"}` for every failed attempt.
+    pub attempt_history: serde_json::Value,
+    /// `pending`, `requeued` or `discarded`.
+    pub status: String,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A job a worker is giving up on.
+#[derive(Debug)]
+pub struct NewDeadLetter<'a> {
+    pub worker: Worker,
+    pub job_id: Uuid,
+    pub payload: serde_json::Value,
+    pub error: &'a str,
+    pub attempts: i32,
+    pub attempt_history: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    /// Defaults to `pending`.
+    pub status: Option<String>,
+    pub worker: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveDeadLetterBody {
+    pub reason: Option<String>,
+}
+
+/// Records a job that exhausted its attempts. A job already pending in the
+/// queue has its entry refreshed instead.
+pub async fn record_dead_letter<'e, E>(
+    executor: E,
+    entry: NewDeadLetter<'_>,
+) -> Result<Uuid, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO dead_letters (worker, job_id, payload, error, attempts, attempt_history)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (worker, job_id) WHERE status = 'pending'
+        DO UPDATE SET payload = EXCLUDED.payload,
+                      error = EXCLUDED.error,
+                      attempts = EXCLUDED.attempts,
+                      attempt_history = EXCLUDED.attempt_history
+        RETURNING id
+        "#,
+    )
+    .bind(entry.worker.as_str())
+    .bind(entry.job_id)
+    .bind(entry.payload)
+    .bind(entry.error)
+    .bind(entry.attempts)
+    .bind(entry.attempt_history)
+    .fetch_one(executor)
+    .await
+}
+
+/// Closes the pending entry for a job that was requeued outside the dead
+/// letter API.
+pub(crate) async fn resolve_for_job<'e, E>(
+    executor: E,
+    worker: Worker,
+    job_id: Uuid,
+    actor: &str,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        UPDATE dead_letters
+        SET status = 'requeued', resolved_by = $3, resolved_at = NOW()
+        WHERE worker = $1 AND job_id = $2 AND status = 'pending'
+        "#,
+    )
+    .bind(worker.as_str())
+    .bind(job_id)
+    .bind(actor)
+    .execute(executor)
+    .await
+    .map(|_| ())
+}
+
+/// Whether moving from `previous` to `current` pending entries crosses the
+/// alert threshold.
+pub fn crossed_threshold(previous: i64, current: i64, threshold: i64) -> bool {
+    previous < threshold && current >= threshold
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+fn not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Pending dead letter not found" })),
+    )
+        .into_response()
+}
+
+fn clean_reason(body: Option<Json<ResolveDeadLetterBody>>) -> Option<String> {
+    body.and_then(|Json(b)| b.reason)
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty())
+}
+
+// Handler: Admin List Dead Letters
+pub async fn list_dead_letters(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DeadLetterQuery>,
+) -> impl IntoResponse {
+    let status = query.status.as_deref().unwrap_or("pending");
+    if !DEAD_LETTER_STATUSES.contains(&status) {
+        return bad_request("status must be one of pending, requeued, discarded");
+    }
+    let worker = match query.worker.as_deref().map(Worker::parse) {
+        Some(None) => {
+            return bad_request("worker must be one of payout_batcher, notification_digest")
+        }
+        Some(Some(worker)) => Some(worker.as_str()),
+        None => None,
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    match sqlx::query_as::<_, DeadLetter>(&format!(
+        r#"
+        SELECT {DEAD_LETTER_COLUMNS}
+        FROM dead_letters
+        WHERE status = $1
+          AND ($2::text IS NULL OR worker = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#
+    ))
+    .bind(status)
+    .bind(worker)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (StatusCode::OK, Json(rows)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list dead letters");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Get Dead Letter
+pub async fn get_dead_letter(
+    State(state): State<Arc<AppState>>,
+    Path(dead_letter_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match sqlx::query_as::<_, DeadLetter>(&format!(
+        "SELECT {DEAD_LETTER_COLUMNS} FROM dead_letters WHERE id = $1"
+    ))
+    .bind(dead_letter_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(entry)) => (StatusCode::OK, Json(entry)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Dead letter not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(dead_letter_id = %dead_letter_id, error = %e, "Failed to load dead letter");
+            database_error()
+        }
+    }
+}
+
+enum Resolution {
+    Done(Box<DeadLetter>),
+    NotFound,
+    /// The job left its failed state since it was dead-lettered.
+    JobChanged,
+}
+
+/// Marks a pending entry `requeued` or `discarded`, resetting the job first
+/// when requeueing.
+async fn resolve(
+    state: &AppState,
+    dead_letter_id: Uuid,
+    requeue: bool,
+    actor: &str,
+    reason: Option<String>,
+) -> Result<Resolution, sqlx::Error> {
+    let mut tx = state.db_pool.begin().await?;
+    let Some(entry) = sqlx::query_as::<_, DeadLetter>(&format!(
+        "SELECT {DEAD_LETTER_COLUMNS} FROM dead_letters WHERE id = $1 AND status = 'pending' FOR UPDATE"
+    ))
+    .bind(dead_letter_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(Resolution::NotFound);
+    };
+
+    if requeue {
+        let reset = match Worker::parse(&entry.worker) {
+            Some(Worker::PayoutBatcher) => {
+                sqlx::query(
+                    r#"
+                UPDATE payouts
+                SET status = 'pending', attempts = 0, failure_reason = NULL,
+                    batch_id = NULL, updated_at = NOW()
+                WHERE id = $1 AND status = 'failed'
+                "#,
+                )
+                .bind(entry.job_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                    > 0
+            }
+            Some(Worker::NotificationDigest) => requeue_failed_delivery(&mut tx, entry.job_id)
+                .await?
+                .is_some(),
+            None => false,
+        };
+        if !reset {
+            return Ok(Resolution::JobChanged);
+        }
+    }
+
+    let status = if requeue { "requeued" } else { "discarded" };
+    let entry = sqlx::query_as::<_, DeadLetter>(&format!(
+        r#"
+        UPDATE dead_letters
+        SET status = $2, resolved_by = $3, resolved_at = NOW()
+        WHERE id = $1
+        RETURNING {DEAD_LETTER_COLUMNS}
+        "#
+    ))
+    .bind(dead_letter_id)
+    .bind(status)
+    .bind(actor)
+    .fetch_one(&mut *tx)
+    .await?;
+    record_audit(
+        &mut *tx,
+        actor,
+        if requeue {
+            "dead_letter.requeue"
+        } else {
+            "dead_letter.discard"
+        },
+        &entry.id.to_string(),
+        serde_json::json!({
+            "worker": entry.worker,
+            "job_id": entry.job_id,
+            "reason": reason,
+        }),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(Resolution::Done(Box::new(entry)))
+}
+
+fn resolution_response(
+    result: Result<Resolution, sqlx::Error>,
+    dead_letter_id: Uuid,
+) -> axum::response::Response {
+    match result {
+        Ok(Resolution::Done(entry)) => (StatusCode::OK, Json(entry)).into_response(),
+        Ok(Resolution::NotFound) => not_found(),
+        Ok(Resolution::JobChanged) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "The job is no longer in a failed state" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(dead_letter_id = %dead_letter_id, error = %e, "Failed to resolve dead letter");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Requeue Dead Letter
+pub async fn requeue_dead_letter(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(dead_letter_id): Path<Uuid>,
+    payload: Option<Json<ResolveDeadLetterBody>>,
+) -> impl IntoResponse {
+    let reason = clean_reason(payload);
+    let result = resolve(&state, dead_letter_id, true, &admin.user_id, reason).await;
+    resolution_response(result, dead_letter_id)
+}
+
+// Handler: Admin Discard Dead Letter
+pub async fn discard_dead_letter(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(dead_letter_id): Path<Uuid>,
+    payload: Option<Json<ResolveDeadLetterBody>>,
+) -> impl IntoResponse {
+    let reason = clean_reason(payload);
+    let result = resolve(&state, dead_letter_id, false, &admin.user_id, reason).await;
+    resolution_response(result, dead_letter_id)
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadLetterMonitorConfig {
+    pub interval: Duration,
+    /// Pending entries at which an alert is raised.
+    pub alert_threshold: i64,
+    /// Addresses emailed when the threshold is crossed; alerts are only
+    /// logged when empty.
+    pub alert_recipients: Vec<String>,
+}
+
+impl DeadLetterMonitorConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("DEAD_LETTER_MONITOR_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let alert_threshold = parse_env("DEAD_LETTER_ALERT_THRESHOLD", DEFAULT_ALERT_THRESHOLD);
+        let alert_recipients = std::env::var("DEAD_LETTER_ALERT_EMAILS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            alert_threshold: alert_threshold.max(1),
+            alert_recipients,
+        }
+    }
+}
+
+pub struct DeadLetterMonitorService {
+    db: PgPool,
+    mailer: Arc<Mailer>,
+    config: DeadLetterMonitorConfig,
+    last_depth: AtomicI64,
+}
+
+impl DeadLetterMonitorService {
+    pub fn new(db: PgPool, mailer: Arc<Mailer>, config: DeadLetterMonitorConfig) -> Self {
+        Self {
+            db,
+            mailer,
+            config,
+            last_depth: AtomicI64::new(0),
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("dead_letter_monitor", self.config.interval, move || {
+            let worker = self.clone();
+            async move { worker.run_once().await }
+        });
+    }
+
+    /// Publishes the pending depth per worker and alerts when the total
+    /// crosses the threshold. Returns the total.
+    pub async fn run_once(&self) -> Result<i64, sqlx::Error> {
+        let counts: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT worker, COUNT(*) FROM dead_letters WHERE status = 'pending' GROUP BY worker",
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut depth = 0;
+        for worker in Worker::ALL {
+            let count = counts
+                .iter()
+                .find(|(name, _)| name == worker.as_str())
+                .map_or(0, |(_, count)| *count);
+            DEAD_LETTER_DEPTH
+                .with_label_values(&[worker.as_str()])
+                .set(count as f64);
+            depth += count;
+        }
+
+        let previous = self.last_depth.swap(depth, Ordering::Relaxed);
+        if crossed_threshold(previous, depth, self.config.alert_threshold) {
+            self.alert(depth, &counts).await;
+        } else if previous >= self.config.alert_threshold && depth < self.config.alert_threshold {
+            info!(depth, "Dead letter queue is back under its alert threshold");
+        }
+        Ok(depth)
+    }
+
+    async fn alert(&self, depth: i64, counts: &[(String, i64)]) {
+        error!(
+            depth,
+            threshold = self.config.alert_threshold,
+            "Dead letter queue crossed its alert threshold"
+        );
+
+        let subject = format!("InheritX dead letter queue: {depth} pending jobs");
+        let mut body = format!(
+            "{depth} background jobs have run out of retries (alert threshold {}).\n\n",
+            self.config.alert_threshold
+        );
+        for (worker, count) in counts {
+            body.push_str(&format!("- {worker}: {count}\n"));
+        }
+        body.push_str("\nReview them at GET /api/admin/dead-letters.\n");
+
+        for recipient in &self.config.alert_recipients {
+            if let Err(e) = self.mailer.send(recipient, &subject, &body).await {
+                warn!(recipient = %recipient, error = %e, "Failed to email dead letter alert");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worker_names_round_trip() {
+        for worker in Worker::ALL {
+            assert_eq!(Worker::parse(worker.as_str()), Some(worker));
+        }
+        assert_eq!(Worker::parse("indexer"), None);
+    }
+
+    #[test]
+    fn alerts_only_when_crossing_threshold() {
+        assert!(crossed_threshold(0, 10, 10));
+        assert!(crossed_threshold(9, 25, 10));
+        assert!(!crossed_threshold(9, 9, 10));
+        assert!(!crossed_threshold(10, 12, 10));
+        assert!(!crossed_threshold(12, 3, 10));
+    }
+}
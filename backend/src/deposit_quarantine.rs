@@ -0,0 +1,324 @@
+//! Deposits the watcher could not route to a single plan.
+//!
+//! A deposit without a memo or muxed id, naming no existing plan, or whose
+//! memo and muxed id name different plans is recorded without a plan (held
+//! in `unallocated_deposits` on the ledger) and quarantined. Admins assign
+//! it to the plan it was meant for, which credits the plan and moves it to
+//! the plan on the ledger, dismiss it, or have it refunded to the sender
+//! (see [`crate::deposit_refunds`]). A payment in an asset other than the
+//! deposit asset is quarantined as well but cannot be assigned. Every
+//! resolution is written to `audit_logs`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::deposits::{credit_plan, from_base_units, lock_funding_plan, Credit, QuarantineReason};
+
+const QUARANTINE_COLUMNS: &str = "id, lending_event_id, reason, account, memo, muxed_id, \
+     from_address, asset, amount, transaction_hash, status, plan_id, resolved_by, \
+     resolution_note, resolved_at, created_at";
+
+pub const QUARANTINE_STATUSES: [&str; 5] =
+    ["open", "assigned", "dismissed", "refunding", "refunded"];
+const MAX_NOTE_LEN: usize = 500;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct QuarantinedDeposit {
+    pub id: Uuid,
+    pub lending_event_id: Uuid,
+    /// `missing_reference`, `unknown_reference`, `conflicting_reference` or
+    /// `wrong_asset`.
+    pub reason: String,
+    /// Deposit account the payment was made to.
+    pub account: String,
+    pub memo: Option<String>,
+    pub muxed_id: Option<String>,
+    #[serde(rename = "from")]
+    pub from_address: String,
+    pub asset: String,
+    /// Amount in base units.
+    pub amount: Decimal,
+    pub transaction_hash: String,
+    /// `open`, `assigned`, `dismissed`, `refunding` or `refunded`.
+    pub status: String,
+    /// Plan the deposit was assigned to.
+    pub plan_id: Option<Uuid>,
+    pub resolved_by: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuarantineQuery {
+    /// Defaults to `open`.
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignDepositRequest {
+    pub plan_id: Uuid,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DismissDepositRequest {
+    pub note: Option<String>,
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn clean_note(note: Option<String>) -> Result<Option<String>, &'static str> {
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_NOTE_LEN)
+    {
+        return Err("note must be at most 500 characters");
+    }
+    Ok(note)
+}
+
+fn outcome_response(
+    result: Result<Outcome<QuarantinedDeposit>, sqlx::Error>,
+    quarantine_id: Uuid,
+) -> axum::response::Response {
+    match result {
+        Ok(Outcome::Done(deposit)) => (StatusCode::OK, Json(deposit)).into_response(),
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(quarantine_id = %quarantine_id, error = %e, "Failed to resolve quarantined deposit");
+            database_error()
+        }
+    }
+}
+
+pub(crate) async fn lock_open(
+    conn: &mut sqlx::PgConnection,
+    quarantine_id: Uuid,
+) -> Result<Option<QuarantinedDeposit>, sqlx::Error> {
+    sqlx::query_as::<_, QuarantinedDeposit>(&format!(
+        "SELECT {QUARANTINE_COLUMNS} FROM quarantined_deposits \
+         WHERE id = $1 AND status = 'open' FOR UPDATE"
+    ))
+    .bind(quarantine_id)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+// Handler: Admin List Quarantined Deposits
+pub async fn list_quarantined_deposits(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<QuarantineQuery>,
+) -> impl IntoResponse {
+    let status = query.status.as_deref().unwrap_or("open");
+    if !QUARANTINE_STATUSES.contains(&status) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "status must be one of open, assigned, dismissed, refunding, refunded",
+        );
+    }
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    match sqlx::query_as::<_, QuarantinedDeposit>(&format!(
+        r#"
+        SELECT {QUARANTINE_COLUMNS}
+        FROM quarantined_deposits
+        WHERE status = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#
+    ))
+    .bind(status)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (StatusCode::OK, Json(rows)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list quarantined deposits");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Assign Quarantined Deposit
+pub async fn assign_quarantined_deposit(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(quarantine_id): Path<Uuid>,
+    Json(payload): Json<AssignDepositRequest>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.note) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    let plan_id = payload.plan_id;
+
+    let result: Result<Outcome<QuarantinedDeposit>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(deposit) = lock_open(&mut tx, quarantine_id).await? else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Open quarantined deposit not found",
+            ));
+        };
+        if deposit.reason == QuarantineReason::WrongAsset.as_str() {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "Deposit is not in the deposit asset and can only be refunded",
+            ));
+        }
+        let Some(plan) = lock_funding_plan(&mut tx, plan_id).await? else {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Plan not found"));
+        };
+
+        sqlx::query("UPDATE lending_events SET plan_id = $2 WHERE id = $1")
+            .bind(deposit.lending_event_id)
+            .bind(plan_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            SELECT ledger_post('deposit_assigned', basis, $1, $2, $3,
+                               'unallocated_deposits', 'plan_liability', $4)
+            FROM (VALUES ('accrual'), ('cash')) AS bases (basis)
+            "#,
+        )
+        .bind(deposit.lending_event_id)
+        .bind(plan_id)
+        .bind(&deposit.asset)
+        .bind(deposit.amount)
+        .execute(&mut *tx)
+        .await?;
+        credit_plan(
+            &mut tx,
+            plan_id,
+            &plan,
+            &Credit {
+                lending_event_id: deposit.lending_event_id,
+                amount: deposit.amount,
+                display_amount: &from_base_units(deposit.amount),
+                asset: &deposit.asset,
+                transaction_hash: &deposit.transaction_hash,
+            },
+        )
+        .await?;
+
+        let deposit = sqlx::query_as::<_, QuarantinedDeposit>(&format!(
+            r#"
+            UPDATE quarantined_deposits
+            SET status = 'assigned', plan_id = $2, resolved_by = $3,
+                resolution_note = $4, resolved_at = NOW()
+            WHERE id = $1
+            RETURNING {QUARANTINE_COLUMNS}
+            "#
+        ))
+        .bind(quarantine_id)
+        .bind(plan_id)
+        .bind(&admin.user_id)
+        .bind(&note)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "deposit.quarantine.assign",
+            &quarantine_id.to_string(),
+            serde_json::json!({
+                "lending_event_id": deposit.lending_event_id,
+                "plan_id": plan_id,
+                "reason": deposit.reason,
+                "amount": deposit.amount,
+                "note": note,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(deposit))
+    }
+    .await;
+
+    outcome_response(result, quarantine_id)
+}
+
+// Handler: Admin Dismiss Quarantined Deposit
+pub async fn dismiss_quarantined_deposit(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(quarantine_id): Path<Uuid>,
+    payload: Option<Json<DismissDepositRequest>>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.and_then(|Json(p)| p.note)) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+
+    let result: Result<Outcome<QuarantinedDeposit>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if lock_open(&mut tx, quarantine_id).await?.is_none() {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Open quarantined deposit not found",
+            ));
+        }
+
+        let deposit = sqlx::query_as::<_, QuarantinedDeposit>(&format!(
+            r#"
+            UPDATE quarantined_deposits
+            SET status = 'dismissed', resolved_by = $2, resolution_note = $3,
+                resolved_at = NOW()
+            WHERE id = $1
+            RETURNING {QUARANTINE_COLUMNS}
+            "#
+        ))
+        .bind(quarantine_id)
+        .bind(&admin.user_id)
+        .bind(&note)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "deposit.quarantine.dismiss",
+            &quarantine_id.to_string(),
+            serde_json::json!({
+                "lending_event_id": deposit.lending_event_id,
+                "reason": deposit.reason,
+                "amount": deposit.amount,
+                "note": note,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(deposit))
+    }
+    .await;
+
+    outcome_response(result, quarantine_id)
+}
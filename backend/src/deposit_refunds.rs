@@ -0,0 +1,1057 @@
+//! Refunds of deposits to the address they were sent from.
+//!
+//! Two kinds of deposit can be returned: the part of a plan's deposits above
+//! the plan amount, which the plan owner requests, and a quarantined deposit
+//! (including a payment in the wrong asset), which an admin requests. Every
+//! refund is approved or rejected by an admin before anything is sent.
+//!
+//! Approved refunds are sent by [`DepositRefundService`] through the chain
+//! tx service, one transaction each. The refund is marked `submitting` with
+//! a memo derived from its id before the transfer is submitted, so when the
+//! outcome is lost later sweeps look the memo up through the indexer; a
+//! refund still missing after its validity window can no longer land and is
+//! failed, and can be requested again. A completed refund is posted to the
+//! ledger, lowers an overfunded plan's funded amount and settles its
+//! quarantined deposit. Every step is written to `audit_logs`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::chain::{BatchReceipt, TokenTransfer, TransferOutcome, TxError, TxService, TX_VALIDITY};
+use crate::deposit_quarantine::lock_open;
+use crate::deposits::from_base_units;
+use crate::jobs::JobRegistry;
+use crate::money::Money;
+use crate::notifications::create_notification;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEPOSIT_REFUND_LOCK_KEY: i64 = 842;
+const MAX_REFUNDS_PER_SWEEP: i64 = 25;
+/// Extra time after a transaction's validity window before the indexer's
+/// answer is treated as final.
+const CONFIRMATION_MARGIN: Duration = Duration::from_secs(60);
+/// Age at which a refund still marked `submitting` is assumed to have lost
+/// its submitter and is reconciled.
+const SUBMISSION_GRACE_SECS: f64 = 60.0;
+const MEMO_PREFIX: &str = "ixr-";
+const MAX_NOTE_LEN: usize = 500;
+
+const REFUND_COLUMNS: &str = "id, lending_event_id, reason, plan_id, quarantine_id, destination, \
+     asset, amount, status, requested_by, request_note, reviewed_by, review_note, reviewed_at, \
+     memo, tx_hash, failure_reason, submitted_at, completed_at, created_at, updated_at";
+
+pub const REFUND_STATUSES: [&str; 6] = [
+    "requested",
+    "approved",
+    "rejected",
+    "submitting",
+    "completed",
+    "failed",
+];
+
+/// Text memo identifying a refund's transaction. A UUID does not fit in a
+/// 28-byte text memo, so its bytes are base64url encoded.
+pub fn refund_memo(refund_id: Uuid) -> String {
+    format!(
+        "{MEMO_PREFIX}{}",
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(refund_id.as_bytes())
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DepositRefundConfig {
+    pub interval: Duration,
+    /// How long after submission a refund's transaction may still turn up.
+    pub confirmation_window: Duration,
+}
+
+impl DepositRefundConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("DEPOSIT_REFUND_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let minimum_window = TX_VALIDITY + CONFIRMATION_MARGIN;
+        let window_secs = parse_env(
+            "DEPOSIT_REFUND_CONFIRMATION_WINDOW_SECS",
+            minimum_window.as_secs(),
+        );
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            confirmation_window: Duration::from_secs(window_secs).max(minimum_window),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DepositRefund {
+    pub id: Uuid,
+    /// The deposit being returned.
+    pub lending_event_id: Uuid,
+    /// `overpayment` or `quarantined`.
+    pub reason: String,
+    pub plan_id: Option<Uuid>,
+    pub quarantine_id: Option<Uuid>,
+    /// Address the deposit was sent from.
+    pub destination: String,
+    pub asset: String,
+    /// Amount in base units. Responses carry it as [`Money`] through
+    /// [`DepositRefundView`].
+    #[serde(skip_serializing)]
+    pub amount: Decimal,
+    pub status: String,
+    pub requested_by: String,
+    pub request_note: Option<String>,
+    pub reviewed_by: Option<String>,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub memo: Option<String>,
+    pub tx_hash: Option<String>,
+    pub failure_reason: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A refund as returned to clients, with its amount tagged by asset.
+#[derive(Debug, Serialize)]
+pub struct DepositRefundView {
+    #[serde(flatten)]
+    pub refund: DepositRefund,
+    pub amount: Money,
+}
+
+impl From<DepositRefund> for DepositRefundView {
+    fn from(refund: DepositRefund) -> Self {
+        Self {
+            amount: Money::new(refund.amount, &refund.asset),
+            refund,
+        }
+    }
+}
+
+impl DepositRefund {
+    /// Ledger account the refunded amount was owed from.
+    fn owed_account(&self) -> &'static str {
+        match self.reason.as_str() {
+            "overpayment" => "plan_liability",
+            _ => "unallocated_deposits",
+        }
+    }
+}
+
+/// Part of a plan's funded amount above the plan amount that is not already
+/// being refunded.
+pub async fn refundable_amount<'e, E>(executor: E, plan_id: Uuid) -> Result<Decimal, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let amount: Option<Decimal> = sqlx::query_scalar(
+        r#"
+        SELECT GREATEST(p.funded_amount - p.amount - COALESCE((
+            SELECT SUM(r.amount)
+            FROM deposit_refunds r
+            WHERE r.plan_id = p.id AND r.reason = 'overpayment'
+              AND r.status IN ('requested', 'approved', 'submitting')
+        ), 0), 0)
+        FROM plans p
+        WHERE p.id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_optional(executor)
+    .await?;
+    Ok(amount.unwrap_or_default())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefundQuery {
+    /// Defaults to `requested`.
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RequestRefundRequest {
+    /// Deposit to return the excess to the sender of; defaults to the plan's
+    /// latest deposit.
+    pub lending_event_id: Option<Uuid>,
+    /// Amount in base units; defaults to all that is refundable.
+    pub amount: Option<Decimal>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RefundNoteRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RefundedDeposit {
+    id: Uuid,
+    user_address: String,
+    asset: String,
+    amount: Decimal,
+    /// Part of the deposit already refunded or being refunded.
+    refunded: Decimal,
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn clean_note(note: Option<String>) -> Result<Option<String>, &'static str> {
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_NOTE_LEN)
+    {
+        return Err("note must be at most 500 characters");
+    }
+    Ok(note)
+}
+
+fn outcome_response(
+    result: Result<Outcome<DepositRefund>, sqlx::Error>,
+    success: StatusCode,
+    subject: Uuid,
+) -> axum::response::Response {
+    match result {
+        Ok(Outcome::Done(refund)) => {
+            (success, Json(DepositRefundView::from(refund))).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(subject = %subject, error = %e, "Failed to update deposit refund");
+            database_error()
+        }
+    }
+}
+
+struct NewRefund<'a> {
+    lending_event_id: Uuid,
+    reason: &'static str,
+    plan_id: Option<Uuid>,
+    quarantine_id: Option<Uuid>,
+    destination: &'a str,
+    asset: &'a str,
+    amount: Decimal,
+    requested_by: &'a str,
+    request_note: Option<&'a str>,
+}
+
+async fn insert_refund(
+    conn: &mut PgConnection,
+    refund: NewRefund<'_>,
+) -> Result<DepositRefund, sqlx::Error> {
+    sqlx::query_as::<_, DepositRefund>(&format!(
+        r#"
+        INSERT INTO deposit_refunds
+            (lending_event_id, reason, plan_id, quarantine_id, destination, asset, amount,
+             requested_by, request_note)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING {REFUND_COLUMNS}
+        "#
+    ))
+    .bind(refund.lending_event_id)
+    .bind(refund.reason)
+    .bind(refund.plan_id)
+    .bind(refund.quarantine_id)
+    .bind(refund.destination)
+    .bind(refund.asset)
+    .bind(refund.amount)
+    .bind(refund.requested_by)
+    .bind(refund.request_note)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+// Handler: Request Plan Overpayment Refund
+pub async fn request_overpayment_refund(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    payload: Option<Json<RequestRefundRequest>>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let note = match clean_note(payload.note) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    if payload
+        .amount
+        .is_some_and(|a| a <= Decimal::ZERO || !a.fract().is_zero())
+    {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "amount must be a positive whole number of base units",
+        );
+    }
+
+    let result: Result<Outcome<DepositRefund>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let owned: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM plans WHERE id = $1 AND owner_address = $2 FOR UPDATE",
+        )
+        .bind(plan_id)
+        .bind(&owner)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if owned.is_none() {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Plan not found"));
+        }
+
+        let refundable = refundable_amount(&mut *tx, plan_id).await?;
+        if refundable.is_zero() {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "Plan has no overpayment to refund",
+            ));
+        }
+        let Some(deposit) = sqlx::query_as::<_, RefundedDeposit>(
+            r#"
+            SELECT e.id, e.user_address, e.asset, e.amount,
+                   COALESCE((
+                       SELECT SUM(r.amount) FROM deposit_refunds r
+                       WHERE r.lending_event_id = e.id
+                         AND r.status NOT IN ('rejected', 'failed')
+                   ), 0) AS refunded
+            FROM lending_events e
+            WHERE e.plan_id = $1 AND e.event_type = 'deposit'
+              AND ($2::uuid IS NULL OR e.id = $2)
+            ORDER BY e.created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(plan_id)
+        .bind(payload.lending_event_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Deposit not found"));
+        };
+
+        let available = refundable.min(deposit.amount - deposit.refunded);
+        let amount = payload.amount.unwrap_or(available);
+        if amount <= Decimal::ZERO || amount > available {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "amount exceeds the refundable overpayment of this deposit",
+            ));
+        }
+
+        let refund = insert_refund(
+            &mut tx,
+            NewRefund {
+                lending_event_id: deposit.id,
+                reason: "overpayment",
+                plan_id: Some(plan_id),
+                quarantine_id: None,
+                destination: &deposit.user_address,
+                asset: &deposit.asset,
+                amount,
+                requested_by: &owner,
+                request_note: note.as_deref(),
+            },
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &owner,
+            "deposit.refund.request",
+            &refund.id.to_string(),
+            serde_json::json!({
+                "reason": refund.reason,
+                "plan_id": plan_id,
+                "lending_event_id": deposit.id,
+                "destination": refund.destination,
+                "amount": amount,
+                "note": note,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        info!(refund_id = %refund.id, plan_id = %plan_id, amount = %amount, "Overpayment refund requested");
+        Ok(Outcome::Done(refund))
+    }
+    .await;
+
+    outcome_response(result, StatusCode::CREATED, plan_id)
+}
+
+// Handler: List Plan Refunds
+pub async fn list_plan_refunds(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<Vec<DepositRefund>>, sqlx::Error> = async {
+        let owned: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM plans WHERE id = $1 AND owner_address = $2)",
+        )
+        .bind(plan_id)
+        .bind(&owner)
+        .fetch_one(&state.db_pool)
+        .await?;
+        if !owned {
+            return Ok(None);
+        }
+        sqlx::query_as::<_, DepositRefund>(&format!(
+            "SELECT {REFUND_COLUMNS} FROM deposit_refunds WHERE plan_id = $1 ORDER BY created_at DESC"
+        ))
+        .bind(plan_id)
+        .fetch_all(&state.db_pool)
+        .await
+        .map(Some)
+    }
+    .await;
+
+    match result {
+        Ok(Some(refunds)) => (
+            StatusCode::OK,
+            Json(
+                refunds
+                    .into_iter()
+                    .map(DepositRefundView::from)
+                    .collect::<Vec<_>>(),
+            ),
+        )
+            .into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to list plan refunds");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Refund Quarantined Deposit
+pub async fn refund_quarantined_deposit(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(quarantine_id): Path<Uuid>,
+    payload: Option<Json<RefundNoteRequest>>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.and_then(|Json(p)| p.note)) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+
+    let result: Result<Outcome<DepositRefund>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(deposit) = lock_open(&mut tx, quarantine_id).await? else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Open quarantined deposit not found",
+            ));
+        };
+
+        let refund = insert_refund(
+            &mut tx,
+            NewRefund {
+                lending_event_id: deposit.lending_event_id,
+                reason: "quarantined",
+                plan_id: None,
+                quarantine_id: Some(quarantine_id),
+                destination: &deposit.from_address,
+                asset: &deposit.asset,
+                amount: deposit.amount,
+                requested_by: &admin.user_id,
+                request_note: note.as_deref(),
+            },
+        )
+        .await?;
+        sqlx::query("UPDATE quarantined_deposits SET status = 'refunding' WHERE id = $1")
+            .bind(quarantine_id)
+            .execute(&mut *tx)
+            .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "deposit.refund.request",
+            &refund.id.to_string(),
+            serde_json::json!({
+                "reason": refund.reason,
+                "quarantine_id": quarantine_id,
+                "lending_event_id": deposit.lending_event_id,
+                "destination": refund.destination,
+                "amount": refund.amount,
+                "note": note,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(refund))
+    }
+    .await;
+
+    outcome_response(result, StatusCode::CREATED, quarantine_id)
+}
+
+// Handler: Admin List Deposit Refunds
+pub async fn list_deposit_refunds(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RefundQuery>,
+) -> impl IntoResponse {
+    let status = query.status.as_deref().unwrap_or("requested");
+    if !REFUND_STATUSES.contains(&status) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "status must be one of requested, approved, rejected, submitting, completed, failed",
+        );
+    }
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    match sqlx::query_as::<_, DepositRefund>(&format!(
+        r#"
+        SELECT {REFUND_COLUMNS}
+        FROM deposit_refunds
+        WHERE status = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#
+    ))
+    .bind(status)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(
+                rows.into_iter()
+                    .map(DepositRefundView::from)
+                    .collect::<Vec<_>>(),
+            ),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list deposit refunds");
+            database_error()
+        }
+    }
+}
+
+/// Approves or rejects a requested refund.
+async fn review_refund(
+    state: &AppState,
+    admin: &UserContext,
+    refund_id: Uuid,
+    approve: bool,
+    note: Option<String>,
+) -> Result<Outcome<DepositRefund>, sqlx::Error> {
+    let mut tx = state.db_pool.begin().await?;
+    let status = if approve { "approved" } else { "rejected" };
+    let Some(refund) = sqlx::query_as::<_, DepositRefund>(&format!(
+        r#"
+        UPDATE deposit_refunds
+        SET status = $2, reviewed_by = $3, review_note = $4, reviewed_at = NOW(),
+            updated_at = NOW()
+        WHERE id = $1 AND status = 'requested'
+        RETURNING {REFUND_COLUMNS}
+        "#
+    ))
+    .bind(refund_id)
+    .bind(status)
+    .bind(&admin.user_id)
+    .bind(&note)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(Outcome::Refused(
+            StatusCode::NOT_FOUND,
+            "Requested refund not found",
+        ));
+    };
+
+    if !approve {
+        reopen_quarantine(&mut tx, &refund).await?;
+        if let Some(plan_id) = refund.plan_id.filter(|_| refund.reason == "overpayment") {
+            create_notification(
+                &mut *tx,
+                &refund.requested_by,
+                "deposit_refund_rejected",
+                "Refund rejected",
+                &format!(
+                    "Your request to refund {} {} was rejected.",
+                    from_base_units(refund.amount),
+                    asset_code(&refund.asset)
+                ),
+                serde_json::json!({
+                    "plan_id": plan_id,
+                    "refund_id": refund.id,
+                    "note": note,
+                }),
+            )
+            .await?;
+        }
+    }
+    record_audit(
+        &mut *tx,
+        &admin.user_id,
+        if approve {
+            "deposit.refund.approve"
+        } else {
+            "deposit.refund.reject"
+        },
+        &refund_id.to_string(),
+        serde_json::json!({
+            "reason": refund.reason,
+            "destination": refund.destination,
+            "amount": refund.amount,
+            "note": note,
+        }),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(Outcome::Done(refund))
+}
+
+// Handler: Admin Approve Deposit Refund
+pub async fn approve_deposit_refund(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(refund_id): Path<Uuid>,
+    payload: Option<Json<RefundNoteRequest>>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.and_then(|Json(p)| p.note)) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    let result = review_refund(&state, &admin, refund_id, true, note).await;
+    outcome_response(result, StatusCode::OK, refund_id)
+}
+
+// Handler: Admin Reject Deposit Refund
+pub async fn reject_deposit_refund(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(refund_id): Path<Uuid>,
+    payload: Option<Json<RefundNoteRequest>>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.and_then(|Json(p)| p.note)) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    let result = review_refund(&state, &admin, refund_id, false, note).await;
+    outcome_response(result, StatusCode::OK, refund_id)
+}
+
+/// Returns a quarantined deposit whose refund did not go ahead to the open
+/// queue.
+async fn reopen_quarantine(
+    conn: &mut PgConnection,
+    refund: &DepositRefund,
+) -> Result<(), sqlx::Error> {
+    if let Some(quarantine_id) = refund.quarantine_id {
+        sqlx::query(
+            "UPDATE quarantined_deposits SET status = 'open' WHERE id = $1 AND status = 'refunding'",
+        )
+        .bind(quarantine_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// How a submitted refund ended.
+enum Settlement {
+    Completed {
+        tx_hash: String,
+    },
+    Failed {
+        reason: String,
+        tx_hash: Option<String>,
+    },
+}
+
+impl Settlement {
+    fn from_receipt(receipt: BatchReceipt) -> Self {
+        match receipt.outcomes.into_iter().next() {
+            Some(TransferOutcome::Succeeded) => Self::Completed {
+                tx_hash: receipt.tx_hash,
+            },
+            Some(TransferOutcome::Failed(reason)) => Self::Failed {
+                reason,
+                tx_hash: Some(receipt.tx_hash),
+            },
+            None => Self::Failed {
+                reason: "transaction carried no transfer".to_string(),
+                tx_hash: Some(receipt.tx_hash),
+            },
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OpenRefund {
+    #[sqlx(flatten)]
+    refund: DepositRefund,
+    window_passed: bool,
+}
+
+/// Sends approved refunds and settles them.
+pub struct DepositRefundService {
+    db: PgPool,
+    tx_service: Arc<dyn TxService>,
+    config: DepositRefundConfig,
+}
+
+impl DepositRefundService {
+    pub fn new(db: PgPool, tx_service: Arc<dyn TxService>, config: DepositRefundConfig) -> Self {
+        Self {
+            db,
+            tx_service,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("deposit_refunds", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
+                        info!("Deposit refund worker settled {count} refund(s)");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Settles refunds whose submission outcome was lost, then sends the
+    /// approved ones. Returns the number of refunds completed or failed.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(DEPOSIT_REFUND_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Deposit refund lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let mut settled = self.reconcile(&mut tx).await?;
+
+        let claimed = sqlx::query_as::<_, DepositRefund>(&format!(
+            r#"
+            SELECT {REFUND_COLUMNS}
+            FROM deposit_refunds
+            WHERE status = 'approved'
+            ORDER BY reviewed_at ASC
+            LIMIT $1
+            FOR UPDATE
+            "#
+        ))
+        .bind(MAX_REFUNDS_PER_SWEEP)
+        .fetch_all(&mut *tx)
+        .await?;
+        for refund in &claimed {
+            sqlx::query(
+                r#"
+                UPDATE deposit_refunds
+                SET status = 'submitting', memo = $2, submitted_at = NOW(), updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(refund.id)
+            .bind(refund_memo(refund.id))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        for refund in &claimed {
+            let transfer = TokenTransfer {
+                reference: refund.id,
+                token: refund.asset.clone(),
+                destination: refund.destination.clone(),
+                amount: refund.amount,
+                memo: Some(refund_memo(refund.id)),
+                correlation_id: None,
+                conversion: None,
+            };
+            let settlement = match self
+                .tx_service
+                .submit_transfers(std::slice::from_ref(&transfer))
+                .await
+            {
+                Ok(receipt) => Settlement::from_receipt(receipt),
+                Err(TxError::Unavailable(e)) => {
+                    warn!(refund_id = %refund.id, error = %e, "Refund submission outcome unknown; will reconcile");
+                    continue;
+                }
+                Err(e) => Settlement::Failed {
+                    reason: e.to_string(),
+                    tx_hash: None,
+                },
+            };
+
+            let mut tx = self.db.begin().await?;
+            if settle(&mut tx, refund, settlement).await? {
+                settled += 1;
+            }
+            tx.commit().await?;
+        }
+
+        Ok(settled)
+    }
+
+    /// Looks up refunds left `submitting` through the indexer.
+    async fn reconcile(&self, conn: &mut PgConnection) -> Result<usize, sqlx::Error> {
+        let open = sqlx::query_as::<_, OpenRefund>(&format!(
+            r#"
+            SELECT {REFUND_COLUMNS},
+                   submitted_at <= NOW() - ($1 * INTERVAL '1 second') AS window_passed
+            FROM deposit_refunds
+            WHERE status = 'submitting'
+              AND submitted_at <= NOW() - ($2 * INTERVAL '1 second')
+            ORDER BY submitted_at ASC
+            LIMIT $3
+            "#
+        ))
+        .bind(self.config.confirmation_window.as_secs() as f64)
+        .bind(SUBMISSION_GRACE_SECS)
+        .bind(MAX_REFUNDS_PER_SWEEP)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut settled = 0;
+        for OpenRefund {
+            refund,
+            window_passed,
+        } in &open
+        {
+            let memo = refund
+                .memo
+                .clone()
+                .unwrap_or_else(|| refund_memo(refund.id));
+            let settlement = match self.tx_service.find_transfers_by_memo(&memo).await {
+                Ok(Some(receipt)) => Settlement::from_receipt(receipt),
+                Ok(None) if *window_passed => Settlement::Failed {
+                    reason: "transaction did not land within its validity window".to_string(),
+                    tx_hash: None,
+                },
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!(refund_id = %refund.id, error = %e, "Failed to look up refund on-chain");
+                    continue;
+                }
+            };
+            if settle(&mut *conn, refund, settlement).await? {
+                settled += 1;
+            }
+        }
+        Ok(settled)
+    }
+}
+
+/// Records how a submitted refund ended. A completed refund leaves custody
+/// on the ledger, lowers an overfunded plan's funded amount and settles its
+/// quarantined deposit; a failed one returns its quarantined deposit to the
+/// open queue.
+async fn settle(
+    conn: &mut PgConnection,
+    refund: &DepositRefund,
+    settlement: Settlement,
+) -> Result<bool, sqlx::Error> {
+    let (status, tx_hash, failure_reason) = match &settlement {
+        Settlement::Completed { tx_hash } => ("completed", Some(tx_hash), None),
+        Settlement::Failed { reason, tx_hash } => ("failed", tx_hash.as_ref(), Some(reason)),
+    };
+    let updated = sqlx::query(
+        r#"
+        UPDATE deposit_refunds
+        SET status = $2, tx_hash = $3, failure_reason = $4, updated_at = NOW(),
+            completed_at = CASE WHEN $2 = 'completed' THEN NOW() END
+        WHERE id = $1 AND status = 'submitting'
+        "#,
+    )
+    .bind(refund.id)
+    .bind(status)
+    .bind(tx_hash)
+    .bind(failure_reason)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Ok(false);
+    }
+
+    match &settlement {
+        Settlement::Completed { tx_hash } => {
+            sqlx::query(
+                r#"
+                SELECT ledger_post('deposit_refunded', basis, $1, $2, $3, $4, 'custody', $5)
+                FROM (VALUES ('accrual'), ('cash')) AS bases (basis)
+                "#,
+            )
+            .bind(refund.id)
+            .bind(refund.plan_id)
+            .bind(&refund.asset)
+            .bind(refund.owed_account())
+            .bind(refund.amount)
+            .execute(&mut *conn)
+            .await?;
+
+            if let Some(quarantine_id) = refund.quarantine_id {
+                sqlx::query(
+                    r#"
+                    UPDATE quarantined_deposits
+                    SET status = 'refunded', resolved_by = $2, resolution_note = $3,
+                        resolved_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(quarantine_id)
+                .bind(&refund.reviewed_by)
+                .bind(&refund.request_note)
+                .execute(&mut *conn)
+                .await?;
+            }
+            if let (Some(plan_id), "overpayment") = (refund.plan_id, refund.reason.as_str()) {
+                let owner: Option<String> = sqlx::query_scalar(
+                    r#"
+                    UPDATE plans SET funded_amount = funded_amount - $2
+                    WHERE id = $1
+                    RETURNING owner_address
+                    "#,
+                )
+                .bind(plan_id)
+                .bind(refund.amount)
+                .fetch_optional(&mut *conn)
+                .await?;
+                if let Some(owner) = owner {
+                    create_notification(
+                        &mut *conn,
+                        &owner,
+                        "deposit_refunded",
+                        "Refund sent",
+                        &format!(
+                            "We returned {} {} to {}.",
+                            from_base_units(refund.amount),
+                            asset_code(&refund.asset),
+                            refund.destination
+                        ),
+                        serde_json::json!({
+                            "plan_id": plan_id,
+                            "refund_id": refund.id,
+                            "transaction_hash": tx_hash,
+                        }),
+                    )
+                    .await?;
+                }
+            }
+            info!(refund_id = %refund.id, tx_hash = %tx_hash, "Deposit refund completed");
+        }
+        Settlement::Failed { reason, .. } => {
+            reopen_quarantine(&mut *conn, refund).await?;
+            error!(refund_id = %refund.id, reason = %reason, "Deposit refund failed");
+        }
+    }
+
+    record_audit(
+        &mut *conn,
+        SYSTEM_ACTOR,
+        if status == "completed" {
+            "deposit.refund.complete"
+        } else {
+            "deposit.refund.fail"
+        },
+        &refund.id.to_string(),
+        serde_json::json!({
+            "reason": refund.reason,
+            "destination": refund.destination,
+            "asset": refund.asset,
+            "amount": refund.amount,
+            "tx_hash": tx_hash,
+            "failure_reason": failure_reason,
+        }),
+    )
+    .await?;
+    Ok(true)
+}
+
+fn asset_code(asset: &str) -> &str {
+    match asset.split_once(':') {
+        Some((code, _)) => code,
+        None => "XLM",
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refund_memo_fits_stellar_text_memo() {
+        let memo = refund_memo(Uuid::new_v4());
+        assert!(memo.len() <= 28);
+        assert!(memo.starts_with(MEMO_PREFIX));
+        assert_ne!(memo, refund_memo(Uuid::new_v4()));
+    }
+
+    #[test]
+    fn settlement_follows_the_transfer_outcome() {
+        let receipt = |outcomes| BatchReceipt {
+            tx_hash: "abc".to_string(),
+            outcomes,
+        };
+        assert!(matches!(
+            Settlement::from_receipt(receipt(vec![TransferOutcome::Succeeded])),
+            Settlement::Completed { tx_hash } if tx_hash == "abc"
+        ));
+        assert!(matches!(
+            Settlement::from_receipt(receipt(vec![TransferOutcome::Failed("no trustline".into())])),
+            Settlement::Failed { reason, tx_hash: Some(_) } if reason == "no trustline"
+        ));
+        assert!(matches!(
+            Settlement::from_receipt(receipt(Vec::new())),
+            Settlement::Failed { .. }
+        ));
+    }
+}
@@ -0,0 +1,1186 @@
+//! Detects plan deposits on the platform's deposit accounts.
+//!
+//! Owners fund a plan by paying the deposit asset to a deposit account with
+//! the plan's deposit memo, or to the plan's muxed deposit address (the
+//! account combined with a per-plan id) without one. The watcher follows
+//! each account's Horizon payments feed from a saved paging cursor, records
+//! every incoming deposit in `lending_events`, credits it to the plan its
+//! memo or muxed id names and marks the plan funded once its deposits cover
+//! the plan amount. Deposits naming no plan, an unknown one or two
+//! different ones, and payments in another asset, are quarantined for an
+//! admin to resolve. Deposits above the plan amount are flagged to the owner,
+//! who can have the excess refunded (see [`crate::deposit_refunds`]).
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::http_client::{HttpClient, HttpError, HttpPolicy};
+use crate::jobs::JobRegistry;
+use crate::notifications::create_notification;
+
+const DEFAULT_INTERVAL_SECS: u64 = 15;
+/// Horizon serves at most 200 records per page.
+const MAX_PAGE_SIZE: u32 = 200;
+/// Pages read per account in one sweep, so a backlog cannot hold the lock
+/// indefinitely.
+const MAX_PAGES_PER_SWEEP: usize = 10;
+const DEPOSIT_WATCHER_LOCK_KEY: i64 = 829;
+/// Classic Stellar amounts always have seven decimal places.
+/// Decimal places of classic Stellar amounts.
+pub const STELLAR_DECIMALS: u32 = 7;
+const MEMO_PREFIX: &str = "ixp-";
+
+const PAYMENT_TYPES: &[&str] = &[
+    "payment",
+    "path_payment_strict_receive",
+    "path_payment_strict_send",
+];
+
+/// Text memo that routes a deposit to `plan_id`. A UUID does not fit in a
+/// 28-byte text memo, so its bytes are base64url encoded (26 bytes in all).
+pub fn deposit_memo(plan_id: Uuid) -> String {
+    format!(
+        "{MEMO_PREFIX}{}",
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(plan_id.as_bytes())
+    )
+}
+
+/// Plan id carried by a deposit memo, if it is one.
+pub fn plan_id_from_memo(memo: &str) -> Option<Uuid> {
+    let encoded = memo.trim().strip_prefix(MEMO_PREFIX)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded)
+        .ok()?;
+    Uuid::from_slice(&bytes).ok()
+}
+
+/// Formats base units as a decimal amount in asset units.
+pub fn from_base_units(amount: Decimal) -> String {
+    (amount / Decimal::from(10u64.pow(STELLAR_DECIMALS)))
+        .normalize()
+        .to_string()
+}
+
+/// Converts a Horizon decimal amount to base units.
+pub fn to_base_units(amount: &str) -> Option<Decimal> {
+    let units = Decimal::from_str(amount).ok()? * Decimal::from(10u64.pow(STELLAR_DECIMALS));
+    (units > Decimal::ZERO && units.fract().is_zero()).then(|| units.normalize())
+}
+
+#[derive(Debug, Clone)]
+pub struct DepositWatcherConfig {
+    pub interval: Duration,
+    pub page_size: u32,
+}
+
+impl DepositWatcherConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("DEPOSIT_WATCHER_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let page_size = parse_env("DEPOSIT_WATCHER_PAGE_SIZE", MAX_PAGE_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            page_size: page_size.clamp(1, MAX_PAGE_SIZE),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HorizonError {
+    #[error("Horizon request failed: {0}")]
+    Http(#[from] HttpError),
+    #[error("Horizon returned {status}: {body}")]
+    Horizon { status: u16, body: String },
+}
+
+/// Subset of a Horizon payment operation record, requested with
+/// `join=transactions` so the memo is included.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HorizonPayment {
+    pub id: String,
+    pub paging_token: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub transaction_successful: bool,
+    pub transaction_hash: String,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    /// Set when the payment was sent to a muxed (`M...`) address.
+    #[serde(default)]
+    pub to_muxed_id: Option<String>,
+    #[serde(default)]
+    pub amount: Option<String>,
+    #[serde(default)]
+    pub asset_type: Option<String>,
+    #[serde(default)]
+    pub asset_code: Option<String>,
+    #[serde(default)]
+    pub asset_issuer: Option<String>,
+    #[serde(default)]
+    pub transaction: Option<HorizonTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HorizonTransaction {
+    #[serde(default)]
+    pub memo_type: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+impl HorizonPayment {
+    /// `native` or `CODE:ISSUER`.
+    fn asset(&self) -> Option<String> {
+        match self.asset_type.as_deref()? {
+            "native" => Some("native".to_string()),
+            _ => Some(format!(
+                "{}:{}",
+                self.asset_code.as_deref()?,
+                self.asset_issuer.as_deref()?
+            )),
+        }
+    }
+
+    fn text_memo(&self) -> Option<&str> {
+        let transaction = self.transaction.as_ref()?;
+        match transaction.memo_type.as_deref() {
+            Some("text") => transaction.memo.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Subset of a Horizon account record.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HorizonAccount {
+    pub thresholds: HorizonThresholds,
+    pub signers: Vec<HorizonSigner>,
+    #[serde(default)]
+    pub balances: Vec<HorizonBalance>,
+}
+
+/// A native balance or a trustline, amounts in asset units.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HorizonBalance {
+    pub asset_type: String,
+    #[serde(default)]
+    pub asset_code: Option<String>,
+    #[serde(default)]
+    pub asset_issuer: Option<String>,
+    pub balance: String,
+    #[serde(default)]
+    pub limit: Option<String>,
+    #[serde(default)]
+    pub is_authorized: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HorizonThresholds {
+    pub low_threshold: u8,
+    pub med_threshold: u8,
+    pub high_threshold: u8,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HorizonSigner {
+    pub key: String,
+    pub weight: u8,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Deserialize)]
+struct PaymentsPage {
+    #[serde(rename = "_embedded")]
+    embedded: PaymentRecords,
+}
+
+#[derive(Deserialize)]
+struct PaymentRecords {
+    records: Vec<HorizonPayment>,
+}
+
+/// An incoming payment on a deposit account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deposit {
+    pub payment_id: String,
+    pub transaction_hash: String,
+    pub from: String,
+    pub asset: String,
+    /// Amount in base units.
+    pub amount: Decimal,
+    /// Amount as Horizon reported it, in asset units.
+    pub display_amount: String,
+    pub memo: Option<String>,
+    /// Id of the muxed address the payment was sent to.
+    pub muxed_id: Option<u64>,
+}
+
+/// The deposit a payment record represents for `account`, or `None` for
+/// outgoing, failed and non-payment operations.
+pub fn deposit_from_payment(payment: &HorizonPayment, account: &str) -> Option<Deposit> {
+    if !PAYMENT_TYPES.contains(&payment.kind.as_str()) || !payment.transaction_successful {
+        return None;
+    }
+    let from = payment.from.clone()?;
+    if payment.to.as_deref() != Some(account) || from == account {
+        return None;
+    }
+    let display_amount = payment.amount.clone()?;
+
+    Some(Deposit {
+        payment_id: payment.id.clone(),
+        transaction_hash: payment.transaction_hash.clone(),
+        from,
+        asset: payment.asset()?,
+        amount: to_base_units(&display_amount)?,
+        display_amount,
+        memo: payment.text_memo().map(str::to_string),
+        muxed_id: payment
+            .to_muxed_id
+            .as_deref()
+            .and_then(|id| id.parse().ok()),
+    })
+}
+
+/// Muxed address of `account` carrying `muxed_id`, if `account` is a valid
+/// `G...` address.
+pub fn muxed_deposit_address(account: &str, muxed_id: i64) -> Option<String> {
+    let key = stellar_strkey::ed25519::PublicKey::from_string(account).ok()?;
+    Some(
+        stellar_strkey::ed25519::MuxedAccount {
+            ed25519: key.0,
+            id: u64::try_from(muxed_id).ok()?,
+        }
+        .to_string(),
+    )
+}
+
+/// What a deposit's memo or muxed id names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositReference {
+    /// The payment carried no memo, or was not sent to a muxed address.
+    Absent,
+    /// A memo or muxed id that names no existing plan.
+    Unknown,
+    Plan(Uuid),
+}
+
+/// Why a deposit was quarantined instead of credited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuarantineReason {
+    MissingReference,
+    UnknownReference,
+    ConflictingReference,
+    /// Paid in an asset other than the deposit asset.
+    WrongAsset,
+}
+
+impl QuarantineReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MissingReference => "missing_reference",
+            Self::UnknownReference => "unknown_reference",
+            Self::ConflictingReference => "conflicting_reference",
+            Self::WrongAsset => "wrong_asset",
+        }
+    }
+}
+
+/// The plan a deposit is credited to. A muxed address is specific to one
+/// plan, so it wins over a memo unless the memo names a different plan; a
+/// memo that names no plan is then taken to be the sender's own note.
+pub fn route_deposit(
+    memo: DepositReference,
+    muxed: DepositReference,
+) -> Result<Uuid, QuarantineReason> {
+    use DepositReference::*;
+    match (memo, muxed) {
+        (Plan(a), Plan(b)) if a != b => Err(QuarantineReason::ConflictingReference),
+        (_, Plan(plan_id)) => Ok(plan_id),
+        (_, Unknown) | (Unknown, Absent) => Err(QuarantineReason::UnknownReference),
+        (Plan(plan_id), Absent) => Ok(plan_id),
+        (Absent, Absent) => Err(QuarantineReason::MissingReference),
+    }
+}
+
+pub struct HorizonClient {
+    http: HttpClient,
+    base_url: String,
+}
+
+impl HorizonClient {
+    pub fn new(base_url: String) -> Self {
+        let http = HttpClient::new(
+            "horizon",
+            HttpPolicy {
+                timeout: Duration::from_secs(15),
+                ..HttpPolicy::default()
+            },
+        );
+        Self { http, base_url }
+    }
+
+    /// Payments to and from `account` after `cursor`, oldest first.
+    pub async fn payments(
+        &self,
+        account: &str,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<HorizonPayment>, HorizonError> {
+        let limit = limit.to_string();
+        let mut query = vec![
+            ("order", "asc"),
+            ("limit", limit.as_str()),
+            ("join", "transactions"),
+        ];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor));
+        }
+
+        let request = self
+            .http
+            .get(format!("{}/accounts/{account}/payments", self.base_url))
+            .query(&query);
+        let response = self.http.send(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(HorizonError::Horizon {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        let page: PaymentsPage = response.json().await.map_err(HttpError::from)?;
+        Ok(page.embedded.records)
+    }
+
+    /// Signers, thresholds and balances of `account`, or `None` if it does
+    /// not exist.
+    pub async fn account(&self, account: &str) -> Result<Option<HorizonAccount>, HorizonError> {
+        let request = self
+            .http
+            .get(format!("{}/accounts/{account}", self.base_url));
+        let response = self.http.send(request).await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(HorizonError::Horizon {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        Ok(Some(response.json().await.map_err(HttpError::from)?))
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct FundingPlan {
+    owner_address: String,
+    amount: Decimal,
+    funded_amount: Decimal,
+    funded_at: Option<DateTime<Utc>>,
+}
+
+/// Follows the deposit accounts' payments and credits deposits to plans.
+pub struct DepositWatcherService {
+    db: PgPool,
+    horizon: HorizonClient,
+    accounts: Vec<String>,
+    asset: String,
+    config: DepositWatcherConfig,
+}
+
+impl DepositWatcherService {
+    pub fn new(
+        db: PgPool,
+        horizon: HorizonClient,
+        accounts: Vec<String>,
+        asset: String,
+        config: DepositWatcherConfig,
+    ) -> Self {
+        Self {
+            db,
+            horizon,
+            accounts,
+            asset,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("deposit_watcher", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
+                        info!("Deposit watcher recorded {count} deposit(s)");
+                    }
+                })
+            }
+        });
+    }
+
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(DEPOSIT_WATCHER_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Deposit watcher lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let mut recorded = 0;
+        for account in &self.accounts {
+            let mut cursor: Option<String> =
+                sqlx::query_scalar("SELECT cursor FROM horizon_cursors WHERE account = $1")
+                    .bind(account)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+            for _ in 0..MAX_PAGES_PER_SWEEP {
+                let payments = match self
+                    .horizon
+                    .payments(account, cursor.as_deref(), self.config.page_size)
+                    .await
+                {
+                    Ok(payments) => payments,
+                    Err(e) => {
+                        warn!(account = %account, error = %e, "Failed to read Horizon payments");
+                        break;
+                    }
+                };
+
+                for payment in &payments {
+                    if let Some(deposit) = deposit_from_payment(payment, account) {
+                        if self.record_deposit(&mut tx, account, &deposit).await? {
+                            recorded += 1;
+                        }
+                    }
+                }
+
+                let Some(last) = payments.last() else {
+                    break;
+                };
+                cursor = Some(last.paging_token.clone());
+                sqlx::query(
+                    r#"
+                    INSERT INTO horizon_cursors (account, cursor)
+                    VALUES ($1, $2)
+                    ON CONFLICT (account)
+                    DO UPDATE SET cursor = EXCLUDED.cursor, updated_at = NOW()
+                    "#,
+                )
+                .bind(account)
+                .bind(&cursor)
+                .execute(&mut *tx)
+                .await?;
+
+                if payments.len() < self.config.page_size as usize {
+                    break;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(recorded)
+    }
+
+    /// The plan `deposit` is for, or why it has to be quarantined.
+    async fn resolve_plan(
+        &self,
+        conn: &mut PgConnection,
+        deposit: &Deposit,
+    ) -> Result<Result<Uuid, QuarantineReason>, sqlx::Error> {
+        if deposit.asset != self.asset {
+            return Ok(Err(QuarantineReason::WrongAsset));
+        }
+
+        let memo = match deposit.memo.as_deref() {
+            None => DepositReference::Absent,
+            Some(memo) => match plan_id_from_memo(memo) {
+                Some(plan_id) => {
+                    let exists: bool =
+                        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM plans WHERE id = $1)")
+                            .bind(plan_id)
+                            .fetch_one(&mut *conn)
+                            .await?;
+                    if exists {
+                        DepositReference::Plan(plan_id)
+                    } else {
+                        DepositReference::Unknown
+                    }
+                }
+                None => DepositReference::Unknown,
+            },
+        };
+        let muxed = match deposit.muxed_id.map(i64::try_from) {
+            None => DepositReference::Absent,
+            Some(Err(_)) => DepositReference::Unknown,
+            Some(Ok(muxed_id)) => sqlx::query_scalar::<_, Uuid>(
+                "SELECT plan_id FROM plan_deposit_addresses WHERE muxed_id = $1",
+            )
+            .bind(muxed_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .map_or(DepositReference::Unknown, DepositReference::Plan),
+        };
+        Ok(route_deposit(memo, muxed))
+    }
+
+    /// Records `deposit` and credits it to the plan its memo or muxed id
+    /// names, or quarantines it. Returns false when the deposit was already
+    /// recorded.
+    async fn record_deposit(
+        &self,
+        conn: &mut PgConnection,
+        account: &str,
+        deposit: &Deposit,
+    ) -> Result<bool, sqlx::Error> {
+        let (plan_id, plan) = match self.resolve_plan(&mut *conn, deposit).await? {
+            Ok(plan_id) => match lock_funding_plan(&mut *conn, plan_id).await? {
+                Some(plan) => (Some(plan_id), Ok(plan)),
+                None => (None, Err(QuarantineReason::UnknownReference)),
+            },
+            Err(reason) => (None, Err(reason)),
+        };
+
+        let event_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            WITH claimed AS (
+                INSERT INTO lending_event_keys (event_type, external_id)
+                VALUES ('deposit', $6)
+                ON CONFLICT DO NOTHING
+                RETURNING external_id
+            )
+            INSERT INTO lending_events
+                (event_type, plan_id, user_address, asset, amount, transaction_hash,
+                 external_id, metadata)
+            SELECT 'deposit', $1, $2, $3, $4, $5, external_id, $7
+            FROM claimed
+            RETURNING id
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&deposit.from)
+        .bind(&deposit.asset)
+        .bind(deposit.amount)
+        .bind(&deposit.transaction_hash)
+        .bind(&deposit.payment_id)
+        .bind(serde_json::json!({
+            "account": account,
+            "memo": deposit.memo,
+            "muxed_id": deposit.muxed_id,
+        }))
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some(event_id) = event_id else {
+            return Ok(false);
+        };
+        let (Some(plan_id), Ok(plan)) = (plan_id, plan.as_ref()) else {
+            let reason = plan.err().unwrap_or(QuarantineReason::UnknownReference);
+            sqlx::query(
+                r#"
+                INSERT INTO quarantined_deposits
+                    (lending_event_id, reason, account, memo, muxed_id, from_address, asset,
+                     amount, transaction_hash)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+            )
+            .bind(event_id)
+            .bind(reason.as_str())
+            .bind(account)
+            .bind(&deposit.memo)
+            .bind(deposit.muxed_id.map(|id| id.to_string()))
+            .bind(&deposit.from)
+            .bind(&deposit.asset)
+            .bind(deposit.amount)
+            .bind(&deposit.transaction_hash)
+            .execute(&mut *conn)
+            .await?;
+            warn!(
+                payment_id = %deposit.payment_id,
+                memo = ?deposit.memo,
+                muxed_id = ?deposit.muxed_id,
+                reason = reason.as_str(),
+                "Quarantined deposit that could not be credited to a plan"
+            );
+            return Ok(true);
+        };
+
+        credit_plan(
+            &mut *conn,
+            plan_id,
+            plan,
+            &Credit {
+                lending_event_id: event_id,
+                amount: deposit.amount,
+                display_amount: &deposit.display_amount,
+                asset: &deposit.asset,
+                transaction_hash: &deposit.transaction_hash,
+            },
+        )
+        .await?;
+        Ok(true)
+    }
+}
+
+/// A recorded deposit being credited to a plan.
+pub(crate) struct Credit<'a> {
+    pub lending_event_id: Uuid,
+    /// Amount in base units.
+    pub amount: Decimal,
+    pub display_amount: &'a str,
+    pub asset: &'a str,
+    pub transaction_hash: &'a str,
+}
+
+/// Locks a plan's funding state for crediting.
+pub(crate) async fn lock_funding_plan(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+) -> Result<Option<FundingPlan>, sqlx::Error> {
+    sqlx::query_as::<_, FundingPlan>(
+        r#"
+        SELECT owner_address, amount, funded_amount, funded_at
+        FROM plans
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+/// Part of a deposit of `amount` that takes the plan's funded amount beyond
+/// the plan amount.
+pub fn overpaid_amount(plan_amount: Decimal, funded_amount: Decimal, amount: Decimal) -> Decimal {
+    (funded_amount + amount - plan_amount.max(funded_amount)).clamp(Decimal::ZERO, amount)
+}
+
+/// Adds a deposit to the plan's funded amount, marking the plan funded once
+/// deposits cover it, and notifies the owner, including of any overpayment.
+pub(crate) async fn credit_plan(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    plan: &FundingPlan,
+    credit: &Credit<'_>,
+) -> Result<(), sqlx::Error> {
+    let funded_amount = plan.funded_amount + credit.amount;
+    let newly_funded = plan.funded_at.is_none() && funded_amount >= plan.amount;
+    let overpaid = overpaid_amount(plan.amount, plan.funded_amount, credit.amount);
+    sqlx::query(
+        r#"
+        UPDATE plans
+        SET funded_amount = $2,
+            funded_at = CASE WHEN $3 THEN NOW() ELSE funded_at END
+        WHERE id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .bind(funded_amount)
+    .bind(newly_funded)
+    .execute(&mut *conn)
+    .await?;
+
+    create_notification(
+        &mut *conn,
+        &plan.owner_address,
+        "deposit_received",
+        "Deposit received",
+        &format!(
+            "We received {} {} for your inheritance plan.",
+            credit.display_amount,
+            asset_code(credit.asset)
+        ),
+        serde_json::json!({
+            "plan_id": plan_id,
+            "lending_event_id": credit.lending_event_id,
+            "amount": credit.amount,
+            "transaction_hash": credit.transaction_hash,
+        }),
+    )
+    .await?;
+
+    if newly_funded {
+        create_notification(
+            &mut *conn,
+            &plan.owner_address,
+            "plan_funded",
+            "Plan funded",
+            "Deposits now cover the full amount of your inheritance plan.",
+            serde_json::json!({
+                "plan_id": plan_id,
+                "funded_amount": funded_amount,
+            }),
+        )
+        .await?;
+    }
+
+    if overpaid > Decimal::ZERO {
+        create_notification(
+            &mut *conn,
+            &plan.owner_address,
+            "plan_overfunded",
+            "Plan overfunded",
+            &format!(
+                "Your deposits exceed the amount of your inheritance plan by {} {}. \
+                 You can request a refund of the excess.",
+                from_base_units(funded_amount - plan.amount),
+                asset_code(credit.asset)
+            ),
+            serde_json::json!({
+                "plan_id": plan_id,
+                "lending_event_id": credit.lending_event_id,
+                "overpaid_amount": overpaid,
+                "excess_amount": funded_amount - plan.amount,
+            }),
+        )
+        .await?;
+        warn!(plan_id = %plan_id, overpaid = %overpaid, "Deposit overfunded plan");
+    }
+
+    info!(plan_id = %plan_id, amount = %credit.amount, newly_funded, "Deposit credited to plan");
+    Ok(())
+}
+
+fn asset_code(asset: &str) -> &str {
+    match asset.split_once(':') {
+        Some((code, _)) => code,
+        None => "XLM",
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DepositPlanRow {
+    amount: Decimal,
+    funded_amount: Decimal,
+    funded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DepositEvent {
+    pub id: Uuid,
+    #[serde(rename = "from")]
+    pub user_address: String,
+    pub asset: String,
+    pub amount: Decimal,
+    pub transaction_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanDeposits {
+    pub plan_id: Uuid,
+    /// Account to pay; `None` while deposit detection is not configured.
+    pub deposit_account: Option<String>,
+    /// The plan's muxed deposit address, once one has been issued.
+    pub muxed_address: Option<String>,
+    pub memo_type: &'static str,
+    pub memo: String,
+    pub asset: String,
+    pub amount: Decimal,
+    pub funded_amount: Decimal,
+    pub funded_at: Option<DateTime<Utc>>,
+    /// Funded amount above the plan amount not already being refunded.
+    pub refundable_amount: Decimal,
+    pub deposits: Vec<DepositEvent>,
+}
+
+// Handler: Plan Deposits
+pub async fn get_plan_deposits(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<PlanDeposits>, sqlx::Error> = async {
+        let Some(plan) = sqlx::query_as::<_, DepositPlanRow>(
+            "SELECT amount, funded_amount, funded_at FROM plans WHERE id = $1 AND owner_address = $2",
+        )
+        .bind(plan_id)
+        .bind(&owner)
+        .fetch_optional(&state.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let muxed_id: Option<i64> =
+            sqlx::query_scalar("SELECT muxed_id FROM plan_deposit_addresses WHERE plan_id = $1")
+                .bind(plan_id)
+                .fetch_optional(&state.db_pool)
+                .await?;
+        let deposit_account = state.config.deposit_accounts.first().cloned();
+
+        let deposits = sqlx::query_as::<_, DepositEvent>(
+            r#"
+            SELECT id, user_address, asset, amount, transaction_hash, created_at
+            FROM lending_events
+            WHERE plan_id = $1 AND event_type = 'deposit'
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(plan_id)
+        .fetch_all(&state.db_pool)
+        .await?;
+        let refundable_amount =
+            crate::deposit_refunds::refundable_amount(&state.db_pool, plan_id).await?;
+
+        Ok(Some(PlanDeposits {
+            plan_id,
+            muxed_address: deposit_account
+                .as_deref()
+                .zip(muxed_id)
+                .and_then(|(account, id)| muxed_deposit_address(account, id)),
+            deposit_account,
+            memo_type: "text",
+            memo: deposit_memo(plan_id),
+            asset: state.config.deposit_asset.clone(),
+            amount: plan.amount,
+            funded_amount: plan.funded_amount,
+            funded_at: plan.funded_at,
+            refundable_amount,
+            deposits,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Some(deposits)) => (StatusCode::OK, Json(deposits)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Plan not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to load plan deposits");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DepositAddress {
+    pub plan_id: Uuid,
+    /// `M...` address to pay; needs no memo.
+    pub muxed_address: String,
+    pub muxed_id: i64,
+    /// The underlying account, with `memo` for wallets that cannot pay a
+    /// muxed address.
+    pub deposit_account: String,
+    pub memo_type: &'static str,
+    pub memo: String,
+    pub asset: String,
+    pub created_at: DateTime<Utc>,
+}
+
+enum Lookup {
+    Created(DepositAddress),
+    Existing(DepositAddress),
+    PlanNotFound,
+    NotIssued,
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// Loads, and with `issue` first creates, the owner's plan deposit address.
+async fn deposit_address(
+    state: &AppState,
+    account: &str,
+    owner: &str,
+    plan_id: Uuid,
+    issue: bool,
+) -> Result<Lookup, sqlx::Error> {
+    let owned: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM plans WHERE id = $1 AND owner_address = $2)",
+    )
+    .bind(plan_id)
+    .bind(owner)
+    .fetch_one(&state.db_pool)
+    .await?;
+    if !owned {
+        return Ok(Lookup::PlanNotFound);
+    }
+
+    let created = if issue {
+        sqlx::query(
+            "INSERT INTO plan_deposit_addresses (plan_id) VALUES ($1) ON CONFLICT (plan_id) DO NOTHING",
+        )
+        .bind(plan_id)
+        .execute(&state.db_pool)
+        .await?
+        .rows_affected()
+            > 0
+    } else {
+        false
+    };
+
+    let Some((muxed_id, created_at)) = sqlx::query_as::<_, (i64, DateTime<Utc>)>(
+        "SELECT muxed_id, created_at FROM plan_deposit_addresses WHERE plan_id = $1",
+    )
+    .bind(plan_id)
+    .fetch_optional(&state.db_pool)
+    .await?
+    else {
+        return Ok(Lookup::NotIssued);
+    };
+
+    let address = DepositAddress {
+        plan_id,
+        muxed_address: muxed_deposit_address(account, muxed_id).unwrap_or_default(),
+        muxed_id,
+        deposit_account: account.to_string(),
+        memo_type: "text",
+        memo: deposit_memo(plan_id),
+        asset: state.config.deposit_asset.clone(),
+        created_at,
+    };
+    Ok(if created {
+        Lookup::Created(address)
+    } else {
+        Lookup::Existing(address)
+    })
+}
+
+async fn deposit_address_response(
+    state: &AppState,
+    user: &UserContext,
+    plan_id: Uuid,
+    issue: bool,
+) -> axum::response::Response {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let Some(account) = state.config.deposit_accounts.first() else {
+        return refused(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Deposit detection is not configured",
+        );
+    };
+
+    match deposit_address(state, account, &owner, plan_id, issue).await {
+        Ok(Lookup::Created(address)) => (StatusCode::CREATED, Json(address)).into_response(),
+        Ok(Lookup::Existing(address)) => (StatusCode::OK, Json(address)).into_response(),
+        Ok(Lookup::PlanNotFound) => refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Ok(Lookup::NotIssued) => refused(
+            StatusCode::NOT_FOUND,
+            "No deposit address has been issued for this plan",
+        ),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan deposit address");
+            refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+        }
+    }
+}
+
+// Handler: Issue Plan Deposit Address
+pub async fn issue_deposit_address(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    deposit_address_response(&state, &user, plan_id, true).await
+}
+
+// Handler: Get Plan Deposit Address
+pub async fn get_deposit_address(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    deposit_address_response(&state, &user, plan_id, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "GDIW7P2XUXC4XZB452Y5Z774N4V27PUDHWTKWTQZ3KHYUGB743WEXG7T";
+    const PAYER: &str = "GAAZI4TCR3TY5OJHCTJC2A4QSY6CJWJH5IAJTGKIN2ER7LBNVKOCCWN7";
+
+    fn payment(value: serde_json::Value) -> HorizonPayment {
+        let mut record = serde_json::json!({
+            "id": "12884905985",
+            "paging_token": "12884905985",
+            "type": "payment",
+            "transaction_successful": true,
+            "transaction_hash": "ab12",
+            "from": PAYER,
+            "to": ACCOUNT,
+            "amount": "12.5000000",
+            "asset_type": "native",
+        });
+        record
+            .as_object_mut()
+            .unwrap()
+            .extend(value.as_object().unwrap().clone());
+        serde_json::from_value(record).unwrap()
+    }
+
+    #[test]
+    fn memo_round_trips_and_fits_text_memo() {
+        let plan_id = Uuid::new_v4();
+        let memo = deposit_memo(plan_id);
+
+        assert!(memo.len() <= 28);
+        assert_eq!(plan_id_from_memo(&memo), Some(plan_id));
+        assert_eq!(plan_id_from_memo("ixv-0123456789abcdef"), None);
+        assert_eq!(plan_id_from_memo("ixp-not-base64!"), None);
+    }
+
+    #[test]
+    fn muxed_addresses_carry_the_plan_id() {
+        let account = stellar_strkey::ed25519::PublicKey([7; 32]).to_string();
+        let address = muxed_deposit_address(&account, 42).unwrap();
+        let muxed = stellar_strkey::ed25519::MuxedAccount::from_string(&address).unwrap();
+
+        assert!(address.starts_with('M'));
+        assert_eq!(muxed.id, 42);
+        assert_eq!(muxed.ed25519, [7; 32]);
+        assert_eq!(muxed_deposit_address(&account, -1), None);
+        assert_eq!(muxed_deposit_address("not-an-account", 42), None);
+    }
+
+    #[test]
+    fn routes_deposits_by_muxed_id_then_memo() {
+        use DepositReference::*;
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(route_deposit(Plan(a), Absent), Ok(a));
+        assert_eq!(route_deposit(Absent, Plan(b)), Ok(b));
+        assert_eq!(route_deposit(Plan(b), Plan(b)), Ok(b));
+        assert_eq!(route_deposit(Unknown, Plan(b)), Ok(b));
+        assert_eq!(
+            route_deposit(Plan(a), Plan(b)),
+            Err(QuarantineReason::ConflictingReference)
+        );
+        assert_eq!(
+            route_deposit(Plan(a), Unknown),
+            Err(QuarantineReason::UnknownReference)
+        );
+        assert_eq!(
+            route_deposit(Unknown, Absent),
+            Err(QuarantineReason::UnknownReference)
+        );
+        assert_eq!(
+            route_deposit(Absent, Absent),
+            Err(QuarantineReason::MissingReference)
+        );
+    }
+
+    #[test]
+    fn overpayment_is_the_part_above_the_plan_amount() {
+        let d = Decimal::from;
+        assert_eq!(overpaid_amount(d(100), d(0), d(50)), d(0));
+        assert_eq!(overpaid_amount(d(100), d(80), d(50)), d(30));
+        assert_eq!(overpaid_amount(d(100), d(100), d(50)), d(50));
+        assert_eq!(overpaid_amount(d(100), d(120), d(10)), d(10));
+    }
+
+    #[test]
+    fn converts_horizon_amounts_to_base_units() {
+        assert_eq!(
+            to_base_units("12.5000000"),
+            Some(Decimal::from(125_000_000))
+        );
+        assert_eq!(to_base_units("0.0000001"), Some(Decimal::ONE));
+        assert_eq!(to_base_units("0.0000000"), None);
+        assert_eq!(to_base_units("abc"), None);
+        assert_eq!(from_base_units(Decimal::from(125_000_000)), "12.5");
+    }
+
+    #[test]
+    fn extracts_incoming_payment_with_memo() {
+        let memo = deposit_memo(Uuid::nil());
+        let deposit = deposit_from_payment(
+            &payment(serde_json::json!({
+                "asset_type": "credit_alphanum4",
+                "asset_code": "USDC",
+                "asset_issuer": PAYER,
+                "transaction": { "memo_type": "text", "memo": memo },
+            })),
+            ACCOUNT,
+        )
+        .unwrap();
+
+        assert_eq!(deposit.asset, format!("USDC:{PAYER}"));
+        assert_eq!(deposit.amount, Decimal::from(125_000_000));
+        assert_eq!(deposit.memo.as_deref(), Some(memo.as_str()));
+        assert_eq!(deposit.muxed_id, None);
+        assert_eq!(asset_code(&deposit.asset), "USDC");
+
+        let muxed = deposit_from_payment(
+            &payment(serde_json::json!({ "to_muxed_id": "18446744073709551615" })),
+            ACCOUNT,
+        )
+        .unwrap();
+        assert_eq!(muxed.muxed_id, Some(u64::MAX));
+    }
+
+    #[test]
+    fn ignores_outgoing_failed_and_other_operations() {
+        let outgoing = payment(serde_json::json!({ "from": ACCOUNT, "to": PAYER }));
+        let failed = payment(serde_json::json!({ "transaction_successful": false }));
+        let create = payment(serde_json::json!({ "type": "create_account" }));
+
+        assert!(deposit_from_payment(&outgoing, ACCOUNT).is_none());
+        assert!(deposit_from_payment(&failed, ACCOUNT).is_none());
+        assert!(deposit_from_payment(&create, ACCOUNT).is_none());
+
+        let hash_memo = payment(serde_json::json!({
+            "transaction": { "memo_type": "hash", "memo": "AAAA" },
+        }));
+        assert_eq!(
+            deposit_from_payment(&hash_memo, ACCOUNT).unwrap().memo,
+            None
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! What-if simulator for the platform's unit economics.
+//!
+//! Given hypothetical parameters, projects month by month how plan
+//! balances, yield paid to plans, payout fee revenue and the platform
+//! reserve would move, so a fee schedule or rate table change can be
+//! weighed before it is proposed. Each month, in order:
+//!
+//! - deposits arrive, growing by `volume_growth_bps` a month;
+//! - the share of the balance earning yield accrues at the rate curve's APY;
+//! - the whole balance, held in custody, earns `custody_return_bps`;
+//! - `payout_rate_bps` of the balance is paid out and charged `fee_bps`.
+//!
+//! The reserve is fee revenue plus custody return less yield paid. The
+//! money math uses the checked helpers from [`crate::cost_breakdown`] and a
+//! scenario that would overflow is refused. Parameters left out default to
+//! live figures for active plans (of `token`, when given) and the current
+//! fee schedule and rate table.
+//!
+//! There is no loan book, so borrower-side inputs such as a utilization
+//! curve or default rate have nothing to act on and are not modelled.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::AppState;
+use crate::cost_breakdown::{checked_fee, checked_yield};
+use crate::platform_settings;
+
+pub const MAX_MONTHS: u32 = 120;
+const MAX_BPS: u32 = 10_000;
+/// A twelfth of the year used by [`checked_yield`].
+const SECONDS_PER_MONTH: i64 = 2_629_800;
+
+/// APY from `from_month` (1-based) until the next point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RatePoint {
+    pub from_month: u32,
+    pub apy_bps: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EconomicsRequest {
+    pub months: u32,
+    /// Token contract whose plans and rate override supply the defaults;
+    /// every active plan and the default APY otherwise.
+    pub token: Option<String>,
+    /// In token base units; the active plans' balance by default.
+    pub starting_balance: Option<Decimal>,
+    /// Deposits in the first month, in token base units.
+    #[serde(default)]
+    pub monthly_deposits: Decimal,
+    /// Month-on-month change in deposits; may be negative.
+    #[serde(default)]
+    pub volume_growth_bps: i32,
+    /// The rate table's APY throughout by default.
+    pub rate_curve: Option<Vec<RatePoint>>,
+    /// Share of the balance in plans earning yield; the active plans'
+    /// share by default.
+    pub yield_participation_bps: Option<u32>,
+    /// Share of the balance paid out each month.
+    #[serde(default)]
+    pub payout_rate_bps: u32,
+    /// The current fee schedule by default.
+    pub fee_bps: Option<u32>,
+    /// APY the platform earns on funds in custody.
+    #[serde(default)]
+    pub custody_return_bps: u32,
+}
+
+/// The scenario as simulated, with defaults filled in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Scenario {
+    pub months: u32,
+    pub token: Option<String>,
+    pub starting_balance: Decimal,
+    pub monthly_deposits: Decimal,
+    pub volume_growth_bps: i32,
+    pub rate_curve: Vec<RatePoint>,
+    pub yield_participation_bps: u32,
+    pub payout_rate_bps: u32,
+    pub fee_bps: u32,
+    pub custody_return_bps: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonthResult {
+    pub month: u32,
+    pub apy_bps: u32,
+    pub deposits: Decimal,
+    pub yield_paid: Decimal,
+    pub custody_return: Decimal,
+    pub payouts: Decimal,
+    pub fees: Decimal,
+    /// Plan balances at the end of the month.
+    pub balance: Decimal,
+    /// Cumulative fees and custody return less yield paid.
+    pub reserve: Decimal,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Totals {
+    pub deposits: Decimal,
+    pub yield_paid: Decimal,
+    pub custody_return: Decimal,
+    pub payouts: Decimal,
+    pub fees: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EconomicsSimulation {
+    pub scenario: Scenario,
+    pub months: Vec<MonthResult>,
+    pub totals: Totals,
+    pub ending_balance: Decimal,
+    pub ending_reserve: Decimal,
+    /// First month the reserve is below zero, if it ever is.
+    pub reserve_negative_from: Option<u32>,
+}
+
+#[derive(sqlx::FromRow)]
+struct LiveFigures {
+    balance: Decimal,
+    earning: Decimal,
+}
+
+fn check_bps(name: &str, bps: u32) -> Result<(), String> {
+    if bps > MAX_BPS {
+        return Err(format!("{name} must not exceed {MAX_BPS} basis points"));
+    }
+    Ok(())
+}
+
+impl Scenario {
+    /// Checks the scenario; the rate curve must start at month 1 and rise.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.months == 0 || self.months > MAX_MONTHS {
+            return Err(format!("months must be between 1 and {MAX_MONTHS}"));
+        }
+        if self.starting_balance.is_sign_negative() || self.monthly_deposits.is_sign_negative() {
+            return Err("Amounts must not be negative".to_string());
+        }
+        if !(-(MAX_BPS as i32)..=MAX_BPS as i32).contains(&self.volume_growth_bps) {
+            return Err(format!(
+                "volume_growth_bps must be between -{MAX_BPS} and {MAX_BPS}"
+            ));
+        }
+        check_bps("yield_participation_bps", self.yield_participation_bps)?;
+        check_bps("payout_rate_bps", self.payout_rate_bps)?;
+        check_bps("fee_bps", self.fee_bps)?;
+        check_bps("custody_return_bps", self.custody_return_bps)?;
+        if self.rate_curve.first().map(|p| p.from_month) != Some(1) {
+            return Err("rate_curve must start at month 1".to_string());
+        }
+        for pair in self.rate_curve.windows(2) {
+            if pair[1].from_month <= pair[0].from_month {
+                return Err("rate_curve months must increase".to_string());
+            }
+        }
+        for point in &self.rate_curve {
+            check_bps("apy_bps", point.apy_bps)?;
+        }
+        Ok(())
+    }
+
+    fn apy_bps(&self, month: u32) -> u32 {
+        self.rate_curve
+            .iter()
+            .take_while(|p| p.from_month <= month)
+            .last()
+            .map_or(0, |p| p.apy_bps)
+    }
+}
+
+/// Runs a validated scenario. `None` if any amount overflows.
+pub fn simulate(scenario: Scenario) -> Option<EconomicsSimulation> {
+    let mut balance = scenario.starting_balance;
+    let mut deposits = scenario.monthly_deposits;
+    let mut reserve = Decimal::ZERO;
+    let mut totals = Totals::default();
+    let mut months = Vec::with_capacity(scenario.months as usize);
+    let mut reserve_negative_from = None;
+
+    for month in 1..=scenario.months {
+        if month > 1 {
+            let change = checked_fee(deposits, scenario.volume_growth_bps.unsigned_abs())?;
+            deposits = if scenario.volume_growth_bps < 0 {
+                deposits.checked_sub(change)?
+            } else {
+                deposits.checked_add(change)?
+            };
+        }
+        balance = balance.checked_add(deposits)?;
+
+        let apy_bps = scenario.apy_bps(month);
+        let earning = checked_fee(balance, scenario.yield_participation_bps)?;
+        let yield_paid = checked_yield(earning, apy_bps, SECONDS_PER_MONTH)?;
+        let custody_return =
+            checked_yield(balance, scenario.custody_return_bps, SECONDS_PER_MONTH)?;
+        balance = balance.checked_add(yield_paid)?;
+
+        let payouts = checked_fee(balance, scenario.payout_rate_bps)?;
+        let fees = checked_fee(payouts, scenario.fee_bps)?;
+        balance = balance.checked_sub(payouts)?;
+        reserve = reserve
+            .checked_add(fees)?
+            .checked_add(custody_return)?
+            .checked_sub(yield_paid)?;
+        if reserve < Decimal::ZERO && reserve_negative_from.is_none() {
+            reserve_negative_from = Some(month);
+        }
+
+        totals.deposits = totals.deposits.checked_add(deposits)?;
+        totals.yield_paid = totals.yield_paid.checked_add(yield_paid)?;
+        totals.custody_return = totals.custody_return.checked_add(custody_return)?;
+        totals.payouts = totals.payouts.checked_add(payouts)?;
+        totals.fees = totals.fees.checked_add(fees)?;
+        months.push(MonthResult {
+            month,
+            apy_bps,
+            deposits,
+            yield_paid,
+            custody_return,
+            payouts,
+            fees,
+            balance,
+            reserve,
+        });
+    }
+
+    Some(EconomicsSimulation {
+        scenario,
+        months,
+        totals,
+        ending_balance: balance,
+        ending_reserve: reserve,
+        reserve_negative_from,
+    })
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+// Handler: Simulate Unit Economics (admin)
+pub async fn simulate_economics(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<EconomicsRequest>,
+) -> impl IntoResponse {
+    let loaded = async {
+        let live = sqlx::query_as::<_, LiveFigures>(
+            r#"
+            SELECT COALESCE(SUM(amount + accrued_yield), 0) AS balance,
+                   COALESCE(SUM(amount + accrued_yield) FILTER (WHERE earn_yield), 0) AS earning
+            FROM plans
+            WHERE is_active AND ($1::text IS NULL OR token_address = $1)
+            "#,
+        )
+        .bind(&request.token)
+        .fetch_one(&state.db_pool)
+        .await?;
+        let fees = platform_settings::fee_schedule(&state.db_pool, &state.config).await?;
+        let rates = platform_settings::rate_table(&state.db_pool, &state.apy_config).await?;
+        Ok::<_, sqlx::Error>((live, fees, rates))
+    }
+    .await;
+    let (live, fees, rates) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!(error = %e, "Failed to load live figures for economics simulation");
+            return refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed");
+        }
+    };
+
+    let apy_bps = request
+        .token
+        .as_ref()
+        .and_then(|token| rates.token_apy_bps.get(token).copied())
+        .unwrap_or(rates.default_apy_bps);
+    let participation_bps = if live.balance.is_zero() {
+        0
+    } else {
+        (live.earning * Decimal::from(MAX_BPS) / live.balance)
+            .floor()
+            .try_into()
+            .unwrap_or(MAX_BPS)
+    };
+    let scenario = Scenario {
+        months: request.months,
+        token: request.token,
+        starting_balance: request.starting_balance.unwrap_or(live.balance),
+        monthly_deposits: request.monthly_deposits,
+        volume_growth_bps: request.volume_growth_bps,
+        rate_curve: request.rate_curve.unwrap_or_else(|| {
+            vec![RatePoint {
+                from_month: 1,
+                apy_bps,
+            }]
+        }),
+        yield_participation_bps: request.yield_participation_bps.unwrap_or(participation_bps),
+        payout_rate_bps: request.payout_rate_bps,
+        fee_bps: request.fee_bps.unwrap_or(fees.payout_fee_bps),
+        custody_return_bps: request.custody_return_bps,
+    };
+    if let Err(message) = scenario.validate() {
+        return refused(StatusCode::BAD_REQUEST, &message);
+    }
+
+    match simulate(scenario) {
+        Some(simulation) => (StatusCode::OK, Json(simulation)).into_response(),
+        None => refused(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Scenario overflows the supported amount range",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario() -> Scenario {
+        Scenario {
+            months: 3,
+            token: None,
+            starting_balance: Decimal::from(1_200_000),
+            monthly_deposits: Decimal::from(100_000),
+            volume_growth_bps: 1_000,
+            rate_curve: vec![
+                RatePoint {
+                    from_month: 1,
+                    apy_bps: 1_200,
+                },
+                RatePoint {
+                    from_month: 3,
+                    apy_bps: 0,
+                },
+            ],
+            yield_participation_bps: 5_000,
+            payout_rate_bps: 1_000,
+            fee_bps: 100,
+            custody_return_bps: 0,
+        }
+    }
+
+    #[test]
+    fn simulates_month_by_month() {
+        let simulation = simulate(scenario()).unwrap();
+        let first = &simulation.months[0];
+        // 1.3M in, half earning 12% for a month, then 10% paid out at 1%.
+        assert_eq!(first.deposits, Decimal::from(100_000));
+        assert_eq!(first.yield_paid, Decimal::from(6_500));
+        assert_eq!(first.payouts, Decimal::from(130_650));
+        assert_eq!(first.fees, Decimal::from(1_306));
+        assert_eq!(first.balance, Decimal::from(1_175_850));
+        assert_eq!(first.reserve, Decimal::from(-5_194));
+        assert_eq!(simulation.months[1].deposits, Decimal::from(110_000));
+        assert_eq!(simulation.months[2].apy_bps, 0);
+        assert_eq!(simulation.months[2].yield_paid, Decimal::ZERO);
+        assert_eq!(simulation.reserve_negative_from, Some(1));
+        assert_eq!(
+            simulation.totals.deposits,
+            Decimal::from(100_000 + 110_000 + 121_000)
+        );
+    }
+
+    #[test]
+    fn validates_the_scenario() {
+        assert!(scenario().validate().is_ok());
+        let invalid = [
+            Scenario {
+                months: 0,
+                ..scenario()
+            },
+            Scenario {
+                fee_bps: 10_001,
+                ..scenario()
+            },
+            Scenario {
+                volume_growth_bps: -10_001,
+                ..scenario()
+            },
+            Scenario {
+                rate_curve: vec![RatePoint {
+                    from_month: 2,
+                    apy_bps: 500,
+                }],
+                ..scenario()
+            },
+            Scenario {
+                monthly_deposits: Decimal::from(-1),
+                ..scenario()
+            },
+        ];
+        for scenario in invalid {
+            assert!(scenario.validate().is_err(), "{scenario:?}");
+        }
+    }
+}
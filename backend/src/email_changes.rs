@@ -0,0 +1,512 @@
+//! Changing or removing the email notifications are delivered to.
+//!
+//! The email controls claim notifications and one-time codes, so once a
+//! wallet has one it can only be changed here: the wallet signs a
+//! `change_email` challenge, a confirmation link is sent to the current
+//! address and to the new one, and the change is applied once both links
+//! have been opened. Removing the email only needs the current address.
+//! Requests expire after [`EMAIL_CHANGE_TTL_HOURS`].
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::mailer::is_plausible_email;
+use crate::notifications::{cancel_queued_email, create_notification};
+use crate::wallet_reauth::{verify_confirmation, ReauthAction, ReauthError, WalletConfirmation};
+
+pub const EMAIL_CHANGE_TTL_HOURS: i64 = 24;
+
+const CHANGE_COLUMNS: &str = "id, user_address, old_email, new_email, old_token_hash, \
+     new_token_hash, old_confirmed_at, new_confirmed_at, status, expires_at, created_at, resolved_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct EmailChange {
+    pub id: Uuid,
+    pub user_address: String,
+    pub old_email: String,
+    /// `None` when the email is being removed.
+    pub new_email: Option<String>,
+    #[serde(skip_serializing)]
+    pub old_token_hash: String,
+    #[serde(skip_serializing)]
+    pub new_token_hash: Option<String>,
+    pub old_confirmed_at: Option<DateTime<Utc>>,
+    pub new_confirmed_at: Option<DateTime<Utc>>,
+    /// `pending`, `completed` or `cancelled`.
+    pub status: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl EmailChange {
+    /// Whether every address that has to confirm has done so.
+    fn is_confirmed(&self) -> bool {
+        self.old_confirmed_at.is_some()
+            && (self.new_email.is_none() || self.new_confirmed_at.is_some())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailChangeRequest {
+    /// `null` removes the email.
+    pub new_email: Option<String>,
+    pub confirmation: Option<WalletConfirmation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmEmailChangeRequest {
+    pub token: String,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn confirm_link(base_url: &str, token: &str) -> String {
+    let separator = if base_url.contains('?') { '&' } else { '?' };
+    format!("{base_url}{separator}token={token}")
+}
+
+enum Outcome {
+    Done(EmailChange),
+    Refused(StatusCode, &'static str),
+    Reauth(ReauthError),
+}
+
+// Handler: Request Email Change
+pub async fn request_email_change(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<EmailChangeRequest>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let new_email = payload
+        .new_email
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+    if new_email.as_deref().is_some_and(|e| !is_plausible_email(e)) {
+        return refused(StatusCode::BAD_REQUEST, "Invalid email address");
+    }
+    let Some(confirmation) = payload.confirmation else {
+        return ReauthError::ConfirmationRequired.into_response();
+    };
+
+    let old_token = generate_token();
+    let new_token = new_email.as_ref().map(|_| generate_token());
+
+    let outcome = async {
+        let mut tx = state.db_pool.begin().await?;
+        if let Err(e) = verify_confirmation(
+            &mut tx,
+            &user_address,
+            ReauthAction::ChangeEmail,
+            None,
+            &confirmation,
+        )
+        .await
+        {
+            return Ok(Outcome::Reauth(e));
+        }
+
+        let current: Option<String> = sqlx::query_scalar(
+            "SELECT email FROM notification_preferences WHERE user_address = $1 FOR UPDATE",
+        )
+        .bind(&user_address)
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+        let Some(old_email) = current else {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "No email on file; set one in notification preferences",
+            ));
+        };
+        if new_email
+            .as_deref()
+            .is_some_and(|e| e.eq_ignore_ascii_case(&old_email))
+        {
+            return Ok(Outcome::Refused(
+                StatusCode::BAD_REQUEST,
+                "New email matches the current one",
+            ));
+        }
+
+        sqlx::query(
+            "UPDATE email_changes SET status = 'cancelled', resolved_at = NOW() WHERE user_address = $1 AND status = 'pending'",
+        )
+        .bind(&user_address)
+        .execute(&mut *tx)
+        .await?;
+        let change = sqlx::query_as::<_, EmailChange>(&format!(
+            r#"
+            INSERT INTO email_changes (user_address, old_email, new_email, old_token_hash, new_token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {CHANGE_COLUMNS}
+            "#
+        ))
+        .bind(&user_address)
+        .bind(&old_email)
+        .bind(&new_email)
+        .bind(hash_token(&old_token))
+        .bind(new_token.as_deref().map(hash_token))
+        .bind(Utc::now() + Duration::hours(EMAIL_CHANGE_TTL_HOURS))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        record_audit(
+            &mut *tx,
+            &user_address,
+            "email_change.requested",
+            &change.id.to_string(),
+            serde_json::json!({ "removal": change.new_email.is_none() }),
+        )
+        .await?;
+        create_notification(
+            &mut *tx,
+            &user_address,
+            "email_change_requested",
+            "Email change requested",
+            "A change to the email on your account was requested. It takes effect once it is \
+             confirmed from the links sent to your email addresses.",
+            serde_json::json!({ "email_change_id": change.id }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Outcome::Done(change))
+    }
+    .await;
+
+    let change = match outcome {
+        Ok(Outcome::Done(change)) => change,
+        Ok(Outcome::Refused(status, message)) => return refused(status, message),
+        Ok(Outcome::Reauth(e)) => return e.into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to request email change");
+            return database_error();
+        }
+    };
+
+    let base_url = &state.config.email_confirm_url;
+    let (subject, body) = match &change.new_email {
+        Some(new_email) => (
+            "Confirm your InheritX email change",
+            format!(
+                "A request was made to change the email on your InheritX account to {new_email}. \
+                 To approve it, open this link within {EMAIL_CHANGE_TTL_HOURS} hours:\n\n{}\n\n\
+                 If you did not request this, ignore this email and the email will not change.",
+                confirm_link(base_url, &old_token)
+            ),
+        ),
+        None => (
+            "Confirm removing your InheritX email",
+            format!(
+                "A request was made to remove this email from your InheritX account. You would \
+                 stop receiving claim and account notifications. To approve it, open this link \
+                 within {EMAIL_CHANGE_TTL_HOURS} hours:\n\n{}",
+                confirm_link(base_url, &old_token)
+            ),
+        ),
+    };
+    if let Err(e) = state.mailer.send(&change.old_email, subject, &body).await {
+        warn!(error = %e, "Failed to send email change confirmation to the current address");
+    }
+    if let (Some(new_email), Some(token)) = (&change.new_email, &new_token) {
+        let body = format!(
+            "Open this link within {EMAIL_CHANGE_TTL_HOURS} hours to confirm this address for \
+             your InheritX account:\n\n{}",
+            confirm_link(base_url, token)
+        );
+        if let Err(e) = state
+            .mailer
+            .send(new_email, "Confirm your new InheritX email", &body)
+            .await
+        {
+            warn!(error = %e, "Failed to send email change confirmation to the new address");
+        }
+    }
+
+    info!(user_address = %user_address, email_change_id = %change.id, "Email change requested");
+    (StatusCode::ACCEPTED, Json(change)).into_response()
+}
+
+// Handler: Get Pending Email Change
+pub async fn get_email_change(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, EmailChange>(&format!(
+        "SELECT {CHANGE_COLUMNS} FROM email_changes WHERE user_address = $1 AND status = 'pending'"
+    ))
+    .bind(&user_address)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(change)) => (StatusCode::OK, Json(change)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "No pending email change"),
+        Err(e) => {
+            error!(error = %e, "Failed to load email change");
+            database_error()
+        }
+    }
+}
+
+// Handler: Cancel Email Change
+pub async fn cancel_email_change(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<bool, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let cancelled: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE email_changes SET status = 'cancelled', resolved_at = NOW()
+            WHERE user_address = $1 AND status = 'pending'
+            RETURNING id
+            "#,
+        )
+        .bind(&user_address)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(id) = cancelled {
+            record_audit(
+                &mut *tx,
+                &user_address,
+                "email_change.cancelled",
+                &id.to_string(),
+                serde_json::json!({}),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(cancelled.is_some())
+    }
+    .await;
+
+    match result {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => refused(StatusCode::NOT_FOUND, "No pending email change"),
+        Err(e) => {
+            error!(error = %e, "Failed to cancel email change");
+            database_error()
+        }
+    }
+}
+
+// Handler: Confirm Email Change
+pub async fn confirm_email_change(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ConfirmEmailChangeRequest>,
+) -> impl IntoResponse {
+    let token_hash = hash_token(payload.token.trim());
+
+    let outcome = async {
+        let mut tx = state.db_pool.begin().await?;
+        let change = sqlx::query_as::<_, EmailChange>(&format!(
+            r#"
+            SELECT {CHANGE_COLUMNS} FROM email_changes
+            WHERE (old_token_hash = $1 OR new_token_hash = $1)
+              AND status = 'pending'
+              AND expires_at > NOW()
+            FOR UPDATE
+            "#
+        ))
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(change) = change else {
+            return Ok(Outcome::Refused(
+                StatusCode::UNAUTHORIZED,
+                "Invalid or expired confirmation link",
+            ));
+        };
+
+        let column = if change.old_token_hash == token_hash {
+            "old_confirmed_at"
+        } else {
+            "new_confirmed_at"
+        };
+        let mut change = sqlx::query_as::<_, EmailChange>(&format!(
+            "UPDATE email_changes SET {column} = COALESCE({column}, NOW()) WHERE id = $1 RETURNING {CHANGE_COLUMNS}"
+        ))
+        .bind(change.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if change.is_confirmed() {
+            let applied = sqlx::query(
+                r#"
+                UPDATE notification_preferences SET email = $3, updated_at = NOW()
+                WHERE user_address = $1 AND email = $2
+                "#,
+            )
+            .bind(&change.user_address)
+            .bind(&change.old_email)
+            .bind(&change.new_email)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+                > 0;
+            let status = if applied { "completed" } else { "cancelled" };
+            change = sqlx::query_as::<_, EmailChange>(&format!(
+                "UPDATE email_changes SET status = $2, resolved_at = NOW() WHERE id = $1 RETURNING {CHANGE_COLUMNS}"
+            ))
+            .bind(change.id)
+            .bind(status)
+            .fetch_one(&mut *tx)
+            .await?;
+            if !applied {
+                tx.commit().await?;
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "The email on file changed since this request was made",
+                ));
+            }
+
+            if change.new_email.is_none() {
+                cancel_queued_email(&mut tx, &change.user_address).await?;
+            }
+            record_audit(
+                &mut *tx,
+                &change.user_address,
+                "email_change.completed",
+                &change.id.to_string(),
+                serde_json::json!({ "removal": change.new_email.is_none() }),
+            )
+            .await?;
+            create_notification(
+                &mut *tx,
+                &change.user_address,
+                "email_changed",
+                "Email updated",
+                match change.new_email {
+                    Some(_) => "Notifications are now sent to your new email address.",
+                    None => "Your email was removed. Notifications are shown in the app only.",
+                },
+                serde_json::json!({ "email_change_id": change.id }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Outcome::Done(change))
+    }
+    .await;
+
+    let change = match outcome {
+        Ok(Outcome::Done(change)) => change,
+        Ok(Outcome::Refused(status, message)) => return refused(status, message),
+        Ok(Outcome::Reauth(e)) => return e.into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to confirm email change");
+            return database_error();
+        }
+    };
+
+    if change.status == "completed" {
+        let body = "The email on your InheritX account was changed. If you did not make this \
+                    change, contact support immediately.";
+        if let Err(e) = state
+            .mailer
+            .send(&change.old_email, "Your InheritX email was changed", body)
+            .await
+        {
+            warn!(error = %e, "Failed to notify the previous address of an email change");
+        }
+        info!(user_address = %change.user_address, email_change_id = %change.id, "Email change completed");
+    }
+    (StatusCode::OK, Json(change)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(new_email: Option<&str>) -> EmailChange {
+        let now = Utc::now();
+        EmailChange {
+            id: Uuid::new_v4(),
+            user_address: "GOWNER".to_string(),
+            old_email: "old@example.com".to_string(),
+            new_email: new_email.map(str::to_string),
+            old_token_hash: hash_token("old"),
+            new_token_hash: new_email.map(|_| hash_token("new")),
+            old_confirmed_at: None,
+            new_confirmed_at: None,
+            status: "pending".to_string(),
+            expires_at: now + Duration::hours(EMAIL_CHANGE_TTL_HOURS),
+            created_at: now,
+            resolved_at: None,
+        }
+    }
+
+    #[test]
+    fn changes_need_both_addresses_and_removals_only_the_current_one() {
+        let mut swap = change(Some("new@example.com"));
+        swap.old_confirmed_at = Some(Utc::now());
+        assert!(!swap.is_confirmed());
+        swap.new_confirmed_at = Some(Utc::now());
+        assert!(swap.is_confirmed());
+
+        let mut removal = change(None);
+        assert!(!removal.is_confirmed());
+        removal.old_confirmed_at = Some(Utc::now());
+        assert!(removal.is_confirmed());
+    }
+
+    #[test]
+    fn tokens_are_random_and_links_carry_them() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_eq!(
+            confirm_link("https://app.example.com/confirm-email", "abc"),
+            "https://app.example.com/confirm-email?token=abc"
+        );
+        assert_eq!(
+            confirm_link("https://app.example.com/confirm?lang=en", "abc"),
+            "https://app.example.com/confirm?lang=en&token=abc"
+        );
+    }
+}
@@ -0,0 +1,663 @@
+//! Emergency contacts alerted when an owner goes quiet.
+//!
+//! Each contact's email and phone are verified with one-time codes before
+//! they receive alerts. Verified contacts are notified when proof-of-life
+//! escalation reaches the contact stage and when a plan becomes claimable.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::mailer::{is_plausible_email, Mailer};
+use crate::sms::{is_e164, SmsClient};
+use crate::templates::{render_builtin, render_for, TemplateKey};
+use crate::user_profiles::DEFAULT_LANGUAGE;
+
+pub const MAX_CONTACTS_PER_USER: i64 = 5;
+const MAX_NAME_LEN: usize = 80;
+
+const CONTACT_COLUMNS: &str = "id, user_address, name, relationship, email, phone, \
+     email_verified_at, phone_verified_at, email_code_hash, phone_code_hash, code_expires_at, \
+     last_alerted_at, created_at, updated_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct EmergencyContact {
+    pub id: Uuid,
+    pub user_address: String,
+    pub name: String,
+    pub relationship: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub phone_verified_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub email_code_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub phone_code_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub code_expires_at: Option<DateTime<Utc>>,
+    pub last_alerted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EmergencyContact {
+    pub fn is_verified(&self) -> bool {
+        self.email_verified_at.is_some() || self.phone_verified_at.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContactChannel {
+    Email,
+    Phone,
+}
+
+impl ContactChannel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Phone => "phone",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContactRequest {
+    pub name: Option<String>,
+    pub relationship: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyContactRequest {
+    pub channel: ContactChannel,
+    pub code: String,
+}
+
+/// Events that are routed to an owner's verified emergency contacts.
+#[derive(Debug, Clone, Copy)]
+pub enum ContactAlert {
+    CheckInOverdue { last_check_in_at: DateTime<Utc> },
+    PlanClaimable { plan_id: Uuid },
+}
+
+impl ContactAlert {
+    fn subject(&self) -> &'static str {
+        match self {
+            Self::CheckInOverdue { .. } => "An InheritX user has not checked in",
+            Self::PlanClaimable { .. } => "An InheritX inheritance plan is now claimable",
+        }
+    }
+
+    fn message(&self, owner_address: &str) -> String {
+        match self {
+            Self::CheckInOverdue { last_check_in_at } => format!(
+                "You are an emergency contact for wallet {owner_address}, which has not checked in \
+                 since {}. If you can reach them, please ask them to check in with InheritX.",
+                last_check_in_at.format("%Y-%m-%d")
+            ),
+            Self::PlanClaimable { plan_id } => format!(
+                "You are an emergency contact for wallet {owner_address}. Their inheritance plan \
+                 {plan_id} is now claimable by its beneficiaries because the owner has been inactive."
+            ),
+        }
+    }
+}
+
+/// Delivers verification codes and alerts to emergency contacts.
+pub struct ContactNotifier {
+    mailer: Arc<Mailer>,
+    sms: Arc<SmsClient>,
+}
+
+impl ContactNotifier {
+    pub fn new(mailer: Arc<Mailer>, sms: Arc<SmsClient>) -> Self {
+        Self { mailer, sms }
+    }
+
+    /// Sends a verification code in the owner's preferred language.
+    async fn send_code(
+        &self,
+        db: &PgPool,
+        owner_address: &str,
+        channel: ContactChannel,
+        to: &str,
+        code: &str,
+        ttl_minutes: i64,
+    ) {
+        let minutes = ttl_minutes.to_string();
+        let vars = [("code", code), ("minutes", minutes.as_str())];
+        let rendered = match db.acquire().await {
+            Ok(mut conn) => {
+                render_for(
+                    &mut conn,
+                    owner_address,
+                    TemplateKey::VerificationCode,
+                    &vars,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        }
+        .unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to load verification template; using English");
+            render_builtin(TemplateKey::VerificationCode, DEFAULT_LANGUAGE, &vars)
+        });
+        let result = match channel {
+            ContactChannel::Email => self
+                .mailer
+                .send(to, &rendered.subject, &rendered.body)
+                .await
+                .map_err(|e| e.to_string()),
+            ContactChannel::Phone => self
+                .sms
+                .send(to, &rendered.body)
+                .await
+                .map_err(|e| e.to_string()),
+        };
+        if let Err(e) = result {
+            warn!(channel = channel.as_str(), error = %e, "Failed to deliver contact verification code");
+        }
+    }
+
+    /// Sends `alert` over every verified channel of `owner_address`'s
+    /// contacts and records when they were alerted. Returns the number of
+    /// contacts alerted.
+    pub async fn alert(
+        &self,
+        db: &PgPool,
+        owner_address: &str,
+        alert: ContactAlert,
+    ) -> Result<usize, sqlx::Error> {
+        let contacts = sqlx::query_as::<_, EmergencyContact>(&format!(
+            r#"
+            SELECT {CONTACT_COLUMNS}
+            FROM emergency_contacts
+            WHERE user_address = $1
+              AND (email_verified_at IS NOT NULL OR phone_verified_at IS NOT NULL)
+            "#
+        ))
+        .bind(owner_address)
+        .fetch_all(db)
+        .await?;
+
+        let message = alert.message(owner_address);
+        for contact in &contacts {
+            if let (Some(email), Some(_)) = (&contact.email, contact.email_verified_at) {
+                if let Err(e) = self.mailer.send(email, alert.subject(), &message).await {
+                    warn!(contact_id = %contact.id, error = %e, "Failed to email emergency contact");
+                }
+            }
+            if let (Some(phone), Some(_)) = (&contact.phone, contact.phone_verified_at) {
+                if let Err(e) = self.sms.send(phone, &message).await {
+                    warn!(contact_id = %contact.id, error = %e, "Failed to text emergency contact");
+                }
+            }
+        }
+
+        let ids: Vec<Uuid> = contacts.iter().map(|c| c.id).collect();
+        if !ids.is_empty() {
+            sqlx::query("UPDATE emergency_contacts SET last_alerted_at = NOW() WHERE id = ANY($1)")
+                .bind(&ids)
+                .execute(db)
+                .await?;
+            info!(owner_address = %owner_address, contacts = ids.len(), "Emergency contacts alerted");
+        }
+        Ok(ids.len())
+    }
+}
+
+fn generate_code() -> String {
+    format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+}
+
+fn hash_code(user_address: &str, channel: ContactChannel, code: &str) -> String {
+    let digest = Sha256::digest(format!("{user_address}:{}:{code}", channel.as_str()).as_bytes());
+    hex::encode(digest)
+}
+
+fn normalize(value: Option<&String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn validate_contact(
+    name: &str,
+    email: Option<&str>,
+    phone: Option<&str>,
+) -> Result<(), &'static str> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err("Name must be between 1 and 80 characters");
+    }
+    if email.is_none() && phone.is_none() {
+        return Err("An email address or phone number is required");
+    }
+    if email.is_some_and(|e| !is_plausible_email(e)) {
+        return Err("Invalid email address");
+    }
+    if phone.is_some_and(|p| !is_e164(p)) {
+        return Err("Phone number must be in E.164 format (e.g. +2348012345678)");
+    }
+    Ok(())
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Emergency contact not found" })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+async fn load_contact(
+    db: &PgPool,
+    id: Uuid,
+    user_address: &str,
+) -> Result<Option<EmergencyContact>, sqlx::Error> {
+    sqlx::query_as::<_, EmergencyContact>(&format!(
+        "SELECT {CONTACT_COLUMNS} FROM emergency_contacts WHERE id = $1 AND user_address = $2"
+    ))
+    .bind(id)
+    .bind(user_address)
+    .fetch_optional(db)
+    .await
+}
+
+// Handler: List Emergency Contacts
+pub async fn list_contacts(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, EmergencyContact>(&format!(
+        "SELECT {CONTACT_COLUMNS} FROM emergency_contacts WHERE user_address = $1 ORDER BY created_at"
+    ))
+    .bind(&user_address)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (StatusCode::OK, Json(rows)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list emergency contacts");
+            database_error()
+        }
+    }
+}
+
+// Handler: Add Emergency Contact
+pub async fn create_contact(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<ContactRequest>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let name = normalize(payload.name.as_ref()).unwrap_or_default();
+    let email = normalize(payload.email.as_ref());
+    let phone = normalize(payload.phone.as_ref());
+    if let Err(message) = validate_contact(&name, email.as_deref(), phone.as_deref()) {
+        return bad_request(message);
+    }
+
+    let existing: i64 =
+        match sqlx::query_scalar("SELECT COUNT(*) FROM emergency_contacts WHERE user_address = $1")
+            .bind(&user_address)
+            .fetch_one(&state.db_pool)
+            .await
+        {
+            Ok(count) => count,
+            Err(e) => {
+                error!(error = %e, "Failed to count emergency contacts");
+                return database_error();
+            }
+        };
+    if existing >= MAX_CONTACTS_PER_USER {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": format!("At most {MAX_CONTACTS_PER_USER} emergency contacts are allowed")
+            })),
+        )
+            .into_response();
+    }
+
+    let code_ttl_minutes = state
+        .system_settings
+        .get()
+        .await
+        .verification_code_ttl_minutes();
+    let email_code = email.as_ref().map(|_| generate_code());
+    let phone_code = phone.as_ref().map(|_| generate_code());
+
+    let result = sqlx::query_as::<_, EmergencyContact>(&format!(
+        r#"
+        INSERT INTO emergency_contacts
+            (user_address, name, relationship, email, phone,
+             email_code_hash, phone_code_hash, code_expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW() + ($8 * INTERVAL '1 minute'))
+        RETURNING {CONTACT_COLUMNS}
+        "#
+    ))
+    .bind(&user_address)
+    .bind(&name)
+    .bind(normalize(payload.relationship.as_ref()))
+    .bind(&email)
+    .bind(&phone)
+    .bind(
+        email_code
+            .as_deref()
+            .map(|c| hash_code(&user_address, ContactChannel::Email, c)),
+    )
+    .bind(
+        phone_code
+            .as_deref()
+            .map(|c| hash_code(&user_address, ContactChannel::Phone, c)),
+    )
+    .bind(code_ttl_minutes as f64)
+    .fetch_one(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(contact) => {
+            if let (Some(to), Some(code)) = (&contact.email, &email_code) {
+                state
+                    .contacts
+                    .send_code(
+                        &state.db_pool,
+                        &user_address,
+                        ContactChannel::Email,
+                        to,
+                        code,
+                        code_ttl_minutes,
+                    )
+                    .await;
+            }
+            if let (Some(to), Some(code)) = (&contact.phone, &phone_code) {
+                state
+                    .contacts
+                    .send_code(
+                        &state.db_pool,
+                        &user_address,
+                        ContactChannel::Phone,
+                        to,
+                        code,
+                        code_ttl_minutes,
+                    )
+                    .await;
+            }
+            (StatusCode::CREATED, Json(contact)).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to save emergency contact");
+            database_error()
+        }
+    }
+}
+
+// Handler: Update Emergency Contact
+pub async fn update_contact(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ContactRequest>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let current = match load_contact(&state.db_pool, id, &user_address).await {
+        Ok(Some(contact)) => contact,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            error!(error = %e, "Failed to load emergency contact");
+            return database_error();
+        }
+    };
+
+    let name = normalize(payload.name.as_ref()).unwrap_or_else(|| current.name.clone());
+    let email = normalize(payload.email.as_ref()).or_else(|| current.email.clone());
+    let phone = normalize(payload.phone.as_ref()).or_else(|| current.phone.clone());
+    if let Err(message) = validate_contact(&name, email.as_deref(), phone.as_deref()) {
+        return bad_request(message);
+    }
+
+    // Changed channels lose their verification and get a fresh code.
+    let code_ttl_minutes = state
+        .system_settings
+        .get()
+        .await
+        .verification_code_ttl_minutes();
+    let email_code = (email != current.email).then(generate_code);
+    let phone_code = (phone != current.phone).then(generate_code);
+
+    let result = sqlx::query_as::<_, EmergencyContact>(&format!(
+        r#"
+        UPDATE emergency_contacts
+        SET name = $2,
+            relationship = COALESCE($3, relationship),
+            email = $4,
+            phone = $5,
+            email_verified_at = CASE WHEN $6::text IS NULL THEN email_verified_at END,
+            email_code_hash = COALESCE($6, email_code_hash),
+            phone_verified_at = CASE WHEN $7::text IS NULL THEN phone_verified_at END,
+            phone_code_hash = COALESCE($7, phone_code_hash),
+            code_expires_at = CASE
+                WHEN $6::text IS NULL AND $7::text IS NULL THEN code_expires_at
+                ELSE NOW() + ($8 * INTERVAL '1 minute')
+            END,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING {CONTACT_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(&name)
+    .bind(normalize(payload.relationship.as_ref()))
+    .bind(&email)
+    .bind(&phone)
+    .bind(
+        email_code
+            .as_deref()
+            .map(|c| hash_code(&user_address, ContactChannel::Email, c)),
+    )
+    .bind(
+        phone_code
+            .as_deref()
+            .map(|c| hash_code(&user_address, ContactChannel::Phone, c)),
+    )
+    .bind(code_ttl_minutes as f64)
+    .fetch_one(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(contact) => {
+            if let (Some(to), Some(code)) = (&contact.email, &email_code) {
+                state
+                    .contacts
+                    .send_code(
+                        &state.db_pool,
+                        &user_address,
+                        ContactChannel::Email,
+                        to,
+                        code,
+                        code_ttl_minutes,
+                    )
+                    .await;
+            }
+            if let (Some(to), Some(code)) = (&contact.phone, &phone_code) {
+                state
+                    .contacts
+                    .send_code(
+                        &state.db_pool,
+                        &user_address,
+                        ContactChannel::Phone,
+                        to,
+                        code,
+                        code_ttl_minutes,
+                    )
+                    .await;
+            }
+            (StatusCode::OK, Json(contact)).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to update emergency contact");
+            database_error()
+        }
+    }
+}
+
+// Handler: Remove Emergency Contact
+pub async fn delete_contact(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query("DELETE FROM emergency_contacts WHERE id = $1 AND user_address = $2")
+        .bind(id)
+        .bind(&user_address)
+        .execute(&state.db_pool)
+        .await
+    {
+        Ok(result) if result.rows_affected() > 0 => StatusCode::NO_CONTENT.into_response(),
+        Ok(_) => not_found(),
+        Err(e) => {
+            error!(error = %e, "Failed to delete emergency contact");
+            database_error()
+        }
+    }
+}
+
+// Handler: Verify Emergency Contact Channel
+pub async fn verify_contact(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<VerifyContactRequest>,
+) -> impl IntoResponse {
+    let user_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let contact = match load_contact(&state.db_pool, id, &user_address).await {
+        Ok(Some(contact)) => contact,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            error!(error = %e, "Failed to load emergency contact");
+            return database_error();
+        }
+    };
+
+    let expected = match payload.channel {
+        ContactChannel::Email => &contact.email_code_hash,
+        ContactChannel::Phone => &contact.phone_code_hash,
+    };
+    let expired = contact.code_expires_at.is_none_or(|at| at <= Utc::now());
+    let provided = hash_code(&user_address, payload.channel, payload.code.trim());
+    if expected.as_deref() != Some(provided.as_str()) || expired {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "error": "Invalid or expired verification code" })),
+        )
+            .into_response();
+    }
+
+    let query = match payload.channel {
+        ContactChannel::Email => {
+            "UPDATE emergency_contacts SET email_verified_at = NOW(), email_code_hash = NULL, updated_at = NOW() WHERE id = $1"
+        }
+        ContactChannel::Phone => {
+            "UPDATE emergency_contacts SET phone_verified_at = NOW(), phone_code_hash = NULL, updated_at = NOW() WHERE id = $1"
+        }
+    };
+    if let Err(e) = sqlx::query(query).bind(id).execute(&state.db_pool).await {
+        error!(error = %e, "Failed to mark emergency contact verified");
+        return database_error();
+    }
+
+    match load_contact(&state.db_pool, id, &user_address).await {
+        Ok(Some(contact)) => (StatusCode::OK, Json(contact)).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(error = %e, "Failed to reload emergency contact");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_six_digits_and_hashes_bind_channel() {
+        let code = generate_code();
+        assert_eq!(code.len(), 6);
+        assert!(code.bytes().all(|b| b.is_ascii_digit()));
+
+        assert_ne!(
+            hash_code("GOWNER", ContactChannel::Email, &code),
+            hash_code("GOWNER", ContactChannel::Phone, &code)
+        );
+        assert_ne!(
+            hash_code("GOWNER", ContactChannel::Email, &code),
+            hash_code("GOTHER", ContactChannel::Email, &code)
+        );
+    }
+
+    #[test]
+    fn contacts_need_a_valid_channel() {
+        assert!(validate_contact("Ada", Some("ada@example.com"), None).is_ok());
+        assert!(validate_contact("Ada", None, Some("+15550100123")).is_ok());
+        assert!(validate_contact("Ada", None, None).is_err());
+        assert!(validate_contact("", Some("ada@example.com"), None).is_err());
+        assert!(validate_contact("Ada", Some("ada"), None).is_err());
+        assert!(validate_contact("Ada", None, Some("5550100")).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! CSV exports of a user's own plans and claims.
+//!
+//! Rows are streamed straight from the database cursor and written out in
+//! chunks, so the response uses chunked transfer encoding and a complete
+//! history never has to fit in memory or be paged through. The filters that
+//! were applied are echoed in `X-Export-Filters` so a saved file can be
+//! matched to the request that produced it. Both exports can be narrowed
+//! to plans carrying given tags.
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::plan_tags::parse_tag_filter;
+use crate::reports::csv_cell;
+use crate::telemetry;
+
+/// Rows written per chunk sent to the client.
+const CHUNK_ROWS: usize = 500;
+/// Chunks buffered ahead of a slow client before the cursor waits.
+const CHANNEL_DEPTH: usize = 4;
+const CLAIM_STATUSES: [&str; 5] = ["pending", "in_review", "executed", "cancelled", "failed"];
+
+const PLAN_HEADER: &[&str] = &[
+    "plan_id",
+    "role",
+    "token_address",
+    "amount",
+    "status",
+    "is_active",
+    "grace_period_seconds",
+    "last_ping_at",
+    "claimable_at",
+    "beneficiaries",
+    "created_at",
+];
+
+const PLAN_EXPORT_SQL: &str = r#"
+    SELECT p.id::text,
+           CASE WHEN p.owner_address = $1 THEN 'owner' ELSE 'co_owner' END,
+           p.token_address,
+           p.amount::text,
+           p.status,
+           p.is_active::text,
+           p.grace_period_seconds::text,
+           to_char(to_timestamp(p.last_ping) AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"'),
+           to_char(to_timestamp(p.last_ping + p.grace_period_seconds) AT TIME ZONE 'UTC',
+                   'YYYY-MM-DD"T"HH24:MI:SS"Z"'),
+           (SELECT COUNT(*) FROM beneficiaries b WHERE b.plan_id = p.id)::text,
+           to_char(p.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"')
+    FROM plans p
+    WHERE (p.owner_address = $1
+           OR EXISTS (SELECT 1 FROM plan_co_owners o
+                      WHERE o.plan_id = p.id AND o.owner_address = $1
+                        AND o.status = 'accepted'))
+      AND ($2::text IS NULL OR p.status = $2)
+      AND ($3::timestamptz IS NULL OR p.created_at >= $3)
+      AND ($4::timestamptz IS NULL OR p.created_at < $4)
+      AND ($5::text[] IS NULL
+           OR (SELECT COUNT(*) FROM plan_tags t
+               WHERE t.plan_id = p.id AND t.tag = ANY($5)) = cardinality($5))
+    ORDER BY p.created_at, p.id
+"#;
+
+const CLAIM_HEADER: &[&str] = &[
+    "claim_id",
+    "plan_id",
+    "role",
+    "requested_by",
+    "filed_by",
+    "status",
+    "payout_amount",
+    "execute_after",
+    "created_at",
+    "resolved_at",
+    "cancel_reason",
+    "failure_reason",
+];
+
+const CLAIM_EXPORT_SQL: &str = r#"
+    SELECT c.id::text,
+           c.plan_id::text,
+           CASE WHEN c.requested_by = $1 THEN 'beneficiary'
+                WHEN c.filed_by = $1 THEN 'executor'
+                ELSE 'owner' END,
+           c.requested_by,
+           c.filed_by,
+           c.status,
+           (SELECT SUM(pay.amount) FROM payouts pay
+            WHERE c.status = 'executed' AND pay.plan_id = c.plan_id
+              AND pay.beneficiary_address = c.requested_by)::text,
+           to_char(c.execute_after AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"'),
+           to_char(c.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"'),
+           to_char(c.resolved_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"'),
+           c.cancel_reason,
+           c.failure_reason
+    FROM claim_requests c
+    JOIN plans p ON p.id = c.plan_id
+    WHERE (c.requested_by = $1
+           OR c.filed_by = $1
+           OR p.owner_address = $1
+           OR EXISTS (SELECT 1 FROM plan_co_owners o
+                      WHERE o.plan_id = p.id AND o.owner_address = $1
+                        AND o.status = 'accepted'))
+      AND ($2::text IS NULL OR c.status = $2)
+      AND ($3::timestamptz IS NULL OR c.created_at >= $3)
+      AND ($4::timestamptz IS NULL OR c.created_at < $4)
+      AND ($5::text[] IS NULL
+           OR (SELECT COUNT(*) FROM plan_tags t
+               WHERE t.plan_id = c.plan_id AND t.tag = ANY($5)) = cardinality($5))
+    ORDER BY c.created_at, c.id
+"#;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportQuery {
+    pub status: Option<String>,
+    /// Rows created at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Rows created before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Comma-separated plan tags; only plans with every one are exported.
+    pub tag: Option<String>,
+}
+
+impl ExportQuery {
+    /// `key=value` pairs of the filters in use, or `none`.
+    pub fn describe(&self) -> String {
+        let mut applied = Vec::new();
+        if let Some(status) = &self.status {
+            applied.push(format!("status={status}"));
+        }
+        if let Some(since) = self.since {
+            applied.push(format!("since={}", since.to_rfc3339()));
+        }
+        if let Some(until) = self.until {
+            applied.push(format!("until={}", until.to_rfc3339()));
+        }
+        if let Some(tag) = &self.tag {
+            applied.push(format!("tag={tag}"));
+        }
+        if applied.is_empty() {
+            "none".to_string()
+        } else {
+            applied.join("; ")
+        }
+    }
+
+    fn validate(&mut self, statuses: Option<&[&str]>) -> Result<(), String> {
+        self.status = self
+            .status
+            .take()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        match (&mut self.status, statuses) {
+            (Some(status), Some(allowed)) => {
+                *status = status.to_ascii_lowercase();
+                if !allowed.contains(&status.as_str()) {
+                    return Err(format!("status must be one of {}", allowed.join(", ")));
+                }
+            }
+            (Some(status), None) => *status = status.to_ascii_uppercase(),
+            (None, _) => {}
+        }
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since >= until {
+                return Err("since must be before until".to_string());
+            }
+        }
+        self.tag = parse_tag_filter(self.tag.as_deref())?.map(|tags| tags.join(","));
+        Ok(())
+    }
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// Runs `sql` for `caller` and streams the rows as CSV under `header`.
+fn stream_csv(
+    db: PgPool,
+    sql: &'static str,
+    header: &'static [&'static str],
+    caller: String,
+    query: ExportQuery,
+) -> Body {
+    let (tx, rx) = mpsc::channel::<Result<Vec<u8>, std::io::Error>>(CHANNEL_DEPTH);
+
+    telemetry::spawn(async move {
+        let written = async {
+            let mut rows = sqlx::query(sql)
+                .bind(&caller)
+                .bind(&query.status)
+                .bind(query.since)
+                .bind(query.until)
+                .bind(
+                    query
+                        .tag
+                        .as_deref()
+                        .map(|tags| tags.split(',').collect::<Vec<_>>()),
+                )
+                .fetch(&db);
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(header)?;
+            let mut count = 0usize;
+            while let Some(row) = rows.try_next().await? {
+                let cells = (0..header.len())
+                    .map(|i| row.try_get::<Option<String>, _>(i))
+                    .collect::<Result<Vec<_>, _>>()?;
+                writer.write_record(
+                    cells
+                        .iter()
+                        .map(|cell| csv_cell(cell.as_deref().unwrap_or(""))),
+                )?;
+                count += 1;
+                if count.is_multiple_of(CHUNK_ROWS) {
+                    let chunk =
+                        std::mem::replace(&mut writer, csv::Writer::from_writer(Vec::new()))
+                            .into_inner()?;
+                    if tx.send(Ok(chunk)).await.is_err() {
+                        // The client went away.
+                        return Ok(count);
+                    }
+                }
+            }
+            let _ = tx.send(Ok(writer.into_inner()?)).await;
+            Ok::<_, anyhow::Error>(count)
+        }
+        .await;
+
+        match written {
+            Ok(count) => info!(rows = count, "CSV export streamed"),
+            Err(e) => {
+                error!(error = %e, "CSV export failed mid-stream");
+                let _ = tx.send(Err(std::io::Error::other("export failed"))).await;
+            }
+        }
+    });
+
+    Body::from_stream(futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }))
+}
+
+fn csv_response(body: Body, name: &str, query: &ExportQuery) -> Response {
+    let disposition = format!(
+        "attachment; filename=\"{name}-{}.csv\"",
+        Utc::now().format("%Y%m%d")
+    );
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+            (
+                header::HeaderName::from_static("x-export-filters"),
+                query.describe(),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+// Handler: Export Plans CSV
+pub async fn export_plans_csv(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Query(mut query): Query<ExportQuery>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(message) = query.validate(None) {
+        return refused(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let body = stream_csv(
+        state.db_pool.clone(),
+        PLAN_EXPORT_SQL,
+        PLAN_HEADER,
+        caller,
+        query.clone(),
+    );
+    csv_response(body, "plans", &query)
+}
+
+// Handler: Export Claims CSV
+pub async fn export_claims_csv(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Query(mut query): Query<ExportQuery>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(message) = query.validate(Some(&CLAIM_STATUSES)) {
+        return refused(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let body = stream_csv(
+        state.db_pool.clone(),
+        CLAIM_EXPORT_SQL,
+        CLAIM_HEADER,
+        caller,
+        query.clone(),
+    );
+    csv_response(body, "claims", &query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_are_normalized_and_described() {
+        let mut query = ExportQuery {
+            status: Some(" Executed ".to_string()),
+            since: Some("2026-01-01T00:00:00Z".parse().unwrap()),
+            until: None,
+            tag: Some("Trust,family".to_string()),
+        };
+        query.validate(Some(&CLAIM_STATUSES)).unwrap();
+        assert_eq!(
+            query.describe(),
+            "status=executed; since=2026-01-01T00:00:00+00:00; tag=family,trust"
+        );
+        assert_eq!(ExportQuery::default().describe(), "none");
+
+        let mut unknown = ExportQuery {
+            status: Some("paid".to_string()),
+            ..ExportQuery::default()
+        };
+        assert!(unknown.validate(Some(&CLAIM_STATUSES)).is_err());
+
+        let mut plans = ExportQuery {
+            status: Some("active".to_string()),
+            ..ExportQuery::default()
+        };
+        plans.validate(None).unwrap();
+        assert_eq!(plans.status.as_deref(), Some("ACTIVE"));
+    }
+
+    #[test]
+    fn inverted_range_is_refused() {
+        let mut query = ExportQuery {
+            since: Some("2026-02-01T00:00:00Z".parse().unwrap()),
+            until: Some("2026-01-01T00:00:00Z".parse().unwrap()),
+            ..ExportQuery::default()
+        };
+        assert!(query.validate(None).is_err());
+    }
+}
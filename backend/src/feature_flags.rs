@@ -0,0 +1,464 @@
+//! Feature flags for soft-launching endpoints without a redeploy.
+//!
+//! A flag lives in `feature_flags` and is off unless it has a row with
+//! `enabled` set. An enabled flag can be limited to some `APP_ENV` values,
+//! and within those it is on for users on its allowlist plus
+//! `rollout_percent` of everyone else. Each user's bucket is a hash of the
+//! flag key and their id, so raising the percentage only ever adds users.
+//! Handlers gate on a flag with the [`Feature`] extractor, or call
+//! [`FeatureFlagCache::is_enabled`] when only part of a request is gated.
+//! Flags are cached like [`crate::system_settings`].
+
+use axum::{
+    extract::{FromRequestParts, Path, State},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::config::Environment;
+
+const DEFAULT_CACHE_TTL_SECS: u64 = 30;
+const MAX_ALLOWLIST_LEN: usize = 1_000;
+
+/// A flag known to the code, for use with [`Feature`].
+pub trait FeatureKey {
+    const KEY: &'static str;
+}
+
+/// Plans paid out in more than one installment.
+pub struct InstallmentPlans;
+
+impl FeatureKey for InstallmentPlans {
+    const KEY: &'static str = "installment_plans";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct FeatureFlag {
+    pub key: String,
+    pub description: String,
+    pub enabled: bool,
+    pub rollout_percent: i16,
+    pub allowlist: Vec<String>,
+    pub environments: Vec<String>,
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FeatureFlag {
+    /// Whether the flag is on for `subject` in `environment`. Requests
+    /// without a user only get fully rolled out flags.
+    pub fn evaluate(&self, environment: Environment, subject: Option<&str>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if !self.environments.is_empty()
+            && !self.environments.iter().any(|e| e == environment.as_str())
+        {
+            return false;
+        }
+        if self.rollout_percent >= 100 {
+            return true;
+        }
+        let Some(subject) = subject else {
+            return false;
+        };
+        self.allowlist.iter().any(|s| s == subject)
+            || rollout_bucket(&self.key, subject) < self.rollout_percent.max(0) as u32
+    }
+}
+
+/// Stable bucket in `0..100` for a user under a flag.
+pub fn rollout_bucket(key: &str, subject: &str) -> u32 {
+    let digest = Sha256::digest(format!("{key}:{subject}").as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % 100
+}
+
+type FlagMap = HashMap<String, FeatureFlag>;
+
+/// In-process cache of all flags.
+pub struct FeatureFlagCache {
+    db: PgPool,
+    environment: Environment,
+    ttl: Duration,
+    cached: RwLock<Option<(Instant, Arc<FlagMap>)>>,
+}
+
+impl FeatureFlagCache {
+    pub fn new(db: PgPool, environment: Environment, ttl: Duration) -> Self {
+        Self {
+            db,
+            environment,
+            ttl,
+            cached: RwLock::new(None),
+        }
+    }
+
+    /// TTL from `FEATURE_FLAGS_CACHE_TTL_SECS`.
+    pub fn ttl_from_env() -> Duration {
+        Duration::from_secs(
+            std::env::var("FEATURE_FLAGS_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| u64::from_str(&v).ok())
+                .unwrap_or(DEFAULT_CACHE_TTL_SECS),
+        )
+    }
+
+    /// Whether `key` is on for `subject`. If flags cannot be loaded the last
+    /// snapshot is used, or every flag is off when there is none.
+    pub async fn is_enabled(&self, key: &str, subject: Option<&str>) -> bool {
+        self.flags()
+            .await
+            .get(key)
+            .is_some_and(|flag| flag.evaluate(self.environment, subject))
+    }
+
+    async fn flags(&self) -> Arc<FlagMap> {
+        if let Some((loaded_at, flags)) = self.cached.read().await.as_ref() {
+            if loaded_at.elapsed() < self.ttl {
+                return flags.clone();
+            }
+        }
+
+        let mut cached = self.cached.write().await;
+        if let Some((loaded_at, flags)) = cached.as_ref() {
+            if loaded_at.elapsed() < self.ttl {
+                return flags.clone();
+            }
+        }
+        match load_flags(&self.db).await {
+            Ok(rows) => {
+                let flags = Arc::new(
+                    rows.into_iter()
+                        .map(|flag| (flag.key.clone(), flag))
+                        .collect::<FlagMap>(),
+                );
+                *cached = Some((Instant::now(), flags.clone()));
+                flags
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to load feature flags; using last known values");
+                cached
+                    .as_ref()
+                    .map(|(_, flags)| flags.clone())
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Drops the snapshot so the next read goes to the database.
+    pub async fn invalidate(&self) {
+        *self.cached.write().await = None;
+    }
+}
+
+const FLAG_COLUMNS: &str =
+    "key, description, enabled, rollout_percent, allowlist, environments, updated_by, updated_at";
+
+async fn load_flags(db: &PgPool) -> Result<Vec<FeatureFlag>, sqlx::Error> {
+    sqlx::query_as(&format!("SELECT {FLAG_COLUMNS} FROM feature_flags"))
+        .fetch_all(db)
+        .await
+}
+
+/// Extractor that answers `404` unless flag `F` is on for the caller.
+/// Place it after the auth middleware so the caller is known; wallets are
+/// matched by their `G...` address.
+pub struct Feature<F>(PhantomData<F>);
+
+impl<F: FeatureKey> FromRequestParts<Arc<AppState>> for Feature<F> {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let subject = parts.extensions.get::<UserContext>().map(|user| {
+            user.wallet_address()
+                .unwrap_or_else(|| user.user_id.clone())
+        });
+        if state
+            .feature_flags
+            .is_enabled(F::KEY, subject.as_deref())
+            .await
+        {
+            Ok(Self(PhantomData))
+        } else {
+            Err(refused(StatusCode::NOT_FOUND, "Not found"))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFeatureFlagRequest {
+    #[serde(default)]
+    pub description: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub rollout_percent: i16,
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    #[serde(default)]
+    pub environments: Vec<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteFeatureFlagRequest {
+    pub reason: Option<String>,
+}
+
+fn validate_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 64
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: List Feature Flags (admin)
+pub async fn list_feature_flags(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match sqlx::query_as::<_, FeatureFlag>(&format!(
+        "SELECT {FLAG_COLUMNS} FROM feature_flags ORDER BY key"
+    ))
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(flags) => (StatusCode::OK, Json(flags)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list feature flags");
+            database_error()
+        }
+    }
+}
+
+// Handler: Create or Update Feature Flag (admin)
+pub async fn update_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(key): Path<String>,
+    Json(payload): Json<UpdateFeatureFlagRequest>,
+) -> impl IntoResponse {
+    if !validate_key(&key) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Flag keys are up to 64 lowercase letters, digits and underscores",
+        );
+    }
+    if !(0..=100).contains(&payload.rollout_percent) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "rollout_percent must be between 0 and 100",
+        );
+    }
+    if payload.allowlist.len() > MAX_ALLOWLIST_LEN {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "allowlist may hold at most 1000 users",
+        );
+    }
+    if let Some(unknown) = payload
+        .environments
+        .iter()
+        .find(|e| !["development", "test", "staging", "production"].contains(&e.as_str()))
+    {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            &format!("Unknown environment '{unknown}'"),
+        );
+    }
+
+    let result: Result<FeatureFlag, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let previous = sqlx::query_as::<_, FeatureFlag>(&format!(
+            "SELECT {FLAG_COLUMNS} FROM feature_flags WHERE key = $1 FOR UPDATE"
+        ))
+        .bind(&key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let flag = sqlx::query_as::<_, FeatureFlag>(&format!(
+            r#"
+            INSERT INTO feature_flags
+                (key, description, enabled, rollout_percent, allowlist, environments, updated_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (key)
+            DO UPDATE SET description = EXCLUDED.description,
+                          enabled = EXCLUDED.enabled,
+                          rollout_percent = EXCLUDED.rollout_percent,
+                          allowlist = EXCLUDED.allowlist,
+                          environments = EXCLUDED.environments,
+                          updated_by = EXCLUDED.updated_by,
+                          updated_at = NOW()
+            RETURNING {FLAG_COLUMNS}
+            "#
+        ))
+        .bind(&key)
+        .bind(&payload.description)
+        .bind(payload.enabled)
+        .bind(payload.rollout_percent)
+        .bind(&payload.allowlist)
+        .bind(&payload.environments)
+        .bind(&admin.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "feature_flag.update",
+            &key,
+            serde_json::json!({
+                "from": previous,
+                "to": flag,
+                "reason": payload.reason,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(flag)
+    }
+    .await;
+
+    match result {
+        Ok(flag) => {
+            state.feature_flags.invalidate().await;
+            info!(
+                key = %key,
+                enabled = flag.enabled,
+                rollout_percent = flag.rollout_percent,
+                admin = %admin.user_id,
+                "Feature flag updated"
+            );
+            (StatusCode::OK, Json(flag)).into_response()
+        }
+        Err(e) => {
+            error!(key = %key, error = %e, "Failed to update feature flag");
+            database_error()
+        }
+    }
+}
+
+// Handler: Delete Feature Flag (admin)
+pub async fn delete_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(key): Path<String>,
+    payload: Option<Json<DeleteFeatureFlagRequest>>,
+) -> impl IntoResponse {
+    let reason = payload.and_then(|Json(p)| p.reason);
+
+    let result: Result<Option<FeatureFlag>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let previous = sqlx::query_as::<_, FeatureFlag>(&format!(
+            "DELETE FROM feature_flags WHERE key = $1 RETURNING {FLAG_COLUMNS}"
+        ))
+        .bind(&key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(previous) = &previous {
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "feature_flag.delete",
+                &key,
+                serde_json::json!({ "from": previous, "reason": reason }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(previous)
+    }
+    .await;
+
+    match result {
+        Ok(Some(_)) => {
+            state.feature_flags.invalidate().await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Unknown feature flag"),
+        Err(e) => {
+            error!(key = %key, error = %e, "Failed to delete feature flag");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(enabled: bool, rollout_percent: i16) -> FeatureFlag {
+        FeatureFlag {
+            key: "installment_plans".to_string(),
+            description: String::new(),
+            enabled,
+            rollout_percent,
+            allowlist: vec!["GALLOWED".to_string()],
+            environments: Vec::new(),
+            updated_by: "admin".to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn disabled_flags_are_off_for_everyone() {
+        let flag = flag(false, 100);
+        assert!(!flag.evaluate(Environment::Production, Some("GALLOWED")));
+        assert!(!flag.evaluate(Environment::Production, None));
+    }
+
+    #[test]
+    fn allowlist_and_environment_targeting() {
+        let mut flag = flag(true, 0);
+        assert!(flag.evaluate(Environment::Production, Some("GALLOWED")));
+        assert!(!flag.evaluate(Environment::Production, Some("GOTHER")));
+        assert!(!flag.evaluate(Environment::Production, None));
+
+        flag.environments = vec!["staging".to_string()];
+        assert!(flag.evaluate(Environment::Staging, Some("GALLOWED")));
+        assert!(!flag.evaluate(Environment::Production, Some("GALLOWED")));
+    }
+
+    #[test]
+    fn rollout_is_stable_and_only_grows() {
+        let subjects: Vec<String> = (0..1_000).map(|i| format!("user-{i}")).collect();
+        let on_at = |percent| {
+            let flag = flag(true, percent);
+            subjects
+                .iter()
+                .filter(|s| flag.evaluate(Environment::Production, Some(s)))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let quarter = on_at(25);
+        let half = on_at(50);
+        assert!((150..350).contains(&quarter.len()));
+        assert!(quarter.iter().all(|s| half.contains(s)));
+        assert_eq!(on_at(100).len(), subjects.len());
+        assert_eq!(
+            rollout_bucket("installment_plans", "user-1"),
+            rollout_bucket("installment_plans", "user-1")
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Application-layer encryption for sensitive columns.
+//!
+//! Designated fields are sealed with AES-256-GCM before they are written
+//! and opened when they are read, so the database only holds ciphertext.
+//! Stored values look like `enc:<key id>:<base64 nonce||ciphertext>`; the
+//! key id lets old rows be read after a new key becomes current, and
+//! `inheritx-cli reencrypt-fields` moves rows onto the current key. Values
+//! without the prefix are legacy plaintext and are returned as-is.
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{AeadCore, Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+const PREFIX: &str = "enc:";
+const NONCE_LEN: usize = 12;
+
+/// A column whose values are stored encrypted. The name is bound into each
+/// ciphertext so a value cannot be moved to another column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensitiveField {
+    /// Bank details and beneficiary name for fiat payouts.
+    BeneficiaryAnchorInfo,
+    /// Seed of a local transaction signing key.
+    SignerSeed,
+    /// Uploaded claim document, base64 encoded.
+    ClaimDocument,
+}
+
+impl SensitiveField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BeneficiaryAnchorInfo => "beneficiaries.fiat_anchor_info",
+            Self::SignerSeed => "signer_keystore.seed",
+            Self::ClaimDocument => "claim_documents.content",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FieldCryptoError {
+    #[error("no encryption key with id {0}")]
+    UnknownKey(String),
+    #[error("encrypted value is malformed")]
+    Malformed,
+    #[error("encrypted value failed authentication")]
+    Decrypt,
+    #[error("encryption failed")]
+    Encrypt,
+}
+
+/// A 256-bit data key, configured as `<id>:<base64 key>`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey {
+    pub id: String,
+    key: [u8; 32],
+}
+
+impl EncryptionKey {
+    /// Generates a random key, printed by `inheritx-cli rotate-field-key`.
+    pub fn generate(id: &str) -> Self {
+        let key = Aes256Gcm::generate_key(OsRng);
+        Self {
+            id: id.to_string(),
+            key: key.into(),
+        }
+    }
+
+    /// Seals a blob, such as a backup, as `nonce || ciphertext` bound to
+    /// `aad`.
+    pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, FieldCryptoError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key))
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| FieldCryptoError::Encrypt)?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Opens a blob sealed with [`EncryptionKey::seal`].
+    pub fn open(&self, aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, FieldCryptoError> {
+        if sealed.len() <= NONCE_LEN {
+            return Err(FieldCryptoError::Malformed);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key))
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| FieldCryptoError::Decrypt)
+    }
+}
+
+impl FromStr for EncryptionKey {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (id, encoded) = value
+            .trim()
+            .split_once(':')
+            .ok_or("expected <id>:<base64 key>")?;
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("key id '{id}' must be alphanumeric"));
+        }
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("key {id}: {e}"))?;
+        let key: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| format!("key {id} must be 32 bytes"))?;
+        Ok(Self {
+            id: id.to_string(),
+            key,
+        })
+    }
+}
+
+impl fmt::Display for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}",
+            self.id,
+            base64::engine::general_purpose::STANDARD.encode(self.key)
+        )
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionKey")
+            .field("id", &self.id)
+            .field("key", &"[redacted]")
+            .finish()
+    }
+}
+
+/// Source of data keys, so keys can come from config or a KMS.
+pub trait KeyProvider: Send + Sync {
+    /// Key used for new ciphertext; `None` leaves values unencrypted.
+    fn current_key_id(&self) -> Option<String>;
+    fn key(&self, id: &str) -> Option<[u8; 32]>;
+}
+
+/// Keys from `FIELD_ENCRYPTION_KEYS`; the first one is current.
+#[derive(Debug, Default)]
+pub struct StaticKeyProvider {
+    current: Option<String>,
+    keys: HashMap<String, [u8; 32]>,
+}
+
+impl StaticKeyProvider {
+    pub fn new(keys: &[EncryptionKey]) -> Self {
+        Self {
+            current: keys.first().map(|k| k.id.clone()),
+            keys: keys.iter().map(|k| (k.id.clone(), k.key)).collect(),
+        }
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key_id(&self) -> Option<String> {
+        self.current.clone()
+    }
+
+    fn key(&self, id: &str) -> Option<[u8; 32]> {
+        self.keys.get(id).copied()
+    }
+}
+
+pub struct FieldCipher {
+    keys: Arc<dyn KeyProvider>,
+}
+
+impl FieldCipher {
+    pub fn new(keys: Arc<dyn KeyProvider>) -> Self {
+        Self { keys }
+    }
+
+    pub fn from_keys(keys: &[EncryptionKey]) -> Self {
+        Self::new(Arc::new(StaticKeyProvider::new(keys)))
+    }
+
+    /// A cipher with no keys, which stores values as plaintext.
+    pub fn disabled() -> Self {
+        Self::new(Arc::new(StaticKeyProvider::default()))
+    }
+
+    fn cipher(&self, id: &str) -> Result<Aes256Gcm, FieldCryptoError> {
+        let key = self
+            .keys
+            .key(id)
+            .ok_or_else(|| FieldCryptoError::UnknownKey(id.to_string()))?;
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+
+    /// Seals `plaintext` with the current key. Empty values stay empty so
+    /// "not set" remains distinguishable without decrypting.
+    pub fn encrypt(
+        &self,
+        field: SensitiveField,
+        plaintext: &str,
+    ) -> Result<String, FieldCryptoError> {
+        let Some(id) = self.keys.current_key_id() else {
+            return Ok(plaintext.to_string());
+        };
+        if plaintext.is_empty() {
+            return Ok(String::new());
+        }
+
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(&id)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: field.as_str().as_bytes(),
+                },
+            )
+            .map_err(|_| FieldCryptoError::Encrypt)?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!(
+            "{PREFIX}{id}:{}",
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Opens a stored value; legacy plaintext is returned unchanged.
+    pub fn decrypt(&self, field: SensitiveField, stored: &str) -> Result<String, FieldCryptoError> {
+        let Some(rest) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let (id, encoded) = rest.split_once(':').ok_or(FieldCryptoError::Malformed)?;
+        let sealed = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| FieldCryptoError::Malformed)?;
+        if sealed.len() <= NONCE_LEN {
+            return Err(FieldCryptoError::Malformed);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+        let plaintext = self
+            .cipher(id)?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: field.as_str().as_bytes(),
+                },
+            )
+            .map_err(|_| FieldCryptoError::Decrypt)?;
+        String::from_utf8(plaintext).map_err(|_| FieldCryptoError::Malformed)
+    }
+
+    /// Same as [`FieldCipher::decrypt`], surfacing failures as a row decode
+    /// error so read paths can propagate them with `?`.
+    pub fn decrypt_column(
+        &self,
+        field: SensitiveField,
+        stored: &str,
+    ) -> Result<String, sqlx::Error> {
+        self.decrypt(field, stored)
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+    }
+
+    pub fn current_key_id(&self) -> Option<String> {
+        self.keys.current_key_id()
+    }
+
+    /// Whether a stored value is plaintext or sealed with a non-current key.
+    pub fn needs_rotation(&self, stored: &str) -> bool {
+        let Some(current) = self.keys.current_key_id() else {
+            return false;
+        };
+        if stored.is_empty() {
+            return false;
+        }
+        match stored
+            .strip_prefix(PREFIX)
+            .and_then(|rest| rest.split_once(':'))
+        {
+            Some((id, _)) => id != current,
+            None => true,
+        }
+    }
+}
+
+/// Re-seals every beneficiary anchor payload that is plaintext or under an
+/// old key with the current key, `batch_size` rows per transaction.
+/// Returns the number of rows rewritten.
+pub async fn reencrypt_beneficiaries(
+    pool: &PgPool,
+    cipher: &FieldCipher,
+    batch_size: i64,
+) -> Result<u64, sqlx::Error> {
+    let Some(current) = cipher.current_key_id() else {
+        return Ok(0);
+    };
+    let current_prefix = format!("{PREFIX}{current}:%");
+    let field = SensitiveField::BeneficiaryAnchorInfo;
+
+    let mut rewritten = 0u64;
+    loop {
+        let mut tx = pool.begin().await?;
+        let rows: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, fiat_anchor_info FROM beneficiaries
+            WHERE fiat_anchor_info <> '' AND fiat_anchor_info NOT LIKE $1
+            ORDER BY id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(&current_prefix)
+        .bind(batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+        if rows.is_empty() {
+            break;
+        }
+
+        for (id, stored) in &rows {
+            let plaintext = cipher.decrypt_column(field, stored)?;
+            let sealed = cipher
+                .encrypt(field, &plaintext)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            sqlx::query("UPDATE beneficiaries SET fiat_anchor_info = $1 WHERE id = $2")
+                .bind(sealed)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        rewritten += rows.len() as u64;
+    }
+    Ok(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELD: SensitiveField = SensitiveField::BeneficiaryAnchorInfo;
+
+    #[test]
+    fn round_trips_and_binds_the_field() {
+        let cipher = FieldCipher::from_keys(&[EncryptionKey::generate("v1")]);
+        let sealed = cipher
+            .encrypt(FIELD, r#"{"account":"0123456789"}"#)
+            .unwrap();
+
+        assert!(sealed.starts_with("enc:v1:"));
+        assert!(!sealed.contains("0123456789"));
+        assert_eq!(
+            cipher.decrypt(FIELD, &sealed).unwrap(),
+            r#"{"account":"0123456789"}"#
+        );
+        assert_eq!(cipher.encrypt(FIELD, "").unwrap(), "");
+
+        let tampered = sealed.replace("enc:v1:", "enc:v1:AAAA");
+        assert!(cipher.decrypt(FIELD, &tampered).is_err());
+    }
+
+    #[test]
+    fn reads_old_keys_and_plaintext_after_rotation() {
+        let v1 = EncryptionKey::generate("v1");
+        let v2 = EncryptionKey::generate("v2");
+        let old = FieldCipher::from_keys(std::slice::from_ref(&v1));
+        let sealed = old.encrypt(FIELD, "Ada Obi;NGN;Zenith;0123456789").unwrap();
+
+        let rotated = FieldCipher::from_keys(&[v2, v1]);
+        assert_eq!(
+            rotated.decrypt(FIELD, &sealed).unwrap(),
+            "Ada Obi;NGN;Zenith;0123456789"
+        );
+        assert!(rotated.needs_rotation(&sealed));
+        assert!(rotated.needs_rotation("plaintext"));
+        assert!(!rotated.needs_rotation(&rotated.encrypt(FIELD, "x").unwrap()));
+        assert_eq!(rotated.decrypt(FIELD, "plaintext").unwrap(), "plaintext");
+
+        assert!(matches!(
+            FieldCipher::disabled().decrypt(FIELD, &sealed),
+            Err(FieldCryptoError::UnknownKey(_))
+        ));
+    }
+
+    #[test]
+    fn parses_configured_keys() {
+        let key = EncryptionKey::generate("v1");
+        assert_eq!(key.to_string().parse::<EncryptionKey>().unwrap(), key);
+        assert!("v1:c2hvcnQ=".parse::<EncryptionKey>().is_err());
+        assert!("no-separator".parse::<EncryptionKey>().is_err());
+        assert!(!format!("{key:?}").contains(&key.to_string()[3..]));
+    }
+}
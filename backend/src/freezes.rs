@@ -0,0 +1,503 @@
+//! Admin freezes of individual users and plans.
+//!
+//! Freezing a user stops their wallet from authenticating, by signature or
+//! SEP-10 token, until an admin unfreezes it. Freezing a plan stops claims,
+//! payouts and owner amendments on it; the claim executor and the claim
+//! expiry sweep leave it alone in the meantime. Every freeze carries a
+//! [`FreezeReason`], is audit logged and tells the people affected.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::notifications::{create_localized_notification, create_notification};
+use crate::templates::TemplateKey;
+
+const MAX_NOTE_LEN: usize = 1000;
+
+/// Why an account or plan was frozen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreezeReason {
+    SuspectedFraud,
+    LegalHold,
+    ComplianceReview,
+    AccountCompromise,
+}
+
+impl FreezeReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SuspectedFraud => "suspected_fraud",
+            Self::LegalHold => "legal_hold",
+            Self::ComplianceReview => "compliance_review",
+            Self::AccountCompromise => "account_compromise",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FreezeRequest {
+    pub reason_code: FreezeReason,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct UnfreezeRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UserFreeze {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub frozen_at: Option<DateTime<Utc>>,
+    pub frozen_by: Option<String>,
+    pub freeze_reason_code: Option<String>,
+    pub freeze_note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PlanFreeze {
+    pub plan_id: Uuid,
+    pub frozen_at: Option<DateTime<Utc>>,
+    pub frozen_by: Option<String>,
+    pub freeze_reason_code: Option<String>,
+    pub freeze_note: Option<String>,
+}
+
+const USER_FREEZE_COLUMNS: &str =
+    "id, wallet_address, frozen_at, frozen_by, freeze_reason_code, freeze_note";
+const PLAN_FREEZE_COLUMNS: &str =
+    "id AS plan_id, frozen_at, frozen_by, freeze_reason_code, freeze_note";
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// Whether the wallet's account is frozen.
+pub async fn user_frozen<'e, E>(executor: E, wallet_address: &str) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM users WHERE wallet_address = $1 AND frozen_at IS NOT NULL)",
+    )
+    .bind(wallet_address)
+    .fetch_one(executor)
+    .await
+}
+
+/// Whether the plan is frozen.
+pub async fn plan_frozen<'e, E>(executor: E, plan_id: Uuid) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM plans WHERE id = $1 AND frozen_at IS NOT NULL)",
+    )
+    .bind(plan_id)
+    .fetch_one(executor)
+    .await
+}
+
+/// Refuses a claim, payout or amendment on a frozen plan.
+pub async fn refuse_frozen_plan(conn: &mut PgConnection, plan_id: Uuid) -> Result<(), Response> {
+    match plan_frozen(&mut *conn, plan_id).await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(refused(
+            StatusCode::FORBIDDEN,
+            "This plan is frozen pending review",
+        )),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to check plan freeze");
+            Err(database_error())
+        }
+    }
+}
+
+fn clean_note(note: Option<String>) -> Result<Option<String>, &'static str> {
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note.as_ref().is_some_and(|n| n.len() > MAX_NOTE_LEN) {
+        return Err("Note is too long");
+    }
+    Ok(note)
+}
+
+/// Locks the user named by id or wallet address. Freezing a wallet that has
+/// no account yet creates one, so it cannot sign in later either.
+async fn lock_user(
+    conn: &mut PgConnection,
+    user: &str,
+    create: bool,
+) -> Result<Option<UserFreeze>, sqlx::Error> {
+    if let Ok(id) = Uuid::parse_str(user) {
+        return sqlx::query_as::<_, UserFreeze>(&format!(
+            "SELECT {USER_FREEZE_COLUMNS} FROM users WHERE id = $1 FOR UPDATE"
+        ))
+        .bind(id)
+        .fetch_optional(conn)
+        .await;
+    }
+    if stellar_strkey::ed25519::PublicKey::from_string(user).is_err() {
+        return Ok(None);
+    }
+    if create {
+        sqlx::query("INSERT INTO users (wallet_address) VALUES ($1) ON CONFLICT DO NOTHING")
+            .bind(user)
+            .execute(&mut *conn)
+            .await?;
+    }
+    sqlx::query_as::<_, UserFreeze>(&format!(
+        "SELECT {USER_FREEZE_COLUMNS} FROM users WHERE wallet_address = $1 FOR UPDATE"
+    ))
+    .bind(user)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Owners, accepted co-owners and beneficiaries of a plan.
+async fn plan_parties(conn: &mut PgConnection, plan_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT owner_address FROM plans WHERE id = $1
+        UNION
+        SELECT owner_address FROM plan_co_owners WHERE plan_id = $1 AND status = 'accepted'
+        UNION
+        SELECT wallet_address FROM beneficiaries WHERE plan_id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(conn)
+    .await
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+// Handler: Freeze User
+pub async fn freeze_user(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(user): Path<String>,
+    Json(payload): Json<FreezeRequest>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.note) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    let reason = payload.reason_code;
+
+    let result: Result<Outcome<UserFreeze>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(current) = lock_user(&mut tx, user.trim(), true).await? else {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "User not found"));
+        };
+        if current.frozen_at.is_some() {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "User is already frozen",
+            ));
+        }
+
+        let frozen = sqlx::query_as::<_, UserFreeze>(&format!(
+            r#"
+            UPDATE users
+            SET frozen_at = NOW(), frozen_by = $2, freeze_reason_code = $3, freeze_note = $4
+            WHERE id = $1
+            RETURNING {USER_FREEZE_COLUMNS}
+            "#
+        ))
+        .bind(current.id)
+        .bind(&admin.user_id)
+        .bind(reason.as_str())
+        .bind(&note)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "user.frozen",
+            &frozen.wallet_address,
+            serde_json::json!({ "reason_code": reason.as_str(), "note": note }),
+        )
+        .await?;
+        create_localized_notification(
+            &mut tx,
+            &frozen.wallet_address,
+            "account_frozen",
+            TemplateKey::AccountFrozen,
+            &[],
+            serde_json::json!({ "reason_code": reason.as_str() }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(frozen))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(frozen)) => {
+            info!(user = %frozen.wallet_address, reason = reason.as_str(), "User frozen");
+            (StatusCode::OK, Json(frozen)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(user = %user, error = %e, "Failed to freeze user");
+            database_error()
+        }
+    }
+}
+
+// Handler: Unfreeze User
+pub async fn unfreeze_user(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(user): Path<String>,
+    payload: Option<Json<UnfreezeRequest>>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.and_then(|Json(p)| p.note)) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+
+    let result: Result<Outcome<UserFreeze>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(current) = lock_user(&mut tx, user.trim(), false).await? else {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "User not found"));
+        };
+        if current.frozen_at.is_none() {
+            return Ok(Outcome::Refused(StatusCode::CONFLICT, "User is not frozen"));
+        }
+
+        let unfrozen = sqlx::query_as::<_, UserFreeze>(&format!(
+            r#"
+            UPDATE users
+            SET frozen_at = NULL, frozen_by = NULL, freeze_reason_code = NULL, freeze_note = NULL
+            WHERE id = $1
+            RETURNING {USER_FREEZE_COLUMNS}
+            "#
+        ))
+        .bind(current.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "user.unfrozen",
+            &unfrozen.wallet_address,
+            serde_json::json!({
+                "reason_code": current.freeze_reason_code,
+                "frozen_at": current.frozen_at,
+                "note": note,
+            }),
+        )
+        .await?;
+        create_localized_notification(
+            &mut tx,
+            &unfrozen.wallet_address,
+            "account_unfrozen",
+            TemplateKey::AccountUnfrozen,
+            &[],
+            serde_json::json!({}),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(unfrozen))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(unfrozen)) => {
+            info!(user = %unfrozen.wallet_address, "User unfrozen");
+            (StatusCode::OK, Json(unfrozen)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(user = %user, error = %e, "Failed to unfreeze user");
+            database_error()
+        }
+    }
+}
+
+// Handler: Freeze Plan
+pub async fn freeze_plan(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<FreezeRequest>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.note) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    let reason = payload.reason_code;
+
+    let result: Result<Outcome<PlanFreeze>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let current = sqlx::query_as::<_, PlanFreeze>(&format!(
+            "SELECT {PLAN_FREEZE_COLUMNS} FROM plans WHERE id = $1 AND is_active = true FOR UPDATE"
+        ))
+        .bind(plan_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(current) = current else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "No active plan found",
+            ));
+        };
+        if current.frozen_at.is_some() {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "Plan is already frozen",
+            ));
+        }
+
+        let frozen = sqlx::query_as::<_, PlanFreeze>(&format!(
+            r#"
+            UPDATE plans
+            SET frozen_at = NOW(), frozen_by = $2, freeze_reason_code = $3, freeze_note = $4
+            WHERE id = $1
+            RETURNING {PLAN_FREEZE_COLUMNS}
+            "#
+        ))
+        .bind(plan_id)
+        .bind(&admin.user_id)
+        .bind(reason.as_str())
+        .bind(&note)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "plan.frozen",
+            &plan_id.to_string(),
+            serde_json::json!({ "reason_code": reason.as_str(), "note": note }),
+        )
+        .await?;
+        for party in plan_parties(&mut tx, plan_id).await? {
+            create_notification(
+                &mut *tx,
+                &party,
+                "plan_frozen",
+                "A plan has been frozen",
+                "Our support team froze a plan you are part of while it is reviewed. Claims, payouts and changes to the plan are on hold until the review is finished.",
+                serde_json::json!({ "plan_id": plan_id, "reason_code": reason.as_str() }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Outcome::Done(frozen))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(frozen)) => {
+            info!(plan_id = %plan_id, reason = reason.as_str(), "Plan frozen");
+            (StatusCode::OK, Json(frozen)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to freeze plan");
+            database_error()
+        }
+    }
+}
+
+// Handler: Unfreeze Plan
+pub async fn unfreeze_plan(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    payload: Option<Json<UnfreezeRequest>>,
+) -> impl IntoResponse {
+    let note = match clean_note(payload.and_then(|Json(p)| p.note)) {
+        Ok(note) => note,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+
+    let result: Result<Outcome<PlanFreeze>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let current = sqlx::query_as::<_, PlanFreeze>(&format!(
+            "SELECT {PLAN_FREEZE_COLUMNS} FROM plans WHERE id = $1 FOR UPDATE"
+        ))
+        .bind(plan_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(current) = current else {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Plan not found"));
+        };
+        if current.frozen_at.is_none() {
+            return Ok(Outcome::Refused(StatusCode::CONFLICT, "Plan is not frozen"));
+        }
+
+        let unfrozen = sqlx::query_as::<_, PlanFreeze>(&format!(
+            r#"
+            UPDATE plans
+            SET frozen_at = NULL, frozen_by = NULL, freeze_reason_code = NULL, freeze_note = NULL
+            WHERE id = $1
+            RETURNING {PLAN_FREEZE_COLUMNS}
+            "#
+        ))
+        .bind(plan_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "plan.unfrozen",
+            &plan_id.to_string(),
+            serde_json::json!({
+                "reason_code": current.freeze_reason_code,
+                "frozen_at": current.frozen_at,
+                "note": note,
+            }),
+        )
+        .await?;
+        for party in plan_parties(&mut tx, plan_id).await? {
+            create_notification(
+                &mut *tx,
+                &party,
+                "plan_unfrozen",
+                "A plan has been unfrozen",
+                "The review of a plan you are part of is finished. Claims, payouts and changes to the plan can go ahead again.",
+                serde_json::json!({ "plan_id": plan_id }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Outcome::Done(unfrozen))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(unfrozen)) => {
+            info!(plan_id = %plan_id, "Plan unfrozen");
+            (StatusCode::OK, Json(unfrozen)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to unfreeze plan");
+            database_error()
+        }
+    }
+}
@@ -0,0 +1,560 @@
+//! Read-only GraphQL API for the dashboard.
+//!
+//! `POST /api/graphql` is authenticated like the other wallet routes (signed
+//! body) and resolves `me` to the signing wallet. `POST /api/admin/graphql`
+//! takes an admin JWT and may look up any wallet. Nested plan data
+//! (beneficiaries, claims, history events) is fetched through per-request
+//! DataLoaders, so a page of plans costs one query per relation rather than
+//! one per plan.
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Error, ErrorExtensions, Json, Object,
+    Schema, SimpleObject,
+};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::{compute_projected_accrued_yield, AppState, BeneficiaryRow, PlanRow};
+use crate::auth::UserContext;
+use crate::claim_requests::ClaimRequest;
+use crate::field_crypto::{FieldCipher, SensitiveField};
+use crate::notifications::Notification;
+use crate::plan_history::{decrypt_snapshot, PlanSnapshot};
+
+const PLAN_COLUMNS: &str = "id, owner_address, token_address, amount, grace_period, \
+     grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, \
+     accrued_yield, created_at";
+
+/// History events returned per plan, newest first.
+const EVENTS_PER_PLAN: i64 = 50;
+const MAX_DEPTH: usize = 8;
+const MAX_COMPLEXITY: usize = 500;
+
+pub type InheritxSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+static SCHEMA: Lazy<InheritxSchema> = Lazy::new(|| {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// The schema, e.g. for printing SDL with `schema().sdl()`.
+pub fn schema() -> &'static InheritxSchema {
+    &SCHEMA
+}
+
+/// Who is asking, taken from the auth middleware's `UserContext`.
+#[derive(Debug, Clone)]
+enum Viewer {
+    Wallet(String),
+    Admin,
+}
+
+impl Viewer {
+    fn can_view_wallet(&self, address: &str) -> bool {
+        match self {
+            Viewer::Admin => true,
+            Viewer::Wallet(own) => own == address,
+        }
+    }
+}
+
+fn forbidden() -> Error {
+    Error::new("Forbidden").extend_with(|_, e| e.set("code", "FORBIDDEN"))
+}
+
+fn database_error(e: impl std::fmt::Display) -> Error {
+    error!(error = %e, "GraphQL database query failed");
+    Error::new("Database query failed").extend_with(|_, e| e.set("code", "INTERNAL"))
+}
+
+// Handler: GraphQL queries for the signing wallet (user routes) or an admin
+pub async fn graphql_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    request: GraphQLRequest,
+) -> Response {
+    let viewer = if user.role == "admin" {
+        Viewer::Admin
+    } else {
+        match user.require_wallet_address() {
+            Ok(address) => Viewer::Wallet(address),
+            Err(e) => return e.into_response(),
+        }
+    };
+
+    let db = state.db_pool.clone();
+    let cipher = state.field_cipher.clone();
+    let request = request
+        .into_inner()
+        .data(viewer)
+        .data(DataLoader::new(
+            BeneficiaryLoader {
+                db: db.clone(),
+                cipher: cipher.clone(),
+            },
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            ClaimLoader { db: db.clone() },
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(EventLoader { db, cipher }, tokio::spawn))
+        .data(state);
+
+    GraphQLResponse::from(SCHEMA.execute(request).await).into_response()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The signing wallet. Not available to admins.
+    async fn me(&self, ctx: &Context<'_>) -> async_graphql::Result<Wallet> {
+        match ctx.data::<Viewer>()? {
+            Viewer::Wallet(address) => Ok(Wallet {
+                address: address.clone(),
+            }),
+            Viewer::Admin => Err(Error::new("`me` requires a wallet signature")),
+        }
+    }
+
+    /// Any wallet for admins; wallets may only look up themselves.
+    async fn wallet(&self, ctx: &Context<'_>, address: String) -> async_graphql::Result<Wallet> {
+        if !ctx.data::<Viewer>()?.can_view_wallet(&address) {
+            return Err(forbidden());
+        }
+        Ok(Wallet { address })
+    }
+
+    /// A plan the caller owns or inherits from, or any plan for admins.
+    async fn plan(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<PlanNode>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let Some(row) = sqlx::query_as::<_, PlanRow>(&format!(
+            "SELECT {PLAN_COLUMNS} FROM plans WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(database_error)?
+        else {
+            return Ok(None);
+        };
+
+        if let Viewer::Wallet(address) = ctx.data::<Viewer>()? {
+            let beneficiaries = ctx
+                .data::<DataLoader<BeneficiaryLoader>>()?
+                .load_one(row.id)
+                .await
+                .map_err(database_error)?
+                .unwrap_or_default();
+            if &row.owner_address != address
+                && !beneficiaries.iter().any(|b| &b.wallet_address == address)
+            {
+                return Err(forbidden());
+            }
+        }
+        Ok(Some(PlanNode::from(row)))
+    }
+}
+
+pub struct Wallet {
+    address: String,
+}
+
+#[Object]
+impl Wallet {
+    async fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// `pending`, `submitted`, `approved` or `rejected`; null before KYC.
+    async fn kyc_status(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<String>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        sqlx::query_scalar("SELECT kyc_status::text FROM users WHERE wallet_address = $1")
+            .bind(&self.address)
+            .fetch_optional(&state.db_pool)
+            .await
+            .map_err(database_error)
+    }
+
+    /// Plans this wallet owns, newest first.
+    async fn plans(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<PlanNode>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let rows = sqlx::query_as::<_, PlanRow>(&format!(
+            "SELECT {PLAN_COLUMNS} FROM plans WHERE owner_address = $1 ORDER BY created_at DESC"
+        ))
+        .bind(&self.address)
+        .fetch_all(&state.db_pool)
+        .await
+        .map_err(database_error)?;
+        Ok(rows.into_iter().map(PlanNode::from).collect())
+    }
+
+    /// Plans naming this wallet as a beneficiary, newest first.
+    async fn inheritances(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<PlanNode>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let rows = sqlx::query_as::<_, PlanRow>(&format!(
+            r#"
+            SELECT {PLAN_COLUMNS} FROM plans
+            WHERE id IN (SELECT plan_id FROM beneficiaries WHERE wallet_address = $1)
+            ORDER BY created_at DESC
+            "#
+        ))
+        .bind(&self.address)
+        .fetch_all(&state.db_pool)
+        .await
+        .map_err(database_error)?;
+        Ok(rows.into_iter().map(PlanNode::from).collect())
+    }
+
+    /// In-app notifications, newest first. `limit` defaults to 50 (max 200).
+    async fn notifications(
+        &self,
+        ctx: &Context<'_>,
+        unread_only: Option<bool>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<NotificationNode>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let rows = sqlx::query_as::<_, Notification>(
+            r#"
+            SELECT id, user_address, notification_type, title, message, metadata, is_read,
+                   status, created_at
+            FROM notifications
+            WHERE user_address = $1
+              AND ($2 = false OR is_read = false)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(&self.address)
+        .bind(unread_only.unwrap_or(false))
+        .bind(limit.unwrap_or(50).clamp(1, 200))
+        .fetch_all(&state.db_pool)
+        .await
+        .map_err(database_error)?;
+        Ok(rows.into_iter().map(NotificationNode::from).collect())
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Plan", complex)]
+pub struct PlanNode {
+    id: Uuid,
+    owner_address: String,
+    token_address: String,
+    amount: Decimal,
+    grace_period_seconds: i64,
+    earn_yield: bool,
+    yield_rate_bps: i32,
+    /// Persisted yield plus yield accrued since the last ping.
+    accrued_yield: Decimal,
+    last_ping: i64,
+    is_active: bool,
+    status: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<PlanRow> for PlanNode {
+    fn from(row: PlanRow) -> Self {
+        Self {
+            accrued_yield: compute_projected_accrued_yield(&row).unwrap_or(row.accrued_yield),
+            id: row.id,
+            owner_address: row.owner_address,
+            token_address: row.token_address,
+            amount: row.amount,
+            grace_period_seconds: row.grace_period_seconds,
+            earn_yield: row.earn_yield,
+            yield_rate_bps: row.yield_rate_bps,
+            last_ping: row.last_ping,
+            is_active: row.is_active,
+            status: row.status,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[ComplexObject]
+impl PlanNode {
+    async fn beneficiaries(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<BeneficiaryNode>> {
+        let rows = ctx
+            .data::<DataLoader<BeneficiaryLoader>>()?
+            .load_one(self.id)
+            .await
+            .map_err(database_error)?;
+        Ok(rows.unwrap_or_default())
+    }
+
+    /// Claim requests, newest first.
+    async fn claims(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<ClaimNode>> {
+        let rows = ctx
+            .data::<DataLoader<ClaimLoader>>()?
+            .load_one(self.id)
+            .await
+            .map_err(database_error)?;
+        Ok(rows.unwrap_or_default())
+    }
+
+    /// The most recent history snapshots, newest first.
+    async fn events(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<PlanEventNode>> {
+        let rows = ctx
+            .data::<DataLoader<EventLoader>>()?
+            .load_one(self.id)
+            .await
+            .map_err(database_error)?;
+        Ok(rows.unwrap_or_default())
+    }
+}
+
+#[derive(Clone, SimpleObject)]
+#[graphql(name = "Beneficiary")]
+pub struct BeneficiaryNode {
+    id: Uuid,
+    wallet_address: String,
+    allocation_bps: i32,
+    fiat_anchor_info: String,
+}
+
+#[derive(Clone, SimpleObject)]
+#[graphql(name = "Claim")]
+pub struct ClaimNode {
+    id: Uuid,
+    requested_by: String,
+    /// Executor who filed the claim for the beneficiary, if delegated.
+    filed_by: Option<String>,
+    /// `pending`, `executed`, `cancelled` or `failed`.
+    status: String,
+    execute_after: DateTime<Utc>,
+    cancelled_by: Option<String>,
+    cancel_reason: Option<String>,
+    failure_reason: Option<String>,
+    created_at: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+}
+
+impl From<ClaimRequest> for ClaimNode {
+    fn from(row: ClaimRequest) -> Self {
+        Self {
+            id: row.id,
+            requested_by: row.requested_by,
+            filed_by: row.filed_by,
+            status: row.status,
+            execute_after: row.execute_after,
+            cancelled_by: row.cancelled_by,
+            cancel_reason: row.cancel_reason,
+            failure_reason: row.failure_reason,
+            created_at: row.created_at,
+            resolved_at: row.resolved_at,
+        }
+    }
+}
+
+#[derive(Clone, SimpleObject)]
+#[graphql(name = "PlanEvent")]
+pub struct PlanEventNode {
+    id: Uuid,
+    /// `baseline`, `created`, `updated` or `deleted`.
+    change_kind: String,
+    /// Plan and beneficiaries after the change; null after deletion.
+    state: Option<Json<serde_json::Value>>,
+    captured_at: DateTime<Utc>,
+}
+
+impl From<PlanSnapshot> for PlanEventNode {
+    fn from(row: PlanSnapshot) -> Self {
+        Self {
+            id: row.id,
+            change_kind: row.change_kind,
+            state: row.state.map(Json),
+            captured_at: row.captured_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Notification")]
+pub struct NotificationNode {
+    id: Uuid,
+    notification_type: String,
+    title: String,
+    message: String,
+    metadata: Json<serde_json::Value>,
+    is_read: bool,
+    /// `queued`, `sent`, `failed` or `read`.
+    status: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<Notification> for NotificationNode {
+    fn from(row: Notification) -> Self {
+        Self {
+            id: row.id,
+            notification_type: row.notification_type,
+            title: row.title,
+            message: row.message,
+            metadata: Json(row.metadata),
+            is_read: row.is_read,
+            status: row.status,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// Groups rows by plan id, keeping their query order.
+fn group_by_plan<T>(rows: impl IntoIterator<Item = (Uuid, T)>) -> HashMap<Uuid, Vec<T>> {
+    let mut grouped: HashMap<Uuid, Vec<T>> = HashMap::new();
+    for (plan_id, row) in rows {
+        grouped.entry(plan_id).or_default().push(row);
+    }
+    grouped
+}
+
+pub struct BeneficiaryLoader {
+    db: PgPool,
+    cipher: Arc<FieldCipher>,
+}
+
+impl Loader<Uuid> for BeneficiaryLoader {
+    type Value = Vec<BeneficiaryNode>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let rows = sqlx::query_as::<_, BeneficiaryRow>(
+            r#"
+            SELECT id, plan_id, wallet_address, allocation_bps, fiat_anchor_info
+            FROM beneficiaries
+            WHERE plan_id = ANY($1)
+            ORDER BY wallet_address
+            "#,
+        )
+        .bind(keys)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut nodes = Vec::with_capacity(rows.len());
+        for row in rows {
+            let fiat_anchor_info = self
+                .cipher
+                .decrypt_column(SensitiveField::BeneficiaryAnchorInfo, &row.fiat_anchor_info)?;
+            nodes.push((
+                row.plan_id,
+                BeneficiaryNode {
+                    id: row.id,
+                    wallet_address: row.wallet_address,
+                    allocation_bps: row.allocation_bps,
+                    fiat_anchor_info,
+                },
+            ));
+        }
+        Ok(group_by_plan(nodes))
+    }
+}
+
+pub struct ClaimLoader {
+    db: PgPool,
+}
+
+impl Loader<Uuid> for ClaimLoader {
+    type Value = Vec<ClaimNode>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let rows = sqlx::query_as::<_, ClaimRequest>(
+            r#"
+            SELECT id, plan_id, requested_by, filed_by, status, execute_after, cancelled_by,
+                   cancel_reason, failure_reason, correlation_id, created_at, resolved_at
+            FROM claim_requests
+            WHERE plan_id = ANY($1)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(keys)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(group_by_plan(
+            rows.into_iter()
+                .map(|row| (row.plan_id, ClaimNode::from(row))),
+        ))
+    }
+}
+
+pub struct EventLoader {
+    db: PgPool,
+    cipher: Arc<FieldCipher>,
+}
+
+impl Loader<Uuid> for EventLoader {
+    type Value = Vec<PlanEventNode>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let rows = sqlx::query_as::<_, PlanSnapshot>(
+            r#"
+            SELECT id, plan_id, change_kind, state, captured_at
+            FROM (
+                SELECT id, plan_id, change_kind, state, captured_at,
+                       ROW_NUMBER() OVER (PARTITION BY plan_id ORDER BY captured_at DESC) AS n
+                FROM plan_snapshots
+                WHERE plan_id = ANY($1)
+            ) recent
+            WHERE n <= $2
+            ORDER BY captured_at DESC
+            "#,
+        )
+        .bind(keys)
+        .bind(EVENTS_PER_PLAN)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut events = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            decrypt_snapshot(&self.cipher, &mut row)?;
+            events.push((row.plan_id, PlanEventNode::from(row)));
+        }
+        Ok(group_by_plan(events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_exposes_dashboard_graph() {
+        let sdl = schema().sdl();
+        for field in [
+            "me: Wallet!",
+            "inheritances: [Plan!]!",
+            "claims: [Claim!]!",
+            "events: [PlanEvent!]!",
+            "notifications(unreadOnly: Boolean, limit: Int): [Notification!]!",
+        ] {
+            assert!(sdl.contains(field), "missing `{field}` in schema:\n{sdl}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_queries_deeper_than_the_limit() {
+        let query = "{ __schema { types { fields { type { ofType { ofType { ofType { ofType { name } } } } } } } } }";
+        let response = schema().execute(query).await;
+        assert!(response.is_err());
+        assert!(response.errors[0].message.contains("nested too deep"));
+    }
+}
@@ -0,0 +1,652 @@
+//! Request and response capture for sensitive routes.
+//!
+//! Mutating calls to claim, KYC and admin configuration routes are written
+//! to `http_audit` with their JSON bodies. Secrets are removed and personal
+//! data is masked before anything is stored. Rows older than the
+//! `http_audit_retention_days` system setting are purged by
+//! [`HttpAuditRetentionService`].
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{MatchedPath, Query, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::claim_eligibility::{mask_email, mask_phone};
+use crate::jobs::JobRegistry;
+use crate::pagination::{split_page, with_next_cursor, Cursor};
+use crate::system_settings::SystemSettingsCache;
+use crate::telemetry;
+
+/// Bodies larger than this are recorded as omitted.
+const MAX_CAPTURED_BODY_BYTES: usize = 64 * 1024;
+const DEFAULT_PURGE_INTERVAL_SECS: u64 = 60 * 60;
+const DEFAULT_PURGE_BATCH_SIZE: i64 = 10_000;
+const HTTP_AUDIT_LOCK_KEY: i64 = 830;
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditCategory {
+    Claim,
+    Kyc,
+    AdminConfig,
+    Consent,
+}
+
+impl AuditCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Claim => "claim",
+            Self::Kyc => "kyc",
+            Self::AdminConfig => "admin_config",
+            Self::Consent => "consent",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [Self::Claim, Self::Kyc, Self::AdminConfig, Self::Consent]
+            .into_iter()
+            .find(|category| category.as_str() == value)
+    }
+}
+
+/// Route templates whose mutating requests are captured.
+const AUDITED_ROUTES: &[(&str, AuditCategory)] = &[
+    ("/api/plans/payout", AuditCategory::Claim),
+    ("/api/plans/{id}/claim", AuditCategory::Claim),
+    ("/api/plans/{id}/claim/cancel", AuditCategory::Claim),
+    ("/api/plans/{id}/fallback-beneficiary", AuditCategory::Claim),
+    ("/api/plans/{id}/claim/documents", AuditCategory::Claim),
+    ("/api/plans/{id}/delegations", AuditCategory::Claim),
+    (
+        "/api/plans/{id}/delegations/{delegation_id}",
+        AuditCategory::Claim,
+    ),
+    ("/api/admin/claims/{id}/cancel", AuditCategory::Claim),
+    ("/api/kyc/submit", AuditCategory::Kyc),
+    ("/api/kyc/upload", AuditCategory::Kyc),
+    ("/api/kyc/webhook", AuditCategory::Kyc),
+    ("/api/admin/kyc/batch", AuditCategory::Kyc),
+    ("/api/admin/plans/batch-status", AuditCategory::AdminConfig),
+    ("/api/admin/pending-changes", AuditCategory::AdminConfig),
+    (
+        "/api/admin/pending-changes/{id}/approve",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/pending-changes/{id}/reject",
+        AuditCategory::AdminConfig,
+    ),
+    ("/api/admin/corrections", AuditCategory::AdminConfig),
+    ("/api/admin/users/{id}/freeze", AuditCategory::AdminConfig),
+    ("/api/admin/users/{id}/unfreeze", AuditCategory::AdminConfig),
+    ("/api/admin/users/{id}/kyc-tier", AuditCategory::Kyc),
+    ("/api/admin/users/{id}/kyc-sync", AuditCategory::Kyc),
+    ("/api/admin/plans/{id}/freeze", AuditCategory::AdminConfig),
+    ("/api/admin/plans/{id}/unfreeze", AuditCategory::AdminConfig),
+    (
+        "/api/admin/corrections/{id}/approve",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/corrections/{id}/reject",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/system-settings/{key}",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/notification-templates/{key}/{language}",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/notification-templates/{key}/{language}/versions/{version}/restore",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/check-ins/{address}/override",
+        AuditCategory::AdminConfig,
+    ),
+    ("/api/admin/organizations", AuditCategory::AdminConfig),
+    ("/api/admin/organizations/{id}", AuditCategory::AdminConfig),
+    (
+        "/api/admin/organizations/{id}/admins/{admin_id}",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/organizations/{id}/transfer-ownership",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/organizations/{id}/invitations",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/organizations/{id}/invitations/{invitation_id}",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/admin/organizations/{id}/users/{user}",
+        AuditCategory::AdminConfig,
+    ),
+    (
+        "/api/organization-invitations/accept",
+        AuditCategory::AdminConfig,
+    ),
+    ("/api/users/me/consents", AuditCategory::Consent),
+    ("/api/admin/consent-documents", AuditCategory::Consent),
+];
+
+/// Category of a request to `route`, or `None` when it is not captured.
+/// Reads are never captured.
+pub fn audit_category(method: &Method, route: &str) -> Option<AuditCategory> {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return None;
+    }
+    AUDITED_ROUTES
+        .iter()
+        .find(|(template, _)| *template == route)
+        .map(|(_, category)| *category)
+}
+
+/// Credentials and one-time values, dropped entirely.
+fn is_secret_key(key: &str) -> bool {
+    const FRAGMENTS: &[&str] = &[
+        "password",
+        "secret",
+        "token",
+        "signature",
+        "private_key",
+        "seed",
+        "api_key",
+    ];
+    const EXACT: &[&str] = &["code", "otp", "confirmation", "authorization"];
+    FRAGMENTS.iter().any(|f| key.contains(f)) || EXACT.contains(&key)
+}
+
+/// Personal data that is not needed to follow what happened.
+fn is_pii_key(key: &str) -> bool {
+    const FRAGMENTS: &[&str] = &[
+        "fiat_anchor_info",
+        "document",
+        "birth",
+        "full_name",
+        "first_name",
+        "last_name",
+        "national_id",
+        "tax_id",
+        "ssn",
+        "iban",
+        "account_number",
+        "bank",
+    ];
+    // Uploaded file bodies, such as claim documents.
+    const EXACT: &[&str] = &["content"];
+    FRAGMENTS.iter().any(|f| key.contains(f)) || EXACT.contains(&key)
+}
+
+/// Applies the redaction rules to a JSON body in place: secrets are
+/// replaced with `[redacted]`, emails and phone numbers are masked and
+/// other personal fields are replaced.
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if field.is_null() {
+                    continue;
+                }
+                if is_secret_key(&key) || is_pii_key(&key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else if key.contains("email") {
+                    if let Some(email) = field.as_str() {
+                        *field = Value::String(mask_email(email));
+                    } else {
+                        *field = Value::String(REDACTED.to_string());
+                    }
+                } else if key.contains("phone") {
+                    if let Some(phone) = field.as_str() {
+                        *field = Value::String(mask_phone(phone));
+                    } else {
+                        *field = Value::String(REDACTED.to_string());
+                    }
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Body as stored: redacted JSON, a note for bodies that are not JSON or
+/// too large, or `None` when empty.
+fn captured_body(headers: &HeaderMap, bytes: &Bytes) -> Option<Value> {
+    if bytes.is_empty() {
+        return None;
+    }
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let omitted = || {
+        serde_json::json!({
+            "omitted": true,
+            "content_type": content_type,
+            "bytes": bytes.len(),
+        })
+    };
+    if bytes.len() > MAX_CAPTURED_BODY_BYTES {
+        return Some(omitted());
+    }
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut value) => {
+            redact(&mut value);
+            Some(value)
+        }
+        Err(_) => Some(omitted()),
+    }
+}
+
+struct AuditEntry {
+    category: AuditCategory,
+    method: String,
+    route: String,
+    path: String,
+    actor: Option<String>,
+    status: u16,
+    request_body: Option<Value>,
+    response_body: Option<Value>,
+    duration_ms: i64,
+    correlation_id: Option<String>,
+}
+
+async fn record_entry(db: &PgPool, entry: AuditEntry) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO http_audit
+            (category, method, route, path, actor, status, request_body, response_body,
+             duration_ms, correlation_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+    )
+    .bind(entry.category.as_str())
+    .bind(&entry.method)
+    .bind(&entry.route)
+    .bind(&entry.path)
+    .bind(&entry.actor)
+    .bind(i32::from(entry.status))
+    .bind(&entry.request_body)
+    .bind(&entry.response_body)
+    .bind(entry.duration_ms)
+    .bind(&entry.correlation_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Captures audited routes. Layered inside the auth middleware so the
+/// caller is known; other routes pass straight through.
+pub async fn http_audit_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let Some((route, category)) =
+        route.and_then(|route| audit_category(req.method(), &route).map(|c| (route, c)))
+    else {
+        return next.run(req).await;
+    };
+
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let actor = req.extensions().get::<UserContext>().map(|user| {
+        user.wallet_address()
+            .unwrap_or_else(|| user.user_id.clone())
+    });
+
+    let (parts, body) = req.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Failed to read request body" })),
+            )
+                .into_response();
+        }
+    };
+    let request_body = captured_body(&parts.headers, &bytes);
+
+    let started = Instant::now();
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    let (parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!(route = %route, error = %e, "Failed to read response body for audit");
+            Bytes::new()
+        }
+    };
+
+    let entry = AuditEntry {
+        category,
+        method,
+        route,
+        path,
+        actor,
+        status: parts.status.as_u16(),
+        request_body,
+        response_body: captured_body(&parts.headers, &bytes),
+        duration_ms,
+        correlation_id: telemetry::correlation_id(),
+    };
+    let db = state.db_pool.clone();
+    telemetry::spawn(async move {
+        if let Err(e) = record_entry(&db, entry).await {
+            error!(error = %e, "Failed to record HTTP audit entry");
+        }
+    });
+
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct HttpAuditRow {
+    pub id: Uuid,
+    pub category: String,
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub actor: Option<String>,
+    pub status: i32,
+    pub request_body: Option<Value>,
+    pub response_body: Option<Value>,
+    pub duration_ms: i64,
+    pub correlation_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HttpAuditQuery {
+    pub category: Option<String>,
+    pub actor: Option<String>,
+    /// Route template, e.g. `/api/plans/{id}/claim`.
+    pub route: Option<String>,
+    pub status: Option<i32>,
+    /// Text to look for in the stored request or response body.
+    pub q: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// `X-Request-Id` of the request.
+    pub correlation_id: Option<String>,
+    pub limit: Option<i64>,
+    /// `X-Next-Cursor` of the previous page.
+    pub cursor: Option<String>,
+}
+
+fn bad_request(message: &str) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+// Handler: Search HTTP Audit (admin)
+pub async fn search_http_audit(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HttpAuditQuery>,
+) -> impl IntoResponse {
+    let category = match query.category.as_deref().map(AuditCategory::parse) {
+        Some(None) => {
+            return bad_request("category must be one of claim, kyc, admin_config, consent")
+        }
+        Some(Some(category)) => Some(category.as_str()),
+        None => None,
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let cursor = match Cursor::parse(query.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(message) => return bad_request(message),
+    };
+    let needle = query
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(str::to_lowercase);
+
+    match sqlx::query_as::<_, HttpAuditRow>(
+        r#"
+        SELECT id, category, method, route, path, actor, status, request_body,
+               response_body, duration_ms, correlation_id, created_at
+        FROM http_audit
+        WHERE ($1::text IS NULL OR category = $1)
+          AND ($2::text IS NULL OR actor = $2)
+          AND ($3::text IS NULL OR route = $3)
+          AND ($4::int IS NULL OR status = $4)
+          AND ($5::text IS NULL
+               OR position($5 IN lower(COALESCE(request_body::text, ''))) > 0
+               OR position($5 IN lower(COALESCE(response_body::text, ''))) > 0)
+          AND ($6::timestamptz IS NULL OR created_at >= $6)
+          AND ($7::timestamptz IS NULL OR created_at < $7)
+          AND ($8::text IS NULL OR correlation_id = $8)
+          AND ($9::timestamptz IS NULL OR (created_at, id) < ($9, $10::uuid))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $11
+        "#,
+    )
+    .bind(category)
+    .bind(&query.actor)
+    .bind(&query.route)
+    .bind(query.status)
+    .bind(&needle)
+    .bind(query.since)
+    .bind(query.until)
+    .bind(&query.correlation_id)
+    .bind(cursor.map(|c| c.created_at))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit + 1)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => {
+            let (rows, next) = split_page(rows, limit, |r| Cursor::new(r.created_at, r.id));
+            with_next_cursor((StatusCode::OK, Json(rows)).into_response(), next)
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to search HTTP audit");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HttpAuditRetentionConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+}
+
+impl HttpAuditRetentionConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env(
+            "HTTP_AUDIT_PURGE_INTERVAL_SECS",
+            DEFAULT_PURGE_INTERVAL_SECS,
+        );
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: DEFAULT_PURGE_BATCH_SIZE,
+        }
+    }
+}
+
+/// Deletes `http_audit` rows older than the retention period.
+pub struct HttpAuditRetentionService {
+    db: PgPool,
+    settings: Arc<SystemSettingsCache>,
+    config: HttpAuditRetentionConfig,
+}
+
+impl HttpAuditRetentionService {
+    pub fn new(
+        db: PgPool,
+        settings: Arc<SystemSettingsCache>,
+        config: HttpAuditRetentionConfig,
+    ) -> Self {
+        Self {
+            db,
+            settings,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("http_audit_retention", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
+                        info!("HTTP audit retention purged {count} row(s)");
+                    }
+                })
+            }
+        });
+    }
+
+    pub async fn run_once(&self) -> Result<u64, sqlx::Error> {
+        let retention = self.settings.get().await.http_audit_retention();
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(HTTP_AUDIT_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("HTTP audit retention lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let purged = sqlx::query(
+            r#"
+            DELETE FROM http_audit
+            WHERE id IN (
+                SELECT id FROM http_audit
+                WHERE created_at < NOW() - ($1 * INTERVAL '1 second')
+                ORDER BY created_at ASC
+                LIMIT $2
+            )
+            "#,
+        )
+        .bind(retention.num_seconds() as f64)
+        .bind(self.config.batch_size)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(purged)
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_mutating_calls_to_listed_routes_are_captured() {
+        assert_eq!(
+            audit_category(&Method::POST, "/api/plans/{id}/claim"),
+            Some(AuditCategory::Claim)
+        );
+        assert_eq!(
+            audit_category(&Method::PUT, "/api/admin/system-settings/{key}"),
+            Some(AuditCategory::AdminConfig)
+        );
+        assert_eq!(
+            audit_category(&Method::POST, "/api/users/me/consents"),
+            Some(AuditCategory::Consent)
+        );
+        assert_eq!(audit_category(&Method::GET, "/api/plans/{id}/claim"), None);
+        assert_eq!(audit_category(&Method::POST, "/api/plans"), None);
+    }
+
+    #[test]
+    fn redacts_secrets_and_masks_personal_data() {
+        let mut body = serde_json::json!({
+            "reason": "owner request",
+            "signature": "abcd",
+            "confirmation": { "challenge_id": "x", "signature": "y" },
+            "contact": { "email": "ada@example.com", "phone": "+2348012345678" },
+            "beneficiaries": [{ "wallet_address": "GABC", "fiat_anchor_info": "acct 1234" }],
+            "user_ids": ["GABC"],
+            "content": "JVBERi0xLjQ=",
+            "note": null,
+        });
+
+        redact(&mut body);
+
+        assert_eq!(body["reason"], "owner request");
+        assert_eq!(body["signature"], REDACTED);
+        assert_eq!(body["confirmation"], REDACTED);
+        assert_eq!(body["contact"]["email"], "a***@example.com");
+        assert_eq!(body["contact"]["phone"], "**********5678");
+        assert_eq!(body["beneficiaries"][0]["wallet_address"], "GABC");
+        assert_eq!(body["beneficiaries"][0]["fiat_anchor_info"], REDACTED);
+        assert_eq!(body["user_ids"][0], "GABC");
+        assert_eq!(body["content"], REDACTED);
+        assert!(body["note"].is_null());
+    }
+
+    #[test]
+    fn non_json_and_oversized_bodies_are_omitted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "text/plain".parse().unwrap());
+
+        let body = captured_body(&headers, &Bytes::from_static(b"hello")).unwrap();
+        assert_eq!(body["omitted"], true);
+        assert_eq!(body["bytes"], 5);
+
+        let large = Bytes::from(vec![b'1'; MAX_CAPTURED_BODY_BYTES + 1]);
+        assert_eq!(captured_body(&headers, &large).unwrap()["omitted"], true);
+        assert!(captured_body(&headers, &Bytes::new()).is_none());
+    }
+}
@@ -0,0 +1,444 @@
+//! Shared client for every outbound HTTP call: Soroban RPC, Horizon, anchors,
+//! signers, mail and SMS providers, and client domains during SEP-10.
+//!
+//! All integrations share one connection pool. Each gets an [`HttpClient`]
+//! with its own name (used as the metrics label), timeout and retry policy.
+//! Failures are tracked per host: after `HTTP_BREAKER_FAILURE_THRESHOLD`
+//! consecutive connection errors, timeouts or 5xx responses the host's
+//! circuit opens and calls fail fast with [`HttpError::CircuitOpen`] for
+//! `HTTP_BREAKER_COOLDOWN_SECS`. After the cooldown one trial request is let
+//! through; its outcome closes or reopens the circuit.
+//!
+//! Only idempotent methods are retried unless the policy says the endpoint
+//! is safe to repeat, so a timed-out email or withdrawal is never sent twice.
+//! Clients marked [`HttpPolicy::public_only`] are for user-supplied hosts and
+//! refuse to connect to loopback, private, link-local and other reserved
+//! addresses, including after DNS resolution and redirects.
+
+use once_cell::sync::Lazy;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{redirect, Method, RequestBuilder, Response, Url};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::metrics::{OUTBOUND_CIRCUIT_OPENED, OUTBOUND_LATENCY, OUTBOUND_REQUESTS};
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
+const MAX_REDIRECTS: usize = 5;
+/// Closed breakers are dropped past this many hosts, which bounds memory
+/// when public-only clients are pointed at arbitrary domains.
+const MAX_TRACKED_HOSTS: usize = 10_000;
+
+#[derive(Debug, Error)]
+pub enum HttpError {
+    #[error("circuit breaker is open for {0}")]
+    CircuitOpen(String),
+    #[error("{0} is not a public address")]
+    ForbiddenAddress(String),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+}
+
+/// How one integration calls out.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpPolicy {
+    pub timeout: Duration,
+    /// Extra attempts after the first for retryable failures.
+    pub retries: u32,
+    /// Also retry non-idempotent methods, for endpoints where repeating a
+    /// request has no side effect (JSON-RPC reads, signing).
+    pub retry_unsafe_methods: bool,
+    /// Refuse non-public destinations; for hosts chosen by users.
+    pub public_only: bool,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            retries: 2,
+            retry_unsafe_methods: false,
+            public_only: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BreakerConfig {
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl BreakerConfig {
+    fn from_env() -> Self {
+        let parse = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<u64>().ok());
+        Self {
+            failure_threshold: parse("HTTP_BREAKER_FAILURE_THRESHOLD")
+                .map_or(DEFAULT_FAILURE_THRESHOLD, |n| {
+                    n.clamp(1, u32::MAX as u64) as u32
+                }),
+            cooldown: Duration::from_secs(
+                parse("HTTP_BREAKER_COOLDOWN_SECS").unwrap_or(DEFAULT_COOLDOWN_SECS),
+            ),
+        }
+    }
+}
+
+static BREAKER_CONFIG: Lazy<BreakerConfig> = Lazy::new(BreakerConfig::from_env);
+static BREAKERS: Lazy<dashmap::DashMap<String, Arc<Mutex<CircuitBreaker>>>> =
+    Lazy::new(dashmap::DashMap::new);
+
+static SHARED: Lazy<reqwest::Client> = Lazy::new(|| {
+    base_builder()
+        .build()
+        .expect("failed to build shared HTTP client")
+});
+
+static PUBLIC_ONLY: Lazy<reqwest::Client> = Lazy::new(|| {
+    base_builder()
+        .dns_resolver(Arc::new(PublicResolver))
+        .redirect(redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.stop()
+            } else if literal_host_is_public(attempt.url()) {
+                attempt.follow()
+            } else {
+                let host = attempt.url().host_str().unwrap_or_default().to_string();
+                attempt.error(HttpError::ForbiddenAddress(host))
+            }
+        }))
+        .build()
+        .expect("failed to build public-only HTTP client")
+});
+
+fn base_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .pool_max_idle_per_host(16)
+        .user_agent(concat!("inheritx-backend/", env!("CARGO_PKG_VERSION")))
+}
+
+/// Outbound client for one integration.
+#[derive(Clone)]
+pub struct HttpClient {
+    name: &'static str,
+    http: reqwest::Client,
+    policy: HttpPolicy,
+}
+
+impl HttpClient {
+    pub fn new(name: &'static str, policy: HttpPolicy) -> Self {
+        let http = if policy.public_only {
+            PUBLIC_ONLY.clone()
+        } else {
+            SHARED.clone()
+        };
+        Self { name, http, policy }
+    }
+
+    pub fn get(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http.get(url)
+    }
+
+    pub fn post(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http.post(url)
+    }
+
+    pub fn put(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http.put(url)
+    }
+
+    /// Sends `request` through the host's circuit breaker, retrying
+    /// retryable failures. Non-2xx responses are returned as they are; only
+    /// 5xx responses count against the breaker.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, HttpError> {
+        let mut request = request.build()?;
+        if request.timeout().is_none() {
+            *request.timeout_mut() = Some(self.policy.timeout);
+        }
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        if self.policy.public_only && !literal_host_is_public(request.url()) {
+            return Err(HttpError::ForbiddenAddress(host));
+        }
+        let may_retry = self.policy.retry_unsafe_methods || is_idempotent(request.method());
+        if BREAKERS.len() >= MAX_TRACKED_HOSTS {
+            BREAKERS.retain(|_, breaker| breaker.lock().unwrap().open_until.is_some());
+        }
+        let breaker = BREAKERS
+            .entry(host.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(CircuitBreaker::default())))
+            .clone();
+
+        let mut attempt = 0;
+        loop {
+            if !breaker
+                .lock()
+                .unwrap()
+                .allow(Instant::now(), &BREAKER_CONFIG)
+            {
+                OUTBOUND_REQUESTS
+                    .with_label_values(&[self.name, "circuit_open"])
+                    .inc();
+                return Err(HttpError::CircuitOpen(host));
+            }
+            let retry_copy = if may_retry && attempt < self.policy.retries {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            let started = Instant::now();
+            let result = self.http.execute(request).await;
+            OUTBOUND_LATENCY
+                .with_label_values(&[self.name])
+                .observe(started.elapsed().as_secs_f64());
+
+            let (outcome, failed, retryable) = match &result {
+                Ok(response) if response.status().is_server_error() => ("server_error", true, true),
+                Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    ("rate_limited", false, true)
+                }
+                Ok(_) => ("ok", false, false),
+                Err(e) if e.is_timeout() => ("timeout", true, true),
+                Err(e) if e.is_connect() => ("connect_error", true, true),
+                Err(_) => ("error", false, false),
+            };
+            OUTBOUND_REQUESTS
+                .with_label_values(&[self.name, outcome])
+                .inc();
+            let opened = breaker
+                .lock()
+                .unwrap()
+                .record(!failed, Instant::now(), &BREAKER_CONFIG);
+            if opened {
+                OUTBOUND_CIRCUIT_OPENED
+                    .with_label_values(&[self.name])
+                    .inc();
+                warn!(client = self.name, host = %host, "Outbound circuit breaker opened");
+            }
+
+            match retry_copy {
+                Some(copy) if retryable => {
+                    attempt += 1;
+                    info!(client = self.name, host = %host, attempt, outcome, "Retrying outbound request");
+                    tokio::time::sleep(RETRY_BACKOFF * 2u32.pow(attempt - 1)).await;
+                    request = copy;
+                }
+                _ => return Ok(result?),
+            }
+        }
+    }
+}
+
+/// Whether the circuit for `url`'s host is open: enough consecutive calls to
+/// it failed and none has succeeded since.
+pub fn circuit_open(url: &str) -> bool {
+    let Some(host) = Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+    else {
+        return false;
+    };
+    BREAKERS
+        .get(&host)
+        .is_some_and(|breaker| breaker.lock().unwrap().open_until.is_some())
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+/// Consecutive-failure breaker for one host.
+#[derive(Debug, Default)]
+struct CircuitBreaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    trial_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Whether a request may go out. Once the cooldown has passed a single
+    /// trial is allowed; a trial that never reports back is replaced after
+    /// another cooldown.
+    fn allow(&mut self, now: Instant, config: &BreakerConfig) -> bool {
+        let Some(open_until) = self.open_until else {
+            return true;
+        };
+        if now < open_until {
+            return false;
+        }
+        match self.trial_started {
+            Some(started) if now < started + config.cooldown => false,
+            _ => {
+                self.trial_started = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Records an outcome; returns whether this failure opened the circuit.
+    fn record(&mut self, success: bool, now: Instant, config: &BreakerConfig) -> bool {
+        self.trial_started = None;
+        if success {
+            self.consecutive_failures = 0;
+            self.open_until = None;
+            return false;
+        }
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let was_open = self.open_until.is_some();
+        if was_open || self.consecutive_failures >= config.failure_threshold {
+            self.open_until = Some(now + config.cooldown);
+        }
+        !was_open && self.open_until.is_some()
+    }
+}
+
+/// Resolves names for [`HttpPolicy::public_only`] clients, dropping any
+/// address that is not publicly routable.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(HttpError::ForbiddenAddress(host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// False when the URL names a non-public IP directly. Domain names are
+/// checked by [`PublicResolver`] when they are resolved.
+fn literal_host_is_public(url: &Url) -> bool {
+    match url.host_str() {
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .map_or(true, is_public_ip),
+        None => false,
+    }
+}
+
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_ipv4(v4),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Carrier-grade NAT 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // Benchmarking 198.18.0.0/15
+        || (a == 198 && (b == 18 || b == 19))
+        // Reserved 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // Link local fe80::/10
+        || (first & 0xffc0) == 0xfe80
+        // Documentation 2001:db8::/32
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: BreakerConfig = BreakerConfig {
+        failure_threshold: 3,
+        cooldown: Duration::from_secs(30),
+    };
+
+    #[test]
+    fn breaker_opens_after_consecutive_failures_and_half_opens_after_cooldown() {
+        let mut breaker = CircuitBreaker::default();
+        let now = Instant::now();
+
+        assert!(!breaker.record(false, now, &CONFIG));
+        assert!(!breaker.record(true, now, &CONFIG));
+        assert!(!breaker.record(false, now, &CONFIG));
+        assert!(!breaker.record(false, now, &CONFIG));
+        assert!(breaker.record(false, now, &CONFIG));
+        assert!(!breaker.allow(now + Duration::from_secs(10), &CONFIG));
+
+        // One trial after the cooldown; a failed trial reopens the circuit.
+        let later = now + Duration::from_secs(31);
+        assert!(breaker.allow(later, &CONFIG));
+        assert!(!breaker.allow(later, &CONFIG));
+        assert!(!breaker.record(false, later, &CONFIG));
+        assert!(!breaker.allow(later + Duration::from_secs(1), &CONFIG));
+
+        // A successful trial closes it.
+        let much_later = later + Duration::from_secs(31);
+        assert!(breaker.allow(much_later, &CONFIG));
+        breaker.record(true, much_later, &CONFIG);
+        assert!(breaker.allow(much_later, &CONFIG));
+        assert!(breaker.allow(much_later, &CONFIG));
+    }
+
+    #[test]
+    fn only_public_addresses_are_allowed() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["1.1.1.1", "93.184.216.34", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+
+        assert!(!literal_host_is_public(
+            &Url::parse("http://169.254.169.254/latest/meta-data").unwrap()
+        ));
+        assert!(literal_host_is_public(
+            &Url::parse("https://anchor.example.com/.well-known/stellar.toml").unwrap()
+        ));
+    }
+}
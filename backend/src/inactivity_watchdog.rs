@@ -2,11 +2,12 @@ use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::MissedTickBehavior;
-use tracing::{error, info, warn};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::cache::PlanCache;
+use crate::emergency_contacts::{ContactAlert, ContactNotifier};
+use crate::jobs::JobRegistry;
 
 const DEFAULT_INTERVAL_SECS: u64 = 60 * 60;
 const DEFAULT_BATCH_SIZE: i64 = 500;
@@ -44,33 +45,34 @@ struct ExpiredPlan {
 pub struct InactivityWatchdogService {
     db: PgPool,
     plan_cache: PlanCache,
+    contacts: Arc<ContactNotifier>,
     config: InactivityWatchdogConfig,
 }
 
 impl InactivityWatchdogService {
-    pub fn new(db: PgPool, plan_cache: PlanCache, config: InactivityWatchdogConfig) -> Self {
+    pub fn new(
+        db: PgPool,
+        plan_cache: PlanCache,
+        contacts: Arc<ContactNotifier>,
+        config: InactivityWatchdogConfig,
+    ) -> Self {
         Self {
             db,
             plan_cache,
+            contacts,
             config,
         }
     }
 
-    pub fn start(self: Arc<Self>) {
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(self.config.interval);
-            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
-
-            loop {
-                interval.tick().await;
-
-                match self.run_once().await {
-                    Ok(count) if count > 0 => {
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("inactivity_watchdog", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
                         info!("Inactivity watchdog marked {count} plan(s) as claimable");
                     }
-                    Ok(_) => {}
-                    Err(e) => error!("Inactivity watchdog sweep failed: {e}"),
-                }
+                })
             }
         });
     }
@@ -101,6 +103,14 @@ impl InactivityWatchdogService {
                   AND p.status <> $1
                   AND p.last_ping IS NOT NULL
                   AND p.inactivity_deadline_at <= NOW()
+                  -- Joint plans wait until every co-owner has gone quiet too
+                  AND NOT EXISTS (
+                      SELECT 1 FROM plan_co_owners o
+                      WHERE o.plan_id = p.id
+                        AND o.status = 'accepted'
+                        AND o.last_ping + p.grace_period_seconds
+                            > EXTRACT(EPOCH FROM NOW())::BIGINT
+                  )
                 ORDER BY p.inactivity_deadline_at ASC
                 LIMIT $2
                 FOR UPDATE SKIP LOCKED
@@ -149,6 +159,18 @@ impl InactivityWatchdogService {
         }
 
         tx.commit().await?;
+
+        for plan in &expired_plans {
+            let alert = ContactAlert::PlanClaimable { plan_id: plan.id };
+            if let Err(err) = self
+                .contacts
+                .alert(&self.db, &plan.owner_address, alert)
+                .await
+            {
+                warn!(plan_id = %plan.id, error = %err, "Failed to alert emergency contacts");
+            }
+        }
+
         Ok(expired_plans.len())
     }
 }
@@ -0,0 +1,539 @@
+//! Scheduling and admin control of background jobs.
+//!
+//! Every background worker registers its sweep with the [`JobRegistry`],
+//! which runs it on the worker's interval and records each run in
+//! `job_runs` with its duration and outcome. Admins can list the jobs
+//! running on an instance, trigger one straight away and pause or resume
+//! its scheduled runs. A job runs at most once at a time per instance: a
+//! scheduled tick that finds a run in progress is skipped and a manual
+//! trigger gets `409`. The workers' own advisory locks keep runs on
+//! different instances apart. Pausing is stored in `job_controls`, so it
+//! applies to every instance and survives restarts; it stops scheduled runs
+//! only, and an admin can still trigger a paused job.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio::time::MissedTickBehavior;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::telemetry;
+
+/// Runs kept per job; older ones are pruned as new runs finish.
+const MAX_RUNS_PER_JOB: i64 = 500;
+const MAX_SUMMARY_LEN: usize = 1_000;
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 200;
+
+const RUN_COLUMNS: &str = "id, job_name, trigger, triggered_by, outcome, summary, error, \
+     started_at, finished_at, duration_ms";
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<String, String>> + Send>>;
+type JobFn = Box<dyn Fn() -> JobFuture + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    Scheduled,
+    Manual,
+}
+
+impl Trigger {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct JobRun {
+    pub id: Uuid,
+    pub job_name: String,
+    pub trigger: String,
+    pub triggered_by: String,
+    /// `running`, `succeeded` or `failed`.
+    pub outcome: String,
+    pub summary: Option<String>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug)]
+pub enum RunRefused {
+    UnknownJob,
+    Paused,
+    AlreadyRunning,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RunRefused {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+struct Job {
+    name: &'static str,
+    interval: Duration,
+    run: JobFn,
+    running: Arc<Mutex<()>>,
+}
+
+/// The background jobs of this instance.
+pub struct JobRegistry {
+    db_pool: PgPool,
+    jobs: RwLock<BTreeMap<&'static str, Arc<Job>>>,
+}
+
+impl JobRegistry {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            jobs: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Registers `run` as the job `name` without scheduling it, so it only
+    /// runs when triggered. Registering a name again replaces the job.
+    pub fn register<F, Fut, T, E>(&self, name: &'static str, interval: Duration, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: Debug,
+        E: Display,
+    {
+        let run: JobFn = Box::new(move || {
+            let future = run();
+            Box::pin(async move {
+                future
+                    .await
+                    .map(|value| truncate(format!("{value:?}")))
+                    .map_err(|e| truncate(e.to_string()))
+            })
+        });
+        self.jobs
+            .write()
+            .expect("job registry lock poisoned")
+            .insert(
+                name,
+                Arc::new(Job {
+                    name,
+                    interval,
+                    run,
+                    running: Arc::new(Mutex::new(())),
+                }),
+            );
+    }
+
+    /// Registers `run` as the job `name` and runs it every `interval`.
+    pub fn schedule<F, Fut, T, E>(self: &Arc<Self>, name: &'static str, interval: Duration, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: Debug,
+        E: Display,
+    {
+        self.register(name, interval, run);
+        let registry = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+
+                let run = telemetry::with_correlation_id(
+                    None,
+                    registry.run(name, Trigger::Scheduled, SYSTEM_ACTOR),
+                );
+                match run.await {
+                    Ok(_) | Err(RunRefused::Paused | RunRefused::AlreadyRunning) => {}
+                    Err(RunRefused::UnknownJob) => return,
+                    Err(RunRefused::Database(e)) => {
+                        error!(job = name, "Could not start scheduled job run: {e}")
+                    }
+                }
+            }
+        });
+    }
+
+    fn job(&self, name: &str) -> Option<Arc<Job>> {
+        self.jobs
+            .read()
+            .expect("job registry lock poisoned")
+            .get(name)
+            .cloned()
+    }
+
+    /// Names and intervals of the registered jobs, by name.
+    pub fn list(&self) -> Vec<(&'static str, Duration, bool)> {
+        self.jobs
+            .read()
+            .expect("job registry lock poisoned")
+            .values()
+            .map(|job| (job.name, job.interval, job.running.try_lock().is_err()))
+            .collect()
+    }
+
+    /// Runs the job now and waits for it to finish.
+    pub async fn run(
+        &self,
+        name: &str,
+        trigger: Trigger,
+        actor: &str,
+    ) -> Result<JobRun, RunRefused> {
+        let (job, guard, run) = self.begin(name, trigger, actor).await?;
+        Ok(self.finish(job, guard, run).await)
+    }
+
+    /// Starts the job in the background and returns its run as recorded
+    /// when it started.
+    pub async fn trigger(self: &Arc<Self>, name: &str, actor: &str) -> Result<JobRun, RunRefused> {
+        let (job, guard, run) = self.begin(name, Trigger::Manual, actor).await?;
+        let registry = self.clone();
+        let started = run.clone();
+        telemetry::spawn(async move { registry.finish(job, guard, run).await });
+        Ok(started)
+    }
+
+    async fn begin(
+        &self,
+        name: &str,
+        trigger: Trigger,
+        actor: &str,
+    ) -> Result<(Arc<Job>, OwnedMutexGuard<()>, JobRun), RunRefused> {
+        let job = self.job(name).ok_or(RunRefused::UnknownJob)?;
+        if trigger == Trigger::Scheduled && is_paused(&self.db_pool, name).await? {
+            return Err(RunRefused::Paused);
+        }
+        let guard = job
+            .running
+            .clone()
+            .try_lock_owned()
+            .map_err(|_| RunRefused::AlreadyRunning)?;
+        let run = sqlx::query_as::<_, JobRun>(&format!(
+            r#"
+            INSERT INTO job_runs (job_name, trigger, triggered_by)
+            VALUES ($1, $2, $3)
+            RETURNING {RUN_COLUMNS}
+            "#
+        ))
+        .bind(name)
+        .bind(trigger.as_str())
+        .bind(actor)
+        .fetch_one(&self.db_pool)
+        .await?;
+        Ok((job, guard, run))
+    }
+
+    async fn finish(&self, job: Arc<Job>, _guard: OwnedMutexGuard<()>, run: JobRun) -> JobRun {
+        let started = Instant::now();
+        let result = (job.run)().await;
+        let duration_ms = i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX);
+        let (outcome, summary, failure) = match result {
+            Ok(summary) => ("succeeded", Some(summary), None),
+            Err(e) => {
+                error!(job = job.name, "Job run failed: {e}");
+                ("failed", None, Some(e))
+            }
+        };
+
+        let recorded = sqlx::query_as::<_, JobRun>(&format!(
+            r#"
+            UPDATE job_runs
+            SET outcome = $2, summary = $3, error = $4, finished_at = NOW(), duration_ms = $5
+            WHERE id = $1
+            RETURNING {RUN_COLUMNS}
+            "#
+        ))
+        .bind(run.id)
+        .bind(outcome)
+        .bind(&summary)
+        .bind(&failure)
+        .bind(duration_ms)
+        .fetch_one(&self.db_pool)
+        .await;
+        if let Err(e) = sqlx::query(
+            r#"
+            DELETE FROM job_runs
+            WHERE job_name = $1
+              AND id NOT IN (SELECT id FROM job_runs WHERE job_name = $1
+                             ORDER BY started_at DESC LIMIT $2)
+            "#,
+        )
+        .bind(job.name)
+        .bind(MAX_RUNS_PER_JOB)
+        .execute(&self.db_pool)
+        .await
+        {
+            warn!(job = job.name, "Could not prune job runs: {e}");
+        }
+
+        recorded.unwrap_or_else(|e| {
+            warn!(job = job.name, run_id = %run.id, "Could not record job run: {e}");
+            JobRun {
+                outcome: outcome.to_string(),
+                summary,
+                error: failure,
+                finished_at: Some(Utc::now()),
+                duration_ms: Some(duration_ms),
+                ..run
+            }
+        })
+    }
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_SUMMARY_LEN {
+        let mut end = MAX_SUMMARY_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+async fn is_paused(pool: &PgPool, name: &str) -> Result<bool, sqlx::Error> {
+    let paused: Option<bool> =
+        sqlx::query_scalar("SELECT paused FROM job_controls WHERE job_name = $1")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?;
+    Ok(paused.unwrap_or(false))
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobStatus {
+    pub name: &'static str,
+    pub interval_secs: u64,
+    pub paused: bool,
+    /// Whether a run is in progress on this instance.
+    pub running: bool,
+    pub last_run: Option<JobRun>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn unknown_job() -> Response {
+    refused(StatusCode::NOT_FOUND, "No such job on this instance")
+}
+
+// Handler: List Jobs
+pub async fn list_jobs(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let result: Result<Vec<JobStatus>, sqlx::Error> = async {
+        let paused: HashMap<String, bool> =
+            sqlx::query_as::<_, (String, bool)>("SELECT job_name, paused FROM job_controls")
+                .fetch_all(&state.db_pool)
+                .await?
+                .into_iter()
+                .collect();
+        let mut last_runs: HashMap<String, JobRun> = sqlx::query_as::<_, JobRun>(&format!(
+            r#"
+            SELECT DISTINCT ON (job_name) {RUN_COLUMNS}
+            FROM job_runs
+            ORDER BY job_name, started_at DESC
+            "#
+        ))
+        .fetch_all(&state.db_pool)
+        .await?
+        .into_iter()
+        .map(|run| (run.job_name.clone(), run))
+        .collect();
+
+        Ok(state
+            .jobs
+            .list()
+            .into_iter()
+            .map(|(name, interval, running)| JobStatus {
+                name,
+                interval_secs: interval.as_secs(),
+                paused: paused.get(name).copied().unwrap_or(false),
+                running,
+                last_run: last_runs.remove(name),
+            })
+            .collect())
+    }
+    .await;
+
+    match result {
+        Ok(jobs) => Json(jobs).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list jobs");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get Job Runs
+pub async fn get_job_runs(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<RunHistoryQuery>,
+) -> impl IntoResponse {
+    if state.jobs.job(&name).is_none() {
+        return unknown_job();
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+
+    match sqlx::query_as::<_, JobRun>(&format!(
+        r#"
+        SELECT {RUN_COLUMNS}
+        FROM job_runs
+        WHERE job_name = $1
+        ORDER BY started_at DESC
+        LIMIT $2
+        "#
+    ))
+    .bind(&name)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(runs) => Json(runs).into_response(),
+        Err(e) => {
+            error!(error = %e, job = %name, "Failed to load job runs");
+            database_error()
+        }
+    }
+}
+
+// Handler: Trigger Job
+pub async fn trigger_job(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.jobs.trigger(&name, &admin.user_id).await {
+        Ok(run) => {
+            if let Err(e) = record_audit(
+                &state.db_pool,
+                &admin.user_id,
+                "job.triggered",
+                &name,
+                serde_json::json!({ "run_id": run.id }),
+            )
+            .await
+            {
+                warn!(error = %e, job = %name, "Failed to audit job trigger");
+            }
+            (StatusCode::ACCEPTED, Json(run)).into_response()
+        }
+        Err(RunRefused::UnknownJob) => unknown_job(),
+        Err(RunRefused::AlreadyRunning | RunRefused::Paused) => {
+            refused(StatusCode::CONFLICT, "The job is already running")
+        }
+        Err(RunRefused::Database(e)) => {
+            error!(error = %e, job = %name, "Failed to trigger job");
+            database_error()
+        }
+    }
+}
+
+async fn set_paused(state: &AppState, admin: &UserContext, name: &str, paused: bool) -> Response {
+    if state.jobs.job(name).is_none() {
+        return unknown_job();
+    }
+
+    let result: Result<(), sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO job_controls (job_name, paused, updated_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (job_name) DO UPDATE
+            SET paused = EXCLUDED.paused, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+            "#,
+        )
+        .bind(name)
+        .bind(paused)
+        .bind(&admin.user_id)
+        .execute(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            if paused { "job.paused" } else { "job.resumed" },
+            name,
+            serde_json::json!({}),
+        )
+        .await?;
+        tx.commit().await
+    }
+    .await;
+
+    match result {
+        Ok(()) => Json(serde_json::json!({ "name": name, "paused": paused })).into_response(),
+        Err(e) => {
+            error!(error = %e, job = %name, "Failed to update job control");
+            database_error()
+        }
+    }
+}
+
+// Handler: Pause Job
+pub async fn pause_job(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    set_paused(&state, &admin, &name, true).await
+}
+
+// Handler: Resume Job
+pub async fn resume_job(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    set_paused(&state, &admin, &name, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_are_truncated_on_a_character_boundary() {
+        let long = "é".repeat(MAX_SUMMARY_LEN);
+        let truncated = truncate(long);
+        assert!(truncated.len() <= MAX_SUMMARY_LEN);
+        assert!(truncated.chars().all(|c| c == 'é'));
+        assert_eq!(truncate("3".to_string()), "3");
+    }
+}
@@ -0,0 +1,884 @@
+//! Keeper for the inheritance contract's permissionless entrypoints.
+//!
+//! Some contract steps only happen when somebody calls them: `claim` starts
+//! the payout timelock once a plan is past its inactivity deadline,
+//! `trigger_payout` pays the beneficiaries once that timelock has run, and
+//! `escheat` releases a plan nobody claimed within the claim window.
+//! [`KeeperService`] finds plans due for each step in the backend's mirror
+//! of contract state, keeps one job per plan and due time in
+//! `keeper_jobs`, and submits the calls.
+//!
+//! With `SOROBAN_RPC_URL` and `KEEPER_SOURCE_ACCOUNT` set, every call is
+//! simulated first, so the contract's own checks decide whether it is due
+//! and the simulated fee is what counts against the budget. Calls over
+//! `KEEPER_MAX_FEE_STROOPS`, or past `KEEPER_DAILY_FEE_BUDGET_STROOPS` in
+//! a rolling day, wait for a later sweep. Failed sweeps, missed sweeps and
+//! jobs that run out of attempts are counted in metrics and emailed to
+//! `KEEPER_ALERT_EMAILS`.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::chain::errors::{ContractError, ContractInterface, InheritanceError};
+use crate::chain::{ContractInvocation, TxError, TxService};
+use crate::jobs::JobRegistry;
+use crate::metrics::{KEEPER_FEES, KEEPER_INVOCATIONS, KEEPER_RUNS};
+use crate::simulation::{build_envelope, summarize, ContractArg};
+use crate::telemetry;
+
+const DEFAULT_INTERVAL_SECS: u64 = 5 * 60;
+const DEFAULT_BATCH_SIZE: i64 = 200;
+const DEFAULT_MAX_INVOCATIONS: i64 = 25;
+/// 1 XLM.
+const DEFAULT_MAX_FEE_STROOPS: i64 = 10_000_000;
+/// 50 XLM.
+const DEFAULT_DAILY_FEE_BUDGET_STROOPS: i64 = 500_000_000;
+/// Fee assumed for a call that could not be simulated.
+const DEFAULT_FEE_ESTIMATE_STROOPS: i64 = 100_000;
+const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+const DEFAULT_RECHECK_SECS: u64 = 60 * 60;
+const MAX_RETRY_DELAY_SECS: u64 = 24 * 60 * 60;
+const KEEPER_LOCK_KEY: i64 = 836;
+const RECENT_RUNS: i64 = 20;
+const OPEN_JOBS_LIMIT: i64 = 100;
+
+/// A contract entrypoint the keeper calls. The name is also the function
+/// name on the contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeeperTask {
+    Claim,
+    TriggerPayout,
+    Escheat,
+}
+
+impl KeeperTask {
+    /// In the order a plan goes through them.
+    pub const ALL: [Self; 3] = [Self::Claim, Self::TriggerPayout, Self::Escheat];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Claim => "claim",
+            Self::TriggerPayout => "trigger_payout",
+            Self::Escheat => "escheat",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|task| task.as_str() == value)
+    }
+
+    /// Plans due for this task as `(id, owner_address, due_at)`. `$2` is
+    /// the claim window in seconds.
+    fn due_plans_sql(self) -> &'static str {
+        match self {
+            // Past the inactivity deadline with the claim window still open.
+            Self::Claim => {
+                r#"
+                SELECT p.id, p.owner_address, p.inactivity_deadline_at AS due_at
+                FROM plans p
+                WHERE p.status = 'CLAIMABLE'
+                  AND p.frozen_at IS NULL
+                  AND p.inactivity_deadline_at > NOW() - $2 * INTERVAL '1 second'
+                "#
+            }
+            // Claimed by the keeper for the current deadline; the contract
+            // refuses the payout until the timelock has run.
+            Self::TriggerPayout => {
+                r#"
+                SELECT p.id, p.owner_address, p.inactivity_deadline_at AS due_at
+                FROM plans p
+                WHERE p.status = 'CLAIMABLE'
+                  AND p.frozen_at IS NULL
+                  AND EXISTS (
+                      SELECT 1 FROM keeper_jobs c
+                      WHERE c.plan_id = p.id AND c.task = 'claim' AND c.status = 'submitted'
+                        AND c.due_at = p.inactivity_deadline_at)
+                "#
+            }
+            // Window closed with no claim filed here or on-chain.
+            Self::Escheat => {
+                r#"
+                SELECT p.id, p.owner_address,
+                       p.inactivity_deadline_at + $2 * INTERVAL '1 second' AS due_at
+                FROM plans p
+                WHERE p.status <> 'PAID_OUT'
+                  AND p.frozen_at IS NULL
+                  AND p.inactivity_deadline_at <= NOW() - $2 * INTERVAL '1 second'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM claim_requests r
+                      WHERE r.plan_id = p.id AND r.status IN ('pending', 'in_review'))
+                  AND NOT EXISTS (
+                      SELECT 1 FROM keeper_jobs c
+                      WHERE c.plan_id = p.id AND c.task = 'claim' AND c.status = 'submitted'
+                        AND c.due_at = p.inactivity_deadline_at)
+                "#
+            }
+        }
+    }
+}
+
+/// What a refused call means for its job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+    /// The contract is not ready for the call yet; check again later.
+    NotDue,
+    /// The work no longer applies, e.g. the plan is gone on-chain.
+    Obsolete,
+    /// Anything else; counts as a failed attempt.
+    Failed,
+}
+
+/// Sorts a contract error from a keeper call into a [`Refusal`].
+pub fn classify(error: InheritanceError) -> Refusal {
+    match error {
+        InheritanceError::InactivityPeriodNotMet
+        | InheritanceError::TimelockNotExpired
+        | InheritanceError::PayoutNotTriggered
+        | InheritanceError::ClaimsPaused
+        | InheritanceError::ClaimInProgress
+        | InheritanceError::ClaimWindowOpen => Refusal::NotDue,
+        InheritanceError::PlanNotFound | InheritanceError::ClaimWindowClosed => Refusal::Obsolete,
+        _ => Refusal::Failed,
+    }
+}
+
+/// Sweeps that should have started in a `gap_secs` gap between two sweeps
+/// `interval_secs` apart. A sweep up to one interval late is not missed.
+pub fn missed_runs(gap_secs: i64, interval_secs: u64) -> i32 {
+    let interval = interval_secs.max(1) as i64;
+    if gap_secs <= 2 * interval {
+        return 0;
+    }
+    (gap_secs / interval - 1).min(i32::MAX as i64) as i32
+}
+
+/// Wait before retrying a call that failed `attempts` times: the sweep
+/// interval, doubling per attempt, at most a day.
+pub fn retry_delay(attempts: i32, interval: Duration) -> Duration {
+    let factor = 1u32 << attempts.clamp(1, 16).saturating_sub(1);
+    (interval * factor).min(Duration::from_secs(MAX_RETRY_DELAY_SECS))
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone)]
+pub struct KeeperConfig {
+    pub interval: Duration,
+    /// Plans taken up per task per sweep.
+    pub batch_size: i64,
+    /// Calls submitted per sweep.
+    pub max_invocations: i64,
+    /// Calls simulated at a higher fee wait for a later sweep.
+    pub max_fee_stroops: i64,
+    /// Fees the keeper may spend in any 24 hours.
+    pub daily_fee_budget_stroops: i64,
+    /// Fee counted for a call that was not simulated.
+    pub fee_estimate_stroops: i64,
+    /// Failed attempts before a job is given up and alerted.
+    pub max_attempts: i32,
+    /// Wait before asking again about a call that was not due.
+    pub recheck_after: Duration,
+    /// Funded account calls are simulated from; calls are not simulated
+    /// without it.
+    pub source_account: Option<String>,
+    /// Addresses emailed about failures; alerts are only logged when empty.
+    pub alert_recipients: Vec<String>,
+}
+
+impl KeeperConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("KEEPER_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let recheck_secs = parse_env("KEEPER_RECHECK_SECS", DEFAULT_RECHECK_SECS);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: parse_env("KEEPER_BATCH_SIZE", DEFAULT_BATCH_SIZE).max(1),
+            max_invocations: parse_env("KEEPER_MAX_INVOCATIONS_PER_RUN", DEFAULT_MAX_INVOCATIONS)
+                .max(1),
+            max_fee_stroops: parse_env("KEEPER_MAX_FEE_STROOPS", DEFAULT_MAX_FEE_STROOPS).max(0),
+            daily_fee_budget_stroops: parse_env(
+                "KEEPER_DAILY_FEE_BUDGET_STROOPS",
+                DEFAULT_DAILY_FEE_BUDGET_STROOPS,
+            )
+            .max(0),
+            fee_estimate_stroops: parse_env(
+                "KEEPER_FEE_ESTIMATE_STROOPS",
+                DEFAULT_FEE_ESTIMATE_STROOPS,
+            )
+            .max(0),
+            max_attempts: parse_env("KEEPER_MAX_ATTEMPTS", DEFAULT_MAX_ATTEMPTS).max(1),
+            recheck_after: Duration::from_secs(recheck_secs.max(1)),
+            source_account: std::env::var("KEEPER_SOURCE_ACCOUNT")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            alert_recipients: std::env::var("KEEPER_ALERT_EMAILS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Work done by one sweep.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeeperSweep {
+    pub missed_runs: i32,
+    pub discovered: i32,
+    pub submitted: i32,
+    pub not_due: i32,
+    pub cancelled: i32,
+    pub failed: i32,
+    pub deferred: i32,
+    pub fees_stroops: i64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DueJob {
+    id: Uuid,
+    task: String,
+    plan_id: Uuid,
+    owner_address: String,
+    attempts: i32,
+}
+
+enum Attempt {
+    Submitted { tx_hash: String, fee: i64 },
+    Refused(Refusal, String),
+    OverFeeCap(i64),
+    OverBudget,
+}
+
+pub struct KeeperService {
+    state: Arc<AppState>,
+    tx_service: Arc<dyn TxService>,
+    contract_id: String,
+    config: KeeperConfig,
+    budget_exhausted: AtomicBool,
+}
+
+impl KeeperService {
+    pub fn new(
+        state: Arc<AppState>,
+        tx_service: Arc<dyn TxService>,
+        contract_id: String,
+        config: KeeperConfig,
+    ) -> Self {
+        Self {
+            state,
+            tx_service,
+            contract_id,
+            config,
+            budget_exhausted: AtomicBool::new(false),
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("keeper", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|sweep| {
+                    if sweep.submitted > 0 || sweep.failed > 0 {
+                        info!(
+                            submitted = sweep.submitted,
+                            failed = sweep.failed,
+                            deferred = sweep.deferred,
+                            fees_stroops = sweep.fees_stroops,
+                            "Keeper sweep finished"
+                        );
+                    }
+                })
+            }
+        });
+    }
+
+    /// Finds due work, submits what the budget allows and records the
+    /// sweep in `keeper_runs`.
+    pub async fn run_once(&self) -> Result<KeeperSweep, sqlx::Error> {
+        let db = &self.state.db_pool;
+        let mut tx = db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(KEEPER_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Keeper lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(KeeperSweep::default());
+        }
+
+        let last_started: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT MAX(started_at) FROM keeper_runs")
+                .fetch_one(&mut *tx)
+                .await?;
+        let missed = last_started.map_or(0, |at| {
+            missed_runs(
+                (Utc::now() - at).num_seconds(),
+                self.config.interval.as_secs(),
+            )
+        });
+        // Recorded outside the sweep's transaction so a sweep that fails
+        // still leaves its row behind.
+        let run_id: Uuid =
+            sqlx::query_scalar("INSERT INTO keeper_runs (missed_runs) VALUES ($1) RETURNING id")
+                .bind(missed)
+                .fetch_one(db)
+                .await?;
+
+        let mut alerts = Vec::new();
+        if missed > 0 {
+            KEEPER_RUNS
+                .with_label_values(&["missed"])
+                .inc_by(f64::from(missed));
+            alerts.push(format!(
+                "The keeper missed {missed} scheduled sweep(s); the previous one started at {}.",
+                last_started.map(|at| at.to_rfc3339()).unwrap_or_default()
+            ));
+        }
+
+        let mut sweep = KeeperSweep {
+            missed_runs: missed,
+            ..KeeperSweep::default()
+        };
+        let result = async {
+            self.sweep(&mut tx, &mut sweep, &mut alerts).await?;
+            sqlx::query(
+                r#"
+                UPDATE keeper_runs
+                SET finished_at = NOW(), discovered = $2, submitted = $3, not_due = $4,
+                    cancelled = $5, failed = $6, deferred = $7, fees_stroops = $8
+                WHERE id = $1
+                "#,
+            )
+            .bind(run_id)
+            .bind(sweep.discovered)
+            .bind(sweep.submitted)
+            .bind(sweep.not_due)
+            .bind(sweep.cancelled)
+            .bind(sweep.failed)
+            .bind(sweep.deferred)
+            .bind(sweep.fees_stroops)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                KEEPER_RUNS.with_label_values(&["completed"]).inc();
+                self.alert(&alerts).await;
+                Ok(sweep)
+            }
+            Err(e) => {
+                KEEPER_RUNS.with_label_values(&["failed"]).inc();
+                if let Err(update) = sqlx::query(
+                    "UPDATE keeper_runs SET finished_at = NOW(), error = $2 WHERE id = $1",
+                )
+                .bind(run_id)
+                .bind(e.to_string())
+                .execute(db)
+                .await
+                {
+                    warn!(error = %update, "Failed to record keeper sweep failure");
+                }
+                alerts.push(format!("A keeper sweep failed: {e}"));
+                self.alert(&alerts).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn sweep(
+        &self,
+        conn: &mut PgConnection,
+        sweep: &mut KeeperSweep,
+        alerts: &mut Vec<String>,
+    ) -> Result<(), sqlx::Error> {
+        let window = self
+            .state
+            .system_settings
+            .get()
+            .await
+            .claim_window()
+            .num_seconds() as f64;
+
+        for task in KeeperTask::ALL {
+            // Owners who pinged, plans paid out or frozen: the job's due
+            // time no longer matches the plan.
+            sqlx::query(&format!(
+                r#"
+                UPDATE keeper_jobs j
+                SET status = 'cancelled', last_error = 'No longer due', updated_at = NOW()
+                WHERE j.task = $1 AND j.status = 'pending'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM ({}) d WHERE d.id = j.plan_id AND d.due_at = j.due_at)
+                "#,
+                task.due_plans_sql()
+            ))
+            .bind(task.as_str())
+            .bind(window)
+            .execute(&mut *conn)
+            .await?;
+
+            let discovered = sqlx::query(&format!(
+                r#"
+                INSERT INTO keeper_jobs (task, plan_id, owner_address, due_at)
+                SELECT $1::text, d.id, d.owner_address, d.due_at
+                FROM ({}) d
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM keeper_jobs j
+                    WHERE j.task = $1 AND j.plan_id = d.id AND j.due_at = d.due_at)
+                ORDER BY d.due_at
+                LIMIT $3
+                ON CONFLICT (task, plan_id, due_at) DO NOTHING
+                "#,
+                task.due_plans_sql()
+            ))
+            .bind(task.as_str())
+            .bind(window)
+            .bind(self.config.batch_size)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+            sweep.discovered += discovered as i32;
+        }
+
+        let spent: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(fee_stroops), 0)::BIGINT FROM keeper_jobs
+            WHERE submitted_at > NOW() - INTERVAL '1 day'
+            "#,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let mut budget_left = self.config.daily_fee_budget_stroops - spent;
+
+        let jobs = sqlx::query_as::<_, DueJob>(
+            r#"
+            SELECT id, task, plan_id, owner_address, attempts
+            FROM keeper_jobs
+            WHERE status = 'pending' AND due_at <= NOW() AND next_attempt_at <= NOW()
+            ORDER BY due_at, created_at
+            LIMIT $1
+            "#,
+        )
+        .bind(self.config.max_invocations)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let recheck_secs = self.config.recheck_after.as_secs() as f64;
+        let mut over_budget = false;
+        for (index, job) in jobs.iter().enumerate() {
+            let Some(task) = KeeperTask::parse(&job.task) else {
+                continue;
+            };
+            match self.attempt(task, job, budget_left).await {
+                Attempt::Submitted { tx_hash, fee } => {
+                    sqlx::query(
+                        r#"
+                        UPDATE keeper_jobs
+                        SET status = 'submitted', attempts = attempts + 1, tx_hash = $2,
+                            fee_stroops = $3, submitted_at = NOW(), last_error = NULL,
+                            updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(job.id)
+                    .bind(&tx_hash)
+                    .bind(fee)
+                    .execute(&mut *conn)
+                    .await?;
+                    record(task, "submitted");
+                    KEEPER_FEES
+                        .with_label_values(&[task.as_str()])
+                        .inc_by(fee as f64);
+                    info!(
+                        plan_id = %job.plan_id,
+                        task = task.as_str(),
+                        tx_hash = %tx_hash,
+                        fee_stroops = fee,
+                        "Keeper submitted contract call"
+                    );
+                    budget_left -= fee;
+                    sweep.submitted += 1;
+                    sweep.fees_stroops += fee;
+                }
+                Attempt::Refused(Refusal::NotDue, reason) => {
+                    self.defer(&mut *conn, job.id, recheck_secs, &reason)
+                        .await?;
+                    record(task, "not_due");
+                    sweep.not_due += 1;
+                }
+                Attempt::Refused(Refusal::Obsolete, reason) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE keeper_jobs
+                        SET status = 'cancelled', last_error = $2, updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(job.id)
+                    .bind(&reason)
+                    .execute(&mut *conn)
+                    .await?;
+                    record(task, "cancelled");
+                    sweep.cancelled += 1;
+                }
+                Attempt::Refused(Refusal::Failed, reason) => {
+                    let attempts = job.attempts + 1;
+                    let gave_up = attempts >= self.config.max_attempts;
+                    let retry_secs = retry_delay(attempts, self.config.interval).as_secs() as f64;
+                    sqlx::query(
+                        r#"
+                        UPDATE keeper_jobs
+                        SET status = CASE WHEN $3 THEN 'failed' ELSE 'pending' END,
+                            attempts = $2, last_error = $4,
+                            next_attempt_at = NOW() + $5 * INTERVAL '1 second',
+                            updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(job.id)
+                    .bind(attempts)
+                    .bind(gave_up)
+                    .bind(&reason)
+                    .bind(retry_secs)
+                    .execute(&mut *conn)
+                    .await?;
+                    warn!(
+                        plan_id = %job.plan_id,
+                        task = task.as_str(),
+                        attempts,
+                        error = %reason,
+                        "Keeper contract call failed"
+                    );
+                    if gave_up {
+                        record(task, "failed");
+                        alerts.push(format!(
+                            "{} for plan {} failed {attempts} times and was given up: {reason}",
+                            task.as_str(),
+                            job.plan_id
+                        ));
+                    } else {
+                        record(task, "retrying");
+                    }
+                    sweep.failed += 1;
+                }
+                Attempt::OverFeeCap(fee) => {
+                    let reason = format!(
+                        "Estimated fee of {fee} stroops is over the {} stroop cap",
+                        self.config.max_fee_stroops
+                    );
+                    self.defer(&mut *conn, job.id, recheck_secs, &reason)
+                        .await?;
+                    record(task, "deferred");
+                    sweep.deferred += 1;
+                }
+                Attempt::OverBudget => {
+                    let deferred = jobs.len() - index;
+                    KEEPER_INVOCATIONS
+                        .with_label_values(&[task.as_str(), "deferred"])
+                        .inc_by(deferred as f64);
+                    sweep.deferred += deferred as i32;
+                    over_budget = true;
+                    break;
+                }
+            }
+        }
+
+        // Alert once when the budget runs out, not on every sweep after.
+        if over_budget && !self.budget_exhausted.swap(true, Ordering::Relaxed) {
+            alerts.push(format!(
+                "The keeper's daily fee budget of {} stroops is spent; due calls are waiting.",
+                self.config.daily_fee_budget_stroops
+            ));
+        } else if !over_budget {
+            self.budget_exhausted.store(false, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    async fn defer(
+        &self,
+        conn: &mut PgConnection,
+        job_id: Uuid,
+        after_secs: f64,
+        reason: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE keeper_jobs
+            SET next_attempt_at = NOW() + $2 * INTERVAL '1 second', last_error = $3,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(job_id)
+        .bind(after_secs)
+        .bind(reason)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    async fn attempt(&self, task: KeeperTask, job: &DueJob, budget_left: i64) -> Attempt {
+        let fee = match self.preflight(task, &job.owner_address).await {
+            Ok(fee) => fee,
+            Err((refusal, reason)) => return Attempt::Refused(refusal, reason),
+        };
+        if fee > self.config.max_fee_stroops {
+            return Attempt::OverFeeCap(fee);
+        }
+        if fee > budget_left {
+            return Attempt::OverBudget;
+        }
+
+        let invocation = ContractInvocation {
+            contract_id: self.contract_id.clone(),
+            function: task.as_str().to_string(),
+            args: vec![job.owner_address.clone()],
+            memo: telemetry::current_memo(),
+        };
+        match self.tx_service.invoke_contract(&invocation).await {
+            Ok(tx_hash) => Attempt::Submitted { tx_hash, fee },
+            Err(e) => {
+                let refusal = match &e {
+                    TxError::Contract(ContractError::Inheritance(code)) => classify(*code),
+                    _ => Refusal::Failed,
+                };
+                Attempt::Refused(refusal, e.to_string())
+            }
+        }
+    }
+
+    /// Simulates the call and returns its fee, or the configured estimate
+    /// when calls are not simulated.
+    async fn preflight(&self, task: KeeperTask, owner: &str) -> Result<i64, (Refusal, String)> {
+        let Some(source) = self
+            .config
+            .source_account
+            .as_deref()
+            .filter(|_| self.state.soroban_rpc.is_configured())
+        else {
+            return Ok(self.config.fee_estimate_stroops);
+        };
+        let envelope = build_envelope(
+            source,
+            &self.contract_id,
+            task.as_str(),
+            &[ContractArg::Address(owner.to_string())],
+        )
+        .map_err(|e| (Refusal::Failed, e))?;
+        let response = self
+            .state
+            .soroban_rpc
+            .simulate_transaction(&envelope)
+            .await
+            .map_err(|e| (Refusal::Failed, format!("Simulation failed: {e}")))?;
+
+        let result = summarize(&response, Some(ContractInterface::Inheritance));
+        if let Some(error) = result.error {
+            let refusal = error
+                .code
+                .and_then(InheritanceError::from_code)
+                .map_or(Refusal::Failed, classify);
+            return Err((refusal, error.detail));
+        }
+        Ok(result
+            .fee
+            .map_or(self.config.fee_estimate_stroops, |fee| fee.total_stroops))
+    }
+
+    async fn alert(&self, problems: &[String]) {
+        if problems.is_empty() {
+            return;
+        }
+        error!(problems = ?problems, "Keeper needs attention");
+
+        let subject = format!("InheritX keeper: {} problem(s)", problems.len());
+        let mut body = String::from("The contract keeper reported:\n\n");
+        for problem in problems {
+            body.push_str(&format!("- {problem}\n"));
+        }
+        body.push_str("\nReview recent sweeps and open jobs at GET /api/admin/keeper.\n");
+
+        for recipient in &self.config.alert_recipients {
+            if let Err(e) = self.state.mailer.send(recipient, &subject, &body).await {
+                warn!(recipient = %recipient, error = %e, "Failed to email keeper alert");
+            }
+        }
+    }
+}
+
+fn record(task: KeeperTask, outcome: &str) {
+    KEEPER_INVOCATIONS
+        .with_label_values(&[task.as_str(), outcome])
+        .inc();
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct KeeperRun {
+    pub id: Uuid,
+    pub started_at: DateTime<Utc>,
+    /// `None` while running, or for a sweep that died.
+    pub finished_at: Option<DateTime<Utc>>,
+    pub missed_runs: i32,
+    pub discovered: i32,
+    pub submitted: i32,
+    pub not_due: i32,
+    pub cancelled: i32,
+    pub failed: i32,
+    pub deferred: i32,
+    pub fees_stroops: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct KeeperJob {
+    pub id: Uuid,
+    pub task: String,
+    pub plan_id: Uuid,
+    pub owner_address: String,
+    pub due_at: DateTime<Utc>,
+    /// `pending`, `submitted`, `failed` or `cancelled`.
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub fee_stroops: Option<i64>,
+    pub tx_hash: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeeperStatus {
+    pub runs: Vec<KeeperRun>,
+    /// Pending and given-up jobs, given-up first.
+    pub open_jobs: Vec<KeeperJob>,
+    pub fees_last_day_stroops: i64,
+}
+
+// Handler: Get Keeper Status (admin)
+pub async fn get_keeper_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let result: Result<KeeperStatus, sqlx::Error> = async {
+        let runs = sqlx::query_as::<_, KeeperRun>(
+            r#"
+            SELECT id, started_at, finished_at, missed_runs, discovered, submitted, not_due,
+                   cancelled, failed, deferred, fees_stroops, error
+            FROM keeper_runs
+            ORDER BY started_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(RECENT_RUNS)
+        .fetch_all(&state.db_pool)
+        .await?;
+        let open_jobs = sqlx::query_as::<_, KeeperJob>(
+            r#"
+            SELECT id, task, plan_id, owner_address, due_at, status, attempts, next_attempt_at,
+                   last_error, fee_stroops, tx_hash, submitted_at
+            FROM keeper_jobs
+            WHERE status IN ('pending', 'failed')
+            ORDER BY status = 'failed' DESC, due_at
+            LIMIT $1
+            "#,
+        )
+        .bind(OPEN_JOBS_LIMIT)
+        .fetch_all(&state.db_pool)
+        .await?;
+        let fees_last_day_stroops = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(fee_stroops), 0)::BIGINT FROM keeper_jobs
+            WHERE submitted_at > NOW() - INTERVAL '1 day'
+            "#,
+        )
+        .fetch_one(&state.db_pool)
+        .await?;
+        Ok(KeeperStatus {
+            runs,
+            open_jobs,
+            fees_last_day_stroops,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to load keeper status");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_names_are_contract_functions() {
+        for task in KeeperTask::ALL {
+            assert_eq!(KeeperTask::parse(task.as_str()), Some(task));
+        }
+        assert_eq!(KeeperTask::TriggerPayout.as_str(), "trigger_payout");
+        assert_eq!(KeeperTask::parse("bump_storage"), None);
+    }
+
+    #[test]
+    fn classifies_contract_refusals() {
+        assert_eq!(
+            classify(InheritanceError::TimelockNotExpired),
+            Refusal::NotDue
+        );
+        assert_eq!(classify(InheritanceError::ClaimWindowOpen), Refusal::NotDue);
+        assert_eq!(classify(InheritanceError::PlanNotFound), Refusal::Obsolete);
+        assert_eq!(
+            classify(InheritanceError::ClaimWindowClosed),
+            Refusal::Obsolete
+        );
+        assert_eq!(
+            classify(InheritanceError::TierLimitExceeded),
+            Refusal::Failed
+        );
+    }
+
+    #[test]
+    fn counts_missed_sweeps() {
+        assert_eq!(missed_runs(300, 300), 0);
+        assert_eq!(missed_runs(600, 300), 0);
+        assert_eq!(missed_runs(601, 300), 1);
+        assert_eq!(missed_runs(3_000, 300), 9);
+    }
+
+    #[test]
+    fn retries_back_off_to_a_day() {
+        let interval = Duration::from_secs(300);
+        assert_eq!(retry_delay(1, interval), interval);
+        assert_eq!(retry_delay(3, interval), interval * 4);
+        assert_eq!(retry_delay(20, interval), Duration::from_secs(24 * 60 * 60));
+    }
+}
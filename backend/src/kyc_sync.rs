@@ -0,0 +1,461 @@
+//! Keeps the inheritance contract's KYC tiers in step with the database.
+//!
+//! The contract holds a tier per address and enforces its limits on-chain:
+//! a user's tier once their KYC is approved, `basic` otherwise. A trigger
+//! on `users` marks a user `pending_push` whenever that tier changes, and
+//! [`KycSyncService`] pushes it with `set_kyc_tier` through the transaction
+//! service. Failed pushes are retried with backoff and marked `failed`
+//! after `KYC_SYNC_MAX_ATTEMPTS`. Admins can see and force a user's sync,
+//! and the dashboard counts users in each state.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::chain::{ContractInvocation, TxService};
+use crate::jobs::JobRegistry;
+use crate::keeper::retry_delay;
+use crate::kyc_tiers::KycTier;
+use crate::metrics::{KYC_SYNC_PUSHES, KYC_SYNC_USERS};
+use crate::telemetry;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_BATCH_SIZE: i64 = 50;
+const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+const KYC_SYNC_LOCK_KEY: i64 = 838;
+const RECENT_FAILURES_LIMIT: i64 = 20;
+const MAX_ERROR_CHARS: usize = 1000;
+
+const SYNC_COLUMNS: &str = "wallet_address, kyc_status::text AS kyc_status, kyc_tier, \
+     kyc_onchain_tier(kyc_status, kyc_tier) AS onchain_tier, kyc_synced_tier, kyc_sync_status, \
+     kyc_sync_attempts, kyc_sync_next_attempt_at, kyc_sync_error, kyc_sync_tx_hash, kyc_synced_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    Synced,
+    PendingPush,
+    Failed,
+}
+
+impl SyncStatus {
+    pub const ALL: [Self; 3] = [Self::Synced, Self::PendingPush, Self::Failed];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Synced => "synced",
+            Self::PendingPush => "pending_push",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// A user's KYC standing and whether the contract has it.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct KycSyncState {
+    pub wallet_address: String,
+    pub kyc_status: String,
+    pub kyc_tier: String,
+    /// Tier the contract should hold.
+    pub onchain_tier: String,
+    /// Tier last pushed to the contract.
+    #[sqlx(rename = "kyc_synced_tier")]
+    pub synced_tier: String,
+    #[sqlx(rename = "kyc_sync_status")]
+    pub sync_status: String,
+    #[sqlx(rename = "kyc_sync_attempts")]
+    pub attempts: i32,
+    #[sqlx(rename = "kyc_sync_next_attempt_at")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    #[sqlx(rename = "kyc_sync_error")]
+    pub last_error: Option<String>,
+    #[sqlx(rename = "kyc_sync_tx_hash")]
+    pub tx_hash: Option<String>,
+    pub kyc_synced_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SyncFailure {
+    pub wallet_address: String,
+    pub onchain_tier: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KycSyncDrift {
+    pub synced: i64,
+    pub pending_push: i64,
+    pub failed: i64,
+    /// When the longest-waiting push was queued.
+    pub oldest_pending_since: Option<DateTime<Utc>>,
+    pub recent_failures: Vec<SyncFailure>,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+async fn load_state<'e, E>(executor: E, user: &str) -> Result<Option<KycSyncState>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, KycSyncState>(&format!(
+        "SELECT {SYNC_COLUMNS} FROM users WHERE id::text = $1 OR wallet_address = $1"
+    ))
+    .bind(user)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Users in each sync state.
+async fn drift_counts(db: &PgPool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT kyc_sync_status, COUNT(*) FROM users GROUP BY kyc_sync_status")
+        .fetch_all(db)
+        .await
+}
+
+#[derive(Debug, Clone)]
+pub struct KycSyncConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+    pub max_attempts: i32,
+    /// The contract admin, which `set_kyc_tier` must be called and signed
+    /// as. Nothing is pushed without it.
+    pub admin_account: Option<String>,
+}
+
+impl KycSyncConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("KYC_SYNC_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: parse_env("KYC_SYNC_BATCH_SIZE", DEFAULT_BATCH_SIZE).max(1),
+            max_attempts: parse_env("KYC_SYNC_MAX_ATTEMPTS", DEFAULT_MAX_ATTEMPTS).max(1),
+            admin_account: std::env::var("KYC_SYNC_ADMIN_ACCOUNT")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+/// Outcome of one sync sweep.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KycSyncSweep {
+    pub pushed: usize,
+    pub retrying: usize,
+    pub failed: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct DueUser {
+    id: uuid::Uuid,
+    wallet_address: String,
+    onchain_tier: String,
+    kyc_sync_attempts: i32,
+}
+
+/// Pushes changed KYC tiers to the inheritance contract.
+pub struct KycSyncService {
+    db: PgPool,
+    tx_service: Arc<dyn TxService>,
+    contract_id: String,
+    admin_account: String,
+    config: KycSyncConfig,
+}
+
+impl KycSyncService {
+    pub fn new(
+        db: PgPool,
+        tx_service: Arc<dyn TxService>,
+        contract_id: String,
+        admin_account: String,
+        config: KycSyncConfig,
+    ) -> Self {
+        Self {
+            db,
+            tx_service,
+            contract_id,
+            admin_account,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("kyc_sync", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|sweep| {
+                    if *sweep != KycSyncSweep::default() {
+                        info!(
+                            pushed = sweep.pushed,
+                            retrying = sweep.retrying,
+                            failed = sweep.failed,
+                            "KYC sync sweep finished"
+                        );
+                    }
+                })
+            }
+        });
+    }
+
+    /// Pushes the tiers of users due a push, each in one locked pass, and
+    /// refreshes the drift gauges.
+    pub async fn run_once(&self) -> Result<KycSyncSweep, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(KYC_SYNC_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("KYC sync lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(KycSyncSweep::default());
+        }
+
+        // Locking the rows holds back tier changes until this push is
+        // recorded, so a change made meanwhile queues a fresh push.
+        let due = sqlx::query_as::<_, DueUser>(
+            r#"
+            SELECT id, wallet_address, kyc_onchain_tier(kyc_status, kyc_tier) AS onchain_tier,
+                   kyc_sync_attempts
+            FROM users
+            WHERE kyc_sync_status = 'pending_push' AND kyc_sync_next_attempt_at <= NOW()
+            ORDER BY kyc_sync_next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut sweep = KycSyncSweep::default();
+        for user in &due {
+            let Some(tier) = KycTier::from_name(&user.onchain_tier) else {
+                warn!(wallet = %user.wallet_address, tier = %user.onchain_tier, "Unknown KYC tier");
+                continue;
+            };
+            let invocation = ContractInvocation {
+                contract_id: self.contract_id.clone(),
+                function: "set_kyc_tier".to_string(),
+                args: vec![
+                    self.admin_account.clone(),
+                    user.wallet_address.clone(),
+                    (tier as u32).to_string(),
+                ],
+                memo: telemetry::current_memo(),
+            };
+            match self.tx_service.invoke_contract(&invocation).await {
+                Ok(tx_hash) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE users
+                        SET kyc_sync_status = 'synced', kyc_synced_tier = $2,
+                            kyc_sync_attempts = 0, kyc_sync_next_attempt_at = NULL,
+                            kyc_sync_error = NULL, kyc_sync_tx_hash = $3, kyc_synced_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(user.id)
+                    .bind(tier.name())
+                    .bind(&tx_hash)
+                    .execute(&mut *tx)
+                    .await?;
+                    info!(wallet = %user.wallet_address, tier = tier.name(), tx_hash = %tx_hash, "Pushed KYC tier on-chain");
+                    KYC_SYNC_PUSHES.with_label_values(&["pushed"]).inc();
+                    sweep.pushed += 1;
+                }
+                Err(e) => {
+                    let attempts = user.kyc_sync_attempts + 1;
+                    let exhausted = attempts >= self.config.max_attempts;
+                    let delay = retry_delay(attempts, self.config.interval);
+                    let message: String = e.to_string().chars().take(MAX_ERROR_CHARS).collect();
+                    warn!(wallet = %user.wallet_address, attempts, error = %message, "Failed to push KYC tier on-chain");
+                    sqlx::query(
+                        r#"
+                        UPDATE users
+                        SET kyc_sync_status = CASE WHEN $3 THEN 'failed' ELSE 'pending_push' END,
+                            kyc_sync_attempts = $2,
+                            kyc_sync_next_attempt_at = CASE WHEN $3 THEN NULL
+                                ELSE NOW() + ($4 * INTERVAL '1 second') END,
+                            kyc_sync_error = $5
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(user.id)
+                    .bind(attempts)
+                    .bind(exhausted)
+                    .bind(delay.as_secs() as i64)
+                    .bind(&message)
+                    .execute(&mut *tx)
+                    .await?;
+                    if exhausted {
+                        KYC_SYNC_PUSHES.with_label_values(&["failed"]).inc();
+                        sweep.failed += 1;
+                    } else {
+                        KYC_SYNC_PUSHES.with_label_values(&["retrying"]).inc();
+                        sweep.retrying += 1;
+                    }
+                }
+            }
+        }
+        tx.commit().await?;
+
+        let counts = drift_counts(&self.db).await?;
+        for status in SyncStatus::ALL {
+            let count = counts
+                .iter()
+                .find(|(s, _)| s == status.as_str())
+                .map_or(0, |(_, c)| *c);
+            KYC_SYNC_USERS
+                .with_label_values(&[status.as_str()])
+                .set(count as f64);
+        }
+        Ok(sweep)
+    }
+}
+
+// Handler: Get User KYC Sync (admin)
+pub async fn get_user_kyc_sync(
+    State(state): State<Arc<AppState>>,
+    Path(user): Path<String>,
+) -> impl IntoResponse {
+    let user = user.trim().to_string();
+    match load_state(&state.db_pool, &user).await {
+        Ok(Some(sync)) => (StatusCode::OK, Json(sync)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "User not found"),
+        Err(e) => {
+            error!(user = %user, error = %e, "Failed to load KYC sync state");
+            database_error()
+        }
+    }
+}
+
+// Handler: Resync User KYC (admin)
+pub async fn resync_user_kyc(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(user): Path<String>,
+) -> impl IntoResponse {
+    let user = user.trim().to_string();
+
+    let result: Result<Option<KycSyncState>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        // Pushed again even when marked synced, in case the contract was
+        // changed some other way.
+        let Some((wallet_address, previous)) = sqlx::query_as::<_, (String, String)>(
+            r#"
+            UPDATE users u
+            SET kyc_sync_status = 'pending_push', kyc_sync_attempts = 0,
+                kyc_sync_next_attempt_at = NOW(), kyc_sync_error = NULL
+            FROM (SELECT id, kyc_sync_status FROM users
+                  WHERE id::text = $1 OR wallet_address = $1 FOR UPDATE) old
+            WHERE u.id = old.id
+            RETURNING u.wallet_address, old.kyc_sync_status
+            "#,
+        )
+        .bind(&user)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "user.kyc_resync_requested",
+            &wallet_address,
+            serde_json::json!({ "previous_status": previous }),
+        )
+        .await?;
+        let sync = load_state(&mut *tx, &wallet_address).await?;
+        tx.commit().await?;
+        Ok(sync)
+    }
+    .await;
+
+    match result {
+        Ok(Some(sync)) => (StatusCode::ACCEPTED, Json(sync)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "User not found"),
+        Err(e) => {
+            error!(user = %user, error = %e, "Failed to queue KYC resync");
+            database_error()
+        }
+    }
+}
+
+// Handler: KYC Sync Drift (admin dashboard)
+pub async fn get_kyc_sync_drift(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let result: Result<KycSyncDrift, sqlx::Error> = async {
+        let counts = drift_counts(&state.db_pool).await?;
+        let count = |status: SyncStatus| {
+            counts
+                .iter()
+                .find(|(s, _)| s == status.as_str())
+                .map_or(0, |(_, c)| *c)
+        };
+        let oldest_pending_since: Option<DateTime<Utc>> = sqlx::query_scalar(
+            "SELECT MIN(kyc_sync_next_attempt_at) FROM users WHERE kyc_sync_status = 'pending_push'",
+        )
+        .fetch_one(&state.db_pool)
+        .await?;
+        let recent_failures = sqlx::query_as::<_, SyncFailure>(
+            r#"
+            SELECT wallet_address, kyc_onchain_tier(kyc_status, kyc_tier) AS onchain_tier,
+                   kyc_sync_attempts AS attempts, kyc_sync_error AS error
+            FROM users
+            WHERE kyc_sync_status = 'failed'
+            ORDER BY wallet_address
+            LIMIT $1
+            "#,
+        )
+        .bind(RECENT_FAILURES_LIMIT)
+        .fetch_all(&state.db_pool)
+        .await?;
+        Ok(KycSyncDrift {
+            synced: count(SyncStatus::Synced),
+            pending_push: count(SyncStatus::PendingPush),
+            failed: count(SyncStatus::Failed),
+            oldest_pending_since,
+            recent_failures,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(drift) => (StatusCode::OK, Json(drift)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to count KYC sync drift");
+            database_error()
+        }
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
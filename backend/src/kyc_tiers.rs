@@ -0,0 +1,374 @@
+//! KYC tiers and the transaction limits that come with them.
+//!
+//! Every user has a tier, `basic` until the KYC provider or an admin raises
+//! it. Each tier has three limits, held as system settings so they can be
+//! tuned without a redeploy: the total of active plans an owner may hold,
+//! the largest loan a borrower may take, and the most a beneficiary may
+//! receive from a single plan's payout, yield included. Plan creation and
+//! claims are refused past the limit, and claims are checked again when
+//! they execute; `GET /api/users/me/limits` shows what is left.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::fmt;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::notifications::create_notification;
+use crate::system_settings::SystemSettings;
+
+pub use inheritx_types::{KycTier, TierLimits};
+
+const MAX_NOTE_LEN: usize = 1000;
+
+/// How much of one limit is taken and how much is left, in token base
+/// units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Headroom {
+    pub limit: Decimal,
+    pub used: Decimal,
+    pub remaining: Decimal,
+}
+
+impl Headroom {
+    fn new(limit: Decimal, used: Decimal) -> Self {
+        Self {
+            limit,
+            used,
+            remaining: (limit - used).max(Decimal::ZERO),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LimitsResponse {
+    pub tier: KycTier,
+    /// Total of the user's active plans against the plan limit.
+    pub plans: Headroom,
+    /// Loans against the loan limit.
+    pub loans: Headroom,
+    /// Largest share the user stands to receive from one active plan
+    /// against the per-claim limit.
+    pub claim_payouts: Headroom,
+}
+
+/// A plan or claim refused because it is over the caller's tier limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub tier: KycTier,
+    pub limit_name: &'static str,
+    pub limit: Decimal,
+    pub remaining: Decimal,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "This exceeds the {} limit of {} for the {} KYC tier",
+            self.limit_name,
+            self.limit,
+            self.tier.name()
+        )
+    }
+}
+
+impl IntoResponse for LimitExceeded {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": self.to_string(),
+                "tier": self.tier,
+                "limit": self.limit,
+                "remaining": self.remaining,
+            })),
+        )
+            .into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetKycTierRequest {
+    pub tier: KycTier,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KycTierChange {
+    pub wallet_address: String,
+    pub previous_tier: KycTier,
+    pub tier: KycTier,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn parse_tier(value: &str) -> KycTier {
+    KycTier::from_name(value).unwrap_or_default()
+}
+
+/// The wallet's tier; wallets without an account are `basic`.
+pub async fn user_tier<'e, E>(executor: E, wallet_address: &str) -> Result<KycTier, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let tier: Option<String> =
+        sqlx::query_scalar("SELECT kyc_tier FROM users WHERE wallet_address = $1")
+            .bind(wallet_address)
+            .fetch_optional(executor)
+            .await?;
+    Ok(tier.as_deref().map(parse_tier).unwrap_or_default())
+}
+
+/// Total of the active plans `owner` holds.
+async fn active_plan_total(conn: &mut PgConnection, owner: &str) -> Result<Decimal, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0) FROM plans WHERE owner_address = $1 AND is_active = true",
+    )
+    .bind(owner)
+    .fetch_one(conn)
+    .await
+}
+
+/// Largest share `beneficiary` stands to receive from one active plan.
+async fn largest_share(conn: &mut PgConnection, beneficiary: &str) -> Result<Decimal, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(MAX(p.amount * b.allocation_bps / 10000), 0)
+        FROM beneficiaries b JOIN plans p ON p.id = b.plan_id
+        WHERE b.wallet_address = $1 AND p.is_active = true
+        "#,
+    )
+    .bind(beneficiary)
+    .fetch_one(conn)
+    .await
+}
+
+/// Refuses a new plan of `amount` that would take `owner`'s active plans
+/// past their tier's plan limit.
+pub async fn check_plan_amount(
+    conn: &mut PgConnection,
+    settings: &SystemSettings,
+    owner: &str,
+    amount: Decimal,
+) -> Result<Result<(), LimitExceeded>, sqlx::Error> {
+    let tier = user_tier(&mut *conn, owner).await?;
+    let limit = settings.tier_limits(tier).max_plan_amount.into();
+    let headroom = Headroom::new(limit, active_plan_total(conn, owner).await?);
+    if amount > headroom.remaining {
+        return Ok(Err(LimitExceeded {
+            tier,
+            limit_name: "plan",
+            limit,
+            remaining: headroom.remaining,
+        }));
+    }
+    Ok(Ok(()))
+}
+
+/// Refuses a claim paying `beneficiary` more than their tier's claim payout
+/// limit.
+pub async fn check_claim_payout(
+    conn: &mut PgConnection,
+    settings: &SystemSettings,
+    beneficiary: &str,
+    payout: Decimal,
+) -> Result<Result<(), LimitExceeded>, sqlx::Error> {
+    let tier = user_tier(&mut *conn, beneficiary).await?;
+    let limit = settings.tier_limits(tier).max_claim_payout.into();
+    if payout > limit {
+        return Ok(Err(LimitExceeded {
+            tier,
+            limit_name: "claim payout",
+            limit,
+            remaining: limit,
+        }));
+    }
+    Ok(Ok(()))
+}
+
+// Handler: Get My Limits
+pub async fn get_my_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let settings = state.system_settings.get().await;
+
+    let result: Result<LimitsResponse, sqlx::Error> = async {
+        let mut conn = state.db_pool.acquire().await?;
+        let tier = user_tier(&mut *conn, &caller).await?;
+        let limits = settings.tier_limits(tier);
+        Ok(LimitsResponse {
+            tier,
+            plans: Headroom::new(
+                limits.max_plan_amount.into(),
+                active_plan_total(&mut conn, &caller).await?,
+            ),
+            loans: Headroom::new(limits.max_loan_amount.into(), Decimal::ZERO),
+            claim_payouts: Headroom::new(
+                limits.max_claim_payout.into(),
+                largest_share(&mut conn, &caller).await?,
+            ),
+        })
+    }
+    .await;
+
+    match result {
+        Ok(limits) => (StatusCode::OK, Json(limits)).into_response(),
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to load KYC tier limits");
+            database_error()
+        }
+    }
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+// Handler: Set User KYC Tier (admin)
+pub async fn set_user_kyc_tier(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(user): Path<String>,
+    Json(payload): Json<SetKycTierRequest>,
+) -> impl IntoResponse {
+    let note = payload
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    if note.as_ref().is_some_and(|n| n.len() > MAX_NOTE_LEN) {
+        return refused(StatusCode::BAD_REQUEST, "Note is too long");
+    }
+    let tier = payload.tier;
+    let user = user.trim().to_string();
+
+    let result: Result<Outcome<KycTierChange>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        // A wallet verified before it ever signed in gets its account now,
+        // so the tier applies from its first plan.
+        if stellar_strkey::ed25519::PublicKey::from_string(&user).is_ok() {
+            sqlx::query("INSERT INTO users (wallet_address) VALUES ($1) ON CONFLICT DO NOTHING")
+                .bind(&user)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let Some((wallet_address, previous)) = sqlx::query_as::<_, (String, String)>(
+            r#"
+            UPDATE users u SET kyc_tier = $2
+            FROM (SELECT id, kyc_tier FROM users
+                  WHERE id::text = $1 OR wallet_address = $1 FOR UPDATE) old
+            WHERE u.id = old.id
+            RETURNING u.wallet_address, old.kyc_tier
+            "#,
+        )
+        .bind(&user)
+        .bind(tier.name())
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "User not found"));
+        };
+        let previous_tier = parse_tier(&previous);
+
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "user.kyc_tier_changed",
+            &wallet_address,
+            serde_json::json!({
+                "previous_tier": previous_tier,
+                "tier": tier,
+                "note": note,
+            }),
+        )
+        .await?;
+        if previous_tier != tier {
+            create_notification(
+                &mut *tx,
+                &wallet_address,
+                "kyc_update",
+                "Your verification level changed",
+                &format!(
+                    "Your account is now at the {} verification level, which changes how much you can hold in plans and receive from claims.",
+                    tier.name()
+                ),
+                serde_json::json!({ "kyc_tier": tier }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Outcome::Done(KycTierChange {
+            wallet_address,
+            previous_tier,
+            tier,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(change)) => {
+            info!(
+                user = %change.wallet_address,
+                tier = change.tier.name(),
+                "KYC tier changed"
+            );
+            (StatusCode::OK, Json(change)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(user = %user, error = %e, "Failed to set KYC tier");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn tiers_raise_every_limit() {
+        let settings = SystemSettings::defaults(&Config::for_tests());
+        let basic = settings.tier_limits(KycTier::Basic);
+        let verified = settings.tier_limits(KycTier::Verified);
+        let enhanced = settings.tier_limits(KycTier::Enhanced);
+        assert_eq!(basic.max_plan_amount, 100_000_000_000);
+        for (lower, higher) in [(&basic, &verified), (&verified, &enhanced)] {
+            assert!(lower.max_plan_amount < higher.max_plan_amount);
+            assert!(lower.max_loan_amount < higher.max_loan_amount);
+            assert!(lower.max_claim_payout < higher.max_claim_payout);
+        }
+    }
+
+    #[test]
+    fn headroom_never_goes_negative() {
+        let over = Headroom::new(Decimal::from(100), Decimal::from(150));
+        assert_eq!(over.remaining, Decimal::ZERO);
+        let under = Headroom::new(Decimal::from(100), Decimal::from(40));
+        assert_eq!(under.remaining, Decimal::from(60));
+        assert_eq!(parse_tier("unknown"), KycTier::Basic);
+    }
+}
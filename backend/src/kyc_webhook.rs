@@ -8,10 +8,16 @@ use axum::{
 use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use sqlx::{PgConnection, PgPool};
 use std::sync::Arc;
+use tokio::sync::broadcast;
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
 use crate::api::AppState;
+use crate::kyc_tiers::KycTier;
+use crate::notifications::create_localized_notification;
+use crate::templates::TemplateKey;
 use crate::ws::KycUpdateEvent;
 
 type HmacSha256 = Hmac<Sha256>;
@@ -26,7 +32,7 @@ pub enum KycStatusPayload {
 }
 
 impl KycStatusPayload {
-    fn as_db_str(&self) -> &str {
+    pub fn as_db_str(&self) -> &str {
         match self {
             KycStatusPayload::Pending => "pending",
             KycStatusPayload::Submitted => "submitted",
@@ -42,6 +48,9 @@ pub struct KycWebhookPayload {
     pub status: KycStatusPayload,
     pub provider_reference: Option<String>,
     pub event_type: String,
+    /// Tier the provider verified the wallet to; left unchanged when absent.
+    #[serde(default)]
+    pub tier: Option<KycTier>,
 }
 
 #[derive(Serialize)]
@@ -63,69 +72,58 @@ fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
     mac.verify_slice(&sig_bytes).is_ok()
 }
 
-pub async fn kyc_webhook_handler(
-    State(state): State<Arc<AppState>>,
-    headers: HeaderMap,
-    body: Bytes,
-) -> impl IntoResponse {
-    let secret = state.kyc_webhook_secret.as_deref().unwrap_or("");
-    let signature = headers
-        .get("x-kyc-signature")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
-
-    if !secret.is_empty() && !verify_signature(secret, &body, signature) {
-        warn!(signature = %signature, "KYC webhook rejected: invalid signature");
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(WebhookResponse {
-                success: false,
-                message: "Invalid webhook signature".to_string(),
-            }),
-        )
-            .into_response();
-    }
-
-    let payload: KycWebhookPayload = match serde_json::from_slice(&body) {
-        Ok(p) => p,
-        Err(e) => {
-            warn!(error = %e, "KYC webhook: failed to parse payload");
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(WebhookResponse {
-                    success: false,
-                    message: format!("Invalid payload: {}", e),
-                }),
-            )
-                .into_response();
-        }
+/// Tells the wallet its KYC review finished, in its preferred language.
+/// Other status changes are not notified.
+pub async fn notify_kyc_status(
+    conn: &mut PgConnection,
+    wallet_address: &str,
+    kyc_status: &str,
+) -> Result<(), sqlx::Error> {
+    let key = match kyc_status {
+        "approved" => TemplateKey::KycApproved,
+        "rejected" => TemplateKey::KycRejected,
+        _ => return Ok(()),
     };
+    create_localized_notification(
+        conn,
+        wallet_address,
+        "kyc_update",
+        key,
+        &[],
+        serde_json::json!({ "kyc_status": kyc_status }),
+    )
+    .await?;
+    Ok(())
+}
 
-    info!(
-        wallet_address = %payload.wallet_address,
-        status = ?payload.status,
-        event_type = %payload.event_type,
-        "KYC webhook received"
-    );
-
+/// Applies a KYC status change, notifies the wallet, broadcasts it to WebSocket subscribers and
+/// records the attempt in `kyc_webhook_logs`.
+///
+/// Shared by the webhook handler and `inheritx-cli replay-webhook`.
+pub async fn process_kyc_event(
+    db: &PgPool,
+    kyc_tx: &broadcast::Sender<KycUpdateEvent>,
+    payload: &KycWebhookPayload,
+    raw_payload: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
     let kyc_status_str = payload.status.as_db_str();
-    let raw_payload =
-        serde_json::from_slice::<serde_json::Value>(&body).unwrap_or(serde_json::Value::Null);
 
     let update_result = sqlx::query(
         r#"
-        INSERT INTO users (wallet_address, kyc_status)
-        VALUES ($1, $2::kyc_status)
+        INSERT INTO users (wallet_address, kyc_status, kyc_tier)
+        VALUES ($1, $2::kyc_status, COALESCE($3, 'basic'))
         ON CONFLICT (wallet_address)
-        DO UPDATE SET kyc_status = $2::kyc_status
+        DO UPDATE SET kyc_status = $2::kyc_status,
+                      kyc_tier = COALESCE($3, users.kyc_tier)
         "#,
     )
     .bind(&payload.wallet_address)
     .bind(kyc_status_str)
-    .execute(&state.db_pool)
+    .bind(payload.tier.map(KycTier::name))
+    .execute(db)
     .await;
 
-    let (success, error_message) = match update_result {
+    let error_message = match &update_result {
         Ok(_) => {
             info!(
                 wallet_address = %payload.wallet_address,
@@ -138,10 +136,10 @@ pub async fn kyc_webhook_handler(
                 kyc_status: kyc_status_str.to_string(),
                 event_type: payload.event_type.clone(),
             };
-            if let Err(e) = state.kyc_tx.send(event) {
+            if let Err(e) = kyc_tx.send(event) {
                 tracing::debug!("No WebSocket subscribers for KYC event: {}", e);
             }
-            (true, None::<String>)
+            None
         }
         Err(e) => {
             error!(
@@ -149,10 +147,22 @@ pub async fn kyc_webhook_handler(
                 error = %e,
                 "Failed to update KYC status in database"
             );
-            (false, Some(e.to_string()))
+            Some(e.to_string())
         }
     };
 
+    if update_result.is_ok() {
+        let notified = match db.acquire().await {
+            Ok(mut conn) => {
+                notify_kyc_status(&mut conn, &payload.wallet_address, kyc_status_str).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = notified {
+            warn!(wallet_address = %payload.wallet_address, error = %e, "Failed to notify KYC status");
+        }
+    }
+
     let log_result = sqlx::query(
         r#"
         INSERT INTO kyc_webhook_logs
@@ -164,16 +174,83 @@ pub async fn kyc_webhook_handler(
     .bind(&payload.provider_reference)
     .bind(&payload.event_type)
     .bind(kyc_status_str)
-    .bind(&raw_payload)
-    .bind(success)
+    .bind(raw_payload)
+    .bind(update_result.is_ok())
     .bind(&error_message)
-    .execute(&state.db_pool)
+    .execute(db)
     .await;
 
     if let Err(e) = log_result {
         error!(error = %e, "Failed to write KYC webhook log");
     }
 
+    update_result.map(|_| ())
+}
+
+/// Loads the raw payload of a previously received webhook so it can be replayed.
+pub async fn load_logged_payload(
+    db: &PgPool,
+    log_id: Uuid,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar("SELECT raw_payload FROM kyc_webhook_logs WHERE id = $1")
+        .bind(log_id)
+        .fetch_optional(db)
+        .await
+}
+
+pub async fn kyc_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let secret = state.config.kyc_webhook_secret.as_deref().unwrap_or("");
+    let signature = headers
+        .get("x-kyc-signature")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    if !secret.is_empty() && !verify_signature(secret, &body, signature) {
+        warn!(signature = %signature, "KYC webhook rejected: invalid signature");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(WebhookResponse {
+                success: false,
+                message: "Invalid webhook signature".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
+    let payload: KycWebhookPayload = match serde_json::from_slice(&body) {
+        Ok(p) => p,
+        Err(e) => {
+            warn!(error = %e, "KYC webhook: failed to parse payload");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(WebhookResponse {
+                    success: false,
+                    message: format!("Invalid payload: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    info!(
+        wallet_address = %payload.wallet_address,
+        status = ?payload.status,
+        event_type = %payload.event_type,
+        "KYC webhook received"
+    );
+
+    let kyc_status_str = payload.status.as_db_str();
+    let raw_payload =
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap_or(serde_json::Value::Null);
+
+    let success = process_kyc_event(&state.db_pool, &state.kyc_tx, &payload, &raw_payload)
+        .await
+        .is_ok();
+
     if success {
         (
             StatusCode::OK,
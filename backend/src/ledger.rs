@@ -0,0 +1,361 @@
+//! Double-entry ledger of the platform's funds.
+//!
+//! Database triggers post a balanced journal for every financial event:
+//! deposits into custody, yield accruing on plans, and payouts being
+//! created, completed, failed and requeued. Assigning a quarantined deposit
+//! to a plan is posted by the handler that does it, and a completed deposit
+//! refund by the refund worker. Each is kept on two bases.
+//! The accrual basis recognizes yield as it accrues and payouts once they
+//! are owed. The cash basis only records money moving in or out of
+//! custody. Accounts are per asset (`custody`, `plan_liability`,
+//! `unallocated_deposits`, `payouts_payable`, `yield_expense`), and the
+//! ledger is append-only.
+//!
+//! Payout fees are not posted: the backend only estimates them from the
+//! fee schedule, and the contract takes the claim fee on-chain.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::money::Money;
+use crate::read_models::DayRangeQuery;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Basis {
+    #[default]
+    Accrual,
+    Cash,
+}
+
+impl Basis {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Accrual => "accrual",
+            Self::Cash => "cash",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceQuery {
+    #[serde(default)]
+    pub basis: Basis,
+    /// Balances as of this time; now by default.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub basis: Basis,
+    #[serde(flatten)]
+    pub range: DayRangeQuery,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct BalanceRow {
+    account_id: Uuid,
+    code: String,
+    asset: String,
+    kind: String,
+    debits: Decimal,
+    credits: Decimal,
+    balance: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountBalance {
+    pub account_id: Uuid,
+    pub code: String,
+    pub asset: String,
+    /// `asset`, `liability` or `expense`.
+    pub kind: String,
+    pub debits: Money,
+    pub credits: Money,
+    /// Debits less credits for assets and expenses, credits less debits
+    /// for liabilities.
+    pub balance: Money,
+}
+
+impl From<BalanceRow> for AccountBalance {
+    fn from(row: BalanceRow) -> Self {
+        Self {
+            account_id: row.account_id,
+            code: row.code,
+            debits: Money::new(row.debits, &row.asset),
+            credits: Money::new(row.credits, &row.asset),
+            balance: Money::new(row.balance, &row.asset),
+            asset: row.asset,
+            kind: row.kind,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyBalance {
+    pub day: NaiveDate,
+    /// Closing balance at the end of the day.
+    pub balance: Money,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountHistory {
+    pub account: AccountBalance,
+    pub basis: Basis,
+    pub days: Vec<DailyBalance>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssetTotals {
+    pub asset: String,
+    pub debits: Money,
+    pub credits: Money,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrialBalance {
+    pub basis: Basis,
+    pub as_of: DateTime<Utc>,
+    pub balanced: bool,
+    pub assets: Vec<AssetTotals>,
+    /// Journals whose entries do not net to zero; always empty unless the
+    /// ledger was written around its triggers.
+    pub unbalanced_journals: Vec<Uuid>,
+}
+
+const BALANCE_SQL: &str = r#"
+    SELECT a.id AS account_id, a.code, a.asset, a.kind,
+           COALESCE(SUM(e.debit), 0) AS debits,
+           COALESCE(SUM(e.credit), 0) AS credits,
+           CASE WHEN a.kind = 'liability'
+                THEN COALESCE(SUM(e.credit), 0) - COALESCE(SUM(e.debit), 0)
+                ELSE COALESCE(SUM(e.debit), 0) - COALESCE(SUM(e.credit), 0) END AS balance
+    FROM ledger_accounts a
+    LEFT JOIN ledger_entries e
+           ON e.account_id = a.id AND e.basis = $1 AND e.occurred_at <= $2
+"#;
+
+/// Every account's balance on `basis` as of `as_of`.
+pub async fn account_balances<'e, E: PgExecutor<'e>>(
+    executor: E,
+    basis: Basis,
+    as_of: DateTime<Utc>,
+) -> Result<Vec<AccountBalance>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, BalanceRow>(&format!(
+        "{BALANCE_SQL} GROUP BY a.id ORDER BY a.asset, a.kind, a.code"
+    ))
+    .bind(basis.as_str())
+    .bind(as_of)
+    .fetch_all(executor)
+    .await?;
+    Ok(rows.into_iter().map(AccountBalance::from).collect())
+}
+
+/// Debit and credit totals per asset on `basis`, which must match.
+pub async fn trial_balance(
+    db: &sqlx::PgPool,
+    basis: Basis,
+    as_of: DateTime<Utc>,
+) -> Result<TrialBalance, sqlx::Error> {
+    let totals: Vec<(String, Decimal, Decimal)> = sqlx::query_as(
+        r#"
+        SELECT a.asset, SUM(e.debit) AS debits, SUM(e.credit) AS credits
+        FROM ledger_entries e
+        JOIN ledger_accounts a ON a.id = e.account_id
+        WHERE e.basis = $1 AND e.occurred_at <= $2
+        GROUP BY a.asset
+        ORDER BY a.asset
+        "#,
+    )
+    .bind(basis.as_str())
+    .bind(as_of)
+    .fetch_all(db)
+    .await?;
+    let assets: Vec<AssetTotals> = totals
+        .into_iter()
+        .map(|(asset, debits, credits)| AssetTotals {
+            debits: Money::new(debits, &asset),
+            credits: Money::new(credits, &asset),
+            asset,
+        })
+        .collect();
+    let unbalanced_journals: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT journal_id FROM ledger_entries
+        WHERE basis = $1 AND occurred_at <= $2
+        GROUP BY journal_id
+        HAVING SUM(debit) <> SUM(credit)
+        "#,
+    )
+    .bind(basis.as_str())
+    .bind(as_of)
+    .fetch_all(db)
+    .await?;
+    Ok(TrialBalance {
+        basis,
+        as_of,
+        balanced: unbalanced_journals.is_empty()
+            && assets.iter().all(|a| a.debits.amount == a.credits.amount),
+        assets,
+        unbalanced_journals,
+    })
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+// Handler: Ledger Account Balances (admin)
+pub async fn get_ledger_accounts(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BalanceQuery>,
+) -> impl IntoResponse {
+    let as_of = query.as_of.unwrap_or_else(Utc::now);
+    match account_balances(&state.db_pool, query.basis, as_of).await {
+        Ok(accounts) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "basis": query.basis,
+                "as_of": as_of,
+                "accounts": accounts,
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to read ledger balances");
+            database_error()
+        }
+    }
+}
+
+// Handler: Ledger Account History (admin)
+pub async fn get_ledger_account_history(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    let (from, to) = match query.range.resolve(Utc::now().date_naive()) {
+        Ok(range) => range,
+        Err(message) => return bad_request(message),
+    };
+
+    let result: Result<Option<AccountHistory>, sqlx::Error> = async {
+        let Some(account) = sqlx::query_as::<_, BalanceRow>(&format!(
+            "{BALANCE_SQL} WHERE a.id = $3 GROUP BY a.id"
+        ))
+        .bind(query.basis.as_str())
+        .bind(Utc::now())
+        .bind(account_id)
+        .fetch_optional(&state.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let account = AccountBalance::from(account);
+        let days: Vec<(NaiveDate, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT d::date AS day,
+                   COALESCE((
+                       SELECT SUM(CASE WHEN a.kind = 'liability'
+                                       THEN e.credit - e.debit ELSE e.debit - e.credit END)
+                       FROM ledger_entries e
+                       JOIN ledger_accounts a ON a.id = e.account_id
+                       WHERE e.account_id = $1 AND e.basis = $2
+                         AND e.occurred_at < (d::date + 1)::timestamp AT TIME ZONE 'UTC'
+                   ), 0) AS balance
+            FROM generate_series($3::date, $4::date, INTERVAL '1 day') AS d
+            ORDER BY day
+            "#,
+        )
+        .bind(account_id)
+        .bind(query.basis.as_str())
+        .bind(from)
+        .bind(to)
+        .fetch_all(&state.db_pool)
+        .await?;
+        let days = days
+            .into_iter()
+            .map(|(day, balance)| DailyBalance {
+                day,
+                balance: Money::new(balance, &account.asset),
+            })
+            .collect();
+        Ok(Some(AccountHistory {
+            account,
+            basis: query.basis,
+            days,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Some(history)) => (StatusCode::OK, Json(history)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Ledger account not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(account_id = %account_id, error = %e, "Failed to read ledger account history");
+            database_error()
+        }
+    }
+}
+
+// Handler: Ledger Trial Balance (admin)
+pub async fn get_trial_balance(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BalanceQuery>,
+) -> impl IntoResponse {
+    let as_of = query.as_of.unwrap_or_else(Utc::now);
+    match trial_balance(&state.db_pool, query.basis, as_of).await {
+        Ok(trial) => {
+            if !trial.balanced {
+                error!(basis = query.basis.as_str(), unbalanced = ?trial.unbalanced_journals, "Ledger trial balance does not balance");
+            }
+            (StatusCode::OK, Json(trial)).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to compute trial balance");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basis_defaults_to_accrual() {
+        assert_eq!(Basis::default(), Basis::Accrual);
+        let cash: Basis = serde_json::from_str("\"cash\"").unwrap();
+        assert_eq!(cash.as_str(), "cash");
+        assert!(serde_json::from_str::<Basis>("\"modified_cash\"").is_err());
+    }
+}
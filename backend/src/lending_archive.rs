@@ -0,0 +1,735 @@
+//! Cold storage for old `lending_events`.
+//!
+//! `lending_events` is partitioned by month. The archiver keeps partitions
+//! for the current and next month in place, and once a month is older than
+//! `LENDING_ARCHIVE_AFTER_MONTHS` it exports the partition to a gzipped CSV
+//! under `LENDING_ARCHIVE_DIR` and drops it. Admins can list archived ranges
+//! and restore a month on demand; a restored month is archived again after
+//! `LENDING_ARCHIVE_RESTORE_HOLD_DAYS`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, SecondsFormat, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::io::Read;
+use std::path::Path as FsPath;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::config::Config;
+use crate::jobs::JobRegistry;
+
+const DEFAULT_INTERVAL_SECS: u64 = 3600;
+const DEFAULT_ARCHIVE_AFTER_MONTHS: u32 = 12;
+const DEFAULT_RESTORE_HOLD_DAYS: i64 = 7;
+const ARCHIVE_LOCK_KEY: i64 = 832;
+const EXPORT_PAGE_SIZE: i64 = 5_000;
+const RESTORE_CHUNK_SIZE: usize = 1_000;
+const PARTITION_PREFIX: &str = "lending_events_p";
+
+const ARCHIVE_COLUMNS: &str = "id, partition_name, range_start, range_end, row_count, object_key, \
+     byte_size, sha256, status, archived_at, restored_at, restored_by";
+const EVENT_HEADER: [&str; 10] = [
+    "id",
+    "event_type",
+    "plan_id",
+    "user_address",
+    "asset",
+    "amount",
+    "transaction_hash",
+    "external_id",
+    "metadata",
+    "created_at",
+];
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("archive I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("archive CSV failed: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("archive is corrupt: {0}")]
+    Corrupt(String),
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct LendingArchive {
+    pub id: Uuid,
+    pub partition_name: String,
+    pub range_start: DateTime<Utc>,
+    pub range_end: DateTime<Utc>,
+    pub row_count: i64,
+    /// Path of the gzipped CSV relative to `LENDING_ARCHIVE_DIR`.
+    pub object_key: String,
+    pub byte_size: i64,
+    pub sha256: String,
+    /// `archived` or `restored`.
+    pub status: String,
+    pub archived_at: DateTime<Utc>,
+    pub restored_at: Option<DateTime<Utc>>,
+    pub restored_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveQuery {
+    /// Only archives whose range ends after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Only archives whose range starts before this time.
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ExportRow {
+    id: Uuid,
+    event_type: String,
+    plan_id: Option<String>,
+    user_address: String,
+    asset: String,
+    amount: String,
+    transaction_hash: String,
+    external_id: String,
+    metadata: String,
+    created_at: DateTime<Utc>,
+}
+
+impl ExportRow {
+    fn record(&self) -> EventRecord {
+        EventRecord {
+            id: self.id.to_string(),
+            event_type: self.event_type.clone(),
+            plan_id: self.plan_id.clone().unwrap_or_default(),
+            user_address: self.user_address.clone(),
+            asset: self.asset.clone(),
+            amount: self.amount.clone(),
+            transaction_hash: self.transaction_hash.clone(),
+            external_id: self.external_id.clone(),
+            metadata: self.metadata.clone(),
+            created_at: self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        }
+    }
+}
+
+/// One `lending_events` row as written to an archive, every column as text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EventRecord {
+    id: String,
+    event_type: String,
+    plan_id: String,
+    user_address: String,
+    asset: String,
+    amount: String,
+    transaction_hash: String,
+    external_id: String,
+    metadata: String,
+    created_at: String,
+}
+
+impl EventRecord {
+    fn fields(&self) -> [&str; 10] {
+        [
+            &self.id,
+            &self.event_type,
+            &self.plan_id,
+            &self.user_address,
+            &self.asset,
+            &self.amount,
+            &self.transaction_hash,
+            &self.external_id,
+            &self.metadata,
+            &self.created_at,
+        ]
+    }
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn add_months(month: NaiveDate, months: i32) -> NaiveDate {
+    let index = month.year() * 12 + month.month0() as i32 + months;
+    NaiveDate::from_ymd_opt(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, 1)
+        .unwrap_or(month)
+}
+
+pub fn partition_name(month: NaiveDate) -> String {
+    format!("{PARTITION_PREFIX}{}", month.format("%Y_%m"))
+}
+
+/// The month a partition created by [`partition_name`] holds.
+pub fn partition_month(name: &str) -> Option<NaiveDate> {
+    let suffix = name.strip_prefix(PARTITION_PREFIX)?;
+    let (year, month) = suffix.split_once('_')?;
+    if year.len() != 4 || month.len() != 2 {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)
+}
+
+fn month_bounds(month: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let at = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    (at(month), at(add_months(month, 1)))
+}
+
+fn object_key(month: NaiveDate) -> String {
+    format!("lending_events/{}.csv.gz", month.format("%Y-%m"))
+}
+
+/// Creates the partition for `month` unless it already exists.
+async fn create_partition(conn: &mut PgConnection, month: NaiveDate) -> Result<(), sqlx::Error> {
+    let (start, end) = month_bounds(month);
+    // Names and bounds are derived from a date, never from user input.
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} PARTITION OF lending_events FOR VALUES FROM ('{}') TO ('{}')",
+        partition_name(month),
+        start.to_rfc3339(),
+        end.to_rfc3339()
+    ))
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+async fn monthly_partitions(conn: &mut PgConnection) -> Result<Vec<NaiveDate>, sqlx::Error> {
+    let names: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT c.relname::text
+        FROM pg_inherits i
+        JOIN pg_class c ON c.oid = i.inhrelid
+        JOIN pg_class p ON p.oid = i.inhparent
+        WHERE p.relname = 'lending_events'
+        "#,
+    )
+    .fetch_all(conn)
+    .await?;
+    let mut months: Vec<NaiveDate> = names.iter().filter_map(|n| partition_month(n)).collect();
+    months.sort();
+    Ok(months)
+}
+
+/// Writes the rows of `month`'s partition as a gzipped CSV. Returns the
+/// compressed bytes and the row count.
+async fn export_partition(
+    conn: &mut PgConnection,
+    month: NaiveDate,
+) -> Result<(Vec<u8>, i64), ArchiveError> {
+    let mut writer = csv::Writer::from_writer(GzEncoder::new(Vec::new(), Compression::default()));
+    writer.write_record(EVENT_HEADER)?;
+
+    let mut rows = 0i64;
+    let mut after: Option<(DateTime<Utc>, Uuid)> = None;
+    loop {
+        let page = sqlx::query_as::<_, ExportRow>(&format!(
+            r#"
+            SELECT id, event_type, plan_id::text, user_address, asset, amount::text,
+                   transaction_hash, external_id, metadata::text, created_at
+            FROM {}
+            WHERE $1::timestamptz IS NULL OR (created_at, id) > ($1, $2)
+            ORDER BY created_at, id
+            LIMIT $3
+            "#,
+            partition_name(month)
+        ))
+        .bind(after.map(|(at, _)| at))
+        .bind(after.map(|(_, id)| id))
+        .bind(EXPORT_PAGE_SIZE)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for row in &page {
+            writer.write_record(row.record().fields())?;
+        }
+        rows += page.len() as i64;
+        match page.last() {
+            Some(last) if page.len() as i64 == EXPORT_PAGE_SIZE => {
+                after = Some((last.created_at, last.id));
+            }
+            _ => break,
+        }
+    }
+
+    let encoder = writer
+        .into_inner()
+        .map_err(|e| ArchiveError::Io(e.into_error()))?;
+    Ok((encoder.finish()?, rows))
+}
+
+fn decode_archive(bytes: &[u8]) -> Result<Vec<EventRecord>, ArchiveError> {
+    let mut csv_bytes = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut csv_bytes)?;
+    let mut reader = csv::Reader::from_reader(csv_bytes.as_slice());
+    if reader.headers()?.iter().ne(EVENT_HEADER) {
+        return Err(ArchiveError::Corrupt("unexpected header".to_string()));
+    }
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            let field = |i: usize| record.get(i).unwrap_or_default().to_string();
+            if record.len() != EVENT_HEADER.len() {
+                return Err(ArchiveError::Corrupt(format!(
+                    "row has {} fields",
+                    record.len()
+                )));
+            }
+            Ok(EventRecord {
+                id: field(0),
+                event_type: field(1),
+                plan_id: field(2),
+                user_address: field(3),
+                asset: field(4),
+                amount: field(5),
+                transaction_hash: field(6),
+                external_id: field(7),
+                metadata: field(8),
+                created_at: field(9),
+            })
+        })
+        .collect()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Writes `bytes` under `dir`, through a temporary file so a partial write
+/// never replaces a good archive.
+async fn store_object(dir: &FsPath, key: &str, bytes: &[u8]) -> Result<(), std::io::Error> {
+    let path = dir.join(key);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let temp = path.with_extension("gz.partial");
+    tokio::fs::write(&temp, bytes).await?;
+    tokio::fs::rename(&temp, &path).await
+}
+
+/// Re-inserts archived rows into the recreated partition. Rows whose plan
+/// has since been deleted keep a NULL plan, as the foreign key would have
+/// left them.
+async fn insert_records(
+    conn: &mut PgConnection,
+    records: &[EventRecord],
+) -> Result<(), sqlx::Error> {
+    for chunk in records.chunks(RESTORE_CHUNK_SIZE) {
+        let column = |get: fn(&EventRecord) -> &str| -> Vec<String> {
+            chunk.iter().map(|r| get(r).to_string()).collect()
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO lending_events
+                (id, event_type, plan_id, user_address, asset, amount, transaction_hash,
+                 external_id, metadata, created_at)
+            SELECT u.id::uuid, u.event_type, p.id, u.user_address, u.asset, u.amount::numeric,
+                   u.transaction_hash, u.external_id, u.metadata::jsonb, u.created_at::timestamptz
+            FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[],
+                        $7::text[], $8::text[], $9::text[], $10::text[])
+                AS u(id, event_type, plan_id, user_address, asset, amount, transaction_hash,
+                     external_id, metadata, created_at)
+            LEFT JOIN plans p ON p.id = NULLIF(u.plan_id, '')::uuid
+            "#,
+        )
+        .bind(column(|r| &r.id))
+        .bind(column(|r| &r.event_type))
+        .bind(column(|r| &r.plan_id))
+        .bind(column(|r| &r.user_address))
+        .bind(column(|r| &r.asset))
+        .bind(column(|r| &r.amount))
+        .bind(column(|r| &r.transaction_hash))
+        .bind(column(|r| &r.external_id))
+        .bind(column(|r| &r.metadata))
+        .bind(column(|r| &r.created_at))
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+fn error_response(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: List Lending Event Archives
+pub async fn list_archives(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ArchiveQuery>,
+) -> impl IntoResponse {
+    match sqlx::query_as::<_, LendingArchive>(&format!(
+        r#"
+        SELECT {ARCHIVE_COLUMNS} FROM lending_event_archives
+        WHERE ($1::timestamptz IS NULL OR range_end > $1)
+          AND ($2::timestamptz IS NULL OR range_start < $2)
+        ORDER BY range_start
+        "#
+    ))
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(archives) => (StatusCode::OK, Json(archives)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list lending event archives");
+            database_error()
+        }
+    }
+}
+
+// Handler: Restore Lending Event Archive
+pub async fn restore_archive(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Some(dir) = state.config.lending_archive_dir.clone() else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Lending event archiving is not configured",
+        );
+    };
+
+    let archive = match sqlx::query_as::<_, LendingArchive>(&format!(
+        "SELECT {ARCHIVE_COLUMNS} FROM lending_event_archives WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(archive)) if archive.status == "archived" => archive,
+        Ok(Some(_)) => return error_response(StatusCode::CONFLICT, "Archive is already restored"),
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "Archive not found"),
+        Err(e) => {
+            error!(archive_id = %id, error = %e, "Failed to load lending event archive");
+            return database_error();
+        }
+    };
+    let Some(month) = partition_month(&archive.partition_name) else {
+        return error_response(StatusCode::CONFLICT, "Archive names an unknown partition");
+    };
+
+    let records = match tokio::fs::read(dir.join(&archive.object_key)).await {
+        Ok(bytes) if sha256_hex(&bytes) == archive.sha256 => decode_archive(&bytes),
+        Ok(_) => Err(ArchiveError::Corrupt("checksum mismatch".to_string())),
+        Err(e) => Err(ArchiveError::Io(e)),
+    };
+    let records = match records {
+        Ok(records) => records,
+        Err(e) => {
+            error!(archive_id = %id, error = %e, "Failed to read lending event archive");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Archive file is missing or corrupt",
+            );
+        }
+    };
+
+    let result: Result<Option<LendingArchive>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let restored = sqlx::query_as::<_, LendingArchive>(&format!(
+            r#"
+            UPDATE lending_event_archives
+            SET status = 'restored', restored_at = NOW(), restored_by = $2
+            WHERE id = $1 AND status = 'archived'
+            RETURNING {ARCHIVE_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(&admin.user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(restored) = restored else {
+            return Ok(None);
+        };
+        create_partition(&mut tx, month).await?;
+        insert_records(&mut tx, &records).await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "lending_archive.restored",
+            &id.to_string(),
+            serde_json::json!({
+                "partition": restored.partition_name,
+                "rows": records.len(),
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(restored))
+    }
+    .await;
+
+    match result {
+        Ok(Some(restored)) => {
+            info!(archive_id = %id, rows = records.len(), "Lending event archive restored");
+            (StatusCode::OK, Json(restored)).into_response()
+        }
+        Ok(None) => error_response(StatusCode::CONFLICT, "Archive is already restored"),
+        Err(e) => {
+            error!(archive_id = %id, error = %e, "Failed to restore lending event archive");
+            database_error()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LendingArchiveConfig {
+    pub interval: Duration,
+    /// Whole months a partition is kept before it is archived.
+    pub archive_after_months: u32,
+    /// How long a restored month stays before it is archived again.
+    pub restore_hold: ChronoDuration,
+}
+
+impl LendingArchiveConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("LENDING_ARCHIVE_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let archive_after_months =
+            parse_env("LENDING_ARCHIVE_AFTER_MONTHS", DEFAULT_ARCHIVE_AFTER_MONTHS);
+        let restore_hold_days = parse_env(
+            "LENDING_ARCHIVE_RESTORE_HOLD_DAYS",
+            DEFAULT_RESTORE_HOLD_DAYS,
+        );
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            archive_after_months: archive_after_months.max(1),
+            restore_hold: ChronoDuration::days(restore_hold_days.max(0)),
+        }
+    }
+}
+
+/// Creates upcoming `lending_events` partitions and archives old ones.
+pub struct LendingArchiveService {
+    db: PgPool,
+    app_config: Arc<Config>,
+    config: LendingArchiveConfig,
+}
+
+impl LendingArchiveService {
+    pub fn new(db: PgPool, app_config: Arc<Config>, config: LendingArchiveConfig) -> Self {
+        Self {
+            db,
+            app_config,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("lending_archive", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&archived| {
+                    if archived > 0 {
+                        info!(partitions = archived, "Lending event partitions archived");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Ensures partitions exist for this month and the next, then archives
+    /// months past the cutoff. Returns the number of partitions archived.
+    pub async fn run_once(&self) -> Result<usize, ArchiveError> {
+        let mut lock_tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(ARCHIVE_LOCK_KEY)
+            .fetch_one(&mut *lock_tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Lending event archiver lock is held by another worker; skipping run");
+            lock_tx.commit().await?;
+            return Ok(0);
+        }
+
+        let current = month_start(Utc::now().date_naive());
+        for month in [current, add_months(current, 1)] {
+            create_partition(&mut lock_tx, month).await?;
+        }
+
+        let Some(dir) = self.app_config.lending_archive_dir.as_deref() else {
+            lock_tx.commit().await?;
+            return Ok(0);
+        };
+        let cutoff = add_months(current, -(self.config.archive_after_months as i32));
+        let due: Vec<NaiveDate> = monthly_partitions(&mut lock_tx)
+            .await?
+            .into_iter()
+            .filter(|month| *month < cutoff)
+            .collect();
+
+        let mut archived = 0;
+        for month in due {
+            match self.archive_month(dir, month).await {
+                Ok(true) => archived += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    error!(partition = %partition_name(month), error = %e, "Failed to archive lending events");
+                }
+            }
+        }
+
+        lock_tx.commit().await?;
+        Ok(archived)
+    }
+
+    /// Exports and drops one month. A month restored less than the hold
+    /// period ago is left alone; after that it is dropped again, since its
+    /// archive file is still in place.
+    async fn archive_month(&self, dir: &FsPath, month: NaiveDate) -> Result<bool, ArchiveError> {
+        let name = partition_name(month);
+        let mut tx = self.db.begin().await?;
+
+        let existing: Option<(Uuid, Option<DateTime<Utc>>)> = sqlx::query_as(
+            "SELECT id, restored_at FROM lending_event_archives WHERE partition_name = $1 FOR UPDATE",
+        )
+        .bind(&name)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (archive_id, details) = match existing {
+            Some((_, Some(restored_at))) if Utc::now() - restored_at < self.config.restore_hold => {
+                return Ok(false);
+            }
+            Some((id, _)) => {
+                sqlx::query(&format!("DROP TABLE {name}"))
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("UPDATE lending_event_archives SET status = 'archived' WHERE id = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                (
+                    id,
+                    serde_json::json!({ "partition": name, "rearchived": true }),
+                )
+            }
+            None => {
+                // Old months no longer receive rows, but hold writers off
+                // while the export runs.
+                sqlx::query(&format!("LOCK TABLE {name} IN SHARE MODE"))
+                    .execute(&mut *tx)
+                    .await?;
+                let (bytes, rows) = export_partition(&mut tx, month).await?;
+                let key = object_key(month);
+                store_object(dir, &key, &bytes).await?;
+
+                let (start, end) = month_bounds(month);
+                let id: Uuid = sqlx::query_scalar(
+                    r#"
+                    INSERT INTO lending_event_archives
+                        (partition_name, range_start, range_end, row_count, object_key, byte_size, sha256)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    RETURNING id
+                    "#,
+                )
+                .bind(&name)
+                .bind(start)
+                .bind(end)
+                .bind(rows)
+                .bind(&key)
+                .bind(bytes.len() as i64)
+                .bind(sha256_hex(&bytes))
+                .fetch_one(&mut *tx)
+                .await?;
+                sqlx::query(&format!("DROP TABLE {name}"))
+                    .execute(&mut *tx)
+                    .await?;
+                (
+                    id,
+                    serde_json::json!({ "partition": name, "rows": rows, "object_key": key }),
+                )
+            }
+        };
+
+        record_audit(
+            &mut *tx,
+            SYSTEM_ACTOR,
+            "lending_archive.archived",
+            &archive_id.to_string(),
+            details,
+        )
+        .await?;
+        tx.commit().await?;
+        info!(partition = %name, "Lending event partition archived");
+        Ok(true)
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn partition_names_round_trip_and_months_wrap_years() {
+        let month = date(2026, 7, 1);
+        assert_eq!(partition_name(month), "lending_events_p2026_07");
+        assert_eq!(partition_month("lending_events_p2026_07"), Some(month));
+        assert_eq!(partition_month("lending_events_default"), None);
+        assert_eq!(partition_month("lending_events_p2026_13"), None);
+
+        assert_eq!(add_months(date(2026, 1, 1), -1), date(2025, 12, 1));
+        assert_eq!(add_months(date(2026, 11, 1), 2), date(2027, 1, 1));
+        assert_eq!(add_months(date(2026, 7, 1), -12), date(2025, 7, 1));
+        assert_eq!(month_start(date(2026, 2, 28)), date(2026, 2, 1));
+
+        let (start, end) = month_bounds(date(2026, 12, 1));
+        assert_eq!(start.to_rfc3339(), "2026-12-01T00:00:00+00:00");
+        assert_eq!(end.to_rfc3339(), "2027-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn archives_decode_to_the_exported_rows() {
+        let record = EventRecord {
+            id: Uuid::new_v4().to_string(),
+            event_type: "deposit".to_string(),
+            plan_id: String::new(),
+            user_address: "GSENDER".to_string(),
+            asset: "native".to_string(),
+            amount: "1000000".to_string(),
+            transaction_hash: "abc".to_string(),
+            external_id: "123".to_string(),
+            metadata: r#"{"memo": "a,\"b\""}"#.to_string(),
+            created_at: "2026-07-01T10:00:00.000000Z".to_string(),
+        };
+        let mut writer =
+            csv::Writer::from_writer(GzEncoder::new(Vec::new(), Compression::default()));
+        writer.write_record(EVENT_HEADER).unwrap();
+        writer.write_record(record.fields()).unwrap();
+        let bytes = writer.into_inner().unwrap().finish().unwrap();
+
+        assert_eq!(decode_archive(&bytes).unwrap(), vec![record]);
+        assert!(decode_archive(b"not gzip").is_err());
+    }
+}
@@ -1,19 +1,115 @@
+pub mod address_book;
+pub mod admin_access;
+pub mod admin_batch;
 pub mod api;
+pub mod audit;
 pub mod auth;
+pub mod backups;
+pub mod balance_monitor;
+pub mod bridge;
+pub mod broadcasts;
 pub mod cache;
+pub mod chain;
+pub mod check_in;
+pub mod claim_delegations;
+pub mod claim_documents;
+pub mod claim_eligibility;
+pub mod claim_expiry;
+pub mod claim_fraud;
+pub mod claim_portal;
+pub mod claim_requests;
 pub mod config;
+pub mod consents;
+pub mod cost_breakdown;
+pub mod data_corrections;
 pub mod db;
+pub mod dead_letters;
+pub mod deposit_quarantine;
+pub mod deposit_refunds;
+pub mod deposits;
+pub mod economics;
+pub mod email_changes;
+pub mod emergency_contacts;
+pub mod exports;
+pub mod feature_flags;
+pub mod field_crypto;
+pub mod freezes;
+pub mod graphql;
+pub mod http_audit;
+pub mod http_client;
 pub mod inactivity_watchdog;
+pub mod jobs;
+pub mod keeper;
+pub mod kyc_sync;
+pub mod kyc_tiers;
 pub mod kyc_webhook;
+pub mod ledger;
+pub mod lending_archive;
+pub mod mailer;
 pub mod metrics;
 pub mod middleware;
+pub mod money;
+pub mod notification_digest;
+pub mod notifications;
+pub mod offramp;
+pub mod organization_members;
+pub mod organizations;
+pub mod pagination;
+pub mod payout_batcher;
+pub mod payout_conversion;
+pub mod payout_destinations;
+pub mod pending_changes;
+pub mod plan_events;
+pub mod plan_history;
+pub mod plan_metadata;
+pub mod plan_owners;
+pub mod plan_tags;
+pub mod plan_validation;
+pub mod platform_settings;
+pub mod projection;
+pub mod read_models;
+pub mod reports;
+pub mod security_events;
+pub mod sep10;
+pub mod simulation;
+pub mod sms;
+pub mod status_page;
 pub mod stellar_anchor;
+pub mod storage_ttl;
+pub mod system_settings;
+pub mod tax_documents;
 pub mod telemetry;
+pub mod templates;
+pub mod trustlines;
+pub mod user_profiles;
+pub mod wallet_reauth;
+pub mod witnesses;
 pub mod ws;
 pub mod yield_calculator;
 
 pub use api::{create_router, AppState, PlanResponse};
+pub use backups::{BackupConfig, BackupService};
+pub use balance_monitor::{BalanceMonitorConfig, BalanceMonitorService};
+pub use bridge::{BridgeTimeoutConfig, BridgeTimeoutService};
+pub use broadcasts::{BroadcastSenderConfig, BroadcastSenderService};
 pub use cache::PlanCache;
+pub use check_in::{CheckInEscalationConfig, CheckInEscalationService};
+pub use claim_expiry::{ClaimExpiryConfig, ClaimExpiryService};
+pub use claim_requests::{ClaimExecutorConfig, ClaimExecutorService};
 pub use config::Config;
 pub use db::DbManager;
+pub use dead_letters::{DeadLetterMonitorConfig, DeadLetterMonitorService};
+pub use deposit_refunds::{DepositRefundConfig, DepositRefundService};
+pub use deposits::{DepositWatcherConfig, DepositWatcherService};
+pub use http_audit::{HttpAuditRetentionConfig, HttpAuditRetentionService};
 pub use inactivity_watchdog::{InactivityWatchdogConfig, InactivityWatchdogService};
+pub use keeper::{KeeperConfig, KeeperService};
+pub use kyc_sync::{KycSyncConfig, KycSyncService};
+pub use lending_archive::{LendingArchiveConfig, LendingArchiveService};
+pub use notification_digest::{NotificationDigestConfig, NotificationDigestService};
+pub use payout_batcher::{PayoutBatcherConfig, PayoutBatcherService};
+pub use plan_metadata::{PlanMetadataConfig, PlanMetadataService};
+pub use read_models::{ReadModelRefreshConfig, ReadModelRefreshService};
+pub use reports::{ReportSchedulerConfig, ReportSchedulerService};
+pub use storage_ttl::{StorageTtlConfig, StorageTtlService};
+pub use witnesses::{WitnessAnchorConfig, WitnessAnchorService};
@@ -0,0 +1,147 @@
+//! Outbound email through an HTTP mail API.
+//!
+//! The provider receives `{ from, to, subject, text }` as JSON with a bearer
+//! key, plus base64 `attachments` when there are any. Without `EMAIL_API_URL` messages are only logged, which keeps local
+//! and test environments from needing a provider.
+
+use base64::Engine;
+use thiserror::Error;
+use tracing::info;
+
+use crate::http_client::{HttpClient, HttpError, HttpPolicy};
+
+const DEFAULT_FROM: &str = "InheritX <no-reply@inheritx.app>";
+
+#[derive(Debug, Clone, Default)]
+pub struct MailerConfig {
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    pub from: String,
+}
+
+impl MailerConfig {
+    pub fn from_env() -> Self {
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+
+        Self {
+            api_url: var("EMAIL_API_URL"),
+            api_key: var("EMAIL_API_KEY"),
+            from: var("EMAIL_FROM").unwrap_or_else(|| DEFAULT_FROM.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("mail request failed: {0}")]
+    Http(#[from] HttpError),
+    #[error("mail provider returned {status}: {body}")]
+    Provider { status: u16, body: String },
+}
+
+/// A file sent along with an email.
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+pub struct Mailer {
+    http: HttpClient,
+    config: MailerConfig,
+}
+
+impl Mailer {
+    pub fn new(config: MailerConfig) -> Self {
+        let http = HttpClient::new("mailer", HttpPolicy::default());
+        Self { http, config }
+    }
+
+    /// The provider's API endpoint; `None` when mail is only logged.
+    pub fn api_url(&self) -> Option<&str> {
+        self.config.api_url.as_deref()
+    }
+
+    pub async fn send(&self, to: &str, subject: &str, text: &str) -> Result<(), MailError> {
+        self.send_with_attachments(to, subject, text, &[]).await
+    }
+
+    pub async fn send_with_attachments(
+        &self,
+        to: &str,
+        subject: &str,
+        text: &str,
+        attachments: &[Attachment],
+    ) -> Result<(), MailError> {
+        let Some(url) = self.config.api_url.as_deref() else {
+            info!(
+                to = %to,
+                subject = %subject,
+                attachments = attachments.len(),
+                "Email delivery not configured; skipping send"
+            );
+            return Ok(());
+        };
+
+        let mut body = serde_json::json!({
+            "from": self.config.from,
+            "to": to,
+            "subject": subject,
+            "text": text,
+        });
+        if !attachments.is_empty() {
+            body["attachments"] = attachments
+                .iter()
+                .map(|attachment| {
+                    serde_json::json!({
+                        "filename": attachment.filename,
+                        "content_type": attachment.content_type,
+                        "content": base64::engine::general_purpose::STANDARD
+                            .encode(&attachment.content),
+                    })
+                })
+                .collect();
+        }
+
+        let mut request = self.http.post(url).json(&body);
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = self.http.send(request).await?;
+        if !response.status().is_success() {
+            return Err(MailError::Provider {
+                status: response.status().as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Loose syntactic check; deliverability is proven by verification mail.
+pub fn is_plausible_email(address: &str) -> bool {
+    let Some((local, domain)) = address.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !address.chars().any(char::is_whitespace)
+        && address.len() <= 254
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn email_check_rejects_obvious_garbage() {
+        assert!(is_plausible_email("heir@example.com"));
+        assert!(!is_plausible_email("heir@example"));
+        assert!(!is_plausible_email("@example.com"));
+        assert!(!is_plausible_email("heir example@example.com"));
+    }
+}
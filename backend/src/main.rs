@@ -1,6 +1,22 @@
+use inheritx_backend::admin_access::AdminAccessPolicy;
+use inheritx_backend::feature_flags::FeatureFlagCache;
+use inheritx_backend::field_crypto::FieldCipher;
+use inheritx_backend::jobs::JobRegistry;
+use inheritx_backend::status_page::{StatusPage, StatusPageConfig};
+use inheritx_backend::system_settings::SystemSettingsCache;
 use inheritx_backend::{
-    create_router, metrics, telemetry, AppState, Config, DbManager, InactivityWatchdogConfig,
-    InactivityWatchdogService,
+    create_router, metrics, telemetry, AppState, BackupConfig, BackupService, BalanceMonitorConfig,
+    BalanceMonitorService, BridgeTimeoutConfig, BridgeTimeoutService, BroadcastSenderConfig,
+    BroadcastSenderService, CheckInEscalationConfig, CheckInEscalationService, ClaimExecutorConfig,
+    ClaimExecutorService, ClaimExpiryConfig, ClaimExpiryService, Config, DbManager,
+    DeadLetterMonitorConfig, DeadLetterMonitorService, DepositRefundConfig, DepositRefundService,
+    DepositWatcherConfig, DepositWatcherService, HttpAuditRetentionConfig,
+    HttpAuditRetentionService, InactivityWatchdogConfig, InactivityWatchdogService, KeeperConfig,
+    KeeperService, KycSyncConfig, KycSyncService, LendingArchiveConfig, LendingArchiveService,
+    NotificationDigestConfig, NotificationDigestService, PayoutBatcherConfig, PayoutBatcherService,
+    PlanMetadataConfig, PlanMetadataService, ReadModelRefreshConfig, ReadModelRefreshService,
+    ReportSchedulerConfig, ReportSchedulerService, StorageTtlConfig, StorageTtlService,
+    WitnessAnchorConfig, WitnessAnchorService,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -19,6 +35,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load configuration
     let config = Config::load()?;
+    info!(config = ?config, "Loaded {} configuration", config.environment.as_str());
     let plan_cache = inheritx_backend::PlanCache::from_redis_url(
         config.redis_url.as_deref(),
         config.plan_cache_ttl_secs,
@@ -37,6 +54,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 warn!("Failed to run database migrations: {:?}", e);
             }
 
+            match DbManager::check_schema_compatibility(&pool).await {
+                Ok(status) => {
+                    if !status.pending_post_deploy.is_empty() {
+                        info!(
+                            pending = ?status.pending_post_deploy,
+                            "Post-deploy migrations are pending; run them once the rollout is complete"
+                        );
+                    }
+                }
+                Err(e) => {
+                    error!("Refusing to start: {e}");
+                    std::process::exit(1);
+                }
+            }
+
             pool
         }
 
@@ -50,23 +82,278 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    let offramp = Arc::new(inheritx_backend::offramp::AnchorClient::new(
+        inheritx_backend::offramp::OfframpConfig::from_env(),
+    ));
+
+    let mailer = Arc::new(inheritx_backend::mailer::Mailer::new(
+        inheritx_backend::mailer::MailerConfig::from_env(),
+    ));
+    let sms = Arc::new(inheritx_backend::sms::SmsClient::new(
+        inheritx_backend::sms::SmsConfig::from_env(),
+    ));
+    let contacts = Arc::new(inheritx_backend::emergency_contacts::ContactNotifier::new(
+        mailer.clone(),
+        sms,
+    ));
+
+    let system_settings = Arc::new(SystemSettingsCache::new(
+        db_pool.clone(),
+        Arc::new(config.clone()),
+        SystemSettingsCache::ttl_from_env(),
+    ));
+    let feature_flags = Arc::new(FeatureFlagCache::new(
+        db_pool.clone(),
+        config.environment,
+        FeatureFlagCache::ttl_from_env(),
+    ));
+
+    let jobs = Arc::new(JobRegistry::new(db_pool.clone()));
+
     // Initialize state skeleton
     let (kyc_tx, _) = tokio::sync::broadcast::channel(100);
     let state = Arc::new(AppState {
         anchor: Arc::new(inheritx_backend::stellar_anchor::AnchorRegistry::new()),
         db_pool: db_pool.clone(),
         kyc_tx,
-        kyc_webhook_secret: std::env::var("KYC_WEBHOOK_SECRET").ok(),
+        config: Arc::new(config.clone()),
         apy_config: inheritx_backend::yield_calculator::ApyConfig::from_env(),
         plan_cache: plan_cache.clone(),
+        offramp: offramp.clone(),
+        contacts: contacts.clone(),
+        mailer: mailer.clone(),
+        soroban_rpc: Arc::new(inheritx_backend::chain::rpc::SorobanRpcClient::new(
+            inheritx_backend::chain::rpc::SorobanRpcConfig::from_env(),
+        )),
+        admin_access: Arc::new(AdminAccessPolicy::from_config(&config)?),
+        field_cipher: Arc::new(FieldCipher::from_keys(&config.field_encryption_keys)),
+        system_settings: system_settings.clone(),
+        feature_flags,
+        jobs: jobs.clone(),
+        status_page: Arc::new(StatusPage::new(StatusPageConfig::from_env())),
     });
 
     let inactivity_watchdog = Arc::new(InactivityWatchdogService::new(
         db_pool.clone(),
-        plan_cache,
+        plan_cache.clone(),
+        contacts.clone(),
         InactivityWatchdogConfig::from_env(),
     ));
-    inactivity_watchdog.start();
+    inactivity_watchdog.start(&jobs);
+
+    let tx_service: Arc<dyn inheritx_backend::chain::TxService> =
+        match inheritx_backend::chain::KeyRing::from_env(&state.field_cipher) {
+            Ok(Some(keys)) => {
+                info!(keys = ?keys, "Loaded transaction signing keys");
+                Arc::new(inheritx_backend::chain::SimulatedTxService::with_keys(
+                    Arc::new(keys),
+                ))
+            }
+            Ok(None) => {
+                warn!("SIGNER_BACKEND not set; transactions are not signed");
+                Arc::new(inheritx_backend::chain::SimulatedTxService::default())
+            }
+            Err(e) => {
+                error!("Refusing to start: {e}");
+                std::process::exit(1);
+            }
+        };
+
+    let payout_batcher = Arc::new(PayoutBatcherService::new(
+        db_pool.clone(),
+        tx_service.clone(),
+        inheritx_backend::trustlines::TrustlineChecker::from_config(&config),
+        PayoutBatcherConfig::from_env(),
+    ));
+    payout_batcher.start(&jobs);
+
+    let deposit_refunds = Arc::new(DepositRefundService::new(
+        db_pool.clone(),
+        tx_service.clone(),
+        DepositRefundConfig::from_env(),
+    ));
+    deposit_refunds.start(&jobs);
+
+    let offramp_config = offramp.config();
+    if offramp_config.sep24_server.is_some() || offramp_config.sep31_server.is_some() {
+        let offramp_poller = Arc::new(inheritx_backend::offramp::OfframpStatusService::new(
+            db_pool.clone(),
+            offramp.clone(),
+            tx_service.clone(),
+        ));
+        offramp_poller.start(&jobs);
+    }
+
+    let check_in_escalation = Arc::new(CheckInEscalationService::new(
+        db_pool.clone(),
+        mailer.clone(),
+        contacts,
+        plan_cache.clone(),
+        system_settings.clone(),
+        CheckInEscalationConfig::from_env(),
+    ));
+    check_in_escalation.start(&jobs);
+
+    let notification_digests = Arc::new(NotificationDigestService::new(
+        db_pool.clone(),
+        mailer.clone(),
+        NotificationDigestConfig::from_env(),
+    ));
+    notification_digests.start(&jobs);
+
+    let dead_letter_monitor = Arc::new(DeadLetterMonitorService::new(
+        db_pool.clone(),
+        mailer.clone(),
+        DeadLetterMonitorConfig::from_env(),
+    ));
+    dead_letter_monitor.start(&jobs);
+
+    let report_scheduler = Arc::new(ReportSchedulerService::new(
+        db_pool.clone(),
+        mailer,
+        state.config.clone(),
+        ReportSchedulerConfig::from_env(),
+    ));
+    report_scheduler.start(&jobs);
+
+    let broadcast_sender = Arc::new(BroadcastSenderService::new(
+        db_pool.clone(),
+        BroadcastSenderConfig::from_env(),
+    ));
+    broadcast_sender.start(&jobs);
+
+    let lending_archive = Arc::new(LendingArchiveService::new(
+        db_pool.clone(),
+        state.config.clone(),
+        LendingArchiveConfig::from_env(),
+    ));
+    lending_archive.start(&jobs);
+
+    match BackupService::new(
+        db_pool.clone(),
+        state.config.database_url.clone(),
+        BackupConfig::from_env(),
+    ) {
+        Some(backups) => Arc::new(backups).start(&jobs),
+        None => info!("Backups are off: BACKUP_S3_ENDPOINT and BACKUP_S3_BUCKET are not set"),
+    }
+
+    let read_model_refresh = Arc::new(ReadModelRefreshService::new(
+        db_pool.clone(),
+        ReadModelRefreshConfig::from_env(),
+    ));
+    read_model_refresh.start(&jobs);
+
+    let claim_executor = Arc::new(ClaimExecutorService::new(
+        state.clone(),
+        ClaimExecutorConfig::from_env(),
+    ));
+    claim_executor.start(&jobs);
+
+    if config.escheat_treasury_address.is_none() {
+        warn!("ESCHEAT_TREASURY_ADDRESS not set; unclaimed plans without a fallback beneficiary are not escheated");
+    }
+    let claim_expiry = Arc::new(ClaimExpiryService::new(
+        state.clone(),
+        ClaimExpiryConfig::from_env(),
+    ));
+    claim_expiry.start(&jobs);
+
+    let bridge_timeouts = Arc::new(BridgeTimeoutService::new(
+        db_pool.clone(),
+        BridgeTimeoutConfig::from_env(),
+    ));
+    bridge_timeouts.start(&jobs);
+
+    match config.inheritance_contract_id.clone() {
+        Some(contract_id) => {
+            let storage_ttl = Arc::new(StorageTtlService::new(
+                db_pool.clone(),
+                tx_service.clone(),
+                contract_id.clone(),
+                StorageTtlConfig::from_env(),
+            ));
+            storage_ttl.start(&jobs);
+
+            let plan_metadata = Arc::new(PlanMetadataService::new(
+                db_pool.clone(),
+                tx_service.clone(),
+                contract_id.clone(),
+                PlanMetadataConfig::from_env(),
+            ));
+            plan_metadata.start(&jobs);
+
+            let witness_anchor = Arc::new(WitnessAnchorService::new(
+                db_pool.clone(),
+                tx_service.clone(),
+                contract_id.clone(),
+                WitnessAnchorConfig::from_env(),
+            ));
+            witness_anchor.start(&jobs);
+
+            let keeper = Arc::new(KeeperService::new(
+                state.clone(),
+                tx_service.clone(),
+                contract_id.clone(),
+                KeeperConfig::from_env(),
+            ));
+            keeper.start(&jobs);
+
+            let balance_monitor_config = BalanceMonitorConfig::from_env();
+            match balance_monitor_config.source_account.clone() {
+                Some(source_account) if state.soroban_rpc.is_configured() => {
+                    let balance_monitor = Arc::new(BalanceMonitorService::new(
+                        state.clone(),
+                        contract_id.clone(),
+                        source_account,
+                        balance_monitor_config,
+                    ));
+                    balance_monitor.start(&jobs);
+                }
+                _ => warn!("SOROBAN_RPC_URL or BALANCE_MONITOR_SOURCE_ACCOUNT not set; on-chain balances will not be reconciled"),
+            }
+
+            let kyc_sync_config = KycSyncConfig::from_env();
+            match kyc_sync_config.admin_account.clone() {
+                Some(admin_account) => {
+                    let kyc_sync = Arc::new(KycSyncService::new(
+                        db_pool.clone(),
+                        tx_service.clone(),
+                        contract_id,
+                        admin_account,
+                        kyc_sync_config,
+                    ));
+                    kyc_sync.start(&jobs);
+                }
+                None => warn!("KYC_SYNC_ADMIN_ACCOUNT not set; KYC tiers will not be pushed on-chain"),
+            }
+        }
+        None => warn!("INHERITANCE_CONTRACT_ID not set; on-chain storage TTL bumps, plan metadata anchoring and the contract keeper are disabled"),
+    }
+
+    match (
+        config.horizon_url.clone(),
+        config.deposit_accounts.is_empty(),
+    ) {
+        (Some(horizon_url), false) => {
+            let deposit_watcher = Arc::new(DepositWatcherService::new(
+                db_pool.clone(),
+                inheritx_backend::deposits::HorizonClient::new(horizon_url),
+                config.deposit_accounts.clone(),
+                config.deposit_asset.clone(),
+                DepositWatcherConfig::from_env(),
+            ));
+            deposit_watcher.start(&jobs);
+        }
+        _ => warn!("HORIZON_URL or DEPOSIT_ACCOUNTS not set; deposit detection is disabled"),
+    }
+
+    let http_audit_retention = Arc::new(HttpAuditRetentionService::new(
+        db_pool.clone(),
+        system_settings.clone(),
+        HttpAuditRetentionConfig::from_env(),
+    ));
+    http_audit_retention.start(&jobs);
 
     // Periodically refresh DB pool metrics
     {
@@ -88,7 +375,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting rebranded INHERITX backend skeleton on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
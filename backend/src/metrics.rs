@@ -1,8 +1,8 @@
 use axum::{extract::Request, http::StatusCode, middleware::Next, response::IntoResponse};
 use once_cell::sync::Lazy;
 use prometheus::{
-    histogram_opts, opts, register_gauge, register_histogram_vec, Encoder, Gauge, HistogramVec,
-    TextEncoder,
+    histogram_opts, opts, register_counter_vec, register_gauge, register_gauge_vec,
+    register_histogram_vec, CounterVec, Encoder, Gauge, GaugeVec, HistogramVec, TextEncoder,
 };
 use std::time::Instant;
 
@@ -48,12 +48,198 @@ pub static DB_POOL_IDLE: Lazy<Gauge> = Lazy::new(|| {
     .expect("failed to register db_pool_idle gauge")
 });
 
+/// Pending dead letter queue entries.
+/// Labels: worker
+pub static DEAD_LETTER_DEPTH: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        opts!(
+            "inheritx_dead_letter_depth",
+            "Background jobs in the dead letter queue awaiting review"
+        ),
+        &["worker"]
+    )
+    .expect("failed to register dead_letter_depth gauge")
+});
+
+/// Outbound HTTP calls by integration and outcome (`ok`, `server_error`,
+/// `rate_limited`, `timeout`, `connect_error`, `error`, `circuit_open`).
+/// Labels: client, outcome
+pub static OUTBOUND_REQUESTS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!(
+            "inheritx_outbound_requests_total",
+            "Outbound HTTP requests by client and outcome"
+        ),
+        &["client", "outcome"]
+    )
+    .expect("failed to register outbound_requests counter")
+});
+
+/// Outbound HTTP attempt latency (seconds).
+/// Labels: client
+pub static OUTBOUND_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        histogram_opts!(
+            "inheritx_outbound_request_duration_seconds",
+            "Outbound HTTP request latency in seconds",
+            vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 15.0]
+        ),
+        &["client"]
+    )
+    .expect("failed to register outbound_latency histogram")
+});
+
+/// Times a host's circuit breaker opened.
+/// Labels: client
+pub static OUTBOUND_CIRCUIT_OPENED: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!(
+            "inheritx_outbound_circuit_opened_total",
+            "Outbound circuit breakers opened after repeated failures"
+        ),
+        &["client"]
+    )
+    .expect("failed to register outbound_circuit_opened counter")
+});
+
+/// Keeper sweeps by outcome (`completed`, `failed`, `missed`).
+/// Labels: outcome
+pub static KEEPER_RUNS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!(
+            "inheritx_keeper_runs_total",
+            "Keeper sweeps completed, failed or missed"
+        ),
+        &["outcome"]
+    )
+    .expect("failed to register keeper_runs counter")
+});
+
+/// Keeper contract calls by task and outcome (`submitted`, `not_due`,
+/// `cancelled`, `retrying`, `failed`, `deferred`).
+/// Labels: task, outcome
+pub static KEEPER_INVOCATIONS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!(
+            "inheritx_keeper_invocations_total",
+            "Keeper contract invocations by task and outcome"
+        ),
+        &["task", "outcome"]
+    )
+    .expect("failed to register keeper_invocations counter")
+});
+
+/// Fees spent on keeper calls, in stroops.
+/// Labels: task
+pub static KEEPER_FEES: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!(
+            "inheritx_keeper_fees_stroops_total",
+            "Transaction fees spent by the keeper in stroops"
+        ),
+        &["task"]
+    )
+    .expect("failed to register keeper_fees counter")
+});
+
+/// Users by KYC sync state (`synced`, `pending_push`, `failed`), refreshed
+/// each KYC sync sweep.
+/// Labels: status
+pub static KYC_SYNC_USERS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        opts!(
+            "inheritx_kyc_sync_users",
+            "Users whose on-chain KYC tier is synced, pending a push or failed"
+        ),
+        &["status"]
+    )
+    .expect("failed to register kyc_sync_users gauge")
+});
+
+/// KYC tier pushes by outcome (`pushed`, `retrying`, `failed`).
+/// Labels: outcome
+pub static KYC_SYNC_PUSHES: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!(
+            "inheritx_kyc_sync_pushes_total",
+            "On-chain KYC tier pushes by outcome"
+        ),
+        &["outcome"]
+    )
+    .expect("failed to register kyc_sync_pushes counter")
+});
+
+/// Backup runs by outcome (`verified`, `failed`).
+/// Labels: outcome
+pub static BACKUP_RUNS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!("inheritx_backup_runs_total", "Backup runs by outcome"),
+        &["outcome"]
+    )
+    .expect("failed to register backup_runs counter")
+});
+
+/// Unix time the last verified backup was taken, for age alerts.
+pub static BACKUP_LAST_VERIFIED: Lazy<Gauge> = Lazy::new(|| {
+    register_gauge!(opts!(
+        "inheritx_backup_last_verified_timestamp_seconds",
+        "Unix time the last verified backup was taken"
+    ))
+    .expect("failed to register backup_last_verified gauge")
+});
+
+/// Encrypted size of the last verified backup.
+pub static BACKUP_SIZE_BYTES: Lazy<Gauge> = Lazy::new(|| {
+    register_gauge!(opts!(
+        "inheritx_backup_size_bytes",
+        "Encrypted size of the last verified backup in bytes"
+    ))
+    .expect("failed to register backup_size_bytes gauge")
+});
+
+/// On-chain less expected balance of the inheritance contract per token.
+pub static BALANCE_DISCREPANCY: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        opts!(
+            "inheritx_balance_discrepancy",
+            "On-chain balance less funded plan balances per token"
+        ),
+        &["asset"]
+    )
+    .expect("failed to register balance_discrepancy gauge")
+});
+
+pub static BALANCE_CHECKS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        opts!(
+            "inheritx_balance_checks_total",
+            "Token balance checks by status"
+        ),
+        &["status"]
+    )
+    .expect("failed to register balance_checks counter")
+});
+
 /// Call once at startup to force lazy initialization of all metrics.
 pub fn init() {
     Lazy::force(&ACTIVE_CONNECTIONS);
     Lazy::force(&REQUEST_LATENCY);
     Lazy::force(&DB_POOL_SIZE);
     Lazy::force(&DB_POOL_IDLE);
+    Lazy::force(&DEAD_LETTER_DEPTH);
+    Lazy::force(&OUTBOUND_REQUESTS);
+    Lazy::force(&OUTBOUND_LATENCY);
+    Lazy::force(&OUTBOUND_CIRCUIT_OPENED);
+    Lazy::force(&KEEPER_RUNS);
+    Lazy::force(&KEEPER_INVOCATIONS);
+    Lazy::force(&KEEPER_FEES);
+    Lazy::force(&KYC_SYNC_USERS);
+    Lazy::force(&KYC_SYNC_PUSHES);
+    Lazy::force(&BACKUP_RUNS);
+    Lazy::force(&BACKUP_LAST_VERIFIED);
+    Lazy::force(&BACKUP_SIZE_BYTES);
+    Lazy::force(&BALANCE_DISCREPANCY);
+    Lazy::force(&BALANCE_CHECKS);
 }
 
 /// Updates DB pool gauges from the current sqlx pool state.
@@ -31,6 +31,28 @@ impl Default for RateLimitConfig {
     }
 }
 
+impl RateLimitConfig {
+    /// The defaults, overridden by `RATE_LIMIT_MAX_REQUESTS` and
+    /// `RATE_LIMIT_WINDOW_SECS`. Load tests from a single host raise the
+    /// request limit.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let max_requests = std::env::var("RATE_LIMIT_MAX_REQUESTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.max_requests);
+        let window_secs = std::env::var("RATE_LIMIT_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.window.as_secs());
+
+        Self {
+            max_requests,
+            window: Duration::from_secs(window_secs.max(1)),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct RateLimitState {
     count: u64,
@@ -0,0 +1,69 @@
+//! Token amounts tagged with the asset they are denominated in.
+//!
+//! Plans, payouts, refunds, bridge transfers and the ledger hold exact
+//! [`Decimal`] amounts in `NUMERIC` columns. Responses carry them as
+//! [`Money`], built from the row and the asset it is denominated in, which
+//! serializes the amount as a string (`{"amount": "1000.5", "currency":
+//! "USDC"}`) so clients never round it through a float. Rows keep plain
+//! `Decimal` fields; the conversion happens where a row becomes a response.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Money {
+    #[serde(
+        serialize_with = "serialize_amount",
+        deserialize_with = "deserialize_amount"
+    )]
+    pub amount: Decimal,
+    /// Asset the amount is in, e.g. the plan's `token_address`.
+    pub currency: String,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: impl Into<String>) -> Self {
+        Self {
+            amount,
+            currency: currency.into(),
+        }
+    }
+
+    pub fn zero(currency: impl Into<String>) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+}
+
+fn serialize_amount<S: Serializer>(amount: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(amount)
+}
+
+fn deserialize_amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    text.trim().parse().map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_round_trips_as_an_exact_string() {
+        let money = Money::new(Decimal::new(10_000_001, 4), "USDC");
+        let json = serde_json::to_value(&money).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({ "amount": "1000.0001", "currency": "USDC" })
+        );
+        assert_eq!(serde_json::from_value::<Money>(json).unwrap(), money);
+    }
+
+    #[test]
+    fn float_amounts_are_rejected() {
+        let parsed = serde_json::from_value::<Money>(
+            serde_json::json!({ "amount": 0.1, "currency": "USDC" }),
+        );
+        assert!(parsed.is_err());
+    }
+}
@@ -0,0 +1,609 @@
+//! Email delivery of notifications, immediately or as hourly/daily digests.
+//!
+//! Wallets opt in by setting an email and a digest frequency. Once set, the
+//! email is changed through [`crate::email_changes`]. The digest
+//! worker groups each wallet's queued email deliveries into one email once
+//! the wallet's frequency allows another send. Failed sends are retried
+//! with backoff and marked `failed` after `NOTIFICATION_MAX_ATTEMPTS`, at
+//! which point they are moved to the dead letter queue.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::dead_letters::{record_dead_letter, NewDeadLetter, Worker};
+use crate::jobs::JobRegistry;
+use crate::mailer::{is_plausible_email, Mailer};
+use crate::notifications::{cancel_queued_email, sync_statuses, Notification};
+use crate::templates::{render_for, Rendered, TemplateKey};
+use crate::user_profiles::timezone_for;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_BATCH_SIZE: i64 = 100;
+const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+const MAX_RETRY_DELAY_MINUTES: i64 = 60;
+const MAX_NOTIFICATIONS_PER_DIGEST: i64 = 100;
+const DIGEST_LOCK_KEY: i64 = 826;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    Immediate,
+    Hourly,
+    Daily,
+}
+
+impl DigestFrequency {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Immediate => "immediate",
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "immediate" => Some(Self::Immediate),
+            "hourly" => Some(Self::Hourly),
+            "daily" => Some(Self::Daily),
+            _ => None,
+        }
+    }
+
+    fn period(self) -> chrono::Duration {
+        match self {
+            Self::Immediate => chrono::Duration::zero(),
+            Self::Hourly => chrono::Duration::hours(1),
+            Self::Daily => chrono::Duration::days(1),
+        }
+    }
+
+    /// Whether another email may be sent given when the last one went out.
+    pub fn is_due(self, last_sent_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        last_sent_at.is_none_or(|sent| now - sent >= self.period())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct NotificationPreferences {
+    pub user_address: String,
+    pub email: Option<String>,
+    pub digest_frequency: String,
+    pub last_digest_sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    /// `null` stops email delivery.
+    pub email: Option<String>,
+    pub digest_frequency: DigestFrequency,
+}
+
+const PREFERENCE_COLUMNS: &str =
+    "user_address, email, digest_frequency, last_digest_sent_at, created_at, updated_at";
+
+/// Wait before retrying a send that has failed `attempts` times: 1, 2, 4,
+/// ... minutes, capped at an hour.
+pub fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 6) as u32;
+    chrono::Duration::minutes((1i64 << exponent).min(MAX_RETRY_DELAY_MINUTES))
+}
+
+/// Builds the email for a batch of notifications: a single notification is
+/// sent as-is, several are grouped by type into one digest that opens with
+/// the localized `intro` and shows times in the recipient's timezone.
+pub fn assemble_digest(
+    notifications: &[Notification],
+    intro: &Rendered,
+    timezone: Tz,
+) -> (String, String) {
+    if let [only] = notifications {
+        return (only.title.clone(), only.message.clone());
+    }
+
+    let mut by_type: BTreeMap<&str, Vec<&Notification>> = BTreeMap::new();
+    for notification in notifications {
+        by_type
+            .entry(notification.notification_type.as_str())
+            .or_default()
+            .push(notification);
+    }
+
+    let subject = intro.subject.clone();
+    let mut body = format!("{}\n", intro.body);
+    for (notification_type, group) in by_type {
+        body.push_str(&format!(
+            "\n{} ({})\n",
+            notification_type.replace('_', " "),
+            group.len()
+        ));
+        for notification in group {
+            body.push_str(&format!(
+                "- [{}] {}: {}\n",
+                notification
+                    .created_at
+                    .with_timezone(&timezone)
+                    .format("%Y-%m-%d %H:%M %Z"),
+                notification.title,
+                notification.message
+            ));
+        }
+    }
+    (subject, body)
+}
+
+// Handler: Get Notification Preferences
+pub async fn get_notification_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, NotificationPreferences>(&format!(
+        "SELECT {PREFERENCE_COLUMNS} FROM notification_preferences WHERE user_address = $1"
+    ))
+    .bind(&address)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(preferences)) => (StatusCode::OK, Json(preferences)).into_response(),
+        Ok(None) => {
+            let now = Utc::now();
+            (
+                StatusCode::OK,
+                Json(NotificationPreferences {
+                    user_address: address,
+                    email: None,
+                    digest_frequency: DigestFrequency::Immediate.as_str().to_string(),
+                    last_digest_sent_at: None,
+                    created_at: now,
+                    updated_at: now,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to load notification preferences");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: Update Notification Preferences
+pub async fn update_notification_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<UpdatePreferencesRequest>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let email = payload
+        .email
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+    if email.as_deref().is_some_and(|e| !is_plausible_email(e)) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Invalid email address" })),
+        )
+            .into_response();
+    }
+
+    let result: Result<Option<NotificationPreferences>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let current: Option<String> = sqlx::query_scalar(
+            "SELECT email FROM notification_preferences WHERE user_address = $1 FOR UPDATE",
+        )
+        .bind(&address)
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+        // An email on file is changed or removed through the confirmed
+        // email change flow only.
+        if current.is_some() && current != email {
+            return Ok(None);
+        }
+        let preferences = sqlx::query_as::<_, NotificationPreferences>(&format!(
+            r#"
+            INSERT INTO notification_preferences (user_address, email, digest_frequency)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_address)
+            DO UPDATE SET email = EXCLUDED.email,
+                          digest_frequency = EXCLUDED.digest_frequency,
+                          updated_at = NOW()
+            RETURNING {PREFERENCE_COLUMNS}
+            "#
+        ))
+        .bind(&address)
+        .bind(email)
+        .bind(payload.digest_frequency.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+        if preferences.email.is_none() {
+            cancel_queued_email(&mut tx, &address).await?;
+        }
+        tx.commit().await?;
+        Ok(Some(preferences))
+    }
+    .await;
+
+    match result {
+        Ok(Some(preferences)) => (StatusCode::OK, Json(preferences)).into_response(),
+        Ok(None) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Use /api/users/me/email-change to change or remove your email"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to update notification preferences");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NotificationDigestConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+    /// Send attempts before an email delivery is marked `failed`.
+    pub max_attempts: i32,
+}
+
+impl NotificationDigestConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("NOTIFICATION_DIGEST_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("NOTIFICATION_DIGEST_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+        let max_attempts = parse_env("NOTIFICATION_MAX_ATTEMPTS", DEFAULT_MAX_ATTEMPTS);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+            max_attempts: max_attempts.max(1),
+        }
+    }
+}
+
+pub struct NotificationDigestService {
+    db: PgPool,
+    mailer: Arc<Mailer>,
+    config: NotificationDigestConfig,
+}
+
+impl NotificationDigestService {
+    pub fn new(db: PgPool, mailer: Arc<Mailer>, config: NotificationDigestConfig) -> Self {
+        Self { db, mailer, config }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("notification_digest", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&sent| {
+                    if sent > 0 {
+                        info!(emails = sent, "Notification digests sent");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Emails every wallet whose digest is due and has queued email
+    /// deliveries ready to send. Returns the number of emails sent.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(DIGEST_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Notification digest lock is held by another worker; skipping run");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let recipients = sqlx::query_as::<_, NotificationPreferences>(&format!(
+            r#"
+            SELECT {PREFERENCE_COLUMNS}
+            FROM notification_preferences p
+            WHERE p.email IS NOT NULL
+              AND EXISTS (
+                  SELECT 1 FROM notification_deliveries d
+                  JOIN notifications n ON n.id = d.notification_id
+                  WHERE n.user_address = p.user_address
+                    AND d.channel = 'email'
+                    AND d.status = 'queued'
+                    AND d.next_attempt_at <= NOW()
+              )
+            ORDER BY p.last_digest_sent_at ASC NULLS FIRST
+            LIMIT $1
+            "#
+        ))
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let now = Utc::now();
+        let mut sent = 0;
+        for recipient in recipients {
+            let frequency = DigestFrequency::parse(&recipient.digest_frequency)
+                .unwrap_or(DigestFrequency::Immediate);
+            let Some(email) = recipient.email.as_deref() else {
+                continue;
+            };
+            if !frequency.is_due(recipient.last_digest_sent_at, now) {
+                continue;
+            }
+
+            let notifications = sqlx::query_as::<_, Notification>(
+                r#"
+                SELECT n.id, n.user_address, n.notification_type, n.title, n.message, n.metadata,
+                       n.is_read, n.status, n.created_at
+                FROM notifications n
+                JOIN notification_deliveries d ON d.notification_id = n.id
+                WHERE n.user_address = $1
+                  AND d.channel = 'email'
+                  AND d.status = 'queued'
+                  AND d.next_attempt_at <= NOW()
+                ORDER BY n.created_at ASC
+                LIMIT $2
+                "#,
+            )
+            .bind(&recipient.user_address)
+            .bind(MAX_NOTIFICATIONS_PER_DIGEST)
+            .fetch_all(&mut *tx)
+            .await?;
+            if notifications.is_empty() {
+                continue;
+            }
+            let ids: Vec<Uuid> = notifications.iter().map(|n| n.id).collect();
+
+            let timezone = timezone_for(&mut *tx, &recipient.user_address).await?;
+            let intro = render_for(
+                &mut tx,
+                &recipient.user_address,
+                TemplateKey::Digest,
+                &[("count", &notifications.len().to_string())],
+            )
+            .await?;
+            let (subject, body) = assemble_digest(&notifications, &intro, timezone);
+            if let Err(e) = self.mailer.send(email, &subject, &body).await {
+                warn!(user_address = %recipient.user_address, error = %e, "Failed to send notification digest");
+                self.record_failure(&mut tx, &recipient.user_address, &ids, &e.to_string())
+                    .await?;
+                continue;
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE notification_deliveries
+                SET status = 'sent', attempts = attempts + 1, sent_at = NOW(),
+                    last_error = NULL, updated_at = NOW()
+                WHERE notification_id = ANY($1) AND channel = 'email'
+                "#,
+            )
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+            sync_statuses(&mut *tx, &ids).await?;
+            sqlx::query(
+                "UPDATE notification_preferences SET last_digest_sent_at = NOW() WHERE user_address = $1",
+            )
+            .bind(&recipient.user_address)
+            .execute(&mut *tx)
+            .await?;
+            sent += 1;
+        }
+
+        tx.commit().await?;
+        Ok(sent)
+    }
+
+    /// Schedules a retry for each delivery, or marks it `failed` and moves
+    /// it to the dead letter queue once it has used up its attempts.
+    async fn record_failure(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        user_address: &str,
+        notification_ids: &[Uuid],
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let attempts: Vec<(Uuid, Uuid, i32, serde_json::Value)> = sqlx::query_as(
+            r#"
+            UPDATE notification_deliveries
+            SET attempts = attempts + 1,
+                last_error = $2,
+                attempt_history = attempt_history || jsonb_build_array(jsonb_build_object(
+                    'attempt', attempts + 1, 'error', $2::text, 'at', NOW()
+                )),
+                updated_at = NOW()
+            WHERE notification_id = ANY($1) AND channel = 'email'
+            RETURNING id, notification_id, attempts, attempt_history
+            "#,
+        )
+        .bind(notification_ids)
+        .bind(error)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let now = Utc::now();
+        for (id, notification_id, attempts, attempt_history) in attempts {
+            let exhausted = attempts >= self.config.max_attempts;
+            let (status, next_attempt_at) = if exhausted {
+                ("failed", now)
+            } else {
+                ("queued", now + retry_delay(attempts))
+            };
+            sqlx::query(
+                "UPDATE notification_deliveries SET status = $2, next_attempt_at = $3 WHERE id = $1",
+            )
+            .bind(id)
+            .bind(status)
+            .bind(next_attempt_at)
+            .execute(&mut *conn)
+            .await?;
+
+            if exhausted {
+                record_dead_letter(
+                    &mut *conn,
+                    NewDeadLetter {
+                        worker: Worker::NotificationDigest,
+                        job_id: id,
+                        payload: serde_json::json!({
+                            "notification_id": notification_id,
+                            "channel": "email",
+                            "user_address": user_address,
+                        }),
+                        error,
+                        attempts,
+                        attempt_history,
+                    },
+                )
+                .await?;
+            }
+        }
+        sync_statuses(&mut *conn, notification_ids).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::render_builtin;
+    use chrono::TimeZone;
+
+    fn notification(notification_type: &str, title: &str) -> Notification {
+        Notification {
+            id: Uuid::new_v4(),
+            user_address: "GOWNER".to_string(),
+            notification_type: notification_type.to_string(),
+            title: title.to_string(),
+            message: format!("{title} details"),
+            metadata: serde_json::json!({}),
+            is_read: false,
+            status: "queued".to_string(),
+            created_at: Utc.with_ymd_and_hms(2026, 7, 1, 9, 30, 0).unwrap(),
+        }
+    }
+
+    fn intro(language: &str, count: usize) -> Rendered {
+        render_builtin(
+            TemplateKey::Digest,
+            language,
+            &[("count", &count.to_string())],
+        )
+    }
+
+    #[test]
+    fn frequency_controls_when_digest_is_due() {
+        let now = Utc.with_ymd_and_hms(2026, 7, 1, 12, 0, 0).unwrap();
+        let half_hour_ago = Some(now - chrono::Duration::minutes(30));
+
+        assert!(DigestFrequency::Immediate.is_due(half_hour_ago, now));
+        assert!(!DigestFrequency::Hourly.is_due(half_hour_ago, now));
+        assert!(DigestFrequency::Hourly.is_due(Some(now - chrono::Duration::hours(2)), now));
+        assert!(!DigestFrequency::Daily.is_due(Some(now - chrono::Duration::hours(2)), now));
+        assert!(DigestFrequency::Daily.is_due(None, now));
+    }
+
+    #[test]
+    fn retries_back_off_up_to_an_hour() {
+        assert_eq!(retry_delay(1), chrono::Duration::minutes(1));
+        assert_eq!(retry_delay(2), chrono::Duration::minutes(2));
+        assert_eq!(retry_delay(4), chrono::Duration::minutes(8));
+        assert_eq!(retry_delay(7), chrono::Duration::minutes(60));
+        assert_eq!(retry_delay(30), chrono::Duration::minutes(60));
+    }
+
+    #[test]
+    fn single_notification_is_sent_as_is() {
+        let (subject, body) = assemble_digest(
+            &[notification("plan_claimable", "Plan ready")],
+            &intro("en", 1),
+            Tz::UTC,
+        );
+        assert_eq!(subject, "Plan ready");
+        assert_eq!(body, "Plan ready details");
+    }
+
+    #[test]
+    fn digest_groups_by_type() {
+        let (subject, body) = assemble_digest(
+            &[
+                notification("withdrawal_update", "Withdrawal completed"),
+                notification("plan_claimable", "Plan ready"),
+                notification("withdrawal_update", "Withdrawal pending"),
+            ],
+            &intro("en", 3),
+            Tz::UTC,
+        );
+        assert_eq!(subject, "Your InheritX digest: 3 new notifications");
+        assert!(body.contains("plan claimable (1)"));
+        assert!(body.contains("withdrawal update (2)"));
+        assert!(body
+            .contains("- [2026-07-01 09:30 UTC] Withdrawal pending: Withdrawal pending details"));
+    }
+
+    #[test]
+    fn digest_times_use_recipient_timezone() {
+        let (_, body) = assemble_digest(
+            &[
+                notification("plan_claimable", "Plan ready"),
+                notification("plan_claimable", "Plan due"),
+            ],
+            &intro("en", 2),
+            Tz::Africa__Lagos,
+        );
+        assert!(body.contains("- [2026-07-01 10:30 WAT] Plan ready: Plan ready details"));
+    }
+
+    #[test]
+    fn digest_opens_in_recipient_language() {
+        let (subject, body) = assemble_digest(
+            &[
+                notification("plan_claimable", "Plan ready"),
+                notification("plan_claimable", "Plan due"),
+            ],
+            &intro("es", 2),
+            Tz::UTC,
+        );
+        assert_eq!(subject, "Tu resumen de InheritX: 2 notificaciones nuevas");
+        assert!(body.starts_with("Tienes 2 notificaciones nuevas en InheritX.\n"));
+    }
+}
@@ -0,0 +1,627 @@
+//! In-app notifications and their outbound delivery state.
+//!
+//! Every notification is visible in-app as soon as it is recorded. When the
+//! wallet has an email on file an `email` delivery is queued alongside it
+//! and worked off by the digest worker. A notification's `status` is
+//! `queued` while a delivery is pending, `sent` once every delivery went
+//! out (or none was needed), `failed` when a delivery ran out of attempts,
+//! and `read` once the wallet has read it.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::dead_letters::{resolve_for_job, Worker};
+use crate::pagination::{split_page, with_next_cursor, Cursor};
+use crate::templates::{render_for, TemplateKey};
+
+pub(crate) const NOTIFICATION_COLUMNS: &str =
+    "id, user_address, notification_type, title, message, metadata, is_read, status, created_at";
+
+const DELIVERY_COLUMNS: &str = "id, notification_id, channel, status, attempts, last_error, \
+     next_attempt_at, sent_at, created_at, updated_at";
+
+pub const NOTIFICATION_STATUSES: [&str; 4] = ["queued", "sent", "failed", "read"];
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_address: String,
+    pub notification_type: String,
+    pub title: String,
+    pub message: String,
+    pub metadata: serde_json::Value,
+    pub is_read: bool,
+    /// `queued`, `sent`, `failed` or `read`.
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct NotificationDelivery {
+    pub id: Uuid,
+    pub notification_id: Uuid,
+    /// Only `email` today.
+    pub channel: String,
+    /// `queued`, `sent` or `failed`.
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A delivery with the notification it belongs to, for the admin view.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DeliveryWithNotification {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub delivery: NotificationDelivery,
+    pub user_address: String,
+    pub notification_type: String,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationDetail {
+    #[serde(flatten)]
+    pub notification: Notification,
+    pub deliveries: Vec<NotificationDelivery>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationQuery {
+    pub unread_only: Option<bool>,
+    /// One of [`NOTIFICATION_STATUSES`].
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    /// `X-Next-Cursor` of the previous page.
+    pub cursor: Option<String>,
+}
+
+/// Most ids accepted by one bulk mark-read request.
+pub const MAX_BULK_IDS: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct MarkReadRequest {
+    pub ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarkReadResult {
+    /// Notifications that were unread and are now read.
+    pub updated: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadCount {
+    pub unread: i64,
+    /// Unread notifications per `notification_type`; types with none are
+    /// omitted.
+    pub by_type: std::collections::BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryQuery {
+    /// Defaults to `failed`.
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Identical notifications (same type and metadata) within this window are
+/// suppressed.
+pub const DUPLICATE_WINDOW_SECS: i64 = 60 * 60;
+
+/// Records an in-app notification for `user_address`, queueing an email
+/// delivery when the wallet has an email on file. If an identical one was
+/// recorded within [`DUPLICATE_WINDOW_SECS`] its id is returned instead.
+pub async fn create_notification<'e, E>(
+    executor: E,
+    user_address: &str,
+    notification_type: &str,
+    title: &str,
+    message: &str,
+    metadata: serde_json::Value,
+) -> Result<Uuid, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        r#"
+        WITH existing AS (
+            SELECT id
+            FROM notifications
+            WHERE user_address = $1
+              AND notification_type = $2
+              AND metadata = $5
+              AND created_at > NOW() - ($6 * INTERVAL '1 second')
+            ORDER BY created_at DESC
+            LIMIT 1
+        ),
+        email AS (
+            SELECT 1 FROM notification_preferences
+            WHERE user_address = $1 AND email IS NOT NULL
+        ),
+        inserted AS (
+            INSERT INTO notifications (user_address, notification_type, title, message, metadata, status)
+            SELECT $1, $2, $3, $4, $5,
+                   CASE WHEN EXISTS (SELECT 1 FROM email) THEN 'queued' ELSE 'sent' END
+            WHERE NOT EXISTS (SELECT 1 FROM existing)
+            RETURNING id
+        ),
+        queued AS (
+            INSERT INTO notification_deliveries (notification_id, channel)
+            SELECT id, 'email' FROM inserted
+            WHERE EXISTS (SELECT 1 FROM email)
+        )
+        SELECT id FROM inserted
+        UNION ALL
+        SELECT id FROM existing
+        "#,
+    )
+    .bind(user_address)
+    .bind(notification_type)
+    .bind(title)
+    .bind(message)
+    .bind(metadata)
+    .bind(DUPLICATE_WINDOW_SECS)
+    .fetch_one(executor)
+    .await
+}
+
+/// Records a notification whose title and message come from `key`,
+/// rendered in the recipient's preferred language.
+pub async fn create_localized_notification(
+    conn: &mut PgConnection,
+    user_address: &str,
+    notification_type: &str,
+    key: TemplateKey,
+    vars: &[(&str, &str)],
+    metadata: serde_json::Value,
+) -> Result<Uuid, sqlx::Error> {
+    let rendered = render_for(&mut *conn, user_address, key, vars).await?;
+    create_notification(
+        conn,
+        user_address,
+        notification_type,
+        &rendered.subject,
+        &rendered.body,
+        metadata,
+    )
+    .await
+}
+
+/// Recomputes `status` for the given notifications from their deliveries.
+pub(crate) async fn sync_statuses<'e, E>(executor: E, ids: &[Uuid]) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        UPDATE notifications n
+        SET status = CASE
+            WHEN n.is_read THEN 'read'
+            WHEN EXISTS (
+                SELECT 1 FROM notification_deliveries d
+                WHERE d.notification_id = n.id AND d.status = 'failed'
+            ) THEN 'failed'
+            WHEN EXISTS (
+                SELECT 1 FROM notification_deliveries d
+                WHERE d.notification_id = n.id AND d.status = 'queued'
+            ) THEN 'queued'
+            ELSE 'sent'
+        END
+        WHERE n.id = ANY($1)
+        "#,
+    )
+    .bind(ids)
+    .execute(executor)
+    .await
+    .map(|_| ())
+}
+
+/// Drops queued email deliveries for a wallet that no longer has an email.
+pub(crate) async fn cancel_queued_email(
+    conn: &mut sqlx::PgConnection,
+    user_address: &str,
+) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        DELETE FROM notification_deliveries d
+        USING notifications n
+        WHERE d.notification_id = n.id
+          AND n.user_address = $1
+          AND d.channel = 'email'
+          AND d.status = 'queued'
+        RETURNING d.notification_id
+        "#,
+    )
+    .bind(user_address)
+    .fetch_all(&mut *conn)
+    .await?;
+    sync_statuses(&mut *conn, &ids).await
+}
+
+/// Puts a `failed` delivery back in the queue with fresh attempts.
+pub(crate) async fn requeue_failed_delivery(
+    conn: &mut sqlx::PgConnection,
+    delivery_id: Uuid,
+) -> Result<Option<NotificationDelivery>, sqlx::Error> {
+    let delivery = sqlx::query_as::<_, NotificationDelivery>(&format!(
+        r#"
+        UPDATE notification_deliveries
+        SET status = 'queued', attempts = 0, next_attempt_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status = 'failed'
+        RETURNING {DELIVERY_COLUMNS}
+        "#
+    ))
+    .bind(delivery_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some(delivery) = &delivery {
+        sync_statuses(&mut *conn, &[delivery.notification_id]).await?;
+    }
+    Ok(delivery)
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+fn not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Notification not found" })),
+    )
+        .into_response()
+}
+
+// Handler: List Notifications
+pub async fn list_notifications(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Query(query): Query<NotificationQuery>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    if query
+        .status
+        .as_deref()
+        .is_some_and(|s| !NOTIFICATION_STATUSES.contains(&s))
+    {
+        return bad_request("status must be one of queued, sent, failed, read");
+    }
+    let cursor = match Cursor::parse(query.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(message) => return bad_request(message),
+    };
+
+    match sqlx::query_as::<_, Notification>(&format!(
+        r#"
+        SELECT {NOTIFICATION_COLUMNS}
+        FROM notifications
+        WHERE user_address = $1
+          AND ($2 = false OR is_read = false)
+          AND ($3::text IS NULL OR status = $3)
+          AND ($4::timestamptz IS NULL OR (created_at, id) < ($4, $5::uuid))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $6
+        "#
+    ))
+    .bind(&address)
+    .bind(query.unread_only.unwrap_or(false))
+    .bind(query.status.as_deref())
+    .bind(cursor.map(|c| c.created_at))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit + 1)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => {
+            let (rows, next) = split_page(rows, limit, |n| Cursor::new(n.created_at, n.id));
+            with_next_cursor((StatusCode::OK, Json(rows)).into_response(), next)
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to list notifications");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get Notification with its deliveries
+pub async fn get_notification(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(notification_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<NotificationDetail>, sqlx::Error> = async {
+        let Some(notification) = sqlx::query_as::<_, Notification>(&format!(
+            "SELECT {NOTIFICATION_COLUMNS} FROM notifications WHERE id = $1 AND user_address = $2"
+        ))
+        .bind(notification_id)
+        .bind(&address)
+        .fetch_optional(&state.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let deliveries = sqlx::query_as::<_, NotificationDelivery>(&format!(
+            "SELECT {DELIVERY_COLUMNS} FROM notification_deliveries WHERE notification_id = $1 ORDER BY channel"
+        ))
+        .bind(notification_id)
+        .fetch_all(&state.db_pool)
+        .await?;
+        Ok(Some(NotificationDetail {
+            notification,
+            deliveries,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Some(detail)) => (StatusCode::OK, Json(detail)).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(error = %e, "Failed to load notification");
+            database_error()
+        }
+    }
+}
+
+// Handler: Mark Notification Read
+pub async fn mark_notification_read(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(notification_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    // Pending deliveries are left alone; reading in-app does not cancel email.
+    match sqlx::query_as::<_, Notification>(&format!(
+        r#"
+        UPDATE notifications SET is_read = true, status = 'read'
+        WHERE id = $1 AND user_address = $2
+        RETURNING {NOTIFICATION_COLUMNS}
+        "#
+    ))
+    .bind(notification_id)
+    .bind(&address)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(notification)) => (StatusCode::OK, Json(notification)).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(error = %e, "Failed to mark notification read");
+            database_error()
+        }
+    }
+}
+
+/// Marks the wallet's unread notifications read, limited to `ids` when
+/// given. Ids that are not the wallet's or already read are skipped.
+async fn mark_read(
+    pool: &sqlx::PgPool,
+    address: &str,
+    ids: Option<&[Uuid]>,
+) -> Result<u64, sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE notifications SET is_read = true, status = 'read'
+        WHERE user_address = $1
+          AND is_read = false
+          AND ($2::uuid[] IS NULL OR id = ANY($2))
+        "#,
+    )
+    .bind(address)
+    .bind(ids)
+    .execute(pool)
+    .await
+    .map(|done| done.rows_affected())
+}
+
+// Handler: Mark All Notifications Read
+pub async fn mark_all_notifications_read(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match mark_read(&state.db_pool, &address, None).await {
+        Ok(updated) => (StatusCode::OK, Json(MarkReadResult { updated })).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to mark all notifications read");
+            database_error()
+        }
+    }
+}
+
+// Handler: Mark Notifications Read by id
+pub async fn mark_notifications_read(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(req): Json<MarkReadRequest>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if req.ids.is_empty() || req.ids.len() > MAX_BULK_IDS {
+        return bad_request("ids must list between 1 and 500 notifications");
+    }
+
+    match mark_read(&state.db_pool, &address, Some(&req.ids)).await {
+        Ok(updated) => (StatusCode::OK, Json(MarkReadResult { updated })).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to mark notifications read");
+            database_error()
+        }
+    }
+}
+
+// Handler: Unread Notification Count, for badge polling
+pub async fn unread_notification_count(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    // Served from notifications_unread_idx without touching the heap.
+    match sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT notification_type, COUNT(*)
+        FROM notifications
+        WHERE user_address = $1 AND is_read = false
+        GROUP BY notification_type
+        "#,
+    )
+    .bind(&address)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => {
+            let by_type: std::collections::BTreeMap<String, i64> = rows.into_iter().collect();
+            let count = UnreadCount {
+                unread: by_type.values().sum(),
+                by_type,
+            };
+            (
+                StatusCode::OK,
+                [(axum::http::header::CACHE_CONTROL, "no-store")],
+                Json(count),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to count unread notifications");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin List Notification Deliveries
+pub async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DeliveryQuery>,
+) -> impl IntoResponse {
+    let status = query.status.as_deref().unwrap_or("failed");
+    if !["queued", "sent", "failed"].contains(&status) {
+        return bad_request("status must be one of queued, sent, failed");
+    }
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    match sqlx::query_as::<_, DeliveryWithNotification>(
+        r#"
+        SELECT d.id, d.notification_id, d.channel, d.status, d.attempts, d.last_error,
+               d.next_attempt_at, d.sent_at, d.created_at, d.updated_at,
+               n.user_address, n.notification_type, n.title
+        FROM notification_deliveries d
+        JOIN notifications n ON n.id = d.notification_id
+        WHERE d.status = $1
+        ORDER BY d.updated_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(status)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (StatusCode::OK, Json(rows)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list notification deliveries");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Retry Notification Delivery
+pub async fn retry_delivery(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(delivery_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let result: Result<Option<NotificationDelivery>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(delivery) = requeue_failed_delivery(&mut tx, delivery_id).await? else {
+            return Ok(None);
+        };
+        resolve_for_job(
+            &mut *tx,
+            Worker::NotificationDigest,
+            delivery.id,
+            &admin.user_id,
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "notification_delivery.retry",
+            &delivery.id.to_string(),
+            serde_json::json!({
+                "notification_id": delivery.notification_id,
+                "channel": delivery.channel,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(delivery))
+    }
+    .await;
+
+    match result {
+        Ok(Some(delivery)) => (StatusCode::OK, Json(delivery)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Failed delivery not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(delivery_id = %delivery_id, error = %e, "Failed to retry notification delivery");
+            database_error()
+        }
+    }
+}
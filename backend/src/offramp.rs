@@ -0,0 +1,721 @@
+//! Fiat off-ramp of claim payouts through a Stellar anchor.
+//!
+//! A beneficiary turns a fiat payout into an anchor withdrawal using either
+//! SEP-24 (interactive: the anchor hosts a KYC/bank-details page) or SEP-31
+//! (direct payment: the platform sends on the beneficiary's behalf). Anchor
+//! progress is polled into the `withdrawals` table; once the anchor is ready
+//! to receive funds the platform sends the payout with the anchor's memo.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::chain::{TokenTransfer, TransferOutcome, TxService};
+use crate::http_client::{HttpClient, HttpError, HttpPolicy};
+use crate::jobs::JobRegistry;
+use crate::notifications::create_notification;
+
+const DEFAULT_ASSET_CODE: &str = "USDC";
+const DEFAULT_ASSET_DECIMALS: u32 = 7;
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 30;
+const DEFAULT_POLL_BATCH_SIZE: i64 = 100;
+const OFFRAMP_LOCK_KEY: i64 = 823;
+
+/// Anchor statuses after which a transfer no longer changes.
+const TERMINAL_STATUSES: &[&str] = &[
+    "completed",
+    "refunded",
+    "expired",
+    "error",
+    "no_market",
+    "too_small",
+    "too_large",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OfframpProtocol {
+    #[default]
+    Sep24,
+    Sep31,
+}
+
+impl OfframpProtocol {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sep24 => "sep24",
+            Self::Sep31 => "sep31",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sep24" => Some(Self::Sep24),
+            "sep31" => Some(Self::Sep31),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OfframpConfig {
+    /// `TRANSFER_SERVER_SEP0024` from the anchor's stellar.toml.
+    pub sep24_server: Option<String>,
+    /// `DIRECT_PAYMENT_SERVER` from the anchor's stellar.toml.
+    pub sep31_server: Option<String>,
+    /// SEP-10 JWT presented to the anchor.
+    pub auth_token: Option<String>,
+    pub asset_code: String,
+    /// Decimals of the on-chain token; payouts are stored in base units.
+    pub asset_decimals: u32,
+    pub poll_interval: Duration,
+    pub poll_batch_size: i64,
+}
+
+impl OfframpConfig {
+    pub fn from_env() -> Self {
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+
+        Self {
+            sep24_server: var("OFFRAMP_SEP24_SERVER").map(|v| v.trim_end_matches('/').to_string()),
+            sep31_server: var("OFFRAMP_SEP31_SERVER").map(|v| v.trim_end_matches('/').to_string()),
+            auth_token: var("OFFRAMP_ANCHOR_TOKEN"),
+            asset_code: var("OFFRAMP_ASSET_CODE").unwrap_or_else(|| DEFAULT_ASSET_CODE.to_string()),
+            asset_decimals: var("OFFRAMP_ASSET_DECIMALS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_ASSET_DECIMALS),
+            poll_interval: Duration::from_secs(
+                var("OFFRAMP_POLL_INTERVAL_SECS")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_POLL_INTERVAL_SECS)
+                    .max(1),
+            ),
+            poll_batch_size: DEFAULT_POLL_BATCH_SIZE,
+        }
+    }
+
+    fn server(&self, protocol: OfframpProtocol) -> Result<&str, OfframpError> {
+        match protocol {
+            OfframpProtocol::Sep24 => self.sep24_server.as_deref(),
+            OfframpProtocol::Sep31 => self.sep31_server.as_deref(),
+        }
+        .ok_or(OfframpError::NotConfigured(protocol.as_str()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum OfframpError {
+    #[error("{0} anchor server is not configured")]
+    NotConfigured(&'static str),
+    #[error("anchor request failed: {0}")]
+    Http(#[from] HttpError),
+    #[error("anchor returned {status}: {body}")]
+    Anchor { status: u16, body: String },
+}
+
+/// Anchor response to starting a transfer.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InitiatedTransfer {
+    pub id: String,
+    /// SEP-24 interactive page the beneficiary completes.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// SEP-31 receiving account and memo.
+    #[serde(default)]
+    pub stellar_account_id: Option<String>,
+    #[serde(default)]
+    pub stellar_memo: Option<String>,
+}
+
+/// Subset of the SEP-24/SEP-31 transaction object the backend tracks.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnchorTransaction {
+    pub id: String,
+    pub status: String,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub amount_out: Option<String>,
+    #[serde(default, alias = "stellar_account_id")]
+    pub withdraw_anchor_account: Option<String>,
+    #[serde(default, alias = "stellar_memo")]
+    pub withdraw_memo: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TransactionEnvelope {
+    transaction: AnchorTransaction,
+}
+
+pub struct AnchorClient {
+    http: HttpClient,
+    config: OfframpConfig,
+}
+
+impl AnchorClient {
+    pub fn new(config: OfframpConfig) -> Self {
+        let http = HttpClient::new(
+            "anchor",
+            HttpPolicy {
+                timeout: Duration::from_secs(15),
+                ..HttpPolicy::default()
+            },
+        );
+        Self { http, config }
+    }
+
+    pub fn config(&self) -> &OfframpConfig {
+        &self.config
+    }
+
+    /// Starts a withdrawal of `amount` (in asset units) for `account`.
+    pub async fn start_withdrawal(
+        &self,
+        protocol: OfframpProtocol,
+        account: &str,
+        amount: &str,
+    ) -> Result<InitiatedTransfer, OfframpError> {
+        let server = self.config.server(protocol)?;
+        let request = match protocol {
+            OfframpProtocol::Sep24 => self
+                .http
+                .post(format!("{server}/transactions/withdraw/interactive"))
+                .json(&serde_json::json!({
+                    "asset_code": self.config.asset_code,
+                    "account": account,
+                    "amount": amount,
+                })),
+            OfframpProtocol::Sep31 => {
+                self.http
+                    .post(format!("{server}/transactions"))
+                    .json(&serde_json::json!({
+                        "asset_code": self.config.asset_code,
+                        "amount": amount,
+                        "receiver_id": account,
+                    }))
+            }
+        };
+
+        self.send(request).await
+    }
+
+    pub async fn fetch_transaction(
+        &self,
+        protocol: OfframpProtocol,
+        id: &str,
+    ) -> Result<AnchorTransaction, OfframpError> {
+        let server = self.config.server(protocol)?;
+        let request = match protocol {
+            OfframpProtocol::Sep24 => self
+                .http
+                .get(format!("{server}/transaction"))
+                .query(&[("id", id)]),
+            OfframpProtocol::Sep31 => self.http.get(format!("{server}/transactions/{id}")),
+        };
+
+        let envelope: TransactionEnvelope = self.send(request).await?;
+        Ok(envelope.transaction)
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, OfframpError> {
+        let request = match &self.config.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+
+        let response = self.http.send(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(OfframpError::Anchor {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        Ok(response.json().await.map_err(HttpError::from)?)
+    }
+}
+
+pub fn is_terminal(status: &str) -> bool {
+    TERMINAL_STATUSES.contains(&status)
+}
+
+/// Payout status implied by a terminal anchor status.
+pub fn payout_status_for(anchor_status: &str) -> Option<&'static str> {
+    match anchor_status {
+        "completed" => Some("completed"),
+        status if is_terminal(status) => Some("failed"),
+        _ => None,
+    }
+}
+
+/// Converts a payout amount in token base units to the anchor's decimal
+/// asset amount.
+pub fn to_asset_amount(base_units: Decimal, decimals: u32) -> String {
+    let scale = Decimal::from(10u64.pow(decimals));
+    (base_units / scale).normalize().to_string()
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WithdrawalRow {
+    pub id: Uuid,
+    pub payout_id: Uuid,
+    pub beneficiary_address: String,
+    pub protocol: String,
+    pub asset_code: String,
+    pub amount: Decimal,
+    pub anchor_transaction_id: Option<String>,
+    pub interactive_url: Option<String>,
+    pub status: String,
+    pub status_message: Option<String>,
+    pub amount_out: Option<String>,
+    pub stellar_transaction_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+const WITHDRAWAL_COLUMNS: &str =
+    "id, payout_id, beneficiary_address, protocol, asset_code, amount, \
+     anchor_transaction_id, interactive_url, status, status_message, amount_out, \
+     stellar_transaction_id, created_at, updated_at, completed_at";
+
+#[derive(Debug, Deserialize)]
+pub struct StartWithdrawalRequest {
+    pub payout_id: Uuid,
+    #[serde(default)]
+    pub protocol: OfframpProtocol,
+}
+
+#[derive(sqlx::FromRow)]
+struct FiatPayout {
+    beneficiary_address: String,
+    amount: Decimal,
+    status: String,
+}
+
+// Handler: Start Off-ramp Withdrawal
+pub async fn start_withdrawal(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<StartWithdrawalRequest>,
+) -> impl IntoResponse {
+    let beneficiary = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let payout = match sqlx::query_as::<_, FiatPayout>(
+        r#"
+        SELECT beneficiary_address, amount, status::text AS status
+        FROM payouts
+        WHERE id = $1 AND payout_type = 'fiat'
+        "#,
+    )
+    .bind(payload.payout_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(payout)) if payout.beneficiary_address == beneficiary => payout,
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Fiat payout not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to load payout for off-ramp");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    if payout.status != "processing" {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": format!("Payout is {} and cannot be withdrawn", payout.status)
+            })),
+        )
+            .into_response();
+    }
+
+    let amount = to_asset_amount(payout.amount, state.offramp.config().asset_decimals);
+    let initiated = match state
+        .offramp
+        .start_withdrawal(payload.protocol, &beneficiary, &amount)
+        .await
+    {
+        Ok(initiated) => initiated,
+        Err(OfframpError::NotConfigured(protocol)) => {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({
+                    "error": format!("{protocol} off-ramp is not available")
+                })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            error!(payout_id = %payload.payout_id, error = %e, "Anchor rejected withdrawal");
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({ "error": "Anchor could not start the withdrawal" })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = sqlx::query_as::<_, WithdrawalRow>(&format!(
+        r#"
+        INSERT INTO withdrawals
+            (payout_id, beneficiary_address, protocol, asset_code, amount,
+             anchor_transaction_id, interactive_url, withdraw_anchor_account, withdraw_memo)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING {WITHDRAWAL_COLUMNS}
+        "#
+    ))
+    .bind(payload.payout_id)
+    .bind(&beneficiary)
+    .bind(payload.protocol.as_str())
+    .bind(&state.offramp.config().asset_code)
+    .bind(payout.amount)
+    .bind(&initiated.id)
+    .bind(&initiated.url)
+    .bind(&initiated.stellar_account_id)
+    .bind(&initiated.stellar_memo)
+    .fetch_one(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(row) => {
+            info!(withdrawal_id = %row.id, anchor_id = %initiated.id, "Off-ramp withdrawal started");
+            (StatusCode::CREATED, Json(row)).into_response()
+        }
+        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "A withdrawal is already in progress for this payout" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to record withdrawal");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to record withdrawal" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: List Off-ramp Withdrawals
+pub async fn list_withdrawals(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let beneficiary = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match sqlx::query_as::<_, WithdrawalRow>(&format!(
+        "SELECT {WITHDRAWAL_COLUMNS} FROM withdrawals WHERE beneficiary_address = $1 ORDER BY created_at DESC"
+    ))
+    .bind(&beneficiary)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => (StatusCode::OK, Json(rows)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list withdrawals");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OpenWithdrawal {
+    id: Uuid,
+    payout_id: Uuid,
+    beneficiary_address: String,
+    protocol: String,
+    amount: Decimal,
+    anchor_transaction_id: String,
+    status: String,
+    stellar_transaction_id: Option<String>,
+    token_address: String,
+    /// Correlation id of the payout being withdrawn.
+    correlation_id: Option<String>,
+}
+
+/// Polls open withdrawals, funds them when the anchor is ready and keeps
+/// payouts and notifications in step with anchor progress.
+pub struct OfframpStatusService {
+    db: PgPool,
+    client: Arc<AnchorClient>,
+    tx_service: Arc<dyn TxService>,
+}
+
+impl OfframpStatusService {
+    pub fn new(db: PgPool, client: Arc<AnchorClient>, tx_service: Arc<dyn TxService>) -> Self {
+        Self {
+            db,
+            client,
+            tx_service,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule(
+            "offramp_status",
+            self.client.config().poll_interval,
+            move || {
+                let worker = self.clone();
+                async move {
+                    worker.run_once().await.inspect(|&count| {
+                        if count > 0 {
+                            info!("Off-ramp poller recorded {count} status change(s)");
+                        }
+                    })
+                }
+            },
+        );
+    }
+
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(OFFRAMP_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Off-ramp poller lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let open = sqlx::query_as::<_, OpenWithdrawal>(
+            r#"
+            SELECT w.id, w.payout_id, w.beneficiary_address, w.protocol, w.amount,
+                   w.anchor_transaction_id, w.status, w.stellar_transaction_id, pl.token_address,
+                   p.correlation_id
+            FROM withdrawals w
+            JOIN payouts p ON p.id = w.payout_id
+            JOIN plans pl ON pl.id = p.plan_id
+            WHERE w.anchor_transaction_id IS NOT NULL
+              AND w.status NOT IN ('completed', 'refunded', 'expired', 'error', 'no_market', 'too_small', 'too_large')
+            ORDER BY w.updated_at ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(self.client.config().poll_batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut changed = 0;
+        for withdrawal in &open {
+            let Some(protocol) = OfframpProtocol::parse(&withdrawal.protocol) else {
+                continue;
+            };
+
+            let anchor_tx = match self
+                .client
+                .fetch_transaction(protocol, &withdrawal.anchor_transaction_id)
+                .await
+            {
+                Ok(anchor_tx) => anchor_tx,
+                Err(e) => {
+                    warn!(withdrawal_id = %withdrawal.id, error = %e, "Failed to poll anchor transaction");
+                    continue;
+                }
+            };
+
+            let stellar_tx = self.fund_if_ready(withdrawal, &anchor_tx).await;
+
+            if anchor_tx.status == withdrawal.status && stellar_tx.is_none() {
+                continue;
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE withdrawals
+                SET status = $2,
+                    status_message = $3,
+                    amount_out = COALESCE($4, amount_out),
+                    withdraw_anchor_account = COALESCE($5, withdraw_anchor_account),
+                    withdraw_memo = COALESCE($6, withdraw_memo),
+                    stellar_transaction_id = COALESCE($7, stellar_transaction_id),
+                    completed_at = CASE WHEN $8 THEN NOW() ELSE completed_at END,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(withdrawal.id)
+            .bind(&anchor_tx.status)
+            .bind(&anchor_tx.message)
+            .bind(&anchor_tx.amount_out)
+            .bind(&anchor_tx.withdraw_anchor_account)
+            .bind(&anchor_tx.withdraw_memo)
+            .bind(&stellar_tx)
+            .bind(is_terminal(&anchor_tx.status))
+            .execute(&mut *tx)
+            .await?;
+
+            if let Some(payout_status) = payout_status_for(&anchor_tx.status) {
+                sqlx::query(
+                    "UPDATE payouts SET status = $2::payout_status, updated_at = NOW() WHERE id = $1",
+                )
+                .bind(withdrawal.payout_id)
+                .bind(payout_status)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            if anchor_tx.status != withdrawal.status {
+                create_notification(
+                    &mut *tx,
+                    &withdrawal.beneficiary_address,
+                    "offramp_status",
+                    "Withdrawal update",
+                    &format!(
+                        "Your fiat withdrawal is now {}.",
+                        anchor_tx.status.replace('_', " ")
+                    ),
+                    serde_json::json!({
+                        "withdrawal_id": withdrawal.id,
+                        "payout_id": withdrawal.payout_id,
+                        "status": anchor_tx.status,
+                    }),
+                )
+                .await?;
+            }
+
+            changed += 1;
+        }
+
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    /// Sends the payout to the anchor once it is waiting for funds. Returns
+    /// the Stellar transaction hash when a transfer was made.
+    async fn fund_if_ready(
+        &self,
+        withdrawal: &OpenWithdrawal,
+        anchor_tx: &AnchorTransaction,
+    ) -> Option<String> {
+        let waiting = matches!(
+            anchor_tx.status.as_str(),
+            "pending_user_transfer_start" | "pending_sender"
+        );
+        if !waiting || withdrawal.stellar_transaction_id.is_some() {
+            return None;
+        }
+        let destination = anchor_tx.withdraw_anchor_account.clone()?;
+
+        let transfer = TokenTransfer {
+            reference: withdrawal.payout_id,
+            token: withdrawal.token_address.clone(),
+            destination,
+            amount: withdrawal.amount,
+            memo: anchor_tx.withdraw_memo.clone(),
+            correlation_id: withdrawal.correlation_id.clone(),
+            conversion: None,
+        };
+
+        match self
+            .tx_service
+            .submit_transfers(std::slice::from_ref(&transfer))
+            .await
+        {
+            Ok(receipt) if receipt.outcomes.first() == Some(&TransferOutcome::Succeeded) => {
+                info!(withdrawal_id = %withdrawal.id, tx_hash = %receipt.tx_hash, "Funded anchor withdrawal");
+                Some(receipt.tx_hash)
+            }
+            Ok(receipt) => {
+                warn!(withdrawal_id = %withdrawal.id, outcome = ?receipt.outcomes.first(), "Anchor funding transfer failed");
+                None
+            }
+            Err(e) => {
+                warn!(withdrawal_id = %withdrawal.id, error = %e, "Anchor funding transfer failed");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_base_units_to_asset_amount() {
+        assert_eq!(
+            to_asset_amount(Decimal::from(1_234_500_000u64), 7),
+            "123.45"
+        );
+        assert_eq!(to_asset_amount(Decimal::from(10_000_000u64), 7), "1");
+        assert_eq!(to_asset_amount(Decimal::from(5u64), 0), "5");
+    }
+
+    #[test]
+    fn maps_terminal_anchor_statuses_to_payouts() {
+        assert_eq!(payout_status_for("completed"), Some("completed"));
+        assert_eq!(payout_status_for("refunded"), Some("failed"));
+        assert_eq!(payout_status_for("too_small"), Some("failed"));
+        assert_eq!(payout_status_for("pending_anchor"), None);
+        assert!(!is_terminal("pending_user_transfer_start"));
+    }
+
+    #[test]
+    fn parses_sep24_and_sep31_transaction_shapes() {
+        let sep24: TransactionEnvelope = serde_json::from_value(serde_json::json!({
+            "transaction": {
+                "id": "82fhs729f63dh0v4",
+                "kind": "withdrawal",
+                "status": "pending_user_transfer_start",
+                "withdraw_anchor_account": "GANCHOR",
+                "withdraw_memo": "186384",
+                "withdraw_memo_type": "id"
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            sep24.transaction.withdraw_anchor_account.as_deref(),
+            Some("GANCHOR")
+        );
+
+        let sep31: TransactionEnvelope = serde_json::from_value(serde_json::json!({
+            "transaction": {
+                "id": "82fhs729f63dh0v4",
+                "status": "pending_sender",
+                "stellar_account_id": "GANCHOR",
+                "stellar_memo": "abc"
+            }
+        }))
+        .unwrap();
+        assert_eq!(sep31.transaction.withdraw_memo.as_deref(), Some("abc"));
+    }
+}
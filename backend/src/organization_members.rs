@@ -0,0 +1,786 @@
+//! Organization admins: invitations, roles and ownership.
+//!
+//! An organization has one `owner`, who manages its admins, and any number
+//! of `manager`s, who work on the organization's plans and users and edit
+//! its branding, and `agent`s, who can only read them. The owner invites
+//! new admins by email. The invitation link carries a one-time token, and
+//! accepting it adds the invitee under their email address and returns an
+//! admin JWT with the [`ORGANIZATION_MEMBER_ROLE`] role. Platform admins can
+//! do everything an owner can. Every change is audited, and admins whose id
+//! is an email address are emailed about changes to their membership.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::{issue_token, UserContext, ORGANIZATION_MEMBER_ROLE};
+use crate::mailer::is_plausible_email;
+use crate::organizations::{
+    add_admin, list_admins, load_organization, Organization, OrganizationAdmin, OrganizationScope,
+    ORGANIZATION_ROLES,
+};
+
+pub const INVITABLE_ROLES: [&str; 2] = ["manager", "agent"];
+const INVITATION_TTL_DAYS: i64 = 7;
+/// Lifetime of the token returned on acceptance, as for `create-admin`.
+const MEMBER_TOKEN_TTL_HOURS: u64 = 24;
+const INVITATION_COLUMNS: &str = "id, organization_id, email, role, invited_by, created_at, \
+     expires_at, accepted_at, revoked_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct OrganizationInvitation {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub invited_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAdminRequest {
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InviteAdminRequest {
+    pub email: String,
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AcceptedInvitation {
+    pub organization_id: Uuid,
+    pub admin_id: String,
+    pub role: String,
+    /// Admin JWT for the new member.
+    pub token: String,
+    pub token_expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferOwnershipRequest {
+    pub admin_id: String,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn owner_only() -> Response {
+    refused(
+        StatusCode::FORBIDDEN,
+        "Only the organization's owner can manage its admins",
+    )
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn invitation_link(base_url: &str, token: &str) -> String {
+    let separator = if base_url.contains('?') { '&' } else { '?' };
+    format!("{base_url}{separator}token={token}")
+}
+
+/// Platform admins and the organization's owner manage its admins.
+fn manages_admins(scope: Option<&Extension<OrganizationScope>>) -> bool {
+    scope.is_none_or(|Extension(scope)| scope.role == "owner")
+}
+
+/// The organization, its previous owner and its admins after a transfer.
+type Transferred = (Organization, Option<String>, Vec<OrganizationAdmin>);
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+/// Emails an admin whose id is an email address. Failures are only logged;
+/// the change has already been made.
+async fn notify_admin(state: &AppState, admin_id: &str, subject: &str, body: &str) {
+    if !is_plausible_email(admin_id) {
+        return;
+    }
+    if let Err(e) = state.mailer.send(admin_id, subject, body).await {
+        warn!(error = %e, "Failed to email organization admin");
+    }
+}
+
+async fn current_role(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    admin_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT role FROM organization_admins
+        WHERE organization_id = $1 AND admin_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(organization_id)
+    .bind(admin_id)
+    .fetch_optional(conn)
+    .await
+}
+
+// Handler: Set Organization Admin
+pub async fn set_organization_admin(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Path((id, admin_id)): Path<(Uuid, String)>,
+    Json(payload): Json<SetAdminRequest>,
+) -> impl IntoResponse {
+    if !manages_admins(scope.as_ref()) {
+        return owner_only();
+    }
+    let role = payload.role.trim();
+    if !ORGANIZATION_ROLES.contains(&role) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "role must be owner, manager or agent",
+        );
+    }
+    let admin_id = admin_id.trim().to_string();
+
+    let result: Result<Outcome<(Organization, Vec<OrganizationAdmin>)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(organization) = load_organization(&mut tx, id).await? else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Organization not found",
+            ));
+        };
+        let current = current_role(&mut tx, id, &admin_id).await?;
+        if current.as_deref() == Some("owner") {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "The owner's role only changes through an ownership transfer",
+            ));
+        }
+        if scope.is_some() {
+            if current.is_none() {
+                return Ok(Outcome::Refused(
+                    StatusCode::NOT_FOUND,
+                    "New admins are invited by email",
+                ));
+            }
+            if role == "owner" {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "Transfer ownership to make another admin the owner",
+                ));
+            }
+        }
+        match add_admin(&mut tx, id, &admin_id, role, &admin.user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "The admin already administers another organization",
+                ));
+            }
+            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "The organization already has an owner",
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "organization.admin_set",
+            &id.to_string(),
+            serde_json::json!({ "admin_id": admin_id, "role": role, "previous_role": current }),
+        )
+        .await?;
+        let admins = list_admins(&mut tx, id).await?;
+        tx.commit().await?;
+        Ok(Outcome::Done((organization, admins)))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done((organization, admins))) => {
+            notify_admin(
+                &state,
+                &admin_id,
+                &format!("Your role at {}", organization.name),
+                &format!(
+                    "You are now {} of {} on InheritX.",
+                    with_article(role),
+                    organization.name
+                ),
+            )
+            .await;
+            Json(admins).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to set organization admin");
+            database_error()
+        }
+    }
+}
+
+fn with_article(role: &str) -> String {
+    match role {
+        "owner" => "the owner".to_string(),
+        "agent" => "an agent".to_string(),
+        other => format!("a {other}"),
+    }
+}
+
+// Handler: Remove Organization Admin
+pub async fn remove_organization_admin(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Path((id, admin_id)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    if !manages_admins(scope.as_ref()) {
+        return owner_only();
+    }
+    let admin_id = admin_id.trim().to_string();
+
+    let result: Result<Outcome<Organization>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(organization) = load_organization(&mut tx, id).await? else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Organization not found",
+            ));
+        };
+        match current_role(&mut tx, id, &admin_id).await?.as_deref() {
+            None => {
+                return Ok(Outcome::Refused(
+                    StatusCode::NOT_FOUND,
+                    "The admin does not administer this organization",
+                ));
+            }
+            Some("owner") => {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "Transfer ownership before removing the owner",
+                ));
+            }
+            Some(_) => {}
+        }
+        sqlx::query("DELETE FROM organization_admins WHERE organization_id = $1 AND admin_id = $2")
+            .bind(id)
+            .bind(&admin_id)
+            .execute(&mut *tx)
+            .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "organization.admin_removed",
+            &id.to_string(),
+            serde_json::json!({ "admin_id": admin_id }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(organization))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(organization)) => {
+            notify_admin(
+                &state,
+                &admin_id,
+                &format!("You were removed from {}", organization.name),
+                &format!(
+                    "You no longer administer {} on InheritX. Contact its owner if this is \
+                     unexpected.",
+                    organization.name
+                ),
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to remove organization admin");
+            database_error()
+        }
+    }
+}
+
+// Handler: Transfer Organization Ownership
+pub async fn transfer_ownership(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransferOwnershipRequest>,
+) -> impl IntoResponse {
+    if !manages_admins(scope.as_ref()) {
+        return owner_only();
+    }
+    let new_owner = payload.admin_id.trim().to_string();
+
+    let result: Result<Outcome<Transferred>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(organization) = load_organization(&mut tx, id).await? else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Organization not found",
+            ));
+        };
+        match current_role(&mut tx, id, &new_owner).await?.as_deref() {
+            None => {
+                return Ok(Outcome::Refused(
+                    StatusCode::NOT_FOUND,
+                    "Ownership can only pass to an admin of the organization",
+                ));
+            }
+            Some("owner") => {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "The admin already owns the organization",
+                ));
+            }
+            Some(_) => {}
+        }
+        // The previous owner stays on as a manager.
+        let previous_owner: Option<String> = sqlx::query_scalar(
+            r#"
+            UPDATE organization_admins SET role = 'manager'
+            WHERE organization_id = $1 AND role = 'owner'
+            RETURNING admin_id
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE organization_admins SET role = 'owner' WHERE organization_id = $1 AND admin_id = $2",
+        )
+        .bind(id)
+        .bind(&new_owner)
+        .execute(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "organization.ownership_transferred",
+            &id.to_string(),
+            serde_json::json!({ "from": previous_owner, "to": new_owner }),
+        )
+        .await?;
+        let admins = list_admins(&mut tx, id).await?;
+        tx.commit().await?;
+        Ok(Outcome::Done((organization, previous_owner, admins)))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done((organization, previous_owner, admins))) => {
+            let subject = format!("{} has a new owner", organization.name);
+            notify_admin(
+                &state,
+                &new_owner,
+                &subject,
+                &format!(
+                    "You are now the owner of {} on InheritX and manage its admins.",
+                    organization.name
+                ),
+            )
+            .await;
+            if let Some(previous_owner) = previous_owner {
+                notify_admin(
+                    &state,
+                    &previous_owner,
+                    &subject,
+                    &format!(
+                        "Ownership of {} passed to {new_owner}. You remain a manager.",
+                        organization.name
+                    ),
+                )
+                .await;
+            }
+            Json(admins).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to transfer organization ownership");
+            database_error()
+        }
+    }
+}
+
+// Handler: Invite Organization Admin
+pub async fn invite_admin(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<InviteAdminRequest>,
+) -> impl IntoResponse {
+    if !manages_admins(scope.as_ref()) {
+        return owner_only();
+    }
+    let email = payload.email.trim().to_lowercase();
+    if !is_plausible_email(&email) {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "email is not a valid email address",
+        );
+    }
+    let role = payload.role.trim();
+    if !INVITABLE_ROLES.contains(&role) {
+        return refused(StatusCode::BAD_REQUEST, "role must be manager or agent");
+    }
+    let token = generate_token();
+
+    let result: Result<Outcome<(Organization, OrganizationInvitation)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(organization) = load_organization(&mut tx, id).await? else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Organization not found",
+            ));
+        };
+        let member: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM organization_admins WHERE lower(admin_id) = $1)",
+        )
+        .bind(&email)
+        .fetch_one(&mut *tx)
+        .await?;
+        if member {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "This email already administers an organization",
+            ));
+        }
+        // A new invitation replaces the pending one.
+        sqlx::query(
+            r#"
+            UPDATE organization_invitations SET revoked_at = NOW()
+            WHERE organization_id = $1 AND email = $2
+              AND accepted_at IS NULL AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(&email)
+        .execute(&mut *tx)
+        .await?;
+        let invitation = sqlx::query_as::<_, OrganizationInvitation>(&format!(
+            r#"
+            INSERT INTO organization_invitations
+                (organization_id, email, role, token_hash, invited_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {INVITATION_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(&email)
+        .bind(role)
+        .bind(hash_token(&token))
+        .bind(&admin.user_id)
+        .bind(Utc::now() + Duration::days(INVITATION_TTL_DAYS))
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "organization.admin_invited",
+            &id.to_string(),
+            serde_json::json!({ "invitation_id": invitation.id, "email": email, "role": role }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done((organization, invitation)))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done((organization, invitation))) => {
+            let body = format!(
+                "You have been invited to join {} on InheritX as {}. To accept, open this link \
+                 within {INVITATION_TTL_DAYS} days:\n\n{}\n\nIf you were not expecting this, \
+                 ignore this email.",
+                organization.name,
+                with_article(role),
+                invitation_link(&state.config.organization_invite_url, &token)
+            );
+            let subject = format!("Join {} on InheritX", organization.name);
+            if let Err(e) = state.mailer.send(&email, &subject, &body).await {
+                warn!(error = %e, invitation_id = %invitation.id, "Failed to send organization invitation");
+            }
+            (StatusCode::CREATED, Json(invitation)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to invite organization admin");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Organization Invitations
+pub async fn list_invitations(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match sqlx::query_as::<_, OrganizationInvitation>(&format!(
+        r#"
+        SELECT {INVITATION_COLUMNS}
+        FROM organization_invitations
+        WHERE organization_id = $1 AND accepted_at IS NULL AND revoked_at IS NULL
+        ORDER BY created_at DESC
+        "#
+    ))
+    .bind(id)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(invitations) => Json(invitations).into_response(),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to list organization invitations");
+            database_error()
+        }
+    }
+}
+
+// Handler: Revoke Organization Invitation
+pub async fn revoke_invitation(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Path((id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if !manages_admins(scope.as_ref()) {
+        return owner_only();
+    }
+
+    let result: Result<bool, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let revoked = sqlx::query(
+            r#"
+            UPDATE organization_invitations SET revoked_at = NOW()
+            WHERE id = $1 AND organization_id = $2
+              AND accepted_at IS NULL AND revoked_at IS NULL
+            "#,
+        )
+        .bind(invitation_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            == 1;
+        if revoked {
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "organization.invitation_revoked",
+                &id.to_string(),
+                serde_json::json!({ "invitation_id": invitation_id }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(revoked)
+    }
+    .await;
+
+    match result {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => refused(StatusCode::NOT_FOUND, "No pending invitation with this id"),
+        Err(e) => {
+            error!(error = %e, invitation_id = %invitation_id, "Failed to revoke organization invitation");
+            database_error()
+        }
+    }
+}
+
+// Handler: Accept Organization Invitation
+pub async fn accept_invitation(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<AcceptInvitationRequest>,
+) -> impl IntoResponse {
+    let token_hash = hash_token(payload.token.trim());
+
+    let result: Result<Outcome<(Organization, OrganizationInvitation)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(invitation) = sqlx::query_as::<_, OrganizationInvitation>(&format!(
+            "SELECT {INVITATION_COLUMNS} FROM organization_invitations WHERE token_hash = $1 FOR UPDATE"
+        ))
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Invitation not found",
+            ));
+        };
+        if invitation.accepted_at.is_some() || invitation.revoked_at.is_some() {
+            return Ok(Outcome::Refused(
+                StatusCode::GONE,
+                "This invitation is no longer valid",
+            ));
+        }
+        if invitation.expires_at <= Utc::now() {
+            return Ok(Outcome::Refused(
+                StatusCode::GONE,
+                "This invitation has expired",
+            ));
+        }
+        let Some(organization) = load_organization(&mut tx, invitation.organization_id).await?
+        else {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Organization not found",
+            ));
+        };
+        let added = sqlx::query(
+            r#"
+            INSERT INTO organization_admins (admin_id, organization_id, role, added_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (admin_id) DO NOTHING
+            "#,
+        )
+        .bind(&invitation.email)
+        .bind(invitation.organization_id)
+        .bind(&invitation.role)
+        .bind(&invitation.invited_by)
+        .execute(&mut *tx)
+        .await?;
+        if added.rows_affected() == 0 {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "This email already administers an organization",
+            ));
+        }
+        sqlx::query("UPDATE organization_invitations SET accepted_at = NOW() WHERE id = $1")
+            .bind(invitation.id)
+            .execute(&mut *tx)
+            .await?;
+        record_audit(
+            &mut *tx,
+            &invitation.email,
+            "organization.invitation_accepted",
+            &invitation.organization_id.to_string(),
+            serde_json::json!({ "invitation_id": invitation.id, "role": invitation.role }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done((organization, invitation)))
+    }
+    .await;
+
+    let (organization, invitation) = match result {
+        Ok(Outcome::Done(accepted)) => accepted,
+        Ok(Outcome::Refused(status, message)) => return refused(status, message),
+        Err(e) => {
+            error!(error = %e, "Failed to accept organization invitation");
+            return database_error();
+        }
+    };
+
+    let ttl = std::time::Duration::from_secs(MEMBER_TOKEN_TTL_HOURS * 3600);
+    let token = match issue_token(
+        &state.config.jwt_secret,
+        &invitation.email,
+        ORGANIZATION_MEMBER_ROLE,
+        ttl,
+    ) {
+        Ok(token) => token,
+        Err(e) => {
+            error!(error = %e, "Failed to issue organization member token");
+            return refused(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to issue a token; the invitation was accepted",
+            );
+        }
+    };
+    notify_admin(
+        &state,
+        &invitation.invited_by,
+        &format!("{} joined {}", invitation.email, organization.name),
+        &format!(
+            "{} accepted your invitation and is now {} of {}.",
+            invitation.email,
+            with_article(&invitation.role),
+            organization.name
+        ),
+    )
+    .await;
+
+    Json(AcceptedInvitation {
+        organization_id: invitation.organization_id,
+        admin_id: invitation.email,
+        role: invitation.role,
+        token,
+        token_expires_at: Utc::now() + Duration::hours(MEMBER_TOKEN_TTL_HOURS as i64),
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_owners_and_platform_admins_manage_admins() {
+        let scope = |role: &str| {
+            Extension(OrganizationScope {
+                organization_id: Uuid::new_v4(),
+                role: role.to_string(),
+            })
+        };
+        assert!(manages_admins(None));
+        assert!(manages_admins(Some(&scope("owner"))));
+        assert!(!manages_admins(Some(&scope("manager"))));
+        assert!(!manages_admins(Some(&scope("agent"))));
+    }
+
+    #[test]
+    fn invitation_links_append_the_token() {
+        assert_eq!(
+            invitation_link("https://firm.example/join", "abc"),
+            "https://firm.example/join?token=abc"
+        );
+        assert_eq!(
+            invitation_link("https://firm.example/join?lang=es", "abc"),
+            "https://firm.example/join?lang=es&token=abc"
+        );
+    }
+}
@@ -0,0 +1,890 @@
+//! White-label organizations.
+//!
+//! Estate-planning firms run InheritX under their own brand as an
+//! organization. A user belongs to at most one organization, and plans take
+//! their owner's organization when created; users and plans without one
+//! belong to the platform. Each organization carries the branding its front
+//! end loads from `GET /api/organizations/:slug`. A payout fee override for
+//! its plans is a platform setting (`organization_fees`), so it takes two
+//! platform admins like any other fee change; see [`crate::pending_changes`].
+//!
+//! Admins listed in `organization_admins` are limited to their organization.
+//! [`organization_scope_middleware`] lets them reach the per-plan and
+//! per-user admin routes for their organization's records and their
+//! organization's own settings; every other admin route stays with platform
+//! admins. Agents only get read access. Invitations and roles are managed
+//! in [`crate::organization_members`].
+
+use axum::{
+    extract::{MatchedPath, Path, RawPathParams, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::types::Json as SqlJson;
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::{UserContext, ORGANIZATION_MEMBER_ROLE};
+use crate::config::Config;
+use crate::mailer::is_plausible_email;
+use crate::platform_settings::{self, FeeSchedule};
+
+pub const ORGANIZATION_ROLES: [&str; 3] = ["owner", "manager", "agent"];
+const MAX_NAME_LEN: usize = 100;
+// The fee override is read from the approved `organization_fees` setting.
+const ORGANIZATION_COLUMNS: &str = "id, slug, name, branding, \
+     (SELECT (value ->> id::text)::int FROM platform_settings WHERE key = 'organization_fees') \
+     AS payout_fee_bps, created_by, created_at, updated_at";
+
+/// How an organization presents itself to its users.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Branding {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// https URL of the logo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_url: Option<String>,
+    /// `#rrggbb`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub support_email: Option<String>,
+}
+
+impl Branding {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if let Some(name) = &self.display_name {
+            if name.trim().is_empty() || name.chars().count() > MAX_NAME_LEN {
+                return Err("display_name must be 1 to 100 characters");
+            }
+        }
+        if let Some(url) = &self.logo_url {
+            if !matches!(reqwest::Url::parse(url), Ok(parsed) if parsed.scheme() == "https") {
+                return Err("logo_url must be an https URL");
+            }
+        }
+        if let Some(color) = &self.primary_color {
+            let hex = color.strip_prefix('#').unwrap_or_default();
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err("primary_color must be a #rrggbb colour");
+            }
+        }
+        if let Some(email) = &self.support_email {
+            if !is_plausible_email(email) {
+                return Err("support_email is not a valid email address");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Organization {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub branding: SqlJson<Branding>,
+    /// Approved payout fee for the organization's plans; the platform fee
+    /// applies when unset.
+    pub payout_fee_bps: Option<i32>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct OrganizationAdmin {
+    pub admin_id: String,
+    pub role: String,
+    pub added_by: String,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrganizationDetail {
+    #[serde(flatten)]
+    pub organization: Organization,
+    pub admins: Vec<OrganizationAdmin>,
+}
+
+/// What white-label front ends load before sign-in.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PublicOrganization {
+    pub slug: String,
+    pub name: String,
+    pub branding: SqlJson<Branding>,
+}
+
+/// The organization an admin is limited to. Set as a request extension by
+/// [`organization_scope_middleware`]; absent for platform admins.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct OrganizationScope {
+    pub organization_id: Uuid,
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateOrganizationRequest {
+    pub slug: String,
+    pub name: String,
+    #[serde(default)]
+    pub branding: Branding,
+    /// Refused: fee overrides go through `organization_fees` review.
+    #[serde(default, deserialize_with = "present")]
+    pub payout_fee_bps: Option<serde_json::Value>,
+    /// Admin JWT subject to make the organization's owner.
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateOrganizationRequest {
+    pub name: Option<String>,
+    /// Replaces the branding as a whole.
+    pub branding: Option<Branding>,
+    /// Refused: fee overrides go through `organization_fees` review.
+    #[serde(default, deserialize_with = "present")]
+    pub payout_fee_bps: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserAssignment {
+    pub user_id: Uuid,
+    pub wallet_address: String,
+    pub organization_id: Option<Uuid>,
+    /// Plans owned by the user that moved with them.
+    pub plans_updated: u64,
+}
+
+/// Distinguishes an explicit `null` from an absent field.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Which records an admin route touches, for scoping organization admins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouteScope {
+    Plan,
+    User,
+    Organization,
+    Platform,
+}
+
+fn route_scope(route: &str) -> RouteScope {
+    if route.starts_with("/api/admin/plans/{id}") {
+        RouteScope::Plan
+    } else if route.starts_with("/api/admin/users/{id}") {
+        RouteScope::User
+    } else if route == "/api/admin/organizations"
+        || route.starts_with("/api/admin/organizations/{id}")
+    {
+        RouteScope::Organization
+    } else {
+        RouteScope::Platform
+    }
+}
+
+fn validate_slug(slug: &str) -> Result<(), &'static str> {
+    let valid = (2..=63).contains(&slug.len())
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-')
+        && !slug.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err("slug must be 2 to 63 lower-case letters, digits or hyphens")
+    }
+}
+
+fn clean_name(name: &str) -> Result<String, &'static str> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err("name must be 1 to 100 characters");
+    }
+    Ok(name.to_string())
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn platform_only() -> Response {
+    refused(
+        StatusCode::FORBIDDEN,
+        "Only platform administrators can do this",
+    )
+}
+
+/// Fee overrides need a second admin's approval, so they cannot be written
+/// with the organization.
+fn fee_needs_review() -> Response {
+    refused(
+        StatusCode::BAD_REQUEST,
+        "payout_fee_bps is changed by proposing an organization_fees change to POST /api/admin/pending-changes",
+    )
+}
+
+fn not_found() -> Response {
+    refused(StatusCode::NOT_FOUND, "Organization not found")
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+/// The organization `admin_id` is limited to, if any.
+pub async fn admin_scope<'e, E>(
+    executor: E,
+    admin_id: &str,
+) -> Result<Option<OrganizationScope>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, OrganizationScope>(
+        "SELECT organization_id, role FROM organization_admins WHERE admin_id = $1",
+    )
+    .bind(admin_id)
+    .fetch_optional(executor)
+    .await
+}
+
+async fn plan_in_organization(
+    pool: &PgPool,
+    plan: &str,
+    organization_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let Ok(plan_id) = Uuid::parse_str(plan) else {
+        return Ok(false);
+    };
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM plans WHERE id = $1 AND organization_id = $2)")
+        .bind(plan_id)
+        .bind(organization_id)
+        .fetch_one(pool)
+        .await
+}
+
+async fn user_in_organization(
+    pool: &PgPool,
+    user: &str,
+    organization_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let user = user.trim();
+    let query = match Uuid::parse_str(user) {
+        Ok(id) => sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM users WHERE id = $1 AND organization_id = $2)",
+        )
+        .bind(id),
+        Err(_) => sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM users WHERE wallet_address = $1 AND organization_id = $2)",
+        )
+        .bind(user),
+    };
+    query.bind(organization_id).fetch_one(pool).await
+}
+
+/// Limits organization admins to their own organization's plans, users and
+/// settings. Records outside the organization are reported as not found.
+pub async fn organization_scope_middleware(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    route: MatchedPath,
+    params: RawPathParams,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let scope = match admin_scope(&state.db_pool, &admin.user_id).await {
+        Ok(Some(scope)) => scope,
+        // Member tokens outlive a removal from the organization.
+        Ok(None) if admin.role == ORGANIZATION_MEMBER_ROLE => {
+            return refused(
+                StatusCode::FORBIDDEN,
+                "You are no longer a member of an organization",
+            );
+        }
+        Ok(None) => return next.run(req).await,
+        Err(e) => {
+            error!(error = %e, admin = %admin.user_id, "Failed to load admin organization");
+            return database_error();
+        }
+    };
+    if scope.role == "agent" && !req.method().is_safe() {
+        return refused(StatusCode::FORBIDDEN, "Agents have read-only access");
+    }
+    let id = params
+        .iter()
+        .find(|(name, _)| *name == "id")
+        .map(|(_, value)| value.to_string());
+
+    let allowed = match (route_scope(route.as_str()), id) {
+        (RouteScope::Platform, _) => {
+            return refused(
+                StatusCode::FORBIDDEN,
+                "Organization admins cannot use this route",
+            );
+        }
+        (RouteScope::Organization, None) => Ok(true),
+        (RouteScope::Organization, Some(id)) => Ok(id == scope.organization_id.to_string()),
+        (RouteScope::Plan, Some(id)) => {
+            plan_in_organization(&state.db_pool, &id, scope.organization_id).await
+        }
+        (RouteScope::User, Some(id)) => {
+            user_in_organization(&state.db_pool, &id, scope.organization_id).await
+        }
+        (RouteScope::Plan | RouteScope::User, None) => Ok(false),
+    };
+    match allowed {
+        Ok(true) => {
+            req.extensions_mut().insert(scope);
+            next.run(req).await
+        }
+        Ok(false) => refused(StatusCode::NOT_FOUND, "Not found"),
+        Err(e) => {
+            error!(error = %e, route = %route.as_str(), "Failed to check organization scope");
+            database_error()
+        }
+    }
+}
+
+/// Fee schedule for a plan: its organization's override, or the platform
+/// schedule.
+pub async fn plan_fee_schedule(
+    pool: &PgPool,
+    config: &Config,
+    plan_id: Uuid,
+) -> Result<FeeSchedule, sqlx::Error> {
+    let organization_id: Option<Option<Uuid>> =
+        sqlx::query_scalar("SELECT organization_id FROM plans WHERE id = $1")
+            .bind(plan_id)
+            .fetch_optional(pool)
+            .await?;
+    with_override(pool, config, organization_id.flatten()).await
+}
+
+/// Fee schedule for plans created by `owner`.
+pub async fn owner_fee_schedule(
+    pool: &PgPool,
+    config: &Config,
+    owner: &str,
+) -> Result<FeeSchedule, sqlx::Error> {
+    let organization_id: Option<Option<Uuid>> =
+        sqlx::query_scalar("SELECT organization_id FROM users WHERE wallet_address = $1")
+            .bind(owner.trim())
+            .fetch_optional(pool)
+            .await?;
+    with_override(pool, config, organization_id.flatten()).await
+}
+
+async fn with_override(
+    pool: &PgPool,
+    config: &Config,
+    organization_id: Option<Uuid>,
+) -> Result<FeeSchedule, sqlx::Error> {
+    if let Some(id) = organization_id {
+        if let Some(bps) = platform_settings::organization_fees(pool).await?.get(&id) {
+            return Ok(FeeSchedule {
+                payout_fee_bps: *bps,
+            });
+        }
+    }
+    platform_settings::fee_schedule(pool, config).await
+}
+
+pub(crate) async fn load_organization(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<Organization>, sqlx::Error> {
+    sqlx::query_as::<_, Organization>(&format!(
+        "SELECT {ORGANIZATION_COLUMNS} FROM organizations WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Adds `admin_id` to the organization or changes their role. Returns
+/// false when they already administer another organization.
+pub(crate) async fn add_admin(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    admin_id: &str,
+    role: &str,
+    added_by: &str,
+) -> Result<bool, sqlx::Error> {
+    let added = sqlx::query(
+        r#"
+        INSERT INTO organization_admins (admin_id, organization_id, role, added_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (admin_id) DO UPDATE
+        SET role = EXCLUDED.role, added_by = EXCLUDED.added_by, added_at = NOW()
+        WHERE organization_admins.organization_id = EXCLUDED.organization_id
+        "#,
+    )
+    .bind(admin_id)
+    .bind(organization_id)
+    .bind(role)
+    .bind(added_by)
+    .execute(conn)
+    .await?;
+    Ok(added.rows_affected() == 1)
+}
+
+// Handler: Create Organization
+pub async fn create_organization(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> impl IntoResponse {
+    if scope.is_some() {
+        return platform_only();
+    }
+    if payload.payout_fee_bps.is_some() {
+        return fee_needs_review();
+    }
+    let slug = payload.slug.trim().to_string();
+    let checked = validate_slug(&slug)
+        .and_then(|_| payload.branding.validate())
+        .and_then(|_| clean_name(&payload.name));
+    let name = match checked {
+        Ok(name) => name,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    let owner = payload
+        .owner
+        .as_deref()
+        .map(str::trim)
+        .filter(|owner| !owner.is_empty());
+
+    let result: Result<Outcome<OrganizationDetail>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let inserted = sqlx::query_as::<_, Organization>(&format!(
+            r#"
+            INSERT INTO organizations (slug, name, branding, created_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (slug) DO NOTHING
+            RETURNING {ORGANIZATION_COLUMNS}
+            "#
+        ))
+        .bind(&slug)
+        .bind(&name)
+        .bind(SqlJson(&payload.branding))
+        .bind(&admin.user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(organization) = inserted else {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "An organization with this slug already exists",
+            ));
+        };
+
+        if let Some(owner) = owner {
+            if !add_admin(&mut tx, organization.id, owner, "owner", &admin.user_id).await? {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "The owner already administers another organization",
+                ));
+            }
+        }
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "organization.created",
+            &organization.id.to_string(),
+            serde_json::json!({
+                "slug": organization.slug,
+                "owner": owner,
+            }),
+        )
+        .await?;
+        let admins = list_admins(&mut tx, organization.id).await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(OrganizationDetail {
+            organization,
+            admins,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(detail)) => (StatusCode::CREATED, Json(detail)).into_response(),
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(error = %e, slug = %slug, "Failed to create organization");
+            database_error()
+        }
+    }
+}
+
+pub(crate) async fn list_admins(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+) -> Result<Vec<OrganizationAdmin>, sqlx::Error> {
+    sqlx::query_as::<_, OrganizationAdmin>(
+        r#"
+        SELECT admin_id, role, added_by, added_at
+        FROM organization_admins
+        WHERE organization_id = $1
+        ORDER BY role DESC, admin_id
+        "#,
+    )
+    .bind(organization_id)
+    .fetch_all(conn)
+    .await
+}
+
+// Handler: List Organizations
+pub async fn list_organizations(
+    State(state): State<Arc<AppState>>,
+    scope: Option<Extension<OrganizationScope>>,
+) -> impl IntoResponse {
+    let only = scope.map(|Extension(scope)| scope.organization_id);
+    match sqlx::query_as::<_, Organization>(&format!(
+        r#"
+        SELECT {ORGANIZATION_COLUMNS}
+        FROM organizations
+        WHERE $1::uuid IS NULL OR id = $1
+        ORDER BY name, id
+        "#
+    ))
+    .bind(only)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(organizations) => Json(organizations).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list organizations");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get Organization
+pub async fn get_organization(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let result: Result<Option<OrganizationDetail>, sqlx::Error> = async {
+        let mut conn = state.db_pool.acquire().await?;
+        let Some(organization) = load_organization(&mut conn, id).await? else {
+            return Ok(None);
+        };
+        let admins = list_admins(&mut conn, id).await?;
+        Ok(Some(OrganizationDetail {
+            organization,
+            admins,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Some(detail)) => Json(detail).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to load organization");
+            database_error()
+        }
+    }
+}
+
+// Handler: Update Organization
+pub async fn update_organization(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateOrganizationRequest>,
+) -> impl IntoResponse {
+    if payload.payout_fee_bps.is_some() {
+        return fee_needs_review();
+    }
+    let name = match payload.name.as_deref().map(clean_name).transpose() {
+        Ok(name) => name,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+    if let Some(Err(message)) = payload.branding.as_ref().map(Branding::validate) {
+        return refused(StatusCode::BAD_REQUEST, message);
+    }
+
+    let result: Result<Option<Organization>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let updated = sqlx::query_as::<_, Organization>(&format!(
+            r#"
+            UPDATE organizations
+            SET name = COALESCE($2, name),
+                branding = COALESCE($3, branding),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING {ORGANIZATION_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(&name)
+        .bind(payload.branding.as_ref().map(SqlJson))
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(organization) = updated else {
+            return Ok(None);
+        };
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "organization.updated",
+            &id.to_string(),
+            serde_json::json!({
+                "name": name,
+                "branding": payload.branding,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(organization))
+    }
+    .await;
+
+    match result {
+        Ok(Some(organization)) => Json(organization).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to update organization");
+            database_error()
+        }
+    }
+}
+
+/// Moves a user, and the plans they own, into `organization_id`, or back
+/// to the platform when it is `None`. Assigning a wallet that has no
+/// account yet creates one.
+async fn assign_user(
+    conn: &mut PgConnection,
+    user: &str,
+    organization_id: Option<Uuid>,
+) -> Result<Option<UserAssignment>, sqlx::Error> {
+    let user = user.trim();
+    let found: Option<(Uuid, String)> = match Uuid::parse_str(user) {
+        Ok(id) => {
+            sqlx::query_as("SELECT id, wallet_address FROM users WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?
+        }
+        Err(_) if stellar_strkey::ed25519::PublicKey::from_string(user).is_ok() => {
+            sqlx::query_as(
+                r#"
+                INSERT INTO users (wallet_address) VALUES ($1)
+                ON CONFLICT (wallet_address) DO UPDATE SET wallet_address = EXCLUDED.wallet_address
+                RETURNING id, wallet_address
+                "#,
+            )
+            .bind(user)
+            .fetch_optional(&mut *conn)
+            .await?
+        }
+        Err(_) => None,
+    };
+    let Some((user_id, wallet_address)) = found else {
+        return Ok(None);
+    };
+
+    sqlx::query("UPDATE users SET organization_id = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(organization_id)
+        .execute(&mut *conn)
+        .await?;
+    let plans_updated =
+        sqlx::query("UPDATE plans SET organization_id = $2 WHERE owner_address = $1")
+            .bind(&wallet_address)
+            .bind(organization_id)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    Ok(Some(UserAssignment {
+        user_id,
+        wallet_address,
+        organization_id,
+        plans_updated,
+    }))
+}
+
+async fn change_user_organization(
+    state: &AppState,
+    admin: &UserContext,
+    id: Uuid,
+    user: &str,
+    join: bool,
+) -> Response {
+    let result: Result<Outcome<UserAssignment>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if load_organization(&mut tx, id).await?.is_none() {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "Organization not found",
+            ));
+        }
+        if !join && !user_in_organization(&state.db_pool, user, id).await? {
+            return Ok(Outcome::Refused(
+                StatusCode::NOT_FOUND,
+                "The user is not in this organization",
+            ));
+        }
+        let Some(assignment) = assign_user(&mut tx, user, join.then_some(id)).await? else {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "User not found"));
+        };
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            if join {
+                "organization.user_assigned"
+            } else {
+                "organization.user_removed"
+            },
+            &id.to_string(),
+            serde_json::json!({
+                "user_id": assignment.user_id,
+                "plans_updated": assignment.plans_updated,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(assignment))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(assignment)) => Json(assignment).into_response(),
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(error = %e, organization = %id, "Failed to change user organization");
+            database_error()
+        }
+    }
+}
+
+// Handler: Assign User to Organization
+pub async fn assign_organization_user(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Path((id, user)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    if scope.is_some() {
+        return platform_only();
+    }
+    change_user_organization(&state, &admin, id, &user, true).await
+}
+
+// Handler: Remove User from Organization
+pub async fn remove_organization_user(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    scope: Option<Extension<OrganizationScope>>,
+    Path((id, user)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    if scope.is_some() {
+        return platform_only();
+    }
+    change_user_organization(&state, &admin, id, &user, false).await
+}
+
+// Handler: Get Organization Branding
+pub async fn get_organization_branding(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> impl IntoResponse {
+    match sqlx::query_as::<_, PublicOrganization>(
+        "SELECT slug, name, branding FROM organizations WHERE slug = $1",
+    )
+    .bind(slug.trim())
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(organization)) => Json(organization).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(error = %e, slug = %slug, "Failed to load organization branding");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branding_is_checked_field_by_field() {
+        let branding = Branding {
+            display_name: Some("Smith & Co Estates".into()),
+            logo_url: Some("https://cdn.example.com/logo.svg".into()),
+            primary_color: Some("#1a2B3c".into()),
+            support_email: Some("help@example.com".into()),
+        };
+        assert_eq!(branding.validate(), Ok(()));
+        assert_eq!(Branding::default().validate(), Ok(()));
+
+        let http_logo = Branding {
+            logo_url: Some("http://cdn.example.com/logo.svg".into()),
+            ..Branding::default()
+        };
+        assert!(http_logo.validate().is_err());
+        let bad_colour = Branding {
+            primary_color: Some("blue".into()),
+            ..Branding::default()
+        };
+        assert!(bad_colour.validate().is_err());
+        assert!(serde_json::from_str::<Branding>(r#"{"font":"serif"}"#).is_err());
+    }
+
+    #[test]
+    fn organization_admins_only_reach_scoped_routes() {
+        assert_eq!(
+            route_scope("/api/admin/plans/{id}/history"),
+            RouteScope::Plan
+        );
+        assert_eq!(
+            route_scope("/api/admin/users/{id}/freeze"),
+            RouteScope::User
+        );
+        assert_eq!(
+            route_scope("/api/admin/organizations"),
+            RouteScope::Organization
+        );
+        assert_eq!(
+            route_scope("/api/admin/organizations/{id}/admins/{admin_id}"),
+            RouteScope::Organization
+        );
+        assert_eq!(
+            route_scope("/api/admin/plans/batch-status"),
+            RouteScope::Platform
+        );
+        assert_eq!(route_scope("/api/admin/jobs"), RouteScope::Platform);
+    }
+
+    #[test]
+    fn slugs_are_url_safe() {
+        assert!(validate_slug("smith-estates").is_ok());
+        assert!(validate_slug("Smith").is_err());
+        assert!(validate_slug("-smith").is_err());
+        assert!(validate_slug("a").is_err());
+    }
+}
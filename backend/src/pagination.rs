@@ -0,0 +1,121 @@
+//! Keyset cursors for lists ordered newest first.
+//!
+//! A cursor names the last row of a page by its `(created_at, id)`, and the
+//! next page starts strictly after that row, so rows inserted while a
+//! client is paging never shift later pages the way an offset would.
+//! Cursors are opaque URL-safe base64 tokens. The cursor of the next page
+//! is returned in the `X-Next-Cursor` header, which is absent on the last
+//! page, so list bodies keep their shape.
+
+use axum::{
+    http::{HeaderName, HeaderValue},
+    response::Response,
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+const CURSOR_LEN: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(CURSOR_LEN);
+        bytes.extend_from_slice(&self.created_at.timestamp_micros().to_be_bytes());
+        bytes.extend_from_slice(self.id.as_bytes());
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    pub fn decode(token: &str) -> Result<Self, &'static str> {
+        const INVALID: &str = "cursor is not valid";
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .map_err(|_| INVALID)?;
+        if bytes.len() != CURSOR_LEN {
+            return Err(INVALID);
+        }
+        let (micros, id) = bytes.split_at(8);
+        let micros = i64::from_be_bytes(micros.try_into().map_err(|_| INVALID)?);
+        Ok(Self {
+            created_at: DateTime::from_timestamp_micros(micros).ok_or(INVALID)?,
+            id: Uuid::from_slice(id).map_err(|_| INVALID)?,
+        })
+    }
+
+    /// Parses an optional `cursor` query parameter.
+    pub fn parse(token: Option<&str>) -> Result<Option<Self>, &'static str> {
+        token.map(Self::decode).transpose()
+    }
+}
+
+/// Trims rows fetched with a limit of `limit + 1` to `limit`, returning
+/// the cursor of the page's last row when more rows follow.
+pub fn split_page<T>(
+    mut rows: Vec<T>,
+    limit: i64,
+    key: impl Fn(&T) -> Cursor,
+) -> (Vec<T>, Option<Cursor>) {
+    let limit = usize::try_from(limit).unwrap_or(0);
+    if rows.len() <= limit {
+        return (rows, None);
+    }
+    rows.truncate(limit);
+    let next = rows.last().map(key);
+    (rows, next)
+}
+
+/// Sets `X-Next-Cursor` when another page follows.
+pub fn with_next_cursor(mut response: Response, next: Option<Cursor>) -> Response {
+    if let Some(value) = next.and_then(|c| HeaderValue::from_str(&c.encode()).ok()) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(NEXT_CURSOR_HEADER), value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_round_trip_and_reject_garbage() {
+        let cursor = Cursor::new(
+            DateTime::from_timestamp_micros(1_760_000_000_123_456).unwrap(),
+            Uuid::new_v4(),
+        );
+        let token = cursor.encode();
+        assert!(token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Cursor::decode(&token), Ok(cursor));
+        assert_eq!(Cursor::parse(None), Ok(None));
+        assert!(Cursor::decode("not a cursor").is_err());
+        assert!(Cursor::decode(&token[..token.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn only_a_full_page_has_a_next_cursor() {
+        let at = Utc::now();
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let key = |id: &Uuid| Cursor::new(at, *id);
+
+        let (page, next) = split_page(ids.clone(), 2, key);
+        assert_eq!(page, ids[..2]);
+        assert_eq!(next, Some(Cursor::new(at, ids[1])));
+
+        let (page, next) = split_page(ids.clone(), 3, key);
+        assert_eq!(page, ids);
+        assert_eq!(next, None);
+    }
+}
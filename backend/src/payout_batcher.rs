@@ -0,0 +1,817 @@
+//! Groups pending crypto payouts into batched on-chain transfers.
+//!
+//! Each batch is a small saga that pays every payout exactly once. The batch
+//! row is the intent: it is committed, with the payouts marked `processing`,
+//! before anything is submitted, and it carries a memo derived from the
+//! batch id that the transaction is sent with. When the outcome of a
+//! submission is unknown (a timeout, or a crash before the receipt was
+//! recorded) the batch stays open and the payouts stay parked. Later sweeps
+//! look the memo up through the indexer: a landed transaction settles the
+//! payouts, and one still missing after its validity window has passed can
+//! no longer land, so the batch is expired and its payouts are released for
+//! a retry under a new batch. Transfers that definitely failed return to
+//! `pending` until they exhaust their attempts, and are then moved to the
+//! dead letter queue.
+//!
+//! When trustline checks are configured, payouts whose destination cannot
+//! receive the asset yet are held back as `pending` with the reason in
+//! `failure_reason`, and checked again on every sweep.
+//!
+//! The parts of a split payout (see [`crate::payout_destinations`]) are
+//! claimed, held and batched together, so they go out in one transaction
+//! unless the split has more parts than `PAYOUT_BATCH_SIZE`.
+//!
+//! A converted payout (see [`crate::payout_conversion`]) is submitted as a
+//! swap to the beneficiary's token with the recorded minimum, and its
+//! trustline is checked for that token.
+
+use base64::Engine;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, PgPool};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::chain::{
+    BatchReceipt, Conversion, TokenTransfer, TransferOutcome, TxError, TxService, TX_VALIDITY,
+};
+use crate::dead_letters::{record_dead_letter, NewDeadLetter, Worker};
+use crate::jobs::JobRegistry;
+use crate::trustlines::TrustlineChecker;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_BATCH_SIZE: usize = 25;
+const MAX_BATCH_SIZE: usize = 100;
+const DEFAULT_MAX_ATTEMPTS: i32 = 3;
+const MAX_BATCHES_PER_SWEEP: usize = 10;
+const PAYOUT_BATCHER_LOCK_KEY: i64 = 821;
+/// Extra time after a transaction's validity window before the indexer's
+/// answer is treated as final.
+const CONFIRMATION_MARGIN: Duration = Duration::from_secs(60);
+/// Age at which a batch still marked `submitting` is assumed to have lost
+/// its submitter and is reconciled.
+const SUBMISSION_GRACE_SECS: f64 = 60.0;
+const RECONCILE_BATCH_LIMIT: i64 = 100;
+const MEMO_PREFIX: &str = "ixb-";
+
+#[derive(Debug, Clone, Copy)]
+pub struct PayoutBatcherConfig {
+    pub interval: Duration,
+    pub batch_size: usize,
+    pub max_attempts: i32,
+    /// How long after submission a batch's transaction may still turn up.
+    pub confirmation_window: Duration,
+}
+
+impl PayoutBatcherConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("PAYOUT_BATCHER_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("PAYOUT_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+        let max_attempts = parse_env("PAYOUT_MAX_ATTEMPTS", DEFAULT_MAX_ATTEMPTS);
+        let minimum_window = TX_VALIDITY + CONFIRMATION_MARGIN;
+        let window_secs = parse_env("PAYOUT_CONFIRMATION_WINDOW_SECS", minimum_window.as_secs());
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.clamp(1, MAX_BATCH_SIZE),
+            max_attempts: max_attempts.max(1),
+            confirmation_window: Duration::from_secs(window_secs).max(minimum_window),
+        }
+    }
+}
+
+/// Text memo identifying a batch's transaction. A UUID does not fit in a
+/// 28-byte text memo, so its bytes are base64url encoded.
+pub fn batch_memo(batch_id: Uuid) -> String {
+    format!(
+        "{MEMO_PREFIX}{}",
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(batch_id.as_bytes())
+    )
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct PendingPayout {
+    id: Uuid,
+    beneficiary_address: String,
+    /// Wallet the transfer is sent to.
+    destination_address: String,
+    split_group: Option<Uuid>,
+    amount: Decimal,
+    token_address: String,
+    attempts: i32,
+    correlation_id: Option<String>,
+    /// Token the destination is paid in when the payout is converted.
+    payout_asset: Option<String>,
+    converted_amount: Option<Decimal>,
+    min_converted_amount: Option<Decimal>,
+}
+
+impl PendingPayout {
+    /// Token and amount the destination receives.
+    fn delivered(&self) -> (&str, Decimal) {
+        match (&self.payout_asset, self.converted_amount) {
+            (Some(asset), Some(amount)) => (asset, amount),
+            _ => (&self.token_address, self.amount),
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct OpenBatch {
+    id: Uuid,
+    memo: String,
+    window_passed: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SweepSummary {
+    pub batches: usize,
+    pub completed: usize,
+    pub retried: usize,
+    pub failed: usize,
+    /// Batches whose submission outcome is not known yet.
+    pub unconfirmed: usize,
+    /// Open batches settled from the indexer or expired.
+    pub reconciled: usize,
+    /// Payouts held back until the beneficiary can receive the asset.
+    pub blocked: usize,
+}
+
+pub struct PayoutBatcherService {
+    db: PgPool,
+    tx_service: Arc<dyn TxService>,
+    trustlines: Option<TrustlineChecker>,
+    config: PayoutBatcherConfig,
+}
+
+impl PayoutBatcherService {
+    pub fn new(
+        db: PgPool,
+        tx_service: Arc<dyn TxService>,
+        trustlines: Option<TrustlineChecker>,
+        config: PayoutBatcherConfig,
+    ) -> Self {
+        Self {
+            db,
+            tx_service,
+            trustlines,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("payout_batcher", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|summary| {
+                    if summary.batches > 0 || summary.reconciled > 0 || summary.blocked > 0 {
+                        info!(
+                            batches = summary.batches,
+                            completed = summary.completed,
+                            retried = summary.retried,
+                            failed = summary.failed,
+                            unconfirmed = summary.unconfirmed,
+                            reconciled = summary.reconciled,
+                            blocked = summary.blocked,
+                            "Payout batcher sweep finished"
+                        );
+                    }
+                })
+            }
+        });
+    }
+
+    pub async fn run_once(&self) -> Result<SweepSummary, sqlx::Error> {
+        let mut summary = SweepSummary::default();
+        if !self.reconcile(&mut summary).await? {
+            return Ok(summary);
+        }
+
+        let batches = match self.claim_batches(&mut summary).await? {
+            Some(batches) => batches,
+            None => return Ok(summary),
+        };
+
+        for (batch_id, payouts) in batches {
+            summary.batches += 1;
+            let memo = batch_memo(batch_id);
+            let transfers: Vec<TokenTransfer> = payouts
+                .iter()
+                .map(|p| TokenTransfer {
+                    reference: p.id,
+                    token: p.token_address.clone(),
+                    destination: p.destination_address.clone(),
+                    amount: p.amount,
+                    memo: Some(memo.clone()),
+                    correlation_id: p.correlation_id.clone(),
+                    conversion: p
+                        .payout_asset
+                        .clone()
+                        .zip(p.min_converted_amount)
+                        .map(|(token, min_amount)| Conversion { token, min_amount }),
+                })
+                .collect();
+
+            let result = self.tx_service.submit_transfers(&transfers).await;
+            self.record_result(batch_id, &payouts, result, &mut summary)
+                .await?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Settles open batches whose submission outcome was never recorded.
+    /// Returns false when another worker holds the lock.
+    async fn reconcile(&self, summary: &mut SweepSummary) -> Result<bool, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(PAYOUT_BATCHER_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Payout batcher lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(false);
+        }
+
+        let open = sqlx::query_as::<_, OpenBatch>(
+            r#"
+            SELECT id, memo,
+                   created_at <= NOW() - ($1 * INTERVAL '1 second') AS window_passed
+            FROM payout_batches
+            WHERE memo IS NOT NULL
+              AND (status = 'unconfirmed'
+                   OR (status = 'submitting'
+                       AND created_at <= NOW() - ($2 * INTERVAL '1 second')))
+            ORDER BY created_at ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(self.config.confirmation_window.as_secs() as f64)
+        .bind(SUBMISSION_GRACE_SECS)
+        .bind(RECONCILE_BATCH_LIMIT)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for batch in &open {
+            let landed = match self.tx_service.find_transfers_by_memo(&batch.memo).await {
+                Ok(landed) => landed,
+                Err(e) => {
+                    warn!(batch_id = %batch.id, error = %e, "Failed to look up payout batch on-chain");
+                    continue;
+                }
+            };
+            if landed.is_none() && !batch.window_passed {
+                continue;
+            }
+
+            let payouts = sqlx::query_as::<_, PendingPayout>(
+                r#"
+                SELECT p.id, p.beneficiary_address,
+                       COALESCE(p.destination_address, p.beneficiary_address) AS destination_address,
+                       p.split_group, p.amount, pl.token_address, p.attempts,
+                       p.payout_asset, p.converted_amount, p.min_converted_amount
+                FROM payouts p
+                JOIN plans pl ON pl.id = p.plan_id
+                WHERE p.batch_id = $1
+                ORDER BY p.created_at ASC, p.id ASC
+                "#,
+            )
+            .bind(batch.id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let (result, not_landed_status) = match landed {
+                Some(receipt) => {
+                    info!(batch_id = %batch.id, tx_hash = %receipt.tx_hash, "Payout batch found on-chain");
+                    (Ok(receipt), "failed")
+                }
+                None => {
+                    warn!(batch_id = %batch.id, "Payout batch did not land; releasing its payouts");
+                    (
+                        Err(TxError::Rejected(
+                            "transaction did not land within its validity window".to_string(),
+                        )),
+                        "expired",
+                    )
+                }
+            };
+            apply_result(
+                &mut tx,
+                batch.id,
+                &payouts,
+                result,
+                not_landed_status,
+                self.config.max_attempts,
+                summary,
+            )
+            .await?;
+            summary.reconciled += 1;
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Claims due payouts, creates their batch rows and marks them
+    /// `processing`. Returns `None` when another worker holds the lock.
+    async fn claim_batches(
+        &self,
+        summary: &mut SweepSummary,
+    ) -> Result<Option<Vec<(Uuid, Vec<PendingPayout>)>>, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(PAYOUT_BATCHER_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Payout batcher lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(None);
+        }
+
+        let pending = sqlx::query_as::<_, PendingPayout>(
+            r#"
+            SELECT p.id, p.beneficiary_address,
+                   COALESCE(p.destination_address, p.beneficiary_address) AS destination_address,
+                   p.split_group, p.amount, pl.token_address, p.attempts, p.correlation_id,
+                   p.payout_asset, p.converted_amount, p.min_converted_amount
+            FROM payouts p
+            JOIN plans pl ON pl.id = p.plan_id
+            WHERE p.payout_type = 'crypto'
+              AND p.status = 'pending'
+            ORDER BY p.created_at ASC, p.id ASC
+            LIMIT $1
+            FOR UPDATE OF p SKIP LOCKED
+            "#,
+        )
+        .bind((self.config.batch_size * MAX_BATCHES_PER_SWEEP) as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+        let pending = drop_partial_splits(&mut tx, pending).await?;
+        let pending = self.hold_unreceivable(&mut tx, pending, summary).await?;
+
+        let mut claimed = Vec::new();
+        for payouts in group_into_batches(pending, self.config.batch_size) {
+            let batch_id = Uuid::new_v4();
+            sqlx::query(
+                r#"
+                INSERT INTO payout_batches (id, token_address, transfer_count, memo)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(batch_id)
+            .bind(&payouts[0].token_address)
+            .bind(payouts.len() as i32)
+            .bind(batch_memo(batch_id))
+            .execute(&mut *tx)
+            .await?;
+
+            let ids: Vec<Uuid> = payouts.iter().map(|p| p.id).collect();
+            sqlx::query(
+                r#"
+                UPDATE payouts
+                SET status = 'processing', batch_id = $1, updated_at = NOW()
+                WHERE id = ANY($2)
+                "#,
+            )
+            .bind(batch_id)
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+
+            claimed.push((batch_id, payouts));
+        }
+
+        tx.commit().await?;
+        Ok(Some(claimed))
+    }
+
+    /// Drops payouts the destination cannot receive yet, recording why on
+    /// the payout. Payouts whose account could not be looked up wait for
+    /// the next sweep, and so do the other parts of their split.
+    async fn hold_unreceivable(
+        &self,
+        conn: &mut PgConnection,
+        pending: Vec<PendingPayout>,
+        summary: &mut SweepSummary,
+    ) -> Result<Vec<PendingPayout>, sqlx::Error> {
+        let Some(checker) = &self.trustlines else {
+            return Ok(pending);
+        };
+
+        let mut ready = Vec::with_capacity(pending.len());
+        let mut held_splits = HashSet::new();
+        for payout in pending {
+            let (token, amount) = payout.delivered();
+            let issue = match checker
+                .check(&payout.destination_address, token, amount)
+                .await
+            {
+                Ok(issue) => issue,
+                Err(e) => {
+                    warn!(payout_id = %payout.id, error = %e, "Failed to check beneficiary trustline");
+                    summary.blocked += 1;
+                    held_splits.extend(payout.split_group);
+                    continue;
+                }
+            };
+            match issue {
+                Some(issue) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE payouts SET failure_reason = $2, updated_at = NOW()
+                        WHERE id = $1 AND failure_reason IS DISTINCT FROM $2
+                        "#,
+                    )
+                    .bind(payout.id)
+                    .bind(issue.to_string())
+                    .execute(&mut *conn)
+                    .await?;
+                    summary.blocked += 1;
+                    held_splits.extend(payout.split_group);
+                }
+                None => ready.push(payout),
+            }
+        }
+        ready.retain(|p| {
+            p.split_group
+                .is_none_or(|group| !held_splits.contains(&group))
+        });
+        Ok(ready)
+    }
+
+    async fn record_result(
+        &self,
+        batch_id: Uuid,
+        payouts: &[PendingPayout],
+        result: Result<BatchReceipt, TxError>,
+        summary: &mut SweepSummary,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        if let Err(TxError::Unavailable(reason)) = &result {
+            // The transaction may still land; leave the payouts parked until
+            // the indexer can tell.
+            summary.unconfirmed += 1;
+            warn!(batch_id = %batch_id, reason = %reason, "Payout batch outcome unknown; awaiting confirmation");
+            sqlx::query(
+                r#"
+                UPDATE payout_batches
+                SET status = 'unconfirmed', error_message = $2
+                WHERE id = $1 AND status = 'submitting'
+                "#,
+            )
+            .bind(batch_id)
+            .bind(reason)
+            .execute(&mut *tx)
+            .await?;
+            return tx.commit().await;
+        }
+
+        apply_result(
+            &mut tx,
+            batch_id,
+            payouts,
+            result,
+            "failed",
+            self.config.max_attempts,
+            summary,
+        )
+        .await?;
+        tx.commit().await
+    }
+}
+
+/// Records the outcome of a batch's transaction on its payouts and closes
+/// the batch. Only payouts still `processing` under this batch are touched,
+/// so a batch settled twice (by its submitter and by reconciliation) is
+/// only accounted once. `not_landed_status` is the batch status used when
+/// the transaction did not go through.
+async fn apply_result(
+    conn: &mut PgConnection,
+    batch_id: Uuid,
+    payouts: &[PendingPayout],
+    result: Result<BatchReceipt, TxError>,
+    not_landed_status: &str,
+    max_attempts: i32,
+    summary: &mut SweepSummary,
+) -> Result<(), sqlx::Error> {
+    let (outcomes, tx_hash, batch_error) = match result {
+        Ok(receipt) if receipt.outcomes.len() == payouts.len() => {
+            (receipt.outcomes, Some(receipt.tx_hash), None)
+        }
+        Ok(receipt) => {
+            let reason = format!(
+                "receipt reported {} outcome(s) for {} transfer(s)",
+                receipt.outcomes.len(),
+                payouts.len()
+            );
+            (
+                vec![TransferOutcome::Failed(reason.clone()); payouts.len()],
+                Some(receipt.tx_hash),
+                Some(reason),
+            )
+        }
+        Err(e) => (
+            vec![TransferOutcome::Failed(e.to_string()); payouts.len()],
+            None,
+            Some(e.to_string()),
+        ),
+    };
+
+    let failures = outcomes
+        .iter()
+        .filter(|o| matches!(o, TransferOutcome::Failed(_)))
+        .count();
+    let batch_status = match (failures, batch_error.is_some()) {
+        (_, true) => not_landed_status,
+        (0, false) => "confirmed",
+        _ => "partial",
+    };
+
+    let closed = sqlx::query(
+        r#"
+        UPDATE payout_batches
+        SET status = $2, tx_hash = $3, error_message = $4, completed_at = NOW()
+        WHERE id = $1 AND status IN ('submitting', 'unconfirmed')
+        "#,
+    )
+    .bind(batch_id)
+    .bind(batch_status)
+    .bind(&tx_hash)
+    .bind(&batch_error)
+    .execute(&mut *conn)
+    .await?;
+    if closed.rows_affected() == 0 {
+        warn!(batch_id = %batch_id, "Payout batch was already settled");
+        return Ok(());
+    }
+
+    for (payout, outcome) in payouts.iter().zip(&outcomes) {
+        match outcome {
+            TransferOutcome::Succeeded => {
+                let updated = sqlx::query(
+                    r#"
+                    UPDATE payouts
+                    SET status = 'completed', failure_reason = NULL, updated_at = NOW()
+                    WHERE id = $1 AND batch_id = $2 AND status = 'processing'
+                    "#,
+                )
+                .bind(payout.id)
+                .bind(batch_id)
+                .execute(&mut *conn)
+                .await?;
+                if updated.rows_affected() > 0 {
+                    summary.completed += 1;
+                }
+            }
+            TransferOutcome::Failed(reason) => {
+                let status = status_after_failure(payout.attempts, max_attempts);
+                let Some((plan_id, attempts, attempt_history)) =
+                    sqlx::query_as::<_, (Uuid, i32, serde_json::Value)>(
+                        r#"
+                        UPDATE payouts
+                        SET status = $2::payout_status,
+                            attempts = attempts + 1,
+                            failure_reason = $3,
+                            attempt_history = attempt_history || jsonb_build_array(
+                                jsonb_build_object(
+                                    'attempt', attempts + 1, 'error', $3::text, 'at', NOW()
+                                )
+                            ),
+                            batch_id = CASE WHEN $2 = 'failed' THEN batch_id END,
+                            updated_at = NOW()
+                        WHERE id = $1 AND batch_id = $4 AND status = 'processing'
+                        RETURNING plan_id, attempts, attempt_history
+                        "#,
+                    )
+                    .bind(payout.id)
+                    .bind(status)
+                    .bind(reason)
+                    .bind(batch_id)
+                    .fetch_optional(&mut *conn)
+                    .await?
+                else {
+                    continue;
+                };
+
+                if status == "failed" {
+                    summary.failed += 1;
+                    error!(payout_id = %payout.id, reason = %reason, "Payout failed permanently");
+                    record_dead_letter(
+                        &mut *conn,
+                        NewDeadLetter {
+                            worker: Worker::PayoutBatcher,
+                            job_id: payout.id,
+                            payload: serde_json::json!({
+                                "plan_id": plan_id,
+                                "batch_id": batch_id,
+                                "beneficiary_address": payout.beneficiary_address,
+                                "destination_address": payout.destination_address,
+                                "amount": payout.amount.to_string(),
+                                "token_address": payout.token_address,
+                            }),
+                            error: reason,
+                            attempts,
+                            attempt_history,
+                        },
+                    )
+                    .await?;
+                } else {
+                    summary.retried += 1;
+                    warn!(payout_id = %payout.id, reason = %reason, "Payout transfer failed; will retry");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Leaves out split groups with parts beyond the claimed page, so a split
+/// is never paid in two transactions just because the page ended mid-way.
+async fn drop_partial_splits(
+    conn: &mut PgConnection,
+    pending: Vec<PendingPayout>,
+) -> Result<Vec<PendingPayout>, sqlx::Error> {
+    let groups: Vec<Uuid> = pending.iter().filter_map(|p| p.split_group).collect();
+    if groups.is_empty() {
+        return Ok(pending);
+    }
+    let totals: HashMap<Uuid, i64> = sqlx::query_as::<_, (Uuid, i64)>(
+        r#"
+        SELECT split_group, COUNT(*)
+        FROM payouts
+        WHERE split_group = ANY($1) AND payout_type = 'crypto' AND status = 'pending'
+        GROUP BY split_group
+        "#,
+    )
+    .bind(&groups)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    let claimed = groups.iter().fold(HashMap::new(), |mut counts, group| {
+        *counts.entry(*group).or_insert(0i64) += 1;
+        counts
+    });
+    Ok(pending
+        .into_iter()
+        .filter(|p| {
+            p.split_group
+                .is_none_or(|group| claimed[&group] >= totals.get(&group).copied().unwrap_or(0))
+        })
+        .collect())
+}
+
+/// Splits payouts into per-token batches of at most `batch_size`, keeping
+/// the oldest payouts first. The parts of a split stay in one batch unless
+/// there are more of them than fit.
+fn group_into_batches(payouts: Vec<PendingPayout>, batch_size: usize) -> Vec<Vec<PendingPayout>> {
+    let batch_size = batch_size.max(1);
+    let mut by_token: Vec<(String, Vec<Vec<PendingPayout>>)> = Vec::new();
+    for payout in payouts {
+        let index = match by_token
+            .iter()
+            .position(|(token, _)| *token == payout.token_address)
+        {
+            Some(index) => index,
+            None => {
+                by_token.push((payout.token_address.clone(), Vec::new()));
+                by_token.len() - 1
+            }
+        };
+        let units = &mut by_token[index].1;
+        match units
+            .iter_mut()
+            .find(|unit| payout.split_group.is_some() && unit[0].split_group == payout.split_group)
+        {
+            Some(unit) => unit.push(payout),
+            None => units.push(vec![payout]),
+        }
+    }
+
+    let mut batches = Vec::new();
+    for (_, units) in by_token {
+        let mut batch: Vec<PendingPayout> = Vec::new();
+        for unit in units {
+            if !batch.is_empty() && batch.len() + unit.len() > batch_size {
+                batches.push(std::mem::take(&mut batch));
+            }
+            if unit.len() > batch_size {
+                batches.extend(unit.chunks(batch_size).map(<[PendingPayout]>::to_vec));
+            } else {
+                batch.extend(unit);
+            }
+        }
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+    }
+    batches
+}
+
+/// Status for a payout whose transfer just failed after `attempts` prior
+/// failures.
+fn status_after_failure(attempts: i32, max_attempts: i32) -> &'static str {
+    if attempts + 1 >= max_attempts {
+        "failed"
+    } else {
+        "pending"
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payout(token: &str) -> PendingPayout {
+        PendingPayout {
+            id: Uuid::new_v4(),
+            beneficiary_address: "GDEST".to_string(),
+            destination_address: "GDEST".to_string(),
+            split_group: None,
+            amount: Decimal::from(100),
+            token_address: token.to_string(),
+            attempts: 0,
+            correlation_id: None,
+            payout_asset: None,
+            converted_amount: None,
+            min_converted_amount: None,
+        }
+    }
+
+    #[test]
+    fn batches_never_mix_tokens_or_exceed_size() {
+        let payouts = vec![
+            payout("USDC"),
+            payout("XLM"),
+            payout("USDC"),
+            payout("USDC"),
+        ];
+        let first_usdc = payouts[0].id;
+
+        let batches = group_into_batches(payouts, 2);
+
+        assert_eq!(batches.len(), 3);
+        assert!(batches.iter().all(|b| b.len() <= 2));
+        assert!(batches
+            .iter()
+            .all(|b| b.iter().all(|p| p.token_address == b[0].token_address)));
+        assert_eq!(batches[0][0].id, first_usdc);
+    }
+
+    #[test]
+    fn split_parts_share_a_batch() {
+        let group = Some(Uuid::new_v4());
+        let split = |p: PendingPayout| PendingPayout {
+            split_group: group,
+            ..p
+        };
+        let payouts = vec![
+            payout("USDC"),
+            split(payout("USDC")),
+            payout("USDC"),
+            split(payout("USDC")),
+        ];
+
+        let batches = group_into_batches(payouts, 2);
+
+        assert_eq!(batches.len(), 3);
+        assert!(batches
+            .iter()
+            .any(|b| b.len() == 2 && b.iter().all(|p| p.split_group == group)));
+        assert!(batches.iter().all(|b| b.len() <= 2));
+    }
+
+    #[test]
+    fn batch_memo_fits_stellar_text_memo() {
+        let memo = batch_memo(Uuid::new_v4());
+        assert!(memo.len() <= 28);
+        assert!(memo.starts_with(MEMO_PREFIX));
+        assert_ne!(memo, batch_memo(Uuid::new_v4()));
+    }
+
+    #[test]
+    fn failures_retry_until_attempts_exhausted() {
+        assert_eq!(status_after_failure(0, 3), "pending");
+        assert_eq!(status_after_failure(1, 3), "pending");
+        assert_eq!(status_after_failure(2, 3), "failed");
+        assert_eq!(status_after_failure(0, 1), "failed");
+    }
+}
@@ -0,0 +1,393 @@
+//! Payouts converted to the token a beneficiary prefers.
+//!
+//! A beneficiary can ask for their crypto payouts in another token than the
+//! plan holds, with the most slippage they accept. At payout time the
+//! conversion is quoted from the platform's price feed
+//! (`asset_price_history`), using prices no older than
+//! `PAYOUT_QUOTE_MAX_AGE_SECS`. The payout keeps its amount in the plan's
+//! token and also records the quoted amount of the preferred token and the
+//! least the transfer may deliver, which the on-chain swap enforces. When
+//! no fresh quote exists the beneficiary is paid in the plan's token, as
+//! before. Both outcomes are recorded in the audit log. Fiat payouts go to
+//! the anchor and are never converted.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::cost_breakdown::checked_fee;
+
+pub const DEFAULT_MAX_SLIPPAGE_BPS: u32 = 100;
+pub const MAX_SLIPPAGE_BPS: u32 = 1_000;
+const MAX_ASSET_LEN: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct PayoutAssetPreference {
+    pub asset: String,
+    pub max_slippage_bps: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPayoutAssetRequest {
+    /// Token address payouts are converted to.
+    pub asset: String,
+    pub max_slippage_bps: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PayoutAssetResponse {
+    pub beneficiary_address: String,
+    /// `None` pays in each plan's own token.
+    pub preference: Option<PayoutAssetPreference>,
+}
+
+/// Latest price of one whole token.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TokenPrice {
+    pub token_address: String,
+    pub price_usd: Decimal,
+    pub decimals: i16,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A rate from the plan's token to the preferred one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub asset: String,
+    /// Base units of `asset` per base unit of the plan's token.
+    pub rate: Decimal,
+    pub max_slippage_bps: u32,
+    /// When the older of the two prices was recorded.
+    pub quoted_at: DateTime<Utc>,
+}
+
+/// An amount converted by a [`Quote`], in base units of the quoted asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Converted {
+    pub amount: Decimal,
+    /// Least the transfer may deliver.
+    pub min_amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PayoutConversion {
+    /// Paid in the plan's token: nothing else was asked for.
+    None,
+    Quoted(Quote),
+    /// The preferred token could not be quoted; paid in the plan's token.
+    Unavailable {
+        asset: String,
+        reason: &'static str,
+    },
+}
+
+impl Quote {
+    /// Base-unit rate between two token prices, or `None` if it overflows.
+    pub fn between(from: &TokenPrice, to: &TokenPrice, max_slippage_bps: u32) -> Option<Self> {
+        let scale = |decimals: i16| {
+            u32::try_from(decimals)
+                .ok()
+                .and_then(|d| 10i128.checked_pow(d))
+                .and_then(|s| Decimal::try_from_i128_with_scale(s, 0).ok())
+        };
+        let rate = from
+            .price_usd
+            .checked_mul(scale(to.decimals)?)?
+            .checked_div(to.price_usd.checked_mul(scale(from.decimals)?)?)?;
+        Some(Self {
+            asset: to.token_address.clone(),
+            rate,
+            max_slippage_bps,
+            quoted_at: from.recorded_at.min(to.recorded_at),
+        })
+    }
+
+    /// `amount` of the plan's token in whole base units of the quoted one;
+    /// `None` if it overflows or rounds down to nothing.
+    pub fn convert(&self, amount: Decimal) -> Option<Converted> {
+        let converted = amount.checked_mul(self.rate)?.floor();
+        if converted <= Decimal::ZERO {
+            return None;
+        }
+        let min_amount = converted.checked_sub(checked_fee(converted, self.max_slippage_bps)?)?;
+        Some(Converted {
+            amount: converted,
+            min_amount,
+        })
+    }
+}
+
+pub fn validate_preference(asset: &str, max_slippage_bps: u32) -> Result<(), String> {
+    if asset.is_empty() || asset.len() > MAX_ASSET_LEN || asset.contains(char::is_whitespace) {
+        return Err("asset must be a token address".to_string());
+    }
+    if max_slippage_bps > MAX_SLIPPAGE_BPS {
+        return Err(format!(
+            "max_slippage_bps must be at most {MAX_SLIPPAGE_BPS}"
+        ));
+    }
+    Ok(())
+}
+
+pub async fn load_preference(
+    conn: &mut PgConnection,
+    beneficiary_address: &str,
+) -> Result<Option<PayoutAssetPreference>, sqlx::Error> {
+    sqlx::query_as::<_, PayoutAssetPreference>(
+        r#"
+        SELECT asset, max_slippage_bps, updated_at
+        FROM payout_asset_preferences
+        WHERE beneficiary_address = $1
+        "#,
+    )
+    .bind(beneficiary_address)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+/// How a beneficiary's payout of `token` is converted.
+pub async fn conversion_for(
+    conn: &mut PgConnection,
+    beneficiary_address: &str,
+    token: &str,
+    max_quote_age: Duration,
+) -> Result<PayoutConversion, sqlx::Error> {
+    let Some(preference) = load_preference(conn, beneficiary_address).await? else {
+        return Ok(PayoutConversion::None);
+    };
+    if preference.asset == token {
+        return Ok(PayoutConversion::None);
+    }
+
+    let prices = sqlx::query_as::<_, TokenPrice>(
+        r#"
+        SELECT DISTINCT ON (token_address) token_address, price_usd, decimals, recorded_at
+        FROM asset_price_history
+        WHERE token_address IN ($1, $2)
+          AND recorded_at >= NOW() - ($3 * INTERVAL '1 second')
+        ORDER BY token_address, recorded_at DESC
+        "#,
+    )
+    .bind(token)
+    .bind(&preference.asset)
+    .bind(max_quote_age.as_secs() as f64)
+    .fetch_all(&mut *conn)
+    .await?;
+    let price_of = |address: &str| prices.iter().find(|p| p.token_address == address);
+    let (Some(from), Some(to)) = (price_of(token), price_of(&preference.asset)) else {
+        return Ok(PayoutConversion::Unavailable {
+            asset: preference.asset,
+            reason: "no_recent_price",
+        });
+    };
+    let max_slippage_bps = preference
+        .max_slippage_bps
+        .clamp(0, MAX_SLIPPAGE_BPS as i32) as u32;
+    Ok(match Quote::between(from, to, max_slippage_bps) {
+        Some(quote) => PayoutConversion::Quoted(quote),
+        None => PayoutConversion::Unavailable {
+            asset: preference.asset,
+            reason: "rate_out_of_range",
+        },
+    })
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: Get Payout Asset
+pub async fn get_payout_asset(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let wallet_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result = async {
+        let mut conn = state.db_pool.acquire().await?;
+        load_preference(&mut conn, &wallet_address).await
+    }
+    .await;
+
+    match result {
+        Ok(preference) => (
+            StatusCode::OK,
+            Json(PayoutAssetResponse {
+                beneficiary_address: wallet_address,
+                preference,
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(wallet_address = %wallet_address, error = %e, "Failed to load payout asset");
+            database_error()
+        }
+    }
+}
+
+// Handler: Set Payout Asset
+pub async fn set_payout_asset(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<SetPayoutAssetRequest>,
+) -> impl IntoResponse {
+    let wallet_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let asset = payload.asset.trim().to_string();
+    let max_slippage_bps = payload.max_slippage_bps.unwrap_or(DEFAULT_MAX_SLIPPAGE_BPS);
+    if let Err(message) = validate_preference(&asset, max_slippage_bps) {
+        return refused(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let result = async {
+        let mut tx = state.db_pool.begin().await?;
+        let preference = sqlx::query_as::<_, PayoutAssetPreference>(
+            r#"
+            INSERT INTO payout_asset_preferences (beneficiary_address, asset, max_slippage_bps)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (beneficiary_address) DO UPDATE
+            SET asset = EXCLUDED.asset,
+                max_slippage_bps = EXCLUDED.max_slippage_bps,
+                updated_at = NOW()
+            RETURNING asset, max_slippage_bps, updated_at
+            "#,
+        )
+        .bind(&wallet_address)
+        .bind(&asset)
+        .bind(max_slippage_bps as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &wallet_address,
+            "payout_asset.update",
+            &wallet_address,
+            serde_json::json!({ "asset": asset, "max_slippage_bps": max_slippage_bps }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(preference)
+    }
+    .await;
+
+    match result {
+        Ok(preference) => {
+            info!(wallet_address = %wallet_address, asset = %preference.asset, "Payout asset updated");
+            (
+                StatusCode::OK,
+                Json(PayoutAssetResponse {
+                    beneficiary_address: wallet_address,
+                    preference: Some(preference),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(wallet_address = %wallet_address, error = %e, "Failed to update payout asset");
+            database_error()
+        }
+    }
+}
+
+// Handler: Clear Payout Asset
+pub async fn clear_payout_asset(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let wallet_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result = async {
+        let mut tx = state.db_pool.begin().await?;
+        let removed =
+            sqlx::query("DELETE FROM payout_asset_preferences WHERE beneficiary_address = $1")
+                .bind(&wallet_address)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                > 0;
+        if removed {
+            record_audit(
+                &mut *tx,
+                &wallet_address,
+                "payout_asset.clear",
+                &wallet_address,
+                serde_json::json!({}),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            error!(wallet_address = %wallet_address, error = %e, "Failed to clear payout asset");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(token: &str, price_usd: Decimal, decimals: i16) -> TokenPrice {
+        TokenPrice {
+            token_address: token.to_string(),
+            price_usd,
+            decimals,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn quotes_across_token_decimals() {
+        // 1 XLM (7 decimals) at $0.10 buys 0.1 of a $1 token with 6 decimals
+        let quote = Quote::between(
+            &price("CXLM", Decimal::new(1, 1), 7),
+            &price("CUSDC", Decimal::ONE, 6),
+            100,
+        )
+        .unwrap();
+        assert_eq!(quote.rate, Decimal::new(1, 2));
+        assert_eq!(
+            quote.convert(Decimal::from(10_000_000)),
+            Some(Converted {
+                amount: Decimal::from(100_000),
+                min_amount: Decimal::from(99_000),
+            })
+        );
+        assert_eq!(quote.convert(Decimal::from(99)), None);
+    }
+
+    #[test]
+    fn rejects_unusable_preferences() {
+        assert!(validate_preference("CUSDC", 100).is_ok());
+        assert!(validate_preference("", 100).is_err());
+        assert!(validate_preference("C USDC", 100).is_err());
+        assert!(validate_preference("CUSDC", MAX_SLIPPAGE_BPS + 1).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Wallets a beneficiary's crypto payouts are split across.
+//!
+//! A beneficiary can name up to [`MAX_DESTINATIONS`] wallets with shares in
+//! basis points that total 10000. At payout time their share of each plan is
+//! split across those wallets, the last one taking the rounding remainder,
+//! and the parts are recorded as separate payouts under one `split_group`.
+//! The payout batcher submits a split group in a single transaction so the
+//! parts land together. Fiat payouts go to the beneficiary's anchor account
+//! and are never split. Without destinations the beneficiary's own wallet is
+//! paid, as before.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::address_book::is_valid_stellar_address;
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+
+pub const MAX_DESTINATIONS: usize = 10;
+const MAX_LABEL_LEN: usize = 64;
+const TOTAL_BPS: i32 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PayoutDestination {
+    pub destination_address: String,
+    pub share_bps: i32,
+    #[serde(default)]
+    pub label: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplaceDestinationsRequest {
+    /// An empty list pays the beneficiary's own wallet again.
+    pub destinations: Vec<PayoutDestination>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DestinationsResponse {
+    pub beneficiary_address: String,
+    pub destinations: Vec<PayoutDestination>,
+}
+
+/// Checks a full set of destinations; an empty set is always valid.
+pub fn validate_destinations(destinations: &[PayoutDestination]) -> Result<(), String> {
+    if destinations.is_empty() {
+        return Ok(());
+    }
+    if destinations.len() > MAX_DESTINATIONS {
+        return Err(format!(
+            "At most {MAX_DESTINATIONS} payout destinations are allowed"
+        ));
+    }
+    let mut seen = HashSet::new();
+    let mut total = 0;
+    for d in destinations {
+        if !is_valid_stellar_address(&d.destination_address) {
+            return Err(format!(
+                "{} is not a valid Stellar address",
+                d.destination_address
+            ));
+        }
+        if !seen.insert(d.destination_address.as_str()) {
+            return Err(format!(
+                "{} is listed more than once",
+                d.destination_address
+            ));
+        }
+        if !(1..=TOTAL_BPS).contains(&d.share_bps) {
+            return Err("Each share_bps must be between 1 and 10000".to_string());
+        }
+        if d.label.chars().count() > MAX_LABEL_LEN {
+            return Err(format!("Labels are limited to {MAX_LABEL_LEN} characters"));
+        }
+        total += d.share_bps;
+    }
+    if total != TOTAL_BPS {
+        return Err(format!(
+            "Destination shares must total 10000 bps, got {total}"
+        ));
+    }
+    Ok(())
+}
+
+/// Splits `amount` by `shares_bps`, rounding each part down to whole base
+/// units; the last part takes the remainder so nothing is lost.
+pub fn split_amount(amount: Decimal, shares_bps: &[i32]) -> Vec<Decimal> {
+    let mut remaining = amount;
+    shares_bps
+        .iter()
+        .enumerate()
+        .map(|(i, bps)| {
+            if i + 1 == shares_bps.len() {
+                remaining
+            } else {
+                let part = (amount * Decimal::from(*bps) / Decimal::from(TOTAL_BPS)).floor();
+                remaining -= part;
+                part
+            }
+        })
+        .collect()
+}
+
+/// The beneficiary's destinations in a stable order; empty when unset.
+pub async fn load_destinations(
+    conn: &mut PgConnection,
+    beneficiary_address: &str,
+) -> Result<Vec<PayoutDestination>, sqlx::Error> {
+    sqlx::query_as::<_, PayoutDestination>(
+        r#"
+        SELECT destination_address, share_bps, label
+        FROM payout_destinations
+        WHERE beneficiary_address = $1
+        ORDER BY destination_address
+        "#,
+    )
+    .bind(beneficiary_address)
+    .fetch_all(&mut *conn)
+    .await
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: Get Payout Destinations
+pub async fn get_payout_destinations(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let wallet_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result = async {
+        let mut conn = state.db_pool.acquire().await?;
+        load_destinations(&mut conn, &wallet_address).await
+    }
+    .await;
+
+    match result {
+        Ok(destinations) => (
+            StatusCode::OK,
+            Json(DestinationsResponse {
+                beneficiary_address: wallet_address,
+                destinations,
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(wallet_address = %wallet_address, error = %e, "Failed to load payout destinations");
+            database_error()
+        }
+    }
+}
+
+// Handler: Replace Payout Destinations
+pub async fn replace_payout_destinations(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<ReplaceDestinationsRequest>,
+) -> impl IntoResponse {
+    let wallet_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = user.require_recently_verified() {
+        return e.into_response();
+    }
+    let mut destinations = payload.destinations;
+    for d in &mut destinations {
+        d.destination_address = d.destination_address.trim().to_string();
+        d.label = d.label.trim().to_string();
+    }
+    if let Err(message) = validate_destinations(&destinations) {
+        return refused(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let result = async {
+        let mut tx = state.db_pool.begin().await?;
+        sqlx::query("DELETE FROM payout_destinations WHERE beneficiary_address = $1")
+            .bind(&wallet_address)
+            .execute(&mut *tx)
+            .await?;
+        for d in &destinations {
+            sqlx::query(
+                r#"
+                INSERT INTO payout_destinations
+                    (beneficiary_address, destination_address, share_bps, label)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(&wallet_address)
+            .bind(&d.destination_address)
+            .bind(d.share_bps)
+            .bind(&d.label)
+            .execute(&mut *tx)
+            .await?;
+        }
+        record_audit(
+            &mut *tx,
+            &wallet_address,
+            "payout_destinations.update",
+            &wallet_address,
+            serde_json::json!({ "destinations": destinations }),
+        )
+        .await?;
+        let stored = load_destinations(&mut tx, &wallet_address).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(stored)
+    }
+    .await;
+
+    match result {
+        Ok(destinations) => {
+            info!(
+                wallet_address = %wallet_address,
+                count = destinations.len(),
+                "Payout destinations updated"
+            );
+            (
+                StatusCode::OK,
+                Json(DestinationsResponse {
+                    beneficiary_address: wallet_address,
+                    destinations,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(wallet_address = %wallet_address, error = %e, "Failed to update payout destinations");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn destination(seed: u8, share_bps: i32) -> PayoutDestination {
+        PayoutDestination {
+            destination_address: stellar_strkey::ed25519::PublicKey([seed; 32]).to_string(),
+            share_bps,
+            label: String::new(),
+        }
+    }
+
+    #[test]
+    fn shares_must_total_ten_thousand_without_duplicates() {
+        assert!(validate_destinations(&[]).is_ok());
+        assert!(validate_destinations(&[destination(1, 8_000), destination(2, 2_000)]).is_ok());
+        assert!(validate_destinations(&[destination(1, 8_000), destination(2, 1_000)]).is_err());
+        assert!(validate_destinations(&[destination(1, 5_000), destination(1, 5_000)]).is_err());
+        assert!(validate_destinations(&[destination(1, 10_000), destination(2, 0)]).is_err());
+
+        let mut invalid = destination(1, 10_000);
+        invalid.destination_address = "savings".to_string();
+        assert!(validate_destinations(&[invalid]).is_err());
+
+        let too_many: Vec<_> = (0..11).map(|i| destination(i, 1)).collect();
+        assert!(validate_destinations(&too_many).is_err());
+    }
+
+    #[test]
+    fn split_keeps_the_whole_amount() {
+        let parts = split_amount(Decimal::from(1_001), &[8_000, 2_000]);
+        assert_eq!(parts, vec![Decimal::from(800), Decimal::from(201)]);
+
+        let parts = split_amount(Decimal::from(10), &[3_333, 3_333, 3_334]);
+        assert_eq!(parts.iter().sum::<Decimal>(), Decimal::from(10));
+        assert_eq!(parts[0], Decimal::from(3));
+    }
+}
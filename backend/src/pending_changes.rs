@@ -0,0 +1,517 @@
+//! Maker-checker review for platform settings changes.
+//!
+//! One admin proposes a change to a setting as a JSON merge patch. A
+//! different admin must approve it before it is applied. Proposals expire
+//! after `PENDING_CHANGE_TTL_HOURS`, and a proposal is superseded if the
+//! setting changes after it was made. Every step is audit logged.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::UserContext;
+use crate::platform_settings::{self, merge_patch, SettingKey};
+
+const CHANGE_COLUMNS: &str = "id, setting_key, changes, base_version, reason, status, \
+     proposed_by, proposed_at, expires_at, reviewed_by, reviewed_at, review_note";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PendingChange {
+    pub id: Uuid,
+    pub setting_key: String,
+    pub changes: Value,
+    pub base_version: i32,
+    pub reason: String,
+    pub status: String,
+    pub proposed_by: String,
+    pub proposed_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProposeChangeRequest {
+    pub setting_key: SettingKey,
+    pub changes: Value,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ReviewRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingChangeQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettingView {
+    pub key: SettingKey,
+    pub value: Value,
+    /// Zero until a change has been applied.
+    pub version: i32,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// Marks pending proposals past their expiry as expired.
+pub async fn expire_stale(db: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let expired: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        UPDATE pending_changes
+        SET status = 'expired'
+        WHERE status = 'pending' AND expires_at <= NOW()
+        RETURNING id, setting_key
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for (id, setting_key) in &expired {
+        record_audit(
+            &mut *tx,
+            SYSTEM_ACTOR,
+            "settings.change_expired",
+            &id.to_string(),
+            serde_json::json!({ "setting_key": setting_key }),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(expired.len() as u64)
+}
+
+async fn current_setting(
+    conn: &mut sqlx::PgConnection,
+    state: &AppState,
+    key: SettingKey,
+    for_update: bool,
+) -> Result<(Value, i32), sqlx::Error> {
+    let stored = if for_update {
+        sqlx::query_as::<_, (Value, i32)>(
+            "SELECT value, version FROM platform_settings WHERE key = $1 FOR UPDATE",
+        )
+        .bind(key.as_str())
+        .fetch_optional(&mut *conn)
+        .await?
+    } else {
+        platform_settings::load(&mut *conn, key)
+            .await?
+            .map(|setting| (setting.value, setting.version))
+    };
+    Ok(stored.unwrap_or_else(|| (key.default_value(&state.config, &state.apy_config), 0)))
+}
+
+// Handler: List Settings
+pub async fn list_settings(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let stored = match sqlx::query_as::<_, platform_settings::PlatformSetting>(
+        "SELECT key, value, version, updated_by, updated_at FROM platform_settings",
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(error = %e, "Failed to load platform settings");
+            return database_error();
+        }
+    };
+
+    let settings: Vec<SettingView> = SettingKey::ALL
+        .into_iter()
+        .map(|key| match stored.iter().find(|s| s.key == key.as_str()) {
+            Some(setting) => SettingView {
+                key,
+                value: setting.value.clone(),
+                version: setting.version,
+                updated_by: Some(setting.updated_by.clone()),
+                updated_at: Some(setting.updated_at),
+            },
+            None => SettingView {
+                key,
+                value: key.default_value(&state.config, &state.apy_config),
+                version: 0,
+                updated_by: None,
+                updated_at: None,
+            },
+        })
+        .collect();
+    (StatusCode::OK, Json(settings)).into_response()
+}
+
+// Handler: Propose Change
+pub async fn propose_change(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(payload): Json<ProposeChangeRequest>,
+) -> impl IntoResponse {
+    let reason = payload.reason.trim();
+    if reason.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "A reason is required");
+    }
+    if !payload.changes.is_object() {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "changes must be a JSON object merge patch",
+        );
+    }
+
+    let result: Result<Result<PendingChange, String>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let (current, version) =
+            current_setting(&mut tx, &state, payload.setting_key, false).await?;
+        let proposed = merge_patch(&current, &payload.changes);
+        if let Err(message) = payload.setting_key.validate(&proposed) {
+            return Ok(Err(message));
+        }
+        if proposed == current {
+            return Ok(Err("The change does not modify the setting".to_string()));
+        }
+
+        let expires_at =
+            Utc::now() + Duration::hours(i64::from(state.config.pending_change_ttl_hours));
+        let change = sqlx::query_as::<_, PendingChange>(&format!(
+            r#"
+            INSERT INTO pending_changes (setting_key, changes, base_version, reason, proposed_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {CHANGE_COLUMNS}
+            "#
+        ))
+        .bind(payload.setting_key.as_str())
+        .bind(&payload.changes)
+        .bind(version)
+        .bind(reason)
+        .bind(&admin.user_id)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "settings.change_proposed",
+            &change.id.to_string(),
+            serde_json::json!({
+                "setting_key": change.setting_key,
+                "changes": change.changes,
+                "base_version": version,
+                "reason": reason,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Ok(change))
+    }
+    .await;
+
+    match result {
+        Ok(Ok(change)) => {
+            info!(change_id = %change.id, admin = %admin.user_id, "Settings change proposed");
+            (StatusCode::CREATED, Json(change)).into_response()
+        }
+        Ok(Err(message)) => error_response(StatusCode::UNPROCESSABLE_ENTITY, &message),
+        Err(e) => {
+            error!(error = %e, "Failed to propose settings change");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Pending Changes
+pub async fn list_pending_changes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PendingChangeQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = expire_stale(&state.db_pool).await {
+        error!(error = %e, "Failed to expire stale settings changes");
+        return database_error();
+    }
+
+    let status = query.status.unwrap_or_else(|| "pending".to_string());
+    match sqlx::query_as::<_, PendingChange>(&format!(
+        "SELECT {CHANGE_COLUMNS} FROM pending_changes WHERE status = $1 ORDER BY proposed_at DESC LIMIT 200"
+    ))
+    .bind(&status)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(changes) => (StatusCode::OK, Json(changes)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list settings changes");
+            database_error()
+        }
+    }
+}
+
+enum ReviewOutcome {
+    Reviewed(Box<PendingChange>),
+    Refused(StatusCode, &'static str),
+}
+
+/// Loads and locks a pending change, refusing reviews by the proposer and
+/// of changes that are no longer pending. An expired change is marked as
+/// such and committed before refusing.
+async fn lock_for_review(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    change_id: Uuid,
+    reviewer: &str,
+) -> Result<Result<PendingChange, ReviewOutcome>, sqlx::Error> {
+    let Some(change) = sqlx::query_as::<_, PendingChange>(&format!(
+        "SELECT {CHANGE_COLUMNS} FROM pending_changes WHERE id = $1 FOR UPDATE"
+    ))
+    .bind(change_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    else {
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::NOT_FOUND,
+            "Pending change not found",
+        )));
+    };
+    if change.status != "pending" {
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::CONFLICT,
+            "Change has already been reviewed",
+        )));
+    }
+    if change.expires_at <= Utc::now() {
+        let expired = sqlx::query_as::<_, PendingChange>(&format!(
+            "UPDATE pending_changes SET status = 'expired' WHERE id = $1 RETURNING {CHANGE_COLUMNS}"
+        ))
+        .bind(change_id)
+        .fetch_one(&mut **tx)
+        .await?;
+        record_audit(
+            &mut **tx,
+            SYSTEM_ACTOR,
+            "settings.change_expired",
+            &change_id.to_string(),
+            serde_json::json!({ "setting_key": expired.setting_key }),
+        )
+        .await?;
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::CONFLICT,
+            "Change has expired",
+        )));
+    }
+    if change.proposed_by == reviewer {
+        return Ok(Err(ReviewOutcome::Refused(
+            StatusCode::FORBIDDEN,
+            "Changes must be reviewed by a different admin",
+        )));
+    }
+    Ok(Ok(change))
+}
+
+fn review_response(result: Result<ReviewOutcome, sqlx::Error>, change_id: Uuid) -> Response {
+    match result {
+        Ok(ReviewOutcome::Reviewed(change)) => (StatusCode::OK, Json(change)).into_response(),
+        Ok(ReviewOutcome::Refused(status, message)) => error_response(status, message),
+        Err(e) => {
+            error!(change_id = %change_id, error = %e, "Failed to review settings change");
+            database_error()
+        }
+    }
+}
+
+async fn mark_reviewed(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    change_id: Uuid,
+    status: &str,
+    reviewer: &str,
+    note: Option<&str>,
+) -> Result<PendingChange, sqlx::Error> {
+    sqlx::query_as::<_, PendingChange>(&format!(
+        r#"
+        UPDATE pending_changes
+        SET status = $2, reviewed_by = $3, reviewed_at = NOW(), review_note = $4
+        WHERE id = $1
+        RETURNING {CHANGE_COLUMNS}
+        "#
+    ))
+    .bind(change_id)
+    .bind(status)
+    .bind(reviewer)
+    .bind(note)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+// Handler: Approve Change
+pub async fn approve_change(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(change_id): Path<Uuid>,
+    payload: Option<Json<ReviewRequest>>,
+) -> impl IntoResponse {
+    let note = payload
+        .and_then(|Json(p)| p.note)
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    let result: Result<ReviewOutcome, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let change = match lock_for_review(&mut tx, change_id, &admin.user_id).await? {
+            Ok(change) => change,
+            Err(refused) => {
+                tx.commit().await?;
+                return Ok(refused);
+            }
+        };
+        let Some(key) = SettingKey::parse(&change.setting_key) else {
+            return Ok(ReviewOutcome::Refused(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Unknown setting",
+            ));
+        };
+
+        let (current, version) = current_setting(&mut tx, &state, key, true).await?;
+        if version != change.base_version {
+            mark_reviewed(
+                &mut tx,
+                change_id,
+                "superseded",
+                &admin.user_id,
+                Some("Setting changed after this proposal was made"),
+            )
+            .await?;
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "settings.change_superseded",
+                &change_id.to_string(),
+                serde_json::json!({
+                    "setting_key": change.setting_key,
+                    "base_version": change.base_version,
+                    "current_version": version,
+                }),
+            )
+            .await?;
+            tx.commit().await?;
+            return Ok(ReviewOutcome::Refused(
+                StatusCode::CONFLICT,
+                "Setting changed after this proposal was made; propose it again",
+            ));
+        }
+
+        let updated = merge_patch(&current, &change.changes);
+        if key.validate(&updated).is_err() {
+            return Ok(ReviewOutcome::Refused(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Change no longer produces a valid setting",
+            ));
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO platform_settings (key, value, version, updated_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (key)
+            DO UPDATE SET value = EXCLUDED.value, version = EXCLUDED.version,
+                          updated_by = EXCLUDED.updated_by, updated_at = NOW()
+            "#,
+        )
+        .bind(key.as_str())
+        .bind(&updated)
+        .bind(version + 1)
+        .bind(&admin.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let applied = mark_reviewed(
+            &mut tx,
+            change_id,
+            "applied",
+            &admin.user_id,
+            note.as_deref(),
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "settings.change_applied",
+            &change_id.to_string(),
+            serde_json::json!({
+                "setting_key": applied.setting_key,
+                "proposed_by": applied.proposed_by,
+                "before": current,
+                "after": updated,
+                "version": version + 1,
+                "note": note,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        info!(change_id = %change_id, admin = %admin.user_id, "Settings change applied");
+        Ok(ReviewOutcome::Reviewed(Box::new(applied)))
+    }
+    .await;
+
+    review_response(result, change_id)
+}
+
+// Handler: Reject Change
+pub async fn reject_change(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(change_id): Path<Uuid>,
+    Json(payload): Json<ReviewRequest>,
+) -> impl IntoResponse {
+    let Some(note) = payload
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+    else {
+        return error_response(StatusCode::BAD_REQUEST, "A note is required to reject");
+    };
+
+    let result: Result<ReviewOutcome, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if let Err(refused) = lock_for_review(&mut tx, change_id, &admin.user_id).await? {
+            tx.commit().await?;
+            return Ok(refused);
+        }
+        let rejected =
+            mark_reviewed(&mut tx, change_id, "rejected", &admin.user_id, Some(&note)).await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "settings.change_rejected",
+            &change_id.to_string(),
+            serde_json::json!({
+                "setting_key": rejected.setting_key,
+                "proposed_by": rejected.proposed_by,
+                "note": note,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        info!(change_id = %change_id, admin = %admin.user_id, "Settings change rejected");
+        Ok(ReviewOutcome::Reviewed(Box::new(rejected)))
+    }
+    .await;
+
+    review_response(result, change_id)
+}
@@ -0,0 +1,502 @@
+//! Event stream of the plan aggregate and rebuilding its projection.
+//!
+//! Triggers on `plans` and `beneficiaries` append a domain event to
+//! `plan_events` for every change, whichever code path made it, so the
+//! stream is the full history of each plan: `PlanCreated`, `PlanUpdated`,
+//! `Claimed` (the plan was paid out), `PlanDeleted`, `BeneficiaryAdded`,
+//! `AllocationChanged`, `BeneficiaryUpdated` and `BeneficiaryRemoved`.
+//! Updates carry only the columns that changed.
+//!
+//! The two tables are the projection of the stream. [`rebuild`] folds each
+//! stream into a [`PlanAggregate`] and compares it with the tables; with
+//! `apply` it writes the folded state back, creating rows that are missing.
+//! `inheritx-cli rebuild-plans` runs it.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{PgConnection, PgPool};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::field_crypto::{FieldCipher, SensitiveField};
+
+const EVENT_COLUMNS: &str = "id, plan_id, sequence, event_type, payload, occurred_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanEventType {
+    PlanCreated,
+    PlanUpdated,
+    Claimed,
+    PlanDeleted,
+    BeneficiaryAdded,
+    AllocationChanged,
+    BeneficiaryUpdated,
+    BeneficiaryRemoved,
+}
+
+impl PlanEventType {
+    pub const ALL: [Self; 8] = [
+        Self::PlanCreated,
+        Self::PlanUpdated,
+        Self::Claimed,
+        Self::PlanDeleted,
+        Self::BeneficiaryAdded,
+        Self::AllocationChanged,
+        Self::BeneficiaryUpdated,
+        Self::BeneficiaryRemoved,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PlanCreated => "PlanCreated",
+            Self::PlanUpdated => "PlanUpdated",
+            Self::Claimed => "Claimed",
+            Self::PlanDeleted => "PlanDeleted",
+            Self::BeneficiaryAdded => "BeneficiaryAdded",
+            Self::AllocationChanged => "AllocationChanged",
+            Self::BeneficiaryUpdated => "BeneficiaryUpdated",
+            Self::BeneficiaryRemoved => "BeneficiaryRemoved",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PlanEvent {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    /// Position in the plan's stream, from 1.
+    pub sequence: i64,
+    pub event_type: String,
+    pub payload: Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// A plan folded from its events, in the row encoding the triggers use.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanAggregate {
+    plan: Option<Map<String, Value>>,
+    beneficiaries: BTreeMap<String, Map<String, Value>>,
+    /// Sequence of the last event applied.
+    pub version: i64,
+}
+
+fn merge(target: &mut Map<String, Value>, changes: Option<&Value>) {
+    if let Some(Value::Object(changes)) = changes {
+        for (key, value) in changes {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+fn id_of(payload: &Value) -> Option<String> {
+    payload
+        .get("id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+impl PlanAggregate {
+    pub fn from_events(events: &[PlanEvent]) -> Self {
+        let mut aggregate = Self::default();
+        for event in events {
+            aggregate.apply(event);
+        }
+        aggregate
+    }
+
+    pub fn apply(&mut self, event: &PlanEvent) {
+        self.version = event.sequence;
+        let Some(kind) = PlanEventType::parse(&event.event_type) else {
+            warn!(event_id = %event.id, event_type = %event.event_type, "Skipping unknown plan event");
+            return;
+        };
+        let payload = &event.payload;
+        match kind {
+            PlanEventType::PlanCreated => {
+                self.plan = payload.as_object().cloned();
+                self.beneficiaries.clear();
+            }
+            PlanEventType::PlanUpdated | PlanEventType::Claimed => {
+                if let Some(plan) = self.plan.as_mut() {
+                    merge(plan, payload.get("changes"));
+                }
+            }
+            PlanEventType::PlanDeleted => {
+                self.plan = None;
+                self.beneficiaries.clear();
+            }
+            PlanEventType::BeneficiaryAdded => {
+                if let (Some(id), Some(row)) = (id_of(payload), payload.as_object()) {
+                    self.beneficiaries.insert(id, row.clone());
+                }
+            }
+            PlanEventType::AllocationChanged | PlanEventType::BeneficiaryUpdated => {
+                let row = id_of(payload).and_then(|id| self.beneficiaries.get_mut(&id));
+                if let Some(row) = row {
+                    merge(row, payload.get("changes"));
+                }
+            }
+            PlanEventType::BeneficiaryRemoved => {
+                if let Some(id) = id_of(payload) {
+                    self.beneficiaries.remove(&id);
+                }
+            }
+        }
+    }
+
+    /// The plan row and its beneficiaries ordered by wallet address, or
+    /// `None` once deleted.
+    pub fn state(&self) -> Option<(Value, Vec<Value>)> {
+        let plan = self.plan.clone()?;
+        let mut beneficiaries: Vec<&Map<String, Value>> = self.beneficiaries.values().collect();
+        beneficiaries.sort_by_key(|b| {
+            b.get("wallet_address")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        });
+        Some((
+            Value::Object(plan),
+            beneficiaries
+                .into_iter()
+                .map(|b| Value::Object(b.clone()))
+                .collect(),
+        ))
+    }
+}
+
+pub async fn load_events(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+) -> Result<Vec<PlanEvent>, sqlx::Error> {
+    sqlx::query_as::<_, PlanEvent>(&format!(
+        "SELECT {EVENT_COLUMNS} FROM plan_events WHERE plan_id = $1 ORDER BY sequence"
+    ))
+    .bind(plan_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// The plan as the tables hold it now, in the event encoding.
+async fn projected_state(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+) -> Result<Option<(Value, Vec<Value>)>, sqlx::Error> {
+    let row: Option<(Value, Value)> = sqlx::query_as(
+        r#"
+        SELECT plan_event_row(p),
+               COALESCE((SELECT jsonb_agg(beneficiary_event_row(b) ORDER BY b.wallet_address)
+                         FROM beneficiaries b WHERE b.plan_id = p.id), '[]'::jsonb)
+        FROM plans p WHERE p.id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_optional(conn)
+    .await?;
+    Ok(row.map(|(plan, beneficiaries)| {
+        let beneficiaries = match beneficiaries {
+            Value::Array(rows) => rows,
+            _ => Vec::new(),
+        };
+        (plan, beneficiaries)
+    }))
+}
+
+/// Writable columns of `table`, to copy a JSON row into it.
+async fn writable_columns(
+    conn: &mut PgConnection,
+    table: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT column_name::text FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = $1 AND is_generated = 'NEVER'
+        ORDER BY ordinal_position
+        "#,
+    )
+    .bind(table)
+    .fetch_all(conn)
+    .await
+}
+
+fn upsert_sql(table: &str, columns: &[String]) -> String {
+    let list = columns
+        .iter()
+        .map(|c| format!("\"{c}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let updates = columns
+        .iter()
+        .filter(|c| c.as_str() != "id")
+        .map(|c| format!("\"{c}\" = EXCLUDED.\"{c}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "INSERT INTO {table} ({list}) SELECT {list} FROM jsonb_populate_record(NULL::{table}, $1) \
+         ON CONFLICT (id) DO UPDATE SET {updates}"
+    )
+}
+
+/// Writes `plan` and `beneficiaries` over the projection of `plan_id`.
+/// Runs with `inheritx.replaying` set, so no events are appended.
+async fn write_projection(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    plan: &Value,
+    beneficiaries: &[Value],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT set_config('inheritx.replaying', 'on', true)")
+        .execute(&mut *conn)
+        .await?;
+    let plan_columns = writable_columns(conn, "plans").await?;
+    sqlx::query(&upsert_sql("plans", &plan_columns))
+        .bind(plan)
+        .execute(&mut *conn)
+        .await?;
+
+    let ids: Vec<Uuid> = beneficiaries
+        .iter()
+        .filter_map(|b| id_of(b).and_then(|id| Uuid::parse_str(&id).ok()))
+        .collect();
+    sqlx::query("DELETE FROM beneficiaries WHERE plan_id = $1 AND NOT (id = ANY($2))")
+        .bind(plan_id)
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?;
+    // Allocations are checked row by row, so clear them before restoring.
+    sqlx::query("UPDATE beneficiaries SET allocation_bps = 0 WHERE plan_id = $1")
+        .bind(plan_id)
+        .execute(&mut *conn)
+        .await?;
+    let beneficiary_columns = writable_columns(conn, "beneficiaries").await?;
+    let sql = upsert_sql("beneficiaries", &beneficiary_columns);
+    for beneficiary in beneficiaries {
+        let mut row = beneficiary.clone();
+        if let Value::Object(fields) = &mut row {
+            fields.insert("plan_id".to_string(), Value::String(plan_id.to_string()));
+        }
+        sqlx::query(&sql).bind(row).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RebuildReport {
+    /// Plans whose streams were replayed.
+    pub plans_checked: usize,
+    /// Plans whose tables did not match their events.
+    pub drifted: Vec<Uuid>,
+    /// Drifted plans written back from their events.
+    pub repaired: usize,
+    /// Plans deleted in their stream that still have rows; they are only
+    /// reported, never deleted.
+    pub deleted_in_stream: Vec<Uuid>,
+}
+
+/// Replays the event streams of `plan_id`, or of every plan, and compares
+/// the result with the tables. With `apply`, drifted plans are rewritten
+/// from their events.
+pub async fn rebuild(
+    db: &PgPool,
+    plan_id: Option<Uuid>,
+    apply: bool,
+) -> Result<RebuildReport, sqlx::Error> {
+    let plan_ids: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT DISTINCT plan_id FROM plan_events WHERE $1::uuid IS NULL OR plan_id = $1 ORDER BY plan_id",
+    )
+    .bind(plan_id)
+    .fetch_all(db)
+    .await?;
+
+    let mut report = RebuildReport::default();
+    for plan_id in plan_ids {
+        let mut tx = db.begin().await?;
+        sqlx::query("SELECT id FROM plans WHERE id = $1 FOR UPDATE")
+            .bind(plan_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let aggregate = PlanAggregate::from_events(&load_events(&mut tx, plan_id).await?);
+        let current = projected_state(&mut tx, plan_id).await?;
+        report.plans_checked += 1;
+
+        let Some((plan, beneficiaries)) = aggregate.state() else {
+            if current.is_some() {
+                report.deleted_in_stream.push(plan_id);
+            }
+            continue;
+        };
+        if current.as_ref() == Some(&(plan.clone(), beneficiaries.clone())) {
+            continue;
+        }
+        report.drifted.push(plan_id);
+        warn!(plan_id = %plan_id, version = aggregate.version, "Plan projection differs from its events");
+        if apply {
+            write_projection(&mut tx, plan_id, &plan, &beneficiaries).await?;
+            tx.commit().await?;
+            report.repaired += 1;
+            info!(plan_id = %plan_id, "Plan projection rebuilt from events");
+        }
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Only events after this sequence number, for paging forward.
+    pub after: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlanEventsResponse {
+    pub plan_id: Uuid,
+    /// Oldest first.
+    pub events: Vec<PlanEvent>,
+}
+
+/// Opens the sealed beneficiary fields that events copy from the rows.
+fn decrypt_event(cipher: &FieldCipher, event: &mut PlanEvent) -> Result<(), sqlx::Error> {
+    let fields = match event.payload.get_mut("changes") {
+        Some(changes) => changes,
+        None => &mut event.payload,
+    };
+    if let Some(Value::String(info)) = fields.get_mut("fiat_anchor_info") {
+        *info = cipher.decrypt_column(SensitiveField::BeneficiaryAnchorInfo, info)?;
+    }
+    Ok(())
+}
+
+// Handler: Admin Get Plan Events
+pub async fn admin_get_plan_events(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+    Query(query): Query<EventsQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let result: Result<Vec<PlanEvent>, sqlx::Error> = async {
+        let mut events = sqlx::query_as::<_, PlanEvent>(&format!(
+            r#"
+            SELECT {EVENT_COLUMNS} FROM plan_events
+            WHERE plan_id = $1 AND sequence > $2
+            ORDER BY sequence
+            LIMIT $3
+            "#
+        ))
+        .bind(plan_id)
+        .bind(query.after.unwrap_or(0))
+        .bind(limit)
+        .fetch_all(&state.db_pool)
+        .await?;
+        for event in &mut events {
+            decrypt_event(&state.field_cipher, event)?;
+        }
+        Ok(events)
+    }
+    .await;
+
+    match result {
+        Ok(events) if events.is_empty() && query.after.is_none() => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Plan events not found" })),
+        )
+            .into_response(),
+        Ok(events) => {
+            (StatusCode::OK, Json(PlanEventsResponse { plan_id, events })).into_response()
+        }
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan events");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(sequence: i64, kind: PlanEventType, payload: Value) -> PlanEvent {
+        PlanEvent {
+            id: Uuid::new_v4(),
+            plan_id: Uuid::nil(),
+            sequence,
+            event_type: kind.as_str().to_string(),
+            payload,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn events_fold_into_plan_state() {
+        let events = [
+            event(
+                1,
+                PlanEventType::PlanCreated,
+                json!({ "id": "p", "amount": "100", "status": "ACTIVE" }),
+            ),
+            event(
+                2,
+                PlanEventType::BeneficiaryAdded,
+                json!({ "id": "b2", "wallet_address": "GB", "allocation_bps": 10000 }),
+            ),
+            event(
+                3,
+                PlanEventType::AllocationChanged,
+                json!({ "id": "b2", "changes": { "allocation_bps": 4000 } }),
+            ),
+            event(
+                4,
+                PlanEventType::BeneficiaryAdded,
+                json!({ "id": "b1", "wallet_address": "GA", "allocation_bps": 6000 }),
+            ),
+            event(
+                5,
+                PlanEventType::Claimed,
+                json!({ "changes": { "status": "PAID_OUT" } }),
+            ),
+        ];
+        let aggregate = PlanAggregate::from_events(&events);
+        assert_eq!(aggregate.version, 5);
+        let (plan, beneficiaries) = aggregate.state().unwrap();
+        assert_eq!(
+            plan,
+            json!({ "id": "p", "amount": "100", "status": "PAID_OUT" })
+        );
+        assert_eq!(
+            beneficiaries,
+            vec![
+                json!({ "id": "b1", "wallet_address": "GA", "allocation_bps": 6000 }),
+                json!({ "id": "b2", "wallet_address": "GB", "allocation_bps": 4000 }),
+            ]
+        );
+
+        let mut deleted = aggregate;
+        deleted.apply(&event(6, PlanEventType::PlanDeleted, json!({})));
+        assert_eq!(deleted.state(), None);
+    }
+
+    #[test]
+    fn upserts_skip_the_key_column() {
+        let sql = upsert_sql("plans", &["id".to_string(), "amount".to_string()]);
+        assert!(sql.contains("ON CONFLICT (id) DO UPDATE SET \"amount\" = EXCLUDED.\"amount\""));
+        assert!(!sql.contains("\"id\" = EXCLUDED"));
+    }
+}
@@ -0,0 +1,245 @@
+//! Read access to the `plan_snapshots` history.
+//!
+//! Snapshots are written by a database trigger at the end of every
+//! transaction that touches a plan or its beneficiaries, so this module
+//! only reads. Wallet callers may see the history of any plan they have
+//! ever owned or been a beneficiary of; admins may see every plan.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::field_crypto::{FieldCipher, SensitiveField};
+
+const SNAPSHOT_COLUMNS: &str = "id, plan_id, change_kind, state, captured_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PlanSnapshot {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    /// `baseline`, `created`, `updated` or `deleted`.
+    pub change_kind: String,
+    /// Plan row with its beneficiaries; `null` after deletion.
+    pub state: Option<serde_json::Value>,
+    pub captured_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    /// Only snapshots captured before this time, for paging back.
+    pub before: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AsOfQuery {
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlanHistoryResponse {
+    pub plan_id: Uuid,
+    /// Newest first.
+    pub snapshots: Vec<PlanSnapshot>,
+}
+
+/// Whether `address` owns or inherits from the plan in any snapshot.
+async fn has_access(db: &sqlx::PgPool, plan_id: Uuid, address: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM plan_snapshots
+            WHERE plan_id = $1
+              AND (state->>'owner_address' = $2
+                   OR state->'beneficiaries' @> jsonb_build_array(
+                          jsonb_build_object('wallet_address', $2::text)))
+        )
+        "#,
+    )
+    .bind(plan_id)
+    .bind(address)
+    .fetch_one(db)
+    .await
+}
+
+/// Snapshots copy beneficiary rows verbatim, so encrypted fields are
+/// opened here the same way as on the live tables.
+pub(crate) fn decrypt_snapshot(
+    cipher: &FieldCipher,
+    snapshot: &mut PlanSnapshot,
+) -> Result<(), sqlx::Error> {
+    let Some(beneficiaries) = snapshot
+        .state
+        .as_mut()
+        .and_then(|state| state.get_mut("beneficiaries"))
+        .and_then(|b| b.as_array_mut())
+    else {
+        return Ok(());
+    };
+    for beneficiary in beneficiaries {
+        if let Some(serde_json::Value::String(info)) = beneficiary.get_mut("fiat_anchor_info") {
+            *info = cipher.decrypt_column(SensitiveField::BeneficiaryAnchorInfo, info)?;
+        }
+    }
+    Ok(())
+}
+
+fn not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Plan history not found" })),
+    )
+        .into_response()
+}
+
+async fn history(
+    state: &AppState,
+    plan_id: Uuid,
+    caller: Option<&str>,
+    query: HistoryQuery,
+) -> axum::response::Response {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let result: Result<Option<Vec<PlanSnapshot>>, sqlx::Error> = async {
+        if let Some(address) = caller {
+            if !has_access(&state.db_pool, plan_id, address).await? {
+                return Ok(None);
+            }
+        }
+        let mut snapshots = sqlx::query_as::<_, PlanSnapshot>(&format!(
+            r#"
+            SELECT {SNAPSHOT_COLUMNS} FROM plan_snapshots
+            WHERE plan_id = $1 AND ($2::timestamptz IS NULL OR captured_at < $2)
+            ORDER BY captured_at DESC
+            LIMIT $3
+            "#
+        ))
+        .bind(plan_id)
+        .bind(query.before)
+        .bind(limit)
+        .fetch_all(&state.db_pool)
+        .await?;
+        for snapshot in &mut snapshots {
+            decrypt_snapshot(&state.field_cipher, snapshot)?;
+        }
+        Ok(Some(snapshots))
+    }
+    .await;
+
+    match result {
+        Ok(Some(snapshots)) if !snapshots.is_empty() || query.before.is_some() => (
+            StatusCode::OK,
+            Json(PlanHistoryResponse { plan_id, snapshots }),
+        )
+            .into_response(),
+        Ok(_) => not_found(),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan history");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn as_of(
+    state: &AppState,
+    plan_id: Uuid,
+    caller: Option<&str>,
+    timestamp: DateTime<Utc>,
+) -> axum::response::Response {
+    let result: Result<Option<PlanSnapshot>, sqlx::Error> = async {
+        if let Some(address) = caller {
+            if !has_access(&state.db_pool, plan_id, address).await? {
+                return Ok(None);
+            }
+        }
+        let mut snapshot = sqlx::query_as::<_, PlanSnapshot>(&format!(
+            r#"
+            SELECT {SNAPSHOT_COLUMNS} FROM plan_snapshots
+            WHERE plan_id = $1 AND captured_at <= $2
+            ORDER BY captured_at DESC
+            LIMIT 1
+            "#
+        ))
+        .bind(plan_id)
+        .bind(timestamp)
+        .fetch_optional(&state.db_pool)
+        .await?;
+        if let Some(snapshot) = snapshot.as_mut() {
+            decrypt_snapshot(&state.field_cipher, snapshot)?;
+        }
+        Ok(snapshot)
+    }
+    .await;
+
+    match result {
+        Ok(Some(snapshot)) => (StatusCode::OK, Json(snapshot)).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan snapshot");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// Handler: Get Plan History
+pub async fn get_plan_history(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    history(&state, plan_id, Some(&caller), query).await
+}
+
+// Handler: Get Plan As Of
+pub async fn get_plan_as_of(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Query(query): Query<AsOfQuery>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    as_of(&state, plan_id, Some(&caller), query.timestamp).await
+}
+
+// Handler: Admin Get Plan History
+pub async fn admin_get_plan_history(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    history(&state, plan_id, None, query).await
+}
+
+// Handler: Admin Get Plan As Of
+pub async fn admin_get_plan_as_of(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+    Query(query): Query<AsOfQuery>,
+) -> impl IntoResponse {
+    as_of(&state, plan_id, None, query.timestamp).await
+}
@@ -0,0 +1,489 @@
+//! Anchors a hash of each plan's terms on-chain.
+//!
+//! A plan's canonical document ([`PlanDocument`]) holds its terms and
+//! beneficiaries, serialized with a fixed field order and beneficiaries
+//! sorted by wallet. Fiat payout details are left out because they are
+//! private and re-encrypted over time. [`PlanMetadataService`] rechecks plans
+//! with new history snapshots, and whenever the document's SHA-256 differs
+//! from the last anchored one it calls the contract's
+//! `set_metadata_hash(owner, hash)`. Every anchored document is kept, so an
+//! admin can show which version of a plan was in force at a given time.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::chain::{ContractInvocation, TxService};
+use crate::jobs::JobRegistry;
+use crate::telemetry;
+
+const DEFAULT_INTERVAL_SECS: u64 = 5 * 60;
+const DEFAULT_BATCH_SIZE: i64 = 100;
+const PLAN_METADATA_LOCK_KEY: i64 = 834;
+/// Bumped when [`PlanDocument`] changes shape, so old hashes stay
+/// reproducible.
+pub const DOCUMENT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentBeneficiary {
+    pub wallet_address: String,
+    pub allocation_bps: i32,
+    /// Paid through a fiat anchor rather than on-chain.
+    pub fiat_payout: bool,
+}
+
+/// The terms of a plan as anchored. Field order is part of the format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanDocument {
+    pub version: u32,
+    pub plan_id: Uuid,
+    pub owner_address: String,
+    pub token_address: String,
+    /// Principal in base units, as a decimal string.
+    pub amount: String,
+    pub grace_period_seconds: i64,
+    pub earn_yield: bool,
+    pub yield_rate_bps: i32,
+    pub installment_count: i32,
+    pub installment_interval_days: i32,
+    /// Sorted by wallet address.
+    pub beneficiaries: Vec<DocumentBeneficiary>,
+}
+
+impl PlanDocument {
+    /// Canonical form: beneficiaries sorted, amount without trailing zeros.
+    pub fn canonicalize(mut self) -> Self {
+        self.beneficiaries
+            .sort_by(|a, b| a.wallet_address.cmp(&b.wallet_address));
+        if let Ok(amount) = Decimal::from_str(&self.amount) {
+            self.amount = amount.normalize().to_string();
+        }
+        self
+    }
+
+    pub fn canonical_json(&self) -> String {
+        serde_json::to_string(self).expect("plan documents always serialize")
+    }
+
+    /// Hex SHA-256 of [`PlanDocument::canonical_json`].
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.canonical_json().as_bytes()))
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DocumentPlanRow {
+    id: Uuid,
+    owner_address: String,
+    token_address: String,
+    amount: Decimal,
+    grace_period_seconds: i64,
+    earn_yield: bool,
+    yield_rate_bps: i32,
+    installment_count: i32,
+    installment_interval_days: i32,
+}
+
+/// The current document for a plan, or `None` if the plan does not exist.
+pub async fn load_document(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+) -> Result<Option<PlanDocument>, sqlx::Error> {
+    let Some(plan) = sqlx::query_as::<_, DocumentPlanRow>(
+        r#"
+        SELECT id, owner_address, token_address, amount, grace_period_seconds, earn_yield,
+               yield_rate_bps, installment_count, installment_interval_days
+        FROM plans
+        WHERE id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(None);
+    };
+    let beneficiaries = sqlx::query_as::<_, (String, i32, bool)>(
+        r#"
+        SELECT wallet_address, allocation_bps, fiat_anchor_info <> ''
+        FROM beneficiaries
+        WHERE plan_id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(Some(
+        PlanDocument {
+            version: DOCUMENT_VERSION,
+            plan_id: plan.id,
+            owner_address: plan.owner_address,
+            token_address: plan.token_address,
+            amount: plan.amount.to_string(),
+            grace_period_seconds: plan.grace_period_seconds,
+            earn_yield: plan.earn_yield,
+            yield_rate_bps: plan.yield_rate_bps,
+            installment_count: plan.installment_count,
+            installment_interval_days: plan.installment_interval_days,
+            beneficiaries: beneficiaries
+                .into_iter()
+                .map(
+                    |(wallet_address, allocation_bps, fiat_payout)| DocumentBeneficiary {
+                        wallet_address,
+                        allocation_bps,
+                        fiat_payout,
+                    },
+                )
+                .collect(),
+        }
+        .canonicalize(),
+    ))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlanMetadataConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+}
+
+impl PlanMetadataConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("PLAN_METADATA_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("PLAN_METADATA_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DuePlan {
+    id: Uuid,
+    owner_address: String,
+    anchored_hash: Option<String>,
+}
+
+/// Anchors the document hash of amended plans.
+pub struct PlanMetadataService {
+    db: PgPool,
+    tx_service: Arc<dyn TxService>,
+    contract_id: String,
+    config: PlanMetadataConfig,
+}
+
+impl PlanMetadataService {
+    pub fn new(
+        db: PgPool,
+        tx_service: Arc<dyn TxService>,
+        contract_id: String,
+        config: PlanMetadataConfig,
+    ) -> Self {
+        Self {
+            db,
+            tx_service,
+            contract_id,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("plan_metadata", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
+                        info!("Plan metadata worker anchored {count} plan(s)");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Rechecks live plans that changed since they were last checked, or
+    /// whose last anchor failed. Returns the number of hashes anchored.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(PLAN_METADATA_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Plan metadata lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        // Pings and yield accrual also snapshot a plan; those recheck the
+        // document but leave the hash unchanged, so nothing is anchored.
+        let due_plans = sqlx::query_as::<_, DuePlan>(
+            r#"
+            SELECT p.id, p.owner_address, m.anchored_hash
+            FROM plans p
+            LEFT JOIN plan_metadata m ON m.plan_id = p.id
+            WHERE p.is_active = true
+              AND (m.plan_id IS NULL
+                   OR m.anchored_hash IS DISTINCT FROM m.metadata_hash
+                   OR EXISTS (SELECT 1 FROM plan_snapshots s
+                              WHERE s.plan_id = p.id AND s.captured_at > m.checked_at))
+            ORDER BY m.checked_at ASC NULLS FIRST
+            LIMIT $1
+            "#,
+        )
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut anchored = 0;
+        for plan in &due_plans {
+            let checked_at = Utc::now();
+            let Some(document) = load_document(&mut tx, plan.id).await? else {
+                continue;
+            };
+            let hash = document.hash();
+            sqlx::query(
+                r#"
+                INSERT INTO plan_metadata (plan_id, metadata_hash, document, checked_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (plan_id) DO UPDATE
+                SET metadata_hash = EXCLUDED.metadata_hash,
+                    document = EXCLUDED.document,
+                    checked_at = EXCLUDED.checked_at
+                "#,
+            )
+            .bind(plan.id)
+            .bind(&hash)
+            .bind(serde_json::json!(document))
+            .bind(checked_at)
+            .execute(&mut *tx)
+            .await?;
+
+            if plan.anchored_hash.as_deref() == Some(hash.as_str()) {
+                continue;
+            }
+
+            let invocation = ContractInvocation {
+                contract_id: self.contract_id.clone(),
+                function: "set_metadata_hash".to_string(),
+                args: vec![plan.owner_address.clone(), hash.clone()],
+                memo: telemetry::current_memo(),
+            };
+            match self.tx_service.invoke_contract(&invocation).await {
+                Ok(tx_hash) => {
+                    sqlx::query(
+                        "UPDATE plan_metadata SET anchored_hash = $2, last_error = NULL WHERE plan_id = $1",
+                    )
+                    .bind(plan.id)
+                    .bind(&hash)
+                    .execute(&mut *tx)
+                    .await?;
+                    sqlx::query(
+                        r#"
+                        INSERT INTO plan_metadata_anchors (plan_id, metadata_hash, document, tx_hash)
+                        VALUES ($1, $2, $3, $4)
+                        "#,
+                    )
+                    .bind(plan.id)
+                    .bind(&hash)
+                    .bind(serde_json::json!(document))
+                    .bind(&tx_hash)
+                    .execute(&mut *tx)
+                    .await?;
+                    info!(plan_id = %plan.id, hash = %hash, tx_hash = %tx_hash, "Anchored plan metadata hash");
+                    anchored += 1;
+                }
+                Err(e) => {
+                    warn!(plan_id = %plan.id, error = %e, "Failed to anchor plan metadata hash");
+                    sqlx::query("UPDATE plan_metadata SET last_error = $2 WHERE plan_id = $1")
+                        .bind(plan.id)
+                        .bind(e.to_string())
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(anchored)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct MetadataAnchor {
+    pub metadata_hash: String,
+    pub tx_hash: String,
+    pub anchored_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetadataVerification {
+    pub plan_id: Uuid,
+    /// Hash of the plan's current document, or of the document submitted.
+    pub metadata_hash: String,
+    /// When that hash was first anchored, if it ever was.
+    pub anchored: Option<MetadataAnchor>,
+    /// Most recent anchor, which is the hash on-chain now.
+    pub latest_anchor: Option<MetadataAnchor>,
+    /// Whether `metadata_hash` is the one on-chain now.
+    pub matches: bool,
+    pub document: PlanDocument,
+}
+
+async fn verify(
+    db: &PgPool,
+    plan_id: Uuid,
+    document: PlanDocument,
+) -> Result<MetadataVerification, sqlx::Error> {
+    let metadata_hash = document.hash();
+    let anchors = sqlx::query_as::<_, MetadataAnchor>(
+        r#"
+        SELECT metadata_hash, tx_hash, anchored_at
+        FROM plan_metadata_anchors
+        WHERE plan_id = $1
+        ORDER BY anchored_at DESC
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(db)
+    .await?;
+    let latest_anchor = anchors.first().cloned();
+    Ok(MetadataVerification {
+        plan_id,
+        matches: latest_anchor
+            .as_ref()
+            .is_some_and(|a| a.metadata_hash == metadata_hash),
+        // Earliest anchor of this hash, which is when these terms took effect
+        anchored: anchors
+            .into_iter()
+            .rev()
+            .find(|a| a.metadata_hash == metadata_hash),
+        latest_anchor,
+        metadata_hash,
+        document,
+    })
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: Admin Verify Plan Metadata against its anchored hash
+pub async fn verify_plan_metadata(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let result = async {
+        let mut conn = state.db_pool.acquire().await?;
+        let Some(document) = load_document(&mut conn, plan_id).await? else {
+            return Ok(None);
+        };
+        verify(&state.db_pool, plan_id, document).await.map(Some)
+    }
+    .await;
+
+    match result {
+        Ok(Some(verification)) => (StatusCode::OK, Json(verification)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to verify plan metadata");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Verify a submitted Plan Document
+pub async fn verify_submitted_plan_metadata(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+    Json(document): Json<PlanDocument>,
+) -> impl IntoResponse {
+    if document.plan_id != plan_id {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Document belongs to a different plan",
+        );
+    }
+
+    match verify(&state.db_pool, plan_id, document.canonicalize()).await {
+        Ok(verification) => (StatusCode::OK, Json(verification)).into_response(),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to verify submitted plan metadata");
+            database_error()
+        }
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(beneficiaries: &[(&str, i32)]) -> PlanDocument {
+        PlanDocument {
+            version: DOCUMENT_VERSION,
+            plan_id: Uuid::nil(),
+            owner_address: "GOWNER".to_string(),
+            token_address: "CTOKEN".to_string(),
+            amount: "1000".to_string(),
+            grace_period_seconds: 7_776_000,
+            earn_yield: false,
+            yield_rate_bps: 0,
+            installment_count: 1,
+            installment_interval_days: 30,
+            beneficiaries: beneficiaries
+                .iter()
+                .map(|(wallet, bps)| DocumentBeneficiary {
+                    wallet_address: wallet.to_string(),
+                    allocation_bps: *bps,
+                    fiat_payout: false,
+                })
+                .collect(),
+        }
+        .canonicalize()
+    }
+
+    #[test]
+    fn hash_ignores_beneficiary_order_but_not_terms() {
+        let a = document(&[("GB", 4_000), ("GA", 6_000)]);
+        let b = document(&[("GA", 6_000), ("GB", 4_000)]);
+        assert_eq!(a.hash(), b.hash());
+        assert!(a.canonical_json().starts_with(
+            r#"{"version":1,"plan_id":"00000000-0000-0000-0000-000000000000","owner_address":"GOWNER""#
+        ));
+
+        let amended = document(&[("GA", 5_000), ("GB", 5_000)]);
+        assert_ne!(a.hash(), amended.hash());
+
+        let mut restated = a.clone();
+        restated.amount = "1000.00".to_string();
+        assert_eq!(restated.canonicalize().hash(), a.hash());
+    }
+}
@@ -0,0 +1,1170 @@
+//! Joint plans, held by two or more wallets (e.g. spouses).
+//!
+//! A plan's `owner_address` is its primary owner, who invites co-owners;
+//! an invitation only takes effect once the invitee accepts it. While a
+//! plan has accepted co-owners:
+//! - amendments and deactivation are proposals that only take effect once
+//!   every owner has approved them;
+//! - the grace period runs from the latest ping of any owner, so the plan
+//!   can only be claimed once all of its owners have gone quiet;
+//! - co-owners see the plan in `GET /api/plans?owner=`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Postgres, Transaction};
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::{
+    invalidate_plan_cache, load_beneficiaries, plan_row_to_response, AppState, PlanBeneficiary,
+    PlanRow,
+};
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::field_crypto::{FieldCipher, SensitiveField};
+use crate::freezes::refuse_frozen_plan;
+use crate::notifications::create_notification;
+use crate::wallet_reauth::{self, ReauthAction, WalletConfirmation};
+
+const CO_OWNER_COLUMNS: &str =
+    "plan_id, owner_address, invited_by, status, last_ping, created_at, responded_at";
+
+const APPROVAL_COLUMNS: &str =
+    "id, plan_id, action, changes, proposed_by, approved_by, status, created_at, resolved_at";
+
+const PLAN_COLUMNS: &str = "id, owner_address, token_address, amount, grace_period, \
+     grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, \
+     accrued_yield, created_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CoOwner {
+    pub plan_id: Uuid,
+    pub owner_address: String,
+    pub invited_by: String,
+    /// `invited`, `accepted` or `declined`.
+    pub status: String,
+    /// Unix seconds of the co-owner's last ping.
+    pub last_ping: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct OwnerApproval {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    /// `amend` or `deactivate`.
+    pub action: String,
+    /// The [`PlanAmendment`] for amendments; empty for deactivation.
+    pub changes: serde_json::Value,
+    pub proposed_by: String,
+    pub approved_by: Vec<String>,
+    /// `pending`, `applied` or `rejected`.
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Owners whose approval is still missing.
+    #[sqlx(skip)]
+    pub awaiting: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InviteCoOwnerRequest {
+    pub address: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ApproveChangeBody {
+    /// Required to approve a deactivation when the caller has wallet
+    /// re-auth enabled.
+    #[serde(default)]
+    pub confirmation: Option<WalletConfirmation>,
+}
+
+/// Changes to an existing plan. Omitted fields are left as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanAmendment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_period_seconds: Option<i64>,
+    /// Replaces every beneficiary of the plan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beneficiaries: Option<Vec<PlanBeneficiary>>,
+}
+
+impl PlanAmendment {
+    fn validate(&self) -> Result<(), String> {
+        if self.grace_period_seconds.is_none() && self.beneficiaries.is_none() {
+            return Err("Amendment must change grace_period_seconds or beneficiaries".to_string());
+        }
+        if self.grace_period_seconds.is_some_and(|secs| secs <= 0) {
+            return Err("Grace period must be greater than zero".to_string());
+        }
+        if let Some(beneficiaries) = &self.beneficiaries {
+            if beneficiaries.is_empty() {
+                return Err("Plan must have at least one beneficiary".to_string());
+            }
+            if beneficiaries.iter().any(|b| b.address.trim().is_empty()) {
+                return Err("Beneficiary address cannot be empty".to_string());
+            }
+            let total_bps: u64 = beneficiaries.iter().map(|b| b.allocation_bps as u64).sum();
+            if total_bps != 10000 {
+                return Err(format!(
+                    "Total allocation_bps must be exactly 10000 (100%), got {total_bps}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `seal` to every beneficiary's anchor info.
+    fn map_anchor_info<E>(
+        mut self,
+        mut seal: impl FnMut(&str) -> Result<String, E>,
+    ) -> Result<Self, E> {
+        for beneficiary in self.beneficiaries.iter_mut().flatten() {
+            beneficiary.fiat_anchor_info = seal(&beneficiary.fiat_anchor_info)?;
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OwnerAction {
+    Amend,
+    Deactivate,
+}
+
+impl OwnerAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Amend => "amend",
+            Self::Deactivate => "deactivate",
+        }
+    }
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+enum Settled {
+    Pending(OwnerApproval),
+    Applied(PlanRow),
+}
+
+/// A recorded approval, with what is needed to respond to it.
+struct Recorded {
+    owners: Vec<String>,
+    /// Beneficiaries before the change, whose cached views go stale.
+    previous_beneficiaries: Vec<String>,
+    settled: Settled,
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// Accepted co-owners of a plan, in the order they were invited.
+pub(crate) async fn co_owners<'e, E>(executor: E, plan_id: Uuid) -> Result<Vec<String>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        r#"
+        SELECT owner_address FROM plan_co_owners
+        WHERE plan_id = $1 AND status = 'accepted'
+        ORDER BY created_at
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Whether `address` is an accepted co-owner of the plan.
+pub(crate) async fn is_co_owner<'e, E>(
+    executor: E,
+    plan_id: Uuid,
+    address: &str,
+) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM plan_co_owners
+            WHERE plan_id = $1 AND owner_address = $2 AND status = 'accepted'
+        )
+        "#,
+    )
+    .bind(plan_id)
+    .bind(address)
+    .fetch_one(executor)
+    .await
+}
+
+/// Unix time at which every owner of the plan has been inactive for the
+/// grace period.
+pub(crate) async fn inactivity_deadline<'e, E>(
+    executor: E,
+    plan: &PlanRow,
+) -> Result<i64, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let latest: Option<i64> = sqlx::query_scalar(
+        "SELECT MAX(last_ping) FROM plan_co_owners WHERE plan_id = $1 AND status = 'accepted'",
+    )
+    .bind(plan.id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(plan.last_ping.max(latest.unwrap_or(plan.last_ping)) + plan.grace_period_seconds)
+}
+
+/// Owners in `owners` who have not approved yet.
+fn awaiting(owners: &[String], approved_by: &[String]) -> Vec<String> {
+    owners
+        .iter()
+        .filter(|owner| !approved_by.contains(owner))
+        .cloned()
+        .collect()
+}
+
+/// Opens anchor details sealed in a stored amendment so owners can review it.
+fn decrypt_changes(cipher: &FieldCipher, approval: &mut OwnerApproval) -> Result<(), sqlx::Error> {
+    if approval.action != OwnerAction::Amend.as_str() {
+        return Ok(());
+    }
+    let amendment: PlanAmendment = serde_json::from_value(approval.changes.clone())
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+    let amendment = amendment.map_anchor_info(|stored| {
+        cipher.decrypt_column(SensitiveField::BeneficiaryAnchorInfo, stored)
+    })?;
+    approval.changes =
+        serde_json::to_value(amendment).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+    Ok(())
+}
+
+async fn invalidate_owner_views(
+    state: &AppState,
+    owners: &[String],
+    beneficiary_addresses: &[String],
+) {
+    for owner in owners {
+        invalidate_plan_cache(&state.plan_cache, owner, beneficiary_addresses).await;
+    }
+}
+
+/// The plan's primary owner followed by its accepted co-owners.
+async fn all_owners(conn: &mut PgConnection, plan: &PlanRow) -> Result<Vec<String>, sqlx::Error> {
+    let mut owners = vec![plan.owner_address.clone()];
+    owners.extend(co_owners(&mut *conn, plan.id).await?);
+    Ok(owners)
+}
+
+/// Carries out an approved proposal.
+async fn apply(
+    conn: &mut PgConnection,
+    plan: &PlanRow,
+    approval: &OwnerApproval,
+) -> Result<PlanRow, sqlx::Error> {
+    let updated = if approval.action == OwnerAction::Deactivate.as_str() {
+        sqlx::query_as::<_, PlanRow>(&format!(
+            "UPDATE plans SET is_active = false, status = 'DEACTIVATED' WHERE id = $1 RETURNING {PLAN_COLUMNS}"
+        ))
+        .bind(plan.id)
+        .fetch_one(&mut *conn)
+        .await?
+    } else {
+        let amendment: PlanAmendment = serde_json::from_value(approval.changes.clone())
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        if let Some(beneficiaries) = &amendment.beneficiaries {
+            sqlx::query("DELETE FROM beneficiaries WHERE plan_id = $1")
+                .bind(plan.id)
+                .execute(&mut *conn)
+                .await?;
+            for b in beneficiaries {
+                sqlx::query(
+                    r#"
+                    INSERT INTO beneficiaries (plan_id, wallet_address, allocation_bps, fiat_anchor_info)
+                    VALUES ($1, $2, $3, $4)
+                    "#,
+                )
+                .bind(plan.id)
+                .bind(&b.address)
+                .bind(b.allocation_bps as i32)
+                .bind(&b.fiat_anchor_info)
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+
+        sqlx::query_as::<_, PlanRow>(&format!(
+            r#"
+            UPDATE plans
+            SET grace_period = COALESCE($2, grace_period),
+                grace_period_seconds = COALESCE($2, grace_period_seconds)
+            WHERE id = $1
+            RETURNING {PLAN_COLUMNS}
+            "#
+        ))
+        .bind(plan.id)
+        .bind(amendment.grace_period_seconds)
+        .fetch_one(&mut *conn)
+        .await?
+    };
+
+    record_audit(
+        &mut *conn,
+        &approval.proposed_by,
+        &format!("plan.{}", approval.action),
+        &plan.id.to_string(),
+        serde_json::json!({
+            "previous_status": plan.status,
+            "approval_id": approval.id,
+            "approved_by": approval.approved_by,
+        }),
+    )
+    .await?;
+
+    Ok(updated)
+}
+
+/// Marks the proposal applied and carries it out once every owner has
+/// approved it; otherwise fills in who it is still waiting on.
+async fn settle(
+    conn: &mut PgConnection,
+    plan: &PlanRow,
+    owners: &[String],
+    mut approval: OwnerApproval,
+) -> Result<Settled, sqlx::Error> {
+    approval.awaiting = awaiting(owners, &approval.approved_by);
+    if !approval.awaiting.is_empty() {
+        return Ok(Settled::Pending(approval));
+    }
+
+    let approval = sqlx::query_as::<_, OwnerApproval>(&format!(
+        r#"
+        UPDATE plan_owner_approvals SET status = 'applied', resolved_at = NOW()
+        WHERE id = $1
+        RETURNING {APPROVAL_COLUMNS}
+        "#
+    ))
+    .bind(approval.id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Settled::Applied(apply(conn, plan, &approval).await?))
+}
+
+/// Commits `tx` and renders a settled proposal: the updated plan once
+/// applied, or the proposal with the owners it is waiting on.
+async fn settled_response(
+    state: &AppState,
+    tx: Transaction<'_, Postgres>,
+    plan: &PlanRow,
+    recorded: Recorded,
+) -> axum::response::Response {
+    let Recorded {
+        owners,
+        previous_beneficiaries,
+        settled,
+    } = recorded;
+    if let Err(e) = tx.commit().await {
+        error!(plan_id = %plan.id, error = %e, "Failed to commit plan approval");
+        return database_error();
+    }
+
+    match settled {
+        Settled::Pending(mut approval) => {
+            if let Err(e) = decrypt_changes(&state.field_cipher, &mut approval) {
+                error!(approval_id = %approval.id, error = %e, "Failed to open amendment");
+                return database_error();
+            }
+            (StatusCode::ACCEPTED, Json(approval)).into_response()
+        }
+        Settled::Applied(updated) => {
+            let beneficiaries =
+                match load_beneficiaries(&state.db_pool, &state.field_cipher, plan.id).await {
+                    Ok(beneficiaries) => beneficiaries,
+                    Err(e) => {
+                        error!(plan_id = %plan.id, error = %e, "Failed to load beneficiaries");
+                        Vec::new()
+                    }
+                };
+            let mut affected = previous_beneficiaries;
+            affected.extend(beneficiaries.iter().map(|b| b.wallet_address.clone()));
+            invalidate_owner_views(state, &owners, &affected).await;
+
+            let mut response = plan_row_to_response(updated, beneficiaries);
+            response.co_owners = owners[1..].to_vec();
+            (StatusCode::OK, Json(response)).into_response()
+        }
+    }
+}
+
+/// Records `caller`'s approval of `action` on a plan, opening a proposal
+/// with `changes` when none is pending, and applies it once every owner
+/// has approved. Consumes and commits `tx`, which must hold the plan row
+/// locked.
+pub(crate) async fn propose(
+    state: &AppState,
+    mut tx: Transaction<'_, Postgres>,
+    plan: PlanRow,
+    caller: &str,
+    action: OwnerAction,
+    changes: serde_json::Value,
+) -> axum::response::Response {
+    let result: Result<Outcome<Recorded>, sqlx::Error> = async {
+        let owners = all_owners(&mut tx, &plan).await?;
+        let previous_beneficiaries: Vec<String> =
+            sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+                .bind(plan.id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let pending = sqlx::query_as::<_, OwnerApproval>(&format!(
+            r#"
+            SELECT {APPROVAL_COLUMNS} FROM plan_owner_approvals
+            WHERE plan_id = $1 AND action = $2 AND status = 'pending'
+            FOR UPDATE
+            "#
+        ))
+        .bind(plan.id)
+        .bind(action.as_str())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let approval = match pending {
+            Some(_) if action == OwnerAction::Amend => {
+                return Ok(Outcome::Refused(
+                    StatusCode::CONFLICT,
+                    "Another amendment is awaiting the owners' approval",
+                ));
+            }
+            Some(pending) => add_approval(&mut tx, pending.id, caller).await?,
+            None => {
+                let approval = sqlx::query_as::<_, OwnerApproval>(&format!(
+                    r#"
+                    INSERT INTO plan_owner_approvals (plan_id, action, changes, proposed_by, approved_by)
+                    VALUES ($1, $2, $3, $4, ARRAY[$4])
+                    RETURNING {APPROVAL_COLUMNS}
+                    "#
+                ))
+                .bind(plan.id)
+                .bind(action.as_str())
+                .bind(&changes)
+                .bind(caller)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                for owner in awaiting(&owners, &approval.approved_by) {
+                    create_notification(
+                        &mut *tx,
+                        &owner,
+                        "plan_approval_requested",
+                        "Plan change awaiting your approval",
+                        &format!(
+                            "A co-owner proposed to {} a plan you own. It takes effect once every owner approves.",
+                            action.as_str()
+                        ),
+                        serde_json::json!({ "plan_id": plan.id, "approval_id": approval.id }),
+                    )
+                    .await?;
+                }
+                approval
+            }
+        };
+
+        let settled = settle(&mut tx, &plan, &owners, approval).await?;
+        Ok(Outcome::Done(Recorded {
+            owners,
+            previous_beneficiaries,
+            settled,
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(recorded)) => settled_response(state, tx, &plan, recorded).await,
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan.id, error = %e, "Failed to record plan approval");
+            database_error()
+        }
+    }
+}
+
+async fn add_approval(
+    conn: &mut PgConnection,
+    approval_id: Uuid,
+    caller: &str,
+) -> Result<OwnerApproval, sqlx::Error> {
+    sqlx::query_as::<_, OwnerApproval>(&format!(
+        r#"
+        UPDATE plan_owner_approvals
+        SET approved_by = CASE WHEN $2 = ANY(approved_by) THEN approved_by
+                               ELSE array_append(approved_by, $2) END
+        WHERE id = $1
+        RETURNING {APPROVAL_COLUMNS}
+        "#
+    ))
+    .bind(approval_id)
+    .bind(caller)
+    .fetch_one(conn)
+    .await
+}
+
+/// Loads an active plan for update if `caller` is one of its owners.
+async fn owned_plan(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    caller: &str,
+) -> Result<Option<PlanRow>, sqlx::Error> {
+    sqlx::query_as::<_, PlanRow>(&format!(
+        r#"
+        SELECT {PLAN_COLUMNS} FROM plans
+        WHERE id = $1
+          AND is_active = true
+          AND (owner_address = $2
+               OR EXISTS (SELECT 1 FROM plan_co_owners o
+                          WHERE o.plan_id = plans.id AND o.owner_address = $2
+                            AND o.status = 'accepted'))
+        FOR UPDATE
+        "#
+    ))
+    .bind(plan_id)
+    .bind(caller)
+    .fetch_optional(conn)
+    .await
+}
+
+// Handler: Invite Co-Owner
+pub async fn invite_co_owner(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<InviteCoOwnerRequest>,
+) -> impl IntoResponse {
+    let owner = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let address = payload.address.trim().to_string();
+    if address.is_empty() {
+        return refused(StatusCode::BAD_REQUEST, "Co-owner address cannot be empty");
+    }
+    if address == owner {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "The plan owner cannot be invited as a co-owner",
+        );
+    }
+
+    let result: Result<Outcome<CoOwner>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+
+        let is_owner: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM plans WHERE id = $1 AND owner_address = $2 AND is_active = true)",
+        )
+        .bind(plan_id)
+        .bind(&owner)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !is_owner {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Active plan not found"));
+        }
+
+        // A declined invitation may be sent again; a live one may not.
+        let invited = sqlx::query_as::<_, CoOwner>(&format!(
+            r#"
+            INSERT INTO plan_co_owners (plan_id, owner_address, invited_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (plan_id, owner_address) DO UPDATE
+            SET status = 'invited', invited_by = EXCLUDED.invited_by,
+                created_at = NOW(), responded_at = NULL, last_ping = NULL
+            WHERE plan_co_owners.status = 'declined'
+            RETURNING {CO_OWNER_COLUMNS}
+            "#
+        ))
+        .bind(plan_id)
+        .bind(&address)
+        .bind(&owner)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(invited) = invited else {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "Address is already a co-owner or has a pending invitation",
+            ));
+        };
+
+        create_notification(
+            &mut *tx,
+            &address,
+            "plan_co_owner_invited",
+            "You have been invited to co-own a plan",
+            "Accept the invitation to hold the plan jointly with its owner.",
+            serde_json::json!({ "plan_id": plan_id, "invited_by": owner }),
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &owner,
+            "plan.co_owner_invited",
+            &plan_id.to_string(),
+            serde_json::json!({ "co_owner": address }),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(Outcome::Done(invited))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(invited)) => (StatusCode::CREATED, Json(invited)).into_response(),
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to invite co-owner");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Co-Owners
+pub async fn list_co_owners(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    // Owners see every invitation; invitees see the plan's list too.
+    let result = sqlx::query_as::<_, CoOwner>(&format!(
+        r#"
+        SELECT {CO_OWNER_COLUMNS} FROM plan_co_owners
+        WHERE plan_id = $1
+          AND (EXISTS (SELECT 1 FROM plans p WHERE p.id = $1 AND p.owner_address = $2)
+               OR EXISTS (SELECT 1 FROM plan_co_owners o
+                          WHERE o.plan_id = $1 AND o.owner_address = $2
+                            AND o.status IN ('invited', 'accepted')))
+        ORDER BY created_at
+        "#
+    ))
+    .bind(plan_id)
+    .bind(&caller)
+    .fetch_all(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(co_owners) => (StatusCode::OK, Json(co_owners)).into_response(),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to list co-owners");
+            database_error()
+        }
+    }
+}
+
+/// Answers the caller's invitation to co-own `plan_id`.
+async fn respond_to_invitation(
+    state: &AppState,
+    plan_id: Uuid,
+    caller: &str,
+    accept: bool,
+) -> Result<Option<CoOwner>, sqlx::Error> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let co_owner = sqlx::query_as::<_, CoOwner>(&format!(
+        r#"
+        UPDATE plan_co_owners c
+        SET status = $3,
+            responded_at = NOW(),
+            last_ping = CASE WHEN $3 = 'accepted' THEN EXTRACT(EPOCH FROM NOW())::BIGINT END
+        FROM plans p
+        WHERE p.id = c.plan_id
+          AND p.is_active = true
+          AND c.plan_id = $1
+          AND c.owner_address = $2
+          AND c.status = 'invited'
+        RETURNING {}
+        "#,
+        CO_OWNER_COLUMNS
+            .split(", ")
+            .map(|column| format!("c.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+    .bind(plan_id)
+    .bind(caller)
+    .bind(if accept { "accepted" } else { "declined" })
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(co_owner) = co_owner else {
+        tx.commit().await?;
+        return Ok(None);
+    };
+
+    create_notification(
+        &mut *tx,
+        &co_owner.invited_by,
+        "plan_co_owner_responded",
+        if accept {
+            "Co-ownership invitation accepted"
+        } else {
+            "Co-ownership invitation declined"
+        },
+        &format!("{caller} has {} your invitation.", co_owner.status),
+        serde_json::json!({ "plan_id": plan_id, "co_owner": caller }),
+    )
+    .await?;
+    record_audit(
+        &mut *tx,
+        caller,
+        if accept {
+            "plan.co_owner_accepted"
+        } else {
+            "plan.co_owner_declined"
+        },
+        &plan_id.to_string(),
+        serde_json::json!({ "invited_by": co_owner.invited_by }),
+    )
+    .await?;
+
+    let beneficiaries: Vec<String> =
+        sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+            .bind(plan_id)
+            .fetch_all(&mut *tx)
+            .await?;
+    tx.commit().await?;
+
+    if accept {
+        invalidate_owner_views(
+            state,
+            &[co_owner.invited_by.clone(), caller.to_string()],
+            &beneficiaries,
+        )
+        .await;
+    }
+    Ok(Some(co_owner))
+}
+
+// Handler: Accept Co-Ownership
+pub async fn accept_co_ownership(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match respond_to_invitation(&state, plan_id, &caller, true).await {
+        Ok(Some(co_owner)) => (StatusCode::OK, Json(co_owner)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "No pending invitation for this plan"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to accept co-ownership");
+            database_error()
+        }
+    }
+}
+
+// Handler: Decline Co-Ownership
+pub async fn decline_co_ownership(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    match respond_to_invitation(&state, plan_id, &caller, false).await {
+        Ok(Some(co_owner)) => (StatusCode::OK, Json(co_owner)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "No pending invitation for this plan"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to decline co-ownership");
+            database_error()
+        }
+    }
+}
+
+// Handler: Co-Owner Ping
+pub async fn ping_co_owner(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result = sqlx::query_as::<_, CoOwner>(&format!(
+        r#"
+        UPDATE plan_co_owners SET last_ping = EXTRACT(EPOCH FROM NOW())::BIGINT
+        WHERE plan_id = $1 AND owner_address = $2 AND status = 'accepted'
+          AND EXISTS (SELECT 1 FROM plans p WHERE p.id = $1 AND p.is_active = true)
+        RETURNING {CO_OWNER_COLUMNS}
+        "#
+    ))
+    .bind(plan_id)
+    .bind(&caller)
+    .fetch_optional(&state.db_pool)
+    .await;
+
+    match result {
+        Ok(Some(co_owner)) => (StatusCode::OK, Json(co_owner)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Active co-owned plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to record co-owner ping");
+            database_error()
+        }
+    }
+}
+
+// Handler: Amend Plan
+pub async fn amend_plan(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<PlanAmendment>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = user.require_recently_verified() {
+        return e.into_response();
+    }
+    if let Err(message) = payload.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": message })),
+        )
+            .into_response();
+    }
+
+    // Anchor details are stored sealed, as on the beneficiaries table.
+    let sealed = match payload.map_anchor_info(|info| {
+        state
+            .field_cipher
+            .encrypt(SensitiveField::BeneficiaryAnchorInfo, info)
+    }) {
+        Ok(sealed) => sealed,
+        Err(e) => {
+            error!(error = %e, "Failed to encrypt beneficiary anchor info");
+            return database_error();
+        }
+    };
+    let changes = match serde_json::to_value(&sealed) {
+        Ok(changes) => changes,
+        Err(e) => {
+            error!(error = %e, "Failed to serialize plan amendment");
+            return database_error();
+        }
+    };
+
+    let mut tx = match state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!(error = %e, "Failed to begin database transaction");
+            return database_error();
+        }
+    };
+    let plan = match owned_plan(&mut tx, plan_id, &caller).await {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return refused(StatusCode::NOT_FOUND, "Active plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan for amendment");
+            return database_error();
+        }
+    };
+    if let Err(response) = refuse_frozen_plan(&mut tx, plan.id).await {
+        return response;
+    }
+
+    propose(&state, tx, plan, &caller, OwnerAction::Amend, changes).await
+}
+
+// Handler: List Plan Approvals
+pub async fn list_approvals(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<Vec<OwnerApproval>>, sqlx::Error> = async {
+        let mut conn = state.db_pool.acquire().await?;
+        let plan = sqlx::query_as::<_, PlanRow>(&format!(
+            "SELECT {PLAN_COLUMNS} FROM plans WHERE id = $1"
+        ))
+        .bind(plan_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(plan) = plan else {
+            return Ok(None);
+        };
+        let owners = all_owners(&mut conn, &plan).await?;
+        if !owners.contains(&caller) {
+            return Ok(None);
+        }
+
+        let mut approvals = sqlx::query_as::<_, OwnerApproval>(&format!(
+            r#"
+            SELECT {APPROVAL_COLUMNS} FROM plan_owner_approvals
+            WHERE plan_id = $1
+            ORDER BY created_at DESC
+            LIMIT 50
+            "#
+        ))
+        .bind(plan_id)
+        .fetch_all(&mut *conn)
+        .await?;
+        for approval in &mut approvals {
+            if approval.status == "pending" {
+                approval.awaiting = awaiting(&owners, &approval.approved_by);
+            }
+            decrypt_changes(&state.field_cipher, approval)?;
+        }
+        Ok(Some(approvals))
+    }
+    .await;
+
+    match result {
+        Ok(Some(approvals)) => (StatusCode::OK, Json(approvals)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to list plan approvals");
+            database_error()
+        }
+    }
+}
+
+/// Loads the pending proposal `approval_id` on `plan_id` for update.
+async fn pending_approval(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    approval_id: Uuid,
+) -> Result<Option<OwnerApproval>, sqlx::Error> {
+    sqlx::query_as::<_, OwnerApproval>(&format!(
+        r#"
+        SELECT {APPROVAL_COLUMNS} FROM plan_owner_approvals
+        WHERE id = $1 AND plan_id = $2 AND status = 'pending'
+        FOR UPDATE
+        "#
+    ))
+    .bind(approval_id)
+    .bind(plan_id)
+    .fetch_optional(conn)
+    .await
+}
+
+// Handler: Approve Plan Change
+pub async fn approve_plan_change(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path((plan_id, approval_id)): Path<(Uuid, Uuid)>,
+    payload: Option<Json<ApproveChangeBody>>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+
+    let mut tx = match state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!(error = %e, "Failed to begin database transaction");
+            return database_error();
+        }
+    };
+    let loaded = async {
+        let Some(plan) = owned_plan(&mut tx, plan_id, &caller).await? else {
+            return Ok(None);
+        };
+        let approval = pending_approval(&mut tx, plan_id, approval_id).await?;
+        Ok::<_, sqlx::Error>(approval.map(|approval| (plan, approval)))
+    }
+    .await;
+    let (plan, approval) = match loaded {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) => return refused(StatusCode::NOT_FOUND, "Pending plan change not found"),
+        Err(e) => {
+            error!(approval_id = %approval_id, error = %e, "Failed to load plan change");
+            return database_error();
+        }
+    };
+    if let Err(response) = refuse_frozen_plan(&mut tx, plan.id).await {
+        return response;
+    }
+
+    if approval.action == OwnerAction::Deactivate.as_str() {
+        if let Err(e) = wallet_reauth::enforce(
+            &mut tx,
+            &caller,
+            ReauthAction::DeactivatePlan,
+            Some(plan.id),
+            payload.confirmation.as_ref(),
+        )
+        .await
+        {
+            return e.into_response();
+        }
+    }
+
+    let result: Result<Recorded, sqlx::Error> = async {
+        let owners = all_owners(&mut tx, &plan).await?;
+        let previous_beneficiaries: Vec<String> =
+            sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+                .bind(plan.id)
+                .fetch_all(&mut *tx)
+                .await?;
+        let approval = add_approval(&mut tx, approval.id, &caller).await?;
+        let settled = settle(&mut tx, &plan, &owners, approval).await?;
+        Ok(Recorded {
+            owners,
+            previous_beneficiaries,
+            settled,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(recorded) => settled_response(&state, tx, &plan, recorded).await,
+        Err(e) => {
+            error!(approval_id = %approval_id, error = %e, "Failed to approve plan change");
+            database_error()
+        }
+    }
+}
+
+// Handler: Reject Plan Change
+pub async fn reject_plan_change(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path((plan_id, approval_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Option<OwnerApproval>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if owned_plan(&mut tx, plan_id, &caller).await?.is_none()
+            || pending_approval(&mut tx, plan_id, approval_id)
+                .await?
+                .is_none()
+        {
+            return Ok(None);
+        }
+
+        let mut rejected = sqlx::query_as::<_, OwnerApproval>(&format!(
+            r#"
+            UPDATE plan_owner_approvals SET status = 'rejected', resolved_at = NOW()
+            WHERE id = $1
+            RETURNING {APPROVAL_COLUMNS}
+            "#
+        ))
+        .bind(approval_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &caller,
+            "plan.change_rejected",
+            &plan_id.to_string(),
+            serde_json::json!({ "approval_id": approval_id, "action": rejected.action }),
+        )
+        .await?;
+        tx.commit().await?;
+
+        decrypt_changes(&state.field_cipher, &mut rejected)?;
+        Ok(Some(rejected))
+    }
+    .await;
+
+    match result {
+        Ok(Some(rejected)) => (StatusCode::OK, Json(rejected)).into_response(),
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Pending plan change not found"),
+        Err(e) => {
+            error!(approval_id = %approval_id, error = %e, "Failed to reject plan change");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beneficiary(address: &str, allocation_bps: u32) -> PlanBeneficiary {
+        PlanBeneficiary {
+            address: address.to_string(),
+            name: String::new(),
+            allocation_bps,
+            fiat_anchor_info: String::new(),
+        }
+    }
+
+    #[test]
+    fn amendment_must_change_something_valid() {
+        assert!(PlanAmendment::default().validate().is_err());
+        assert!(PlanAmendment {
+            grace_period_seconds: Some(0),
+            beneficiaries: None,
+        }
+        .validate()
+        .is_err());
+        assert!(PlanAmendment {
+            grace_period_seconds: None,
+            beneficiaries: Some(vec![beneficiary("GA", 6000), beneficiary("GB", 3000)]),
+        }
+        .validate()
+        .is_err());
+        assert!(PlanAmendment {
+            grace_period_seconds: Some(86400),
+            beneficiaries: Some(vec![beneficiary("GA", 6000), beneficiary("GB", 4000)]),
+        }
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn waits_on_every_owner_who_has_not_approved() {
+        let owners = vec!["GOWNER".to_string(), "GSPOUSE".to_string()];
+
+        assert_eq!(
+            awaiting(&owners, &["GOWNER".to_string()]),
+            vec!["GSPOUSE".to_string()]
+        );
+        assert!(awaiting(&owners, &["GSPOUSE".to_string(), "GOWNER".to_string()]).is_empty());
+    }
+}
@@ -0,0 +1,493 @@
+//! Owner-defined plan tags and saved plan filters.
+//!
+//! Owners and accepted co-owners label their plans with short tags such as
+//! `family` or `client-042`. `GET /api/plans` and the plans and claims CSV
+//! exports take `tag=a,b` and return only plans carrying every listed tag.
+//! `GET /api/users/me/plan-tags` counts the caller's plans per tag, and a
+//! user can save named filters (tags and a beneficiary) to run again
+//! later.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::{invalidate_plan_cache, AppState};
+use crate::auth::UserContext;
+
+pub const MAX_TAG_LEN: usize = 32;
+pub const MAX_TAGS_PER_PLAN: usize = 20;
+pub const MAX_SAVED_FILTERS: i64 = 50;
+const MAX_FILTER_NAME_LEN: usize = 64;
+
+/// Lowercases `tag` and checks it is 1 to [`MAX_TAG_LEN`] letters, digits,
+/// `-`, `_`, `.` or `:`.
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_ascii_lowercase();
+    let valid = !tag.is_empty()
+        && tag.len() <= MAX_TAG_LEN
+        && tag
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'));
+    if valid {
+        Ok(tag)
+    } else {
+        Err(format!(
+            "Tags must be 1 to {MAX_TAG_LEN} letters, digits, '-', '_', '.' or ':'"
+        ))
+    }
+}
+
+/// Normalizes, sorts and de-duplicates `tags`.
+pub fn normalize_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>, String> {
+    let mut tags = tags
+        .into_iter()
+        .map(normalize_tag)
+        .collect::<Result<Vec<_>, _>>()?;
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+/// Parses a comma-separated `tag` query parameter. Blank means no filter.
+pub fn parse_tag_filter(value: Option<&str>) -> Result<Option<Vec<String>>, String> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => normalize_tags(value.split(',')).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Tags on a plan, alphabetically.
+pub(crate) async fn tags_for<'e, E>(executor: E, plan_id: Uuid) -> Result<Vec<String>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar("SELECT tag FROM plan_tags WHERE plan_id = $1 ORDER BY tag")
+        .bind(plan_id)
+        .fetch_all(executor)
+        .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddTagsRequest {
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlanTags {
+    pub plan_id: Uuid,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TagCount {
+    pub tag: String,
+    pub plans: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavePlanFilterRequest {
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub beneficiary: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SavedPlanFilter {
+    pub id: Uuid,
+    pub name: String,
+    pub tags: Vec<String>,
+    pub beneficiary: Option<String>,
+    /// Query string that runs the filter against `GET /api/plans`.
+    #[sqlx(skip)]
+    pub query: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SavedPlanFilter {
+    fn with_query(mut self, owner: &str) -> Self {
+        let mut query = format!("owner={owner}");
+        if !self.tags.is_empty() {
+            query.push_str(&format!("&tag={}", self.tags.join(",")));
+        }
+        if let Some(beneficiary) = &self.beneficiary {
+            query.push_str(&format!("&beneficiary={beneficiary}"));
+        }
+        self.query = query;
+        self
+    }
+}
+
+const FILTER_COLUMNS: &str = "id, name, tags, beneficiary, created_at, updated_at";
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// Locks the plan and checks `caller` owns or co-owns it. Returns the
+/// owner and beneficiary addresses for cache invalidation.
+async fn lock_owned_plan(
+    conn: &mut sqlx::PgConnection,
+    plan_id: Uuid,
+    caller: &str,
+) -> Result<Outcome<(String, Vec<String>)>, sqlx::Error> {
+    let owner: Option<(String, bool)> = sqlx::query_as(
+        r#"
+        SELECT p.owner_address,
+               p.owner_address = $2
+               OR EXISTS (SELECT 1 FROM plan_co_owners o
+                          WHERE o.plan_id = p.id AND o.owner_address = $2
+                            AND o.status = 'accepted')
+        FROM plans p
+        WHERE p.id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(plan_id)
+    .bind(caller)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match owner {
+        None => Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Plan not found")),
+        Some((_, false)) => Ok(Outcome::Refused(
+            StatusCode::FORBIDDEN,
+            "Only the plan's owners can tag it",
+        )),
+        Some((owner, true)) => {
+            let beneficiaries =
+                sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+                    .bind(plan_id)
+                    .fetch_all(&mut *conn)
+                    .await?;
+            Ok(Outcome::Done((owner, beneficiaries)))
+        }
+    }
+}
+
+// Handler: Add Plan Tags
+pub async fn add_plan_tags(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<AddTagsRequest>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let tags = match normalize_tags(payload.tags.iter().map(String::as_str)) {
+        Ok(tags) if !tags.is_empty() => tags,
+        Ok(_) => return refused(StatusCode::BAD_REQUEST, "Give at least one tag"),
+        Err(message) => return refused(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let result: Result<Outcome<(PlanTags, String, Vec<String>)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let (owner, beneficiaries) = match lock_owned_plan(&mut tx, plan_id, &caller).await? {
+            Outcome::Done(parties) => parties,
+            Outcome::Refused(status, message) => return Ok(Outcome::Refused(status, message)),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO plan_tags (plan_id, tag, added_by)
+            SELECT $1, tag, $3 FROM UNNEST($2::text[]) AS tag
+            ON CONFLICT (plan_id, tag) DO NOTHING
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&tags)
+        .bind(&caller)
+        .execute(&mut *tx)
+        .await?;
+        let tags = tags_for(&mut *tx, plan_id).await?;
+        if tags.len() > MAX_TAGS_PER_PLAN {
+            return Ok(Outcome::Refused(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "A plan can have at most 20 tags",
+            ));
+        }
+        tx.commit().await?;
+        Ok(Outcome::Done((
+            PlanTags { plan_id, tags },
+            owner,
+            beneficiaries,
+        )))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done((tags, owner, beneficiaries))) => {
+            invalidate_plan_cache(&state.plan_cache, &owner, &beneficiaries).await;
+            (StatusCode::OK, Json(tags)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to tag plan");
+            database_error()
+        }
+    }
+}
+
+// Handler: Remove Plan Tag
+pub async fn remove_plan_tag(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path((plan_id, tag)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let tag = match normalize_tag(&tag) {
+        Ok(tag) => tag,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let result: Result<Outcome<(String, Vec<String>)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let parties = match lock_owned_plan(&mut tx, plan_id, &caller).await? {
+            Outcome::Done(parties) => parties,
+            Outcome::Refused(status, message) => return Ok(Outcome::Refused(status, message)),
+        };
+        let removed = sqlx::query("DELETE FROM plan_tags WHERE plan_id = $1 AND tag = $2")
+            .bind(plan_id)
+            .bind(&tag)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if removed == 0 {
+            return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Tag not found"));
+        }
+        tx.commit().await?;
+        Ok(Outcome::Done(parties))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done((owner, beneficiaries))) => {
+            invalidate_plan_cache(&state.plan_cache, &owner, &beneficiaries).await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to remove plan tag");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get My Plan Tags
+pub async fn get_my_plan_tags(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let counts = sqlx::query_as::<_, TagCount>(
+        r#"
+        SELECT t.tag, COUNT(*) AS plans
+        FROM plan_tags t
+        JOIN plans p ON p.id = t.plan_id
+        WHERE p.owner_address = $1
+           OR EXISTS (SELECT 1 FROM plan_co_owners o
+                      WHERE o.plan_id = p.id AND o.owner_address = $1
+                        AND o.status = 'accepted')
+        GROUP BY t.tag
+        ORDER BY plans DESC, t.tag
+        "#,
+    )
+    .bind(&caller)
+    .fetch_all(&state.db_pool)
+    .await;
+
+    match counts {
+        Ok(counts) => (StatusCode::OK, Json(counts)).into_response(),
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to count plan tags");
+            database_error()
+        }
+    }
+}
+
+// Handler: List My Plan Filters
+pub async fn list_plan_filters(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let filters = sqlx::query_as::<_, SavedPlanFilter>(&format!(
+        "SELECT {FILTER_COLUMNS} FROM saved_plan_filters WHERE user_address = $1 ORDER BY name"
+    ))
+    .bind(&caller)
+    .fetch_all(&state.db_pool)
+    .await;
+
+    match filters {
+        Ok(filters) => {
+            let filters: Vec<SavedPlanFilter> =
+                filters.into_iter().map(|f| f.with_query(&caller)).collect();
+            (StatusCode::OK, Json(filters)).into_response()
+        }
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to list plan filters");
+            database_error()
+        }
+    }
+}
+
+// Handler: Save Plan Filter
+pub async fn save_plan_filter(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<SavePlanFilterRequest>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_FILTER_NAME_LEN {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Filter name must be between 1 and 64 characters",
+        );
+    }
+    let tags = match normalize_tags(payload.tags.iter().map(String::as_str)) {
+        Ok(tags) => tags,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, &message),
+    };
+    let beneficiary = payload
+        .beneficiary
+        .as_deref()
+        .map(str::trim)
+        .filter(|b| !b.is_empty());
+    if tags.is_empty() && beneficiary.is_none() {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "A filter needs at least one tag or a beneficiary",
+        );
+    }
+
+    let result: Result<Outcome<SavedPlanFilter>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        // Serializes saves by the same user so the limit holds.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("saved_plan_filters:{caller}"))
+            .execute(&mut *tx)
+            .await?;
+        let (saved, replacing): (i64, bool) = sqlx::query_as(
+            "SELECT COUNT(*), COALESCE(BOOL_OR(name = $2), false) FROM saved_plan_filters WHERE user_address = $1",
+        )
+        .bind(&caller)
+        .bind(name)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !replacing && saved >= MAX_SAVED_FILTERS {
+            return Ok(Outcome::Refused(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "At most 50 plan filters can be saved",
+            ));
+        }
+        let filter = sqlx::query_as::<_, SavedPlanFilter>(&format!(
+            r#"
+            INSERT INTO saved_plan_filters (user_address, name, tags, beneficiary)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_address, name)
+            DO UPDATE SET tags = EXCLUDED.tags,
+                          beneficiary = EXCLUDED.beneficiary,
+                          updated_at = NOW()
+            RETURNING {FILTER_COLUMNS}
+            "#
+        ))
+        .bind(&caller)
+        .bind(name)
+        .bind(&tags)
+        .bind(beneficiary)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(filter))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(filter)) => {
+            (StatusCode::OK, Json(filter.with_query(&caller))).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to save plan filter");
+            database_error()
+        }
+    }
+}
+
+// Handler: Delete Plan Filter
+pub async fn delete_plan_filter(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(filter_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let deleted = sqlx::query("DELETE FROM saved_plan_filters WHERE id = $1 AND user_address = $2")
+        .bind(filter_id)
+        .bind(&caller)
+        .execute(&state.db_pool)
+        .await;
+
+    match deleted {
+        Ok(done) if done.rows_affected() > 0 => StatusCode::NO_CONTENT.into_response(),
+        Ok(_) => refused(StatusCode::NOT_FOUND, "Filter not found"),
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to delete plan filter");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_normalized_and_filters_parsed() {
+        assert_eq!(normalize_tag("  Client-042 ").unwrap(), "client-042");
+        assert!(normalize_tag("two words").is_err());
+        assert!(normalize_tag(&"a".repeat(MAX_TAG_LEN + 1)).is_err());
+        assert_eq!(
+            parse_tag_filter(Some("Family, trust,family")).unwrap(),
+            Some(vec!["family".to_string(), "trust".to_string()])
+        );
+        assert_eq!(parse_tag_filter(Some(" ")).unwrap(), None);
+        assert!(parse_tag_filter(Some("ok,,")).is_err());
+    }
+}
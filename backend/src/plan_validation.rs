@@ -0,0 +1,420 @@
+//! Dry-run validation of a plan before it is created.
+//!
+//! Errors mirror the rules `create_plan` enforces in the inheritance
+//! contract, so a plan that passes here will not be rejected on-chain for
+//! its shape. Warnings flag things that are allowed but probably not what
+//! the owner wants, such as a beneficiary who has not finished KYC.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::{AppState, Plan};
+use crate::organizations;
+use crate::projection::fee_for;
+
+/// The inheritance contract's limit, from the shared types crate.
+pub const MAX_BENEFICIARIES: usize = inheritx_types::MAX_BENEFICIARIES as usize;
+const TOTAL_BPS: u32 = inheritx_types::BPS_DENOMINATOR;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    /// Stable identifier, e.g. `allocation_total`.
+    pub code: &'static str,
+    /// Request field the issue refers to, e.g. `beneficiaries[1].address`.
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(code: &'static str, field: Option<String>, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub errors: Vec<ValidationIssue>,
+    pub warnings: Vec<ValidationIssue>,
+}
+
+/// Net amount each beneficiary would receive from one installment.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NetShare {
+    pub address: String,
+    pub gross_amount: Decimal,
+    pub fee_amount: Decimal,
+    pub net_amount: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidatePlanResponse {
+    #[serde(flatten)]
+    pub report: ValidationReport,
+    pub fee_bps: u32,
+    pub per_installment: Vec<NetShare>,
+}
+
+fn beneficiary_field(index: usize, name: &str) -> Option<String> {
+    Some(format!("beneficiaries[{index}].{name}"))
+}
+
+fn is_stellar_address(value: &str) -> bool {
+    stellar_strkey::ed25519::PublicKey::from_string(value).is_ok()
+        || stellar_strkey::Contract::from_string(value).is_ok()
+}
+
+/// Splits one installment of `amount` by allocation, the same way the
+/// projection does, and withholds the fee from each share.
+pub fn net_shares(plan: &Plan, amount: Decimal, fee_bps: u32) -> Vec<NetShare> {
+    let installment = (amount / Decimal::from(plan.installment_count.max(1))).floor();
+    plan.beneficiaries
+        .iter()
+        .map(|beneficiary| {
+            let gross_amount = (installment * Decimal::from(beneficiary.allocation_bps)
+                / Decimal::from(TOTAL_BPS))
+            .floor();
+            let fee_amount = fee_for(gross_amount, fee_bps);
+            NetShare {
+                address: beneficiary.address.clone(),
+                gross_amount,
+                fee_amount,
+                net_amount: gross_amount - fee_amount,
+            }
+        })
+        .collect()
+}
+
+/// Checks everything that does not need the database. `min_net_payout` is
+/// the smallest net amount a beneficiary may receive per installment.
+pub fn check_plan(plan: &Plan, fee_bps: u32, min_net_payout: u64) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let errors = &mut report.errors;
+    let warnings = &mut report.warnings;
+
+    if plan.owner.trim().is_empty() {
+        errors.push(ValidationIssue::new(
+            "owner_missing",
+            Some("owner".into()),
+            "Owner address cannot be empty",
+        ));
+    } else if !is_stellar_address(plan.owner.trim()) {
+        warnings.push(ValidationIssue::new(
+            "owner_address_format",
+            Some("owner".into()),
+            "Owner is not a Stellar address",
+        ));
+    }
+    if plan.token.trim().is_empty() {
+        errors.push(ValidationIssue::new(
+            "token_missing",
+            Some("token".into()),
+            "Token address cannot be empty",
+        ));
+    }
+    let amount = Some(plan.amount.normalize()).filter(|d| *d > Decimal::ZERO);
+    if amount.is_none() {
+        errors.push(ValidationIssue::new(
+            "amount_not_positive",
+            Some("amount".into()),
+            "Amount must be greater than zero",
+        ));
+    }
+    if plan.grace_period == 0 {
+        errors.push(ValidationIssue::new(
+            "grace_period_zero",
+            Some("grace_period".into()),
+            "Grace period must be greater than zero",
+        ));
+    }
+    if !(1..=600).contains(&plan.installment_count) || plan.installment_interval_days == 0 {
+        errors.push(ValidationIssue::new(
+            "installments_out_of_range",
+            Some("installment_count".into()),
+            "installment_count must be between 1 and 600 and installment_interval_days greater than zero",
+        ));
+    }
+
+    if plan.beneficiaries.is_empty() {
+        errors.push(ValidationIssue::new(
+            "no_beneficiaries",
+            Some("beneficiaries".into()),
+            "Plan must have at least one beneficiary",
+        ));
+    } else if plan.beneficiaries.len() > MAX_BENEFICIARIES {
+        errors.push(ValidationIssue::new(
+            "too_many_beneficiaries",
+            Some("beneficiaries".into()),
+            format!("A plan can have at most {MAX_BENEFICIARIES} beneficiaries"),
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    let mut total_bps: u32 = 0;
+    for (index, beneficiary) in plan.beneficiaries.iter().enumerate() {
+        let address = beneficiary.address.trim();
+        if address.is_empty() {
+            errors.push(ValidationIssue::new(
+                "beneficiary_address_missing",
+                beneficiary_field(index, "address"),
+                "Beneficiary address cannot be empty",
+            ));
+        } else {
+            if !seen.insert(address.to_uppercase()) {
+                errors.push(ValidationIssue::new(
+                    "duplicate_beneficiary",
+                    beneficiary_field(index, "address"),
+                    format!("{address} is listed more than once"),
+                ));
+            }
+            if !is_stellar_address(address) {
+                warnings.push(ValidationIssue::new(
+                    "beneficiary_address_format",
+                    beneficiary_field(index, "address"),
+                    format!("{address} is not a Stellar address"),
+                ));
+            }
+            if address.eq_ignore_ascii_case(plan.owner.trim()) {
+                warnings.push(ValidationIssue::new(
+                    "beneficiary_is_owner",
+                    beneficiary_field(index, "address"),
+                    "The owner is listed as a beneficiary",
+                ));
+            }
+        }
+        if beneficiary.allocation_bps > TOTAL_BPS {
+            errors.push(ValidationIssue::new(
+                "allocation_exceeds_total",
+                beneficiary_field(index, "allocation_bps"),
+                "Beneficiary allocation_bps cannot exceed 10000",
+            ));
+        } else if beneficiary.allocation_bps == 0 {
+            warnings.push(ValidationIssue::new(
+                "zero_allocation",
+                beneficiary_field(index, "allocation_bps"),
+                "Beneficiary would receive nothing",
+            ));
+        }
+        total_bps = total_bps.saturating_add(beneficiary.allocation_bps);
+    }
+    if !plan.beneficiaries.is_empty() && total_bps != TOTAL_BPS {
+        errors.push(ValidationIssue::new(
+            "allocation_total",
+            Some("beneficiaries".into()),
+            format!("Total allocation_bps must be exactly 10000 (100%), got {total_bps}"),
+        ));
+    }
+
+    if let Some(amount) = amount {
+        let minimum = Decimal::from(min_net_payout);
+        for (index, share) in net_shares(plan, amount, fee_bps).iter().enumerate() {
+            let allocation_bps = plan.beneficiaries[index].allocation_bps;
+            if allocation_bps > 0 && share.net_amount < minimum {
+                errors.push(ValidationIssue::new(
+                    "below_minimum_payout",
+                    beneficiary_field(index, "allocation_bps"),
+                    format!(
+                        "Beneficiary would receive {} per installment after fees; the minimum is {minimum}",
+                        share.net_amount
+                    ),
+                ));
+            }
+        }
+    }
+
+    report.valid = report.errors.is_empty();
+    report
+}
+
+/// Warns about beneficiaries whose KYC is not approved. `statuses` maps
+/// wallet address to KYC status; missing addresses have no account yet.
+pub fn kyc_warnings(plan: &Plan, statuses: &HashMap<String, String>) -> Vec<ValidationIssue> {
+    plan.beneficiaries
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| !b.address.trim().is_empty())
+        .filter_map(|(index, beneficiary)| {
+            let address = beneficiary.address.trim();
+            let message = match statuses.get(address).map(String::as_str) {
+                Some("approved") => return None,
+                Some("rejected") => format!("KYC for {address} was rejected"),
+                Some(_) => format!("KYC for {address} is not complete"),
+                None => format!("{address} has not started KYC"),
+            };
+            Some(ValidationIssue::new(
+                "beneficiary_kyc_missing",
+                beneficiary_field(index, "address"),
+                message,
+            ))
+        })
+        .collect()
+}
+
+// Handler: Validate Plan
+pub async fn validate_plan(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<Plan>,
+) -> impl IntoResponse {
+    let addresses: Vec<String> = payload
+        .beneficiaries
+        .iter()
+        .map(|b| b.address.trim().to_string())
+        .collect();
+
+    let result: Result<(u32, HashMap<String, String>), sqlx::Error> = async {
+        let fees = organizations::owner_fee_schedule(&state.db_pool, &state.config, &payload.owner)
+            .await?;
+        let statuses: Vec<(String, String)> = sqlx::query_as(
+            "SELECT wallet_address, kyc_status::text FROM users WHERE wallet_address = ANY($1)",
+        )
+        .bind(&addresses)
+        .fetch_all(&state.db_pool)
+        .await?;
+        Ok((fees.payout_fee_bps, statuses.into_iter().collect()))
+    }
+    .await;
+
+    let (fee_bps, statuses) = match result {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!(error = %e, "Failed to load data for plan validation");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to validate plan" })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut report = check_plan(&payload, fee_bps, state.config.min_beneficiary_payout);
+    report.warnings.extend(kyc_warnings(&payload, &statuses));
+    let per_installment = Some(payload.amount)
+        .filter(|amount| *amount > Decimal::ZERO)
+        .map(|amount| net_shares(&payload, amount.normalize(), fee_bps))
+        .unwrap_or_default();
+
+    (
+        StatusCode::OK,
+        Json(ValidatePlanResponse {
+            report,
+            fee_bps,
+            per_installment,
+        }),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::PlanBeneficiary;
+
+    const OWNER: &str = "GAAZI4TCR3TY5OJHCTJC2A4QSY6CJWJH5IAJTGKIN2ER7LBNVKOCCWN7";
+    const HEIR_A: &str = "GABAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEJXA";
+    const HEIR_B: &str = "GABQGAYDAMBQGAYDAMBQGAYDAMBQGAYDAMBQGAYDAMBQGAYDAMBQHGPC";
+
+    fn beneficiary(address: &str, allocation_bps: u32) -> PlanBeneficiary {
+        PlanBeneficiary {
+            address: address.to_string(),
+            name: String::new(),
+            allocation_bps,
+            fiat_anchor_info: String::new(),
+        }
+    }
+
+    fn plan(amount: i64, beneficiaries: Vec<PlanBeneficiary>) -> Plan {
+        Plan {
+            owner: OWNER.to_string(),
+            token: "CTOKEN".to_string(),
+            amount: Decimal::from(amount),
+            beneficiaries,
+            last_ping: 0,
+            grace_period: 86_400,
+            earn_yield: false,
+            yield_rate_bps: 0,
+            is_active: true,
+            installment_count: 1,
+            installment_interval_days: 30,
+        }
+    }
+
+    fn codes(issues: &[ValidationIssue]) -> Vec<&'static str> {
+        issues.iter().map(|issue| issue.code).collect()
+    }
+
+    #[test]
+    fn accepts_well_formed_plan() {
+        let report = check_plan(
+            &plan(
+                1_000,
+                vec![beneficiary(HEIR_A, 6_000), beneficiary(HEIR_B, 4_000)],
+            ),
+            100,
+            1,
+        );
+        assert!(report.valid);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_totals_and_duplicates() {
+        let report = check_plan(
+            &plan(
+                1_000,
+                vec![beneficiary(HEIR_A, 6_000), beneficiary(HEIR_A, 3_000)],
+            ),
+            0,
+            1,
+        );
+        assert!(!report.valid);
+        assert_eq!(
+            codes(&report.errors),
+            vec!["duplicate_beneficiary", "allocation_total"]
+        );
+    }
+
+    #[test]
+    fn enforces_minimum_after_fees() {
+        // 10 units split 99/1: the small share is 0 after rounding.
+        let report = check_plan(
+            &plan(
+                10,
+                vec![beneficiary(HEIR_A, 9_900), beneficiary(HEIR_B, 100)],
+            ),
+            0,
+            1,
+        );
+        assert_eq!(codes(&report.errors), vec!["below_minimum_payout"]);
+        assert_eq!(
+            report.errors[0].field.as_deref(),
+            Some("beneficiaries[1].allocation_bps")
+        );
+
+        // A 50% fee takes 1,000 down to 500, under a 600 minimum.
+        let report = check_plan(&plan(1_000, vec![beneficiary(HEIR_A, 10_000)]), 5_000, 600);
+        assert_eq!(codes(&report.errors), vec!["below_minimum_payout"]);
+    }
+
+    #[test]
+    fn warns_about_missing_kyc() {
+        let plan = plan(
+            1_000,
+            vec![beneficiary(HEIR_A, 5_000), beneficiary(HEIR_B, 5_000)],
+        );
+        let statuses = HashMap::from([(HEIR_A.to_string(), "approved".to_string())]);
+        let warnings = kyc_warnings(&plan, &statuses);
+        assert_eq!(codes(&warnings), vec!["beneficiary_kyc_missing"]);
+        assert_eq!(
+            warnings[0].field.as_deref(),
+            Some("beneficiaries[1].address")
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Runtime platform settings managed through maker-checker review.
+//!
+//! Each setting is a JSON document keyed by name. Until a change has been
+//! approved the values fall back to the static configuration. Changes are
+//! proposed as JSON merge patches (RFC 7386) and only written here once a
+//! second admin approves them; see [`crate::pending_changes`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgExecutor;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::yield_calculator::ApyConfig;
+
+const MAX_BPS: u32 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingKey {
+    FeeSchedule,
+    RateTable,
+    WebhookEndpoints,
+    OrganizationFees,
+}
+
+impl SettingKey {
+    pub const ALL: [SettingKey; 4] = [
+        Self::FeeSchedule,
+        Self::RateTable,
+        Self::WebhookEndpoints,
+        Self::OrganizationFees,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FeeSchedule => "fee_schedule",
+            Self::RateTable => "rate_table",
+            Self::WebhookEndpoints => "webhook_endpoints",
+            Self::OrganizationFees => "organization_fees",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+
+    /// Value used before any change has been approved.
+    pub fn default_value(self, config: &Config, apy: &ApyConfig) -> Value {
+        let value = match self {
+            Self::FeeSchedule => serde_json::to_value(FeeSchedule {
+                payout_fee_bps: config.payout_fee_bps,
+            }),
+            Self::RateTable => serde_json::to_value(RateTable {
+                default_apy_bps: apy.rate_bps,
+                token_apy_bps: BTreeMap::new(),
+            }),
+            Self::WebhookEndpoints => serde_json::to_value(WebhookEndpoints::new()),
+            Self::OrganizationFees => serde_json::to_value(OrganizationFees::new()),
+        };
+        value.unwrap_or(Value::Null)
+    }
+
+    /// Checks that `value` is a well-formed document for this setting.
+    pub fn validate(self, value: &Value) -> Result<(), String> {
+        match self {
+            Self::FeeSchedule => {
+                let fees: FeeSchedule = parse(value)?;
+                check_bps("payout_fee_bps", fees.payout_fee_bps)
+            }
+            Self::RateTable => {
+                let rates: RateTable = parse(value)?;
+                check_bps("default_apy_bps", rates.default_apy_bps)?;
+                rates
+                    .token_apy_bps
+                    .iter()
+                    .try_for_each(|(token, bps)| check_bps(token, *bps))
+            }
+            Self::WebhookEndpoints => {
+                let endpoints: WebhookEndpoints = parse(value)?;
+                endpoints.iter().try_for_each(|(event, url)| {
+                    if event.trim().is_empty() {
+                        return Err("Webhook event names must not be empty".to_string());
+                    }
+                    match reqwest::Url::parse(url) {
+                        Ok(parsed) if parsed.scheme() == "https" => Ok(()),
+                        _ => Err(format!("Webhook endpoint for {event} must be an https URL")),
+                    }
+                })
+            }
+            Self::OrganizationFees => {
+                let fees: OrganizationFees = parse(value)?;
+                fees.iter()
+                    .try_for_each(|(organization, bps)| check_bps(&organization.to_string(), *bps))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeSchedule {
+    pub payout_fee_bps: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateTable {
+    pub default_apy_bps: u32,
+    /// Per-token overrides keyed by token contract address.
+    #[serde(default)]
+    pub token_apy_bps: BTreeMap<String, u32>,
+}
+
+/// Outbound webhook URL for each event name.
+pub type WebhookEndpoints = BTreeMap<String, String>;
+
+/// Payout fee overrides in basis points keyed by organization id. An
+/// organization without an entry pays the platform fee schedule.
+pub type OrganizationFees = BTreeMap<Uuid, u32>;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PlatformSetting {
+    pub key: String,
+    pub value: Value,
+    pub version: i32,
+    pub updated_by: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, String> {
+    serde_json::from_value(value.clone()).map_err(|e| e.to_string())
+}
+
+fn check_bps(name: &str, bps: u32) -> Result<(), String> {
+    if bps > MAX_BPS {
+        return Err(format!("{name} must not exceed {MAX_BPS} basis points"));
+    }
+    Ok(())
+}
+
+/// Applies a JSON merge patch: objects merge recursively, `null` removes a
+/// field and any other value replaces it.
+pub fn merge_patch(target: &Value, patch: &Value) -> Value {
+    let Value::Object(patch_fields) = patch else {
+        return patch.clone();
+    };
+    let mut merged = match target {
+        Value::Object(fields) => fields.clone(),
+        _ => serde_json::Map::new(),
+    };
+    for (key, value) in patch_fields {
+        if value.is_null() {
+            merged.remove(key);
+        } else {
+            let current = merged.get(key).cloned().unwrap_or(Value::Null);
+            merged.insert(key.clone(), merge_patch(&current, value));
+        }
+    }
+    Value::Object(merged)
+}
+
+pub async fn load<'e, E: PgExecutor<'e>>(
+    executor: E,
+    key: SettingKey,
+) -> Result<Option<PlatformSetting>, sqlx::Error> {
+    sqlx::query_as::<_, PlatformSetting>(
+        "SELECT key, value, version, updated_by, updated_at FROM platform_settings WHERE key = $1",
+    )
+    .bind(key.as_str())
+    .fetch_optional(executor)
+    .await
+}
+
+/// Current fee schedule, falling back to `PAYOUT_FEE_BPS`.
+pub async fn fee_schedule<'e, E: PgExecutor<'e>>(
+    executor: E,
+    config: &Config,
+) -> Result<FeeSchedule, sqlx::Error> {
+    let stored = load(executor, SettingKey::FeeSchedule).await?;
+    Ok(stored
+        .and_then(|setting| serde_json::from_value(setting.value).ok())
+        .unwrap_or(FeeSchedule {
+            payout_fee_bps: config.payout_fee_bps,
+        }))
+}
+
+/// Current rate table, falling back to `APY_RATE_BPS` for every token.
+pub async fn rate_table<'e, E: PgExecutor<'e>>(
+    executor: E,
+    apy: &ApyConfig,
+) -> Result<RateTable, sqlx::Error> {
+    let stored = load(executor, SettingKey::RateTable).await?;
+    Ok(stored
+        .and_then(|setting| serde_json::from_value(setting.value).ok())
+        .unwrap_or(RateTable {
+            default_apy_bps: apy.rate_bps,
+            token_apy_bps: BTreeMap::new(),
+        }))
+}
+
+/// Approved organization fee overrides; empty until a change is applied.
+pub async fn organization_fees<'e, E: PgExecutor<'e>>(
+    executor: E,
+) -> Result<OrganizationFees, sqlx::Error> {
+    let stored = load(executor, SettingKey::OrganizationFees).await?;
+    Ok(stored
+        .and_then(|setting| serde_json::from_value(setting.value).ok())
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_patch_merges_and_removes() {
+        let target = json!({ "a": 1, "b": { "c": 2, "d": 3 } });
+        let patch = json!({ "a": null, "b": { "c": 5 }, "e": "x" });
+        assert_eq!(
+            merge_patch(&target, &patch),
+            json!({ "b": { "c": 5, "d": 3 }, "e": "x" })
+        );
+    }
+
+    #[test]
+    fn validates_each_setting() {
+        assert!(SettingKey::FeeSchedule
+            .validate(&json!({ "payout_fee_bps": 50 }))
+            .is_ok());
+        assert!(SettingKey::FeeSchedule
+            .validate(&json!({ "payout_fee_bps": 10_001 }))
+            .is_err());
+        assert!(SettingKey::FeeSchedule
+            .validate(&json!({ "payout_fee": 50 }))
+            .is_err());
+        assert!(SettingKey::RateTable
+            .validate(&json!({ "default_apy_bps": 500, "token_apy_bps": { "CUSDC": 20_000 } }))
+            .is_err());
+        assert!(SettingKey::WebhookEndpoints
+            .validate(&json!({ "kyc_status": "https://hooks.example.com/kyc" }))
+            .is_ok());
+        assert!(SettingKey::WebhookEndpoints
+            .validate(&json!({ "kyc_status": "http://hooks.example.com/kyc" }))
+            .is_err());
+        let organization = Uuid::new_v4().to_string();
+        assert!(SettingKey::OrganizationFees
+            .validate(&json!({ organization.clone(): 250 }))
+            .is_ok());
+        assert!(SettingKey::OrganizationFees
+            .validate(&json!({ organization: 10_001 }))
+            .is_err());
+        assert!(SettingKey::OrganizationFees
+            .validate(&json!({ "smith-estates": 250 }))
+            .is_err());
+    }
+}
@@ -0,0 +1,530 @@
+//! Projected payout schedule for a plan.
+//!
+//! Mirrors the claim-time math (yield accrual, beneficiary split, platform
+//! fee) so clients can chart what each beneficiary should receive and when.
+//! Installment plans keep earning yield on the undistributed balance between
+//! installments. Token amounts are [`Money`] in the plan's token; fiat
+//! values are estimated from the token's recent price trend and are omitted
+//! when no price history exists.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::money::Money;
+use crate::yield_calculator::calculate_yield;
+
+const PRICE_TREND_WINDOW_DAYS: i32 = 90;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct ProjectionPlanRow {
+    id: Uuid,
+    token_address: String,
+    amount: Decimal,
+    earn_yield: bool,
+    yield_rate_bps: i32,
+    accrued_yield: Decimal,
+    last_ping: i64,
+    grace_period_seconds: i64,
+    is_active: bool,
+    installment_count: i32,
+    installment_interval_days: i32,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct PricePoint {
+    price_usd: Decimal,
+    decimals: i16,
+    recorded_at: DateTime<Utc>,
+}
+
+/// Inputs to [`build_schedule`], independent of storage.
+#[derive(Debug, Clone)]
+pub struct ScheduleInput {
+    /// Asset the amounts are in: the plan's `token_address`.
+    pub currency: String,
+    pub principal: Decimal,
+    /// Yield already credited to the plan.
+    pub accrued_yield: Decimal,
+    /// Annual rate in basis points; zero when the plan does not earn yield.
+    pub yield_rate_bps: u32,
+    /// When yield last started accruing (the owner's last ping).
+    pub accrual_start: DateTime<Utc>,
+    /// When the first installment becomes claimable.
+    pub first_payout_at: DateTime<Utc>,
+    pub installment_count: u32,
+    pub interval_days: u32,
+    pub fee_bps: u32,
+    /// `(wallet_address, allocation_bps)` for each beneficiary.
+    pub beneficiaries: Vec<(String, u32)>,
+}
+
+/// Log-linear fit of recent prices, used to extrapolate token value.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PriceTrend {
+    pub latest_price_usd: f64,
+    pub latest_at: DateTime<Utc>,
+    /// Fitted change in ln(price) per day.
+    pub daily_log_drift: f64,
+    pub samples: usize,
+    pub decimals: u32,
+}
+
+impl PriceTrend {
+    /// Fits a trend to `(recorded_at, price)` samples. Returns `None` when
+    /// there are no usable prices.
+    pub fn fit(points: &[(DateTime<Utc>, f64)], decimals: u32) -> Option<Self> {
+        let usable: Vec<(f64, f64)> = points
+            .iter()
+            .filter(|(_, price)| *price > 0.0 && price.is_finite())
+            .map(|(at, price)| (at.timestamp() as f64 / SECONDS_PER_DAY as f64, price.ln()))
+            .collect();
+        let (latest_at, latest_price) = points
+            .iter()
+            .filter(|(_, price)| *price > 0.0 && price.is_finite())
+            .max_by_key(|(at, _)| *at)
+            .copied()?;
+
+        let n = usable.len() as f64;
+        let mean_x = usable.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = usable.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let (cov, var) = usable.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+            (
+                cov + (x - mean_x) * (y - mean_y),
+                var + (x - mean_x) * (x - mean_x),
+            )
+        });
+        let daily_log_drift = if var > 0.0 { cov / var } else { 0.0 };
+
+        Some(Self {
+            latest_price_usd: latest_price,
+            latest_at,
+            daily_log_drift,
+            samples: usable.len(),
+            decimals,
+        })
+    }
+
+    pub fn price_at(&self, at: DateTime<Utc>) -> f64 {
+        let days = (at - self.latest_at).num_seconds().max(0) as f64 / SECONDS_PER_DAY as f64;
+        self.latest_price_usd * (self.daily_log_drift * days).exp()
+    }
+
+    /// USD value of `amount` base units at the projected price for `at`.
+    pub fn value_of(&self, amount: Decimal, at: DateTime<Utc>) -> f64 {
+        let whole_tokens = amount.to_f64().unwrap_or(0.0) / 10f64.powi(self.decimals as i32);
+        whole_tokens * self.price_at(at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BeneficiaryShare {
+    pub wallet_address: String,
+    pub allocation_bps: u32,
+    pub gross_amount: Money,
+    pub fee_amount: Money,
+    pub net_amount: Money,
+    pub estimated_value_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Installment {
+    pub index: u32,
+    pub scheduled_at: DateTime<Utc>,
+    pub gross_amount: Money,
+    pub fee_amount: Money,
+    pub net_amount: Money,
+    /// Yield earned on the undistributed balance since the previous
+    /// installment (or since the last ping for the first one).
+    pub yield_amount: Money,
+    pub estimated_price_usd: Option<f64>,
+    pub estimated_value_usd: Option<f64>,
+    pub beneficiaries: Vec<BeneficiaryShare>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanProjection {
+    pub plan_id: Uuid,
+    pub token_address: String,
+    /// `lump_sum` or `installments`.
+    pub distribution: &'static str,
+    pub installment_count: u32,
+    pub installment_interval_days: u32,
+    pub fee_bps: u32,
+    pub total_gross: Money,
+    pub total_fees: Money,
+    pub total_net: Money,
+    pub total_estimated_value_usd: Option<f64>,
+    pub price_trend: Option<PriceTrend>,
+    pub installments: Vec<Installment>,
+}
+
+/// Interest on `balance` over the given period, truncated to whole base units.
+fn yield_between(
+    balance: Decimal,
+    rate_bps: u32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Decimal {
+    let elapsed = (to - from).num_seconds().max(0) as u64;
+    calculate_yield(balance, rate_bps, elapsed)
+        .unwrap_or(Decimal::ZERO)
+        .floor()
+}
+
+pub(crate) fn fee_for(amount: Decimal, fee_bps: u32) -> Decimal {
+    (amount * Decimal::from(fee_bps) / Decimal::from(10_000)).floor()
+}
+
+/// Splits `gross` by allocation, giving the rounding remainder to the last
+/// beneficiary so the shares always sum to `gross`.
+fn split_by_allocation(gross: Decimal, beneficiaries: &[(String, u32)]) -> Vec<Decimal> {
+    let mut shares: Vec<Decimal> = beneficiaries
+        .iter()
+        .map(|(_, bps)| (gross * Decimal::from(*bps) / Decimal::from(10_000)).floor())
+        .collect();
+    if let Some((last, others)) = shares.split_last_mut() {
+        *last = gross - others.iter().sum::<Decimal>();
+    }
+    shares
+}
+
+/// Computes the payout schedule. Amounts are whole base units; the final
+/// installment absorbs rounding so nothing is left undistributed.
+pub fn build_schedule(input: &ScheduleInput, trend: Option<&PriceTrend>) -> Vec<Installment> {
+    let count = input.installment_count.max(1);
+    let interval = chrono::Duration::days(i64::from(input.interval_days.max(1)));
+
+    let mut balance = input.principal + input.accrued_yield;
+    let mut accrued_since = input.accrual_start;
+    let mut installments = Vec::with_capacity(count as usize);
+    let money = |amount: Decimal| Money::new(amount, &input.currency);
+
+    for index in 0..count {
+        let scheduled_at = input.first_payout_at + interval * index as i32;
+        let yield_amount =
+            yield_between(balance, input.yield_rate_bps, accrued_since, scheduled_at);
+        balance += yield_amount;
+        accrued_since = scheduled_at;
+
+        let remaining = count - index;
+        let gross = if remaining == 1 {
+            balance
+        } else {
+            (balance / Decimal::from(remaining)).floor()
+        };
+        balance -= gross;
+
+        let beneficiaries: Vec<BeneficiaryShare> = input
+            .beneficiaries
+            .iter()
+            .zip(split_by_allocation(gross, &input.beneficiaries))
+            .map(|((wallet_address, allocation_bps), share)| {
+                let fee_amount = fee_for(share, input.fee_bps);
+                let net_amount = share - fee_amount;
+                BeneficiaryShare {
+                    wallet_address: wallet_address.clone(),
+                    allocation_bps: *allocation_bps,
+                    gross_amount: money(share),
+                    fee_amount: money(fee_amount),
+                    net_amount: money(net_amount),
+                    estimated_value_usd: trend.map(|t| t.value_of(net_amount, scheduled_at)),
+                }
+            })
+            .collect();
+
+        let fee_amount: Decimal = beneficiaries.iter().map(|b| b.fee_amount.amount).sum();
+        let net_amount = gross - fee_amount;
+
+        installments.push(Installment {
+            index,
+            scheduled_at,
+            gross_amount: money(gross),
+            fee_amount: money(fee_amount),
+            net_amount: money(net_amount),
+            yield_amount: money(yield_amount),
+            estimated_price_usd: trend.map(|t| t.price_at(scheduled_at)),
+            estimated_value_usd: trend.map(|t| t.value_of(net_amount, scheduled_at)),
+            beneficiaries,
+        });
+    }
+
+    installments
+}
+
+// Handler: Plan Payout Projection
+pub async fn get_plan_projection(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let plan = match sqlx::query_as::<_, ProjectionPlanRow>(
+        r#"
+        SELECT id, token_address, amount, earn_yield, yield_rate_bps, accrued_yield,
+               last_ping, grace_period_seconds, is_active,
+               installment_count, installment_interval_days
+        FROM plans
+        WHERE id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    {
+        Ok(Some(plan)) => plan,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Plan not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan for projection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    if !plan.is_active {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "Plan has already been paid out" })),
+        )
+            .into_response();
+    }
+
+    let beneficiaries: Vec<(String, i32)> = match sqlx::query_as(
+        "SELECT wallet_address, allocation_bps FROM beneficiaries WHERE plan_id = $1 ORDER BY wallet_address",
+    )
+    .bind(plan.id)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load beneficiaries for projection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    let prices = match sqlx::query_as::<_, PricePoint>(
+        r#"
+        SELECT price_usd, decimals, recorded_at
+        FROM asset_price_history
+        WHERE token_address = $1
+          AND recorded_at >= NOW() - ($2 * INTERVAL '1 day')
+        ORDER BY recorded_at ASC
+        "#,
+    )
+    .bind(&plan.token_address)
+    .bind(PRICE_TREND_WINDOW_DAYS)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load price history for projection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    let fees = match crate::organizations::plan_fee_schedule(&state.db_pool, &state.config, plan_id)
+        .await
+    {
+        Ok(fees) => fees,
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load fee schedule for projection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response();
+        }
+    };
+
+    let decimals = prices.last().map(|p| p.decimals.max(0) as u32).unwrap_or(7);
+    let samples: Vec<(DateTime<Utc>, f64)> = prices
+        .iter()
+        .filter_map(|p| Some((p.recorded_at, p.price_usd.to_f64()?)))
+        .collect();
+    let trend = PriceTrend::fit(&samples, decimals);
+
+    let now = Utc::now();
+    let accrual_start = Utc.timestamp_opt(plan.last_ping, 0).single().unwrap_or(now);
+    let first_payout_at =
+        (accrual_start + chrono::Duration::seconds(plan.grace_period_seconds)).max(now);
+
+    let input = ScheduleInput {
+        currency: plan.token_address.clone(),
+        principal: plan.amount,
+        accrued_yield: plan.accrued_yield.floor(),
+        yield_rate_bps: if plan.earn_yield {
+            plan.yield_rate_bps.max(0) as u32
+        } else {
+            0
+        },
+        accrual_start,
+        first_payout_at,
+        installment_count: plan.installment_count.max(1) as u32,
+        interval_days: plan.installment_interval_days.max(1) as u32,
+        fee_bps: fees.payout_fee_bps,
+        beneficiaries: beneficiaries
+            .into_iter()
+            .map(|(address, bps)| (address, bps.max(0) as u32))
+            .collect(),
+    };
+
+    let installments = build_schedule(&input, trend.as_ref());
+    let total = |amount: fn(&Installment) -> Decimal| {
+        Money::new(installments.iter().map(amount).sum(), &plan.token_address)
+    };
+    let total_gross = total(|i| i.gross_amount.amount);
+    let total_fees = total(|i| i.fee_amount.amount);
+    let total_net = total(|i| i.net_amount.amount);
+    let total_estimated_value_usd = trend.map(|_| {
+        installments
+            .iter()
+            .filter_map(|i| i.estimated_value_usd)
+            .sum()
+    });
+
+    let projection = PlanProjection {
+        plan_id: plan.id,
+        token_address: plan.token_address,
+        distribution: if input.installment_count > 1 {
+            "installments"
+        } else {
+            "lump_sum"
+        },
+        installment_count: input.installment_count,
+        installment_interval_days: input.interval_days,
+        fee_bps: input.fee_bps,
+        total_gross,
+        total_fees,
+        total_net,
+        total_estimated_value_usd,
+        price_trend: trend,
+        installments,
+    };
+
+    (StatusCode::OK, Json(projection)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(installment_count: u32, yield_rate_bps: u32, fee_bps: u32) -> ScheduleInput {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        ScheduleInput {
+            currency: "USDC".to_string(),
+            principal: Decimal::from(1_000_000),
+            accrued_yield: Decimal::ZERO,
+            yield_rate_bps,
+            accrual_start: start,
+            first_payout_at: start,
+            installment_count,
+            interval_days: 30,
+            fee_bps,
+            beneficiaries: vec![("GA".to_string(), 3_333), ("GB".to_string(), 6_667)],
+        }
+    }
+
+    #[test]
+    fn lump_sum_pays_everything_at_once() {
+        let schedule = build_schedule(&input(1, 0, 100), None);
+
+        assert_eq!(schedule.len(), 1);
+        let only = &schedule[0];
+        assert_eq!(only.gross_amount.amount, Decimal::from(1_000_000));
+        assert_eq!(only.fee_amount.amount, Decimal::from(10_000));
+        assert_eq!(only.net_amount.amount, Decimal::from(990_000));
+        assert_eq!(
+            only.beneficiaries[0].gross_amount.amount,
+            Decimal::from(333_300)
+        );
+        assert_eq!(
+            only.beneficiaries[1].gross_amount.amount,
+            Decimal::from(666_700)
+        );
+        assert!(only.estimated_value_usd.is_none());
+    }
+
+    #[test]
+    fn installments_distribute_the_full_balance_with_yield() {
+        let schedule = build_schedule(&input(3, 1_000, 0), None);
+
+        assert_eq!(schedule.len(), 3);
+        assert_eq!(
+            schedule[1].scheduled_at - schedule[0].scheduled_at,
+            chrono::Duration::days(30)
+        );
+        assert_eq!(schedule[0].yield_amount.amount, Decimal::ZERO);
+        assert!(schedule[1].yield_amount.amount > Decimal::ZERO);
+
+        let yield_total: Decimal = schedule.iter().map(|i| i.yield_amount.amount).sum();
+        let paid: Decimal = schedule.iter().map(|i| i.gross_amount.amount).sum();
+        assert_eq!(paid, Decimal::from(1_000_000) + yield_total);
+
+        for installment in &schedule {
+            let shares: Decimal = installment
+                .beneficiaries
+                .iter()
+                .map(|b| b.gross_amount.amount)
+                .sum();
+            assert_eq!(shares, installment.gross_amount.amount);
+        }
+    }
+
+    #[test]
+    fn price_trend_extrapolates_growth() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let points: Vec<(DateTime<Utc>, f64)> = (0..10)
+            .map(|day| {
+                (
+                    start + chrono::Duration::days(day),
+                    (0.01 * day as f64).exp(),
+                )
+            })
+            .collect();
+
+        let trend = PriceTrend::fit(&points, 7).unwrap();
+        assert!((trend.daily_log_drift - 0.01).abs() < 1e-9);
+
+        let later = trend.latest_at + chrono::Duration::days(10);
+        assert!((trend.price_at(later) - (0.01f64 * 19.0).exp()).abs() < 1e-6);
+        assert!(
+            (trend.value_of(Decimal::from(10_000_000), trend.latest_at) - trend.latest_price_usd)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn no_prices_means_no_trend() {
+        assert!(PriceTrend::fit(&[], 7).is_none());
+    }
+}
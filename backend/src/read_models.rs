@@ -0,0 +1,374 @@
+//! Materialized views behind the admin dashboards.
+//!
+//! Dashboard aggregations read from pre-aggregated views instead of the raw
+//! tables. [`ReadModelRefreshService`] refreshes them every
+//! `READ_MODEL_REFRESH_INTERVAL_SECS`, and every response says when its
+//! view was last refreshed so the dashboard can show how current it is.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use crate::api::AppState;
+use crate::jobs::JobRegistry;
+use crate::platform_settings;
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+const READ_MODEL_LOCK_KEY: i64 = 833;
+const DEFAULT_RANGE_DAYS: i64 = 30;
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadModel {
+    PlansByStatusDaily,
+    FeesCollectedDaily,
+}
+
+impl ReadModel {
+    pub const ALL: [Self; 2] = [Self::PlansByStatusDaily, Self::FeesCollectedDaily];
+
+    pub fn view_name(self) -> &'static str {
+        match self {
+            Self::PlansByStatusDaily => "plans_by_status_daily",
+            Self::FeesCollectedDaily => "fees_collected_daily",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Freshness {
+    pub view: &'static str,
+    pub refreshed_at: DateTime<Utc>,
+    pub age_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadModelResponse<T> {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub rows: Vec<T>,
+    pub freshness: Freshness,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PlansByStatusDay {
+    pub day: NaiveDate,
+    pub status: String,
+    pub plan_count: i64,
+    pub total_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FeesCollectedDay {
+    pub day: NaiveDate,
+    pub token_address: String,
+    pub payout_count: i64,
+    pub gross_amount: Decimal,
+    /// Fee at the current approved schedule, as in the `fees` report.
+    pub estimated_fees: Decimal,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DayRangeQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DayRangeQuery {
+    /// Inclusive day range, the last 30 days by default.
+    pub(crate) fn resolve(&self, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), &'static str> {
+        let to = self.to.unwrap_or(today);
+        let from = self
+            .from
+            .unwrap_or(to - ChronoDuration::days(DEFAULT_RANGE_DAYS - 1));
+        if from > to {
+            return Err("from must not be after to");
+        }
+        if (to - from).num_days() >= MAX_RANGE_DAYS {
+            return Err("Range must not exceed 366 days");
+        }
+        Ok((from, to))
+    }
+}
+
+pub async fn freshness<'e, E: PgExecutor<'e>>(
+    executor: E,
+    model: ReadModel,
+) -> Result<Freshness, sqlx::Error> {
+    let refreshed_at: Option<DateTime<Utc>> =
+        sqlx::query_scalar("SELECT refreshed_at FROM read_model_refreshes WHERE view_name = $1")
+            .bind(model.view_name())
+            .fetch_optional(executor)
+            .await?;
+    let refreshed_at = refreshed_at.unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+    Ok(Freshness {
+        view: model.view_name(),
+        refreshed_at,
+        age_seconds: (Utc::now() - refreshed_at).num_seconds().max(0),
+    })
+}
+
+pub async fn plans_by_status_daily<'e, E: PgExecutor<'e>>(
+    executor: E,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PlansByStatusDay>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT day, status, plan_count, total_amount
+        FROM plans_by_status_daily
+        WHERE day BETWEEN $1 AND $2
+        ORDER BY day, status
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(executor)
+    .await
+}
+
+pub async fn fees_collected_daily<'e, E: PgExecutor<'e>>(
+    executor: E,
+    from: NaiveDate,
+    to: NaiveDate,
+    fee_bps: u32,
+) -> Result<Vec<FeesCollectedDay>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT day, token_address, payout_count, gross_amount,
+               floor(gross_amount * $3 / 10000) AS estimated_fees
+        FROM fees_collected_daily
+        WHERE day BETWEEN $1 AND $2
+        ORDER BY day, token_address
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(i64::from(fee_bps))
+    .fetch_all(executor)
+    .await
+}
+
+/// Recomputes `model` without blocking readers and records when.
+pub async fn refresh(db: &PgPool, model: ReadModel) -> Result<Duration, sqlx::Error> {
+    let started = Instant::now();
+    let mut tx = db.begin().await?;
+    sqlx::query(&format!(
+        "REFRESH MATERIALIZED VIEW CONCURRENTLY {}",
+        model.view_name()
+    ))
+    .execute(&mut *tx)
+    .await?;
+    let elapsed = started.elapsed();
+    sqlx::query(
+        r#"
+        INSERT INTO read_model_refreshes (view_name, refreshed_at, duration_ms)
+        VALUES ($1, NOW(), $2)
+        ON CONFLICT (view_name)
+        DO UPDATE SET refreshed_at = EXCLUDED.refreshed_at, duration_ms = EXCLUDED.duration_ms
+        "#,
+    )
+    .bind(model.view_name())
+    .bind(elapsed.as_millis() as i64)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(elapsed)
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+// Handler: Plans by Status per Day
+pub async fn get_plans_by_status_daily(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DayRangeQuery>,
+) -> impl IntoResponse {
+    let (from, to) = match query.resolve(Utc::now().date_naive()) {
+        Ok(range) => range,
+        Err(message) => return bad_request(message),
+    };
+
+    let result = async {
+        let rows = plans_by_status_daily(&state.db_pool, from, to).await?;
+        let freshness = freshness(&state.db_pool, ReadModel::PlansByStatusDaily).await?;
+        Ok::<_, sqlx::Error>(ReadModelResponse {
+            from,
+            to,
+            rows,
+            freshness,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to read plans_by_status_daily");
+            database_error()
+        }
+    }
+}
+
+// Handler: Fees Collected per Day
+pub async fn get_fees_collected_daily(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DayRangeQuery>,
+) -> impl IntoResponse {
+    let (from, to) = match query.resolve(Utc::now().date_naive()) {
+        Ok(range) => range,
+        Err(message) => return bad_request(message),
+    };
+
+    let result = async {
+        let fees = platform_settings::fee_schedule(&state.db_pool, &state.config).await?;
+        let rows = fees_collected_daily(&state.db_pool, from, to, fees.payout_fee_bps).await?;
+        let freshness = freshness(&state.db_pool, ReadModel::FeesCollectedDaily).await?;
+        Ok::<_, sqlx::Error>(ReadModelResponse {
+            from,
+            to,
+            rows,
+            freshness,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to read fees_collected_daily");
+            database_error()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReadModelRefreshConfig {
+    pub interval: Duration,
+}
+
+impl ReadModelRefreshConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("READ_MODEL_REFRESH_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+        }
+    }
+}
+
+/// Keeps the dashboard read models current.
+pub struct ReadModelRefreshService {
+    db: PgPool,
+    config: ReadModelRefreshConfig,
+}
+
+impl ReadModelRefreshService {
+    pub fn new(db: PgPool, config: ReadModelRefreshConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("read_model_refresh", self.config.interval, move || {
+            let worker = self.clone();
+            async move { worker.run_once().await }
+        });
+    }
+
+    /// Refreshes every read model. Returns the number refreshed.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut lock_tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(READ_MODEL_LOCK_KEY)
+            .fetch_one(&mut *lock_tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Read model refresh lock is held by another worker; skipping run");
+            lock_tx.commit().await?;
+            return Ok(0);
+        }
+
+        let mut refreshed = 0;
+        for model in ReadModel::ALL {
+            match refresh(&self.db, model).await {
+                Ok(elapsed) => {
+                    info!(
+                        view = model.view_name(),
+                        elapsed_ms = elapsed.as_millis() as u64,
+                        "Read model refreshed"
+                    );
+                    refreshed += 1;
+                }
+                Err(e) => {
+                    error!(view = model.view_name(), error = %e, "Failed to refresh read model")
+                }
+            }
+        }
+
+        lock_tx.commit().await?;
+        Ok(refreshed)
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn day_ranges_default_to_the_last_thirty_days_and_are_bounded() {
+        let today = date(2026, 10, 16);
+        assert_eq!(
+            DayRangeQuery::default().resolve(today),
+            Ok((date(2026, 9, 17), today))
+        );
+
+        let reversed = DayRangeQuery {
+            from: Some(date(2026, 10, 2)),
+            to: Some(date(2026, 10, 1)),
+        };
+        assert!(reversed.resolve(today).is_err());
+
+        let too_long = DayRangeQuery {
+            from: Some(date(2024, 1, 1)),
+            to: Some(date(2026, 1, 1)),
+        };
+        assert!(too_long.resolve(today).is_err());
+    }
+}
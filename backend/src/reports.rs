@@ -0,0 +1,1303 @@
+//! Admin report builder with scheduled CSV delivery.
+//!
+//! A report definition names an entity, the columns to export, filters and
+//! an optional aggregation. Columns and filters are checked against a fixed
+//! list per entity, and filter values are always bound as parameters, so a
+//! definition can never inject SQL. Reports run on demand or on a cron
+//! schedule, and scheduled runs email the CSV to the listed recipients.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::config::Config;
+use crate::jobs::JobRegistry;
+use crate::mailer::{is_plausible_email, Attachment, Mailer};
+use crate::platform_settings;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_BATCH_SIZE: i64 = 10;
+const REPORT_LOCK_KEY: i64 = 827;
+/// Rows beyond this are dropped from a single report.
+pub const MAX_ROWS: i64 = 50_000;
+const MAX_RECIPIENTS: usize = 20;
+
+const DEFINITION_COLUMNS: &str = "id, name, entity, columns, filters, aggregation, schedule, recipients, next_run_at, last_run_at, created_by, created_at, updated_at";
+const RUN_COLUMNS: &str =
+    "id, report_id, triggered_by, status, row_count, emailed_to, error, started_at, finished_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportEntity {
+    Plans,
+    /// Beneficiary payouts.
+    Claims,
+    /// KYC provider decisions, for throughput.
+    Kyc,
+    /// Completed payouts with the platform fee they carry.
+    Fees,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Text,
+    Number,
+    Timestamp,
+    Bool,
+}
+
+impl ColumnKind {
+    fn sql_type(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Number => "numeric",
+            Self::Timestamp => "timestamptz",
+            Self::Bool => "boolean",
+        }
+    }
+}
+
+struct ColumnSpec {
+    name: &'static str,
+    expr: &'static str,
+    kind: ColumnKind,
+}
+
+const fn col(name: &'static str, expr: &'static str, kind: ColumnKind) -> ColumnSpec {
+    ColumnSpec { name, expr, kind }
+}
+
+const PLAN_COLUMNS: &[ColumnSpec] = &[
+    col("id", "p.id", ColumnKind::Text),
+    col("owner_address", "p.owner_address", ColumnKind::Text),
+    col("token_address", "p.token_address", ColumnKind::Text),
+    col("amount", "p.amount", ColumnKind::Number),
+    col("status", "p.status", ColumnKind::Text),
+    col("is_active", "p.is_active", ColumnKind::Bool),
+    col("earn_yield", "p.earn_yield", ColumnKind::Bool),
+    col("yield_rate_bps", "p.yield_rate_bps", ColumnKind::Number),
+    col("accrued_yield", "p.accrued_yield", ColumnKind::Number),
+    col(
+        "installment_count",
+        "p.installment_count",
+        ColumnKind::Number,
+    ),
+    col("created_at", "p.created_at", ColumnKind::Timestamp),
+    col(
+        "created_day",
+        "date_trunc('day', p.created_at)",
+        ColumnKind::Timestamp,
+    ),
+];
+
+const CLAIM_COLUMNS: &[ColumnSpec] = &[
+    col("id", "py.id", ColumnKind::Text),
+    col("plan_id", "py.plan_id", ColumnKind::Text),
+    col(
+        "beneficiary_address",
+        "py.beneficiary_address",
+        ColumnKind::Text,
+    ),
+    col("amount", "py.amount", ColumnKind::Number),
+    col("payout_type", "py.payout_type::text", ColumnKind::Text),
+    col("status", "py.status::text", ColumnKind::Text),
+    col("attempts", "py.attempts", ColumnKind::Number),
+    col("failure_reason", "py.failure_reason", ColumnKind::Text),
+    col("created_at", "py.created_at", ColumnKind::Timestamp),
+    col(
+        "created_day",
+        "date_trunc('day', py.created_at)",
+        ColumnKind::Timestamp,
+    ),
+    col("updated_at", "py.updated_at", ColumnKind::Timestamp),
+];
+
+const KYC_COLUMNS: &[ColumnSpec] = &[
+    col("id", "k.id", ColumnKind::Text),
+    col("wallet_address", "k.wallet_address", ColumnKind::Text),
+    col("event_type", "k.event_type", ColumnKind::Text),
+    col("kyc_status", "k.kyc_status::text", ColumnKind::Text),
+    col("success", "k.success", ColumnKind::Bool),
+    col("processed_at", "k.processed_at", ColumnKind::Timestamp),
+    col(
+        "processed_day",
+        "date_trunc('day', k.processed_at)",
+        ColumnKind::Timestamp,
+    ),
+];
+
+/// `{fee_bps}` is replaced with the approved payout fee when the query is
+/// built. Payouts do not store the fee they were charged, so this is the
+/// current schedule applied to each completed payout.
+const FEE_COLUMNS: &[ColumnSpec] = &[
+    col("payout_id", "py.id", ColumnKind::Text),
+    col("plan_id", "py.plan_id", ColumnKind::Text),
+    col(
+        "beneficiary_address",
+        "py.beneficiary_address",
+        ColumnKind::Text,
+    ),
+    col("payout_type", "py.payout_type::text", ColumnKind::Text),
+    col("amount", "py.amount", ColumnKind::Number),
+    col(
+        "estimated_fee",
+        "floor(py.amount * {fee_bps} / 10000)",
+        ColumnKind::Number,
+    ),
+    col("completed_at", "py.updated_at", ColumnKind::Timestamp),
+    col(
+        "completed_day",
+        "date_trunc('day', py.updated_at)",
+        ColumnKind::Timestamp,
+    ),
+];
+
+impl ReportEntity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Plans => "plans",
+            Self::Claims => "claims",
+            Self::Kyc => "kyc",
+            Self::Fees => "fees",
+        }
+    }
+
+    fn columns(self) -> &'static [ColumnSpec] {
+        match self {
+            Self::Plans => PLAN_COLUMNS,
+            Self::Claims => CLAIM_COLUMNS,
+            Self::Kyc => KYC_COLUMNS,
+            Self::Fees => FEE_COLUMNS,
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Self::Plans => "plans p",
+            Self::Claims => "payouts py",
+            Self::Kyc => "kyc_webhook_logs k",
+            Self::Fees => "payouts py",
+        }
+    }
+
+    fn base_condition(self) -> &'static str {
+        match self {
+            Self::Fees => "py.status = 'completed'",
+            _ => "TRUE",
+        }
+    }
+
+    /// Column used for the default newest-first ordering.
+    fn time_column(self) -> &'static str {
+        match self {
+            Self::Plans | Self::Claims => "created_at",
+            Self::Kyc => "processed_at",
+            Self::Fees => "completed_at",
+        }
+    }
+
+    fn column(self, name: &str) -> Result<&'static ColumnSpec, String> {
+        self.columns()
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| format!("Unknown column {name} for {}", self.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    Contains,
+    IsNull,
+    NotNull,
+}
+
+/// `value` is a JSON scalar, or an array for `in`. Timestamp filters also
+/// accept relative values such as `now-7d` or `now-12h`, resolved each run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportFilter {
+    pub column: String,
+    pub op: FilterOp,
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metric {
+    pub function: MetricFunction,
+    /// Optional for `count`, which then counts rows.
+    pub column: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Aggregation {
+    #[serde(default)]
+    pub group_by: Vec<String>,
+    pub metrics: Vec<Metric>,
+}
+
+/// The query part of a report definition.
+#[derive(Debug, Clone)]
+pub struct ReportSpec {
+    pub entity: ReportEntity,
+    pub columns: Vec<String>,
+    pub filters: Vec<ReportFilter>,
+    pub aggregation: Option<Aggregation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum BindValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+/// SQL ready to run, with its bind values and CSV header.
+#[derive(Debug)]
+pub struct BuiltQuery {
+    pub sql: String,
+    binds: Vec<BindValue>,
+    pub header: Vec<String>,
+}
+
+fn scalar_value(
+    kind: ColumnKind,
+    value: &serde_json::Value,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    use serde_json::Value;
+    match (kind, value) {
+        (ColumnKind::Text, Value::String(s)) => Ok(s.clone()),
+        (ColumnKind::Number, Value::Number(n)) => Ok(n.to_string()),
+        (ColumnKind::Number, Value::String(s)) if rust_decimal::Decimal::from_str(s).is_ok() => {
+            Ok(s.clone())
+        }
+        (ColumnKind::Bool, Value::Bool(b)) => Ok(b.to_string()),
+        (ColumnKind::Timestamp, Value::String(s)) => resolve_timestamp(s, now)
+            .map(|t| t.to_rfc3339())
+            .ok_or_else(|| format!("Invalid timestamp {s}")),
+        _ => Err(format!("Value {value} does not match the column type")),
+    }
+}
+
+/// Parses RFC 3339 or `now-<n>d|h|m`.
+fn resolve_timestamp(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    if value == "now" {
+        return Some(now);
+    }
+    let offset = value.strip_prefix("now-")?;
+    let (amount, unit) = offset.split_at(offset.len().checked_sub(1)?);
+    let amount: i64 = amount.parse().ok()?;
+    let delta = match unit {
+        "d" => ChronoDuration::days(amount),
+        "h" => ChronoDuration::hours(amount),
+        "m" => ChronoDuration::minutes(amount),
+        _ => return None,
+    };
+    Some(now - delta)
+}
+
+impl ReportSpec {
+    pub fn new(
+        entity: ReportEntity,
+        columns: Vec<String>,
+        filters: Vec<ReportFilter>,
+        aggregation: Option<Aggregation>,
+    ) -> Self {
+        Self {
+            entity,
+            columns,
+            filters,
+            aggregation,
+        }
+    }
+
+    /// Builds the query. `now` anchors relative timestamps and `fee_bps` is
+    /// the approved payout fee used by the `fees` entity.
+    pub fn build(&self, now: DateTime<Utc>, fee_bps: u32) -> Result<BuiltQuery, String> {
+        let entity = self.entity;
+        let expr = |spec: &ColumnSpec| spec.expr.replace("{fee_bps}", &fee_bps.to_string());
+        let mut binds = Vec::new();
+
+        let mut conditions = vec![entity.base_condition().to_string()];
+        for filter in &self.filters {
+            let spec = entity.column(&filter.column)?;
+            let target = format!("({})", expr(spec));
+            let cast = spec.kind.sql_type();
+            let condition = match filter.op {
+                FilterOp::IsNull => format!("{target} IS NULL"),
+                FilterOp::NotNull => format!("{target} IS NOT NULL"),
+                FilterOp::In => {
+                    let values = filter
+                        .value
+                        .as_array()
+                        .filter(|values| !values.is_empty())
+                        .ok_or_else(|| format!("{} in needs a non-empty array", spec.name))?
+                        .iter()
+                        .map(|value| scalar_value(spec.kind, value, now))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    binds.push(BindValue::List(values));
+                    format!("{target} = ANY(${}::{cast}[])", binds.len())
+                }
+                FilterOp::Contains => {
+                    if spec.kind != ColumnKind::Text {
+                        return Err(format!(
+                            "contains only applies to text columns, not {}",
+                            spec.name
+                        ));
+                    }
+                    binds.push(BindValue::Text(scalar_value(
+                        spec.kind,
+                        &filter.value,
+                        now,
+                    )?));
+                    format!("{target} ILIKE '%' || ${} || '%'", binds.len())
+                }
+                op => {
+                    let operator = match op {
+                        FilterOp::Eq => "=",
+                        FilterOp::Ne => "<>",
+                        FilterOp::Gt => ">",
+                        FilterOp::Gte => ">=",
+                        FilterOp::Lt => "<",
+                        _ => "<=",
+                    };
+                    binds.push(BindValue::Text(scalar_value(
+                        spec.kind,
+                        &filter.value,
+                        now,
+                    )?));
+                    format!("{target} {operator} ${}::{cast}", binds.len())
+                }
+            };
+            conditions.push(condition);
+        }
+
+        let (select, header, tail) = match &self.aggregation {
+            None => {
+                if self.columns.is_empty() {
+                    return Err("At least one column is required".to_string());
+                }
+                let specs = self
+                    .columns
+                    .iter()
+                    .map(|name| entity.column(name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let select = specs
+                    .iter()
+                    .map(|spec| format!("({})::text", expr(spec)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let order = expr(entity.column(entity.time_column())?);
+                (
+                    select,
+                    self.columns.clone(),
+                    format!("ORDER BY {order} DESC"),
+                )
+            }
+            Some(aggregation) => {
+                if aggregation.metrics.is_empty() {
+                    return Err("An aggregation needs at least one metric".to_string());
+                }
+                let mut select = Vec::new();
+                let mut header = Vec::new();
+                for name in &aggregation.group_by {
+                    let spec = entity.column(name)?;
+                    select.push(format!("({})::text", expr(spec)));
+                    header.push(name.clone());
+                }
+                for metric in &aggregation.metrics {
+                    let function = match metric.function {
+                        MetricFunction::Count => "count",
+                        MetricFunction::Sum => "sum",
+                        MetricFunction::Avg => "avg",
+                        MetricFunction::Min => "min",
+                        MetricFunction::Max => "max",
+                    };
+                    match (&metric.column, metric.function) {
+                        (None, MetricFunction::Count) => {
+                            select.push("count(*)::text".to_string());
+                            header.push("count".to_string());
+                        }
+                        (None, _) => return Err(format!("{function} needs a column")),
+                        (Some(name), _) => {
+                            let spec = entity.column(name)?;
+                            let numeric = spec.kind == ColumnKind::Number;
+                            let ordered = numeric || spec.kind == ColumnKind::Timestamp;
+                            let allowed = match metric.function {
+                                MetricFunction::Count => true,
+                                MetricFunction::Sum | MetricFunction::Avg => numeric,
+                                MetricFunction::Min | MetricFunction::Max => ordered,
+                            };
+                            if !allowed {
+                                return Err(format!("{function} does not apply to {name}"));
+                            }
+                            select.push(format!("{function}({})::text", expr(spec)));
+                            header.push(format!("{function}_{name}"));
+                        }
+                    }
+                }
+                let groups = aggregation.group_by.len();
+                let tail = if groups == 0 {
+                    String::new()
+                } else {
+                    let positions = (1..=groups)
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("GROUP BY {positions} ORDER BY {positions}")
+                };
+                (select.join(", "), header, tail)
+            }
+        };
+
+        let sql = format!(
+            "SELECT {select} FROM {} WHERE {} {tail} LIMIT {MAX_ROWS}",
+            entity.source(),
+            conditions.join(" AND "),
+        );
+        Ok(BuiltQuery { sql, binds, header })
+    }
+}
+
+/// Prefixes cells a spreadsheet would treat as a formula.
+pub(crate) fn csv_cell(value: &str) -> String {
+    let risky = value.starts_with(['=', '+', '@', '\t', '\r'])
+        || (value.starts_with('-') && value.parse::<f64>().is_err());
+    if risky {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(header: &[String], rows: &[Vec<Option<String>>]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(header)?;
+    for row in rows {
+        writer.write_record(
+            row.iter()
+                .map(|cell| csv_cell(cell.as_deref().unwrap_or(""))),
+        )?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))
+}
+
+async fn fetch_rows(
+    db: &PgPool,
+    query: &BuiltQuery,
+) -> Result<Vec<Vec<Option<String>>>, sqlx::Error> {
+    let mut statement = sqlx::query(&query.sql);
+    for bind in &query.binds {
+        statement = match bind {
+            BindValue::Text(value) => statement.bind(value),
+            BindValue::List(values) => statement.bind(values),
+        };
+    }
+    let rows = statement.fetch_all(db).await?;
+    rows.iter()
+        .map(|row| {
+            (0..query.header.len())
+                .map(|i| row.try_get::<Option<String>, _>(i))
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses a cron expression. Five-field expressions get a leading `0`
+/// seconds field; all schedules are in UTC.
+pub fn parse_schedule(expression: &str) -> Result<cron::Schedule, String> {
+    let expression = expression.trim();
+    let normalized = if expression.split_whitespace().count() == 5 {
+        format!("0 {expression}")
+    } else {
+        expression.to_string()
+    };
+    cron::Schedule::from_str(&normalized).map_err(|e| format!("Invalid schedule: {e}"))
+}
+
+pub fn next_run_after(expression: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_schedule(expression).ok()?.after(&after).next()
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ReportDefinition {
+    pub id: Uuid,
+    pub name: String,
+    pub entity: String,
+    pub columns: Vec<String>,
+    pub filters: serde_json::Value,
+    pub aggregation: Option<serde_json::Value>,
+    pub schedule: Option<String>,
+    pub recipients: Vec<String>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReportDefinition {
+    pub fn spec(&self) -> Result<ReportSpec, String> {
+        let entity = serde_json::from_value(serde_json::Value::String(self.entity.clone()))
+            .map_err(|_| format!("Unknown entity {}", self.entity))?;
+        let filters = serde_json::from_value(self.filters.clone()).map_err(|e| e.to_string())?;
+        let aggregation = self
+            .aggregation
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        Ok(ReportSpec::new(
+            entity,
+            self.columns.clone(),
+            filters,
+            aggregation,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ReportRun {
+    pub id: Uuid,
+    pub report_id: Uuid,
+    pub triggered_by: String,
+    pub status: String,
+    pub row_count: Option<i32>,
+    pub emailed_to: Vec<String>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportRequest {
+    pub name: String,
+    pub entity: ReportEntity,
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub filters: Vec<ReportFilter>,
+    pub aggregation: Option<Aggregation>,
+    pub schedule: Option<String>,
+    #[serde(default)]
+    pub recipients: Vec<String>,
+}
+
+impl ReportRequest {
+    /// Normalizes and checks the request; returns the next scheduled run.
+    fn validate(&mut self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err("A name is required".to_string());
+        }
+        ReportSpec::new(
+            self.entity,
+            self.columns.clone(),
+            self.filters.clone(),
+            self.aggregation.clone(),
+        )
+        .build(now, 0)?;
+
+        self.recipients = self
+            .recipients
+            .iter()
+            .map(|r| r.trim().to_lowercase())
+            .collect();
+        self.recipients.sort();
+        self.recipients.dedup();
+        if self.recipients.len() > MAX_RECIPIENTS {
+            return Err(format!("At most {MAX_RECIPIENTS} recipients are allowed"));
+        }
+        if let Some(bad) = self.recipients.iter().find(|r| !is_plausible_email(r)) {
+            return Err(format!("{bad} is not a valid email address"));
+        }
+
+        self.schedule = self
+            .schedule
+            .take()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        match &self.schedule {
+            None => Ok(None),
+            Some(_) if self.recipients.is_empty() => {
+                Err("Scheduled reports need at least one recipient".to_string())
+            }
+            Some(expression) => parse_schedule(expression)?
+                .after(&now)
+                .next()
+                .map(Some)
+                .ok_or_else(|| "The schedule never fires".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RunReportRequest {
+    /// Also email the CSV to the report's recipients.
+    #[serde(default)]
+    pub deliver: bool,
+}
+
+/// Output of one report run.
+pub struct ReportOutput {
+    pub csv: Vec<u8>,
+    pub row_count: usize,
+    pub emailed_to: Vec<String>,
+}
+
+fn attachment_name(definition: &ReportDefinition, at: DateTime<Utc>) -> String {
+    let slug: String = definition
+        .name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("{slug}-{}.csv", at.format("%Y%m%d-%H%M"))
+}
+
+/// Runs a report, optionally emails it, and records the run.
+pub async fn run_report(
+    db: &PgPool,
+    config: &Config,
+    mailer: &Mailer,
+    definition: &ReportDefinition,
+    triggered_by: &str,
+    deliver: bool,
+) -> Result<Result<ReportOutput, String>, sqlx::Error> {
+    let started_at = Utc::now();
+    let fees = platform_settings::fee_schedule(db, config).await?;
+
+    let outcome: Result<ReportOutput, String> = async {
+        let query = definition.spec()?.build(started_at, fees.payout_fee_bps)?;
+        let rows = fetch_rows(db, &query).await.map_err(|e| {
+            error!(report_id = %definition.id, error = %e, "Report query failed");
+            "The report query failed".to_string()
+        })?;
+        let csv = to_csv(&query.header, &rows).map_err(|e| e.to_string())?;
+
+        let mut emailed_to = Vec::new();
+        if deliver && !definition.recipients.is_empty() {
+            let attachment = Attachment {
+                filename: attachment_name(definition, started_at),
+                content_type: "text/csv".to_string(),
+                content: csv.clone(),
+            };
+            let subject = format!("InheritX report: {}", definition.name);
+            let text = format!(
+                "{} row(s) from the {} report, generated {}.",
+                rows.len(),
+                definition.name,
+                started_at.format("%Y-%m-%d %H:%M UTC")
+            );
+            for recipient in &definition.recipients {
+                match mailer
+                    .send_with_attachments(recipient, &subject, &text, std::slice::from_ref(&attachment))
+                    .await
+                {
+                    Ok(()) => emailed_to.push(recipient.clone()),
+                    Err(e) => {
+                        warn!(report_id = %definition.id, recipient = %recipient, error = %e, "Failed to email report")
+                    }
+                }
+            }
+            if emailed_to.is_empty() {
+                return Err("The report could not be emailed to any recipient".to_string());
+            }
+        }
+
+        Ok(ReportOutput {
+            csv,
+            row_count: rows.len(),
+            emailed_to,
+        })
+    }
+    .await;
+
+    let (status, row_count, emailed_to, error_message) = match &outcome {
+        Ok(output) => (
+            "succeeded",
+            Some(output.row_count as i32),
+            output.emailed_to.clone(),
+            None,
+        ),
+        Err(message) => ("failed", None, Vec::new(), Some(message.clone())),
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO report_runs (report_id, triggered_by, status, row_count, emailed_to, error, started_at, finished_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+        "#,
+    )
+    .bind(definition.id)
+    .bind(triggered_by)
+    .bind(status)
+    .bind(row_count)
+    .bind(&emailed_to)
+    .bind(&error_message)
+    .bind(started_at)
+    .execute(db)
+    .await?;
+    sqlx::query("UPDATE report_definitions SET last_run_at = $2 WHERE id = $1")
+        .bind(definition.id)
+        .bind(started_at)
+        .execute(db)
+        .await?;
+
+    Ok(outcome)
+}
+
+fn error_response(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+async fn load_definition(db: &PgPool, id: Uuid) -> Result<Option<ReportDefinition>, sqlx::Error> {
+    sqlx::query_as::<_, ReportDefinition>(&format!(
+        "SELECT {DEFINITION_COLUMNS} FROM report_definitions WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(db)
+    .await
+}
+
+// Handler: List Reports
+pub async fn list_reports(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match sqlx::query_as::<_, ReportDefinition>(&format!(
+        "SELECT {DEFINITION_COLUMNS} FROM report_definitions ORDER BY name"
+    ))
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(reports) => (StatusCode::OK, Json(reports)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list reports");
+            database_error()
+        }
+    }
+}
+
+// Handler: Create Report
+pub async fn create_report(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(mut payload): Json<ReportRequest>,
+) -> impl IntoResponse {
+    let next_run_at = match payload.validate(Utc::now()) {
+        Ok(next) => next,
+        Err(message) => return error_response(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let result: Result<ReportDefinition, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let report = sqlx::query_as::<_, ReportDefinition>(&format!(
+            r#"
+            INSERT INTO report_definitions (name, entity, columns, filters, aggregation, schedule, recipients, next_run_at, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {DEFINITION_COLUMNS}
+            "#
+        ))
+        .bind(&payload.name)
+        .bind(payload.entity.as_str())
+        .bind(&payload.columns)
+        .bind(serde_json::to_value(&payload.filters).unwrap_or_default())
+        .bind(payload.aggregation.as_ref().and_then(|a| serde_json::to_value(a).ok()))
+        .bind(&payload.schedule)
+        .bind(&payload.recipients)
+        .bind(next_run_at)
+        .bind(&admin.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "report.created",
+            &report.id.to_string(),
+            serde_json::json!({ "name": report.name, "entity": report.entity, "schedule": report.schedule }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(report)
+    }
+    .await;
+
+    match result {
+        Ok(report) => (StatusCode::CREATED, Json(report)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to create report");
+            database_error()
+        }
+    }
+}
+
+// Handler: Update Report
+pub async fn update_report(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(report_id): Path<Uuid>,
+    Json(mut payload): Json<ReportRequest>,
+) -> impl IntoResponse {
+    let next_run_at = match payload.validate(Utc::now()) {
+        Ok(next) => next,
+        Err(message) => return error_response(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let result: Result<Option<ReportDefinition>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let report = sqlx::query_as::<_, ReportDefinition>(&format!(
+            r#"
+            UPDATE report_definitions
+            SET name = $2, entity = $3, columns = $4, filters = $5, aggregation = $6,
+                schedule = $7, recipients = $8, next_run_at = $9, updated_at = NOW()
+            WHERE id = $1
+            RETURNING {DEFINITION_COLUMNS}
+            "#
+        ))
+        .bind(report_id)
+        .bind(&payload.name)
+        .bind(payload.entity.as_str())
+        .bind(&payload.columns)
+        .bind(serde_json::to_value(&payload.filters).unwrap_or_default())
+        .bind(payload.aggregation.as_ref().and_then(|a| serde_json::to_value(a).ok()))
+        .bind(&payload.schedule)
+        .bind(&payload.recipients)
+        .bind(next_run_at)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(report) = &report {
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "report.updated",
+                &report.id.to_string(),
+                serde_json::json!({ "name": report.name, "entity": report.entity, "schedule": report.schedule }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(report)
+    }
+    .await;
+
+    match result {
+        Ok(Some(report)) => (StatusCode::OK, Json(report)).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Report not found"),
+        Err(e) => {
+            error!(error = %e, "Failed to update report");
+            database_error()
+        }
+    }
+}
+
+// Handler: Delete Report
+pub async fn delete_report(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(report_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let result: Result<bool, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let name: Option<String> =
+            sqlx::query_scalar("DELETE FROM report_definitions WHERE id = $1 RETURNING name")
+                .bind(report_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if let Some(name) = &name {
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "report.deleted",
+                &report_id.to_string(),
+                serde_json::json!({ "name": name }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(name.is_some())
+    }
+    .await;
+
+    match result {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => error_response(StatusCode::NOT_FOUND, "Report not found"),
+        Err(e) => {
+            error!(error = %e, "Failed to delete report");
+            database_error()
+        }
+    }
+}
+
+// Handler: Run Report
+pub async fn run_report_now(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(report_id): Path<Uuid>,
+    payload: Option<Json<RunReportRequest>>,
+) -> impl IntoResponse {
+    let deliver = payload.map(|Json(p)| p.deliver).unwrap_or(false);
+    let definition = match load_definition(&state.db_pool, report_id).await {
+        Ok(Some(definition)) => definition,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "Report not found"),
+        Err(e) => {
+            error!(error = %e, "Failed to load report");
+            return database_error();
+        }
+    };
+
+    match run_report(
+        &state.db_pool,
+        &state.config,
+        &state.mailer,
+        &definition,
+        &admin.user_id,
+        deliver,
+    )
+    .await
+    {
+        Ok(Ok(output)) => {
+            info!(report_id = %report_id, rows = output.row_count, admin = %admin.user_id, "Report run");
+            let disposition = format!(
+                "attachment; filename=\"{}\"",
+                attachment_name(&definition, Utc::now())
+            );
+            (
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                output.csv,
+            )
+                .into_response()
+        }
+        Ok(Err(message)) => error_response(StatusCode::UNPROCESSABLE_ENTITY, &message),
+        Err(e) => {
+            error!(error = %e, "Failed to record report run");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Report Runs
+pub async fn list_report_runs(
+    State(state): State<Arc<AppState>>,
+    Path(report_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match sqlx::query_as::<_, ReportRun>(&format!(
+        "SELECT {RUN_COLUMNS} FROM report_runs WHERE report_id = $1 ORDER BY started_at DESC LIMIT 100"
+    ))
+    .bind(report_id)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(runs) => (StatusCode::OK, Json(runs)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list report runs");
+            database_error()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportSchedulerConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+}
+
+impl ReportSchedulerConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("REPORT_SCHEDULER_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("REPORT_SCHEDULER_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+pub struct ReportSchedulerService {
+    db: PgPool,
+    mailer: Arc<Mailer>,
+    app_config: Arc<Config>,
+    config: ReportSchedulerConfig,
+}
+
+impl ReportSchedulerService {
+    pub fn new(
+        db: PgPool,
+        mailer: Arc<Mailer>,
+        app_config: Arc<Config>,
+        config: ReportSchedulerConfig,
+    ) -> Self {
+        Self {
+            db,
+            mailer,
+            app_config,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("report_scheduler", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&runs| {
+                    if runs > 0 {
+                        info!(reports = runs, "Scheduled reports run");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Runs and emails every report whose schedule is due, then moves its
+    /// next run forward. Returns the number of reports run.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(REPORT_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Report scheduler lock is held by another worker; skipping run");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let due = sqlx::query_as::<_, ReportDefinition>(&format!(
+            r#"
+            SELECT {DEFINITION_COLUMNS} FROM report_definitions
+            WHERE schedule IS NOT NULL AND next_run_at <= NOW()
+            ORDER BY next_run_at
+            LIMIT $1
+            "#
+        ))
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for definition in &due {
+            match run_report(
+                &self.db,
+                &self.app_config,
+                &self.mailer,
+                definition,
+                crate::audit::SYSTEM_ACTOR,
+                true,
+            )
+            .await?
+            {
+                Ok(output) => info!(
+                    report_id = %definition.id,
+                    rows = output.row_count,
+                    recipients = output.emailed_to.len(),
+                    "Scheduled report delivered"
+                ),
+                Err(message) => {
+                    warn!(report_id = %definition.id, error = %message, "Scheduled report failed")
+                }
+            }
+
+            // A failed report waits for its next slot rather than retrying
+            // every tick.
+            let next_run_at = definition
+                .schedule
+                .as_deref()
+                .and_then(|expression| next_run_after(expression, Utc::now()));
+            sqlx::query("UPDATE report_definitions SET next_run_at = $2 WHERE id = $1")
+                .bind(definition.id)
+                .bind(next_run_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(due.len())
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 7, 1, 12, 0, 0).unwrap()
+    }
+
+    fn filter(column: &str, op: FilterOp, value: serde_json::Value) -> ReportFilter {
+        ReportFilter {
+            column: column.to_string(),
+            op,
+            value,
+        }
+    }
+
+    #[test]
+    fn builds_filtered_column_query() {
+        let spec = ReportSpec::new(
+            ReportEntity::Claims,
+            vec!["id".into(), "amount".into()],
+            vec![
+                filter("status", FilterOp::In, json!(["completed", "failed"])),
+                filter("created_at", FilterOp::Gte, json!("now-7d")),
+            ],
+            None,
+        );
+        let query = spec.build(now(), 0).unwrap();
+        assert_eq!(
+            query.sql,
+            "SELECT (py.id)::text, (py.amount)::text FROM payouts py \
+             WHERE TRUE AND (py.status::text) = ANY($1::text[]) AND (py.created_at) >= $2::timestamptz \
+             ORDER BY py.created_at DESC LIMIT 50000"
+        );
+        assert_eq!(
+            query.binds[1],
+            BindValue::Text("2026-06-24T12:00:00+00:00".into())
+        );
+        assert_eq!(query.header, vec!["id", "amount"]);
+    }
+
+    #[test]
+    fn builds_aggregated_fee_query() {
+        let spec = ReportSpec::new(
+            ReportEntity::Fees,
+            Vec::new(),
+            Vec::new(),
+            Some(Aggregation {
+                group_by: vec!["completed_day".into()],
+                metrics: vec![
+                    Metric {
+                        function: MetricFunction::Count,
+                        column: None,
+                    },
+                    Metric {
+                        function: MetricFunction::Sum,
+                        column: Some("estimated_fee".into()),
+                    },
+                ],
+            }),
+        );
+        let query = spec.build(now(), 50).unwrap();
+        assert!(query
+            .sql
+            .contains("sum(floor(py.amount * 50 / 10000))::text"));
+        assert!(query
+            .sql
+            .contains("WHERE py.status = 'completed' GROUP BY 1 ORDER BY 1"));
+        assert_eq!(
+            query.header,
+            vec!["completed_day", "count", "sum_estimated_fee"]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_columns_and_mismatched_values() {
+        let unknown = ReportSpec::new(
+            ReportEntity::Plans,
+            vec!["owner_address; DROP TABLE plans".into()],
+            Vec::new(),
+            None,
+        );
+        assert!(unknown.build(now(), 0).is_err());
+
+        let mismatched = ReportSpec::new(
+            ReportEntity::Plans,
+            vec!["id".into()],
+            vec![filter("amount", FilterOp::Gt, json!("lots"))],
+            None,
+        );
+        assert!(mismatched.build(now(), 0).is_err());
+
+        let sum_of_text = ReportSpec::new(
+            ReportEntity::Kyc,
+            Vec::new(),
+            Vec::new(),
+            Some(Aggregation {
+                group_by: Vec::new(),
+                metrics: vec![Metric {
+                    function: MetricFunction::Sum,
+                    column: Some("wallet_address".into()),
+                }],
+            }),
+        );
+        assert!(sum_of_text.build(now(), 0).is_err());
+    }
+
+    #[test]
+    fn writes_csv_and_neutralizes_formulas() {
+        let csv = to_csv(
+            &["name".to_string(), "amount".to_string()],
+            &[
+                vec![Some("=HYPERLINK(\"x\")".into()), Some("-5".into())],
+                vec![None, Some("1,000".into())],
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "name,amount\n\"'=HYPERLINK(\"\"x\"\")\",-5\n,\"1,000\"\n"
+        );
+    }
+
+    #[test]
+    fn parses_five_field_schedules() {
+        let next = next_run_after("0 8 * * Mon", now()).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 7, 6, 8, 0, 0).unwrap());
+        assert!(parse_schedule("every day").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! A user's own feed of sign-ins and security-relevant account changes.
+//!
+//! SEP-10 sign-ins and failed attempts are written to `audit_logs` with the
+//! client's IP address and user agent. `GET /api/users/me/security-events`
+//! reads those back together with the user's email changes, wallet
+//! re-authentication changes, payout destination updates and linked claim
+//! wallets, keeping only the details that are safe to show the user. A
+//! sign-in from a user agent the wallet has not used before raises a
+//! `new_device_login` notification, which is emailed when the user has an
+//! email on file.
+
+use axum::{
+    extract::{ConnectInfo, Query, Request, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::claim_fraud::{record_sighting, ClientDevice};
+use crate::notifications::create_localized_notification;
+use crate::pagination::{split_page, with_next_cursor, Cursor};
+use crate::templates::TemplateKey;
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+const MAX_USER_AGENT_CHARS: usize = 256;
+
+/// Audit actions listed in the feed, with the event name users see.
+const SECURITY_ACTIONS: &[(&str, &str)] = &[
+    ("auth.login", "login"),
+    ("auth.login_failed", "login_failed"),
+    ("email_change.requested", "email_change_requested"),
+    ("email_change.completed", "email_changed"),
+    ("email_change.cancelled", "email_change_cancelled"),
+    ("wallet_reauth.update", "wallet_reauth_changed"),
+    ("payout_destinations.update", "payout_destinations_changed"),
+    ("claim_invitation.wallet_linked", "wallet_linked"),
+];
+
+/// Detail fields shown to users; everything else stays in the audit log.
+const SAFE_DETAILS: &[&str] = &[
+    "ip",
+    "device",
+    "new_device",
+    "client_domain",
+    "reason",
+    "enabled",
+    "removal",
+];
+
+/// Where a sign-in attempt came from.
+#[derive(Debug, Clone)]
+pub struct LoginAttempt {
+    pub ip: Option<String>,
+    pub user_agent: String,
+    pub client_domain: Option<String>,
+}
+
+impl LoginAttempt {
+    pub fn from_request(state: &AppState, req: &Request) -> Self {
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ci| ci.0.ip());
+        let user_agent = req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        Self {
+            ip: state
+                .admin_access
+                .client_ip(peer, req.headers())
+                .map(|ip| ip.to_string()),
+            user_agent: user_agent.chars().take(MAX_USER_AGENT_CHARS).collect(),
+            client_domain: None,
+        }
+    }
+
+    fn device(&self) -> ClientDevice {
+        ClientDevice {
+            ip: self.ip.clone(),
+            user_agent_hash: hex::encode(Sha256::digest(self.user_agent.as_bytes())),
+        }
+    }
+
+    fn details(&self) -> serde_json::Value {
+        serde_json::json!({
+            "ip": self.ip,
+            "device": self.user_agent,
+            "client_domain": self.client_domain,
+        })
+    }
+}
+
+/// Records a successful sign-in by `wallet` and alerts the user when it
+/// came from a new device. A wallet's first sign-in is not treated as new.
+/// Returns whether the device was new.
+pub async fn record_login(
+    pool: &sqlx::PgPool,
+    wallet: &str,
+    attempt: &LoginAttempt,
+) -> Result<bool, sqlx::Error> {
+    let device = attempt.device();
+    let mut tx = pool.begin().await?;
+    let (seen_any, seen_device): (bool, bool) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) > 0, COALESCE(BOOL_OR(user_agent_hash = $2), false)
+        FROM wallet_devices
+        WHERE wallet_address = $1
+        "#,
+    )
+    .bind(wallet)
+    .bind(&device.user_agent_hash)
+    .fetch_one(&mut *tx)
+    .await?;
+    let new_device = seen_any && !seen_device;
+
+    let mut details = attempt.details();
+    details["new_device"] = serde_json::Value::Bool(new_device);
+    record_audit(&mut *tx, wallet, "auth.login", wallet, details).await?;
+    if new_device {
+        let device_name = if attempt.user_agent.is_empty() {
+            "an unknown device"
+        } else {
+            attempt.user_agent.as_str()
+        };
+        create_localized_notification(
+            &mut tx,
+            wallet,
+            "new_device_login",
+            TemplateKey::NewDeviceLogin,
+            &[
+                ("device", device_name),
+                ("ip", attempt.ip.as_deref().unwrap_or("an unknown address")),
+            ],
+            serde_json::json!({ "ip": attempt.ip, "device": attempt.user_agent }),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    record_sighting(pool, wallet, &device).await?;
+    Ok(new_device)
+}
+
+/// Records a refused sign-in as `wallet`. Best effort: a failure to record
+/// it is logged and does not change the response.
+pub async fn record_failed_login(
+    pool: &sqlx::PgPool,
+    wallet: &str,
+    attempt: &LoginAttempt,
+    reason: &str,
+) {
+    let mut details = attempt.details();
+    details["reason"] = serde_json::Value::from(reason);
+    if let Err(e) = record_audit(pool, wallet, "auth.login_failed", wallet, details).await {
+        warn!(error = %e, "Failed to record failed sign-in");
+    }
+}
+
+fn event_name(action: &str) -> Option<&'static str> {
+    SECURITY_ACTIONS
+        .iter()
+        .find(|(audit_action, _)| *audit_action == action)
+        .map(|(_, event)| *event)
+}
+
+fn safe_details(details: serde_json::Value) -> serde_json::Value {
+    match details {
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .filter(|(key, _)| SAFE_DETAILS.contains(&key.as_str()))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        _ => serde_json::json!({}),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SecurityEventsQuery {
+    pub limit: Option<i64>,
+    /// Only events before this time. Superseded by `cursor`, which does not
+    /// skip events that share a timestamp.
+    pub before: Option<DateTime<Utc>>,
+    /// `X-Next-Cursor` of the previous page.
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecurityEvent {
+    pub id: Uuid,
+    pub event: &'static str,
+    pub occurred_at: DateTime<Utc>,
+    pub details: serde_json::Value,
+}
+
+// Handler: Get My Security Events
+pub async fn get_my_security_events(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Query(query): Query<SecurityEventsQuery>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let cursor = match Cursor::parse(query.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": message })),
+            )
+                .into_response()
+        }
+    };
+    let actions: Vec<&str> = SECURITY_ACTIONS.iter().map(|(action, _)| *action).collect();
+
+    let rows = sqlx::query_as::<_, (Uuid, String, serde_json::Value, DateTime<Utc>)>(
+        r#"
+        SELECT id, action, details, created_at FROM audit_logs
+        WHERE actor = $1 AND action = ANY($2)
+          AND ($3::timestamptz IS NULL OR created_at < $3)
+          AND ($4::timestamptz IS NULL OR (created_at, id) < ($4, $5::uuid))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(&caller)
+    .bind(&actions)
+    .bind(query.before)
+    .bind(cursor.map(|c| c.created_at))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit + 1)
+    .fetch_all(&state.db_pool)
+    .await;
+
+    match rows {
+        Ok(rows) => {
+            let (rows, next) = split_page(rows, limit, |(id, _, _, at)| Cursor::new(*at, *id));
+            let events: Vec<SecurityEvent> = rows
+                .into_iter()
+                .filter_map(|(id, action, details, occurred_at)| {
+                    Some(SecurityEvent {
+                        id,
+                        event: event_name(&action)?,
+                        occurred_at,
+                        details: safe_details(details),
+                    })
+                })
+                .collect();
+            with_next_cursor((StatusCode::OK, Json(events)).into_response(), next)
+        }
+        Err(e) => {
+            error!(user = %caller, error = %e, "Failed to load security events");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Database query failed" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feed_hides_unlisted_actions_and_private_details() {
+        assert_eq!(event_name("auth.login"), Some("login"));
+        assert_eq!(event_name("user.frozen"), None);
+        let details = safe_details(serde_json::json!({
+            "ip": "203.0.113.7",
+            "new_device": true,
+            "destinations": [{ "account": "12345678" }],
+            "plan_id": "p-1",
+        }));
+        assert_eq!(
+            details,
+            serde_json::json!({ "ip": "203.0.113.7", "new_device": true })
+        );
+        assert_eq!(safe_details(serde_json::json!(null)), serde_json::json!({}));
+    }
+}
@@ -0,0 +1,1156 @@
+//! Stellar web authentication (SEP-10).
+//!
+//! `GET /auth` hands the wallet a challenge transaction signed by the
+//! server: sequence number zero, a 15 minute time bound and a
+//! `<home_domain> auth` manage data operation carrying a random nonce. The
+//! wallet signs it and posts it back to `POST /auth`. Once the signatures
+//! meet the account's medium threshold (or the master key signs, for
+//! accounts that do not exist yet) a JWT with the SEP-10 claims is issued.
+//! Wallet routes accept that token in place of per-request signatures.
+//!
+//! With `client_domain`, the challenge also names the signing key published
+//! in that domain's stellar.toml, which must sign too.
+//!
+//! Each token is also a session in `auth_sessions`. It stops working once it
+//! has been idle for the timeout written into it at sign-in. Its
+//! `auth_level` is `recently_verified` only within the step-up window of the
+//! last wallet signature; after that, sensitive actions need a `step_up`
+//! wallet challenge signed and exchanged at `POST /api/auth/step-up` for a
+//! refreshed token.
+
+use axum::{
+    extract::{FromRequest, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgExecutor, PgPool};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use stellar_xdr::curr::{
+    DataValue, DecoratedSignature, Hash, Limits, ManageDataOp, Memo, MuxedAccount, Operation,
+    OperationBody, Preconditions, ReadXdr, SequenceNumber, Signature, SignatureHint, String64,
+    TimeBounds, TimePoint, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+    TransactionV1Envelope, Uint256, WriteXdr,
+};
+use thiserror::Error;
+use tracing::{error, warn};
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::{bearer_token, AuthLevel};
+use crate::config::Config;
+use crate::deposits::{HorizonAccount, HorizonClient};
+use crate::freezes::user_frozen;
+use crate::http_client::{HttpClient, HttpPolicy};
+use crate::security_events::{self, LoginAttempt};
+use crate::wallet_reauth::{self, ReauthAction, ReauthError, WalletConfirmation};
+
+/// How long a challenge may be signed and returned.
+pub const CHALLENGE_TTL: Duration = Duration::from_secs(15 * 60);
+/// Lifetime of an issued token.
+pub const TOKEN_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const NONCE_BYTES: usize = 48;
+const BASE_FEE_STROOPS: u32 = 100;
+const WEB_AUTH_DOMAIN_KEY: &str = "web_auth_domain";
+const CLIENT_DOMAIN_KEY: &str = "client_domain";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Sep10Error {
+    #[error("account must be a Stellar account address (G...)")]
+    InvalidAccount,
+    #[error("home_domain is not served by this server")]
+    UnknownHomeDomain,
+    #[error("client_domain is not a valid domain")]
+    InvalidClientDomain,
+    #[error("could not read SIGNING_KEY from the client domain's stellar.toml")]
+    ClientDomainUnavailable,
+    #[error("transaction is not a valid challenge: {0}")]
+    InvalidChallenge(&'static str),
+    #[error("challenge has expired")]
+    Expired,
+    #[error("challenge is not signed by this server")]
+    MissingServerSignature,
+    #[error("challenge is not signed by the client domain")]
+    MissingClientDomainSignature,
+    #[error("challenge has a signature from an unknown signer")]
+    UnrecognizedSignature,
+    #[error("signatures do not meet the account's threshold")]
+    InsufficientSignatures,
+}
+
+/// JWT claims defined by SEP-10.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sep10Claims {
+    /// The auth endpoint that issued the token.
+    pub iss: String,
+    /// Authenticated account (`G...`).
+    pub sub: String,
+    pub iat: usize,
+    pub exp: usize,
+    /// Hex hash of the challenge transaction.
+    pub jti: String,
+    pub home_domain: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_domain: Option<String>,
+    /// Level granted by the last wallet signature.
+    #[serde(default)]
+    pub auth_level: AuthLevel,
+    /// When the wallet last signed: at sign-in or a later step-up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<usize>,
+    /// Seconds of inactivity after which the session ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<u64>,
+}
+
+impl Sep10Claims {
+    /// `auth_level`, downgraded once the last wallet signature is older
+    /// than `step_up_window`.
+    pub fn auth_level_at(&self, now: DateTime<Utc>, step_up_window: chrono::Duration) -> AuthLevel {
+        let recent = self.auth_time.is_some_and(|auth_time| {
+            now.timestamp() - auth_time as i64 <= step_up_window.num_seconds()
+        });
+        if recent {
+            self.auth_level
+        } else {
+            AuthLevel::Standard
+        }
+    }
+}
+
+/// A client domain named in a challenge and the key it signs with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientDomain {
+    pub domain: String,
+    pub signing_key: String,
+}
+
+/// A challenge that is well formed, current and signed by this server.
+#[derive(Debug, Clone)]
+pub struct Challenge {
+    pub account: String,
+    pub client_domain: Option<ClientDomain>,
+    pub hash: [u8; 32],
+    pub expires_at: DateTime<Utc>,
+    signatures: Vec<DecoratedSignature>,
+}
+
+/// A key allowed to sign for the account, with its weight.
+#[derive(Debug, Clone, Copy)]
+pub struct AccountSigner {
+    pub public_key: [u8; 32],
+    pub weight: u32,
+}
+
+impl AccountSigner {
+    /// Signers that satisfy an account that does not exist on the network:
+    /// only its master key.
+    pub fn master(account: &str) -> Result<Vec<Self>, Sep10Error> {
+        Ok(vec![Self {
+            public_key: parse_account(account)?,
+            weight: 1,
+        }])
+    }
+
+    /// Ed25519 signers of an on-chain account and its medium threshold.
+    pub fn from_horizon(account: &HorizonAccount) -> (Vec<Self>, u32) {
+        let signers = account
+            .signers
+            .iter()
+            .filter(|s| s.kind == "ed25519_public_key" && s.weight > 0)
+            .filter_map(|s| {
+                Some(Self {
+                    public_key: parse_account(&s.key).ok()?,
+                    weight: u32::from(s.weight),
+                })
+            })
+            .collect();
+        (signers, u32::from(account.thresholds.med_threshold))
+    }
+}
+
+/// The server side of SEP-10, built from configuration.
+pub struct Sep10Server {
+    signing_key: SigningKey,
+    account: String,
+    home_domain: String,
+    web_auth_domain: String,
+    network_id: [u8; 32],
+    jwt_secret: String,
+}
+
+impl Sep10Server {
+    /// `None` unless `SEP10_SIGNING_SEED` and `SEP10_HOME_DOMAIN` are set.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let seed = config.sep10_signing_seed.as_deref()?;
+        let home_domain = config.sep10_home_domain.clone()?;
+        let seed = stellar_strkey::ed25519::PrivateKey::from_string(seed).ok()?;
+        let signing_key = SigningKey::from_bytes(&seed.0);
+        let account =
+            stellar_strkey::ed25519::PublicKey(signing_key.verifying_key().to_bytes()).to_string();
+
+        Some(Self {
+            signing_key,
+            account,
+            web_auth_domain: config
+                .sep10_web_auth_domain
+                .clone()
+                .unwrap_or_else(|| home_domain.clone()),
+            home_domain,
+            network_id: Sha256::digest(config.network_passphrase.as_bytes()).into(),
+            jwt_secret: config.jwt_secret.clone(),
+        })
+    }
+
+    /// Server account (`G...`), published as `SIGNING_KEY`.
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    pub fn home_domain(&self) -> &str {
+        &self.home_domain
+    }
+
+    /// `iss` of issued tokens.
+    pub fn issuer(&self) -> String {
+        format!("https://{}/auth", self.web_auth_domain)
+    }
+
+    /// Builds and signs a challenge for `account`, returned as base64 XDR.
+    pub fn challenge(
+        &self,
+        account: &str,
+        client_domain: Option<&ClientDomain>,
+        now: DateTime<Utc>,
+    ) -> Result<String, Sep10Error> {
+        let client = parse_account(account)?;
+        let server = self.signing_key.verifying_key().to_bytes();
+
+        let mut nonce = [0u8; NONCE_BYTES];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let nonce = base64::engine::general_purpose::STANDARD.encode(nonce);
+
+        let mut operations = vec![
+            manage_data(
+                client,
+                &format!("{} auth", self.home_domain),
+                nonce.as_bytes(),
+            )?,
+            manage_data(server, WEB_AUTH_DOMAIN_KEY, self.web_auth_domain.as_bytes())?,
+        ];
+        if let Some(client_domain) = client_domain {
+            operations.push(manage_data(
+                parse_account(&client_domain.signing_key)?,
+                CLIENT_DOMAIN_KEY,
+                client_domain.domain.as_bytes(),
+            )?);
+        }
+
+        let min_time = now.timestamp().max(0) as u64;
+        let operation_count = operations.len() as u32;
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(server)),
+            fee: BASE_FEE_STROOPS * operation_count,
+            seq_num: SequenceNumber(0),
+            cond: Preconditions::Time(TimeBounds {
+                min_time: TimePoint(min_time),
+                max_time: TimePoint(min_time + CHALLENGE_TTL.as_secs()),
+            }),
+            memo: Memo::None,
+            operations: operations
+                .try_into()
+                .map_err(|_| Sep10Error::InvalidChallenge("too many operations"))?,
+            ext: TransactionExt::V0,
+        };
+
+        let hash = self.transaction_hash(&tx);
+        let signature = self.signing_key.sign(&hash).to_bytes();
+        let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx,
+            signatures: vec![DecoratedSignature {
+                hint: signature_hint(&server),
+                signature: Signature(
+                    signature
+                        .as_slice()
+                        .try_into()
+                        .map_err(|_| Sep10Error::InvalidChallenge("signature"))?,
+                ),
+            }]
+            .try_into()
+            .map_err(|_| Sep10Error::InvalidChallenge("signature"))?,
+        });
+
+        envelope
+            .to_xdr_base64(Limits::none())
+            .map_err(|_| Sep10Error::InvalidChallenge("could not encode"))
+    }
+
+    /// Checks that `envelope` is a challenge this server issued and that it
+    /// is still current. Client signatures are checked separately by
+    /// [`Sep10Server::verify_signers`].
+    pub fn read_challenge(
+        &self,
+        envelope: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Challenge, Sep10Error> {
+        let invalid = Sep10Error::InvalidChallenge;
+        let TransactionEnvelope::Tx(envelope) =
+            TransactionEnvelope::from_xdr_base64(envelope.trim(), Limits::none())
+                .map_err(|_| invalid("not a transaction envelope"))?
+        else {
+            return Err(invalid("not a v1 transaction envelope"));
+        };
+        let tx = &envelope.tx;
+        let server = self.signing_key.verifying_key().to_bytes();
+
+        if tx.source_account != MuxedAccount::Ed25519(Uint256(server)) {
+            return Err(invalid("source account is not the server account"));
+        }
+        if tx.seq_num.0 != 0 {
+            return Err(invalid("sequence number must be zero"));
+        }
+        if tx.memo != Memo::None {
+            return Err(invalid("memos are not supported"));
+        }
+        let Preconditions::Time(bounds) = &tx.cond else {
+            return Err(invalid("missing time bounds"));
+        };
+        let now_secs = now.timestamp().max(0) as u64;
+        if bounds.max_time.0 == 0 {
+            return Err(invalid("missing time bounds"));
+        }
+        if now_secs < bounds.min_time.0 || now_secs > bounds.max_time.0 {
+            return Err(Sep10Error::Expired);
+        }
+
+        let mut operations = tx.operations.iter().map(read_manage_data);
+        let (client, name, value) = operations.next().ok_or(invalid("no operations"))??;
+        if name != format!("{} auth", self.home_domain) {
+            return Err(Sep10Error::UnknownHomeDomain);
+        }
+        if value.len() != 64 {
+            return Err(invalid("nonce must be 64 bytes"));
+        }
+
+        let mut client_domain = None;
+        for operation in operations {
+            let (source, name, value) = operation?;
+            if name == CLIENT_DOMAIN_KEY {
+                if client_domain.is_some() {
+                    return Err(invalid("more than one client_domain operation"));
+                }
+                client_domain = Some(ClientDomain {
+                    domain: String::from_utf8(value)
+                        .map_err(|_| invalid("client_domain is not text"))?,
+                    signing_key: stellar_strkey::ed25519::PublicKey(source).to_string(),
+                });
+                continue;
+            }
+            if source != server {
+                return Err(invalid("operation source is not the server account"));
+            }
+            if name == WEB_AUTH_DOMAIN_KEY && value != self.web_auth_domain.as_bytes() {
+                return Err(invalid("web_auth_domain does not match"));
+            }
+        }
+
+        let hash = self.transaction_hash(tx);
+        if !envelope
+            .signatures
+            .iter()
+            .any(|signature| verify_signature(&server, &hash, signature))
+        {
+            return Err(Sep10Error::MissingServerSignature);
+        }
+
+        Ok(Challenge {
+            account: stellar_strkey::ed25519::PublicKey(client).to_string(),
+            client_domain,
+            hash,
+            expires_at: DateTime::from_timestamp(bounds.max_time.0 as i64, 0).unwrap_or(now),
+            signatures: envelope.signatures.to_vec(),
+        })
+    }
+
+    /// Requires every signature besides the server's to come from the client
+    /// domain key or a distinct account signer, and the signer weights to
+    /// reach `threshold` (at least 1).
+    pub fn verify_signers(
+        &self,
+        challenge: &Challenge,
+        signers: &[AccountSigner],
+        threshold: u32,
+    ) -> Result<(), Sep10Error> {
+        let server = self.signing_key.verifying_key().to_bytes();
+        let client_domain_key = match &challenge.client_domain {
+            Some(client_domain) => Some(parse_account(&client_domain.signing_key)?),
+            None => None,
+        };
+
+        let mut client_domain_signed = false;
+        let mut used = HashSet::new();
+        let mut weight = 0u32;
+        for signature in &challenge.signatures {
+            if verify_signature(&server, &challenge.hash, signature) {
+                continue;
+            }
+            if let Some(key) = &client_domain_key {
+                if verify_signature(key, &challenge.hash, signature) {
+                    client_domain_signed = true;
+                    continue;
+                }
+            }
+            let signer = signers
+                .iter()
+                .find(|s| {
+                    !used.contains(&s.public_key)
+                        && verify_signature(&s.public_key, &challenge.hash, signature)
+                })
+                .ok_or(Sep10Error::UnrecognizedSignature)?;
+            used.insert(signer.public_key);
+            weight += signer.weight;
+        }
+
+        if client_domain_key.is_some() && !client_domain_signed {
+            return Err(Sep10Error::MissingClientDomainSignature);
+        }
+        if weight < threshold.max(1) {
+            return Err(Sep10Error::InsufficientSignatures);
+        }
+        Ok(())
+    }
+
+    /// Claims of the session token for a verified challenge.
+    pub fn session_claims(
+        &self,
+        challenge: &Challenge,
+        now: DateTime<Utc>,
+        idle_timeout: chrono::Duration,
+    ) -> Sep10Claims {
+        let iat = now.timestamp().max(0) as usize;
+        Sep10Claims {
+            iss: self.issuer(),
+            sub: challenge.account.clone(),
+            iat,
+            exp: iat + TOKEN_TTL.as_secs() as usize,
+            jti: hex::encode(challenge.hash),
+            home_domain: self.home_domain.clone(),
+            client_domain: challenge.client_domain.as_ref().map(|c| c.domain.clone()),
+            // Signing the challenge is a fresh wallet signature.
+            auth_level: AuthLevel::RecentlyVerified,
+            auth_time: Some(iat),
+            idle_timeout: Some(idle_timeout.num_seconds().max(0) as u64),
+        }
+    }
+
+    /// The same session after a step-up: recently verified as of `now`,
+    /// with its expiry unchanged.
+    pub fn step_up_claims(&self, claims: &Sep10Claims, now: DateTime<Utc>) -> Sep10Claims {
+        Sep10Claims {
+            auth_level: AuthLevel::RecentlyVerified,
+            auth_time: Some(now.timestamp().max(0) as usize),
+            ..claims.clone()
+        }
+    }
+
+    pub fn issue_token(&self, claims: &Sep10Claims) -> Result<String, jsonwebtoken::errors::Error> {
+        encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(self.jwt_secret.as_ref()),
+        )
+    }
+
+    fn transaction_hash(&self, tx: &Transaction) -> [u8; 32] {
+        let payload = TransactionSignaturePayload {
+            network_id: Hash(self.network_id),
+            tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+        };
+        let bytes = payload.to_xdr(Limits::none()).unwrap_or_default();
+        Sha256::digest(bytes).into()
+    }
+}
+
+/// Decodes a token issued by [`Sep10Server::issue_token`] and returns the
+/// authenticated account.
+pub fn verify_token(config: &Config, token: &str) -> Option<String> {
+    verify_claims(config, token).map(|claims| claims.sub)
+}
+
+/// Decodes a token issued by [`Sep10Server::issue_token`].
+pub fn verify_claims(config: &Config, token: &str) -> Option<Sep10Claims> {
+    let server = Sep10Server::from_config(config)?;
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&[server.issuer()]);
+    validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+
+    let claims = decode::<Sep10Claims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_ref()),
+        &validation,
+    )
+    .ok()?
+    .claims;
+    parse_account(&claims.sub).ok()?;
+    Some(claims)
+}
+
+/// Records the session of a newly issued token.
+pub async fn open_session<'e, E: PgExecutor<'e>>(
+    executor: E,
+    claims: &Sep10Claims,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        WITH purged AS (DELETE FROM auth_sessions WHERE expires_at < NOW())
+        INSERT INTO auth_sessions (jti, account, expires_at)
+        VALUES ($1, $2, to_timestamp($3))
+        ON CONFLICT (jti) DO NOTHING
+        "#,
+    )
+    .bind(&claims.jti)
+    .bind(&claims.sub)
+    .bind(claims.exp as f64)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Marks the session active. False when it is unknown or has been idle
+/// longer than its timeout; `default_idle_timeout` applies to tokens that
+/// do not carry one.
+pub async fn touch_session(
+    db: &PgPool,
+    claims: &Sep10Claims,
+    default_idle_timeout: chrono::Duration,
+) -> Result<bool, sqlx::Error> {
+    let idle_timeout = claims
+        .idle_timeout
+        .map_or(default_idle_timeout.num_seconds() as f64, |secs| {
+            secs as f64
+        });
+    let touched = sqlx::query(
+        r#"
+        UPDATE auth_sessions SET last_active_at = NOW()
+        WHERE jti = $1 AND account = $2
+          AND last_active_at > NOW() - make_interval(secs => $3)
+        "#,
+    )
+    .bind(&claims.jti)
+    .bind(&claims.sub)
+    .bind(idle_timeout)
+    .execute(db)
+    .await?;
+    Ok(touched.rows_affected() > 0)
+}
+
+fn parse_account(account: &str) -> Result<[u8; 32], Sep10Error> {
+    stellar_strkey::ed25519::PublicKey::from_string(account.trim())
+        .map(|key| key.0)
+        .map_err(|_| Sep10Error::InvalidAccount)
+}
+
+fn manage_data(source: [u8; 32], name: &str, value: &[u8]) -> Result<Operation, Sep10Error> {
+    Ok(Operation {
+        source_account: Some(MuxedAccount::Ed25519(Uint256(source))),
+        body: OperationBody::ManageData(ManageDataOp {
+            data_name: String64::try_from(name.as_bytes().to_vec())
+                .map_err(|_| Sep10Error::InvalidChallenge("data name is too long"))?,
+            data_value: Some(
+                DataValue::try_from(value.to_vec())
+                    .map_err(|_| Sep10Error::InvalidChallenge("data value is too long"))?,
+            ),
+        }),
+    })
+}
+
+/// Source, name and value of a manage data operation with an explicit
+/// `G...` source.
+fn read_manage_data(operation: &Operation) -> Result<([u8; 32], String, Vec<u8>), Sep10Error> {
+    let invalid = Sep10Error::InvalidChallenge;
+    let Some(MuxedAccount::Ed25519(Uint256(source))) = &operation.source_account else {
+        return Err(invalid("operations must have a G... source account"));
+    };
+    let OperationBody::ManageData(op) = &operation.body else {
+        return Err(invalid("only manage data operations are allowed"));
+    };
+    let name =
+        String::from_utf8(op.data_name.to_vec()).map_err(|_| invalid("data name is not text"))?;
+    let value = op
+        .data_value
+        .as_ref()
+        .map(|v| v.to_vec())
+        .ok_or(invalid("data value is missing"))?;
+    Ok((*source, name, value))
+}
+
+fn signature_hint(public_key: &[u8; 32]) -> SignatureHint {
+    SignatureHint([
+        public_key[28],
+        public_key[29],
+        public_key[30],
+        public_key[31],
+    ])
+}
+
+fn verify_signature(
+    public_key: &[u8; 32],
+    hash: &[u8; 32],
+    signature: &DecoratedSignature,
+) -> bool {
+    if signature.hint != signature_hint(public_key) {
+        return false;
+    }
+    let Ok(key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    let Ok(signature) = ed25519_dalek::Signature::from_slice(signature.signature.0.as_slice())
+    else {
+        return false;
+    };
+    key.verify(hash, &signature).is_ok()
+}
+
+fn is_valid_domain(domain: &str) -> bool {
+    !domain.is_empty()
+        && domain.len() <= 253
+        && domain
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b':'))
+}
+
+/// `SIGNING_KEY` from `https://<domain>/.well-known/stellar.toml`.
+async fn fetch_client_signing_key(domain: &str) -> Result<String, Sep10Error> {
+    let http = HttpClient::new(
+        "client_domain",
+        HttpPolicy {
+            retries: 0,
+            public_only: true,
+            ..HttpPolicy::default()
+        },
+    );
+    let request = http.get(format!("https://{domain}/.well-known/stellar.toml"));
+    let body = http
+        .send(request)
+        .await
+        .and_then(|response| response.error_for_status().map_err(Into::into))
+        .map_err(|e| {
+            warn!(domain, error = %e, "Failed to fetch client domain stellar.toml");
+            Sep10Error::ClientDomainUnavailable
+        })?
+        .text()
+        .await
+        .map_err(|_| Sep10Error::ClientDomainUnavailable)?;
+
+    let document: toml::Table = body
+        .parse()
+        .map_err(|_| Sep10Error::ClientDomainUnavailable)?;
+    let key = document
+        .get("SIGNING_KEY")
+        .and_then(|v| v.as_str())
+        .ok_or(Sep10Error::ClientDomainUnavailable)?;
+    parse_account(key).map_err(|_| Sep10Error::ClientDomainUnavailable)?;
+    Ok(key.to_string())
+}
+
+fn error_response(status: StatusCode, message: impl ToString) -> Response {
+    (
+        status,
+        Json(serde_json::json!({ "error": message.to_string() })),
+    )
+        .into_response()
+}
+
+fn not_configured() -> Response {
+    error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "SEP-10 web authentication is not configured",
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengeQuery {
+    pub account: String,
+    pub home_domain: Option<String>,
+    pub client_domain: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeResponse {
+    pub transaction: String,
+    pub network_passphrase: String,
+}
+
+// Handler: SEP-10 Challenge
+pub async fn get_challenge(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ChallengeQuery>,
+) -> Response {
+    let Some(server) = Sep10Server::from_config(&state.config) else {
+        return not_configured();
+    };
+    if query
+        .home_domain
+        .as_deref()
+        .is_some_and(|domain| domain != server.home_domain())
+    {
+        return error_response(StatusCode::BAD_REQUEST, Sep10Error::UnknownHomeDomain);
+    }
+
+    let client_domain = match query.client_domain.as_deref().map(str::trim) {
+        Some(domain) if !is_valid_domain(domain) => {
+            return error_response(StatusCode::BAD_REQUEST, Sep10Error::InvalidClientDomain);
+        }
+        Some(domain) => match fetch_client_signing_key(domain).await {
+            Ok(signing_key) => Some(ClientDomain {
+                domain: domain.to_string(),
+                signing_key,
+            }),
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+        },
+        None => None,
+    };
+
+    match server.challenge(&query.account, client_domain.as_ref(), Utc::now()) {
+        Ok(transaction) => Json(ChallengeResponse {
+            transaction,
+            network_passphrase: state.config.network_passphrase.clone(),
+        })
+        .into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub transaction: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub token: String,
+}
+
+// Handler: SEP-10 Token
+pub async fn post_challenge(State(state): State<Arc<AppState>>, req: Request) -> Response {
+    let Some(server) = Sep10Server::from_config(&state.config) else {
+        return not_configured();
+    };
+
+    let mut attempt = LoginAttempt::from_request(&state, &req);
+
+    // SEP-10 allows both JSON and form-encoded bodies.
+    let is_form = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+    let body = if is_form {
+        Form::<TokenRequest>::from_request(req, &())
+            .await
+            .map(|Form(body)| body)
+            .map_err(|e| e.body_text())
+    } else {
+        Json::<TokenRequest>::from_request(req, &())
+            .await
+            .map(|Json(body)| body)
+            .map_err(|e| e.body_text())
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(message) => return error_response(StatusCode::BAD_REQUEST, message),
+    };
+
+    let now = Utc::now();
+    let challenge = match server.read_challenge(&body.transaction, now) {
+        Ok(challenge) => challenge,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+    attempt.client_domain = challenge.client_domain.as_ref().map(|c| c.domain.clone());
+
+    let horizon_account = match &state.config.horizon_url {
+        Some(url) => match HorizonClient::new(url.clone())
+            .account(&challenge.account)
+            .await
+        {
+            Ok(account) => account,
+            Err(e) => {
+                error!(error = %e, "Failed to load account signers from Horizon");
+                return error_response(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Could not load account signers",
+                );
+            }
+        },
+        None => None,
+    };
+    let (signers, threshold) = match &horizon_account {
+        Some(account) => AccountSigner::from_horizon(account),
+        None => match AccountSigner::master(&challenge.account) {
+            Ok(signers) => (signers, 1),
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+        },
+    };
+    if let Err(e) = server.verify_signers(&challenge, &signers, threshold) {
+        security_events::record_failed_login(
+            &state.db_pool,
+            &challenge.account,
+            &attempt,
+            "invalid_signature",
+        )
+        .await;
+        return error_response(StatusCode::BAD_REQUEST, e);
+    }
+
+    match user_frozen(&state.db_pool, &challenge.account).await {
+        Ok(false) => {}
+        Ok(true) => {
+            security_events::record_failed_login(
+                &state.db_pool,
+                &challenge.account,
+                &attempt,
+                "account_frozen",
+            )
+            .await;
+            return error_response(StatusCode::FORBIDDEN, "Account is frozen pending review");
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to check account freeze");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed");
+        }
+    }
+
+    // Each challenge is exchanged for a token once.
+    let consumed = sqlx::query(
+        r#"
+        WITH purged AS (DELETE FROM sep10_challenges WHERE expires_at < NOW())
+        INSERT INTO sep10_challenges (transaction_hash, account, client_domain, expires_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (transaction_hash) DO NOTHING
+        "#,
+    )
+    .bind(hex::encode(challenge.hash))
+    .bind(&challenge.account)
+    .bind(challenge.client_domain.as_ref().map(|c| c.domain.as_str()))
+    .bind(challenge.expires_at)
+    .execute(&state.db_pool)
+    .await;
+    match consumed {
+        Ok(result) if result.rows_affected() == 0 => {
+            security_events::record_failed_login(
+                &state.db_pool,
+                &challenge.account,
+                &attempt,
+                "challenge_reused",
+            )
+            .await;
+            return error_response(StatusCode::BAD_REQUEST, "challenge has already been used");
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!(error = %e, "Failed to record SEP-10 challenge");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed");
+        }
+    }
+
+    let idle_timeout = state.system_settings.get().await.session_idle_timeout();
+    let claims = server.session_claims(&challenge, now, idle_timeout);
+    if let Err(e) = open_session(&state.db_pool, &claims).await {
+        error!(error = %e, "Failed to record session");
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed");
+    }
+    match server.issue_token(&claims) {
+        Ok(token) => {
+            if let Err(e) =
+                security_events::record_login(&state.db_pool, &challenge.account, &attempt).await
+            {
+                warn!(error = %e, "Failed to record sign-in");
+            }
+            Json(TokenResponse { token }).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to sign SEP-10 token");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to issue token")
+        }
+    }
+}
+
+// Handler: Session Step-Up
+pub async fn step_up(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(confirmation): Json<WalletConfirmation>,
+) -> Response {
+    let Some(server) = Sep10Server::from_config(&state.config) else {
+        return not_configured();
+    };
+    let Some(claims) = bearer_token(&headers).and_then(|token| verify_claims(&state.config, token))
+    else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "Step-up needs a SEP-10 session token",
+        );
+    };
+
+    let result: Result<(), ReauthError> = async {
+        let mut tx = state.db_pool.begin().await?;
+        wallet_reauth::verify_confirmation(
+            &mut tx,
+            &claims.sub,
+            ReauthAction::StepUp,
+            None,
+            &confirmation,
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &claims.sub,
+            "session.step_up",
+            &claims.jti,
+            serde_json::json!({}),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        return e.into_response();
+    }
+
+    match server.issue_token(&server.step_up_claims(&claims, Utc::now())) {
+        Ok(token) => Json(TokenResponse { token }).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to sign SEP-10 token");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to issue token")
+        }
+    }
+}
+
+// Handler: stellar.toml
+pub async fn get_stellar_toml(State(state): State<Arc<AppState>>) -> Response {
+    let Some(server) = Sep10Server::from_config(&state.config) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let body = format!(
+        "NETWORK_PASSPHRASE = {:?}\nSIGNING_KEY = {:?}\nWEB_AUTH_ENDPOINT = {:?}\n",
+        state.config.network_passphrase,
+        server.account(),
+        server.issuer(),
+    );
+    (
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        let mut config = Config::for_tests();
+        config.sep10_signing_seed = Some(stellar_strkey::ed25519::PrivateKey([1; 32]).to_string());
+        config.sep10_home_domain = Some("inheritx.app".to_string());
+        config.sep10_web_auth_domain = Some("api.inheritx.app".to_string());
+        config
+    }
+
+    fn server() -> Sep10Server {
+        Sep10Server::from_config(&config()).unwrap()
+    }
+
+    fn wallet(seed: u8) -> (SigningKey, String) {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let address =
+            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
+        (key, address)
+    }
+
+    /// Adds `key`'s signature to a challenge envelope.
+    fn sign(server: &Sep10Server, envelope: &str, key: &SigningKey) -> String {
+        let TransactionEnvelope::Tx(mut envelope) =
+            TransactionEnvelope::from_xdr_base64(envelope, Limits::none()).unwrap()
+        else {
+            panic!("expected a v1 envelope");
+        };
+        let hash = server.transaction_hash(&envelope.tx);
+        let mut signatures = envelope.signatures.to_vec();
+        signatures.push(DecoratedSignature {
+            hint: signature_hint(&key.verifying_key().to_bytes()),
+            signature: Signature(key.sign(&hash).to_bytes().as_slice().try_into().unwrap()),
+        });
+        envelope.signatures = signatures.try_into().unwrap();
+        TransactionEnvelope::Tx(envelope)
+            .to_xdr_base64(Limits::none())
+            .unwrap()
+    }
+
+    #[test]
+    fn signed_challenge_is_accepted_by_the_master_key() {
+        let server = server();
+        let (key, address) = wallet(2);
+        let now = Utc::now();
+
+        let envelope = server.challenge(&address, None, now).unwrap();
+        let signed = sign(&server, &envelope, &key);
+        let challenge = server.read_challenge(&signed, now).unwrap();
+        assert_eq!(challenge.account, address);
+        server
+            .verify_signers(&challenge, &AccountSigner::master(&address).unwrap(), 1)
+            .unwrap();
+
+        let unsigned = server.read_challenge(&envelope, now).unwrap();
+        assert_eq!(
+            server.verify_signers(&unsigned, &AccountSigner::master(&address).unwrap(), 1),
+            Err(Sep10Error::InsufficientSignatures)
+        );
+
+        let claims = server.session_claims(&challenge, now, chrono::Duration::minutes(30));
+        let token = server.issue_token(&claims).unwrap();
+        assert_eq!(verify_token(&config(), &token), Some(address));
+    }
+
+    #[test]
+    fn rejects_expired_foreign_and_unknown_signatures() {
+        let server = server();
+        let (key, address) = wallet(2);
+        let (stranger, _) = wallet(3);
+        let now = Utc::now();
+        let envelope = server.challenge(&address, None, now).unwrap();
+
+        let later = now + chrono::Duration::seconds(CHALLENGE_TTL.as_secs() as i64 + 1);
+        assert_eq!(
+            server.read_challenge(&envelope, later).unwrap_err(),
+            Sep10Error::Expired
+        );
+
+        let signed = sign(&server, &sign(&server, &envelope, &key), &stranger);
+        let challenge = server.read_challenge(&signed, now).unwrap();
+        assert_eq!(
+            server.verify_signers(&challenge, &AccountSigner::master(&address).unwrap(), 1),
+            Err(Sep10Error::UnrecognizedSignature)
+        );
+
+        let mut other = config();
+        other.sep10_signing_seed = Some(stellar_strkey::ed25519::PrivateKey([9; 32]).to_string());
+        let other = Sep10Server::from_config(&other).unwrap();
+        assert!(matches!(
+            other.read_challenge(&envelope, now),
+            Err(Sep10Error::InvalidChallenge(_))
+        ));
+    }
+
+    #[test]
+    fn client_domain_key_must_sign() {
+        let server = server();
+        let (key, address) = wallet(2);
+        let (domain_key, domain_address) = wallet(4);
+        let client_domain = ClientDomain {
+            domain: "wallet.example".to_string(),
+            signing_key: domain_address,
+        };
+        let now = Utc::now();
+        let envelope = server
+            .challenge(&address, Some(&client_domain), now)
+            .unwrap();
+        let signers = AccountSigner::master(&address).unwrap();
+
+        let signed = sign(&server, &envelope, &key);
+        let challenge = server.read_challenge(&signed, now).unwrap();
+        assert_eq!(challenge.client_domain, Some(client_domain));
+        assert_eq!(
+            server.verify_signers(&challenge, &signers, 1),
+            Err(Sep10Error::MissingClientDomainSignature)
+        );
+
+        let signed = sign(&server, &signed, &domain_key);
+        let challenge = server.read_challenge(&signed, now).unwrap();
+        server.verify_signers(&challenge, &signers, 1).unwrap();
+    }
+
+    #[test]
+    fn multisig_accounts_need_the_medium_threshold() {
+        let server = server();
+        let (first, address) = wallet(2);
+        let (second, second_address) = wallet(5);
+        let now = Utc::now();
+        let signers = vec![
+            AccountSigner {
+                public_key: parse_account(&address).unwrap(),
+                weight: 1,
+            },
+            AccountSigner {
+                public_key: parse_account(&second_address).unwrap(),
+                weight: 1,
+            },
+        ];
+        let envelope = server.challenge(&address, None, now).unwrap();
+
+        let one = sign(&server, &envelope, &first);
+        let challenge = server.read_challenge(&one, now).unwrap();
+        assert_eq!(
+            server.verify_signers(&challenge, &signers, 2),
+            Err(Sep10Error::InsufficientSignatures)
+        );
+
+        let both = sign(&server, &one, &second);
+        let challenge = server.read_challenge(&both, now).unwrap();
+        server.verify_signers(&challenge, &signers, 2).unwrap();
+    }
+
+    #[test]
+    fn sessions_need_a_recent_signature_to_stay_verified() {
+        let server = server();
+        let (key, address) = wallet(2);
+        let signed_in = Utc::now() - chrono::Duration::minutes(20);
+        let envelope = server.challenge(&address, None, signed_in).unwrap();
+        let challenge = server
+            .read_challenge(&sign(&server, &envelope, &key), signed_in)
+            .unwrap();
+        let claims = server.session_claims(&challenge, signed_in, chrono::Duration::minutes(30));
+        assert_eq!(claims.idle_timeout, Some(30 * 60));
+
+        let window = chrono::Duration::minutes(10);
+        assert_eq!(
+            claims.auth_level_at(signed_in + chrono::Duration::minutes(5), window),
+            AuthLevel::RecentlyVerified
+        );
+        assert_eq!(
+            claims.auth_level_at(Utc::now(), window),
+            AuthLevel::Standard
+        );
+
+        let stepped_up = server.step_up_claims(&claims, Utc::now());
+        assert_eq!(
+            stepped_up.auth_level_at(Utc::now(), window),
+            AuthLevel::RecentlyVerified
+        );
+        assert_eq!(
+            (stepped_up.jti.as_str(), stepped_up.exp),
+            (claims.jti.as_str(), claims.exp)
+        );
+
+        // Tokens issued before sessions carried an auth level are standard.
+        let legacy: Sep10Claims = serde_json::from_value(serde_json::json!({
+            "iss": claims.iss, "sub": claims.sub, "iat": claims.iat, "exp": claims.exp,
+            "jti": claims.jti, "home_domain": claims.home_domain,
+        }))
+        .unwrap();
+        assert_eq!(legacy.auth_level_at(signed_in, window), AuthLevel::Standard);
+    }
+}
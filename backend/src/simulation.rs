@@ -0,0 +1,546 @@
+//! Preflight for contract calls through Soroban transaction simulation.
+//!
+//! The caller describes a contract invocation with typed arguments. The
+//! backend builds an unsigned transaction with the caller as source, runs
+//! it through the RPC `simulateTransaction` call and returns whether it
+//! would succeed, the fee it would cost and the ledger entries it would
+//! change.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use stellar_xdr::curr::{
+    AccountId, ContractDataDurability, Hash, HostFunction, Int128Parts, InvokeContractArgs,
+    InvokeHostFunctionOp, LedgerKey, Limits, Memo, MuxedAccount, Operation, OperationBody,
+    Preconditions, PublicKey, ReadXdr, ScAddress, ScBytes, ScString, ScSymbol, ScVal,
+    SequenceNumber, SorobanTransactionData, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionV1Envelope, Uint256, VecM, WriteXdr,
+};
+use tracing::{error, warn};
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::chain::errors::{ContractError, ContractErrorInfo, ContractInterface};
+use crate::chain::rpc::{RpcError, SimulateTransactionResponse};
+
+/// Inclusion fee per operation, in stroops, added to the resource fee.
+pub const BASE_FEE_STROOPS: i64 = 100;
+const MAX_ARGS: usize = 16;
+
+/// A contract argument with an explicit Soroban type. 64- and 128-bit
+/// integers are passed as decimal strings so JSON clients keep precision.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ContractArg {
+    Address(String),
+    Bool(bool),
+    U32(u32),
+    I32(i32),
+    U64(String),
+    I64(String),
+    I128(String),
+    Symbol(String),
+    String(String),
+    /// Hex-encoded bytes.
+    Bytes(String),
+}
+
+impl ContractArg {
+    fn to_sc_val(&self) -> Result<ScVal, String> {
+        let invalid = |kind: &str, value: &str| format!("Invalid {kind} argument: {value}");
+        Ok(match self {
+            Self::Address(value) => ScVal::Address(parse_address(value)?),
+            Self::Bool(value) => ScVal::Bool(*value),
+            Self::U32(value) => ScVal::U32(*value),
+            Self::I32(value) => ScVal::I32(*value),
+            Self::U64(value) => ScVal::U64(value.parse().map_err(|_| invalid("u64", value))?),
+            Self::I64(value) => ScVal::I64(value.parse().map_err(|_| invalid("i64", value))?),
+            Self::I128(value) => {
+                let n: i128 = value.parse().map_err(|_| invalid("i128", value))?;
+                ScVal::I128(Int128Parts {
+                    hi: (n >> 64) as i64,
+                    lo: n as u64,
+                })
+            }
+            Self::Symbol(value) => ScVal::Symbol(ScSymbol(
+                value
+                    .as_str()
+                    .try_into()
+                    .map_err(|_| invalid("symbol", value))?,
+            )),
+            Self::String(value) => ScVal::String(ScString(
+                value
+                    .as_str()
+                    .try_into()
+                    .map_err(|_| invalid("string", value))?,
+            )),
+            Self::Bytes(value) => {
+                let bytes = hex::decode(value.trim_start_matches("0x"))
+                    .map_err(|_| invalid("bytes", value))?;
+                ScVal::Bytes(ScBytes(
+                    bytes.try_into().map_err(|_| invalid("bytes", value))?,
+                ))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimulateRequest {
+    /// Defaults to the configured inheritance contract.
+    pub contract_id: Option<String>,
+    pub function: String,
+    #[serde(default)]
+    pub args: Vec<ContractArg>,
+    /// Interface used to name contract error codes. Defaults to
+    /// `inheritance` when targeting the configured inheritance contract.
+    pub interface: Option<ContractInterface>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationError {
+    /// Host error category, e.g. `contract`, `auth` or `budget`.
+    pub kind: String,
+    /// Contract error code when `kind` is `contract`.
+    pub code: Option<u32>,
+    /// Named error when the code is known for the target interface.
+    pub contract_error: Option<ContractErrorInfo>,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeeEstimate {
+    pub resource_fee_stroops: i64,
+    pub inclusion_fee_stroops: i64,
+    pub total_stroops: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResourceUsage {
+    pub cpu_instructions: u64,
+    pub memory_bytes: u64,
+    pub read_bytes: u32,
+    pub write_bytes: u32,
+    pub ledger_entries_read: usize,
+    pub ledger_entries_written: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateChangeSummary {
+    /// `created`, `updated` or `deleted`.
+    pub change: String,
+    pub entry_type: String,
+    pub owner: Option<String>,
+    pub durability: Option<String>,
+    pub key: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulationResult {
+    pub success: bool,
+    pub error: Option<SimulationError>,
+    pub return_value: Option<serde_json::Value>,
+    pub fee: Option<FeeEstimate>,
+    pub resources: Option<ResourceUsage>,
+    pub state_changes: Vec<StateChangeSummary>,
+    /// Archived entries must be restored before the call can succeed.
+    pub restore_required: bool,
+    pub latest_ledger: u32,
+}
+
+fn parse_address(value: &str) -> Result<ScAddress, String> {
+    let value = value.trim();
+    if let Ok(key) = stellar_strkey::ed25519::PublicKey::from_string(value) {
+        return Ok(ScAddress::Account(AccountId(
+            PublicKey::PublicKeyTypeEd25519(Uint256(key.0)),
+        )));
+    }
+    if let Ok(contract) = stellar_strkey::Contract::from_string(value) {
+        return Ok(ScAddress::Contract(Hash(contract.0)));
+    }
+    Err(format!("Invalid address argument: {value}"))
+}
+
+fn address_to_string(address: &ScAddress) -> String {
+    match address {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(bytes)))) => {
+            stellar_strkey::ed25519::PublicKey(*bytes).to_string()
+        }
+        ScAddress::Contract(Hash(bytes)) => stellar_strkey::Contract(*bytes).to_string(),
+    }
+}
+
+/// Builds the unsigned base64 envelope submitted for simulation.
+pub fn build_envelope(
+    source_account: &str,
+    contract_id: &str,
+    function: &str,
+    args: &[ContractArg],
+) -> Result<String, String> {
+    let source = stellar_strkey::ed25519::PublicKey::from_string(source_account)
+        .map_err(|_| "Caller is not a Stellar account".to_string())?;
+    let ScAddress::Contract(contract) = parse_address(contract_id)? else {
+        return Err("contract_id must be a contract address".to_string());
+    };
+    if args.len() > MAX_ARGS {
+        return Err(format!("At most {MAX_ARGS} arguments are supported"));
+    }
+    let args = args
+        .iter()
+        .map(ContractArg::to_sc_val)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let invoke = InvokeHostFunctionOp {
+        host_function: HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address: ScAddress::Contract(contract),
+            function_name: ScSymbol(
+                function
+                    .try_into()
+                    .map_err(|_| format!("Invalid function name: {function}"))?,
+            ),
+            args: args
+                .try_into()
+                .map_err(|_| "Too many arguments".to_string())?,
+        }),
+        auth: VecM::default(),
+    };
+    let tx = Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(source.0)),
+        fee: BASE_FEE_STROOPS as u32,
+        // Simulation does not check the sequence number.
+        seq_num: SequenceNumber(0),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![Operation {
+            source_account: None,
+            body: OperationBody::InvokeHostFunction(invoke),
+        }]
+        .try_into()
+        .map_err(|_| "Failed to build operation".to_string())?,
+        ext: TransactionExt::V0,
+    };
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: VecM::default(),
+    })
+    .to_xdr_base64(Limits::none())
+    .map_err(|e| format!("Failed to encode transaction: {e}"))
+}
+
+/// Extracts the first `Error(Kind, Detail)` from a host error message.
+pub fn parse_host_error(message: &str, interface: Option<ContractInterface>) -> SimulationError {
+    let parsed = message.find("Error(").and_then(|start| {
+        let rest = &message[start + "Error(".len()..];
+        let (inner, _) = rest.split_once(')')?;
+        let (kind, detail) = inner.split_once(',')?;
+        Some((kind.trim().to_lowercase(), detail.trim().to_string()))
+    });
+
+    match parsed {
+        Some((kind, detail)) => {
+            let code = (kind == "contract")
+                .then(|| detail.trim_start_matches('#').parse().ok())
+                .flatten();
+            SimulationError {
+                contract_error: interface
+                    .zip(code)
+                    .and_then(|(interface, code)| ContractError::decode(interface, code))
+                    .map(ContractErrorInfo::from),
+                code,
+                kind,
+                detail,
+            }
+        }
+        None => SimulationError {
+            kind: "unknown".to_string(),
+            code: None,
+            contract_error: None,
+            detail: message.lines().next().unwrap_or_default().to_string(),
+        },
+    }
+}
+
+/// Renders a contract value as JSON for display.
+pub fn sc_val_to_json(value: &ScVal) -> serde_json::Value {
+    use serde_json::json;
+    match value {
+        ScVal::Bool(b) => json!(b),
+        ScVal::Void => serde_json::Value::Null,
+        ScVal::U32(n) => json!(n),
+        ScVal::I32(n) => json!(n),
+        ScVal::U64(n) => json!(n.to_string()),
+        ScVal::I64(n) => json!(n.to_string()),
+        ScVal::U128(parts) => json!((((parts.hi as u128) << 64) | parts.lo as u128).to_string()),
+        ScVal::I128(parts) => json!((((parts.hi as i128) << 64) | parts.lo as i128).to_string()),
+        ScVal::Symbol(symbol) => json!(symbol.0.to_utf8_string_lossy()),
+        ScVal::String(string) => json!(string.0.to_utf8_string_lossy()),
+        ScVal::Bytes(bytes) => json!(hex::encode(bytes.0.as_slice())),
+        ScVal::Address(address) => json!(address_to_string(address)),
+        ScVal::Vec(Some(items)) => {
+            serde_json::Value::Array(items.0.iter().map(sc_val_to_json).collect())
+        }
+        ScVal::Map(Some(entries)) => serde_json::Value::Array(
+            entries
+                .0
+                .iter()
+                .map(|entry| json!({ "key": sc_val_to_json(&entry.key), "value": sc_val_to_json(&entry.val) }))
+                .collect(),
+        ),
+        ScVal::LedgerKeyContractInstance => json!("instance"),
+        other => json!(other.name()),
+    }
+}
+
+/// Describes the ledger entry behind a base64 `LedgerKey`.
+pub fn summarize_state_change(change: &str, key_xdr: &str) -> StateChangeSummary {
+    let mut summary = StateChangeSummary {
+        change: change.to_string(),
+        entry_type: "unknown".to_string(),
+        owner: None,
+        durability: None,
+        key: None,
+    };
+    let Ok(key) = LedgerKey::from_xdr_base64(key_xdr, Limits::none()) else {
+        return summary;
+    };
+    summary.entry_type = key.name().to_lowercase();
+    match key {
+        LedgerKey::ContractData(data) => {
+            summary.entry_type = "contract_data".to_string();
+            summary.owner = Some(address_to_string(&data.contract));
+            summary.durability = Some(
+                match data.durability {
+                    ContractDataDurability::Persistent => "persistent",
+                    ContractDataDurability::Temporary => "temporary",
+                }
+                .to_string(),
+            );
+            summary.key = Some(sc_val_to_json(&data.key));
+        }
+        LedgerKey::ContractCode(code) => {
+            summary.entry_type = "contract_code".to_string();
+            summary.key = Some(serde_json::json!(hex::encode(code.hash.0)));
+        }
+        LedgerKey::Account(account) => {
+            summary.owner = Some(address_to_string(&ScAddress::Account(account.account_id)));
+        }
+        LedgerKey::Trustline(trustline) => {
+            summary.owner = Some(address_to_string(&ScAddress::Account(trustline.account_id)));
+        }
+        _ => {}
+    }
+    summary
+}
+
+/// Turns an RPC simulation response into the client-facing result.
+pub fn summarize(
+    response: &SimulateTransactionResponse,
+    interface: Option<ContractInterface>,
+) -> SimulationResult {
+    let state_changes = response
+        .state_changes
+        .iter()
+        .map(|c| summarize_state_change(&c.change_type, &c.key))
+        .collect();
+
+    if let Some(message) = &response.error {
+        return SimulationResult {
+            success: false,
+            error: Some(parse_host_error(message, interface)),
+            return_value: None,
+            fee: None,
+            resources: None,
+            state_changes,
+            restore_required: false,
+            latest_ledger: response.latest_ledger,
+        };
+    }
+
+    let resource_fee = response
+        .min_resource_fee
+        .as_deref()
+        .and_then(|fee| fee.parse::<i64>().ok())
+        .unwrap_or(0);
+    let transaction_data = response
+        .transaction_data
+        .as_deref()
+        .and_then(|data| SorobanTransactionData::from_xdr_base64(data, Limits::none()).ok());
+    let cost = response.cost.as_ref();
+    let resources = transaction_data.map(|data| ResourceUsage {
+        cpu_instructions: cost.and_then(|c| c.cpu_insns.parse().ok()).unwrap_or(0),
+        memory_bytes: cost.and_then(|c| c.mem_bytes.parse().ok()).unwrap_or(0),
+        read_bytes: data.resources.read_bytes,
+        write_bytes: data.resources.write_bytes,
+        ledger_entries_read: data.resources.footprint.read_only.len()
+            + data.resources.footprint.read_write.len(),
+        ledger_entries_written: data.resources.footprint.read_write.len(),
+    });
+
+    SimulationResult {
+        success: true,
+        error: None,
+        return_value: response
+            .results
+            .first()
+            .and_then(|r| ScVal::from_xdr_base64(&r.xdr, Limits::none()).ok())
+            .map(|value| sc_val_to_json(&value)),
+        fee: Some(FeeEstimate {
+            resource_fee_stroops: resource_fee,
+            inclusion_fee_stroops: BASE_FEE_STROOPS,
+            total_stroops: resource_fee + BASE_FEE_STROOPS,
+        }),
+        resources,
+        state_changes,
+        restore_required: response.restore_preamble.is_some(),
+        latest_ledger: response.latest_ledger,
+    }
+}
+
+// Handler: Simulate Contract Call
+pub async fn simulate_contract_call(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<SimulateRequest>,
+) -> impl IntoResponse {
+    let source = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if !state.soroban_rpc.is_configured() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Transaction simulation is not configured" })),
+        )
+            .into_response();
+    }
+    let Some(contract_id) = payload
+        .contract_id
+        .clone()
+        .or_else(|| state.config.inheritance_contract_id.clone())
+    else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "contract_id is required" })),
+        )
+            .into_response();
+    };
+
+    let interface = payload.interface.or_else(|| {
+        (state.config.inheritance_contract_id.as_deref() == Some(contract_id.as_str()))
+            .then_some(ContractInterface::Inheritance)
+    });
+
+    let envelope = match build_envelope(&source, &contract_id, &payload.function, &payload.args) {
+        Ok(envelope) => envelope,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": message })),
+            )
+                .into_response();
+        }
+    };
+
+    match state.soroban_rpc.simulate_transaction(&envelope).await {
+        Ok(response) => (StatusCode::OK, Json(summarize(&response, interface))).into_response(),
+        Err(RpcError::Rpc { code, message }) => {
+            warn!(code, message = %message, "Simulation rejected by RPC");
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(serde_json::json!({ "error": message })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Transaction simulation failed");
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({ "error": "Simulation service unavailable" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stellar_xdr::curr::{LedgerKeyContractData, TransactionEnvelope};
+
+    const ACCOUNT: &str = "GAAZI4TCR3TY5OJHCTJC2A4QSY6CJWJH5IAJTGKIN2ER7LBNVKOCCWN7";
+    const CONTRACT: &str = "CAAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQC526";
+
+    #[test]
+    fn builds_invocation_envelope() {
+        let envelope = build_envelope(
+            ACCOUNT,
+            CONTRACT,
+            "claim",
+            &[
+                ContractArg::Address(ACCOUNT.to_string()),
+                ContractArg::I128("-5".to_string()),
+            ],
+        )
+        .unwrap();
+
+        let TransactionEnvelope::Tx(decoded) =
+            TransactionEnvelope::from_xdr_base64(envelope, Limits::none()).unwrap()
+        else {
+            panic!("expected a v1 envelope");
+        };
+        let OperationBody::InvokeHostFunction(op) = &decoded.tx.operations[0].body else {
+            panic!("expected an invoke operation");
+        };
+        let HostFunction::InvokeContract(call) = &op.host_function else {
+            panic!("expected a contract call");
+        };
+        assert_eq!(call.function_name.0.to_utf8_string_lossy(), "claim");
+        assert_eq!(sc_val_to_json(&call.args[1]), serde_json::json!("-5"));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(build_envelope(ACCOUNT, ACCOUNT, "claim", &[]).is_err());
+        assert!(build_envelope(
+            ACCOUNT,
+            CONTRACT,
+            "claim",
+            &[ContractArg::U64("-1".to_string())]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parses_contract_error_codes() {
+        let error = parse_host_error(
+            "HostError: Error(Contract, #3)\n\nEvent log (newest first):\n   0: ...",
+            Some(ContractInterface::Inheritance),
+        );
+        assert_eq!(error.kind, "contract");
+        assert_eq!(error.code, Some(3));
+        assert_eq!(error.contract_error.unwrap().name, "unauthorized");
+
+        let error = parse_host_error("HostError: Error(Contract, #3)", None);
+        assert!(error.contract_error.is_none());
+
+        let error = parse_host_error("HostError: Error(Auth, InvalidAction)", None);
+        assert_eq!((error.kind.as_str(), error.code), ("auth", None));
+    }
+
+    #[test]
+    fn summarizes_contract_data_changes() {
+        let key = LedgerKey::ContractData(LedgerKeyContractData {
+            contract: parse_address(CONTRACT).unwrap(),
+            key: ScVal::Symbol(ScSymbol("Plan".try_into().unwrap())),
+            durability: ContractDataDurability::Persistent,
+        })
+        .to_xdr_base64(Limits::none())
+        .unwrap();
+
+        let summary = summarize_state_change("updated", &key);
+        assert_eq!(summary.entry_type, "contract_data");
+        assert_eq!(summary.owner.as_deref(), Some(CONTRACT));
+        assert_eq!(summary.durability.as_deref(), Some("persistent"));
+        assert_eq!(summary.key, Some(serde_json::json!("Plan")));
+    }
+}
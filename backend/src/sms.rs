@@ -0,0 +1,92 @@
+//! Outbound SMS through an HTTP messaging API.
+//!
+//! Mirrors [`crate::mailer`]: the provider receives `{ from, to, text }` as
+//! JSON with a bearer key, and without `SMS_API_URL` messages are only logged.
+
+use thiserror::Error;
+use tracing::info;
+
+use crate::http_client::{HttpClient, HttpError, HttpPolicy};
+
+#[derive(Debug, Clone, Default)]
+pub struct SmsConfig {
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    pub from: Option<String>,
+}
+
+impl SmsConfig {
+    pub fn from_env() -> Self {
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+
+        Self {
+            api_url: var("SMS_API_URL"),
+            api_key: var("SMS_API_KEY"),
+            from: var("SMS_FROM"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SmsError {
+    #[error("sms request failed: {0}")]
+    Http(#[from] HttpError),
+    #[error("sms provider returned {status}: {body}")]
+    Provider { status: u16, body: String },
+}
+
+pub struct SmsClient {
+    http: HttpClient,
+    config: SmsConfig,
+}
+
+impl SmsClient {
+    pub fn new(config: SmsConfig) -> Self {
+        let http = HttpClient::new("sms", HttpPolicy::default());
+        Self { http, config }
+    }
+
+    pub async fn send(&self, to: &str, text: &str) -> Result<(), SmsError> {
+        let Some(url) = self.config.api_url.as_deref() else {
+            info!(to = %to, "SMS delivery not configured; skipping send");
+            return Ok(());
+        };
+
+        let mut request = self.http.post(url).json(&serde_json::json!({
+            "from": self.config.from,
+            "to": to,
+            "text": text,
+        }));
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = self.http.send(request).await?;
+        if !response.status().is_success() {
+            return Err(SmsError::Provider {
+                status: response.status().as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// E.164 format: `+` followed by 8 to 15 digits.
+pub fn is_e164(phone: &str) -> bool {
+    phone.strip_prefix('+').is_some_and(|digits| {
+        (8..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phone_numbers_must_be_e164() {
+        assert!(is_e164("+2348012345678"));
+        assert!(!is_e164("08012345678"));
+        assert!(!is_e164("+1-555-0100"));
+    }
+}
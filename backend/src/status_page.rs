@@ -0,0 +1,801 @@
+//! Public service status for status pages and uptime monitors.
+//!
+//! `GET /api/status` needs no authentication. It reports each component —
+//! the database, Horizon, Soroban RPC, the background job queue and the
+//! email provider — as `operational`, `maintenance`, `degraded` or
+//! `major_outage`, with the worst of them as the overall status, and
+//! answers `503` during a major outage so plain HTTP monitors notice.
+//! Components that are not configured are left out.
+//!
+//! Admins annotate the page with incidents. An unresolved incident raises
+//! the components it names to at least its impact, and resolved incidents
+//! stay listed for a week. The report is cached for `STATUS_CACHE_SECS` and
+//! built by one request at a time, so however often the page is polled the
+//! probes run at most once per window; the route also has its own per-IP
+//! limit. Incident changes are written to `audit_logs`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::http_client::{circuit_open, HttpClient, HttpPolicy};
+use crate::middleware::{RateLimitConfig, RateLimitStore};
+
+const DEFAULT_CACHE_SECS: u64 = 15;
+const DEFAULT_PROBE_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_BACKLOG_MAX_AGE_SECS: u64 = 900;
+const DEFAULT_RATE_LIMIT_MAX_REQUESTS: u64 = 30;
+const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 60;
+/// A probe slower than this reports its component as degraded.
+const SLOW_PROBE: Duration = Duration::from_secs(2);
+/// How far back a failed job run makes the job queue degraded.
+const FAILED_RUN_WINDOW_SECS: f64 = 3600.0;
+const RESOLVED_INCIDENT_DAYS: i32 = 7;
+const MAX_TITLE_LEN: usize = 200;
+const MAX_MESSAGE_LEN: usize = 2_000;
+
+const INCIDENT_COLUMNS: &str = "id, title, message, impact, components, status, created_by, \
+     updated_by, started_at, resolved_at, updated_at";
+
+pub const COMPONENTS: [&str; 5] = ["database", "horizon", "soroban_rpc", "job_queue", "email"];
+pub const INCIDENT_STATUSES: [&str; 4] = ["investigating", "identified", "monitoring", "resolved"];
+
+/// Component health, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Health {
+    Operational,
+    Maintenance,
+    Degraded,
+    MajorOutage,
+}
+
+impl Health {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "maintenance" => Some(Self::Maintenance),
+            "degraded" => Some(Self::Degraded),
+            "major_outage" => Some(Self::MajorOutage),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusPageConfig {
+    pub cache_ttl: Duration,
+    pub probe_timeout: Duration,
+    /// Age at which a queued payout counts as a backlog.
+    pub backlog_max_age: Duration,
+    pub rate_limit: RateLimitConfig,
+}
+
+impl Default for StatusPageConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl: Duration::from_secs(DEFAULT_CACHE_SECS),
+            probe_timeout: Duration::from_secs(DEFAULT_PROBE_TIMEOUT_SECS),
+            backlog_max_age: Duration::from_secs(DEFAULT_BACKLOG_MAX_AGE_SECS),
+            rate_limit: RateLimitConfig {
+                max_requests: DEFAULT_RATE_LIMIT_MAX_REQUESTS,
+                window: Duration::from_secs(DEFAULT_RATE_LIMIT_WINDOW_SECS),
+            },
+        }
+    }
+}
+
+impl StatusPageConfig {
+    pub fn from_env() -> Self {
+        let cache_secs = parse_env("STATUS_CACHE_SECS", DEFAULT_CACHE_SECS);
+        let probe_timeout_secs = parse_env("STATUS_PROBE_TIMEOUT_SECS", DEFAULT_PROBE_TIMEOUT_SECS);
+        let backlog_secs = parse_env("STATUS_BACKLOG_MAX_AGE_SECS", DEFAULT_BACKLOG_MAX_AGE_SECS);
+        let max_requests = parse_env(
+            "STATUS_RATE_LIMIT_MAX_REQUESTS",
+            DEFAULT_RATE_LIMIT_MAX_REQUESTS,
+        );
+        let window_secs = parse_env(
+            "STATUS_RATE_LIMIT_WINDOW_SECS",
+            DEFAULT_RATE_LIMIT_WINDOW_SECS,
+        );
+
+        Self {
+            cache_ttl: Duration::from_secs(cache_secs),
+            probe_timeout: Duration::from_secs(probe_timeout_secs.max(1)),
+            backlog_max_age: Duration::from_secs(backlog_secs.max(1)),
+            rate_limit: RateLimitConfig {
+                max_requests,
+                window: Duration::from_secs(window_secs.max(1)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentStatus {
+    pub name: &'static str,
+    pub status: Health,
+    /// How long the probe took, for components that are probed.
+    pub latency_ms: Option<u64>,
+    pub message: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct StatusIncident {
+    pub id: Uuid,
+    pub title: String,
+    pub message: String,
+    /// `maintenance`, `degraded` or `major_outage`.
+    pub impact: String,
+    pub components: Vec<String>,
+    /// `investigating`, `identified`, `monitoring` or `resolved`.
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub created_by: String,
+    #[serde(skip_serializing)]
+    pub updated_by: String,
+    pub started_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Incident as admins see it, with who opened and last changed it.
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminStatusIncident {
+    #[serde(flatten)]
+    pub incident: StatusIncident,
+    pub created_by: String,
+    pub updated_by: String,
+}
+
+impl From<StatusIncident> for AdminStatusIncident {
+    fn from(incident: StatusIncident) -> Self {
+        Self {
+            created_by: incident.created_by.clone(),
+            updated_by: incident.updated_by.clone(),
+            incident,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    pub status: Health,
+    pub components: Vec<ComponentStatus>,
+    /// Unresolved incidents, then those resolved in the last week.
+    pub incidents: Vec<StatusIncident>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Builds and caches the status report.
+pub struct StatusPage {
+    config: StatusPageConfig,
+    horizon: HttpClient,
+    limiter: RateLimitStore,
+    cached: Mutex<Option<(Instant, StatusReport)>>,
+}
+
+impl StatusPage {
+    pub fn new(config: StatusPageConfig) -> Self {
+        let horizon = HttpClient::new(
+            "status_horizon",
+            HttpPolicy {
+                timeout: config.probe_timeout,
+                retries: 0,
+                ..HttpPolicy::default()
+            },
+        );
+        Self {
+            config,
+            horizon,
+            limiter: RateLimitStore::new(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn limiter(&self) -> (RateLimitStore, Arc<RateLimitConfig>) {
+        (
+            self.limiter.clone(),
+            Arc::new(self.config.rate_limit.clone()),
+        )
+    }
+
+    /// Drops the cached report, so an incident change shows straight away.
+    pub async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+
+    async fn report(&self, state: &AppState) -> StatusReport {
+        let mut cached = self.cached.lock().await;
+        if let Some((built, report)) = cached.as_ref() {
+            if built.elapsed() < self.config.cache_ttl {
+                return report.clone();
+            }
+        }
+        let report = self.build(state).await;
+        *cached = Some((Instant::now(), report.clone()));
+        report
+    }
+
+    async fn build(&self, state: &AppState) -> StatusReport {
+        let (database, horizon, soroban_rpc, job_queue, incidents) = tokio::join!(
+            self.probe_database(state),
+            self.probe_horizon(state),
+            self.probe_soroban_rpc(state),
+            self.job_queue(state),
+            load_public_incidents(state),
+        );
+        let email = email_status(state);
+
+        let incidents = incidents.unwrap_or_else(|e| {
+            error!(error = %e, "Failed to load status incidents");
+            Vec::new()
+        });
+        let mut components: Vec<ComponentStatus> =
+            [Some(database), horizon, soroban_rpc, Some(job_queue), email]
+                .into_iter()
+                .flatten()
+                .collect();
+        apply_incidents(&mut components, &incidents);
+
+        StatusReport {
+            status: overall(&components),
+            components,
+            incidents,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Runs `probe` with the probe timeout and grades it by outcome and
+    /// latency.
+    async fn timed<F, E>(&self, name: &'static str, probe: F) -> ComponentStatus
+    where
+        F: Future<Output = Result<bool, E>>,
+        E: std::fmt::Display,
+    {
+        let started = Instant::now();
+        let result = tokio::time::timeout(self.config.probe_timeout, probe).await;
+        let latency = started.elapsed();
+        let (status, message) = match result {
+            Ok(Ok(true)) if latency >= SLOW_PROBE => (Health::Degraded, Some("Responding slowly")),
+            Ok(Ok(true)) => (Health::Operational, None),
+            Ok(Ok(false)) => (Health::Degraded, Some("Reporting itself unhealthy")),
+            Ok(Err(e)) => {
+                warn!(component = name, error = %e, "Status probe failed");
+                (Health::MajorOutage, Some("Unreachable"))
+            }
+            Err(_) => {
+                warn!(component = name, "Status probe timed out");
+                (Health::MajorOutage, Some("Not responding"))
+            }
+        };
+        ComponentStatus {
+            name,
+            status,
+            latency_ms: Some(latency.as_millis() as u64),
+            message,
+        }
+    }
+
+    async fn probe_database(&self, state: &AppState) -> ComponentStatus {
+        self.timed("database", async {
+            sqlx::query_scalar::<_, i32>("SELECT 1")
+                .fetch_one(&state.db_pool)
+                .await
+                .map(|_| true)
+        })
+        .await
+    }
+
+    async fn probe_horizon(&self, state: &AppState) -> Option<ComponentStatus> {
+        let url = state.config.horizon_url.as_deref()?;
+        Some(
+            self.timed("horizon", async {
+                self.horizon
+                    .send(self.horizon.get(url))
+                    .await
+                    .map(|response| response.status().is_success())
+            })
+            .await,
+        )
+    }
+
+    async fn probe_soroban_rpc(&self, state: &AppState) -> Option<ComponentStatus> {
+        if !state.soroban_rpc.is_configured() {
+            return None;
+        }
+        Some(
+            self.timed("soroban_rpc", async {
+                state
+                    .soroban_rpc
+                    .get_health()
+                    .await
+                    .map(|status| status == "healthy")
+            })
+            .await,
+        )
+    }
+
+    /// Degraded while queued payouts wait longer than the backlog age or a
+    /// job's latest run in the last hour failed.
+    async fn job_queue(&self, state: &AppState) -> ComponentStatus {
+        let backlog = sqlx::query_as::<_, (bool, bool)>(
+            r#"
+            SELECT
+                EXISTS (
+                    SELECT 1 FROM payouts
+                    WHERE status IN ('pending', 'processing') AND failure_reason IS NULL
+                      AND created_at <= NOW() - ($1 * INTERVAL '1 second')
+                ),
+                EXISTS (
+                    SELECT 1 FROM (
+                        SELECT DISTINCT ON (job_name) outcome
+                        FROM job_runs
+                        WHERE started_at > NOW() - ($2 * INTERVAL '1 second')
+                        ORDER BY job_name, started_at DESC
+                    ) latest
+                    WHERE outcome = 'failed'
+                )
+            "#,
+        )
+        .bind(self.config.backlog_max_age.as_secs() as f64)
+        .bind(FAILED_RUN_WINDOW_SECS)
+        .fetch_one(&state.db_pool)
+        .await;
+
+        let (status, message) = match backlog {
+            Ok((true, _)) => (Health::Degraded, Some("Queued payouts are delayed")),
+            Ok((false, true)) => (Health::Degraded, Some("Some background jobs are failing")),
+            Ok((false, false)) => (Health::Operational, None),
+            Err(e) => {
+                warn!(error = %e, "Failed to read job queue status");
+                (Health::Degraded, Some("Queue state could not be read"))
+            }
+        };
+        ComponentStatus {
+            name: "job_queue",
+            status,
+            latency_ms: None,
+            message,
+        }
+    }
+}
+
+/// The email provider is not probed, since that would send mail; it is
+/// down while calls to it keep failing.
+fn email_status(state: &AppState) -> Option<ComponentStatus> {
+    let url = state.mailer.api_url()?;
+    let (status, message) = if circuit_open(url) {
+        (Health::MajorOutage, Some("Email delivery is failing"))
+    } else {
+        (Health::Operational, None)
+    };
+    Some(ComponentStatus {
+        name: "email",
+        status,
+        latency_ms: None,
+        message,
+    })
+}
+
+/// Raises each component to the impact of the unresolved incidents that
+/// name it.
+fn apply_incidents(components: &mut [ComponentStatus], incidents: &[StatusIncident]) {
+    for incident in incidents.iter().filter(|i| i.resolved_at.is_none()) {
+        let Some(impact) = Health::parse(&incident.impact) else {
+            continue;
+        };
+        for component in components
+            .iter_mut()
+            .filter(|c| incident.components.iter().any(|name| name == c.name))
+        {
+            component.status = component.status.max(impact);
+        }
+    }
+}
+
+fn overall(components: &[ComponentStatus]) -> Health {
+    components
+        .iter()
+        .map(|c| c.status)
+        .max()
+        .unwrap_or(Health::Operational)
+}
+
+async fn load_public_incidents(state: &AppState) -> Result<Vec<StatusIncident>, sqlx::Error> {
+    sqlx::query_as::<_, StatusIncident>(&format!(
+        r#"
+        SELECT {INCIDENT_COLUMNS}
+        FROM status_incidents
+        WHERE resolved_at IS NULL OR resolved_at > NOW() - ($1 * INTERVAL '1 day')
+        ORDER BY resolved_at IS NULL DESC, started_at DESC
+        "#
+    ))
+    .bind(RESOLVED_INCIDENT_DAYS)
+    .fetch_all(&state.db_pool)
+    .await
+}
+
+// Handler: Public Status
+pub async fn get_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let report = state.status_page.report(&state).await;
+    let code = if report.status == Health::MajorOutage {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    let cache_control = format!(
+        "public, max-age={}",
+        state.status_page.config.cache_ttl.as_secs()
+    );
+    (code, [(header::CACHE_CONTROL, cache_control)], Json(report))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IncidentQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateIncidentRequest {
+    pub title: String,
+    pub message: String,
+    pub impact: String,
+    #[serde(default)]
+    pub components: Vec<String>,
+    /// Defaults to `investigating`.
+    pub status: Option<String>,
+}
+
+/// Fields left out are unchanged.
+#[derive(Debug, Deserialize)]
+pub struct UpdateIncidentRequest {
+    pub title: Option<String>,
+    pub message: Option<String>,
+    pub impact: Option<String>,
+    pub components: Option<Vec<String>>,
+    pub status: Option<String>,
+}
+
+fn refused(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+fn clean_text(value: &str, max_len: usize, error: &'static str) -> Result<String, &'static str> {
+    let value = value.trim();
+    if value.is_empty() || value.chars().count() > max_len {
+        return Err(error);
+    }
+    Ok(value.to_string())
+}
+
+fn check_impact(impact: &str) -> Result<(), &'static str> {
+    match Health::parse(impact) {
+        Some(_) => Ok(()),
+        None => Err("impact must be one of maintenance, degraded, major_outage"),
+    }
+}
+
+fn check_status(status: &str) -> Result<(), &'static str> {
+    if INCIDENT_STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err("status must be one of investigating, identified, monitoring, resolved")
+    }
+}
+
+fn clean_components(components: Vec<String>) -> Result<Vec<String>, &'static str> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for component in components {
+        if !COMPONENTS.contains(&component.as_str()) {
+            return Err(
+                "components must be among database, horizon, soroban_rpc, job_queue, email",
+            );
+        }
+        if !cleaned.contains(&component) {
+            cleaned.push(component);
+        }
+    }
+    Ok(cleaned)
+}
+
+// Handler: Admin List Status Incidents
+pub async fn list_status_incidents(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<IncidentQuery>,
+) -> impl IntoResponse {
+    if let Some(Err(message)) = query.status.as_deref().map(check_status) {
+        return refused(StatusCode::BAD_REQUEST, message);
+    }
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    match sqlx::query_as::<_, StatusIncident>(&format!(
+        r#"
+        SELECT {INCIDENT_COLUMNS}
+        FROM status_incidents
+        WHERE $1::text IS NULL OR status = $1
+        ORDER BY started_at DESC
+        LIMIT $2
+        "#
+    ))
+    .bind(&query.status)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => {
+            let rows: Vec<AdminStatusIncident> = rows.into_iter().map(Into::into).collect();
+            (StatusCode::OK, Json(rows)).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to list status incidents");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Create Status Incident
+pub async fn create_status_incident(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(payload): Json<CreateIncidentRequest>,
+) -> impl IntoResponse {
+    let status = payload
+        .status
+        .unwrap_or_else(|| "investigating".to_string());
+    let checked = (|| {
+        let title = clean_text(
+            &payload.title,
+            MAX_TITLE_LEN,
+            "title must be 1 to 200 characters",
+        )?;
+        let message = clean_text(
+            &payload.message,
+            MAX_MESSAGE_LEN,
+            "message must be 1 to 2000 characters",
+        )?;
+        check_impact(&payload.impact)?;
+        check_status(&status)?;
+        Ok::<_, &'static str>((title, message, clean_components(payload.components)?))
+    })();
+    let (title, message, components) = match checked {
+        Ok(checked) => checked,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+
+    let result: Result<StatusIncident, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let incident = sqlx::query_as::<_, StatusIncident>(&format!(
+            r#"
+            INSERT INTO status_incidents
+                (title, message, impact, components, status, created_by, updated_by,
+                 resolved_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $6,
+                    CASE WHEN $5 = 'resolved' THEN NOW() END)
+            RETURNING {INCIDENT_COLUMNS}
+            "#
+        ))
+        .bind(&title)
+        .bind(&message)
+        .bind(&payload.impact)
+        .bind(&components)
+        .bind(&status)
+        .bind(&admin.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "status.incident.create",
+            &incident.id.to_string(),
+            serde_json::json!({
+                "title": incident.title,
+                "impact": incident.impact,
+                "components": incident.components,
+                "status": incident.status,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(incident)
+    }
+    .await;
+
+    match result {
+        Ok(incident) => {
+            state.status_page.invalidate().await;
+            (
+                StatusCode::CREATED,
+                Json(AdminStatusIncident::from(incident)),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create status incident");
+            database_error()
+        }
+    }
+}
+
+// Handler: Admin Update Status Incident
+pub async fn update_status_incident(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(incident_id): Path<Uuid>,
+    Json(payload): Json<UpdateIncidentRequest>,
+) -> impl IntoResponse {
+    let checked = (|| {
+        let title = payload
+            .title
+            .as_deref()
+            .map(|t| clean_text(t, MAX_TITLE_LEN, "title must be 1 to 200 characters"))
+            .transpose()?;
+        let message = payload
+            .message
+            .as_deref()
+            .map(|m| clean_text(m, MAX_MESSAGE_LEN, "message must be 1 to 2000 characters"))
+            .transpose()?;
+        payload.impact.as_deref().map(check_impact).transpose()?;
+        payload.status.as_deref().map(check_status).transpose()?;
+        let components = payload.components.map(clean_components).transpose()?;
+        Ok::<_, &'static str>((title, message, components))
+    })();
+    let (title, message, components) = match checked {
+        Ok(checked) => checked,
+        Err(message) => return refused(StatusCode::BAD_REQUEST, message),
+    };
+
+    let result: Result<Option<StatusIncident>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some(incident) = sqlx::query_as::<_, StatusIncident>(&format!(
+            r#"
+            UPDATE status_incidents
+            SET title = COALESCE($2, title),
+                message = COALESCE($3, message),
+                impact = COALESCE($4, impact),
+                components = COALESCE($5, components),
+                status = COALESCE($6, status),
+                resolved_at = CASE
+                    WHEN COALESCE($6, status) <> 'resolved' THEN NULL
+                    ELSE COALESCE(resolved_at, NOW())
+                END,
+                updated_by = $7,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING {INCIDENT_COLUMNS}
+            "#
+        ))
+        .bind(incident_id)
+        .bind(&title)
+        .bind(&message)
+        .bind(&payload.impact)
+        .bind(&components)
+        .bind(&payload.status)
+        .bind(&admin.user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "status.incident.update",
+            &incident_id.to_string(),
+            serde_json::json!({
+                "title": title,
+                "message_changed": message.is_some(),
+                "impact": payload.impact,
+                "components": components,
+                "status": payload.status,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(incident))
+    }
+    .await;
+
+    match result {
+        Ok(Some(incident)) => {
+            state.status_page.invalidate().await;
+            (StatusCode::OK, Json(AdminStatusIncident::from(incident))).into_response()
+        }
+        Ok(None) => refused(StatusCode::NOT_FOUND, "Incident not found"),
+        Err(e) => {
+            error!(incident_id = %incident_id, error = %e, "Failed to update status incident");
+            database_error()
+        }
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(name: &'static str, status: Health) -> ComponentStatus {
+        ComponentStatus {
+            name,
+            status,
+            latency_ms: None,
+            message: None,
+        }
+    }
+
+    fn incident(impact: &str, components: &[&str], resolved: bool) -> StatusIncident {
+        StatusIncident {
+            id: Uuid::new_v4(),
+            title: "Incident".to_string(),
+            message: "Looking into it".to_string(),
+            impact: impact.to_string(),
+            components: components.iter().map(|c| c.to_string()).collect(),
+            status: if resolved {
+                "resolved"
+            } else {
+                "investigating"
+            }
+            .to_string(),
+            created_by: "admin".to_string(),
+            updated_by: "admin".to_string(),
+            started_at: Utc::now(),
+            resolved_at: resolved.then(Utc::now),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn unresolved_incidents_raise_the_components_they_name() {
+        let mut components = vec![
+            component("database", Health::Operational),
+            component("horizon", Health::Degraded),
+            component("email", Health::Operational),
+        ];
+        apply_incidents(
+            &mut components,
+            &[
+                incident("maintenance", &["database", "horizon"], false),
+                incident("major_outage", &["email"], true),
+            ],
+        );
+
+        assert_eq!(components[0].status, Health::Maintenance);
+        assert_eq!(components[1].status, Health::Degraded);
+        assert_eq!(components[2].status, Health::Operational);
+        assert_eq!(overall(&components), Health::Degraded);
+        assert_eq!(overall(&[]), Health::Operational);
+    }
+
+    #[test]
+    fn components_must_be_known() {
+        assert_eq!(
+            clean_components(vec!["email".into(), "email".into(), "database".into()]),
+            Ok(vec!["email".to_string(), "database".to_string()])
+        );
+        assert!(clean_components(vec!["payments".into()]).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -6,7 +7,7 @@ pub struct AnchorPayoutRequest {
     pub beneficiary_address: String,
     pub beneficiary_name: String,
     pub token: String,
-    pub token_amount: f64,
+    pub token_amount: Decimal,
     pub fiat_currency: String,
     pub bank_name: String,
     pub account_number: String,
@@ -24,9 +25,9 @@ pub enum AnchorPayoutStatus {
 pub struct AnchorPayout {
     pub id: String,
     pub request: AnchorPayoutRequest,
-    pub exchange_rate: f64,
-    pub fiat_amount: f64,
-    pub anchor_fee_usd: f64,
+    pub exchange_rate: Decimal,
+    pub fiat_amount: Decimal,
+    pub anchor_fee_usd: Decimal,
     pub status: AnchorPayoutStatus,
     pub created_at: String,
     pub updated_at: String,
@@ -47,9 +48,9 @@ impl AnchorRegistry {
         AnchorPayout {
             id: "".to_string(),
             request: req,
-            exchange_rate: 1.0,
-            fiat_amount: 0.0,
-            anchor_fee_usd: 0.0,
+            exchange_rate: Decimal::ONE,
+            fiat_amount: Decimal::ZERO,
+            anchor_fee_usd: Decimal::ZERO,
             status: AnchorPayoutStatus::Pending,
             created_at: "".to_string(),
             updated_at: "".to_string(),
@@ -0,0 +1,165 @@
+//! Keeps on-chain plan storage alive under Soroban state archival.
+//!
+//! The contract extends entries whenever they are touched, but a plan whose
+//! owner goes quiet may not be touched for months. This worker calls
+//! `bump_storage(owner)` for live plans whose last bump is older than
+//! `bump_after`, well inside the contract's 120-day extension window.
+
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::chain::{ContractInvocation, TxService};
+use crate::jobs::JobRegistry;
+use crate::telemetry;
+
+const DEFAULT_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const DEFAULT_BUMP_AFTER_DAYS: u64 = 30;
+const DEFAULT_BATCH_SIZE: i64 = 200;
+const STORAGE_TTL_LOCK_KEY: i64 = 822;
+
+#[derive(Debug, Clone, Copy)]
+pub struct StorageTtlConfig {
+    pub interval: Duration,
+    pub bump_after: Duration,
+    pub batch_size: i64,
+}
+
+impl StorageTtlConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("STORAGE_TTL_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let bump_after_days = parse_env("STORAGE_TTL_BUMP_AFTER_DAYS", DEFAULT_BUMP_AFTER_DAYS);
+        let batch_size = parse_env("STORAGE_TTL_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            bump_after: Duration::from_secs(bump_after_days.max(1) * 24 * 60 * 60),
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DuePlan {
+    id: Uuid,
+    owner_address: String,
+}
+
+pub struct StorageTtlService {
+    db: PgPool,
+    tx_service: Arc<dyn TxService>,
+    contract_id: String,
+    config: StorageTtlConfig,
+}
+
+impl StorageTtlService {
+    pub fn new(
+        db: PgPool,
+        tx_service: Arc<dyn TxService>,
+        contract_id: String,
+        config: StorageTtlConfig,
+    ) -> Self {
+        Self {
+            db,
+            tx_service,
+            contract_id,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("storage_ttl", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
+                        info!("Storage TTL worker bumped {count} plan(s)");
+                    }
+                })
+            }
+        });
+    }
+
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(STORAGE_TTL_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Storage TTL lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let due_plans = sqlx::query_as::<_, DuePlan>(
+            r#"
+            SELECT id, owner_address
+            FROM plans
+            WHERE status <> 'PAID_OUT'
+              AND COALESCE(storage_bumped_at, created_at) <= NOW() - ($1 * INTERVAL '1 second')
+            ORDER BY COALESCE(storage_bumped_at, created_at) ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(self.config.bump_after.as_secs() as f64)
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut bumped = 0;
+        for plan in &due_plans {
+            let invocation = ContractInvocation {
+                contract_id: self.contract_id.clone(),
+                function: "bump_storage".to_string(),
+                args: vec![plan.owner_address.clone()],
+                memo: telemetry::current_memo(),
+            };
+
+            match self.tx_service.invoke_contract(&invocation).await {
+                Ok(tx_hash) => {
+                    sqlx::query("UPDATE plans SET storage_bumped_at = NOW() WHERE id = $1")
+                        .bind(plan.id)
+                        .execute(&mut *tx)
+                        .await?;
+                    info!(plan_id = %plan.id, tx_hash = %tx_hash, "Extended plan storage TTL");
+                    bumped += 1;
+                }
+                Err(e) => {
+                    warn!(plan_id = %plan.id, error = %e, "Failed to extend plan storage TTL");
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(bumped)
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bump_window_is_inside_contract_extension() {
+        std::env::remove_var("STORAGE_TTL_BUMP_AFTER_DAYS");
+
+        let config = StorageTtlConfig::from_env();
+
+        // The contract extends entries to 120 days; bumping monthly leaves
+        // ample margin for missed sweeps.
+        assert_eq!(config.bump_after, Duration::from_secs(30 * 24 * 60 * 60));
+        assert!(config.bump_after < Duration::from_secs(120 * 24 * 60 * 60));
+    }
+}
@@ -0,0 +1,633 @@
+//! Operational limits that admins can change without a redeploy.
+//!
+//! Each setting is an integer with a default from the static configuration
+//! and an allowed range. Overrides live in `system_settings` and are read
+//! through [`SystemSettingsCache`], which keeps a snapshot in memory for
+//! `SYSTEM_SETTINGS_CACHE_TTL_SECS` and drops it as soon as an admin
+//! changes a value on this instance. Other instances pick the change up
+//! when their snapshot expires.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::check_in::{EscalationPolicy, DEFAULT_CONTACT_AFTER_DAYS, DEFAULT_ESCALATE_AFTER_DAYS};
+use crate::config::Config;
+use crate::kyc_tiers::{KycTier, TierLimits};
+
+const DEFAULT_CACHE_TTL_SECS: u64 = 30;
+const DEFAULT_VERIFICATION_CODE_TTL_MINUTES: i64 = 30;
+const DEFAULT_REAUTH_CHALLENGE_TTL_MINUTES: i64 = 5;
+const DEFAULT_HTTP_AUDIT_RETENTION_DAYS: i64 = 90;
+const DEFAULT_CLAIM_WINDOW_DAYS: i64 = 365;
+const DEFAULT_SESSION_IDLE_TIMEOUT_MINUTES: i64 = 30;
+const DEFAULT_STEP_UP_WINDOW_MINUTES: i64 = 10;
+/// One whole token in base units; Stellar assets have 7 decimals.
+const TOKEN: i64 = 10_000_000;
+/// Largest tier limit an admin can set, in base units.
+const MAX_TIER_LIMIT: i64 = 100_000_000_000 * TOKEN;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemSettingKey {
+    /// Lifetime of emergency contact verification codes.
+    VerificationCodeTtlMinutes,
+    /// Lifetime of wallet re-authentication challenges.
+    ReauthChallengeTtlMinutes,
+    /// Wait between a claim request and its payout.
+    ClaimCoolingOffHours,
+    /// Grace period after a missed check-in reminder before contacts are told.
+    CheckInContactAfterDays,
+    /// Grace period after contacts are told before plans become claimable.
+    CheckInEscalateAfterDays,
+    /// How long captured requests and responses are kept in `http_audit`.
+    HttpAuditRetentionDays,
+    /// Time after a plan's inactivity deadline in which beneficiaries can
+    /// claim it before it is escheated.
+    ClaimWindowDays,
+    /// Total of active plans an owner of each KYC tier may hold, in token
+    /// base units like the other tier limits.
+    KycBasicMaxPlanAmount,
+    KycVerifiedMaxPlanAmount,
+    KycEnhancedMaxPlanAmount,
+    /// Largest loan a borrower of each KYC tier may take.
+    KycBasicMaxLoanAmount,
+    KycVerifiedMaxLoanAmount,
+    KycEnhancedMaxLoanAmount,
+    /// Largest share of one plan a beneficiary of each KYC tier may claim.
+    KycBasicMaxClaimPayout,
+    KycVerifiedMaxClaimPayout,
+    KycEnhancedMaxClaimPayout,
+    /// Inactivity after which a SEP-10 session stops working; set in each
+    /// token when it is issued.
+    SessionIdleTimeoutMinutes,
+    /// How long a wallet signature counts as recent for sensitive actions.
+    StepUpWindowMinutes,
+}
+
+impl SystemSettingKey {
+    pub const ALL: [Self; 18] = [
+        Self::VerificationCodeTtlMinutes,
+        Self::ReauthChallengeTtlMinutes,
+        Self::ClaimCoolingOffHours,
+        Self::CheckInContactAfterDays,
+        Self::CheckInEscalateAfterDays,
+        Self::HttpAuditRetentionDays,
+        Self::ClaimWindowDays,
+        Self::KycBasicMaxPlanAmount,
+        Self::KycVerifiedMaxPlanAmount,
+        Self::KycEnhancedMaxPlanAmount,
+        Self::KycBasicMaxLoanAmount,
+        Self::KycVerifiedMaxLoanAmount,
+        Self::KycEnhancedMaxLoanAmount,
+        Self::KycBasicMaxClaimPayout,
+        Self::KycVerifiedMaxClaimPayout,
+        Self::KycEnhancedMaxClaimPayout,
+        Self::SessionIdleTimeoutMinutes,
+        Self::StepUpWindowMinutes,
+    ];
+
+    /// Keys of the plan, loan and claim payout limits of `tier`.
+    pub fn tier_limit_keys(tier: KycTier) -> [Self; 3] {
+        match tier {
+            KycTier::Basic => [
+                Self::KycBasicMaxPlanAmount,
+                Self::KycBasicMaxLoanAmount,
+                Self::KycBasicMaxClaimPayout,
+            ],
+            KycTier::Verified => [
+                Self::KycVerifiedMaxPlanAmount,
+                Self::KycVerifiedMaxLoanAmount,
+                Self::KycVerifiedMaxClaimPayout,
+            ],
+            KycTier::Enhanced => [
+                Self::KycEnhancedMaxPlanAmount,
+                Self::KycEnhancedMaxLoanAmount,
+                Self::KycEnhancedMaxClaimPayout,
+            ],
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::VerificationCodeTtlMinutes => "verification_code_ttl_minutes",
+            Self::ReauthChallengeTtlMinutes => "reauth_challenge_ttl_minutes",
+            Self::ClaimCoolingOffHours => "claim_cooling_off_hours",
+            Self::CheckInContactAfterDays => "check_in_contact_after_days",
+            Self::CheckInEscalateAfterDays => "check_in_escalate_after_days",
+            Self::HttpAuditRetentionDays => "http_audit_retention_days",
+            Self::ClaimWindowDays => "claim_window_days",
+            Self::KycBasicMaxPlanAmount => "kyc_basic_max_plan_amount",
+            Self::KycVerifiedMaxPlanAmount => "kyc_verified_max_plan_amount",
+            Self::KycEnhancedMaxPlanAmount => "kyc_enhanced_max_plan_amount",
+            Self::KycBasicMaxLoanAmount => "kyc_basic_max_loan_amount",
+            Self::KycVerifiedMaxLoanAmount => "kyc_verified_max_loan_amount",
+            Self::KycEnhancedMaxLoanAmount => "kyc_enhanced_max_loan_amount",
+            Self::KycBasicMaxClaimPayout => "kyc_basic_max_claim_payout",
+            Self::KycVerifiedMaxClaimPayout => "kyc_verified_max_claim_payout",
+            Self::KycEnhancedMaxClaimPayout => "kyc_enhanced_max_claim_payout",
+            Self::SessionIdleTimeoutMinutes => "session_idle_timeout_minutes",
+            Self::StepUpWindowMinutes => "step_up_window_minutes",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+
+    /// Inclusive range an override must fall in.
+    pub fn range(self) -> (i64, i64) {
+        match self {
+            Self::VerificationCodeTtlMinutes => (1, 1_440),
+            Self::ReauthChallengeTtlMinutes => (1, 60),
+            Self::ClaimCoolingOffHours => (0, 720),
+            Self::CheckInContactAfterDays | Self::CheckInEscalateAfterDays => (0, 365),
+            Self::HttpAuditRetentionDays => (1, 3_650),
+            Self::ClaimWindowDays => (30, 3_650),
+            Self::SessionIdleTimeoutMinutes => (5, 1_440),
+            Self::StepUpWindowMinutes => (1, 120),
+            _ => (0, MAX_TIER_LIMIT),
+        }
+    }
+
+    /// Value used while no override is stored.
+    pub fn default_value(self, config: &Config) -> i64 {
+        match self {
+            Self::VerificationCodeTtlMinutes => DEFAULT_VERIFICATION_CODE_TTL_MINUTES,
+            Self::ReauthChallengeTtlMinutes => DEFAULT_REAUTH_CHALLENGE_TTL_MINUTES,
+            Self::ClaimCoolingOffHours => i64::from(config.claim_cooling_off_hours),
+            Self::CheckInContactAfterDays => {
+                parse_env("CHECK_IN_CONTACT_AFTER_DAYS", DEFAULT_CONTACT_AFTER_DAYS).max(0)
+            }
+            Self::CheckInEscalateAfterDays => {
+                parse_env("CHECK_IN_ESCALATE_AFTER_DAYS", DEFAULT_ESCALATE_AFTER_DAYS).max(0)
+            }
+            Self::HttpAuditRetentionDays => parse_env(
+                "HTTP_AUDIT_RETENTION_DAYS",
+                DEFAULT_HTTP_AUDIT_RETENTION_DAYS,
+            )
+            .max(1),
+            Self::ClaimWindowDays => {
+                parse_env("CLAIM_WINDOW_DAYS", DEFAULT_CLAIM_WINDOW_DAYS).clamp(30, 3_650)
+            }
+            Self::KycBasicMaxPlanAmount | Self::KycBasicMaxClaimPayout => 10_000 * TOKEN,
+            Self::KycBasicMaxLoanAmount => 1_000 * TOKEN,
+            Self::KycVerifiedMaxPlanAmount | Self::KycVerifiedMaxClaimPayout => 250_000 * TOKEN,
+            Self::KycVerifiedMaxLoanAmount => 50_000 * TOKEN,
+            Self::KycEnhancedMaxPlanAmount | Self::KycEnhancedMaxClaimPayout => 10_000_000 * TOKEN,
+            Self::KycEnhancedMaxLoanAmount => 1_000_000 * TOKEN,
+            Self::SessionIdleTimeoutMinutes => DEFAULT_SESSION_IDLE_TIMEOUT_MINUTES,
+            Self::StepUpWindowMinutes => DEFAULT_STEP_UP_WINDOW_MINUTES,
+        }
+    }
+
+    pub fn validate(self, value: i64) -> Result<(), String> {
+        let (min, max) = self.range();
+        if (min..=max).contains(&value) {
+            Ok(())
+        } else {
+            Err(format!("{} must be between {min} and {max}", self.as_str()))
+        }
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Resolved values of every setting, with typed accessors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemSettings {
+    values: BTreeMap<SystemSettingKey, i64>,
+}
+
+impl SystemSettings {
+    pub fn defaults(config: &Config) -> Self {
+        Self {
+            values: SystemSettingKey::ALL
+                .into_iter()
+                .map(|key| (key, key.default_value(config)))
+                .collect(),
+        }
+    }
+
+    /// Applies stored overrides; values outside a key's range are ignored.
+    pub fn with_overrides(mut self, overrides: &[(String, i64)]) -> Self {
+        for (key, value) in overrides {
+            match SystemSettingKey::parse(key) {
+                Some(key) if key.validate(*value).is_ok() => {
+                    self.values.insert(key, *value);
+                }
+                _ => warn!(key = %key, value, "Ignoring invalid system setting"),
+            }
+        }
+        self
+    }
+
+    pub fn get(&self, key: SystemSettingKey) -> i64 {
+        self.values.get(&key).copied().unwrap_or_default()
+    }
+
+    pub fn verification_code_ttl_minutes(&self) -> i64 {
+        self.get(SystemSettingKey::VerificationCodeTtlMinutes)
+    }
+
+    pub fn reauth_challenge_ttl(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.get(SystemSettingKey::ReauthChallengeTtlMinutes))
+    }
+
+    pub fn session_idle_timeout(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.get(SystemSettingKey::SessionIdleTimeoutMinutes))
+    }
+
+    pub fn step_up_window(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.get(SystemSettingKey::StepUpWindowMinutes))
+    }
+
+    pub fn claim_cooling_off(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.get(SystemSettingKey::ClaimCoolingOffHours))
+    }
+
+    pub fn http_audit_retention(&self) -> chrono::Duration {
+        chrono::Duration::days(self.get(SystemSettingKey::HttpAuditRetentionDays))
+    }
+
+    pub fn claim_window(&self) -> chrono::Duration {
+        chrono::Duration::days(self.get(SystemSettingKey::ClaimWindowDays))
+    }
+
+    pub fn tier_limits(&self, tier: KycTier) -> TierLimits {
+        let [plan, loan, claim_payout] =
+            SystemSettingKey::tier_limit_keys(tier).map(|key| self.get(key).into());
+        TierLimits {
+            max_plan_amount: plan,
+            max_loan_amount: loan,
+            max_claim_payout: claim_payout,
+        }
+    }
+
+    pub fn escalation_policy(&self) -> EscalationPolicy {
+        EscalationPolicy {
+            contact_after: chrono::Duration::days(
+                self.get(SystemSettingKey::CheckInContactAfterDays),
+            ),
+            escalate_after: chrono::Duration::days(
+                self.get(SystemSettingKey::CheckInEscalateAfterDays),
+            ),
+        }
+    }
+}
+
+/// In-process cache of [`SystemSettings`].
+pub struct SystemSettingsCache {
+    db: PgPool,
+    config: Arc<Config>,
+    ttl: Duration,
+    cached: RwLock<Option<(Instant, Arc<SystemSettings>)>>,
+}
+
+impl SystemSettingsCache {
+    pub fn new(db: PgPool, config: Arc<Config>, ttl: Duration) -> Self {
+        Self {
+            db,
+            config,
+            ttl,
+            cached: RwLock::new(None),
+        }
+    }
+
+    /// TTL from `SYSTEM_SETTINGS_CACHE_TTL_SECS`.
+    pub fn ttl_from_env() -> Duration {
+        Duration::from_secs(parse_env(
+            "SYSTEM_SETTINGS_CACHE_TTL_SECS",
+            DEFAULT_CACHE_TTL_SECS,
+        ))
+    }
+
+    /// Current settings. If they cannot be loaded the last snapshot is
+    /// kept, or the defaults are used when there is none.
+    pub async fn get(&self) -> Arc<SystemSettings> {
+        if let Some((loaded_at, settings)) = self.cached.read().await.as_ref() {
+            if loaded_at.elapsed() < self.ttl {
+                return settings.clone();
+            }
+        }
+
+        let mut cached = self.cached.write().await;
+        if let Some((loaded_at, settings)) = cached.as_ref() {
+            if loaded_at.elapsed() < self.ttl {
+                return settings.clone();
+            }
+        }
+        match load_overrides(&self.db).await {
+            Ok(overrides) => {
+                let settings =
+                    Arc::new(SystemSettings::defaults(&self.config).with_overrides(&overrides));
+                *cached = Some((Instant::now(), settings.clone()));
+                settings
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to load system settings; using last known values");
+                cached
+                    .as_ref()
+                    .map(|(_, settings)| settings.clone())
+                    .unwrap_or_else(|| Arc::new(SystemSettings::defaults(&self.config)))
+            }
+        }
+    }
+
+    /// Drops the snapshot so the next read goes to the database.
+    pub async fn invalidate(&self) {
+        *self.cached.write().await = None;
+    }
+}
+
+async fn load_overrides(db: &PgPool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT key, value FROM system_settings")
+        .fetch_all(db)
+        .await
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct StoredSetting {
+    key: String,
+    value: i64,
+    updated_by: String,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SystemSettingView {
+    pub key: SystemSettingKey,
+    pub value: i64,
+    pub default_value: i64,
+    pub min: i64,
+    pub max: i64,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSystemSettingRequest {
+    pub value: i64,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ResetSystemSettingRequest {
+    pub reason: Option<String>,
+}
+
+fn view(
+    key: SystemSettingKey,
+    config: &Config,
+    stored: Option<&StoredSetting>,
+) -> SystemSettingView {
+    let default_value = key.default_value(config);
+    let (min, max) = key.range();
+    SystemSettingView {
+        key,
+        value: stored.map_or(default_value, |s| s.value),
+        default_value,
+        min,
+        max,
+        updated_by: stored.map(|s| s.updated_by.clone()),
+        updated_at: stored.map(|s| s.updated_at),
+    }
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+fn not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Unknown system setting" })),
+    )
+        .into_response()
+}
+
+// Handler: List System Settings (admin)
+pub async fn list_system_settings(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let stored = match sqlx::query_as::<_, StoredSetting>(
+        "SELECT key, value, updated_by, updated_at FROM system_settings",
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(error = %e, "Failed to list system settings");
+            return database_error();
+        }
+    };
+
+    let views: Vec<SystemSettingView> = SystemSettingKey::ALL
+        .into_iter()
+        .map(|key| {
+            let row = stored.iter().find(|s| s.key == key.as_str());
+            view(key, &state.config, row)
+        })
+        .collect();
+    (StatusCode::OK, Json(views)).into_response()
+}
+
+// Handler: Update System Setting (admin)
+pub async fn update_system_setting(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(key): Path<String>,
+    Json(payload): Json<UpdateSystemSettingRequest>,
+) -> impl IntoResponse {
+    let Some(key) = SystemSettingKey::parse(&key) else {
+        return not_found();
+    };
+    if let Err(message) = key.validate(payload.value) {
+        return bad_request(&message);
+    }
+
+    let result: Result<StoredSetting, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let previous: Option<i64> =
+            sqlx::query_scalar("SELECT value FROM system_settings WHERE key = $1 FOR UPDATE")
+                .bind(key.as_str())
+                .fetch_optional(&mut *tx)
+                .await?;
+        let stored = sqlx::query_as::<_, StoredSetting>(
+            r#"
+            INSERT INTO system_settings (key, value, updated_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (key)
+            DO UPDATE SET value = EXCLUDED.value,
+                          updated_by = EXCLUDED.updated_by,
+                          updated_at = NOW()
+            RETURNING key, value, updated_by, updated_at
+            "#,
+        )
+        .bind(key.as_str())
+        .bind(payload.value)
+        .bind(&admin.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "system_setting.update",
+            key.as_str(),
+            serde_json::json!({
+                "from": previous.unwrap_or_else(|| key.default_value(&state.config)),
+                "to": payload.value,
+                "reason": payload.reason,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(stored)
+    }
+    .await;
+
+    match result {
+        Ok(stored) => {
+            state.system_settings.invalidate().await;
+            info!(key = key.as_str(), value = stored.value, admin = %admin.user_id, "System setting updated");
+            (
+                StatusCode::OK,
+                Json(view(key, &state.config, Some(&stored))),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to update system setting");
+            database_error()
+        }
+    }
+}
+
+// Handler: Reset System Setting To Default (admin)
+pub async fn reset_system_setting(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path(key): Path<String>,
+    payload: Option<Json<ResetSystemSettingRequest>>,
+) -> impl IntoResponse {
+    let Some(key) = SystemSettingKey::parse(&key) else {
+        return not_found();
+    };
+    let reason = payload.and_then(|Json(p)| p.reason);
+
+    let result: Result<(), sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let previous: Option<i64> =
+            sqlx::query_scalar("DELETE FROM system_settings WHERE key = $1 RETURNING value")
+                .bind(key.as_str())
+                .fetch_optional(&mut *tx)
+                .await?;
+        if let Some(previous) = previous {
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "system_setting.reset",
+                key.as_str(),
+                serde_json::json!({
+                    "from": previous,
+                    "to": key.default_value(&state.config),
+                    "reason": reason,
+                }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            state.system_settings.invalidate().await;
+            (StatusCode::OK, Json(view(key, &state.config, None))).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to reset system setting");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_follow_config() {
+        let mut config = Config::for_tests();
+        config.claim_cooling_off_hours = 48;
+        let settings = SystemSettings::defaults(&config);
+        assert_eq!(settings.claim_cooling_off(), chrono::Duration::hours(48));
+        assert_eq!(settings.verification_code_ttl_minutes(), 30);
+        assert_eq!(
+            settings.reauth_challenge_ttl(),
+            chrono::Duration::minutes(5)
+        );
+    }
+
+    #[test]
+    fn overrides_apply_within_range() {
+        let settings = SystemSettings::defaults(&Config::for_tests()).with_overrides(&[
+            ("claim_cooling_off_hours".to_string(), 0),
+            ("check_in_escalate_after_days".to_string(), 3),
+            ("reauth_challenge_ttl_minutes".to_string(), 0),
+            ("unknown".to_string(), 1),
+        ]);
+        assert_eq!(settings.claim_cooling_off(), chrono::Duration::zero());
+        assert_eq!(
+            settings.escalation_policy().escalate_after,
+            chrono::Duration::days(3)
+        );
+        assert_eq!(
+            settings.reauth_challenge_ttl(),
+            chrono::Duration::minutes(5)
+        );
+    }
+
+    #[test]
+    fn values_outside_range_are_rejected() {
+        assert!(SystemSettingKey::VerificationCodeTtlMinutes
+            .validate(0)
+            .is_err());
+        assert!(SystemSettingKey::ClaimCoolingOffHours.validate(0).is_ok());
+        assert!(SystemSettingKey::ClaimCoolingOffHours
+            .validate(721)
+            .is_err());
+    }
+}
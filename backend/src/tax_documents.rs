@@ -0,0 +1,815 @@
+//! Annual tax statements of interest earned and inheritance received.
+//!
+//! A statement sums, for one wallet and calendar year (UTC), the yield
+//! accrued on plans it owns and the payouts it received as a beneficiary,
+//! per asset and in the asset's base units, from the ledger. It is tagged
+//! with the jurisdiction in the wallet's profile, labelled in its preferred
+//! language, and can be downloaded as CSV or PDF. Plans carry no late fees,
+//! so statements have no line for them.
+//!
+//! Admins generate a year's statements in bulk once it has ended; running
+//! it again replaces them. `GET /api/users/me/tax-documents` lists a
+//! wallet's statements with download links signed for [`LINK_TTL_SECS`],
+//! so a browser can fetch the files without a bearer token.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::money::Money;
+use crate::reports::to_csv;
+use crate::templates::fallback_chain;
+use crate::user_profiles::DEFAULT_LANGUAGE;
+
+/// How long a signed download link stays valid.
+pub const LINK_TTL_SECS: i64 = 15 * 60;
+/// Earliest year statements can be generated for.
+const FIRST_TAX_YEAR: i32 = 2020;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxCategory {
+    /// Yield accrued on plans the wallet owns.
+    InterestIncome,
+    /// Completed payouts the wallet received as a beneficiary.
+    InheritancePayouts,
+}
+
+impl TaxCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InterestIncome => "interest_income",
+            Self::InheritancePayouts => "inheritance_payouts",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "interest_income" => Some(Self::InterestIncome),
+            "inheritance_payouts" => Some(Self::InheritancePayouts),
+            _ => None,
+        }
+    }
+}
+
+/// A statement line as stored in the document's summary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxLine {
+    pub category: TaxCategory,
+    pub asset: String,
+    /// In the asset's base units.
+    pub amount: Decimal,
+}
+
+/// A statement line as the API returns it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaxDocumentLine {
+    pub category: TaxCategory,
+    /// In the asset's base units.
+    pub amount: Money,
+}
+
+impl From<TaxLine> for TaxDocumentLine {
+    fn from(line: TaxLine) -> Self {
+        Self {
+            category: line.category,
+            amount: Money::new(line.amount, line.asset),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxSummary {
+    pub tax_year: i32,
+    /// ISO country code from the wallet's profile, if it set one.
+    pub jurisdiction: Option<String>,
+    pub language: String,
+    pub lines: Vec<TaxLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    Csv,
+    Pdf,
+}
+
+impl DocumentFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Pdf => "application/pdf",
+        }
+    }
+}
+
+struct Labels {
+    title: &'static str,
+    account: &'static str,
+    jurisdiction: &'static str,
+    unspecified: &'static str,
+    interest_income: &'static str,
+    inheritance_payouts: &'static str,
+    nothing_to_report: &'static str,
+    disclaimer: &'static str,
+}
+
+const EN: Labels = Labels {
+    title: "Annual tax statement",
+    account: "Account",
+    jurisdiction: "Jurisdiction",
+    unspecified: "unspecified",
+    interest_income: "Interest income",
+    inheritance_payouts: "Inheritance payouts received",
+    nothing_to_report: "No reportable amounts",
+    disclaimer: "Informational summary in base units; not an official tax form.",
+};
+
+const ES: Labels = Labels {
+    title: "Declaración fiscal anual",
+    account: "Cuenta",
+    jurisdiction: "Jurisdicción",
+    unspecified: "sin especificar",
+    interest_income: "Intereses recibidos",
+    inheritance_payouts: "Pagos de herencia recibidos",
+    nothing_to_report: "Sin importes declarables",
+    disclaimer: "Resumen informativo en unidades base; no es un formulario fiscal oficial.",
+};
+
+const FR: Labels = Labels {
+    title: "Relevé fiscal annuel",
+    account: "Compte",
+    jurisdiction: "Juridiction",
+    unspecified: "non précisée",
+    interest_income: "Intérêts perçus",
+    inheritance_payouts: "Versements d'héritage reçus",
+    nothing_to_report: "Aucun montant à déclarer",
+    disclaimer: "Relevé informatif en unités de base ; ce n'est pas un formulaire fiscal officiel.",
+};
+
+fn labels(language: &str) -> &'static Labels {
+    for candidate in fallback_chain(language) {
+        match candidate.as_str() {
+            "es" => return &ES,
+            "fr" => return &FR,
+            "en" => return &EN,
+            _ => {}
+        }
+    }
+    &EN
+}
+
+impl Labels {
+    fn category(&self, category: TaxCategory) -> &'static str {
+        match category {
+            TaxCategory::InterestIncome => self.interest_income,
+            TaxCategory::InheritancePayouts => self.inheritance_payouts,
+        }
+    }
+}
+
+/// The statement as CSV, one row per category and asset.
+pub fn render_csv(summary: &TaxSummary) -> Result<Vec<u8>, csv::Error> {
+    let labels = labels(&summary.language);
+    let header: Vec<String> = [
+        "tax_year",
+        "jurisdiction",
+        "category",
+        "description",
+        "asset",
+        "amount",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+    let rows: Vec<Vec<Option<String>>> = summary
+        .lines
+        .iter()
+        .map(|line| {
+            vec![
+                Some(summary.tax_year.to_string()),
+                summary.jurisdiction.clone(),
+                Some(line.category.as_str().to_string()),
+                Some(labels.category(line.category).to_string()),
+                Some(line.asset.clone()),
+                Some(line.amount.normalize().to_string()),
+            ]
+        })
+        .collect();
+    to_csv(&header, &rows)
+}
+
+/// The statement as a one-page PDF.
+pub fn render_pdf(summary: &TaxSummary, user_address: &str) -> Vec<u8> {
+    let labels = labels(&summary.language);
+    let mut lines = vec![
+        format!("{} {}", labels.title, summary.tax_year),
+        String::new(),
+        format!("{}: {user_address}", labels.account),
+        format!(
+            "{}: {}",
+            labels.jurisdiction,
+            summary
+                .jurisdiction
+                .as_deref()
+                .unwrap_or(labels.unspecified)
+        ),
+        String::new(),
+    ];
+    if summary.lines.is_empty() {
+        lines.push(labels.nothing_to_report.to_string());
+    }
+    for line in &summary.lines {
+        lines.push(format!(
+            "{}: {} ({})",
+            labels.category(line.category),
+            line.amount.normalize(),
+            line.asset
+        ));
+    }
+    lines.push(String::new());
+    lines.push(labels.disclaimer.to_string());
+    pdf_document(&lines)
+}
+
+/// Escapes text for a PDF string in WinAnsi encoding. Characters outside
+/// Latin-1 are replaced with `?`.
+fn pdf_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            ' '..='~' => escaped.push(ch),
+            '\u{a0}'..='\u{ff}' => escaped.push_str(&format!("\\{:03o}", ch as u32)),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// A single A4 page of Helvetica text, one entry per line.
+fn pdf_document(lines: &[String]) -> Vec<u8> {
+    let mut content = String::from("BT\n/F1 11 Tf\n16 TL\n50 790 Td\n");
+    for line in lines {
+        content.push_str(&format!("({}) Tj T*\n", pdf_text(line)));
+    }
+    content.push_str("ET\n");
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] \
+         /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", index + 1).as_bytes());
+    }
+    let xref_at = pdf.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        trailer.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    trailer.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_at}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    pdf.extend_from_slice(trailer.as_bytes());
+    pdf
+}
+
+fn link_mac(secret: &str, document_id: Uuid, format: DocumentFormat, expires: i64) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("tax-document:{document_id}:{}:{expires}", format.as_str()).as_bytes());
+    mac
+}
+
+/// Download path for a statement, signed until `expires` (Unix seconds).
+pub fn signed_download_path(
+    secret: &str,
+    document_id: Uuid,
+    format: DocumentFormat,
+    expires: i64,
+) -> String {
+    let signature = hex::encode(
+        link_mac(secret, document_id, format, expires)
+            .finalize()
+            .into_bytes(),
+    );
+    format!(
+        "/api/tax-documents/{document_id}/download?format={}&expires={expires}&signature={signature}",
+        format.as_str()
+    )
+}
+
+/// Whether `signature` was issued for the document, format and expiry.
+pub fn verify_download_signature(
+    secret: &str,
+    document_id: Uuid,
+    format: DocumentFormat,
+    expires: i64,
+    signature: &str,
+) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    link_mac(secret, document_id, format, expires)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+const SUMMARY_SQL: &str = r#"
+    SELECT 'interest_income' AS category, j.asset, SUM(j.amount) AS amount
+    FROM ledger_journals j
+    JOIN plans p ON p.id = j.plan_id
+    WHERE j.event_type = 'yield_accrued' AND j.basis = 'accrual'
+      AND p.owner_address = $1
+      AND j.occurred_at >= $2 AND j.occurred_at < $3
+    GROUP BY j.asset
+    UNION ALL
+    SELECT 'inheritance_payouts', j.asset, SUM(j.amount)
+    FROM ledger_journals j
+    JOIN payouts pay ON pay.id = j.source_id
+    WHERE j.event_type = 'payout_completed' AND j.basis = 'cash'
+      AND pay.beneficiary_address = $1
+      AND j.occurred_at >= $2 AND j.occurred_at < $3
+    GROUP BY j.asset
+    ORDER BY 1, 2
+"#;
+
+const RECIPIENTS_SQL: &str = r#"
+    SELECT p.owner_address
+    FROM ledger_journals j
+    JOIN plans p ON p.id = j.plan_id
+    WHERE j.event_type = 'yield_accrued' AND j.basis = 'accrual'
+      AND j.occurred_at >= $1 AND j.occurred_at < $2
+    UNION
+    SELECT pay.beneficiary_address
+    FROM ledger_journals j
+    JOIN payouts pay ON pay.id = j.source_id
+    WHERE j.event_type = 'payout_completed' AND j.basis = 'cash'
+      AND j.occurred_at >= $1 AND j.occurred_at < $2
+"#;
+
+/// Start and end of a calendar year in UTC.
+fn year_bounds(tax_year: i32) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = |year| {
+        Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+            .single()
+            .expect("January 1st is unambiguous in UTC")
+    };
+    (start(tax_year), start(tax_year + 1))
+}
+
+/// Builds the statement of `user_address` for `tax_year` from the ledger.
+pub async fn summarize(
+    conn: &mut PgConnection,
+    user_address: &str,
+    tax_year: i32,
+) -> Result<TaxSummary, sqlx::Error> {
+    let (from, to) = year_bounds(tax_year);
+    let rows: Vec<(String, String, Decimal)> = sqlx::query_as(SUMMARY_SQL)
+        .bind(user_address)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&mut *conn)
+        .await?;
+    let profile: Option<(Option<String>, String)> = sqlx::query_as(
+        "SELECT country, preferred_language FROM user_profiles WHERE user_address = $1",
+    )
+    .bind(user_address)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (jurisdiction, language) = profile.unwrap_or_else(|| (None, DEFAULT_LANGUAGE.to_string()));
+
+    Ok(TaxSummary {
+        tax_year,
+        jurisdiction,
+        language,
+        lines: rows
+            .into_iter()
+            .filter_map(|(category, asset, amount)| {
+                Some(TaxLine {
+                    category: TaxCategory::parse(&category)?,
+                    asset,
+                    amount,
+                })
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateRequest {
+    pub tax_year: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateReceipt {
+    pub tax_year: i32,
+    pub documents: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DownloadLinks {
+    pub csv: String,
+    pub pdf: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaxDocument {
+    pub id: Uuid,
+    pub tax_year: i32,
+    pub jurisdiction: Option<String>,
+    pub lines: Vec<TaxDocumentLine>,
+    pub generated_at: DateTime<Utc>,
+    pub links: DownloadLinks,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DocumentRow {
+    id: Uuid,
+    tax_year: i32,
+    jurisdiction: Option<String>,
+    summary: serde_json::Value,
+    generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DownloadQuery {
+    pub format: DocumentFormat,
+    pub expires: i64,
+    pub signature: String,
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+// Handler: Generate Tax Documents
+pub async fn generate_tax_documents(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Json(payload): Json<GenerateRequest>,
+) -> impl IntoResponse {
+    let tax_year = payload.tax_year;
+    if tax_year < FIRST_TAX_YEAR || tax_year >= Utc::now().year() {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "Statements can only be generated for a tax year that has ended",
+        );
+    }
+
+    let result: Result<usize, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let (from, to) = year_bounds(tax_year);
+        let recipients: Vec<String> = sqlx::query_scalar(RECIPIENTS_SQL)
+            .bind(from)
+            .bind(to)
+            .fetch_all(&mut *tx)
+            .await?;
+        for user_address in &recipients {
+            let summary = summarize(&mut tx, user_address, tax_year).await?;
+            sqlx::query(
+                r#"
+                INSERT INTO tax_documents (user_address, tax_year, jurisdiction, language, summary, generated_by)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (user_address, tax_year) DO UPDATE
+                SET jurisdiction = EXCLUDED.jurisdiction,
+                    language = EXCLUDED.language,
+                    summary = EXCLUDED.summary,
+                    generated_by = EXCLUDED.generated_by,
+                    generated_at = NOW()
+                "#,
+            )
+            .bind(user_address)
+            .bind(tax_year)
+            .bind(&summary.jurisdiction)
+            .bind(&summary.language)
+            .bind(serde_json::to_value(&summary).unwrap_or_default())
+            .bind(&admin.user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "tax_documents.generated",
+            &tax_year.to_string(),
+            serde_json::json!({ "documents": recipients.len() }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(recipients.len())
+    }
+    .await;
+
+    match result {
+        Ok(documents) => Json(GenerateReceipt {
+            tax_year,
+            documents,
+        })
+        .into_response(),
+        Err(e) => {
+            error!(error = %e, tax_year, "Failed to generate tax documents");
+            database_error()
+        }
+    }
+}
+
+// Handler: Get My Tax Documents
+pub async fn get_my_tax_documents(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let rows: Vec<DocumentRow> = match sqlx::query_as(
+        r#"
+            SELECT id, tax_year, jurisdiction, summary, generated_at
+            FROM tax_documents
+            WHERE user_address = $1
+            ORDER BY tax_year DESC
+            "#,
+    )
+    .bind(caller)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(error = %e, "Failed to load tax documents");
+            return database_error();
+        }
+    };
+
+    let expires_at = Utc::now() + chrono::Duration::seconds(LINK_TTL_SECS);
+    let secret = &state.config.jwt_secret;
+    let documents: Vec<TaxDocument> = rows
+        .into_iter()
+        .map(|row| {
+            let expires = expires_at.timestamp();
+            TaxDocument {
+                id: row.id,
+                tax_year: row.tax_year,
+                jurisdiction: row.jurisdiction,
+                lines: serde_json::from_value::<TaxSummary>(row.summary)
+                    .map(|s| s.lines.into_iter().map(TaxDocumentLine::from).collect())
+                    .unwrap_or_default(),
+                generated_at: row.generated_at,
+                links: DownloadLinks {
+                    csv: signed_download_path(secret, row.id, DocumentFormat::Csv, expires),
+                    pdf: signed_download_path(secret, row.id, DocumentFormat::Pdf, expires),
+                    expires_at,
+                },
+            }
+        })
+        .collect();
+    Json(documents).into_response()
+}
+
+// Handler: Download Tax Document
+pub async fn download_tax_document(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+    Query(query): Query<DownloadQuery>,
+) -> impl IntoResponse {
+    if !verify_download_signature(
+        &state.config.jwt_secret,
+        document_id,
+        query.format,
+        query.expires,
+        &query.signature,
+    ) {
+        return refused(StatusCode::FORBIDDEN, "Invalid download link");
+    }
+    if query.expires < Utc::now().timestamp() {
+        return refused(StatusCode::GONE, "Download link has expired");
+    }
+
+    let row: Option<(String, serde_json::Value)> =
+        match sqlx::query_as("SELECT user_address, summary FROM tax_documents WHERE id = $1")
+            .bind(document_id)
+            .fetch_optional(&state.db_pool)
+            .await
+        {
+            Ok(row) => row,
+            Err(e) => {
+                error!(error = %e, %document_id, "Failed to load tax document");
+                return database_error();
+            }
+        };
+    let Some((user_address, summary)) = row else {
+        return refused(StatusCode::NOT_FOUND, "Tax document not found");
+    };
+    let summary: TaxSummary = match serde_json::from_value(summary) {
+        Ok(summary) => summary,
+        Err(e) => {
+            error!(error = %e, %document_id, "Stored tax summary is unreadable");
+            return refused(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Tax document is unreadable",
+            );
+        }
+    };
+
+    let body = match query.format {
+        DocumentFormat::Csv => match render_csv(&summary) {
+            Ok(body) => body,
+            Err(e) => {
+                error!(error = %e, %document_id, "Failed to render tax CSV");
+                return refused(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to render tax document",
+                );
+            }
+        },
+        DocumentFormat::Pdf => render_pdf(&summary, &user_address),
+    };
+    let disposition = format!(
+        "attachment; filename=\"inheritx-tax-{}.{}\"",
+        summary.tax_year,
+        query.format.as_str()
+    );
+    (
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                query.format.content_type().to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(language: &str) -> TaxSummary {
+        TaxSummary {
+            tax_year: 2025,
+            jurisdiction: Some("FR".to_string()),
+            language: language.to_string(),
+            lines: vec![
+                TaxLine {
+                    category: TaxCategory::InterestIncome,
+                    asset: "USDC".to_string(),
+                    amount: Decimal::new(125_000, 4),
+                },
+                TaxLine {
+                    category: TaxCategory::InheritancePayouts,
+                    asset: "USDC".to_string(),
+                    amount: Decimal::new(400, 0),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn download_signature_is_bound_to_document_format_and_expiry() {
+        let id = Uuid::new_v4();
+        let path = signed_download_path("secret", id, DocumentFormat::Csv, 1_900_000_000);
+        let signature = path.rsplit_once("signature=").unwrap().1;
+
+        assert!(verify_download_signature(
+            "secret",
+            id,
+            DocumentFormat::Csv,
+            1_900_000_000,
+            signature
+        ));
+        assert!(!verify_download_signature(
+            "secret",
+            id,
+            DocumentFormat::Pdf,
+            1_900_000_000,
+            signature
+        ));
+        assert!(!verify_download_signature(
+            "secret",
+            id,
+            DocumentFormat::Csv,
+            1_900_000_001,
+            signature
+        ));
+        assert!(!verify_download_signature(
+            "other",
+            id,
+            DocumentFormat::Csv,
+            1_900_000_000,
+            signature
+        ));
+        assert!(!verify_download_signature(
+            "secret",
+            id,
+            DocumentFormat::Csv,
+            1_900_000_000,
+            "not-hex"
+        ));
+    }
+
+    #[test]
+    fn csv_is_localized_and_keeps_machine_readable_columns() {
+        let csv = String::from_utf8(render_csv(&summary("es-MX")).unwrap()).unwrap();
+        let mut rows = csv.lines();
+        assert_eq!(
+            rows.next(),
+            Some("tax_year,jurisdiction,category,description,asset,amount")
+        );
+        assert_eq!(
+            rows.next(),
+            Some("2025,FR,interest_income,Intereses recibidos,USDC,12.5")
+        );
+        assert_eq!(
+            rows.next(),
+            Some("2025,FR,inheritance_payouts,Pagos de herencia recibidos,USDC,400")
+        );
+    }
+
+    #[test]
+    fn pdf_has_a_valid_cross_reference_table() {
+        let pdf = render_pdf(&summary("fr"), "GOWNER");
+        let text = String::from_utf8(pdf.clone()).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        // Accented labels are written as WinAnsi octal escapes.
+        assert!(text.contains("(Relev\\351 fiscal annuel 2025) Tj"));
+        assert!(text.contains("(Int\\351r\\352ts per\\347us: 12.5 \\(USDC\\)) Tj"));
+
+        let xref_at: usize = text
+            .rsplit_once("startxref\n")
+            .unwrap()
+            .1
+            .trim_end_matches("%%EOF\n")
+            .trim()
+            .parse()
+            .unwrap();
+        assert!(text[xref_at..].starts_with("xref\n0 6\n"));
+        let offsets: Vec<usize> = text[xref_at..]
+            .lines()
+            .skip(3)
+            .take(5)
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        for (index, offset) in offsets.into_iter().enumerate() {
+            assert!(text[offset..].starts_with(&format!("{} 0 obj\n", index + 1)));
+        }
+    }
+
+    #[test]
+    fn labels_fall_back_to_english() {
+        assert_eq!(labels("pt-BR").title, EN.title);
+        assert_eq!(labels("fr-CA").title, FR.title);
+        assert_eq!(pdf_text("a (b) \\ 日"), "a \\(b\\) \\\\ ?");
+    }
+}
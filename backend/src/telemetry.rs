@@ -1,4 +1,38 @@
+//! Tracing setup and per-request correlation ids.
+//!
+//! Every request gets a correlation id: the caller's `X-Request-Id` when it
+//! is well formed, otherwise a new UUID. The id is echoed in the response's
+//! `X-Request-Id` and recorded on the request span, so every log line of the
+//! request carries it, sqlx's query logs included. Code further down reads it
+//! with [`correlation_id`]. Audit rows, HTTP audit entries, claim requests
+//! and payouts store it. Tasks started with [`spawn`] keep it, workers carry
+//! it over from the rows they pick up, and chain submissions carry it as
+//! their memo (see [`correlation_memo`]). An operation can therefore be
+//! followed from the HTTP request through the database to the ledger.
+
+use std::future::Future;
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use tokio::task::JoinHandle;
+use tracing::Instrument;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+/// Longest inbound request id that is kept; longer ones are replaced.
+const MAX_REQUEST_ID_LEN: usize = 64;
+const MEMO_PREFIX: &str = "ixr-";
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
 
 pub fn init_tracing() -> Result<(), anyhow::Error> {
     tracing_subscriber::registry()
@@ -10,3 +44,123 @@ pub fn init_tracing() -> Result<(), anyhow::Error> {
         .init();
     Ok(())
 }
+
+/// The correlation id of the operation the current task is running, if any.
+pub fn correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+fn new_correlation_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Accepts an inbound request id made of letters, digits and `-_.:` only,
+/// so it is safe to log, store and echo back.
+fn accept_request_id(value: &str) -> Option<String> {
+    let valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b));
+    valid.then(|| value.to_string())
+}
+
+/// Text memo for a chain transaction made on behalf of `correlation_id`.
+/// Request ids do not fit in a 28-byte text memo, so the memo is a hash
+/// prefix: it can be recomputed from the id to find the transaction.
+pub fn correlation_memo(correlation_id: &str) -> String {
+    let digest = Sha256::digest(correlation_id.as_bytes());
+    let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest);
+    format!("{MEMO_PREFIX}{}", &encoded[..24])
+}
+
+/// The memo for the current operation's chain transactions.
+pub fn current_memo() -> Option<String> {
+    correlation_id().map(|id| correlation_memo(&id))
+}
+
+/// Runs `future` as its own operation under `id`, or a fresh id when there
+/// is none. Workers use this for work that did not come from a request.
+pub async fn with_correlation_id<F: Future>(id: Option<String>, future: F) -> F::Output {
+    let id = id.unwrap_or_else(new_correlation_id);
+    let span = tracing::info_span!("operation", correlation_id = %id);
+    CORRELATION_ID.scope(id, future.instrument(span)).await
+}
+
+/// `tokio::spawn` that keeps the caller's correlation id and span, for
+/// background work started while handling a request.
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let span = tracing::Span::current();
+    match correlation_id() {
+        Some(id) => tokio::spawn(CORRELATION_ID.scope(id, future.instrument(span))),
+        None => tokio::spawn(future.instrument(span)),
+    }
+}
+
+/// Assigns the request its correlation id and opens the request span.
+/// Layered outermost so every other middleware runs inside it.
+pub async fn correlation_middleware(req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(accept_request_id)
+        .unwrap_or_else(new_correlation_id);
+    let span = tracing::info_span!(
+        "request",
+        correlation_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+
+    let mut response = CORRELATION_ID
+        .scope(id.clone(), next.run(req).instrument(span))
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inbound_request_ids_are_checked() {
+        assert_eq!(
+            accept_request_id("req-42:retry.1").as_deref(),
+            Some("req-42:retry.1")
+        );
+        assert_eq!(accept_request_id(""), None);
+        assert_eq!(accept_request_id("has space"), None);
+        assert_eq!(accept_request_id("a\nb"), None);
+        assert_eq!(accept_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)), None);
+    }
+
+    #[test]
+    fn memo_fits_stellar_text_memo() {
+        let memo = correlation_memo(&new_correlation_id());
+        assert_eq!(memo.len(), 28);
+        assert!(memo.starts_with(MEMO_PREFIX));
+        assert_eq!(correlation_memo("abc"), correlation_memo("abc"));
+        assert_ne!(correlation_memo("abc"), correlation_memo("abd"));
+    }
+
+    #[tokio::test]
+    async fn spawned_tasks_keep_the_correlation_id() {
+        assert_eq!(correlation_id(), None);
+        let seen = with_correlation_id(Some("op-1".to_string()), async {
+            spawn(async { correlation_id() }).await.unwrap()
+        })
+        .await;
+        assert_eq!(seen.as_deref(), Some("op-1"));
+
+        let generated = with_correlation_id(None, async { correlation_id() }).await;
+        assert!(generated.is_some_and(|id| Uuid::parse_str(&id).is_ok()));
+    }
+}
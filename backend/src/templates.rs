@@ -0,0 +1,943 @@
+//! Localized notification and email templates.
+//!
+//! Each template has a subject and body with `{placeholder}` variables.
+//! English, Spanish and French copies are built in; admins can override
+//! them or add other languages, and overrides are stored in
+//! `notification_templates`. Every upload is kept as a numbered version
+//! in `notification_template_versions` and can be restored, and admins
+//! can preview a template or a draft with sample values before saving.
+//! A message is rendered in the recipient's preferred language, falling
+//! back from a regional tag to its base language (`pt-BR`, then `pt`) and
+//! finally to English.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::user_profiles::{language_for, normalize_language, DEFAULT_LANGUAGE};
+
+const MAX_SUBJECT_LEN: usize = 200;
+const MAX_BODY_LEN: usize = 5_000;
+
+/// Languages with built-in copies of every template.
+pub const BUILTIN_LANGUAGES: [&str; 3] = ["en", "es", "fr"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateKey {
+    /// One-time code confirming an emergency contact's email or phone.
+    VerificationCode,
+    ClaimRequested,
+    ClaimCancelled,
+    ClaimExecuted,
+    ClaimFailed,
+    /// Reminder that a plan's claim window is about to close.
+    ClaimExpiring,
+    /// A plan's claim window closed unclaimed and its funds were escheated.
+    PlanEscheated,
+    KycApproved,
+    KycRejected,
+    /// Sign-in from a device the wallet has not used before.
+    NewDeviceLogin,
+    AccountFrozen,
+    AccountUnfrozen,
+    /// Subject and opening line of a notification digest.
+    Digest,
+}
+
+impl TemplateKey {
+    pub const ALL: [Self; 13] = [
+        Self::VerificationCode,
+        Self::ClaimRequested,
+        Self::ClaimCancelled,
+        Self::ClaimExecuted,
+        Self::ClaimFailed,
+        Self::ClaimExpiring,
+        Self::PlanEscheated,
+        Self::KycApproved,
+        Self::KycRejected,
+        Self::NewDeviceLogin,
+        Self::AccountFrozen,
+        Self::AccountUnfrozen,
+        Self::Digest,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::VerificationCode => "verification_code",
+            Self::ClaimRequested => "claim_requested",
+            Self::ClaimCancelled => "claim_cancelled",
+            Self::ClaimExecuted => "claim_executed",
+            Self::ClaimFailed => "claim_failed",
+            Self::ClaimExpiring => "claim_expiring",
+            Self::PlanEscheated => "plan_escheated",
+            Self::KycApproved => "kyc_approved",
+            Self::KycRejected => "kyc_rejected",
+            Self::NewDeviceLogin => "new_device_login",
+            Self::AccountFrozen => "account_frozen",
+            Self::AccountUnfrozen => "account_unfrozen",
+            Self::Digest => "digest",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+
+    /// Variables the template may use.
+    pub fn placeholders(self) -> &'static [&'static str] {
+        match self {
+            Self::VerificationCode => &["code", "minutes"],
+            Self::ClaimRequested => &["execute_after"],
+            Self::ClaimFailed => &["reason"],
+            Self::ClaimExpiring => &["expires_at", "days"],
+            Self::NewDeviceLogin => &["device", "ip"],
+            Self::Digest => &["count"],
+            Self::ClaimCancelled
+            | Self::ClaimExecuted
+            | Self::PlanEscheated
+            | Self::KycApproved
+            | Self::KycRejected
+            | Self::AccountFrozen
+            | Self::AccountUnfrozen => &[],
+        }
+    }
+}
+
+/// Built-in `(subject, body)` for a template in one of [`BUILTIN_LANGUAGES`].
+fn builtin(key: TemplateKey, language: &str) -> Option<(&'static str, &'static str)> {
+    use TemplateKey::*;
+    let copy = match (key, language) {
+        (VerificationCode, "en") => (
+            "Verify your InheritX emergency contact details",
+            "Your InheritX emergency contact verification code is {code}. It expires in {minutes} minutes.",
+        ),
+        (VerificationCode, "es") => (
+            "Verifica los datos de tu contacto de emergencia en InheritX",
+            "Tu código de verificación de contacto de emergencia de InheritX es {code}. Caduca en {minutes} minutos.",
+        ),
+        (VerificationCode, "fr") => (
+            "Vérifiez les coordonnées de votre contact d'urgence InheritX",
+            "Votre code de vérification de contact d'urgence InheritX est {code}. Il expire dans {minutes} minutes.",
+        ),
+        (ClaimRequested, "en") => (
+            "Claim requested",
+            "A payout was requested for this inheritance plan. It will execute after {execute_after} unless the owner or an administrator cancels it.",
+        ),
+        (ClaimRequested, "es") => (
+            "Reclamación solicitada",
+            "Se solicitó un pago para este plan de herencia. Se ejecutará después de {execute_after} salvo que el titular o un administrador lo cancele.",
+        ),
+        (ClaimRequested, "fr") => (
+            "Réclamation demandée",
+            "Un versement a été demandé pour ce plan de succession. Il sera exécuté après {execute_after}, sauf annulation par le titulaire ou un administrateur.",
+        ),
+        (ClaimCancelled, "en") => (
+            "Claim cancelled",
+            "A pending claim on this inheritance plan was cancelled before payout.",
+        ),
+        (ClaimCancelled, "es") => (
+            "Reclamación cancelada",
+            "Una reclamación pendiente sobre este plan de herencia se canceló antes del pago.",
+        ),
+        (ClaimCancelled, "fr") => (
+            "Réclamation annulée",
+            "Une réclamation en attente sur ce plan de succession a été annulée avant le versement.",
+        ),
+        (ClaimExecuted, "en") => (
+            "Claim paid out",
+            "The cooling-off period ended and the plan's payout has been issued.",
+        ),
+        (ClaimExecuted, "es") => (
+            "Reclamación pagada",
+            "El periodo de espera terminó y se emitió el pago del plan.",
+        ),
+        (ClaimExecuted, "fr") => (
+            "Réclamation versée",
+            "Le délai de réflexion est écoulé et le versement du plan a été effectué.",
+        ),
+        (ClaimFailed, "en") => (
+            "Claim not paid out",
+            "Your claim could not be paid out: {reason}",
+        ),
+        (ClaimFailed, "es") => (
+            "Reclamación no pagada",
+            "No se pudo pagar tu reclamación: {reason}",
+        ),
+        (ClaimFailed, "fr") => (
+            "Réclamation non versée",
+            "Votre réclamation n'a pas pu être versée : {reason}",
+        ),
+        (ClaimExpiring, "en") => (
+            "Claim window closing in {days} days",
+            "The claim window for this inheritance plan closes at {expires_at}. Unclaimed funds are then transferred to the plan's fallback beneficiary or the platform treasury.",
+        ),
+        (ClaimExpiring, "es") => (
+            "El plazo de reclamación cierra en {days} días",
+            "El plazo para reclamar este plan de herencia cierra el {expires_at}. Los fondos no reclamados se transferirán entonces al beneficiario alternativo del plan o a la tesorería de la plataforma.",
+        ),
+        (ClaimExpiring, "fr") => (
+            "Le délai de réclamation se termine dans {days} jours",
+            "Le délai pour réclamer ce plan de succession se termine le {expires_at}. Les fonds non réclamés seront alors transférés au bénéficiaire de repli du plan ou à la trésorerie de la plateforme.",
+        ),
+        (PlanEscheated, "en") => (
+            "Unclaimed plan transferred",
+            "The claim window for this inheritance plan closed without a claim, and its funds were transferred to the fallback beneficiary or the platform treasury.",
+        ),
+        (PlanEscheated, "es") => (
+            "Plan no reclamado transferido",
+            "El plazo de reclamación de este plan de herencia cerró sin reclamaciones y sus fondos se transfirieron al beneficiario alternativo o a la tesorería de la plataforma.",
+        ),
+        (PlanEscheated, "fr") => (
+            "Plan non réclamé transféré",
+            "Le délai de réclamation de ce plan de succession s'est terminé sans réclamation et ses fonds ont été transférés au bénéficiaire de repli ou à la trésorerie de la plateforme.",
+        ),
+        (KycApproved, "en") => (
+            "Identity verification approved",
+            "Your identity verification has been approved.",
+        ),
+        (KycApproved, "es") => (
+            "Verificación de identidad aprobada",
+            "Tu verificación de identidad ha sido aprobada.",
+        ),
+        (KycApproved, "fr") => (
+            "Vérification d'identité approuvée",
+            "Votre vérification d'identité a été approuvée.",
+        ),
+        (KycRejected, "en") => (
+            "Identity verification rejected",
+            "Your identity verification was not approved. Please review your documents and submit them again.",
+        ),
+        (KycRejected, "es") => (
+            "Verificación de identidad rechazada",
+            "Tu verificación de identidad no fue aprobada. Revisa tus documentos y vuelve a enviarlos.",
+        ),
+        (KycRejected, "fr") => (
+            "Vérification d'identité refusée",
+            "Votre vérification d'identité n'a pas été approuvée. Veuillez vérifier vos documents et les soumettre à nouveau.",
+        ),
+        (NewDeviceLogin, "en") => (
+            "New sign-in to your account",
+            "Your account was signed in to from {device} at {ip}. If this was not you, review your security events and contact support.",
+        ),
+        (NewDeviceLogin, "es") => (
+            "Nuevo inicio de sesión en tu cuenta",
+            "Se inició sesión en tu cuenta desde {device} en {ip}. Si no fuiste tú, revisa tus eventos de seguridad y contacta con soporte.",
+        ),
+        (NewDeviceLogin, "fr") => (
+            "Nouvelle connexion à votre compte",
+            "Une connexion à votre compte a eu lieu depuis {device} à l'adresse {ip}. Si ce n'était pas vous, consultez vos événements de sécurité et contactez le support.",
+        ),
+        (AccountFrozen, "en") => (
+            "Your account has been frozen",
+            "Our support team froze your account while it is reviewed. You cannot sign in until the review is finished. Contact support if you have questions.",
+        ),
+        (AccountFrozen, "es") => (
+            "Tu cuenta ha sido congelada",
+            "Nuestro equipo de soporte congeló tu cuenta mientras se revisa. No puedes iniciar sesión hasta que termine la revisión. Contacta con soporte si tienes preguntas.",
+        ),
+        (AccountFrozen, "fr") => (
+            "Votre compte a été gelé",
+            "Notre équipe d'assistance a gelé votre compte pendant son examen. Vous ne pouvez pas vous connecter avant la fin de l'examen. Contactez le support si vous avez des questions.",
+        ),
+        (AccountUnfrozen, "en") => (
+            "Your account has been unfrozen",
+            "The review of your account is finished and you can sign in again.",
+        ),
+        (AccountUnfrozen, "es") => (
+            "Tu cuenta ha sido descongelada",
+            "La revisión de tu cuenta ha terminado y puedes volver a iniciar sesión.",
+        ),
+        (AccountUnfrozen, "fr") => (
+            "Votre compte a été dégelé",
+            "L'examen de votre compte est terminé et vous pouvez vous reconnecter.",
+        ),
+        (Digest, "en") => (
+            "Your InheritX digest: {count} new notifications",
+            "You have {count} new notifications on InheritX.",
+        ),
+        (Digest, "es") => (
+            "Tu resumen de InheritX: {count} notificaciones nuevas",
+            "Tienes {count} notificaciones nuevas en InheritX.",
+        ),
+        (Digest, "fr") => (
+            "Votre récapitulatif InheritX : {count} nouvelles notifications",
+            "Vous avez {count} nouvelles notifications sur InheritX.",
+        ),
+        _ => return None,
+    };
+    Some(copy)
+}
+
+/// Languages to try for `language`, most specific first and ending in
+/// English: `pt-BR` gives `["pt-BR", "pt", "en"]`.
+pub fn fallback_chain(language: &str) -> Vec<String> {
+    let mut chain = Vec::with_capacity(3);
+    if let Some(normalized) = normalize_language(language) {
+        if let Some((base, _)) = normalized.split_once('-') {
+            let base = base.to_string();
+            chain.push(normalized);
+            chain.push(base);
+        } else {
+            chain.push(normalized);
+        }
+    }
+    if !chain.iter().any(|l| l == DEFAULT_LANGUAGE) {
+        chain.push(DEFAULT_LANGUAGE.to_string());
+    }
+    chain
+}
+
+/// Substitutes `{name}` placeholders in one pass, so values containing
+/// braces are never expanded again. Unknown placeholders are left as-is.
+pub fn fill(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Placeholder names used in `text`.
+fn placeholders_in(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                if !name.is_empty() && name.bytes().all(|b| b.is_ascii_lowercase() || b == b'_') {
+                    names.push(name);
+                }
+                rest = &after[end + 1..];
+            }
+            None => break,
+        }
+    }
+    names
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rendered {
+    /// Language the copy was found in after falling back.
+    pub language: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Renders a built-in template without consulting overrides.
+pub fn render_builtin(key: TemplateKey, language: &str, vars: &[(&str, &str)]) -> Rendered {
+    for candidate in fallback_chain(language) {
+        if let Some((subject, body)) = builtin(key, &candidate) {
+            return Rendered {
+                language: candidate,
+                subject: fill(subject, vars),
+                body: fill(body, vars),
+            };
+        }
+    }
+    unreachable!("every template has an English copy")
+}
+
+/// Renders `key` in `language`, preferring an admin override over the
+/// built-in copy at each step of the fallback chain.
+pub async fn render(
+    conn: &mut PgConnection,
+    key: TemplateKey,
+    language: &str,
+    vars: &[(&str, &str)],
+) -> Result<Rendered, sqlx::Error> {
+    let chain = fallback_chain(language);
+    let overrides: HashMap<String, (String, String)> = sqlx::query_as::<_, (String, String, String)>(
+        "SELECT language, subject, body FROM notification_templates WHERE template_key = $1 AND language = ANY($2)",
+    )
+    .bind(key.as_str())
+    .bind(&chain)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|(language, subject, body)| (language, (subject, body)))
+    .collect();
+
+    for candidate in &chain {
+        if let Some((subject, body)) = overrides.get(candidate) {
+            return Ok(Rendered {
+                language: candidate.clone(),
+                subject: fill(subject, vars),
+                body: fill(body, vars),
+            });
+        }
+        if builtin(key, candidate).is_some() {
+            return Ok(render_builtin(key, candidate, vars));
+        }
+    }
+    Ok(render_builtin(key, DEFAULT_LANGUAGE, vars))
+}
+
+/// Renders `key` in `user_address`'s preferred language.
+pub async fn render_for(
+    conn: &mut PgConnection,
+    user_address: &str,
+    key: TemplateKey,
+    vars: &[(&str, &str)],
+) -> Result<Rendered, sqlx::Error> {
+    let language = language_for(&mut *conn, user_address).await?;
+    render(conn, key, &language, vars).await
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TemplateOverride {
+    pub template_key: String,
+    pub language: String,
+    pub subject: String,
+    pub body: String,
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+    pub version: i32,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TemplateVersion {
+    pub version: i32,
+    pub subject: String,
+    pub body: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateSummary {
+    pub key: TemplateKey,
+    pub placeholders: &'static [&'static str],
+    pub builtin_languages: &'static [&'static str],
+    pub overrides: Vec<TemplateOverride>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertTemplateRequest {
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PreviewRequest {
+    /// Draft copy to render instead of the saved one; `subject` and `body`
+    /// are given together.
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    /// Values for the template's placeholders.
+    #[serde(default)]
+    pub sample: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Preview {
+    #[serde(flatten)]
+    pub rendered: Rendered,
+    /// Whether the draft in the request was rendered.
+    pub draft: bool,
+    /// Placeholders with no sample value, left as `{name}`.
+    pub missing: Vec<&'static str>,
+}
+
+const OVERRIDE_COLUMNS: &str =
+    "template_key, language, subject, body, updated_by, updated_at, version";
+
+/// Checks an uploaded template against the key's allowed placeholders.
+pub fn validate_template(key: TemplateKey, subject: &str, body: &str) -> Result<(), String> {
+    if subject.trim().is_empty() || subject.chars().count() > MAX_SUBJECT_LEN {
+        return Err(format!(
+            "Subject must be between 1 and {MAX_SUBJECT_LEN} characters"
+        ));
+    }
+    if body.trim().is_empty() || body.chars().count() > MAX_BODY_LEN {
+        return Err(format!(
+            "Body must be between 1 and {MAX_BODY_LEN} characters"
+        ));
+    }
+    let allowed = key.placeholders();
+    let mut used = placeholders_in(subject);
+    used.extend(placeholders_in(body));
+    if let Some(unknown) = used.iter().find(|name| !allowed.contains(name)) {
+        return Err(format!(
+            "Unknown placeholder {{{unknown}}} for {}; allowed: {}",
+            key.as_str(),
+            if allowed.is_empty() {
+                "none".to_string()
+            } else {
+                allowed.join(", ")
+            }
+        ));
+    }
+    Ok(())
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Template override not found" })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+/// Saves `subject` and `body` as the override for `key` in `language`,
+/// recorded as the next version.
+async fn save_override(
+    conn: &mut PgConnection,
+    key: TemplateKey,
+    language: &str,
+    subject: &str,
+    body: &str,
+    actor: &str,
+) -> Result<TemplateOverride, sqlx::Error> {
+    // Numbering carries on after an override is deleted and uploaded again.
+    let version: i32 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(version), 0) + 1 FROM notification_template_versions WHERE template_key = $1 AND language = $2",
+    )
+    .bind(key.as_str())
+    .bind(language)
+    .fetch_one(&mut *conn)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO notification_template_versions (template_key, language, version, subject, body, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(key.as_str())
+    .bind(language)
+    .bind(version)
+    .bind(subject)
+    .bind(body)
+    .bind(actor)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_as::<_, TemplateOverride>(&format!(
+        r#"
+        INSERT INTO notification_templates (template_key, language, subject, body, updated_by, version)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (template_key, language)
+        DO UPDATE SET subject = EXCLUDED.subject,
+                      body = EXCLUDED.body,
+                      updated_by = EXCLUDED.updated_by,
+                      updated_at = NOW(),
+                      version = EXCLUDED.version
+        RETURNING {OVERRIDE_COLUMNS}
+        "#
+    ))
+    .bind(key.as_str())
+    .bind(language)
+    .bind(subject)
+    .bind(body)
+    .bind(actor)
+    .bind(version)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+fn parse_path(key: &str, language: &str) -> Result<(TemplateKey, String), &'static str> {
+    let key = TemplateKey::parse(key).ok_or("Unknown template")?;
+    let language = normalize_language(language)
+        .ok_or("Language must be a language tag such as en or pt-BR")?;
+    Ok((key, language))
+}
+
+// Handler: List Notification Templates (admin)
+pub async fn list_templates(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let overrides = match sqlx::query_as::<_, TemplateOverride>(&format!(
+        "SELECT {OVERRIDE_COLUMNS} FROM notification_templates ORDER BY template_key, language"
+    ))
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(error = %e, "Failed to list notification templates");
+            return database_error();
+        }
+    };
+
+    let summaries: Vec<TemplateSummary> = TemplateKey::ALL
+        .into_iter()
+        .map(|key| TemplateSummary {
+            key,
+            placeholders: key.placeholders(),
+            builtin_languages: &BUILTIN_LANGUAGES,
+            overrides: overrides
+                .iter()
+                .filter(|o| o.template_key == key.as_str())
+                .cloned()
+                .collect(),
+        })
+        .collect();
+    (StatusCode::OK, Json(summaries)).into_response()
+}
+
+// Handler: Upload Notification Template (admin)
+pub async fn upsert_template(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path((key, language)): Path<(String, String)>,
+    Json(payload): Json<UpsertTemplateRequest>,
+) -> impl IntoResponse {
+    let (key, language) = match parse_path(&key, &language) {
+        Ok(parsed) => parsed,
+        Err(message) => return bad_request(message),
+    };
+    if let Err(message) = validate_template(key, &payload.subject, &payload.body) {
+        return bad_request(&message);
+    }
+
+    let result: Result<TemplateOverride, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let saved = save_override(
+            &mut tx,
+            key,
+            &language,
+            payload.subject.trim(),
+            payload.body.trim(),
+            &admin.user_id,
+        )
+        .await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "template.upsert",
+            &format!("{}:{}", key.as_str(), language),
+            serde_json::json!({
+                "version": saved.version,
+                "subject": saved.subject,
+                "body": saved.body,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(saved)
+    }
+    .await;
+
+    match result {
+        Ok(saved) => (StatusCode::OK, Json(saved)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to save notification template");
+            database_error()
+        }
+    }
+}
+
+// Handler: Remove Notification Template Override (admin)
+pub async fn delete_template(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path((key, language)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (key, language) = match parse_path(&key, &language) {
+        Ok(parsed) => parsed,
+        Err(message) => return bad_request(message),
+    };
+
+    let result: Result<bool, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let deleted = sqlx::query(
+            "DELETE FROM notification_templates WHERE template_key = $1 AND language = $2",
+        )
+        .bind(key.as_str())
+        .bind(&language)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+        if deleted {
+            record_audit(
+                &mut *tx,
+                &admin.user_id,
+                "template.delete",
+                &format!("{}:{}", key.as_str(), language),
+                serde_json::json!({}),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(deleted)
+    }
+    .await;
+
+    match result {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => not_found(),
+        Err(e) => {
+            error!(error = %e, "Failed to delete notification template");
+            database_error()
+        }
+    }
+}
+
+// Handler: List Notification Template Versions (admin)
+pub async fn list_template_versions(
+    State(state): State<Arc<AppState>>,
+    Path((key, language)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (key, language) = match parse_path(&key, &language) {
+        Ok(parsed) => parsed,
+        Err(message) => return bad_request(message),
+    };
+
+    match sqlx::query_as::<_, TemplateVersion>(
+        r#"
+        SELECT version, subject, body, created_by, created_at
+        FROM notification_template_versions
+        WHERE template_key = $1 AND language = $2
+        ORDER BY version DESC
+        "#,
+    )
+    .bind(key.as_str())
+    .bind(&language)
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(versions) => (StatusCode::OK, Json(versions)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list notification template versions");
+            database_error()
+        }
+    }
+}
+
+// Handler: Restore Notification Template Version (admin)
+pub async fn restore_template_version(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<UserContext>,
+    Path((key, language, version)): Path<(String, String, i32)>,
+) -> impl IntoResponse {
+    let (key, language) = match parse_path(&key, &language) {
+        Ok(parsed) => parsed,
+        Err(message) => return bad_request(message),
+    };
+
+    let result: Result<Option<TemplateOverride>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let Some((subject, body)) = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT subject, body FROM notification_template_versions
+            WHERE template_key = $1 AND language = $2 AND version = $3
+            "#,
+        )
+        .bind(key.as_str())
+        .bind(&language)
+        .bind(version)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        // Restoring saves the old copy as a new version, so the history
+        // only ever grows.
+        let saved = save_override(&mut tx, key, &language, &subject, &body, &admin.user_id).await?;
+        record_audit(
+            &mut *tx,
+            &admin.user_id,
+            "template.restore",
+            &format!("{}:{}", key.as_str(), language),
+            serde_json::json!({ "restored_version": version, "version": saved.version }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(saved))
+    }
+    .await;
+
+    match result {
+        Ok(Some(saved)) => (StatusCode::OK, Json(saved)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Template version not found" })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to restore notification template version");
+            database_error()
+        }
+    }
+}
+
+// Handler: Preview Notification Template (admin)
+pub async fn preview_template(
+    State(state): State<Arc<AppState>>,
+    Path((key, language)): Path<(String, String)>,
+    payload: Option<Json<PreviewRequest>>,
+) -> impl IntoResponse {
+    let (key, language) = match parse_path(&key, &language) {
+        Ok(parsed) => parsed,
+        Err(message) => return bad_request(message),
+    };
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+
+    let allowed = key.placeholders();
+    if let Some(unknown) = payload
+        .sample
+        .keys()
+        .find(|name| !allowed.contains(&name.as_str()))
+    {
+        return bad_request(&format!(
+            "Unknown placeholder {unknown} for {}",
+            key.as_str()
+        ));
+    }
+    let vars: Vec<(&str, &str)> = payload
+        .sample
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let missing: Vec<&'static str> = allowed
+        .iter()
+        .copied()
+        .filter(|name| !payload.sample.contains_key(*name))
+        .collect();
+
+    let draft = match (&payload.subject, &payload.body) {
+        (Some(subject), Some(body)) => {
+            if let Err(message) = validate_template(key, subject, body) {
+                return bad_request(&message);
+            }
+            Some(Rendered {
+                language: language.clone(),
+                subject: fill(subject.trim(), &vars),
+                body: fill(body.trim(), &vars),
+            })
+        }
+        (None, None) => None,
+        _ => return bad_request("Give both subject and body to preview a draft"),
+    };
+
+    let rendered = match draft {
+        Some(rendered) => Ok((rendered, true)),
+        None => {
+            async {
+                let mut conn = state.db_pool.acquire().await?;
+                render(&mut conn, key, &language, &vars)
+                    .await
+                    .map(|rendered| (rendered, false))
+            }
+            .await
+        }
+    };
+
+    match rendered {
+        Ok((rendered, draft)) => (
+            StatusCode::OK,
+            Json(Preview {
+                rendered,
+                draft,
+                missing,
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to preview notification template");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_template_has_every_builtin_language() {
+        for key in TemplateKey::ALL {
+            for language in BUILTIN_LANGUAGES {
+                let (subject, body) = builtin(key, language).unwrap();
+                validate_template(key, subject, body).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn regional_tags_fall_back_to_base_language_then_english() {
+        assert_eq!(fallback_chain("pt-br"), ["pt-BR", "pt", "en"]);
+        assert_eq!(fallback_chain("fr"), ["fr", "en"]);
+        assert_eq!(fallback_chain("en-GB"), ["en-GB", "en"]);
+        assert_eq!(fallback_chain("not a tag"), ["en"]);
+
+        assert_eq!(
+            render_builtin(TemplateKey::ClaimCancelled, "es-MX", &[]).language,
+            "es"
+        );
+        assert_eq!(
+            render_builtin(TemplateKey::ClaimCancelled, "de", &[]).language,
+            "en"
+        );
+    }
+
+    #[test]
+    fn placeholders_are_filled_once() {
+        assert_eq!(
+            fill(
+                "Code {code}, {unknown} and {reason}",
+                &[("code", "123456"), ("reason", "{code}")]
+            ),
+            "Code 123456, {unknown} and {code}"
+        );
+        let rendered = render_builtin(TemplateKey::Digest, "fr", &[("count", "3")]);
+        assert_eq!(
+            rendered.subject,
+            "Votre récapitulatif InheritX : 3 nouvelles notifications"
+        );
+    }
+
+    #[test]
+    fn uploads_may_only_use_known_placeholders() {
+        assert!(validate_template(TemplateKey::ClaimFailed, "Failed", "Because {reason}").is_ok());
+        assert!(validate_template(TemplateKey::ClaimFailed, "Failed", "Code {code}").is_err());
+        assert!(validate_template(TemplateKey::KycApproved, " ", "Approved").is_err());
+    }
+}
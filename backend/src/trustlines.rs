@@ -0,0 +1,418 @@
+//! Trustline pre-checks for crypto payouts.
+//!
+//! A transfer of a classic Stellar asset fails on-chain if the destination
+//! account does not exist, has no trustline for the asset, is not
+//! authorized by the issuer or would exceed its trustline limit. Plan
+//! tokens listed in `PAYOUT_ASSETS` are mapped to their classic asset and
+//! the beneficiary's account is checked on Horizon first, so the payout
+//! batcher holds such payouts back instead of failing them, and owners and
+//! beneficiaries can see what has to be fixed.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::auth::UserContext;
+use crate::config::{is_valid_asset, Config};
+use crate::deposits::{HorizonAccount, HorizonClient, HorizonError, STELLAR_DECIMALS};
+use crate::field_crypto::SensitiveField;
+
+/// A plan token paid out as a classic Stellar asset, configured as
+/// `TOKEN=CODE:ISSUER` or `TOKEN=native`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAsset {
+    pub token: String,
+    pub asset: String,
+}
+
+impl FromStr for TokenAsset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (token, asset) = value
+            .split_once('=')
+            .ok_or_else(|| format!("'{value}' must be TOKEN=CODE:ISSUER or TOKEN=native"))?;
+        let (token, asset) = (token.trim(), asset.trim());
+        if token.is_empty() || !is_valid_asset(asset) {
+            return Err(format!(
+                "'{value}' must be TOKEN=CODE:ISSUER or TOKEN=native"
+            ));
+        }
+        Ok(Self {
+            token: token.to_string(),
+            asset: asset.to_string(),
+        })
+    }
+}
+
+/// Why a payout to an account would fail, phrased as what to do about it.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TrustlineIssue {
+    #[error("beneficiary account does not exist on the Stellar network; it must be funded with XLM first")]
+    AccountMissing,
+    #[error("beneficiary must add {0} trustline")]
+    MissingTrustline(String),
+    #[error("beneficiary's {0} trustline is not authorized by the issuer")]
+    NotAuthorized(String),
+    #[error("beneficiary's {0} trustline limit is too low for this payout")]
+    LimitTooLow(String),
+}
+
+fn to_units(amount: &str) -> Option<Decimal> {
+    Some(Decimal::from_str(amount).ok()? * Decimal::from(10u64.pow(STELLAR_DECIMALS)))
+}
+
+/// What stops `account` from receiving `amount` base units of `asset`, or
+/// `None` when nothing does. `account` is `None` when it does not exist.
+pub fn trustline_issue(
+    account: Option<&HorizonAccount>,
+    asset: &str,
+    amount: Decimal,
+) -> Option<TrustlineIssue> {
+    let Some(account) = account else {
+        return Some(TrustlineIssue::AccountMissing);
+    };
+    let Some((code, issuer)) = asset.split_once(':') else {
+        // The native asset needs no trustline.
+        return None;
+    };
+
+    let Some(line) = account.balances.iter().find(|b| {
+        b.asset_type != "native"
+            && b.asset_code.as_deref() == Some(code)
+            && b.asset_issuer.as_deref() == Some(issuer)
+    }) else {
+        return Some(TrustlineIssue::MissingTrustline(code.to_string()));
+    };
+    if line.is_authorized == Some(false) {
+        return Some(TrustlineIssue::NotAuthorized(code.to_string()));
+    }
+
+    let headroom = line
+        .limit
+        .as_deref()
+        .and_then(to_units)
+        .zip(to_units(&line.balance))
+        .map(|(limit, balance)| limit - balance);
+    match headroom {
+        Some(headroom) if headroom < amount => Some(TrustlineIssue::LimitTooLow(code.to_string())),
+        _ => None,
+    }
+}
+
+/// Looks up beneficiary accounts on Horizon.
+pub struct TrustlineChecker {
+    horizon: HorizonClient,
+    assets: HashMap<String, String>,
+}
+
+impl TrustlineChecker {
+    /// `None` unless `HORIZON_URL` and `PAYOUT_ASSETS` are set.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let horizon_url = config.horizon_url.clone()?;
+        if config.payout_assets.is_empty() {
+            return None;
+        }
+        Some(Self {
+            horizon: HorizonClient::new(horizon_url),
+            assets: config
+                .payout_assets
+                .iter()
+                .map(|a| (a.token.clone(), a.asset.clone()))
+                .collect(),
+        })
+    }
+
+    /// Checks a payout of `amount` base units of the plan token `token` to
+    /// `destination`. Tokens without a classic asset are not checked.
+    pub async fn check(
+        &self,
+        destination: &str,
+        token: &str,
+        amount: Decimal,
+    ) -> Result<Option<TrustlineIssue>, HorizonError> {
+        let Some(asset) = self.assets.get(token) else {
+            return Ok(None);
+        };
+        let account = self.horizon.account(destination).await?;
+        Ok(trustline_issue(account.as_ref(), asset, amount))
+    }
+}
+
+/// What stops `beneficiary` from receiving a crypto payout of their share
+/// of a plan. Fiat beneficiaries, unchecked tokens and Horizon outages
+/// yield `None`; the payout batcher checks again before submitting.
+pub(crate) async fn beneficiary_issue(
+    state: &AppState,
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    token: &str,
+    plan_amount: Decimal,
+    beneficiary: &str,
+) -> Result<Option<TrustlineIssue>, sqlx::Error> {
+    let Some(checker) = TrustlineChecker::from_config(&state.config) else {
+        return Ok(None);
+    };
+    let Some(row) = sqlx::query_as::<_, (i32, String)>(
+        "SELECT allocation_bps, fiat_anchor_info FROM beneficiaries WHERE plan_id = $1 AND wallet_address = $2",
+    )
+    .bind(plan_id)
+    .bind(beneficiary)
+    .fetch_optional(conn)
+    .await?
+    else {
+        return Ok(None);
+    };
+    let (allocation_bps, fiat_anchor_info) = row;
+    let fiat_anchor_info = state
+        .field_cipher
+        .decrypt_column(SensitiveField::BeneficiaryAnchorInfo, &fiat_anchor_info)?;
+    if !fiat_anchor_info.trim().is_empty() {
+        return Ok(None);
+    }
+
+    match checker
+        .check(beneficiary, token, share(plan_amount, allocation_bps))
+        .await
+    {
+        Ok(issue) => Ok(issue),
+        Err(e) => {
+            warn!(plan_id = %plan_id, error = %e, "Failed to check beneficiary trustline");
+            Ok(None)
+        }
+    }
+}
+
+/// A beneficiary's share of `amount`, rounded down like the payout split.
+fn share(amount: Decimal, allocation_bps: i32) -> Decimal {
+    (amount * Decimal::from(allocation_bps) / Decimal::from(10_000)).floor()
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReadinessPlan {
+    owner_address: String,
+    token_address: String,
+    amount: Decimal,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReadinessBeneficiary {
+    wallet_address: String,
+    allocation_bps: i32,
+    fiat_anchor_info: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeneficiaryReadiness {
+    pub wallet_address: String,
+    pub payout_type: &'static str,
+    /// What the beneficiary must fix before a crypto payout can be sent.
+    pub issue: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PayoutReadiness {
+    pub plan_id: Uuid,
+    /// False when trustline checks are not configured for the plan token.
+    pub checked: bool,
+    pub ready: bool,
+    pub beneficiaries: Vec<BeneficiaryReadiness>,
+}
+
+fn error_response(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+// Handler: Payout Readiness
+pub async fn get_payout_readiness(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let loaded: Result<Option<(ReadinessPlan, Vec<ReadinessBeneficiary>)>, sqlx::Error> = async {
+        let Some(plan) = sqlx::query_as::<_, ReadinessPlan>(
+            "SELECT owner_address, token_address, amount FROM plans WHERE id = $1",
+        )
+        .bind(plan_id)
+        .fetch_optional(&state.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let beneficiaries = sqlx::query_as::<_, ReadinessBeneficiary>(
+            r#"
+            SELECT wallet_address, allocation_bps, fiat_anchor_info
+            FROM beneficiaries
+            WHERE plan_id = $1
+            ORDER BY wallet_address
+            "#,
+        )
+        .bind(plan_id)
+        .fetch_all(&state.db_pool)
+        .await?
+        .into_iter()
+        .map(|mut b| {
+            b.fiat_anchor_info = state
+                .field_cipher
+                .decrypt_column(SensitiveField::BeneficiaryAnchorInfo, &b.fiat_anchor_info)?;
+            Ok(b)
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+        Ok(Some((plan, beneficiaries)))
+    }
+    .await;
+
+    let (plan, beneficiaries) = match loaded {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "Plan not found"),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan for payout readiness");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed");
+        }
+    };
+    if plan.owner_address != caller && !beneficiaries.iter().any(|b| b.wallet_address == caller) {
+        return error_response(StatusCode::NOT_FOUND, "Plan not found");
+    }
+
+    let checker = TrustlineChecker::from_config(&state.config)
+        .filter(|checker| checker.assets.contains_key(&plan.token_address));
+    let mut report = Vec::with_capacity(beneficiaries.len());
+    for b in beneficiaries {
+        if !b.fiat_anchor_info.trim().is_empty() {
+            report.push(BeneficiaryReadiness {
+                wallet_address: b.wallet_address,
+                payout_type: "fiat",
+                issue: None,
+            });
+            continue;
+        }
+        let issue = match &checker {
+            Some(checker) => {
+                match checker
+                    .check(
+                        &b.wallet_address,
+                        &plan.token_address,
+                        share(plan.amount, b.allocation_bps),
+                    )
+                    .await
+                {
+                    Ok(issue) => issue.map(|i| i.to_string()),
+                    Err(e) => {
+                        error!(plan_id = %plan_id, error = %e, "Failed to check beneficiary trustline");
+                        return error_response(
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            "Could not reach Horizon to check trustlines",
+                        );
+                    }
+                }
+            }
+            None => None,
+        };
+        report.push(BeneficiaryReadiness {
+            wallet_address: b.wallet_address,
+            payout_type: "crypto",
+            issue,
+        });
+    }
+
+    let readiness = PayoutReadiness {
+        plan_id,
+        checked: checker.is_some(),
+        ready: report.iter().all(|b| b.issue.is_none()),
+        beneficiaries: report,
+    };
+    (StatusCode::OK, Json(readiness)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deposits::{HorizonBalance, HorizonThresholds};
+
+    const ISSUER: &str = "GDUKMGUGDZQK6YHYA5Z6AY2G4XDSZPSZ3SW5UN3ARVMO6QSRDWP5YLEX";
+
+    fn account(balances: Vec<HorizonBalance>) -> HorizonAccount {
+        HorizonAccount {
+            thresholds: HorizonThresholds {
+                low_threshold: 0,
+                med_threshold: 0,
+                high_threshold: 0,
+            },
+            signers: Vec::new(),
+            balances,
+        }
+    }
+
+    fn usdc_line(balance: &str, limit: &str, authorized: bool) -> HorizonBalance {
+        HorizonBalance {
+            asset_type: "credit_alphanum4".to_string(),
+            asset_code: Some("USDC".to_string()),
+            asset_issuer: Some(ISSUER.to_string()),
+            balance: balance.to_string(),
+            limit: Some(limit.to_string()),
+            is_authorized: Some(authorized),
+        }
+    }
+
+    #[test]
+    fn reports_what_blocks_a_payout() {
+        let usdc = format!("USDC:{ISSUER}");
+        let amount = Decimal::from(50_000_000); // 5 USDC
+
+        assert_eq!(
+            trustline_issue(None, "native", amount),
+            Some(TrustlineIssue::AccountMissing)
+        );
+        assert_eq!(
+            trustline_issue(Some(&account(vec![])), "native", amount),
+            None
+        );
+
+        let issue = trustline_issue(Some(&account(vec![])), &usdc, amount).unwrap();
+        assert_eq!(issue.to_string(), "beneficiary must add USDC trustline");
+
+        let unauthorized = account(vec![usdc_line("0.0000000", "100.0000000", false)]);
+        assert_eq!(
+            trustline_issue(Some(&unauthorized), &usdc, amount),
+            Some(TrustlineIssue::NotAuthorized("USDC".to_string()))
+        );
+
+        let nearly_full = account(vec![usdc_line("97.0000000", "100.0000000", true)]);
+        assert_eq!(
+            trustline_issue(Some(&nearly_full), &usdc, amount),
+            Some(TrustlineIssue::LimitTooLow("USDC".to_string()))
+        );
+
+        let ready = account(vec![usdc_line("10.0000000", "100.0000000", true)]);
+        assert_eq!(trustline_issue(Some(&ready), &usdc, amount), None);
+    }
+
+    #[test]
+    fn parses_token_asset_mappings() {
+        let mapping: TokenAsset = format!("CUSDC=USDC:{ISSUER}").parse().unwrap();
+        assert_eq!(mapping.token, "CUSDC");
+        assert_eq!(mapping.asset, format!("USDC:{ISSUER}"));
+
+        assert!("CXLM=native".parse::<TokenAsset>().is_ok());
+        assert!("CUSDC=USDC".parse::<TokenAsset>().is_err());
+        assert!("CUSDC".parse::<TokenAsset>().is_err());
+    }
+}
@@ -0,0 +1,519 @@
+//! Self-managed user profiles.
+//!
+//! Each wallet can keep a display name, phone number, country, preferred
+//! language and timezone. Updates are JSON merge patches: fields left out
+//! are kept and `null` clears a field, with language and timezone falling
+//! back to `en` and `UTC`. Notification emails are written in the
+//! preferred language and show times in the timezone. Every change is
+//! written to `audit_logs`.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgConnection, PgExecutor};
+use std::sync::Arc;
+use tracing::error;
+
+use crate::api::AppState;
+use crate::audit::record_audit;
+use crate::auth::UserContext;
+use crate::platform_settings::merge_patch;
+use crate::sms::is_e164;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+const MAX_DISPLAY_NAME_LEN: usize = 80;
+
+const PROFILE_COLUMNS: &str = "user_address, display_name, phone, country, preferred_language, \
+     timezone, created_at, updated_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UserProfile {
+    pub user_address: String,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub country: Option<String>,
+    pub preferred_language: String,
+    pub timezone: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UserProfile {
+    fn empty(user_address: String) -> Self {
+        let now = Utc::now();
+        Self {
+            user_address,
+            display_name: None,
+            phone: None,
+            country: None,
+            preferred_language: DEFAULT_LANGUAGE.to_string(),
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn fields(&self) -> ProfileFields {
+        ProfileFields {
+            display_name: self.display_name.clone(),
+            phone: self.phone.clone(),
+            country: self.country.clone(),
+            preferred_language: Some(self.preferred_language.clone()),
+            timezone: Some(self.timezone.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileResponse {
+    #[serde(flatten)]
+    pub profile: UserProfile,
+    pub kyc_status: String,
+}
+
+/// The editable part of a profile, as it looks after a patch is applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileFields {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub preferred_language: Option<String>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: &'static str,
+}
+
+fn normalize(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Normalizes a language tag such as `pt-br` to `pt-BR`. Only a two or
+/// three letter language with an optional two letter region is accepted.
+pub(crate) fn normalize_language(tag: &str) -> Option<String> {
+    let (language, region) = match tag.split_once(['-', '_']) {
+        Some((language, region)) => (language, Some(region)),
+        None => (tag, None),
+    };
+    if !(2..=3).contains(&language.len()) || !language.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    let language = language.to_ascii_lowercase();
+    match region {
+        None => Some(language),
+        Some(region) if region.len() == 2 && region.bytes().all(|b| b.is_ascii_alphabetic()) => {
+            Some(format!("{language}-{}", region.to_ascii_uppercase()))
+        }
+        Some(_) => None,
+    }
+}
+
+/// Trims and canonicalizes each field, or returns every field that is
+/// invalid. Country is an ISO 3166-1 alpha-2 code and timezone an IANA
+/// name such as `Africa/Lagos`.
+pub fn validate_profile(fields: ProfileFields) -> Result<ProfileFields, Vec<FieldError>> {
+    let mut errors = Vec::new();
+
+    let display_name = normalize(fields.display_name);
+    if display_name
+        .as_ref()
+        .is_some_and(|name| name.chars().count() > MAX_DISPLAY_NAME_LEN)
+    {
+        errors.push(FieldError {
+            field: "display_name",
+            message: "Display name must be at most 80 characters",
+        });
+    }
+
+    let phone = normalize(fields.phone);
+    if phone.as_deref().is_some_and(|p| !is_e164(p)) {
+        errors.push(FieldError {
+            field: "phone",
+            message: "Phone number must be in E.164 format (e.g. +2348012345678)",
+        });
+    }
+
+    let country = normalize(fields.country).map(|c| c.to_ascii_uppercase());
+    if country
+        .as_deref()
+        .is_some_and(|c| c.len() != 2 || !c.bytes().all(|b| b.is_ascii_uppercase()))
+    {
+        errors.push(FieldError {
+            field: "country",
+            message: "Country must be a two-letter ISO 3166-1 code",
+        });
+    }
+
+    let preferred_language = match normalize(fields.preferred_language) {
+        None => Some(DEFAULT_LANGUAGE.to_string()),
+        Some(tag) => {
+            let normalized = normalize_language(&tag);
+            if normalized.is_none() {
+                errors.push(FieldError {
+                    field: "preferred_language",
+                    message: "Preferred language must be a language tag such as en or pt-BR",
+                });
+            }
+            normalized
+        }
+    };
+
+    let timezone = match normalize(fields.timezone) {
+        None => Some(DEFAULT_TIMEZONE.to_string()),
+        Some(name) => {
+            let parsed = name.parse::<Tz>().ok().map(|tz| tz.name().to_string());
+            if parsed.is_none() {
+                errors.push(FieldError {
+                    field: "timezone",
+                    message: "Timezone must be an IANA name such as Africa/Lagos",
+                });
+            }
+            parsed
+        }
+    };
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(ProfileFields {
+        display_name,
+        phone,
+        country,
+        preferred_language,
+        timezone,
+    })
+}
+
+/// Describes what changed between two profiles for the audit log. Phone
+/// numbers are not copied into the log, only the fact that one changed.
+fn profile_changes(
+    before: &ProfileFields,
+    after: &ProfileFields,
+) -> serde_json::Map<String, Value> {
+    let mut changes = serde_json::Map::new();
+    let fields = [
+        ("display_name", &before.display_name, &after.display_name),
+        ("phone", &before.phone, &after.phone),
+        ("country", &before.country, &after.country),
+        (
+            "preferred_language",
+            &before.preferred_language,
+            &after.preferred_language,
+        ),
+        ("timezone", &before.timezone, &after.timezone),
+    ];
+    for (name, from, to) in fields {
+        if from == to {
+            continue;
+        }
+        let change = if name == "phone" {
+            serde_json::json!({ "redacted": true })
+        } else {
+            serde_json::json!({ "from": from, "to": to })
+        };
+        changes.insert(name.to_string(), change);
+    }
+    changes
+}
+
+pub async fn load_profile<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_address: &str,
+) -> Result<Option<UserProfile>, sqlx::Error> {
+    sqlx::query_as::<_, UserProfile>(&format!(
+        "SELECT {PROFILE_COLUMNS} FROM user_profiles WHERE user_address = $1"
+    ))
+    .bind(user_address)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Preferred language of a wallet, defaulting to English.
+pub async fn language_for<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_address: &str,
+) -> Result<String, sqlx::Error> {
+    let language: Option<String> =
+        sqlx::query_scalar("SELECT preferred_language FROM user_profiles WHERE user_address = $1")
+            .bind(user_address)
+            .fetch_optional(executor)
+            .await?;
+    Ok(language.unwrap_or_else(|| DEFAULT_LANGUAGE.to_string()))
+}
+
+/// Timezone a wallet's notifications are rendered in, defaulting to UTC.
+pub async fn timezone_for<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_address: &str,
+) -> Result<Tz, sqlx::Error> {
+    let timezone: Option<String> =
+        sqlx::query_scalar("SELECT timezone FROM user_profiles WHERE user_address = $1")
+            .bind(user_address)
+            .fetch_optional(executor)
+            .await?;
+    Ok(timezone.and_then(|tz| tz.parse().ok()).unwrap_or(Tz::UTC))
+}
+
+async fn kyc_status(conn: &mut PgConnection, user_address: &str) -> Result<String, sqlx::Error> {
+    let status: Option<String> =
+        sqlx::query_scalar("SELECT kyc_status::text FROM users WHERE wallet_address = $1")
+            .bind(user_address)
+            .fetch_optional(conn)
+            .await?;
+    Ok(status.unwrap_or_else(|| "pending".to_string()))
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+fn database_error() -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Database query failed" })),
+    )
+        .into_response()
+}
+
+// Handler: Get My Profile
+pub async fn get_profile(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<ProfileResponse, sqlx::Error> = async {
+        let mut conn = state.db_pool.acquire().await?;
+        let profile = load_profile(&mut *conn, &address)
+            .await?
+            .unwrap_or_else(|| UserProfile::empty(address.clone()));
+        let kyc_status = kyc_status(&mut conn, &address).await?;
+        Ok(ProfileResponse {
+            profile,
+            kyc_status,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to load user profile");
+            database_error()
+        }
+    }
+}
+
+enum UpdateOutcome {
+    Updated(ProfileResponse),
+    Invalid(Vec<FieldError>),
+}
+
+// Handler: Update My Profile
+pub async fn update_profile(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(patch): Json<Value>,
+) -> impl IntoResponse {
+    let address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if !patch.is_object() {
+        return bad_request("Profile changes must be a JSON object");
+    }
+
+    let result: Result<Result<UpdateOutcome, String>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let current = sqlx::query_as::<_, UserProfile>(&format!(
+            "SELECT {PROFILE_COLUMNS} FROM user_profiles WHERE user_address = $1 FOR UPDATE"
+        ))
+        .bind(&address)
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or_else(|| UserProfile::empty(address.clone()));
+
+        let before = current.fields();
+        let merged = merge_patch(
+            &serde_json::to_value(&before).unwrap_or(Value::Null),
+            &patch,
+        );
+        let requested = match serde_json::from_value::<ProfileFields>(merged) {
+            Ok(fields) => fields,
+            Err(e) => return Ok(Err(e.to_string())),
+        };
+        let after = match validate_profile(requested) {
+            Ok(fields) => fields,
+            Err(errors) => return Ok(Ok(UpdateOutcome::Invalid(errors))),
+        };
+
+        let changes = profile_changes(&before, &after);
+        let profile = if changes.is_empty() {
+            current
+        } else {
+            let profile = sqlx::query_as::<_, UserProfile>(&format!(
+                r#"
+                INSERT INTO user_profiles
+                    (user_address, display_name, phone, country, preferred_language, timezone)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (user_address)
+                DO UPDATE SET display_name = EXCLUDED.display_name,
+                              phone = EXCLUDED.phone,
+                              country = EXCLUDED.country,
+                              preferred_language = EXCLUDED.preferred_language,
+                              timezone = EXCLUDED.timezone,
+                              updated_at = NOW()
+                RETURNING {PROFILE_COLUMNS}
+                "#
+            ))
+            .bind(&address)
+            .bind(&after.display_name)
+            .bind(&after.phone)
+            .bind(&after.country)
+            .bind(
+                after
+                    .preferred_language
+                    .as_deref()
+                    .unwrap_or(DEFAULT_LANGUAGE),
+            )
+            .bind(after.timezone.as_deref().unwrap_or(DEFAULT_TIMEZONE))
+            .fetch_one(&mut *tx)
+            .await?;
+            record_audit(
+                &mut *tx,
+                &address,
+                "profile.update",
+                &address,
+                serde_json::json!({ "changes": changes }),
+            )
+            .await?;
+            profile
+        };
+        let kyc_status = kyc_status(&mut tx, &address).await?;
+        tx.commit().await?;
+        Ok(Ok(UpdateOutcome::Updated(ProfileResponse {
+            profile,
+            kyc_status,
+        })))
+    }
+    .await;
+
+    match result {
+        Ok(Ok(UpdateOutcome::Updated(response))) => {
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(Ok(UpdateOutcome::Invalid(errors))) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Invalid profile", "errors": errors })),
+        )
+            .into_response(),
+        Ok(Err(message)) => bad_request(&message),
+        Err(e) => {
+            error!(error = %e, "Failed to update user profile");
+            database_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> ProfileFields {
+        let value: serde_json::Map<String, Value> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect();
+        serde_json::from_value(Value::Object(value)).unwrap()
+    }
+
+    #[test]
+    fn valid_profile_is_normalized() {
+        let profile = validate_profile(fields(&[
+            ("display_name", "  Ada Obi "),
+            ("phone", "+2348012345678"),
+            ("country", "ng"),
+            ("preferred_language", "pt_br"),
+            ("timezone", "Africa/Lagos"),
+        ]))
+        .unwrap();
+        assert_eq!(profile.display_name.as_deref(), Some("Ada Obi"));
+        assert_eq!(profile.country.as_deref(), Some("NG"));
+        assert_eq!(profile.preferred_language.as_deref(), Some("pt-BR"));
+        assert_eq!(profile.timezone.as_deref(), Some("Africa/Lagos"));
+    }
+
+    #[test]
+    fn cleared_language_and_timezone_fall_back_to_defaults() {
+        let profile = validate_profile(fields(&[("display_name", " ")])).unwrap();
+        assert_eq!(profile.display_name, None);
+        assert_eq!(profile.preferred_language.as_deref(), Some("en"));
+        assert_eq!(profile.timezone.as_deref(), Some("UTC"));
+    }
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let errors = validate_profile(fields(&[
+            ("phone", "0801 234 5678"),
+            ("country", "Nigeria"),
+            ("preferred_language", "english"),
+            ("timezone", "Lagos"),
+        ]))
+        .unwrap_err();
+        let invalid: Vec<&str> = errors.iter().map(|e| e.field).collect();
+        assert_eq!(
+            invalid,
+            ["phone", "country", "preferred_language", "timezone"]
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(serde_json::from_value::<ProfileFields>(
+            serde_json::json!({ "kyc_status": "approved" })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn audit_changes_redact_phone() {
+        let before = ProfileFields {
+            phone: Some("+2348012345678".to_string()),
+            timezone: Some("UTC".to_string()),
+            ..Default::default()
+        };
+        let after = ProfileFields {
+            phone: Some("+2348098765432".to_string()),
+            timezone: Some("Africa/Lagos".to_string()),
+            ..Default::default()
+        };
+        let changes = profile_changes(&before, &after);
+        assert_eq!(changes["phone"], serde_json::json!({ "redacted": true }));
+        assert_eq!(
+            changes["timezone"],
+            serde_json::json!({ "from": "UTC", "to": "Africa/Lagos" })
+        );
+        assert!(!changes.contains_key("country"));
+    }
+}
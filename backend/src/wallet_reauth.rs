@@ -0,0 +1,477 @@
+//! Wallet signature re-authentication for sensitive actions.
+//!
+//! Wallets that opt in must confirm claims, plan deactivation and turning
+//! the feature off by signing a one-time challenge with their Stellar key.
+//! Email changes always need one.
+//! The challenge spells out the action, plan, amount and a nonce so the
+//! wallet shows the user exactly what they are approving.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::{compute_projected_accrued_yield, AppState, PlanRow};
+use crate::audit::record_audit;
+use crate::auth::{verify_wallet_signature, UserContext};
+use crate::plan_owners;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReauthAction {
+    Claim,
+    DeactivatePlan,
+    DisableReauth,
+    ChangeEmail,
+    /// Refreshes a SEP-10 session's `auth_level`.
+    StepUp,
+}
+
+impl ReauthAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Claim => "claim",
+            Self::DeactivatePlan => "deactivate_plan",
+            Self::DisableReauth => "disable_reauth",
+            Self::ChangeEmail => "change_email",
+            Self::StepUp => "step_up",
+        }
+    }
+
+    fn requires_plan(self) -> bool {
+        !matches!(self, Self::DisableReauth | Self::ChangeEmail | Self::StepUp)
+    }
+}
+
+/// A signed challenge supplied alongside a sensitive request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletConfirmation {
+    pub challenge_id: Uuid,
+    /// Hex-encoded ed25519 signature over the challenge message.
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequest {
+    pub action: ReauthAction,
+    pub plan_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeResponse {
+    pub challenge_id: Uuid,
+    pub action: ReauthAction,
+    pub plan_id: Option<Uuid>,
+    pub amount: Option<Decimal>,
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReauthSettingsRequest {
+    pub enabled: bool,
+    pub confirmation: Option<WalletConfirmation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReauthSettings {
+    pub wallet_address: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum ReauthError {
+    #[error("Wallet confirmation required for this action")]
+    ConfirmationRequired,
+    #[error("Challenge not found, expired or already used")]
+    InvalidChallenge,
+    #[error("Challenge does not match this action")]
+    ChallengeMismatch,
+    #[error("Invalid challenge signature")]
+    InvalidSignature,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl IntoResponse for ReauthError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::ConfirmationRequired => StatusCode::PRECONDITION_REQUIRED,
+            Self::InvalidChallenge | Self::ChallengeMismatch | Self::InvalidSignature => {
+                StatusCode::UNAUTHORIZED
+            }
+            Self::Database(ref e) => {
+                error!(error = %e, "Wallet re-auth database error");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": "Database query failed" })),
+                )
+                    .into_response();
+            }
+        };
+        (
+            status,
+            Json(serde_json::json!({ "error": self.to_string() })),
+        )
+            .into_response()
+    }
+}
+
+/// Builds the text the wallet signs. Every field the backend acts on is
+/// included so a signature cannot be replayed for a different action.
+pub fn challenge_message(
+    wallet_address: &str,
+    action: ReauthAction,
+    plan_id: Option<Uuid>,
+    amount: Option<Decimal>,
+    nonce: &str,
+    expires_at: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "InheritX wallet confirmation".to_string(),
+        format!("Action: {}", action.as_str()),
+    ];
+    if let Some(plan_id) = plan_id {
+        lines.push(format!("Plan: {plan_id}"));
+    }
+    if let Some(amount) = amount {
+        lines.push(format!("Amount: {}", amount.normalize()));
+    }
+    lines.push(format!("Wallet: {wallet_address}"));
+    lines.push(format!("Nonce: {nonce}"));
+    lines.push(format!("Expires: {}", expires_at.to_rfc3339()));
+    lines.join("\n")
+}
+
+pub async fn is_enabled(
+    conn: &mut PgConnection,
+    wallet_address: &str,
+) -> Result<bool, sqlx::Error> {
+    let enabled: Option<bool> =
+        sqlx::query_scalar("SELECT enabled FROM wallet_reauth_settings WHERE wallet_address = $1")
+            .bind(wallet_address)
+            .fetch_optional(conn)
+            .await?;
+    Ok(enabled.unwrap_or(false))
+}
+
+/// Checks and consumes `confirmation` for `action` on `plan_id`. Run it in
+/// the same transaction as the action so a failed action leaves the
+/// challenge unused.
+pub async fn verify_confirmation(
+    conn: &mut PgConnection,
+    wallet_address: &str,
+    action: ReauthAction,
+    plan_id: Option<Uuid>,
+    confirmation: &WalletConfirmation,
+) -> Result<(), ReauthError> {
+    let challenge: Option<(String, String, Option<Uuid>)> = sqlx::query_as(
+        r#"
+        SELECT action, message, plan_id
+        FROM wallet_challenges
+        WHERE id = $1
+          AND wallet_address = $2
+          AND consumed_at IS NULL
+          AND expires_at > NOW()
+        FOR UPDATE
+        "#,
+    )
+    .bind(confirmation.challenge_id)
+    .bind(wallet_address)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some((challenge_action, message, challenge_plan_id)) = challenge else {
+        return Err(ReauthError::InvalidChallenge);
+    };
+    if challenge_action != action.as_str() || challenge_plan_id != plan_id {
+        return Err(ReauthError::ChallengeMismatch);
+    }
+    if !verify_wallet_signature(wallet_address, message.as_bytes(), &confirmation.signature) {
+        return Err(ReauthError::InvalidSignature);
+    }
+
+    sqlx::query("UPDATE wallet_challenges SET consumed_at = NOW() WHERE id = $1")
+        .bind(confirmation.challenge_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Requires a valid confirmation when the wallet has re-auth enabled.
+pub async fn enforce(
+    conn: &mut PgConnection,
+    wallet_address: &str,
+    action: ReauthAction,
+    plan_id: Option<Uuid>,
+    confirmation: Option<&WalletConfirmation>,
+) -> Result<(), ReauthError> {
+    if !is_enabled(&mut *conn, wallet_address).await? {
+        return Ok(());
+    }
+    let confirmation = confirmation.ok_or(ReauthError::ConfirmationRequired)?;
+    verify_confirmation(conn, wallet_address, action, plan_id, confirmation).await
+}
+
+// Handler: Create Re-auth Challenge
+pub async fn create_challenge(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<ChallengeRequest>,
+) -> impl IntoResponse {
+    let wallet_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    let plan_id = match (payload.action.requires_plan(), payload.plan_id) {
+        (true, Some(plan_id)) => Some(plan_id),
+        (true, None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "plan_id is required for this action" })),
+            )
+                .into_response();
+        }
+        (false, _) => None,
+    };
+
+    let amount = match plan_id {
+        Some(plan_id) => {
+            let plan = match sqlx::query_as::<_, PlanRow>(
+                r#"
+                SELECT id, owner_address, token_address, amount, grace_period, grace_period_seconds,
+                       earn_yield, last_ping, is_active, status, yield_rate_bps, accrued_yield, created_at
+                FROM plans
+                WHERE id = $1 AND is_active = true
+                "#,
+            )
+            .bind(plan_id)
+            .fetch_optional(&state.db_pool)
+            .await
+            {
+                Ok(plan) => plan,
+                Err(e) => return ReauthError::from(e).into_response(),
+            };
+            let is_beneficiary = match sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM beneficiaries WHERE plan_id = $1 AND wallet_address = $2)",
+            )
+            .bind(plan_id)
+            .bind(&wallet_address)
+            .fetch_one(&state.db_pool)
+            .await
+            {
+                Ok(exists) => exists,
+                Err(e) => return ReauthError::from(e).into_response(),
+            };
+
+            let is_co_owner =
+                match plan_owners::is_co_owner(&state.db_pool, plan_id, &wallet_address).await {
+                    Ok(co_owner) => co_owner,
+                    Err(e) => return ReauthError::from(e).into_response(),
+                };
+
+            let allowed = plan.as_ref().is_some_and(|p| match payload.action {
+                ReauthAction::Claim => p.owner_address == wallet_address || is_beneficiary,
+                _ => p.owner_address == wallet_address || is_co_owner,
+            });
+            let Some(plan) = plan.filter(|_| allowed) else {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({ "error": "Active plan not found" })),
+                )
+                    .into_response();
+            };
+            let amount = match payload.action {
+                ReauthAction::Claim => {
+                    let accrued =
+                        compute_projected_accrued_yield(&plan).unwrap_or(plan.accrued_yield);
+                    plan.amount + accrued
+                }
+                _ => plan.amount,
+            };
+            Some(amount)
+        }
+        None => None,
+    };
+
+    let mut nonce_bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = hex::encode(nonce_bytes);
+    let expires_at = Utc::now() + state.system_settings.get().await.reauth_challenge_ttl();
+    let message = challenge_message(
+        &wallet_address,
+        payload.action,
+        plan_id,
+        amount,
+        &nonce,
+        expires_at,
+    );
+
+    let challenge_id = match sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO wallet_challenges (wallet_address, action, plan_id, amount, nonce, message, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id
+        "#,
+    )
+    .bind(&wallet_address)
+    .bind(payload.action.as_str())
+    .bind(plan_id)
+    .bind(amount)
+    .bind(&nonce)
+    .bind(&message)
+    .bind(expires_at)
+    .fetch_one(&state.db_pool)
+    .await
+    {
+        Ok(id) => id,
+        Err(e) => return ReauthError::from(e).into_response(),
+    };
+
+    (
+        StatusCode::CREATED,
+        Json(ChallengeResponse {
+            challenge_id,
+            action: payload.action,
+            plan_id,
+            amount,
+            message,
+            expires_at,
+        }),
+    )
+        .into_response()
+}
+
+// Handler: Update Re-auth Settings
+pub async fn update_reauth_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Json(payload): Json<ReauthSettingsRequest>,
+) -> impl IntoResponse {
+    let wallet_address = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<(), ReauthError> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if !payload.enabled {
+            enforce(
+                &mut tx,
+                &wallet_address,
+                ReauthAction::DisableReauth,
+                None,
+                payload.confirmation.as_ref(),
+            )
+            .await?;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO wallet_reauth_settings (wallet_address, enabled)
+            VALUES ($1, $2)
+            ON CONFLICT (wallet_address)
+            DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = NOW()
+            "#,
+        )
+        .bind(&wallet_address)
+        .bind(payload.enabled)
+        .execute(&mut *tx)
+        .await?;
+        record_audit(
+            &mut *tx,
+            &wallet_address,
+            "wallet_reauth.update",
+            &wallet_address,
+            serde_json::json!({ "enabled": payload.enabled }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            info!(wallet_address = %wallet_address, enabled = payload.enabled, "Wallet re-auth setting updated");
+            (
+                StatusCode::OK,
+                Json(ReauthSettings {
+                    wallet_address,
+                    enabled: payload.enabled,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn message_binds_action_plan_and_amount() {
+        let plan_id = Uuid::nil();
+        let expires_at = DateTime::parse_from_rfc3339("2026-07-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let message = challenge_message(
+            "GABC",
+            ReauthAction::Claim,
+            Some(plan_id),
+            Some(Decimal::new(150000, 2)),
+            "abcd",
+            expires_at,
+        );
+        assert_eq!(
+            message,
+            "InheritX wallet confirmation\n\
+             Action: claim\n\
+             Plan: 00000000-0000-0000-0000-000000000000\n\
+             Amount: 1500\n\
+             Wallet: GABC\n\
+             Nonce: abcd\n\
+             Expires: 2026-07-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn signed_message_verifies_against_wallet_address() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let address =
+            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
+        let message = challenge_message(
+            &address,
+            ReauthAction::DisableReauth,
+            None,
+            None,
+            "00",
+            Utc::now(),
+        );
+        let signature = hex::encode(key.sign(message.as_bytes()).to_bytes());
+
+        assert!(verify_wallet_signature(
+            &address,
+            message.as_bytes(),
+            &signature
+        ));
+        assert!(!verify_wallet_signature(
+            &address,
+            message.replace("disable_reauth", "claim").as_bytes(),
+            &signature
+        ));
+    }
+}
@@ -0,0 +1,953 @@
+//! Witness attestations of the event that makes a plan claimable.
+//!
+//! An owner registers witnesses for a plan, such as a family lawyer or a
+//! doctor, by the Stellar key they sign with, and how many of them must
+//! attest. A witness signs [`attestation_message`] for the owner's death or
+//! incapacity and posts it to `POST /api/plans/:id/attestations`; the
+//! signature is their only credential. Once `threshold` registered witnesses
+//! have attested, the plan is marked `CLAIMABLE` without waiting for its
+//! inactivity deadline, and [`WitnessAnchorService`] anchors a hash over the
+//! attestations with the contract's `set_attestation_hash(owner, hash)`.
+//! Claims are accepted from then on ([`claimable_at`]) unless an owner
+//! pings afterwards. Other claim conditions, such as a current
+//! proof-of-life check-in or a freeze, still apply.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::{invalidate_plan_cache, AppState, PlanRow};
+use crate::audit::{record_audit, SYSTEM_ACTOR};
+use crate::auth::{verify_wallet_signature, UserContext};
+use crate::chain::{ContractInvocation, TxService};
+use crate::emergency_contacts::ContactAlert;
+use crate::jobs::JobRegistry;
+use crate::telemetry;
+
+pub const MAX_WITNESSES: usize = 10;
+const MAX_NAME_LEN: usize = 100;
+const CLAIMABLE_STATUS: &str = "CLAIMABLE";
+const DEFAULT_INTERVAL_SECS: u64 = 5 * 60;
+const DEFAULT_BATCH_SIZE: i64 = 100;
+const WITNESS_ANCHOR_LOCK_KEY: i64 = 841;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttestedEvent {
+    Death,
+    Incapacity,
+}
+
+impl AttestedEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Death => "death",
+            Self::Incapacity => "incapacity",
+        }
+    }
+}
+
+/// Message a witness signs to attest `event` for the plan.
+pub fn attestation_message(
+    plan_id: Uuid,
+    witness_address: &str,
+    event: AttestedEvent,
+    occurred_on: NaiveDate,
+) -> String {
+    format!(
+        "InheritX witness attestation\nplan: {plan_id}\nwitness: {witness_address}\nevent: {}\ndate: {occurred_on}",
+        event.as_str()
+    )
+}
+
+/// Hex SHA-256 of a signed attestation message.
+pub fn attestation_hash(message: &str) -> String {
+    hex::encode(Sha256::digest(message.as_bytes()))
+}
+
+/// Hex SHA-256 over a set of attestation hashes, independent of their order.
+pub fn attestation_set_hash(hashes: &[String]) -> String {
+    let mut sorted = hashes.to_vec();
+    sorted.sort();
+    hex::encode(Sha256::digest(sorted.join("\n").as_bytes()))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WitnessInput {
+    /// Stellar key (`G...`) the witness signs attestations with.
+    pub witness_address: String,
+    pub name: String,
+    /// How the witness knows the owner, e.g. `lawyer` or `doctor`.
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetWitnessesRequest {
+    /// Attestations needed. Ignored when `witnesses` is empty, which
+    /// removes the plan's witnesses.
+    pub threshold: u32,
+    pub witnesses: Vec<WitnessInput>,
+}
+
+impl SetWitnessesRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.witnesses.len() > MAX_WITNESSES {
+            return Err(format!("A plan can have at most {MAX_WITNESSES} witnesses"));
+        }
+        if self.witnesses.is_empty() {
+            return Ok(());
+        }
+        if self.threshold == 0 || self.threshold as usize > self.witnesses.len() {
+            return Err("threshold must be between 1 and the number of witnesses".to_string());
+        }
+        for (i, witness) in self.witnesses.iter().enumerate() {
+            if stellar_strkey::ed25519::PublicKey::from_string(&witness.witness_address).is_err() {
+                return Err(format!(
+                    "{} is not a Stellar public key",
+                    witness.witness_address
+                ));
+            }
+            if self.witnesses[..i]
+                .iter()
+                .any(|w| w.witness_address == witness.witness_address)
+            {
+                return Err(format!(
+                    "{} is listed more than once",
+                    witness.witness_address
+                ));
+            }
+            let name = witness.name.trim();
+            if name.is_empty() || name.len() > MAX_NAME_LEN {
+                return Err(format!(
+                    "Witness names must be 1 to {MAX_NAME_LEN} characters"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitAttestationRequest {
+    pub witness_address: String,
+    pub event: AttestedEvent,
+    pub occurred_on: NaiveDate,
+    /// Hex-encoded ed25519 signature over [`attestation_message`].
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Witness {
+    pub witness_address: String,
+    pub name: String,
+    pub role: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Attestation {
+    pub id: Uuid,
+    pub witness_address: String,
+    pub event: String,
+    pub occurred_on: NaiveDate,
+    pub attestation_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct PolicyRow {
+    threshold: i32,
+    quorum_reached_at: Option<DateTime<Utc>>,
+    attestation_hash: Option<String>,
+    anchored_hash: Option<String>,
+    anchor_tx_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WitnessPanel {
+    pub plan_id: Uuid,
+    /// `None` when the plan has no witnesses.
+    pub threshold: Option<i32>,
+    pub witnesses: Vec<Witness>,
+    pub attestations: Vec<Attestation>,
+    pub quorum_reached_at: Option<DateTime<Utc>>,
+    pub attestation_hash: Option<String>,
+    /// Whether `attestation_hash` has been anchored on-chain.
+    pub anchored: bool,
+    pub anchor_tx_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttestationReceipt {
+    pub attestation_id: Uuid,
+    pub attestation_hash: String,
+    pub attestations: usize,
+    pub threshold: i32,
+    /// Whether the plan is claimable on the witnesses' word.
+    pub quorum_reached: bool,
+}
+
+enum Outcome<T> {
+    Done(T),
+    Refused(StatusCode, &'static str),
+}
+
+fn refused(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+fn database_error() -> Response {
+    refused(StatusCode::INTERNAL_SERVER_ERROR, "Database query failed")
+}
+
+/// Locks the plan and checks `caller` owns or co-owns it.
+async fn lock_owned_plan(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+    caller: &str,
+) -> Result<Outcome<()>, sqlx::Error> {
+    let owned: Option<bool> = sqlx::query_scalar(
+        r#"
+        SELECT p.owner_address = $2
+               OR EXISTS (SELECT 1 FROM plan_co_owners o
+                          WHERE o.plan_id = p.id AND o.owner_address = $2
+                            AND o.status = 'accepted')
+        FROM plans p
+        WHERE p.id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(plan_id)
+    .bind(caller)
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(match owned {
+        None => Outcome::Refused(StatusCode::NOT_FOUND, "Plan not found"),
+        Some(false) => Outcome::Refused(
+            StatusCode::FORBIDDEN,
+            "Only the plan's owners can manage its witnesses",
+        ),
+        Some(true) => Outcome::Done(()),
+    })
+}
+
+async fn load_panel(conn: &mut PgConnection, plan_id: Uuid) -> Result<WitnessPanel, sqlx::Error> {
+    let policy = sqlx::query_as::<_, PolicyRow>(
+        r#"
+        SELECT threshold, quorum_reached_at, attestation_hash, anchored_hash, anchor_tx_hash
+        FROM plan_witness_policies
+        WHERE plan_id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let witnesses = sqlx::query_as::<_, Witness>(
+        r#"
+        SELECT witness_address, name, role, created_at
+        FROM plan_witnesses
+        WHERE plan_id = $1
+        ORDER BY created_at, witness_address
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let attestations = sqlx::query_as::<_, Attestation>(
+        r#"
+        SELECT id, witness_address, event, occurred_on, attestation_hash, created_at
+        FROM plan_attestations
+        WHERE plan_id = $1
+        ORDER BY created_at
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(match policy {
+        Some(policy) => WitnessPanel {
+            plan_id,
+            threshold: Some(policy.threshold),
+            witnesses,
+            attestations,
+            quorum_reached_at: policy.quorum_reached_at,
+            anchored: policy.attestation_hash.is_some()
+                && policy.anchored_hash == policy.attestation_hash,
+            attestation_hash: policy.attestation_hash,
+            anchor_tx_hash: policy.anchor_tx_hash,
+        },
+        None => WitnessPanel {
+            plan_id,
+            threshold: None,
+            witnesses,
+            attestations,
+            quorum_reached_at: None,
+            attestation_hash: None,
+            anchored: false,
+            anchor_tx_hash: None,
+        },
+    })
+}
+
+/// When `plan`, whose owners' inactivity deadline is `deadline`, became
+/// claimable: when its witnesses reached quorum if that came first and no
+/// owner has pinged since, otherwise the deadline.
+pub(crate) async fn claimable_at(
+    conn: &mut PgConnection,
+    plan: &PlanRow,
+    deadline: i64,
+) -> Result<i64, sqlx::Error> {
+    let quorum_reached_at: Option<Option<i64>> = sqlx::query_scalar(
+        r#"
+        SELECT EXTRACT(EPOCH FROM quorum_reached_at)::BIGINT
+        FROM plan_witness_policies
+        WHERE plan_id = $1
+        "#,
+    )
+    .bind(plan.id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let last_ping = deadline - plan.grace_period_seconds;
+    Ok(match quorum_reached_at.flatten() {
+        Some(reached) if reached >= last_ping => reached.min(deadline),
+        _ => deadline,
+    })
+}
+
+/// Marks the plan claimable if enough registered witnesses have attested
+/// and it was not already. Returns the attestation set hash when it does.
+async fn reach_quorum(
+    conn: &mut PgConnection,
+    plan_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    let policy: Option<(i32, bool)> = sqlx::query_as(
+        r#"
+        SELECT threshold, quorum_reached_at IS NOT NULL
+        FROM plan_witness_policies
+        WHERE plan_id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some((threshold, false)) = policy else {
+        return Ok(None);
+    };
+    let hashes: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT a.attestation_hash
+        FROM plan_attestations a
+        JOIN plan_witnesses w
+          ON w.plan_id = a.plan_id AND w.witness_address = a.witness_address
+        WHERE a.plan_id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    if hashes.len() < threshold as usize {
+        return Ok(None);
+    }
+
+    let hash = attestation_set_hash(&hashes);
+    sqlx::query(
+        r#"
+        UPDATE plan_witness_policies
+        SET quorum_reached_at = NOW(), attestation_hash = $2, updated_at = NOW()
+        WHERE plan_id = $1
+        "#,
+    )
+    .bind(plan_id)
+    .bind(&hash)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        r#"
+        UPDATE plans SET status = $2
+        WHERE id = $1 AND is_active = true AND status <> $2
+        "#,
+    )
+    .bind(plan_id)
+    .bind(CLAIMABLE_STATUS)
+    .execute(&mut *conn)
+    .await?;
+    record_audit(
+        &mut *conn,
+        SYSTEM_ACTOR,
+        "plan.witness_quorum",
+        &plan_id.to_string(),
+        serde_json::json!({ "attestations": hashes.len(), "attestation_hash": hash }),
+    )
+    .await?;
+    Ok(Some(hash))
+}
+
+/// After a quorum is reached: refresh cached listings and tell the owner's
+/// emergency contacts, as the inactivity watchdog does.
+async fn announce_claimable(state: &AppState, plan_id: Uuid) {
+    let parties: Result<(String, Vec<String>), sqlx::Error> = async {
+        let owner: String = sqlx::query_scalar("SELECT owner_address FROM plans WHERE id = $1")
+            .bind(plan_id)
+            .fetch_one(&state.db_pool)
+            .await?;
+        let beneficiaries =
+            sqlx::query_scalar("SELECT wallet_address FROM beneficiaries WHERE plan_id = $1")
+                .bind(plan_id)
+                .fetch_all(&state.db_pool)
+                .await?;
+        Ok((owner, beneficiaries))
+    }
+    .await;
+    let (owner, beneficiaries) = match parties {
+        Ok(parties) => parties,
+        Err(e) => {
+            warn!(plan_id = %plan_id, error = %e, "Failed to load parties of attested plan");
+            return;
+        }
+    };
+    info!(plan_id = %plan_id, "Plan marked claimable by witness attestations");
+    invalidate_plan_cache(&state.plan_cache, &owner, &beneficiaries).await;
+    if let Err(e) = state
+        .contacts
+        .alert(
+            &state.db_pool,
+            &owner,
+            ContactAlert::PlanClaimable { plan_id },
+        )
+        .await
+    {
+        warn!(plan_id = %plan_id, error = %e, "Failed to alert emergency contacts");
+    }
+}
+
+// Handler: Get Plan Witnesses
+pub async fn get_plan_witnesses(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+
+    let result: Result<Outcome<WitnessPanel>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if let Outcome::Refused(status, message) =
+            lock_owned_plan(&mut tx, plan_id, &caller).await?
+        {
+            return Ok(Outcome::Refused(status, message));
+        }
+        let panel = load_panel(&mut tx, plan_id).await?;
+        tx.commit().await?;
+        Ok(Outcome::Done(panel))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done(panel)) => (StatusCode::OK, Json(panel)).into_response(),
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to load plan witnesses");
+            database_error()
+        }
+    }
+}
+
+// Handler: Set Plan Witnesses
+pub async fn set_plan_witnesses(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<SetWitnessesRequest>,
+) -> impl IntoResponse {
+    let caller = match user.require_wallet_address() {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = user.require_recently_verified() {
+        return e.into_response();
+    }
+    if let Err(message) = payload.validate() {
+        return refused(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let result: Result<Outcome<(WitnessPanel, bool)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        if let Outcome::Refused(status, message) =
+            lock_owned_plan(&mut tx, plan_id, &caller).await?
+        {
+            return Ok(Outcome::Refused(status, message));
+        }
+        let reached: Option<bool> = sqlx::query_scalar(
+            "SELECT quorum_reached_at IS NOT NULL FROM plan_witness_policies WHERE plan_id = $1",
+        )
+        .bind(plan_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if reached == Some(true) {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "Witnesses have already attested; the plan is claimable",
+            ));
+        }
+
+        let addresses: Vec<String> = payload
+            .witnesses
+            .iter()
+            .map(|w| w.witness_address.clone())
+            .collect();
+        // Attestations from witnesses who are removed no longer count.
+        sqlx::query(
+            "DELETE FROM plan_attestations WHERE plan_id = $1 AND NOT (witness_address = ANY($2))",
+        )
+        .bind(plan_id)
+        .bind(&addresses)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "DELETE FROM plan_witnesses WHERE plan_id = $1 AND NOT (witness_address = ANY($2))",
+        )
+        .bind(plan_id)
+        .bind(&addresses)
+        .execute(&mut *tx)
+        .await?;
+        if payload.witnesses.is_empty() {
+            sqlx::query("DELETE FROM plan_witness_policies WHERE plan_id = $1")
+                .bind(plan_id)
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            sqlx::query(
+                r#"
+                INSERT INTO plan_witness_policies (plan_id, threshold)
+                VALUES ($1, $2)
+                ON CONFLICT (plan_id) DO UPDATE
+                SET threshold = EXCLUDED.threshold, updated_at = NOW()
+                "#,
+            )
+            .bind(plan_id)
+            .bind(payload.threshold as i32)
+            .execute(&mut *tx)
+            .await?;
+            for witness in &payload.witnesses {
+                sqlx::query(
+                    r#"
+                    INSERT INTO plan_witnesses (plan_id, witness_address, name, role)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (plan_id, witness_address) DO UPDATE
+                    SET name = EXCLUDED.name, role = EXCLUDED.role
+                    "#,
+                )
+                .bind(plan_id)
+                .bind(&witness.witness_address)
+                .bind(witness.name.trim())
+                .bind(witness.role.as_deref().map(str::trim))
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        record_audit(
+            &mut *tx,
+            &caller,
+            "plan.witnesses.update",
+            &plan_id.to_string(),
+            serde_json::json!({ "threshold": payload.threshold, "witnesses": addresses }),
+        )
+        .await?;
+
+        // A lower threshold can be met by attestations already received.
+        let reached = reach_quorum(&mut tx, plan_id).await?.is_some();
+        let panel = load_panel(&mut tx, plan_id).await?;
+        tx.commit().await?;
+        Ok(Outcome::Done((panel, reached)))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done((panel, reached))) => {
+            if reached {
+                announce_claimable(&state, plan_id).await;
+            }
+            (StatusCode::OK, Json(panel)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to update plan witnesses");
+            database_error()
+        }
+    }
+}
+
+// Handler: Submit Attestation (called by a witness, authenticated by signature)
+pub async fn submit_attestation(
+    State(state): State<Arc<AppState>>,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<SubmitAttestationRequest>,
+) -> impl IntoResponse {
+    if payload.occurred_on > Utc::now().date_naive() {
+        return refused(
+            StatusCode::BAD_REQUEST,
+            "occurred_on cannot be in the future",
+        );
+    }
+    let message = attestation_message(
+        plan_id,
+        &payload.witness_address,
+        payload.event,
+        payload.occurred_on,
+    );
+    if !verify_wallet_signature(
+        &payload.witness_address,
+        message.as_bytes(),
+        &payload.signature,
+    ) {
+        warn!(plan_id = %plan_id, "Rejected witness attestation with invalid signature");
+        return refused(StatusCode::UNAUTHORIZED, "Invalid attestation signature");
+    }
+    let hash = attestation_hash(&message);
+
+    let result: Result<Outcome<(AttestationReceipt, bool)>, sqlx::Error> = async {
+        let mut tx = state.db_pool.begin().await?;
+        let plan: Option<(bool, Option<i32>, bool)> = sqlx::query_as(
+            r#"
+            SELECT p.is_active, wp.threshold,
+                   EXISTS (SELECT 1 FROM plan_witnesses w
+                           WHERE w.plan_id = p.id AND w.witness_address = $2)
+            FROM plans p
+            LEFT JOIN plan_witness_policies wp ON wp.plan_id = p.id
+            WHERE p.id = $1
+            FOR UPDATE OF p
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&payload.witness_address)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (is_active, threshold) = match plan {
+            None => return Ok(Outcome::Refused(StatusCode::NOT_FOUND, "Plan not found")),
+            Some((is_active, Some(threshold), true)) => (is_active, threshold),
+            Some(_) => {
+                return Ok(Outcome::Refused(
+                    StatusCode::FORBIDDEN,
+                    "Not a registered witness of this plan",
+                ))
+            }
+        };
+        if !is_active {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "Plan has already been paid out",
+            ));
+        }
+
+        let attestation_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO plan_attestations
+                (plan_id, witness_address, event, occurred_on, signature, attestation_hash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (plan_id, witness_address) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(plan_id)
+        .bind(&payload.witness_address)
+        .bind(payload.event.as_str())
+        .bind(payload.occurred_on)
+        .bind(&payload.signature)
+        .bind(&hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(attestation_id) = attestation_id else {
+            return Ok(Outcome::Refused(
+                StatusCode::CONFLICT,
+                "This witness has already attested",
+            ));
+        };
+        record_audit(
+            &mut *tx,
+            &payload.witness_address,
+            "plan.attestation.submit",
+            &plan_id.to_string(),
+            serde_json::json!({
+                "event": payload.event.as_str(),
+                "occurred_on": payload.occurred_on,
+                "attestation_hash": hash,
+            }),
+        )
+        .await?;
+
+        let reached = reach_quorum(&mut tx, plan_id).await?.is_some();
+        let attestations: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM plan_attestations WHERE plan_id = $1")
+                .bind(plan_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        let quorum_reached: bool = sqlx::query_scalar(
+            "SELECT quorum_reached_at IS NOT NULL FROM plan_witness_policies WHERE plan_id = $1",
+        )
+        .bind(plan_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Outcome::Done((
+            AttestationReceipt {
+                attestation_id,
+                attestation_hash: hash.clone(),
+                attestations: attestations as usize,
+                threshold,
+                quorum_reached,
+            },
+            reached,
+        )))
+    }
+    .await;
+
+    match result {
+        Ok(Outcome::Done((receipt, reached))) => {
+            if reached {
+                announce_claimable(&state, plan_id).await;
+            }
+            (StatusCode::CREATED, Json(receipt)).into_response()
+        }
+        Ok(Outcome::Refused(status, message)) => refused(status, message),
+        Err(e) => {
+            error!(plan_id = %plan_id, error = %e, "Failed to record witness attestation");
+            database_error()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WitnessAnchorConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+}
+
+impl WitnessAnchorConfig {
+    pub fn from_env() -> Self {
+        let interval_secs = parse_env("WITNESS_ANCHOR_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+        let batch_size = parse_env("WITNESS_ANCHOR_BATCH_SIZE", DEFAULT_BATCH_SIZE);
+
+        Self {
+            interval: Duration::from_secs(interval_secs.max(1)),
+            batch_size: batch_size.max(1),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct UnanchoredQuorum {
+    plan_id: Uuid,
+    owner_address: String,
+    attestation_hash: String,
+}
+
+/// Anchors the attestation hash of plans whose witnesses reached quorum.
+pub struct WitnessAnchorService {
+    db: PgPool,
+    tx_service: Arc<dyn TxService>,
+    contract_id: String,
+    config: WitnessAnchorConfig,
+}
+
+impl WitnessAnchorService {
+    pub fn new(
+        db: PgPool,
+        tx_service: Arc<dyn TxService>,
+        contract_id: String,
+        config: WitnessAnchorConfig,
+    ) -> Self {
+        Self {
+            db,
+            tx_service,
+            contract_id,
+            config,
+        }
+    }
+
+    pub fn start(self: Arc<Self>, jobs: &Arc<JobRegistry>) {
+        jobs.schedule("witness_anchor", self.config.interval, move || {
+            let worker = self.clone();
+            async move {
+                worker.run_once().await.inspect(|&count| {
+                    if count > 0 {
+                        info!("Witness anchor worker anchored {count} attestation hash(es)");
+                    }
+                })
+            }
+        });
+    }
+
+    /// Anchors attestation hashes not yet on-chain, retrying failed ones.
+    /// Returns the number anchored.
+    pub async fn run_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let lock_acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(WITNESS_ANCHOR_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if !lock_acquired {
+            warn!("Witness anchor lock is held by another worker; skipping sweep");
+            tx.commit().await?;
+            return Ok(0);
+        }
+
+        let due = sqlx::query_as::<_, UnanchoredQuorum>(
+            r#"
+            SELECT wp.plan_id, p.owner_address, wp.attestation_hash
+            FROM plan_witness_policies wp
+            JOIN plans p ON p.id = wp.plan_id
+            WHERE wp.attestation_hash IS DISTINCT FROM wp.anchored_hash
+              AND wp.attestation_hash IS NOT NULL
+              AND p.is_active = true
+            ORDER BY wp.quorum_reached_at
+            LIMIT $1
+            "#,
+        )
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut anchored = 0;
+        for quorum in &due {
+            let invocation = ContractInvocation {
+                contract_id: self.contract_id.clone(),
+                function: "set_attestation_hash".to_string(),
+                args: vec![
+                    quorum.owner_address.clone(),
+                    quorum.attestation_hash.clone(),
+                ],
+                memo: telemetry::current_memo(),
+            };
+            match self.tx_service.invoke_contract(&invocation).await {
+                Ok(tx_hash) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE plan_witness_policies
+                        SET anchored_hash = $2, anchor_tx_hash = $3, last_error = NULL,
+                            updated_at = NOW()
+                        WHERE plan_id = $1
+                        "#,
+                    )
+                    .bind(quorum.plan_id)
+                    .bind(&quorum.attestation_hash)
+                    .bind(&tx_hash)
+                    .execute(&mut *tx)
+                    .await?;
+                    info!(plan_id = %quorum.plan_id, tx_hash = %tx_hash, "Anchored witness attestation hash");
+                    anchored += 1;
+                }
+                Err(e) => {
+                    warn!(plan_id = %quorum.plan_id, error = %e, "Failed to anchor witness attestation hash");
+                    sqlx::query(
+                        "UPDATE plan_witness_policies SET last_error = $2 WHERE plan_id = $1",
+                    )
+                    .bind(quorum.plan_id)
+                    .bind(e.to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(anchored)
+    }
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn witness(address: &str) -> WitnessInput {
+        WitnessInput {
+            witness_address: address.to_string(),
+            name: "Dr. Okafor".to_string(),
+            role: Some("doctor".to_string()),
+        }
+    }
+
+    #[test]
+    fn validates_witness_lists() {
+        let a = stellar_strkey::ed25519::PublicKey([1; 32]).to_string();
+        let b = stellar_strkey::ed25519::PublicKey([2; 32]).to_string();
+        let request = |threshold, witnesses| SetWitnessesRequest {
+            threshold,
+            witnesses,
+        };
+
+        assert!(request(2, vec![witness(&a), witness(&b)])
+            .validate()
+            .is_ok());
+        assert!(request(0, vec![]).validate().is_ok());
+        assert!(request(3, vec![witness(&a), witness(&b)])
+            .validate()
+            .is_err());
+        assert!(request(0, vec![witness(&a)]).validate().is_err());
+        assert!(request(1, vec![witness(&a), witness(&a)])
+            .validate()
+            .is_err());
+        assert!(request(1, vec![witness("GNOTAKEY")]).validate().is_err());
+    }
+
+    #[test]
+    fn attestation_signature_binds_plan_and_event() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[5; 32]);
+        let address =
+            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
+        let plan_id = Uuid::new_v4();
+        let day = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap();
+        let message = attestation_message(plan_id, &address, AttestedEvent::Death, day);
+        let signature = hex::encode(key.sign(message.as_bytes()).to_bytes());
+
+        assert!(verify_wallet_signature(
+            &address,
+            message.as_bytes(),
+            &signature
+        ));
+        let other = attestation_message(plan_id, &address, AttestedEvent::Incapacity, day);
+        assert!(!verify_wallet_signature(
+            &address,
+            other.as_bytes(),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn set_hash_ignores_order() {
+        let (a, b) = (attestation_hash("a"), attestation_hash("b"));
+        assert_eq!(
+            attestation_set_hash(&[a.clone(), b.clone()]),
+            attestation_set_hash(&[b.clone(), a.clone()])
+        );
+        assert_ne!(
+            attestation_set_hash(std::slice::from_ref(&a)),
+            attestation_set_hash(&[a, b])
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
 /// Configuration for yield calculations.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ApyConfig {
@@ -16,61 +18,81 @@ impl ApyConfig {
     }
 }
 
+/// Seconds in the Julian year yield is quoted over.
+pub const SECONDS_PER_YEAR: u64 = 31_557_600;
+/// Decimal places `plans.accrued_yield` is stored with.
+pub const YIELD_SCALE: u32 = 4;
+
 /// Projects the virtual yield generated by a locked plan over time.
 ///
 /// Uses simple interest formula:
 ///   accrued = principal * (rate_bps / 10000) * (elapsed_secs / seconds_per_year)
 ///
-/// The yield_rate_bps is in basis points (e.g., 500 = 5% APY).
-pub fn calculate_yield(amount: f64, yield_rate_bps: u32, elapsed_secs: u64) -> f64 {
+/// The yield_rate_bps is in basis points (e.g., 500 = 5% APY). The result is
+/// exact decimal arithmetic truncated to [`YIELD_SCALE`] places, or `None`
+/// if it would overflow.
+pub fn calculate_yield(amount: Decimal, yield_rate_bps: u32, elapsed_secs: u64) -> Option<Decimal> {
     if yield_rate_bps == 0 || elapsed_secs == 0 {
-        return 0.0;
+        return Some(Decimal::ZERO);
     }
 
-    let rate = yield_rate_bps as f64 / 10_000.0;
-    let seconds_per_year = 365.25 * 24.0 * 3600.0;
-    let time_years = elapsed_secs as f64 / seconds_per_year;
-
-    amount * rate * time_years
+    amount
+        .checked_mul(Decimal::from(yield_rate_bps))?
+        .checked_mul(Decimal::from(elapsed_secs))?
+        .checked_div(Decimal::from(10_000 * SECONDS_PER_YEAR))
+        .map(|y| y.round_dp_with_strategy(YIELD_SCALE, RoundingStrategy::ToZero))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn yield_of(amount: i64, bps: u32, secs: u64) -> Decimal {
+        calculate_yield(Decimal::from(amount), bps, secs).unwrap()
+    }
+
     #[test]
     fn test_zero_yield_rate() {
-        assert_eq!(calculate_yield(1000.0, 0, 86400), 0.0);
+        assert_eq!(yield_of(1000, 0, 86400), Decimal::ZERO);
     }
 
     #[test]
     fn test_zero_elapsed() {
-        assert_eq!(calculate_yield(1000.0, 500, 0), 0.0);
+        assert_eq!(yield_of(1000, 500, 0), Decimal::ZERO);
     }
 
     #[test]
     fn test_one_year_at_5_percent() {
-        // 5% APY = 500 bps, 1 year = 31,536,000 seconds (approx)
-        let one_year_secs = (365.25 * 24.0 * 3600.0) as u64;
-        let yield_amount = calculate_yield(1000.0, 500, one_year_secs);
-        // Should be ~50.0 (5% of 1000)
-        assert!((yield_amount - 50.0).abs() < 0.01);
+        // 5% APY = 500 bps over a full year is exactly 5% of principal
+        assert_eq!(yield_of(1000, 500, SECONDS_PER_YEAR), Decimal::from(50));
     }
 
     #[test]
     fn test_half_year() {
-        // 10% APY = 1000 bps, half year should yield ~5% of principal
-        let half_year_secs = (365.25 * 24.0 * 3600.0 / 2.0) as u64;
-        let yield_amount = calculate_yield(1000.0, 1000, half_year_secs);
-        // Should be ~50.0 (10% of 1000 for half year)
-        assert!((yield_amount - 50.0).abs() < 0.01);
+        // 10% APY = 1000 bps, half year yields 5% of principal
+        assert_eq!(
+            yield_of(1000, 1000, SECONDS_PER_YEAR / 2),
+            Decimal::from(50)
+        );
     }
 
     #[test]
     fn test_large_amount() {
-        let one_year_secs = (365.25 * 24.0 * 3600.0) as u64;
-        let yield_amount = calculate_yield(1_000_000.0, 200, one_year_secs);
         // 2% of 1,000,000 = 20,000
-        assert!((yield_amount - 20_000.0).abs() < 1.0);
+        assert_eq!(
+            yield_of(1_000_000, 200, SECONDS_PER_YEAR),
+            Decimal::from(20_000)
+        );
+    }
+
+    #[test]
+    fn test_truncates_to_stored_scale() {
+        // 1000 * 5% over one day is 0.13689253...
+        assert_eq!(yield_of(1000, 500, 86_400), Decimal::new(1368, 4));
+    }
+
+    #[test]
+    fn test_overflow_is_refused() {
+        assert_eq!(calculate_yield(Decimal::MAX, 500, SECONDS_PER_YEAR), None);
     }
 }
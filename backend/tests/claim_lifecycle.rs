@@ -0,0 +1,1406 @@
+//! End-to-end claim lifecycle against a real database: an owner passes KYC,
+//! creates a plan for an heir, goes quiet past the grace period, and the
+//! heir's claim is paid out by the claim executor. Plans nobody claims in
+//! time are escheated by the claim expiry worker instead, and an heir can
+//! let an estate executor file the claim for them. The keeper makes the
+//! matching contract calls within its fee budget.
+//!
+//! Runs against `DATABASE_URL`, or a Postgres container when it is unset:
+//!
+//! ```sh
+//! cargo test --test claim_lifecycle
+//! ```
+
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+    Router,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use hmac::{Hmac, Mac};
+use inheritx_backend::claim_expiry::{ClaimExpiryConfig, ClaimExpiryService};
+use inheritx_backend::claim_requests::{ClaimExecutorConfig, ClaimExecutorService};
+use inheritx_backend::keeper::{KeeperConfig, KeeperService};
+use inheritx_backend::{create_router, AppState, Config};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+mod factory;
+use factory::{AdminFactory, PlanFactory, UserFactory};
+
+const KYC_WEBHOOK_SECRET: &str = "e2e-webhook-secret";
+const GRACE_PERIOD_SECS: i64 = 3600;
+
+async fn test_state(pool: PgPool) -> Arc<AppState> {
+    test_state_with(pool, Config::for_tests()).await
+}
+
+async fn test_state_with(pool: PgPool, config: Config) -> Arc<AppState> {
+    let config = Config {
+        kyc_webhook_secret: Some(KYC_WEBHOOK_SECRET.to_string()),
+        ..config
+    };
+    let soroban_rpc = inheritx_backend::chain::rpc::SorobanRpcConfig {
+        url: factory::containers::soroban_rpc_url().await,
+    };
+
+    Arc::new(AppState {
+        anchor: Arc::new(inheritx_backend::stellar_anchor::AnchorRegistry::new()),
+        kyc_tx: tokio::sync::broadcast::channel(16).0,
+        db_pool: pool.clone(),
+        config: Arc::new(config.clone()),
+        apy_config: inheritx_backend::yield_calculator::ApyConfig::default(),
+        plan_cache: inheritx_backend::PlanCache::disabled(),
+        offramp: Arc::new(inheritx_backend::offramp::AnchorClient::new(
+            inheritx_backend::offramp::OfframpConfig::from_env(),
+        )),
+        contacts: Arc::new(inheritx_backend::emergency_contacts::ContactNotifier::new(
+            Arc::new(inheritx_backend::mailer::Mailer::new(
+                inheritx_backend::mailer::MailerConfig::default(),
+            )),
+            Arc::new(inheritx_backend::sms::SmsClient::new(
+                inheritx_backend::sms::SmsConfig::default(),
+            )),
+        )),
+        mailer: Arc::new(inheritx_backend::mailer::Mailer::new(
+            inheritx_backend::mailer::MailerConfig::default(),
+        )),
+        soroban_rpc: Arc::new(inheritx_backend::chain::rpc::SorobanRpcClient::new(
+            soroban_rpc,
+        )),
+        admin_access: Arc::new(inheritx_backend::admin_access::AdminAccessPolicy::default()),
+        field_cipher: Arc::new(inheritx_backend::field_crypto::FieldCipher::disabled()),
+        system_settings: Arc::new(inheritx_backend::system_settings::SystemSettingsCache::new(
+            pool.clone(),
+            Arc::new(config.clone()),
+            Duration::from_secs(30),
+        )),
+        feature_flags: Arc::new(inheritx_backend::feature_flags::FeatureFlagCache::new(
+            pool.clone(),
+            inheritx_backend::config::Environment::Test,
+            Duration::from_secs(30),
+        )),
+        jobs: Arc::new(inheritx_backend::jobs::JobRegistry::new(pool)),
+        status_page: Arc::new(inheritx_backend::status_page::StatusPage::new(
+            inheritx_backend::status_page::StatusPageConfig::default(),
+        )),
+    })
+}
+
+fn wallet(key: &SigningKey) -> String {
+    stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string()
+}
+
+fn signed(method: http::Method, uri: &str, key: &SigningKey, body: String) -> Request<Body> {
+    signed_at(method, uri, key, body, chrono::Utc::now().timestamp())
+}
+
+/// A request signed over the body alone, which only authenticates at the
+/// standard level.
+fn signed_standard(
+    method: http::Method,
+    uri: &str,
+    key: &SigningKey,
+    body: String,
+) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(
+            "X-Public-Key",
+            format!("0x{}", hex::encode(key.verifying_key().to_bytes())),
+        )
+        .header(
+            "X-Signature",
+            hex::encode(key.sign(body.as_bytes()).to_bytes()),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// A request signed with `X-Signature-Timestamp` set to `timestamp` and a
+/// fresh nonce.
+fn signed_at(
+    method: http::Method,
+    uri: &str,
+    key: &SigningKey,
+    body: String,
+    timestamp: i64,
+) -> Request<Body> {
+    let nonce = uuid::Uuid::new_v4().to_string();
+    let message = format!("{timestamp}\n{nonce}\n{body}");
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(
+            "X-Public-Key",
+            format!("0x{}", hex::encode(key.verifying_key().to_bytes())),
+        )
+        .header("X-Signature-Timestamp", timestamp.to_string())
+        .header("X-Signature-Nonce", nonce)
+        .header(
+            "X-Signature",
+            hex::encode(key.sign(message.as_bytes()).to_bytes()),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_claim_lifecycle_pays_out_beneficiary() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let owner_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let owner = wallet(&owner_key);
+    let heir = wallet(&heir_key);
+
+    // Owner signs up and the KYC provider approves them.
+    UserFactory::new()
+        .wallet_address(&owner)
+        .kyc_status("submitted")
+        .insert(&pool)
+        .await
+        .unwrap();
+    let body = json!({
+        "wallet_address": owner,
+        "status": "approved",
+        "event_type": "kyc.status_update",
+        "provider_reference": format!("e2e-{}", uuid::Uuid::new_v4()),
+    })
+    .to_string();
+    let mut mac = Hmac::<Sha256>::new_from_slice(KYC_WEBHOOK_SECRET.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    let (status, _) = send(
+        &app,
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("/api/kyc/webhook")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(
+                "X-KYC-Signature",
+                format!("sha256={}", hex::encode(mac.finalize().into_bytes())),
+            )
+            .body(Body::from(body))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let kyc_status: String =
+        sqlx::query_scalar("SELECT kyc_status::text FROM users WHERE wallet_address = $1")
+            .bind(&owner)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(kyc_status, "approved");
+
+    // Owner creates a plan leaving everything to the heir.
+    let body = json!({
+        "owner": owner,
+        "token": "USDC",
+        "amount": 1000.0,
+        "grace_period": GRACE_PERIOD_SECS,
+        "earn_yield": false,
+        "yield_rate_bps": 0,
+        "last_ping": chrono::Utc::now().timestamp(),
+        "is_active": true,
+        "beneficiaries": [{
+            "address": heir,
+            "name": "Heir",
+            "allocation_bps": 10000,
+            "fiat_anchor_info": ""
+        }]
+    })
+    .to_string();
+    let (status, plan) = send(
+        &app,
+        signed(http::Method::POST, "/api/plans", &owner_key, body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let plan_id = plan["id"].as_str().unwrap().to_string();
+    let claim_uri = format!("/api/plans/{plan_id}/claim");
+
+    // The heir cannot claim while the owner is still within the grace period.
+    let (status, _) = send(
+        &app,
+        signed(http::Method::POST, &claim_uri, &heir_key, "{}".to_string()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The plan matures: the owner's last check-in falls outside the grace period.
+    sqlx::query("UPDATE plans SET last_ping = $2 WHERE id = $1::uuid")
+        .bind(&plan_id)
+        .bind(chrono::Utc::now().timestamp() - GRACE_PERIOD_SECS - 60)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, claim) = send(
+        &app,
+        signed(http::Method::POST, &claim_uri, &heir_key, "{}".to_string()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(claim["status"], "pending");
+
+    // Skip the cooling-off window and let the executor pay the claim.
+    sqlx::query("UPDATE claim_requests SET execute_after = NOW() WHERE id = $1::uuid")
+        .bind(claim["id"].as_str().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let executor = ClaimExecutorService::new(
+        test_state(pool.clone()).await,
+        ClaimExecutorConfig {
+            interval: Duration::from_secs(1),
+            batch_size: 1_000,
+        },
+    );
+    assert!(executor.run_once().await.unwrap() >= 1);
+
+    let (status, claim) = send(
+        &app,
+        signed(http::Method::GET, &claim_uri, &heir_key, String::new()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(claim["status"], "executed");
+
+    let (is_active, plan_status): (bool, String) =
+        sqlx::query_as("SELECT is_active, status FROM plans WHERE id = $1::uuid")
+            .bind(&plan_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(!is_active);
+    assert_eq!(plan_status, "PAID_OUT");
+
+    let payouts: Vec<(String, rust_decimal::Decimal)> =
+        sqlx::query_as("SELECT beneficiary_address, amount FROM payouts WHERE plan_id = $1::uuid")
+            .bind(&plan_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(payouts.len(), 1);
+    assert_eq!(payouts[0].0, heir);
+    assert!(payouts[0].1 > rust_decimal::Decimal::ZERO);
+
+    let notified: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE user_address = $1 AND notification_type = 'claim_executed'",
+    )
+    .bind(&heir)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(notified, 1);
+}
+
+#[tokio::test]
+async fn test_unclaimed_plans_are_reminded_then_escheated() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let owner_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let owner = wallet(&owner_key);
+    let heir = wallet(&heir_key);
+    let fallback = factory::wallet_address();
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+    let window = chrono::Duration::days(365);
+
+    // One plan whose window closed yesterday, one closing in five days.
+    let expired = PlanFactory::new()
+        .owner(&owner)
+        .grace_period(grace)
+        .last_ping(chrono::Utc::now() - grace - window - chrono::Duration::days(1))
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let closing = PlanFactory::new()
+        .grace_period(grace)
+        .last_ping(chrono::Utc::now() - grace - window + chrono::Duration::days(5))
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+
+    let claim_uri = format!("/api/plans/{}/claim", expired.id());
+    let (status, _) = send(
+        &app,
+        signed(http::Method::POST, &claim_uri, &heir_key, "{}".to_string()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::GONE);
+
+    let fallback_uri = format!("/api/plans/{}/fallback-beneficiary", expired.id());
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::PUT,
+            &fallback_uri,
+            &heir_key,
+            json!({ "address": fallback }).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, body) = send(
+        &app,
+        signed(
+            http::Method::PUT,
+            &fallback_uri,
+            &owner_key,
+            json!({ "address": fallback }).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["fallback_address"], fallback.as_str());
+
+    let worker = ClaimExpiryService::new(
+        test_state(pool.clone()).await,
+        ClaimExpiryConfig {
+            interval: Duration::from_secs(1),
+            batch_size: 1_000,
+            reminder_days: vec![30, 7, 1],
+        },
+    );
+    let sweep = worker.run_once().await.unwrap();
+    assert!(sweep.escheated >= 1 && sweep.reminded >= 1);
+    worker.run_once().await.unwrap();
+
+    let (is_active, plan_status): (bool, String) =
+        sqlx::query_as("SELECT is_active, status FROM plans WHERE id = $1")
+            .bind(expired.id())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(!is_active);
+    assert_eq!(plan_status, "ESCHEATED");
+    let payouts: Vec<String> =
+        sqlx::query_scalar("SELECT beneficiary_address FROM payouts WHERE plan_id = $1")
+            .bind(expired.id())
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(payouts, vec![fallback.clone()]);
+    let audited: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM audit_logs WHERE action = 'plan.escheated' AND subject = $1",
+    )
+    .bind(expired.id().to_string())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(audited, 1);
+
+    // The closing plan is still claimable; its heir was reminded once.
+    let is_active: bool = sqlx::query_scalar("SELECT is_active FROM plans WHERE id = $1")
+        .bind(closing.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(is_active);
+    let notifications: Vec<String> = sqlx::query_scalar(
+        "SELECT notification_type FROM notifications WHERE user_address = $1 ORDER BY notification_type",
+    )
+    .bind(&heir)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(notifications, ["claim_expiring", "plan_escheated"]);
+}
+
+#[tokio::test]
+async fn test_executor_files_claim_under_delegation() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let executor_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let executor = wallet(&executor_key);
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+    let plan = PlanFactory::new()
+        .grace_period(grace)
+        .last_ping(chrono::Utc::now() - grace - chrono::Duration::minutes(1))
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let claim_uri = format!("/api/plans/{}/claim", plan.id());
+    let documents_uri = format!("/api/plans/{}/claim/documents", plan.id());
+    let delegations_uri = format!("/api/plans/{}/delegations", plan.id());
+    let on_behalf = json!({ "on_behalf_of": heir }).to_string();
+    let document = json!({
+        "document_type": "death_certificate",
+        "file_name": "certificate.pdf",
+        "content_type": "application/pdf",
+        "content": "JVBERi0xLjQK",
+        "on_behalf_of": heir,
+    })
+    .to_string();
+    let grant = |scopes: serde_json::Value| {
+        json!({
+            "executor_address": executor,
+            "scopes": scopes,
+            "expires_at": chrono::Utc::now() + chrono::Duration::days(30),
+        })
+        .to_string()
+    };
+
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::POST,
+            &claim_uri,
+            &executor_key,
+            on_behalf.clone(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Document access alone does not let the executor file the claim.
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::POST,
+            &delegations_uri,
+            &heir_key,
+            grant(json!(["upload_documents"])),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, uploaded) = send(
+        &app,
+        signed(
+            http::Method::POST,
+            &documents_uri,
+            &executor_key,
+            document.clone(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(uploaded["beneficiary_address"], heir.as_str());
+    assert_eq!(uploaded["uploaded_by"], executor.as_str());
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::POST,
+            &claim_uri,
+            &executor_key,
+            on_behalf.clone(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, delegation) = send(
+        &app,
+        signed(
+            http::Method::POST,
+            &delegations_uri,
+            &heir_key,
+            grant(json!(["file_claim", "upload_documents"])),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, claim) = send(
+        &app,
+        signed(http::Method::POST, &claim_uri, &executor_key, on_behalf),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(claim["requested_by"], heir.as_str());
+    assert_eq!(claim["filed_by"], executor.as_str());
+
+    let attributed: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM audit_logs
+        WHERE actor = $1 AND details->>'on_behalf_of' = $2
+          AND action IN ('claim.requested', 'claim_document.uploaded')
+        "#,
+    )
+    .bind(&executor)
+    .bind(&heir)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(attributed, 2);
+
+    // Once revoked, the executor can no longer act for the heir.
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::DELETE,
+            &format!("{delegations_uri}/{}", delegation["id"].as_str().unwrap()),
+            &heir_key,
+            String::new(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        signed(http::Method::POST, &documents_uri, &executor_key, document),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_claim_pays_out_in_preferred_asset() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let (token, preferred) = (factory::contract_address(), factory::contract_address());
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+    let plan = PlanFactory::new()
+        .token(&token)
+        .grace_period(grace)
+        .last_ping(chrono::Utc::now() - grace - chrono::Duration::minutes(1))
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::PUT,
+            "/api/users/me/payout-asset",
+            &heir_key,
+            json!({ "asset": preferred, "max_slippage_bps": 5_000 }).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = send(
+        &app,
+        signed(
+            http::Method::PUT,
+            "/api/users/me/payout-asset",
+            &heir_key,
+            json!({ "asset": preferred, "max_slippage_bps": 50 }).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["preference"]["asset"], preferred.as_str());
+
+    // The plan's token trades at $0.10 with 7 decimals, the preferred one
+    // at $1 with 6 decimals.
+    sqlx::query(
+        "INSERT INTO asset_price_history (token_address, price_usd, decimals) \
+         VALUES ($1, 0.1, 7), ($2, 1, 6)",
+    )
+    .bind(&token)
+    .bind(&preferred)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::POST,
+            &format!("/api/plans/{}/claim", plan.id()),
+            &heir_key,
+            "{}".to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    sqlx::query("UPDATE claim_requests SET execute_after = NOW() WHERE plan_id = $1")
+        .bind(plan.id())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let executor = ClaimExecutorService::new(
+        test_state(pool.clone()).await,
+        ClaimExecutorConfig {
+            interval: Duration::from_secs(1),
+            batch_size: 1_000,
+        },
+    );
+    executor.run_once().await.unwrap();
+
+    // 1,000 tokens at $0.10 are $100, paid as 100 of the preferred token.
+    let payout: (String, Option<String>, Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT amount::text, payout_asset, converted_amount::text, min_converted_amount::text \
+         FROM payouts WHERE plan_id = $1",
+    )
+    .bind(plan.id())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        payout,
+        (
+            "10000000000".to_string(),
+            Some(preferred.clone()),
+            Some("100000000".to_string()),
+            Some("99500000".to_string()),
+        )
+    );
+    let converted: serde_json::Value = sqlx::query_scalar(
+        "SELECT details FROM audit_logs WHERE action = 'payout.converted' AND subject = $1",
+    )
+    .bind(plan.id().to_string())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(converted["amount"], "10000000000");
+    assert_eq!(converted["converted_amount"], "100000000");
+}
+
+#[tokio::test]
+async fn test_claim_is_traced_by_request_id() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+    let plan = PlanFactory::new()
+        .grace_period(grace)
+        .last_ping(chrono::Utc::now() - grace - chrono::Duration::minutes(1))
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let request_id = format!("trace-{}", uuid::Uuid::new_v4());
+
+    let mut request = signed(
+        http::Method::POST,
+        &format!("/api/plans/{}/claim", plan.id()),
+        &heir_key,
+        "{}".to_string(),
+    );
+    request
+        .headers_mut()
+        .insert("X-Request-Id", request_id.parse().unwrap());
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    assert_eq!(response.headers()["x-request-id"], request_id.as_str());
+
+    // Without one, a request id is generated and returned.
+    let response = app
+        .clone()
+        .oneshot(signed(
+            http::Method::GET,
+            "/api/users/me",
+            &heir_key,
+            String::new(),
+        ))
+        .await
+        .unwrap();
+    let generated = response.headers()["x-request-id"].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(generated).is_ok());
+
+    sqlx::query("UPDATE claim_requests SET execute_after = NOW() WHERE plan_id = $1")
+        .bind(plan.id())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let executor = ClaimExecutorService::new(
+        test_state(pool.clone()).await,
+        ClaimExecutorConfig {
+            interval: Duration::from_secs(1),
+            batch_size: 1_000,
+        },
+    );
+    executor.run_once().await.unwrap();
+
+    // The claim, its payout and every audit entry carry the caller's id.
+    let claim_id: Option<String> =
+        sqlx::query_scalar("SELECT correlation_id FROM claim_requests WHERE plan_id = $1")
+            .bind(plan.id())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(claim_id.as_deref(), Some(request_id.as_str()));
+    let payout_id: Option<String> =
+        sqlx::query_scalar("SELECT correlation_id FROM payouts WHERE plan_id = $1")
+            .bind(plan.id())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(payout_id.as_deref(), Some(request_id.as_str()));
+    let actions: Vec<String> = sqlx::query_scalar(
+        "SELECT action FROM audit_logs WHERE correlation_id = $1 ORDER BY created_at",
+    )
+    .bind(&request_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert!(actions.contains(&"claim.requested".to_string()));
+    assert!(actions.contains(&"claim.executed".to_string()));
+}
+
+#[tokio::test]
+async fn test_immediate_payout_holds_suspicious_claims_for_review() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let config = Config {
+        claim_cooling_off_hours: 0,
+        ..Config::for_tests()
+    };
+    let app = create_router(test_state_with(pool.clone(), config).await);
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+    let payout = |owner: &str, key: &SigningKey| {
+        signed(
+            http::Method::POST,
+            "/api/plans/payout",
+            key,
+            json!({ "owner": owner }).to_string(),
+        )
+    };
+
+    // Re-auth turned off and the email swapped just before claiming.
+    let suspicious_key = SigningKey::generate(&mut rand::thread_rng());
+    let suspicious = wallet(&suspicious_key);
+    let held_owner = factory::wallet_address();
+    let held = PlanFactory::new()
+        .owner(&held_owner)
+        .grace_period(grace)
+        .claimable()
+        .beneficiary(&suspicious, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO wallet_reauth_settings (wallet_address, enabled) VALUES ($1, false)")
+        .bind(&suspicious)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO email_changes (user_address, old_email, new_email, old_token_hash,
+                                   status, expires_at, resolved_at)
+        VALUES ($1, 'old@example.com', 'new@example.com', $2, 'completed', NOW(), NOW())
+        "#,
+    )
+    .bind(&suspicious)
+    .bind(uuid::Uuid::new_v4().to_string())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Only a beneficiary can trigger the payout.
+    let stranger_key = SigningKey::generate(&mut rand::thread_rng());
+    let (status, _) = send(&app, payout(&held_owner, &stranger_key)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, claim) = send(&app, payout(&held_owner, &suspicious_key)).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(claim["status"], "in_review");
+    let paid: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payouts WHERE plan_id = $1")
+        .bind(held.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(paid, 0);
+    let score: i32 =
+        sqlx::query_scalar("SELECT score FROM claim_fraud_assessments WHERE claim_id = $1::uuid")
+            .bind(claim["id"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(score >= 60);
+
+    // An unremarkable claim is still paid out straight away.
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let owner = factory::wallet_address();
+    let plan = PlanFactory::new()
+        .owner(&owner)
+        .grace_period(grace)
+        .claimable()
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let (status, payouts) = send(&app, payout(&owner, &heir_key)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payouts.as_array().unwrap().len(), 1);
+    let claim_status: String =
+        sqlx::query_scalar("SELECT status FROM claim_requests WHERE plan_id = $1")
+            .bind(plan.id())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(claim_status, "executed");
+}
+
+#[tokio::test]
+async fn test_immediate_payout_checks_claim_eligibility() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let config = Config {
+        claim_cooling_off_hours: 0,
+        ..Config::for_tests()
+    };
+    let app = create_router(test_state_with(pool.clone(), config).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+    let payout = |owner: &str| {
+        signed(
+            http::Method::POST,
+            "/api/plans/payout",
+            &heir_key,
+            json!({ "owner": owner }).to_string(),
+        )
+    };
+
+    // 20,000 tokens, twice the basic tier's claim payout limit.
+    let large_owner = factory::wallet_address();
+    let large = PlanFactory::new()
+        .owner(&large_owner)
+        .amount(200_000_000_000)
+        .grace_period(grace)
+        .claimable()
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let (status, body) = send(&app, payout(&large_owner)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["tier"], "basic");
+
+    let expired_owner = factory::wallet_address();
+    PlanFactory::new()
+        .owner(&expired_owner)
+        .grace_period(grace)
+        .last_ping(chrono::Utc::now() - grace - chrono::Duration::days(366))
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let (status, _) = send(&app, payout(&expired_owner)).await;
+    assert_eq!(status, StatusCode::GONE);
+
+    let paid: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM payouts WHERE beneficiary_address = $1")
+            .bind(&heir)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(paid, 0);
+    let is_active: bool = sqlx::query_scalar("SELECT is_active FROM plans WHERE id = $1")
+        .bind(large.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(is_active);
+}
+
+#[tokio::test]
+async fn test_witness_quorum_lets_heir_claim_before_deadline() {
+    use inheritx_backend::witnesses::{attestation_message, AttestedEvent};
+
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let owner_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let witness_keys: Vec<SigningKey> = (0..2)
+        .map(|_| SigningKey::generate(&mut rand::thread_rng()))
+        .collect();
+    let heir = wallet(&heir_key);
+    let plan = PlanFactory::new()
+        .owner(&wallet(&owner_key))
+        .grace_period(chrono::Duration::seconds(GRACE_PERIOD_SECS))
+        .last_ping(chrono::Utc::now())
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let claim_uri = format!("/api/plans/{}/claim", plan.id());
+    let attestations_uri = format!("/api/plans/{}/attestations", plan.id());
+    let claim = || signed(http::Method::POST, &claim_uri, &heir_key, "{}".to_string());
+
+    // The owner pinged just now, so the grace period has not passed.
+    let (status, _) = send(&app, claim()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let witnesses = json!({
+        "threshold": 2,
+        "witnesses": witness_keys
+            .iter()
+            .map(|key| json!({ "witness_address": wallet(key), "name": "Witness" }))
+            .collect::<Vec<_>>(),
+    });
+    let (status, _) = send(
+        &app,
+        signed(
+            http::Method::PUT,
+            &format!("/api/plans/{}/witnesses", plan.id()),
+            &owner_key,
+            witnesses.to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let day = chrono::Utc::now().date_naive();
+    for key in &witness_keys {
+        let message = attestation_message(plan.id(), &wallet(key), AttestedEvent::Death, day);
+        let body = json!({
+            "witness_address": wallet(key),
+            "event": AttestedEvent::Death,
+            "occurred_on": day,
+            "signature": hex::encode(key.sign(message.as_bytes()).to_bytes()),
+        });
+        let (status, _) = send(
+            &app,
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(&attestations_uri)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, claim) = send(&app, claim()).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    sqlx::query("UPDATE claim_requests SET execute_after = NOW() WHERE id = $1::uuid")
+        .bind(claim["id"].as_str().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let executor = ClaimExecutorService::new(
+        test_state(pool.clone()).await,
+        ClaimExecutorConfig {
+            interval: Duration::from_secs(1),
+            batch_size: 1_000,
+        },
+    );
+    assert!(executor.run_once().await.unwrap() >= 1);
+
+    let (status, claim) = send(
+        &app,
+        signed(http::Method::GET, &claim_uri, &heir_key, String::new()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(claim["status"], "executed");
+    let paid: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM payouts WHERE beneficiary_address = $1")
+            .bind(&heir)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(paid, 1);
+}
+
+#[tokio::test]
+async fn test_claim_requires_fresh_wallet_signature() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let plan = PlanFactory::new()
+        .grace_period(chrono::Duration::seconds(GRACE_PERIOD_SECS))
+        .claimable()
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let claim_uri = format!("/api/plans/{}/claim", plan.id());
+
+    // Signed two hours ago, outside the step-up window.
+    let stale = signed_at(
+        http::Method::POST,
+        &claim_uri,
+        &heir_key,
+        "{}".to_string(),
+        (chrono::Utc::now() - chrono::Duration::hours(2)).timestamp(),
+    );
+    let (status, body) = send(&app, stale).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["step_up"]["action"], "step_up");
+
+    // A bare body signature carries no freshness at all.
+    let bare = signed_standard(http::Method::POST, &claim_uri, &heir_key, "{}".to_string());
+    let (status, _) = send(&app, bare).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A fresh signature is accepted once; replaying it is refused.
+    let fresh = signed(http::Method::POST, &claim_uri, &heir_key, "{}".to_string());
+    let mut replay = Request::builder()
+        .method(http::Method::POST)
+        .uri(&claim_uri)
+        .body(Body::from("{}"))
+        .unwrap();
+    *replay.headers_mut() = fresh.headers().clone();
+    let (status, _) = send(&app, fresh).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let (status, _) = send(&app, replay).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_immediate_payout_requires_fresh_wallet_signature() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let config = Config {
+        claim_cooling_off_hours: 0,
+        ..Config::for_tests()
+    };
+    let app = create_router(test_state_with(pool.clone(), config).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let owner = factory::wallet_address();
+    let plan = PlanFactory::new()
+        .owner(&owner)
+        .grace_period(chrono::Duration::seconds(GRACE_PERIOD_SECS))
+        .claimable()
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+
+    let (status, body) = send(
+        &app,
+        signed_standard(
+            http::Method::POST,
+            "/api/plans/payout",
+            &heir_key,
+            json!({ "owner": owner }).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["step_up"]["action"], "step_up");
+
+    let is_active: bool = sqlx::query_scalar("SELECT is_active FROM plans WHERE id = $1")
+        .bind(plan.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(is_active);
+    let claims: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM claim_requests WHERE plan_id = $1")
+        .bind(plan.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(claims, 0);
+}
+
+#[tokio::test]
+async fn test_verified_payout_addresses_cover_split_destinations() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let config = Config {
+        claim_cooling_off_hours: 0,
+        require_verified_payout_addresses: true,
+        ..Config::for_tests()
+    };
+    let app = create_router(test_state_with(pool.clone(), config).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let owner = factory::wallet_address();
+    let savings = factory::wallet_address();
+    let spending = factory::wallet_address();
+    let plan = PlanFactory::new()
+        .owner(&owner)
+        .grace_period(chrono::Duration::seconds(GRACE_PERIOD_SECS))
+        .claimable()
+        .beneficiary(&heir, 10_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let save_address = |address: String, verified: bool| {
+        sqlx::query(
+            r#"
+            INSERT INTO address_book_entries
+                (owner_address, label, address, verification_nonce, verification_memo, verified_at)
+            VALUES ($1, 'heir', $2, 'nonce', $3, CASE WHEN $4 THEN NOW() END)
+            "#,
+        )
+        .bind(owner.clone())
+        .bind(address)
+        .bind(uuid::Uuid::new_v4().simple().to_string()[..28].to_string())
+        .bind(verified)
+        .execute(&pool)
+    };
+    save_address(heir.clone(), true).await.unwrap();
+    save_address(savings.clone(), true).await.unwrap();
+    for (destination, share_bps) in [(&savings, 8_000), (&spending, 2_000)] {
+        sqlx::query(
+            "INSERT INTO payout_destinations (beneficiary_address, destination_address, share_bps) VALUES ($1, $2, $3)",
+        )
+        .bind(&heir)
+        .bind(destination)
+        .bind(share_bps)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    let payout = || {
+        signed(
+            http::Method::POST,
+            "/api/plans/payout",
+            &heir_key,
+            json!({ "owner": owner }).to_string(),
+        )
+    };
+
+    // The heir's own wallet is verified, but one split wallet is not.
+    let (status, body) = send(&app, payout()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["error"].as_str().unwrap().contains(&spending));
+    let paid: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payouts WHERE plan_id = $1")
+        .bind(plan.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(paid, 0);
+
+    save_address(spending.clone(), true).await.unwrap();
+    let (status, _) = send(&app, payout()).await;
+    assert_eq!(status, StatusCode::OK);
+    let paid: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payouts WHERE plan_id = $1")
+        .bind(plan.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(paid, 2);
+}
+
+#[tokio::test]
+async fn test_claim_payout_limits_cover_every_share_with_yield() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let app = create_router(test_state(pool.clone()).await);
+    let heir_key = SigningKey::generate(&mut rand::thread_rng());
+    let heir = wallet(&heir_key);
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+    let claim = |plan_id: uuid::Uuid| {
+        signed(
+            http::Method::POST,
+            &format!("/api/plans/{plan_id}/claim"),
+            &heir_key,
+            "{}".to_string(),
+        )
+    };
+
+    // 15,000 tokens: the heir's 1,500 is within the basic tier's 10,000
+    // limit, the other beneficiary's 13,500 is not.
+    let uneven = PlanFactory::new()
+        .amount(150_000_000_000)
+        .grace_period(grace)
+        .claimable()
+        .beneficiary(&heir, 1_000)
+        .beneficiary(&factory::wallet_address(), 9_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let (status, body) = send(&app, claim(uneven.id())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["tier"], "basic");
+
+    // Split evenly both shares fit, until yield pushes them over.
+    let even = PlanFactory::new()
+        .amount(150_000_000_000)
+        .grace_period(grace)
+        .claimable()
+        .beneficiary(&heir, 5_000)
+        .beneficiary(&factory::wallet_address(), 5_000)
+        .insert(&pool)
+        .await
+        .unwrap();
+    let (status, filed) = send(&app, claim(even.id())).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    sqlx::query("UPDATE plans SET accrued_yield = 100000000000 WHERE id = $1")
+        .bind(even.id())
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE claim_requests SET execute_after = NOW() WHERE id = $1::uuid")
+        .bind(filed["id"].as_str().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let executor = ClaimExecutorService::new(
+        test_state(pool.clone()).await,
+        ClaimExecutorConfig {
+            interval: Duration::from_secs(1),
+            batch_size: 1_000,
+        },
+    );
+    executor.run_once().await.unwrap();
+
+    let (status, failure): (String, Option<String>) =
+        sqlx::query_as("SELECT status, failure_reason FROM claim_requests WHERE id = $1::uuid")
+            .bind(filed["id"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "failed");
+    assert!(failure.unwrap().contains("claim payout limit"));
+    let paid: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payouts WHERE plan_id = $1")
+        .bind(even.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(paid, 0);
+}
+
+fn keeper_config(max_fee_stroops: i64) -> KeeperConfig {
+    KeeperConfig {
+        interval: Duration::from_secs(60),
+        batch_size: 1_000,
+        max_invocations: 1_000,
+        max_fee_stroops,
+        daily_fee_budget_stroops: i64::MAX,
+        fee_estimate_stroops: 100,
+        max_attempts: 3,
+        recheck_after: Duration::from_secs(3600),
+        source_account: None,
+        alert_recipients: Vec::new(),
+    }
+}
+
+async fn keeper_job(
+    pool: &PgPool,
+    plan_id: uuid::Uuid,
+    task: &str,
+) -> Option<(String, Option<String>)> {
+    sqlx::query_as("SELECT status, tx_hash FROM keeper_jobs WHERE plan_id = $1 AND task = $2")
+        .bind(plan_id)
+        .bind(task)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_keeper_claims_pays_out_and_escheats_on_chain() {
+    let Some(pool) = factory::test_pool().await else {
+        return;
+    };
+    let state = test_state(pool.clone()).await;
+    let tx_service = Arc::new(inheritx_backend::chain::SimulatedTxService::default());
+    let contract_id = factory::contract_address();
+    let grace = chrono::Duration::seconds(GRACE_PERIOD_SECS);
+
+    let claimable = PlanFactory::new()
+        .grace_period(grace)
+        .claimable()
+        .insert(&pool)
+        .await
+        .unwrap();
+    let unclaimed = PlanFactory::new()
+        .grace_period(grace)
+        .last_ping(chrono::Utc::now() - grace - chrono::Duration::days(366))
+        .insert(&pool)
+        .await
+        .unwrap();
+
+    // A call over the fee cap waits for a later sweep.
+    let capped = KeeperService::new(
+        state.clone(),
+        tx_service.clone(),
+        contract_id.clone(),
+        keeper_config(50),
+    );
+    let sweep = capped.run_once().await.unwrap();
+    assert!(sweep.deferred >= 2);
+    assert_eq!(
+        keeper_job(&pool, claimable.id(), "claim").await,
+        Some(("pending".to_string(), None))
+    );
+    sqlx::query("UPDATE keeper_jobs SET next_attempt_at = NOW() WHERE plan_id = ANY($1)")
+        .bind(vec![claimable.id(), unclaimed.id()])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let keeper = KeeperService::new(state.clone(), tx_service, contract_id, keeper_config(1_000));
+    let sweep = keeper.run_once().await.unwrap();
+    assert!(sweep.submitted >= 2);
+    assert!(sweep.fees_stroops >= 200);
+    let (status, tx_hash) = keeper_job(&pool, claimable.id(), "claim").await.unwrap();
+    assert_eq!(status, "submitted");
+    assert!(tx_hash.is_some());
+    assert_eq!(
+        keeper_job(&pool, unclaimed.id(), "escheat")
+            .await
+            .unwrap()
+            .0,
+        "submitted"
+    );
+    assert_eq!(
+        keeper_job(&pool, claimable.id(), "trigger_payout").await,
+        None
+    );
+
+    // The payout follows once the claim has been made.
+    keeper.run_once().await.unwrap();
+    assert_eq!(
+        keeper_job(&pool, claimable.id(), "trigger_payout")
+            .await
+            .unwrap()
+            .0,
+        "submitted"
+    );
+    assert_eq!(keeper_job(&pool, unclaimed.id(), "claim").await, None);
+
+    let token = AdminFactory::new().token(&Config::for_tests().jwt_secret);
+    let app = create_router(state);
+    let (status, body) = send(
+        &app,
+        Request::builder()
+            .uri("/api/admin/keeper")
+            .header(http::header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["runs"].as_array().unwrap().len() >= 3);
+    assert!(body["fees_last_day_stroops"].as_i64().unwrap() >= 300);
+}
@@ -0,0 +1,92 @@
+//! Throwaway infrastructure for the integration tests, started with
+//! testcontainers.
+//!
+//! When `DATABASE_URL` is unset, the first test that asks for a pool starts
+//! a Postgres container and points `DATABASE_URL` at it, so `cargo test`
+//! needs nothing but a running Docker daemon. The container is named and
+//! marked for reuse, so later test binaries and later runs share it instead
+//! of paying for a fresh start; `docker rm -f inheritx-test-postgres`
+//! removes it. Without Docker the tests fall back to the default test
+//! database, as before.
+//!
+//! With `E2E_SOROBAN=1` a Stellar quickstart node is started the same way
+//! for tests that want a real Soroban RPC endpoint.
+
+use testcontainers::core::{IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{GenericImage, ImageExt, ReuseDirective};
+use testcontainers_modules::postgres::Postgres;
+use tokio::sync::OnceCell;
+
+const POSTGRES_CONTAINER: &str = "inheritx-test-postgres";
+const POSTGRES_TAG: &str = "16-alpine";
+const QUICKSTART_CONTAINER: &str = "inheritx-test-quickstart";
+const QUICKSTART_PORT: u16 = 8000;
+
+static DATABASE: OnceCell<()> = OnceCell::const_new();
+static SOROBAN_RPC: OnceCell<Option<String>> = OnceCell::const_new();
+
+/// Sets `DATABASE_URL` to a Postgres container unless it is already set.
+pub async fn ensure_database_url() {
+    DATABASE
+        .get_or_init(|| async {
+            if std::env::var_os("DATABASE_URL").is_some() {
+                return;
+            }
+            let started = async {
+                let container = Postgres::default()
+                    .with_tag(POSTGRES_TAG)
+                    .with_container_name(POSTGRES_CONTAINER)
+                    .with_reuse(ReuseDirective::Always)
+                    .start()
+                    .await?;
+                let host = container.get_host().await?;
+                let port = container.get_host_port_ipv4(5432).await?;
+                Ok::<_, testcontainers::TestcontainersError>(format!(
+                    "postgres://postgres:postgres@{host}:{port}/postgres"
+                ))
+            }
+            .await;
+            match started {
+                Ok(url) => std::env::set_var("DATABASE_URL", url),
+                Err(e) => eprintln!("no Postgres container ({e}); using the default test database"),
+            }
+        })
+        .await;
+}
+
+/// Soroban RPC URL of a local quickstart node when `E2E_SOROBAN=1` and
+/// Docker is available.
+pub async fn soroban_rpc_url() -> Option<String> {
+    SOROBAN_RPC
+        .get_or_init(|| async {
+            if std::env::var("E2E_SOROBAN").ok().as_deref() != Some("1") {
+                return None;
+            }
+            let started = async {
+                let container = GenericImage::new("stellar/quickstart", "testing")
+                    .with_exposed_port(QUICKSTART_PORT.tcp())
+                    .with_wait_for(WaitFor::message_on_stdout("soroban rpc: up and ready"))
+                    .with_cmd(["--local", "--enable-soroban-rpc"])
+                    .with_container_name(QUICKSTART_CONTAINER)
+                    .with_reuse(ReuseDirective::Always)
+                    .start()
+                    .await?;
+                let host = container.get_host().await?;
+                let port = container.get_host_port_ipv4(QUICKSTART_PORT).await?;
+                Ok::<_, testcontainers::TestcontainersError>(format!(
+                    "http://{host}:{port}/soroban/rpc"
+                ))
+            }
+            .await;
+            match started {
+                Ok(url) => Some(url),
+                Err(e) => {
+                    eprintln!("no quickstart container ({e}); skipping Soroban RPC");
+                    None
+                }
+            }
+        })
+        .await
+        .clone()
+}
@@ -0,0 +1,519 @@
+//! Builders for database fixtures shared by the integration tests and the
+//! `seed` binary.
+//!
+//! Every builder fills in valid defaults (unique Stellar addresses, a funded
+//! active plan, ...) so a test only spells out the fields it cares about:
+//!
+//! ```ignore
+//! let owner = UserFactory::new().kyc_status("approved").insert(&pool).await?;
+//! let plan = PlanFactory::new()
+//!     .owner(&owner.wallet_address)
+//!     .beneficiary(&heir, 10_000)
+//!     .insert(&pool)
+//!     .await?;
+//! ClaimFactory::new(plan.id(), &heir).insert(&pool).await?;
+//! ```
+//!
+//! Rows are written with the same columns the API uses, so fixtures follow
+//! the migrations instead of each test carrying its own INSERT statements.
+//! Admins are not stored in the database; [`AdminFactory`] issues the JWT
+//! the admin routes expect.
+
+#![allow(dead_code)]
+
+#[cfg(test)]
+pub mod containers;
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use inheritx_backend::api::PlanRow;
+use inheritx_backend::claim_requests::ClaimRequest;
+use inheritx_backend::db::MigrationPhase;
+use inheritx_backend::notifications::Notification;
+use inheritx_backend::{auth, Config, DbManager};
+use rand::RngCore;
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+const PLAN_COLUMNS: &str = "id, owner_address, token_address, amount, grace_period, \
+     grace_period_seconds, earn_yield, last_ping, is_active, status, yield_rate_bps, \
+     accrued_yield, created_at";
+
+const CLAIM_COLUMNS: &str = "id, plan_id, requested_by, status, execute_after, cancelled_by, \
+     cancel_reason, failure_reason, created_at, resolved_at";
+
+/// Connects to `DATABASE_URL` and applies both migration phases. Returns `None` when
+/// the database is unreachable so tests that need one can skip themselves.
+///
+/// In tests an unset `DATABASE_URL` first starts a Postgres container; see
+/// [`containers`].
+pub async fn test_pool() -> Option<PgPool> {
+    #[cfg(test)]
+    containers::ensure_database_url().await;
+    let config = Config::for_tests();
+    let pool = match PgPoolOptions::new()
+        .max_connections(5)
+        .acquire_timeout(Duration::from_secs(2))
+        .connect(&config.database_url)
+        .await
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("skipping: no test database ({e})");
+            return None;
+        }
+    };
+    DbManager::run_migrations(&pool)
+        .await
+        .expect("failed to migrate test database");
+    DbManager::run_phase(&pool, MigrationPhase::PostDeploy)
+        .await
+        .expect("failed to run post-deploy migrations on test database");
+    Some(pool)
+}
+
+/// A random, well-formed Stellar account address (`G...`).
+pub fn wallet_address() -> String {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    stellar_strkey::ed25519::PublicKey(key).to_string()
+}
+
+/// A random, well-formed Soroban contract address (`C...`).
+pub fn contract_address() -> String {
+    let mut id = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut id);
+    stellar_strkey::Contract(id).to_string()
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UserFixture {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub kyc_status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct UserFactory {
+    wallet_address: String,
+    kyc_status: String,
+}
+
+impl Default for UserFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UserFactory {
+    pub fn new() -> Self {
+        Self {
+            wallet_address: wallet_address(),
+            kyc_status: "pending".to_string(),
+        }
+    }
+
+    pub fn wallet_address(mut self, address: &str) -> Self {
+        self.wallet_address = address.to_string();
+        self
+    }
+
+    /// `pending`, `submitted`, `approved` or `rejected`.
+    pub fn kyc_status(mut self, status: &str) -> Self {
+        self.kyc_status = status.to_string();
+        self
+    }
+
+    pub async fn insert(self, pool: &PgPool) -> Result<UserFixture, sqlx::Error> {
+        sqlx::query_as::<_, UserFixture>(
+            r#"
+            INSERT INTO users (wallet_address, kyc_status)
+            VALUES ($1, $2::kyc_status)
+            RETURNING id, wallet_address, kyc_status::text AS kyc_status, created_at
+            "#,
+        )
+        .bind(&self.wallet_address)
+        .bind(&self.kyc_status)
+        .fetch_one(pool)
+        .await
+    }
+}
+
+pub struct AdminFactory {
+    subject: String,
+    ttl: Duration,
+}
+
+impl Default for AdminFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdminFactory {
+    pub fn new() -> Self {
+        Self {
+            subject: format!("admin-{}", Uuid::new_v4()),
+            ttl: Duration::from_secs(3600),
+        }
+    }
+
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.subject = subject.to_string();
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Admin JWT signed with `jwt_secret`.
+    pub fn token(&self, jwt_secret: &str) -> String {
+        auth::issue_token(jwt_secret, &self.subject, "admin", self.ttl)
+            .expect("failed to sign admin token")
+    }
+}
+
+pub struct BeneficiarySpec {
+    pub wallet_address: String,
+    pub allocation_bps: i32,
+    pub fiat_anchor_info: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlanFixture {
+    pub plan: PlanRow,
+    pub beneficiaries: Vec<String>,
+}
+
+impl PlanFixture {
+    pub fn id(&self) -> Uuid {
+        self.plan.id
+    }
+}
+
+pub struct PlanFactory {
+    owner_address: String,
+    token_address: String,
+    amount: Decimal,
+    grace_period_seconds: i64,
+    last_ping: DateTime<Utc>,
+    status: String,
+    is_active: bool,
+    earn_yield: bool,
+    yield_rate_bps: i32,
+    beneficiaries: Vec<BeneficiarySpec>,
+}
+
+impl Default for PlanFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlanFactory {
+    /// An active plan of 1,000 tokens (7 decimals) with a 90 day grace
+    /// period, pinged now. Without explicit beneficiaries a single random
+    /// one receives the full allocation.
+    pub fn new() -> Self {
+        Self {
+            owner_address: wallet_address(),
+            token_address: contract_address(),
+            amount: Decimal::from(10_000_000_000i64),
+            grace_period_seconds: 90 * 24 * 60 * 60,
+            last_ping: Utc::now(),
+            status: "ACTIVE".to_string(),
+            is_active: true,
+            earn_yield: false,
+            yield_rate_bps: 0,
+            beneficiaries: Vec::new(),
+        }
+    }
+
+    pub fn owner(mut self, address: &str) -> Self {
+        self.owner_address = address.to_string();
+        self
+    }
+
+    pub fn token(mut self, address: &str) -> Self {
+        self.token_address = address.to_string();
+        self
+    }
+
+    pub fn amount(mut self, amount: i64) -> Self {
+        self.amount = Decimal::from(amount);
+        self
+    }
+
+    pub fn grace_period(mut self, grace: ChronoDuration) -> Self {
+        self.grace_period_seconds = grace.num_seconds();
+        self
+    }
+
+    pub fn last_ping(mut self, at: DateTime<Utc>) -> Self {
+        self.last_ping = at;
+        self
+    }
+
+    /// A plan whose owner stopped checking in, as the inactivity watchdog
+    /// leaves it.
+    pub fn claimable(mut self) -> Self {
+        self.status = "CLAIMABLE".to_string();
+        self.last_ping = Utc::now() - ChronoDuration::seconds(self.grace_period_seconds + 60);
+        self
+    }
+
+    pub fn paid_out(mut self) -> Self {
+        self.status = "PAID_OUT".to_string();
+        self.is_active = false;
+        self
+    }
+
+    pub fn earning_yield(mut self, rate_bps: i32) -> Self {
+        self.earn_yield = true;
+        self.yield_rate_bps = rate_bps;
+        self
+    }
+
+    pub fn beneficiary(self, address: &str, allocation_bps: i32) -> Self {
+        self.fiat_beneficiary(address, allocation_bps, "")
+    }
+
+    /// `anchor_info` is stored as given; it is read back as legacy
+    /// plaintext when field encryption is configured.
+    pub fn fiat_beneficiary(
+        mut self,
+        address: &str,
+        allocation_bps: i32,
+        anchor_info: &str,
+    ) -> Self {
+        self.beneficiaries.push(BeneficiarySpec {
+            wallet_address: address.to_string(),
+            allocation_bps,
+            fiat_anchor_info: anchor_info.to_string(),
+        });
+        self
+    }
+
+    pub async fn insert(mut self, pool: &PgPool) -> Result<PlanFixture, sqlx::Error> {
+        if self.beneficiaries.is_empty() {
+            self = self.beneficiary(&wallet_address(), 10_000);
+        }
+
+        let mut tx = pool.begin().await?;
+        let plan = sqlx::query_as::<_, PlanRow>(&format!(
+            r#"
+            INSERT INTO plans (
+                owner_address, token_address, amount, grace_period, grace_period_seconds,
+                earn_yield, yield_rate_bps, last_ping, is_active, status
+            ) VALUES ($1, $2, $3, $4, $4, $5, $6, $7, $8, $9)
+            RETURNING {PLAN_COLUMNS}
+            "#
+        ))
+        .bind(&self.owner_address)
+        .bind(&self.token_address)
+        .bind(self.amount)
+        .bind(self.grace_period_seconds)
+        .bind(self.earn_yield)
+        .bind(self.yield_rate_bps)
+        .bind(self.last_ping.timestamp())
+        .bind(self.is_active)
+        .bind(&self.status)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for beneficiary in &self.beneficiaries {
+            sqlx::query(
+                r#"
+                INSERT INTO beneficiaries (plan_id, wallet_address, allocation_bps, fiat_anchor_info)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(plan.id)
+            .bind(&beneficiary.wallet_address)
+            .bind(beneficiary.allocation_bps)
+            .bind(&beneficiary.fiat_anchor_info)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(PlanFixture {
+            plan,
+            beneficiaries: self
+                .beneficiaries
+                .into_iter()
+                .map(|b| b.wallet_address)
+                .collect(),
+        })
+    }
+}
+
+pub struct ClaimFactory {
+    plan_id: Uuid,
+    requested_by: String,
+    status: String,
+    execute_after: DateTime<Utc>,
+}
+
+impl ClaimFactory {
+    /// A pending claim whose cooling-off period ends in 24 hours.
+    pub fn new(plan_id: Uuid, requested_by: &str) -> Self {
+        Self {
+            plan_id,
+            requested_by: requested_by.to_string(),
+            status: "pending".to_string(),
+            execute_after: Utc::now() + ChronoDuration::hours(24),
+        }
+    }
+
+    /// Pending and already past its cooling-off period.
+    pub fn due(mut self) -> Self {
+        self.execute_after = Utc::now() - ChronoDuration::minutes(1);
+        self
+    }
+
+    pub fn execute_after(mut self, at: DateTime<Utc>) -> Self {
+        self.execute_after = at;
+        self
+    }
+
+    /// `pending`, `executed`, `cancelled` or `failed`.
+    pub fn status(mut self, status: &str) -> Self {
+        self.status = status.to_string();
+        self
+    }
+
+    pub async fn insert(self, pool: &PgPool) -> Result<ClaimRequest, sqlx::Error> {
+        sqlx::query_as::<_, ClaimRequest>(&format!(
+            r#"
+            INSERT INTO claim_requests (plan_id, requested_by, status, execute_after, resolved_at)
+            VALUES ($1, $2, $3, $4, CASE WHEN $3 = 'pending' THEN NULL ELSE NOW() END)
+            RETURNING {CLAIM_COLUMNS}
+            "#
+        ))
+        .bind(self.plan_id)
+        .bind(&self.requested_by)
+        .bind(&self.status)
+        .bind(self.execute_after)
+        .fetch_one(pool)
+        .await
+    }
+}
+
+pub struct NotificationFactory {
+    user_address: String,
+    notification_type: String,
+    title: String,
+    message: String,
+    metadata: serde_json::Value,
+    is_read: bool,
+}
+
+impl NotificationFactory {
+    pub fn new(user_address: &str) -> Self {
+        Self {
+            user_address: user_address.to_string(),
+            notification_type: "plan_update".to_string(),
+            title: "Plan updated".to_string(),
+            message: "One of your plans was updated.".to_string(),
+            metadata: serde_json::json!({}),
+            is_read: false,
+        }
+    }
+
+    pub fn notification_type(mut self, notification_type: &str) -> Self {
+        self.notification_type = notification_type.to_string();
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn message(mut self, message: &str) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    pub fn metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn read(mut self) -> Self {
+        self.is_read = true;
+        self
+    }
+
+    /// Inserts directly, bypassing the duplicate suppression and delivery
+    /// queueing in `notifications::create_notification`.
+    pub async fn insert(self, pool: &PgPool) -> Result<Notification, sqlx::Error> {
+        sqlx::query_as::<_, Notification>(
+            r#"
+            INSERT INTO notifications (user_address, notification_type, title, message, metadata, is_read, status)
+            VALUES ($1, $2, $3, $4, $5, $6, CASE WHEN $6 THEN 'read' ELSE 'sent' END)
+            RETURNING id, user_address, notification_type, title, message, metadata, is_read,
+                      status, created_at
+            "#,
+        )
+        .bind(&self.user_address)
+        .bind(&self.notification_type)
+        .bind(&self.title)
+        .bind(&self.message)
+        .bind(&self.metadata)
+        .bind(self.is_read)
+        .fetch_one(pool)
+        .await
+    }
+}
+
+pub struct NotificationPreferencesFactory {
+    user_address: String,
+    email: Option<String>,
+    digest_frequency: String,
+}
+
+impl NotificationPreferencesFactory {
+    /// Immediate email delivery to a per-wallet example.com address.
+    pub fn new(user_address: &str) -> Self {
+        Self {
+            user_address: user_address.to_string(),
+            email: Some(format!(
+                "{}@example.com",
+                &user_address[..12].to_lowercase()
+            )),
+            digest_frequency: "immediate".to_string(),
+        }
+    }
+
+    pub fn email(mut self, email: Option<&str>) -> Self {
+        self.email = email.map(str::to_string);
+        self
+    }
+
+    /// `immediate`, `hourly` or `daily`.
+    pub fn digest_frequency(mut self, frequency: &str) -> Self {
+        self.digest_frequency = frequency.to_string();
+        self
+    }
+
+    pub async fn insert(self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_address, email, digest_frequency)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(&self.user_address)
+        .bind(&self.email)
+        .bind(&self.digest_frequency)
+        .execute(pool)
+        .await
+        .map(|_| ())
+    }
+}
@@ -1,63 +1,69 @@
 #![no_std]
-use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, String, Vec,
-};
-
-const MAX_BENEFICIARIES: u32 = 100;
-const PLAN_TTL_THRESHOLD: u32 = 500;
-const PLAN_TTL_LEEWAY: u32 = 100;
-
-#[contracterror]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum Error {
-    PlanAlreadyExists = 1,
-    PlanNotFound = 2,
-    Unauthorized = 3,
-    InactivityPeriodNotMet = 4,
-    InvalidBasisPoints = 5,
-    NegativeAmount = 6,
-    InsufficientBalance = 7,
-    TooManyBeneficiaries = 8,
-    TimelockNotExpired = 9,
-    PayoutNotTriggered = 10,
-}
+use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, Vec};
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Beneficiary {
-    pub address: Address,
-    pub allocation_bps: u32,
-    pub fiat_anchor_info: String,
-}
+pub use inheritx_types::{
+    Beneficiary, ChangeDelay, FeeAccount, FeeConfig, Guardian, GuardianSet,
+    InheritanceError as Error, KycTier, PendingBeneficiaryChange, Plan, PlanSummary, TierLimits,
+};
+use inheritx_types::{
+    BPS_DENOMINATOR, DEFAULT_CHANGE_DELAY, DEFAULT_CLAIM_WINDOW, MAX_BENEFICIARIES,
+    MAX_CHANGE_DELAY, MAX_CLAIM_WINDOW, MAX_GUARDIANS, MIN_CLAIM_WINDOW,
+};
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Plan {
-    pub owner: Address,
-    pub token: Address,
-    pub amount: i128,
-    pub beneficiaries: Vec<Beneficiary>,
-    pub last_ping: u64,
-    pub grace_period: u64,
-    pub earn_yield: bool,
-    pub yield_rate_bps: u32,
-    pub is_active: bool,
-    pub timelock_duration: u64,
-}
+const DAY_IN_LEDGERS: u32 = 17_280;
+/// Entries touched by an entrypoint are extended to live this long.
+const PLAN_TTL_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
+/// Extension is skipped while the remaining TTL is above this, so repeated
+/// reads don't pay for a write every ledger.
+const PLAN_TTL_THRESHOLD: u32 = PLAN_TTL_EXTEND_TO - DAY_IN_LEDGERS;
+const INSTANCE_TTL_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_THRESHOLD: u32 = INSTANCE_TTL_EXTEND_TO - DAY_IN_LEDGERS;
 
 pub type InheritancePlan = Plan;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
+    /// The plan's [`PlanSummary`]; its beneficiaries are kept apart.
     Plan(Address),
+    /// The plan's beneficiary list, read only when it is needed.
+    Beneficiaries(Address),
     ClaimStatus(Address),
+    /// Unclaimed referral fees, keyed by referrer and token.
+    ReferralBalance(Address, Address),
+    Guardians(Address),
+    /// Present while guardians have paused claims on the owner's plan.
+    ClaimsPaused(Address),
+    /// Platform fee accounting per token.
+    Fees(Address),
+    PendingChange(Address),
+    ChangeDelay(Address),
+    /// SHA-256 of the plan's canonical off-chain document.
+    MetadataHash(Address),
+    /// SHA-256 over the witness attestations that made the plan claimable.
+    AttestationHash(Address),
+    /// Where the plan's funds go if no beneficiary claims them in time.
+    Fallback(Address),
+    /// KYC tier the admin has assigned to an address; `Basic` when absent.
+    KycTier(Address),
+    /// Claim fee rate fixed when the owner's plan was created; absent when
+    /// the plan pays no claim fee.
+    ClaimFee(Address),
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum InstanceDataKey {
     Admin,
+    FeeConfig,
+    /// Second signer required for fee withdrawals outside the treasury.
+    FeeApprover,
+    /// Seconds beneficiaries have to claim a timed-out plan.
+    ClaimWindow,
+    /// Limits of a KYC tier. Tiers without limits are unrestricted.
+    TierLimits(KycTier),
+    /// Claim fee rate applied to plans created from now on.
+    ClaimFeeBps,
 }
 
 #[contract]
@@ -67,13 +73,559 @@ impl InheritanceContract {
     fn extend_plan_ttl(env: &Env, key: &DataKey) {
         env.storage()
             .persistent()
-            .extend_ttl(key, PLAN_TTL_LEEWAY, PLAN_TTL_THRESHOLD);
+            .extend_ttl(key, PLAN_TTL_THRESHOLD, PLAN_TTL_EXTEND_TO);
+        Self::extend_instance_ttl(env);
+    }
+
+    fn extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+    }
+
+    fn load_summary(env: &Env, owner: &Address) -> Result<PlanSummary, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Plan(owner.clone()))
+            .ok_or(Error::PlanNotFound)
+    }
+
+    fn save_summary(env: &Env, summary: &PlanSummary) {
+        let key = DataKey::Plan(summary.owner.clone());
+        env.storage().persistent().set(&key, summary);
+        Self::extend_plan_ttl(env, &key);
+    }
+
+    fn load_beneficiaries(env: &Env, owner: &Address) -> Vec<Beneficiary> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Beneficiaries(owner.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn save_beneficiaries(env: &Env, owner: &Address, beneficiaries: &Vec<Beneficiary>) {
+        let key = DataKey::Beneficiaries(owner.clone());
+        env.storage().persistent().set(&key, beneficiaries);
+        Self::extend_plan_ttl(env, &key);
+    }
+
+    /// The full plan, assembled from its summary and beneficiary entries.
+    fn load_plan(env: &Env, owner: &Address) -> Result<Plan, Error> {
+        let summary = Self::load_summary(env, owner)?;
+        Ok(Plan {
+            beneficiaries: Self::load_beneficiaries(env, owner),
+            owner: summary.owner,
+            token: summary.token,
+            amount: summary.amount,
+            last_ping: summary.last_ping,
+            grace_period: summary.grace_period,
+            earn_yield: summary.earn_yield,
+            yield_rate_bps: summary.yield_rate_bps,
+            is_active: summary.is_active,
+            timelock_duration: summary.timelock_duration,
+            referrer: summary.referrer,
+        })
+    }
+
+    fn remove_plan(env: &Env, owner: &Address) {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Plan(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Beneficiaries(owner.clone()));
+    }
+
+    fn require_admin(env: &Env, admin: &Address) -> Result<(), Error> {
+        let stored: Address = env
+            .storage()
+            .instance()
+            .get(&InstanceDataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        if stored != *admin {
+            return Err(Error::Unauthorized);
+        }
+        admin.require_auth();
+        Ok(())
+    }
+
+    /// Check that `approvers` are distinct guardians of `owner`'s plan whose
+    /// weights meet the threshold, and require each of them to authorize
+    /// the call.
+    fn require_guardian_quorum(
+        env: &Env,
+        owner: &Address,
+        approvers: &Vec<Address>,
+    ) -> Result<GuardianSet, Error> {
+        let key = DataKey::Guardians(owner.clone());
+        let set: GuardianSet = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::GuardiansNotSet)?;
+
+        let mut weight: u32 = 0;
+        for (i, approver) in approvers.iter().enumerate() {
+            if approvers.first_index_of(&approver) != Some(i as u32) {
+                return Err(Error::InvalidGuardianConfig);
+            }
+            let guardian = set
+                .guardians
+                .iter()
+                .find(|g| g.address == approver)
+                .ok_or(Error::NotGuardian)?;
+            weight += guardian.weight;
+        }
+        if weight < set.threshold {
+            return Err(Error::QuorumNotMet);
+        }
+
+        for approver in approvers.iter() {
+            approver.require_auth();
+        }
+        Self::extend_plan_ttl(env, &key);
+        Ok(set)
+    }
+
+    /// Challenge window configured for `owner`'s plan, zero without guardians.
+    fn challenge_window(env: &Env, owner: &Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get::<_, GuardianSet>(&DataKey::Guardians(owner.clone()))
+            .map_or(0, |set| set.challenge_window)
+    }
+
+    fn ensure_claims_not_paused(env: &Env, owner: &Address) -> Result<(), Error> {
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::ClaimsPaused(owner.clone()))
+        {
+            return Err(Error::ClaimsPaused);
+        }
+        Ok(())
+    }
+
+    /// Remove guardian state left behind when a plan is deleted.
+    fn remove_guardian_state(env: &Env, owner: &Address) {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Guardians(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ClaimsPaused(owner.clone()));
+    }
+
+    /// Remove queued beneficiary changes, the change delay, the anchored
+    /// metadata and attestation hashes, the fallback beneficiary and the
+    /// claim fee rate when a plan is deleted.
+    fn remove_change_state(env: &Env, owner: &Address) {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ClaimFee(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::MetadataHash(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::AttestationHash(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Fallback(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingChange(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ChangeDelay(owner.clone()));
+    }
+
+    /// Change delay in force for `owner`'s plan at `now`.
+    fn change_delay(env: &Env, owner: &Address, now: u64) -> u64 {
+        match env
+            .storage()
+            .persistent()
+            .get::<_, ChangeDelay>(&DataKey::ChangeDelay(owner.clone()))
+        {
+            Some(ChangeDelay {
+                lowered_to: Some(lowered),
+                lowered_at,
+                ..
+            }) if now >= lowered_at => lowered,
+            Some(config) => config.delay,
+            None => DEFAULT_CHANGE_DELAY,
+        }
+    }
+
+    fn claim_window(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&InstanceDataKey::ClaimWindow)
+            .unwrap_or(DEFAULT_CLAIM_WINDOW)
+    }
+
+    fn plan_claim_fee(env: &Env, owner: &Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ClaimFee(owner.clone()))
+            .unwrap_or(0)
+    }
+
+    fn kyc_tier(env: &Env, address: &Address) -> KycTier {
+        env.storage()
+            .persistent()
+            .get(&DataKey::KycTier(address.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Refuse `amount` when it is above the limit `pick` selects from the
+    /// limits of `address`'s tier.
+    fn check_tier_limit(
+        env: &Env,
+        address: &Address,
+        amount: i128,
+        pick: fn(&TierLimits) -> i128,
+    ) -> Result<(), Error> {
+        let tier = Self::kyc_tier(env, address);
+        match env
+            .storage()
+            .instance()
+            .get::<_, TierLimits>(&InstanceDataKey::TierLimits(tier))
+        {
+            Some(limits) if amount > pick(&limits) => Err(Error::TierLimitExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// End of the window in which beneficiaries can claim `plan`.
+    fn claim_expiry(env: &Env, plan: &PlanSummary) -> u64 {
+        plan.last_ping + plan.grace_period + Self::claim_window(env)
+    }
+
+    /// Check a replacement beneficiary list the way `create_plan` does, and
+    /// additionally reject duplicate addresses.
+    fn validate_beneficiaries(beneficiaries: &Vec<Beneficiary>) -> Result<(), Error> {
+        if beneficiaries.len() > MAX_BENEFICIARIES {
+            return Err(Error::TooManyBeneficiaries);
+        }
+        let mut total_bps: u32 = 0;
+        for (i, beneficiary) in beneficiaries.iter().enumerate() {
+            let first = beneficiaries
+                .iter()
+                .position(|b| b.address == beneficiary.address);
+            if first != Some(i) {
+                return Err(Error::DuplicateBeneficiary);
+            }
+            total_bps = total_bps
+                .checked_add(beneficiary.allocation_bps)
+                .ok_or(Error::InvalidBasisPoints)?;
+        }
+        if total_bps != BPS_DENOMINATOR {
+            return Err(Error::InvalidBasisPoints);
+        }
+        Ok(())
+    }
+
+    /// Take the platform fee out of a deposit already held by the contract,
+    /// crediting the referrer's share and adding the rest to the token's fee
+    /// account. Returns the fee so the caller can reduce the plan amount.
+    fn charge_platform_fee(
+        env: &Env,
+        owner: &Address,
+        token: &Address,
+        amount: i128,
+        referrer: &Option<Address>,
+    ) -> i128 {
+        let config: FeeConfig = match env.storage().instance().get(&InstanceDataKey::FeeConfig) {
+            Some(config) => config,
+            None => return 0,
+        };
+
+        let fee = amount * config.platform_fee_bps as i128 / BPS_DENOMINATOR as i128;
+        if fee == 0 {
+            return 0;
+        }
+
+        let referral_share = match referrer {
+            Some(referrer) => {
+                let share = fee * config.referral_fee_bps as i128 / BPS_DENOMINATOR as i128;
+                if share > 0 {
+                    let key = DataKey::ReferralBalance(referrer.clone(), token.clone());
+                    let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+                    env.storage().persistent().set(&key, &(balance + share));
+                    Self::extend_plan_ttl(env, &key);
+                    env.events().publish(
+                        (symbol_short!("referral"), referrer.clone()),
+                        (owner.clone(), token.clone(), share),
+                    );
+                }
+                share
+            }
+            None => 0,
+        };
+
+        let platform_share = fee - referral_share;
+        if platform_share > 0 {
+            let key = DataKey::Fees(token.clone());
+            let mut account: FeeAccount = env.storage().persistent().get(&key).unwrap_or_default();
+            account.collected += platform_share;
+            env.storage().persistent().set(&key, &account);
+            Self::extend_plan_ttl(env, &key);
+            env.events().publish(
+                (symbol_short!("fee_in"), token.clone()),
+                (owner.clone(), platform_share),
+            );
+        }
+
+        fee
     }
 }
 
 #[contractimpl]
 #[allow(clippy::too_many_arguments)]
 impl InheritanceContract {
+    /// Set the admin allowed to configure platform fees. Can only be called once.
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&InstanceDataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&InstanceDataKey::Admin, &admin);
+        Self::extend_instance_ttl(&env);
+
+        Ok(())
+    }
+
+    /// Configure the platform fee charged on plan creation and the share of
+    /// it paid to referrers. Both rates are in basis points; the referral
+    /// rate is a fraction of the platform fee, not of the deposit.
+    pub fn set_fee_config(
+        env: Env,
+        admin: Address,
+        platform_fee_bps: u32,
+        referral_fee_bps: u32,
+        treasury: Address,
+    ) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        if platform_fee_bps > BPS_DENOMINATOR || referral_fee_bps > BPS_DENOMINATOR {
+            return Err(Error::InvalidFeeConfig);
+        }
+
+        let config = FeeConfig {
+            platform_fee_bps,
+            referral_fee_bps,
+            treasury,
+        };
+        env.storage()
+            .instance()
+            .set(&InstanceDataKey::FeeConfig, &config);
+        Self::extend_instance_ttl(&env);
+        env.events().publish(
+            (symbol_short!("fee_cfg"), admin),
+            (platform_fee_bps, referral_fee_bps),
+        );
+
+        Ok(())
+    }
+
+    /// Set the claim fee taken from plans created from now on, in basis
+    /// points of the plan amount. Each plan keeps the rate in force when it
+    /// was created, so a change never reaches existing plans. The fee goes
+    /// to the treasury at payout, which needs a fee config to be set.
+    pub fn set_claim_fee(env: Env, admin: Address, claim_fee_bps: u32) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+        if claim_fee_bps > BPS_DENOMINATOR {
+            return Err(Error::InvalidFeeConfig);
+        }
+        if !env.storage().instance().has(&InstanceDataKey::FeeConfig) {
+            return Err(Error::NotInitialized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&InstanceDataKey::ClaimFeeBps, &claim_fee_bps);
+        Self::extend_instance_ttl(&env);
+        env.events()
+            .publish((symbol_short!("claim_fee"), admin), claim_fee_bps);
+
+        Ok(())
+    }
+
+    /// Claim fee rate new plans are created with.
+    pub fn get_claim_fee(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&InstanceDataKey::ClaimFeeBps)
+            .unwrap_or(0)
+    }
+
+    /// Claim fee rate the owner's plan pays at payout.
+    pub fn get_plan_claim_fee(env: Env, owner: Address) -> Result<u32, Error> {
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+        Ok(Self::plan_claim_fee(&env, &owner))
+    }
+
+    /// Current fee configuration, if one has been set.
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        env.storage().instance().get(&InstanceDataKey::FeeConfig)
+    }
+
+    /// Change where fee withdrawals go by default.
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut config: FeeConfig = env
+            .storage()
+            .instance()
+            .get(&InstanceDataKey::FeeConfig)
+            .ok_or(Error::InvalidFeeConfig)?;
+        config.treasury = treasury.clone();
+        env.storage()
+            .instance()
+            .set(&InstanceDataKey::FeeConfig, &config);
+        Self::extend_instance_ttl(&env);
+        env.events()
+            .publish((symbol_short!("treasury"), admin), treasury);
+
+        Ok(())
+    }
+
+    /// Set or clear the second signer for fee withdrawals. While set, every
+    /// withdrawal needs its signature; without one, fees can only be
+    /// withdrawn to the treasury. The approver cannot be the admin.
+    pub fn set_fee_approver(
+        env: Env,
+        admin: Address,
+        approver: Option<Address>,
+    ) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        match &approver {
+            Some(approver) if *approver == admin => return Err(Error::InvalidFeeConfig),
+            Some(approver) => env
+                .storage()
+                .instance()
+                .set(&InstanceDataKey::FeeApprover, approver),
+            None => env
+                .storage()
+                .instance()
+                .remove(&InstanceDataKey::FeeApprover),
+        }
+        Self::extend_instance_ttl(&env);
+        env.events()
+            .publish((symbol_short!("fee_appr"), admin), approver);
+
+        Ok(())
+    }
+
+    /// The second signer for fee withdrawals, if one is set.
+    pub fn get_fee_approver(env: Env) -> Option<Address> {
+        env.storage().instance().get(&InstanceDataKey::FeeApprover)
+    }
+
+    /// Platform fees collected and withdrawn in `token`.
+    pub fn get_fee_account(env: Env, token: Address) -> FeeAccount {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Fees(token))
+            .unwrap_or_default()
+    }
+
+    /// Withdraw `amount` of the platform fees held in `token` to `to`.
+    /// The admin must authorize. When a fee approver is set it must
+    /// authorize too, maker-checker style; without one, `to` must be the
+    /// treasury. Returns the fee balance left.
+    pub fn withdraw_fees(
+        env: Env,
+        admin: Address,
+        approver: Option<Address>,
+        token: Address,
+        amount: i128,
+        to: Address,
+    ) -> Result<i128, Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let required: Option<Address> = env.storage().instance().get(&InstanceDataKey::FeeApprover);
+        match (&required, &approver) {
+            (Some(required), Some(approver)) if required == approver => approver.require_auth(),
+            (Some(_), _) => return Err(Error::ApprovalRequired),
+            (None, _) => {
+                let config: FeeConfig = env
+                    .storage()
+                    .instance()
+                    .get(&InstanceDataKey::FeeConfig)
+                    .ok_or(Error::InvalidFeeConfig)?;
+                if to != config.treasury {
+                    return Err(Error::ApprovalRequired);
+                }
+            }
+        }
+
+        if amount <= 0 {
+            return Err(Error::NegativeAmount);
+        }
+        let key = DataKey::Fees(token.clone());
+        let mut account: FeeAccount = env.storage().persistent().get(&key).unwrap_or_default();
+        if amount > account.balance() {
+            return Err(Error::InsufficientFees);
+        }
+
+        account.withdrawn += amount;
+        env.storage().persistent().set(&key, &account);
+        Self::extend_plan_ttl(&env, &key);
+
+        soroban_sdk::token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &to,
+            &amount,
+        );
+        env.events().publish(
+            (symbol_short!("fee_wd"), token),
+            (to, amount, admin, required),
+        );
+
+        Ok(account.balance())
+    }
+
+    /// Unclaimed referral fees owed to `referrer` in `token`.
+    pub fn get_referral_balance(env: Env, referrer: Address, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ReferralBalance(referrer, token))
+            .unwrap_or(0)
+    }
+
+    /// Withdraw all referral fees accrued to `referrer` in `token`.
+    /// Returns the amount transferred.
+    pub fn claim_referral_fees(env: Env, referrer: Address, token: Address) -> Result<i128, Error> {
+        referrer.require_auth();
+
+        let key = DataKey::ReferralBalance(referrer.clone(), token.clone());
+        let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if balance <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+
+        env.storage().persistent().remove(&key);
+
+        let token_client = soroban_sdk::token::Client::new(&env, &token);
+        token_client.transfer(&env.current_contract_address(), &referrer, &balance);
+        env.events()
+            .publish((symbol_short!("ref_claim"), referrer), (token, balance));
+
+        Ok(balance)
+    }
+
     /// Create a yield-bearing inheritance plan with mass beneficiaries payout allocations.
     /// Contributors: Implement token transfers from owner, validation checks, and storage configuration.
     #[allow(clippy::too_many_arguments)]
@@ -87,9 +639,14 @@ impl InheritanceContract {
         earn_yield: bool,
         yield_rate_bps: u32,
         timelock_duration: u64,
+        referrer: Option<Address>,
     ) -> Result<(), Error> {
         owner.require_auth();
 
+        if referrer.as_ref() == Some(&owner) {
+            return Err(Error::InvalidReferrer);
+        }
+
         if beneficiaries.len() > MAX_BENEFICIARIES {
             return Err(Error::TooManyBeneficiaries);
         }
@@ -102,6 +659,7 @@ impl InheritanceContract {
         if amount <= 0 {
             return Err(Error::NegativeAmount);
         }
+        Self::check_tier_limit(&env, &owner, amount, |limits| limits.max_plan_amount)?;
 
         let mut total_bps: u32 = 0;
         for beneficiary in beneficiaries.iter() {
@@ -118,22 +676,31 @@ impl InheritanceContract {
         }
 
         token_client.transfer(&owner, &env.current_contract_address(), &amount);
+        let fee = Self::charge_platform_fee(&env, &owner, &token, amount, &referrer);
 
-        let plan = Plan {
+        let summary = PlanSummary {
             owner: owner.clone(),
             token,
-            amount,
-            beneficiaries,
+            amount: amount - fee,
+            beneficiary_count: beneficiaries.len(),
             last_ping: env.ledger().timestamp(),
             grace_period,
             earn_yield,
             yield_rate_bps,
             is_active: true,
             timelock_duration,
+            referrer,
         };
 
-        env.storage().persistent().set(&key, &plan);
-        Self::extend_plan_ttl(&env, &key);
+        Self::save_summary(&env, &summary);
+        Self::save_beneficiaries(&env, &owner, &beneficiaries);
+
+        let claim_fee_bps = Self::get_claim_fee(env.clone());
+        if claim_fee_bps > 0 {
+            let fee_key = DataKey::ClaimFee(owner.clone());
+            env.storage().persistent().set(&fee_key, &claim_fee_bps);
+            Self::extend_plan_ttl(&env, &fee_key);
+        }
 
         Ok(())
     }
@@ -143,17 +710,11 @@ impl InheritanceContract {
     pub fn ping(env: Env, owner: Address) -> Result<(), Error> {
         owner.require_auth();
 
-        let key = DataKey::Plan(owner.clone());
-        if !env.storage().persistent().has(&key) {
-            return Err(Error::PlanNotFound);
-        }
-
-        let mut plan: Plan = env.storage().persistent().get(&key).unwrap();
+        let mut plan = Self::load_summary(&env, &owner)?;
         let current_timestamp = env.ledger().timestamp();
         plan.last_ping = current_timestamp;
 
-        env.storage().persistent().set(&key, &plan);
-        Self::extend_plan_ttl(&env, &key);
+        Self::save_summary(&env, &plan);
         env.events()
             .publish((symbol_short!("ping"), owner), current_timestamp);
 
@@ -164,12 +725,7 @@ impl InheritanceContract {
     /// Contributors: Calculate final yield-bearing payout, split assets among beneficiaries,
     /// emit payout events, and trigger anchor event emissions for fiat recipients.
     pub fn claim(env: Env, owner: Address) -> Result<(), Error> {
-        let key = DataKey::Plan(owner.clone());
-        let plan: Plan = env
-            .storage()
-            .persistent()
-            .get(&key)
-            .ok_or(Error::PlanNotFound)?;
+        let plan = Self::load_summary(&env, &owner)?;
 
         if plan.is_active {
             return Err(Error::InactivityPeriodNotMet);
@@ -180,10 +736,15 @@ impl InheritanceContract {
             return Err(Error::InactivityPeriodNotMet);
         }
 
+        Self::ensure_claims_not_paused(&env, &owner)?;
+
         let claim_key = DataKey::ClaimStatus(owner.clone());
         if env.storage().persistent().has(&claim_key) {
             return Ok(()); // Already claimed
         }
+        if current_time >= Self::claim_expiry(&env, &plan) {
+            return Err(Error::ClaimWindowClosed);
+        }
 
         env.storage().persistent().set(&claim_key, &current_time);
         Self::extend_plan_ttl(&env, &claim_key);
@@ -195,12 +756,7 @@ impl InheritanceContract {
     pub fn cancel_claim(env: Env, owner: Address) -> Result<(), Error> {
         owner.require_auth();
 
-        let key = DataKey::Plan(owner.clone());
-        let mut plan: Plan = env
-            .storage()
-            .persistent()
-            .get(&key)
-            .ok_or(Error::PlanNotFound)?;
+        let mut plan = Self::load_summary(&env, &owner)?;
 
         let claim_key = DataKey::ClaimStatus(owner.clone());
         if !env.storage().persistent().has(&claim_key) {
@@ -211,8 +767,7 @@ impl InheritanceContract {
 
         plan.is_active = true;
         plan.last_ping = env.ledger().timestamp();
-        env.storage().persistent().set(&key, &plan);
-        Self::extend_plan_ttl(&env, &key);
+        Self::save_summary(&env, &plan);
 
         Ok(())
     }
@@ -221,13 +776,8 @@ impl InheritanceContract {
     /// Returns true if current_time >= last_ping + grace_period, false otherwise.
     /// This is a read-only query method that does not modify state.
     pub fn is_plan_timed_out(env: Env, owner: Address) -> Result<bool, Error> {
-        let key = DataKey::Plan(owner.clone());
-        if !env.storage().persistent().has(&key) {
-            return Err(Error::PlanNotFound);
-        }
-
-        let plan: Plan = env.storage().persistent().get(&key).unwrap();
-        Self::extend_plan_ttl(&env, &key);
+        let plan = Self::load_summary(&env, &owner)?;
+        Self::extend_plan_ttl(&env, &DataKey::Plan(owner));
 
         let current_time = env.ledger().timestamp();
         let timeout_deadline = plan.last_ping + plan.grace_period;
@@ -239,13 +789,8 @@ impl InheritanceContract {
     /// Returns the timestamp when the grace period expires (last_ping + grace_period).
     /// This is a read-only query method for external monitoring.
     pub fn get_timeout_deadline(env: Env, owner: Address) -> Result<u64, Error> {
-        let key = DataKey::Plan(owner.clone());
-        if !env.storage().persistent().has(&key) {
-            return Err(Error::PlanNotFound);
-        }
-
-        let plan: Plan = env.storage().persistent().get(&key).unwrap();
-        Self::extend_plan_ttl(&env, &key);
+        let plan = Self::load_summary(&env, &owner)?;
+        Self::extend_plan_ttl(&env, &DataKey::Plan(owner));
 
         Ok(plan.last_ping + plan.grace_period)
     }
@@ -253,29 +798,476 @@ impl InheritanceContract {
     /// Retrieve the current inheritance plan data.
     /// Contributors: Query plan storage, dynamically projects the accumulated yield.
     pub fn get_plan(env: Env, owner: Address) -> Result<InheritancePlan, Error> {
-        let key = DataKey::Plan(owner.clone());
-        if !env.storage().persistent().has(&key) {
-            return Err(Error::PlanNotFound);
-        }
-
-        let plan: Plan = env.storage().persistent().get(&key).unwrap();
-        Self::extend_plan_ttl(&env, &key);
+        let plan = Self::load_plan(&env, &owner)?;
+        Self::extend_plan_ttl(&env, &DataKey::Plan(owner.clone()));
+        Self::extend_plan_ttl(&env, &DataKey::Beneficiaries(owner));
 
         Ok(plan)
     }
 
-    /// Trigger payout to all beneficiaries once the plan is claimable.
-    /// Iterates over beneficiaries, computes pro-rata token allocations
-    /// using the stored basis points, and transfers tokens safely.
-    /// Remaining dust from integer division is allocated to the last beneficiary.
-    /// Aborts the entire transaction if any single transfer fails.
-    pub fn trigger_payout(env: Env, owner: Address) -> Result<(), Error> {
+    /// The plan without its beneficiary list, read from a single storage
+    /// entry. Cheaper than `get_plan` for callers that only need the
+    /// amount, timer or status.
+    pub fn get_plan_summary(env: Env, owner: Address) -> Result<PlanSummary, Error> {
+        let summary = Self::load_summary(&env, &owner)?;
+        Self::extend_plan_ttl(&env, &DataKey::Plan(owner));
+
+        Ok(summary)
+    }
+
+    /// Extend the storage TTL of a plan, its pending claim, its claim fee,
+    /// its guardian state and any queued beneficiary change so they are not
+    /// archived while the owner is inactive. Callable by anyone (typically a
+    /// maintenance worker); returns the TTL the entries were extended to.
+    pub fn bump_storage(env: Env, owner: Address) -> Result<u32, Error> {
         let key = DataKey::Plan(owner.clone());
-        let plan: Plan = env
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::PlanNotFound);
+        }
+
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PLAN_TTL_EXTEND_TO, PLAN_TTL_EXTEND_TO);
+
+        for related in [
+            DataKey::Beneficiaries(owner.clone()),
+            DataKey::ClaimStatus(owner.clone()),
+            DataKey::Guardians(owner.clone()),
+            DataKey::ClaimsPaused(owner.clone()),
+            DataKey::PendingChange(owner.clone()),
+            DataKey::ChangeDelay(owner.clone()),
+            DataKey::MetadataHash(owner.clone()),
+            DataKey::AttestationHash(owner.clone()),
+            DataKey::Fallback(owner.clone()),
+            DataKey::ClaimFee(owner.clone()),
+        ] {
+            if env.storage().persistent().has(&related) {
+                env.storage().persistent().extend_ttl(
+                    &related,
+                    PLAN_TTL_EXTEND_TO,
+                    PLAN_TTL_EXTEND_TO,
+                );
+            }
+        }
+
+        Self::extend_instance_ttl(&env);
+        env.events()
+            .publish((symbol_short!("bump"), owner), PLAN_TTL_EXTEND_TO);
+
+        Ok(PLAN_TTL_EXTEND_TO)
+    }
+
+    /// Anchor the hash of `owner`'s plan document as kept off-chain
+    /// (beneficiaries and terms), so either side of a dispute can show which
+    /// version was in force. Admin only; each amendment replaces the hash.
+    pub fn set_metadata_hash(env: Env, owner: Address, hash: BytesN<32>) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&InstanceDataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+
+        let key = DataKey::MetadataHash(owner.clone());
+        env.storage().persistent().set(&key, &hash);
+        Self::extend_plan_ttl(&env, &key);
+        env.events()
+            .publish((symbol_short!("metadata"), owner), hash);
+        Ok(())
+    }
+
+    pub fn get_metadata_hash(env: Env, owner: Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MetadataHash(owner))
+    }
+
+    /// Anchor the hash of the witness attestations (of the owner's death or
+    /// incapacity) that made `owner`'s plan claimable off-chain. Admin only.
+    pub fn set_attestation_hash(env: Env, owner: Address, hash: BytesN<32>) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&InstanceDataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+
+        let key = DataKey::AttestationHash(owner.clone());
+        env.storage().persistent().set(&key, &hash);
+        Self::extend_plan_ttl(&env, &key);
+        env.events()
+            .publish((symbol_short!("attested"), owner), hash);
+        Ok(())
+    }
+
+    pub fn get_attestation_hash(env: Env, owner: Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AttestationHash(owner))
+    }
+
+    /// Designate guardians who can jointly pause claims, replace a lost
+    /// beneficiary wallet, or veto a triggered claim within
+    /// `challenge_window` seconds. Any action needs approving guardians
+    /// whose weights add up to `threshold`. Replaces an existing set.
+    pub fn set_guardians(
+        env: Env,
+        owner: Address,
+        guardians: Vec<Guardian>,
+        threshold: u32,
+        challenge_window: u64,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+
+        if guardians.is_empty() || guardians.len() > MAX_GUARDIANS {
+            return Err(Error::InvalidGuardianConfig);
+        }
+        let mut total_weight: u32 = 0;
+        for (i, guardian) in guardians.iter().enumerate() {
+            let first = guardians.iter().position(|g| g.address == guardian.address);
+            if guardian.weight == 0 || guardian.address == owner || first != Some(i) {
+                return Err(Error::InvalidGuardianConfig);
+            }
+            total_weight = total_weight
+                .checked_add(guardian.weight)
+                .ok_or(Error::InvalidGuardianConfig)?;
+        }
+        if threshold == 0 || threshold > total_weight {
+            return Err(Error::InvalidGuardianConfig);
+        }
+
+        let count = guardians.len();
+        let key = DataKey::Guardians(owner.clone());
+        let set = GuardianSet {
+            guardians,
+            threshold,
+            challenge_window,
+        };
+        env.storage().persistent().set(&key, &set);
+        Self::extend_plan_ttl(&env, &key);
+        env.events().publish(
+            (symbol_short!("guardians"), owner),
+            (count, threshold, challenge_window),
+        );
+
+        Ok(())
+    }
+
+    /// Remove the plan's guardians, lifting any pause they placed.
+    pub fn remove_guardians(env: Env, owner: Address) -> Result<(), Error> {
+        owner.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Guardians(owner.clone()))
+        {
+            return Err(Error::GuardiansNotSet);
+        }
+
+        Self::remove_guardian_state(&env, &owner);
+        env.events().publish((symbol_short!("guard_rm"), owner), ());
+
+        Ok(())
+    }
+
+    /// Guardians configured for the owner's plan, if any.
+    pub fn get_guardians(env: Env, owner: Address) -> Option<GuardianSet> {
+        env.storage().persistent().get(&DataKey::Guardians(owner))
+    }
+
+    /// Whether guardians have paused claims on the owner's plan.
+    pub fn is_claims_paused(env: Env, owner: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::ClaimsPaused(owner))
+    }
+
+    /// Block `claim` and `trigger_payout` on the owner's plan until
+    /// guardians resume it. `approvers` must meet the guardian threshold
+    /// and each authorize the call.
+    pub fn pause_claims(env: Env, owner: Address, approvers: Vec<Address>) -> Result<(), Error> {
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+        Self::require_guardian_quorum(&env, &owner, &approvers)?;
+
+        let key = DataKey::ClaimsPaused(owner.clone());
+        env.storage()
+            .persistent()
+            .set(&key, &env.ledger().timestamp());
+        Self::extend_plan_ttl(&env, &key);
+        env.events()
+            .publish((symbol_short!("g_pause"), owner), approvers);
+
+        Ok(())
+    }
+
+    /// Lift a guardian pause on the owner's plan.
+    pub fn resume_claims(env: Env, owner: Address, approvers: Vec<Address>) -> Result<(), Error> {
+        Self::require_guardian_quorum(&env, &owner, &approvers)?;
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ClaimsPaused(owner.clone()));
+        env.events()
+            .publish((symbol_short!("g_resume"), owner), approvers);
+
+        Ok(())
+    }
+
+    /// Queue a change pointing a beneficiary's allocation at a new wallet,
+    /// for a beneficiary who has lost their keys. The allocation and payout
+    /// details are kept. Like an owner's change it waits for the change
+    /// delay, the owner can cancel it, and it cannot be queued while a claim
+    /// or another change is pending. Returns the time it takes effect.
+    pub fn replace_beneficiary(
+        env: Env,
+        owner: Address,
+        approvers: Vec<Address>,
+        old_address: Address,
+        new_address: Address,
+    ) -> Result<u64, Error> {
+        Self::load_summary(&env, &owner)?;
+        Self::require_guardian_quorum(&env, &owner, &approvers)?;
+
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::ClaimStatus(owner.clone()))
+        {
+            return Err(Error::ClaimInProgress);
+        }
+        let key = DataKey::PendingChange(owner.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(Error::ChangeAlreadyQueued);
+        }
+
+        let mut beneficiaries = Self::load_beneficiaries(&env, &owner);
+        if beneficiaries.iter().any(|b| b.address == new_address) {
+            return Err(Error::DuplicateBeneficiary);
+        }
+        let index = beneficiaries
+            .iter()
+            .position(|b| b.address == old_address)
+            .ok_or(Error::BeneficiaryNotFound)? as u32;
+
+        let mut beneficiary = beneficiaries.get(index).unwrap();
+        beneficiary.address = new_address.clone();
+        beneficiaries.set(index, beneficiary);
+
+        let now = env.ledger().timestamp();
+        let effective_at = now + Self::change_delay(&env, &owner, now);
+        let pending = PendingBeneficiaryChange {
+            beneficiaries,
+            queued_at: now,
+            effective_at,
+        };
+        env.storage().persistent().set(&key, &pending);
+        Self::extend_plan_ttl(&env, &key);
+        env.events().publish(
+            (symbol_short!("g_benef"), owner),
+            (old_address, new_address, effective_at),
+        );
+
+        Ok(effective_at)
+    }
+
+    /// Set how long beneficiary changes on the owner's plan wait before
+    /// they can be applied, at most 90 days. A longer delay applies at once;
+    /// a shorter one only after the current delay has passed, so a coerced
+    /// owner cannot shorten the window first. Returns when the new delay
+    /// takes effect.
+    pub fn set_change_delay(env: Env, owner: Address, delay: u64) -> Result<u64, Error> {
+        owner.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+        if delay > MAX_CHANGE_DELAY {
+            return Err(Error::InvalidChangeDelay);
+        }
+
+        let now = env.ledger().timestamp();
+        let current = Self::change_delay(&env, &owner, now);
+        let config = if delay >= current {
+            ChangeDelay {
+                delay,
+                lowered_to: None,
+                lowered_at: now,
+            }
+        } else {
+            ChangeDelay {
+                delay: current,
+                lowered_to: Some(delay),
+                lowered_at: now + current,
+            }
+        };
+
+        let key = DataKey::ChangeDelay(owner.clone());
+        env.storage().persistent().set(&key, &config);
+        Self::extend_plan_ttl(&env, &key);
+        env.events().publish(
+            (symbol_short!("chg_delay"), owner),
+            (delay, config.lowered_at),
+        );
+
+        Ok(config.lowered_at)
+    }
+
+    /// Delay currently applied to beneficiary changes on the owner's plan.
+    pub fn get_change_delay(env: Env, owner: Address) -> u64 {
+        Self::change_delay(&env, &owner, env.ledger().timestamp())
+    }
+
+    /// Queue a new beneficiary list for the owner's plan. It can be applied
+    /// with `apply_beneficiary_change` once the change delay has passed and
+    /// cancelled by the owner until then. Returns the time it takes effect.
+    pub fn queue_beneficiary_change(
+        env: Env,
+        owner: Address,
+        beneficiaries: Vec<Beneficiary>,
+    ) -> Result<u64, Error> {
+        owner.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::ClaimStatus(owner.clone()))
+        {
+            return Err(Error::ClaimInProgress);
+        }
+        let key = DataKey::PendingChange(owner.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(Error::ChangeAlreadyQueued);
+        }
+        Self::validate_beneficiaries(&beneficiaries)?;
+
+        let now = env.ledger().timestamp();
+        let effective_at = now + Self::change_delay(&env, &owner, now);
+        let count = beneficiaries.len();
+        let pending = PendingBeneficiaryChange {
+            beneficiaries,
+            queued_at: now,
+            effective_at,
+        };
+        env.storage().persistent().set(&key, &pending);
+        Self::extend_plan_ttl(&env, &key);
+        env.events()
+            .publish((symbol_short!("chg_queue"), owner), (count, effective_at));
+
+        Ok(effective_at)
+    }
+
+    /// Drop the owner's queued beneficiary change.
+    pub fn cancel_beneficiary_change(env: Env, owner: Address) -> Result<(), Error> {
+        owner.require_auth();
+
+        let key = DataKey::PendingChange(owner.clone());
+        let pending: PendingBeneficiaryChange = env
             .storage()
             .persistent()
             .get(&key)
-            .ok_or(Error::PlanNotFound)?;
+            .ok_or(Error::NoPendingChange)?;
+
+        env.storage().persistent().remove(&key);
+        env.events().publish(
+            (symbol_short!("chg_cncl"), owner),
+            (pending.queued_at, pending.effective_at),
+        );
+
+        Ok(())
+    }
+
+    /// Replace the plan's beneficiaries with the queued ones once the
+    /// change delay has passed. Callable by anyone, so a change takes
+    /// effect even if the owner can no longer act. Not allowed while a
+    /// claim is in progress.
+    pub fn apply_beneficiary_change(env: Env, owner: Address) -> Result<(), Error> {
+        let mut plan = Self::load_summary(&env, &owner)?;
+
+        let pending_key = DataKey::PendingChange(owner.clone());
+        let pending: PendingBeneficiaryChange = env
+            .storage()
+            .persistent()
+            .get(&pending_key)
+            .ok_or(Error::NoPendingChange)?;
+        if env.ledger().timestamp() < pending.effective_at {
+            return Err(Error::ChangeNotDue);
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::ClaimStatus(owner.clone()))
+        {
+            return Err(Error::ClaimInProgress);
+        }
+
+        plan.beneficiary_count = pending.beneficiaries.len();
+        env.storage().persistent().remove(&pending_key);
+        Self::save_summary(&env, &plan);
+        Self::save_beneficiaries(&env, &owner, &pending.beneficiaries);
+        env.events().publish(
+            (symbol_short!("chg_apply"), owner),
+            (pending.queued_at, pending.effective_at),
+        );
+
+        Ok(())
+    }
+
+    /// The owner's queued beneficiary change, if any.
+    pub fn get_pending_beneficiary_change(
+        env: Env,
+        owner: Address,
+    ) -> Option<PendingBeneficiaryChange> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingChange(owner))
+    }
+
+    /// Cancel a triggered claim within the guardians' challenge window,
+    /// reactivating the plan as `cancel_claim` does for the owner.
+    pub fn veto_claim(env: Env, owner: Address, approvers: Vec<Address>) -> Result<(), Error> {
+        let mut plan = Self::load_summary(&env, &owner)?;
 
         let claim_key = DataKey::ClaimStatus(owner.clone());
         let claim_time: u64 = env
@@ -284,34 +1276,274 @@ impl InheritanceContract {
             .get(&claim_key)
             .ok_or(Error::PayoutNotTriggered)?;
 
+        let set = Self::require_guardian_quorum(&env, &owner, &approvers)?;
         let current_time = env.ledger().timestamp();
-        if current_time < claim_time + plan.timelock_duration {
+        if current_time >= claim_time + set.challenge_window {
+            return Err(Error::ChallengeWindowClosed);
+        }
+
+        env.storage().persistent().remove(&claim_key);
+
+        plan.is_active = true;
+        plan.last_ping = current_time;
+        Self::save_summary(&env, &plan);
+        env.events()
+            .publish((symbol_short!("g_veto"), owner), (claim_time, approvers));
+
+        Ok(())
+    }
+
+    /// Set how long beneficiaries have to claim a timed-out plan before its
+    /// funds can be escheated, between 30 days and 10 years. Applies to
+    /// every plan, including ones already timed out.
+    pub fn set_claim_window(env: Env, admin: Address, window: u64) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+        if !(MIN_CLAIM_WINDOW..=MAX_CLAIM_WINDOW).contains(&window) {
+            return Err(Error::InvalidClaimWindow);
+        }
+
+        env.storage()
+            .instance()
+            .set(&InstanceDataKey::ClaimWindow, &window);
+        Self::extend_instance_ttl(&env);
+        env.events()
+            .publish((symbol_short!("claim_win"), admin), window);
+
+        Ok(())
+    }
+
+    /// Seconds beneficiaries have to claim a timed-out plan.
+    pub fn get_claim_window(env: Env) -> u64 {
+        Self::claim_window(&env)
+    }
+
+    /// Time after which the owner's plan can no longer be claimed and its
+    /// funds can be escheated.
+    pub fn get_claim_expiry(env: Env, owner: Address) -> Result<u64, Error> {
+        let plan = Self::load_summary(&env, &owner)?;
+        Ok(Self::claim_expiry(&env, &plan))
+    }
+
+    /// Set the limits of a KYC tier. Plans above `max_plan_amount` cannot be
+    /// created by owners of the tier, and payouts are held while a
+    /// beneficiary's share is above `max_claim_payout`. `max_loan_amount`
+    /// is published for the lending contract.
+    pub fn set_tier_limits(
+        env: Env,
+        admin: Address,
+        tier: KycTier,
+        limits: TierLimits,
+    ) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+        if !limits.is_valid() {
+            return Err(Error::InvalidTierLimits);
+        }
+
+        env.storage()
+            .instance()
+            .set(&InstanceDataKey::TierLimits(tier), &limits);
+        Self::extend_instance_ttl(&env);
+        env.events()
+            .publish((symbol_short!("tier_lim"), admin), (tier, limits));
+
+        Ok(())
+    }
+
+    /// Limits of a KYC tier, if the admin has set any.
+    pub fn get_tier_limits(env: Env, tier: KycTier) -> Option<TierLimits> {
+        env.storage()
+            .instance()
+            .get(&InstanceDataKey::TierLimits(tier))
+    }
+
+    /// Record the KYC tier an address has been verified to.
+    pub fn set_kyc_tier(
+        env: Env,
+        admin: Address,
+        address: Address,
+        tier: KycTier,
+    ) -> Result<(), Error> {
+        Self::require_admin(&env, &admin)?;
+
+        let key = DataKey::KycTier(address.clone());
+        if tier == KycTier::Basic {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &tier);
+            Self::extend_plan_ttl(&env, &key);
+        }
+        env.events()
+            .publish((symbol_short!("kyc_tier"), address), tier);
+
+        Ok(())
+    }
+
+    /// KYC tier of an address, `Basic` unless the admin has raised it.
+    pub fn get_kyc_tier(env: Env, address: Address) -> KycTier {
+        Self::kyc_tier(&env, &address)
+    }
+
+    /// Set or clear where the owner's plan goes if no beneficiary claims it
+    /// within the claim window. Without one, it goes to the treasury.
+    pub fn set_fallback_beneficiary(
+        env: Env,
+        owner: Address,
+        fallback: Option<Address>,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Plan(owner.clone()))
+        {
+            return Err(Error::PlanNotFound);
+        }
+
+        let key = DataKey::Fallback(owner.clone());
+        match &fallback {
+            Some(address) => {
+                env.storage().persistent().set(&key, address);
+                Self::extend_plan_ttl(&env, &key);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+        env.events()
+            .publish((symbol_short!("fallback"), owner), fallback);
+
+        Ok(())
+    }
+
+    /// The owner's fallback beneficiary, if one is set.
+    pub fn get_fallback_beneficiary(env: Env, owner: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Fallback(owner))
+    }
+
+    /// Send an unclaimed plan to its fallback beneficiary, or to the
+    /// treasury without one, once the claim window has closed with no claim
+    /// filed. Callable by anyone; deletes the plan like a payout does.
+    pub fn escheat(env: Env, owner: Address) -> Result<Address, Error> {
+        let plan = Self::load_summary(&env, &owner)?;
+
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::ClaimStatus(owner.clone()))
+        {
+            return Err(Error::ClaimInProgress);
+        }
+        if env.ledger().timestamp() < Self::claim_expiry(&env, &plan) {
+            return Err(Error::ClaimWindowOpen);
+        }
+
+        let recipient = match env
+            .storage()
+            .persistent()
+            .get::<_, Address>(&DataKey::Fallback(owner.clone()))
+        {
+            Some(fallback) => fallback,
+            None => {
+                env.storage()
+                    .instance()
+                    .get::<_, FeeConfig>(&InstanceDataKey::FeeConfig)
+                    .ok_or(Error::NotInitialized)?
+                    .treasury
+            }
+        };
+
+        Self::remove_plan(&env, &owner);
+        Self::remove_guardian_state(&env, &owner);
+        Self::remove_change_state(&env, &owner);
+
+        let token_client = soroban_sdk::token::Client::new(&env, &plan.token);
+        token_client.transfer(&env.current_contract_address(), &recipient, &plan.amount);
+        env.events().publish(
+            (symbol_short!("escheat"), owner),
+            (recipient.clone(), plan.token, plan.amount),
+        );
+
+        Ok(recipient)
+    }
+
+    /// Trigger payout to all beneficiaries once the plan is claimable.
+    /// Waits for the longer of the plan timelock and the guardians'
+    /// challenge window, and is blocked while guardians have paused claims
+    /// or while a beneficiary's share is above their KYC tier's limit.
+    /// Takes the plan's claim fee, sending it to the treasury in the same
+    /// transaction, then iterates over beneficiaries, computes pro-rata
+    /// token allocations of the rest using the stored basis points, and
+    /// transfers tokens safely. Emits `payout` with both amounts.
+    /// Remaining dust from integer division is allocated to the last beneficiary.
+    /// Aborts the entire transaction if any single transfer fails.
+    pub fn trigger_payout(env: Env, owner: Address) -> Result<(), Error> {
+        let plan = Self::load_plan(&env, &owner)?;
+
+        let claim_key = DataKey::ClaimStatus(owner.clone());
+        let claim_time: u64 = env
+            .storage()
+            .persistent()
+            .get(&claim_key)
+            .ok_or(Error::PayoutNotTriggered)?;
+
+        Self::ensure_claims_not_paused(&env, &owner)?;
+
+        let current_time = env.ledger().timestamp();
+        let wait = plan
+            .timelock_duration
+            .max(Self::challenge_window(&env, &owner));
+        if current_time < claim_time + wait {
             return Err(Error::TimelockNotExpired);
         }
 
+        let fee =
+            plan.amount * Self::plan_claim_fee(&env, &owner) as i128 / BPS_DENOMINATOR as i128;
+        let treasury = if fee > 0 {
+            Some(
+                env.storage()
+                    .instance()
+                    .get::<_, FeeConfig>(&InstanceDataKey::FeeConfig)
+                    .ok_or(Error::NotInitialized)?
+                    .treasury,
+            )
+        } else {
+            None
+        };
+        let payout = plan.amount - fee;
+
         // Checks-effects-interactions: remove plan before transfers
         // to prevent double payout and guard against re-entrancy
-        env.storage().persistent().remove(&key);
+        Self::remove_plan(&env, &owner);
         env.storage().persistent().remove(&claim_key);
+        Self::remove_guardian_state(&env, &owner);
+        Self::remove_change_state(&env, &owner);
 
         let token_client = soroban_sdk::token::Client::new(&env, &plan.token);
+        if let Some(treasury) = &treasury {
+            token_client.transfer(&env.current_contract_address(), treasury, &fee);
+        }
+
         let n = plan.beneficiaries.len();
-        let mut remaining = plan.amount;
+        let mut remaining = payout;
 
         for (i, beneficiary) in plan.beneficiaries.iter().enumerate() {
             let share = if i == (n - 1) as usize {
                 remaining
             } else {
-                let amount = plan.amount * (beneficiary.allocation_bps as i128) / 10000;
+                let amount = payout * (beneficiary.allocation_bps as i128) / 10000;
                 remaining -= amount;
                 amount
             };
+            Self::check_tier_limit(&env, &beneficiary.address, share, |limits| {
+                limits.max_claim_payout
+            })?;
             token_client.transfer(
                 &env.current_contract_address(),
                 &beneficiary.address,
                 &share,
             );
         }
+        env.events()
+            .publish((symbol_short!("payout"), owner), (plan.token, payout, fee));
 
         Ok(())
     }
@@ -321,16 +1553,10 @@ impl InheritanceContract {
     /// The plan owner can call close_plan() for an early refund.
     #[allow(dead_code)]
     fn deactivate_plan(env: &Env, owner: &Address) -> Result<(), Error> {
-        let key = DataKey::Plan(owner.clone());
-        if !env.storage().persistent().has(&key) {
-            return Err(Error::PlanNotFound);
-        }
-
-        let mut plan: Plan = env.storage().persistent().get(&key).unwrap();
+        let mut plan = Self::load_summary(env, owner)?;
         plan.is_active = false;
 
-        env.storage().persistent().set(&key, &plan);
-        Self::extend_plan_ttl(env, &key);
+        Self::save_summary(env, &plan);
 
         Ok(())
     }
@@ -341,19 +1567,16 @@ impl InheritanceContract {
     pub fn close_plan(env: Env, owner: Address) -> Result<(), Error> {
         owner.require_auth();
 
-        let key = DataKey::Plan(owner.clone());
-        let plan: Plan = env
-            .storage()
-            .persistent()
-            .get(&key)
-            .ok_or(Error::PlanNotFound)?;
+        let plan = Self::load_summary(&env, &owner)?;
 
         let claim_key = DataKey::ClaimStatus(owner.clone());
         if env.storage().persistent().has(&claim_key) {
             env.storage().persistent().remove(&claim_key);
         }
 
-        env.storage().persistent().remove(&key);
+        Self::remove_plan(&env, &owner);
+        Self::remove_guardian_state(&env, &owner);
+        Self::remove_change_state(&env, &owner);
 
         let token_client = soroban_sdk::token::Client::new(&env, &plan.token);
         token_client.transfer(&env.current_contract_address(), &owner, &plan.amount);
@@ -365,19 +1588,16 @@ impl InheritanceContract {
     pub fn reclaim(env: Env, owner: Address) -> Result<(), Error> {
         owner.require_auth();
 
-        let key = DataKey::Plan(owner.clone());
-        let plan: Plan = env
-            .storage()
-            .persistent()
-            .get(&key)
-            .ok_or(Error::PlanNotFound)?;
+        let plan = Self::load_summary(&env, &owner)?;
 
         let claim_key = DataKey::ClaimStatus(owner.clone());
         if env.storage().persistent().has(&claim_key) {
             env.storage().persistent().remove(&claim_key);
         }
 
-        env.storage().persistent().remove(&key);
+        Self::remove_plan(&env, &owner);
+        Self::remove_guardian_state(&env, &owner);
+        Self::remove_change_state(&env, &owner);
 
         let token_client = soroban_sdk::token::Client::new(&env, &plan.token);
         token_client.transfer(&env.current_contract_address(), &owner, &plan.amount);
@@ -388,3 +1608,5 @@ impl InheritanceContract {
 
 #[cfg(test)]
 mod test;
+#[cfg(test)]
+mod test_negative;
@@ -4,9 +4,9 @@ use soroban_sdk::testutils::{Events, Ledger};
 use soroban_sdk::{symbol_short, vec, Address, Env, IntoVal, String, Vec};
 
 // Helper function to deactivate a plan for grace period testing
-fn deactivate_plan_for_testing(env: &Env, contract_id: &Address, owner: &Address) {
+pub(super) fn deactivate_plan_for_testing(env: &Env, contract_id: &Address, owner: &Address) {
     let key = DataKey::Plan(owner.clone());
-    let plan_option: Option<Plan> =
+    let plan_option: Option<PlanSummary> =
         env.as_contract(contract_id, || env.storage().persistent().get(&key));
 
     if let Some(mut plan) = plan_option {
@@ -59,6 +59,7 @@ fn test_create_plan_success() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     // Verify balances
@@ -114,6 +115,7 @@ fn test_ping_updates_last_ping_and_emits_event() {
         &true,
         &500,
         &86400,
+        &None,
     );
     assert_eq!(client.get_plan(&owner).last_ping, start);
 
@@ -147,17 +149,18 @@ fn test_ping_requires_owner_auth() {
 
     let owner = Address::generate(&env);
     let key = DataKey::Plan(owner.clone());
-    let plan = Plan {
+    let plan = PlanSummary {
         owner: owner.clone(),
         token: Address::generate(&env),
         amount: 1,
-        beneficiaries: Vec::new(&env),
+        beneficiary_count: 0,
         last_ping: env.ledger().timestamp(),
         grace_period: 3600,
         earn_yield: false,
         yield_rate_bps: 0,
         is_active: true,
         timelock_duration: 86400,
+        referrer: None,
     };
 
     env.as_contract(&contract_id, || {
@@ -197,6 +200,7 @@ fn test_create_plan_insufficient_balance() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
@@ -232,6 +236,7 @@ fn test_create_plan_negative_or_zero_amount() {
         &true,
         &500,
         &86400,
+        &None,
     );
     assert_eq!(result_zero, Err(Ok(Error::NegativeAmount)));
 
@@ -245,6 +250,7 @@ fn test_create_plan_negative_or_zero_amount() {
         &true,
         &500,
         &86400,
+        &None,
     );
     assert_eq!(result_neg, Err(Ok(Error::NegativeAmount)));
 }
@@ -284,6 +290,7 @@ fn test_create_plan_invalid_basis_points() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     assert_eq!(result, Err(Ok(Error::InvalidBasisPoints)));
@@ -319,6 +326,7 @@ fn test_create_plan_already_exists() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     // Second creation on same owner
@@ -331,6 +339,7 @@ fn test_create_plan_already_exists() {
         &true,
         &500,
         &86400,
+        &None,
     );
     assert_eq!(result2, Err(Ok(Error::PlanAlreadyExists)));
 }
@@ -369,6 +378,7 @@ fn test_trigger_payout_single_beneficiary() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     // Deactivate plan to start grace period
@@ -436,6 +446,7 @@ fn test_trigger_payout_multiple_beneficiaries() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     // Deactivate plan to start grace period
@@ -494,6 +505,7 @@ fn test_trigger_payout_dust_goes_to_last_beneficiary() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Deactivate plan to start grace period
@@ -544,6 +556,7 @@ fn test_trigger_payout_plan_still_active() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Plan is still active — deactivate_plan_for_testing was never called
@@ -586,6 +599,7 @@ fn test_trigger_payout_grace_period_not_met() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Deactivate plan to start grace period
@@ -631,6 +645,7 @@ fn test_trigger_payout_double_payout_prevented() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Deactivate plan to start grace period
@@ -696,6 +711,7 @@ fn test_cancel_claim_success() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Deactivate plan to start grace period
@@ -748,6 +764,7 @@ fn test_reclaim_success() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Owner reclaims before claim
@@ -796,6 +813,7 @@ fn test_ping_success_from_owner_updates_timestamp() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     // Verify initial ping timestamp
@@ -849,6 +867,7 @@ fn test_ping_from_third_party_fails() {
         &true,
         &500,
         &86400,
+        &None,
     );
 
     // Try to ping as third party without auth
@@ -914,6 +933,7 @@ fn test_close_plan_refunds_all_tokens_and_deletes_storage() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Verify tokens are transferred to contract
@@ -964,6 +984,7 @@ fn test_close_plan_requires_owner_auth() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Try to close plan as unauthorized user
@@ -1050,6 +1071,7 @@ fn test_trigger_payout_5_beneficiaries_with_equal_allocations() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Deactivate, claim, and payout
@@ -1119,6 +1141,7 @@ fn test_trigger_payout_10_beneficiaries_unequal_allocations() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     // Deactivate, claim, and payout
@@ -1182,6 +1205,7 @@ fn test_trigger_payout_rounding_with_3_beneficiaries() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     deactivate_plan_for_testing(&env, &contract_id, &owner);
@@ -1243,6 +1267,7 @@ fn test_trigger_payout_after_grace_period_and_timelock_expiry() {
         &false,
         &0,
         &timelock_duration,
+        &None,
     );
 
     // Deactivate plan
@@ -1308,6 +1333,7 @@ fn test_trigger_payout_with_single_beneficiary_receives_all() {
         &false,
         &0,
         &86400,
+        &None,
     );
 
     deactivate_plan_for_testing(&env, &contract_id, &owner);
@@ -1368,6 +1394,7 @@ fn test_create_plan_stores_all_fields_with_multiple_beneficiaries() {
         &true,
         &300,
         &172800,
+        &None,
     );
 
     // Tokens are transferred: owner balance reduced, contract holds the amount
@@ -1411,3 +1438,1348 @@ fn test_get_plan_returns_not_found_for_unknown_owner() {
     let result = client.try_get_plan(&unknown);
     assert_eq!(result, Err(Ok(Error::PlanNotFound)));
 }
+
+/// Verifies that bump_storage restores the full TTL of a plan, its pending
+/// claim and its claim fee after ledgers have elapsed.
+#[test]
+fn test_bump_storage_extends_plan_and_claim_ttl() {
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, contract_id, token_client, token_id, admin, _) = setup_fee_sharing(&env);
+    client.set_claim_fee(&admin, &100);
+
+    let owner = Address::generate(&env);
+    token_client.mint(&owner, &1000);
+
+    let beneficiary = Beneficiary {
+        address: Address::generate(&env),
+        allocation_bps: 10000,
+        fiat_anchor_info: String::from_str(&env, ""),
+    };
+    client.create_plan(
+        &owner,
+        &token_id,
+        &1000,
+        &Vec::from_array(&env, [beneficiary]),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &None,
+    );
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 4000);
+    client.claim(&owner);
+
+    let plan_key = DataKey::Plan(owner.clone());
+    let claim_key = DataKey::ClaimStatus(owner.clone());
+    let fee_key = DataKey::ClaimFee(owner.clone());
+
+    env.ledger()
+        .set_sequence_number(env.ledger().sequence() + 60 * DAY_IN_LEDGERS);
+    let aged_ttl = env.as_contract(&contract_id, || {
+        env.storage().persistent().get_ttl(&plan_key)
+    });
+    assert!(aged_ttl < PLAN_TTL_EXTEND_TO);
+
+    assert_eq!(client.bump_storage(&owner), PLAN_TTL_EXTEND_TO);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&plan_key),
+            PLAN_TTL_EXTEND_TO
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&claim_key),
+            PLAN_TTL_EXTEND_TO
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&fee_key),
+            PLAN_TTL_EXTEND_TO
+        );
+    });
+}
+
+/// Verifies that bump_storage rejects owners without a plan.
+#[test]
+fn test_bump_storage_returns_not_found_for_unknown_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, InheritanceContract);
+    let client = InheritanceContractClient::new(&env, &contract_id);
+
+    let result = client.try_bump_storage(&Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::PlanNotFound)));
+}
+
+pub(super) fn setup_fee_sharing(
+    env: &Env,
+) -> (
+    InheritanceContractClient<'_>,
+    Address,
+    mock_token::MockTokenClient<'_>,
+    Address,
+    Address,
+    Address,
+) {
+    let contract_id = env.register_contract(None, InheritanceContract);
+    let client = InheritanceContractClient::new(env, &contract_id);
+    let token_id = env.register_contract(None, mock_token::MockToken);
+    let token_client = mock_token::MockTokenClient::new(env, &token_id);
+
+    let admin = Address::generate(env);
+    let treasury = Address::generate(env);
+    client.initialize(&admin);
+    // 2% platform fee, a quarter of which goes to the referrer.
+    client.set_fee_config(&admin, &200, &2500, &treasury);
+
+    (client, contract_id, token_client, token_id, admin, treasury)
+}
+
+pub(super) fn single_beneficiary(env: &Env) -> Vec<Beneficiary> {
+    Vec::from_array(
+        env,
+        [Beneficiary {
+            address: Address::generate(env),
+            allocation_bps: 10000,
+            fiat_anchor_info: String::from_str(env, ""),
+        }],
+    )
+}
+
+/// Verifies that the whole platform fee is held for the treasury without a referrer.
+#[test]
+fn test_platform_fee_without_referrer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, _, treasury) = setup_fee_sharing(&env);
+
+    let owner = Address::generate(&env);
+    token_client.mint(&owner, &10000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &10000,
+        &single_beneficiary(&env),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &None,
+    );
+
+    assert_eq!(token_client.balance(&treasury), 0);
+    assert_eq!(token_client.balance(&contract_id), 10000);
+    assert_eq!(client.get_plan(&owner).amount, 9800);
+    assert_eq!(
+        client.get_fee_account(&token_id),
+        FeeAccount {
+            collected: 200,
+            withdrawn: 0
+        }
+    );
+}
+
+/// Verifies that referral shares accrue per referrer and can be claimed once.
+#[test]
+fn test_referral_fees_accrue_and_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, _, treasury) = setup_fee_sharing(&env);
+
+    let referrer = Address::generate(&env);
+    for _ in 0..2 {
+        let owner = Address::generate(&env);
+        token_client.mint(&owner, &10000);
+        client.create_plan(
+            &owner,
+            &token_id,
+            &10000,
+            &single_beneficiary(&env),
+            &3600,
+            &false,
+            &0,
+            &0,
+            &Some(referrer.clone()),
+        );
+        assert_eq!(client.get_plan(&owner).referrer, Some(referrer.clone()));
+    }
+
+    assert_eq!(token_client.balance(&treasury), 0);
+    assert_eq!(client.get_fee_account(&token_id).balance(), 300);
+    assert_eq!(client.get_referral_balance(&referrer, &token_id), 100);
+    assert_eq!(token_client.balance(&contract_id), 20000);
+
+    assert_eq!(client.claim_referral_fees(&referrer, &token_id), 100);
+    assert_eq!(token_client.balance(&referrer), 100);
+    assert_eq!(client.get_referral_balance(&referrer, &token_id), 0);
+    assert_eq!(token_client.balance(&contract_id), 19900);
+
+    let last_event = env.events().all().last().unwrap();
+    assert_eq!(
+        vec![&env, last_event],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("ref_claim"), referrer.clone()).into_val(&env),
+                (token_id.clone(), 100_i128).into_val(&env),
+            ),
+        ]
+    );
+
+    let result = client.try_claim_referral_fees(&referrer, &token_id);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+}
+
+/// Verifies that an owner cannot refer their own plan.
+#[test]
+fn test_create_plan_rejects_self_referral() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, token_client, token_id, _, _) = setup_fee_sharing(&env);
+
+    let owner = Address::generate(&env);
+    token_client.mint(&owner, &10000);
+    let result = client.try_create_plan(
+        &owner,
+        &token_id,
+        &10000,
+        &single_beneficiary(&env),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &Some(owner.clone()),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidReferrer)));
+}
+
+/// Verifies fee configuration is admin-only and bounded.
+#[test]
+fn test_fee_config_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, InheritanceContract);
+    let client = InheritanceContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    assert_eq!(
+        client.try_set_fee_config(&admin, &100, &0, &treasury),
+        Err(Ok(Error::NotInitialized))
+    );
+
+    client.initialize(&admin);
+    assert_eq!(
+        client.try_initialize(&admin),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+    assert_eq!(
+        client.try_set_fee_config(&Address::generate(&env), &100, &0, &treasury),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_fee_config(&admin, &10001, &0, &treasury),
+        Err(Ok(Error::InvalidFeeConfig))
+    );
+
+    client.set_fee_config(&admin, &100, &5000, &treasury);
+    let config = client.get_fee_config().unwrap();
+    assert_eq!(config.platform_fee_bps, 100);
+    assert_eq!(config.referral_fee_bps, 5000);
+    assert_eq!(config.treasury, treasury);
+}
+
+/// Creates a plan paying a 200 platform fee in a fresh fee-sharing setup.
+fn setup_collected_fees(
+    env: &Env,
+) -> (
+    InheritanceContractClient<'_>,
+    mock_token::MockTokenClient<'_>,
+    Address,
+    Address,
+    Address,
+) {
+    let (client, _, token_client, token_id, admin, treasury) = setup_fee_sharing(env);
+    let owner = Address::generate(env);
+    token_client.mint(&owner, &10000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &10000,
+        &single_beneficiary(env),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &None,
+    );
+    (client, token_client, token_id, admin, treasury)
+}
+
+/// Verifies the admin alone can withdraw collected fees to the treasury only.
+#[test]
+fn test_withdraw_fees_to_treasury() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, token_client, token_id, admin, treasury) = setup_collected_fees(&env);
+
+    assert_eq!(
+        client.try_withdraw_fees(&Address::generate(&env), &None, &token_id, &50, &treasury),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &None, &token_id, &50, &Address::generate(&env)),
+        Err(Ok(Error::ApprovalRequired))
+    );
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &None, &token_id, &0, &treasury),
+        Err(Ok(Error::NegativeAmount))
+    );
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &None, &token_id, &201, &treasury),
+        Err(Ok(Error::InsufficientFees))
+    );
+
+    assert_eq!(
+        client.withdraw_fees(&admin, &None, &token_id, &150, &treasury),
+        50
+    );
+    assert_eq!(token_client.balance(&treasury), 150);
+    assert_eq!(
+        client.get_fee_account(&token_id),
+        FeeAccount {
+            collected: 200,
+            withdrawn: 150
+        }
+    );
+
+    let last_event = env.events().all().last().unwrap();
+    assert_eq!(
+        vec![&env, last_event],
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("fee_wd"), token_id.clone()).into_val(&env),
+                (treasury.clone(), 150_i128, admin.clone(), None::<Address>).into_val(&env),
+            ),
+        ]
+    );
+
+    let new_treasury = Address::generate(&env);
+    client.set_treasury(&admin, &new_treasury);
+    assert_eq!(client.get_fee_config().unwrap().treasury, new_treasury);
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &None, &token_id, &50, &treasury),
+        Err(Ok(Error::ApprovalRequired))
+    );
+    client.withdraw_fees(&admin, &None, &token_id, &50, &new_treasury);
+    assert_eq!(token_client.balance(&new_treasury), 50);
+    assert_eq!(client.get_fee_account(&token_id).balance(), 0);
+}
+
+/// Verifies that with a fee approver set, every withdrawal needs its co-signature.
+#[test]
+fn test_withdraw_fees_requires_approver() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, token_client, token_id, admin, treasury) = setup_collected_fees(&env);
+
+    assert_eq!(
+        client.try_set_fee_approver(&admin, &Some(admin.clone())),
+        Err(Ok(Error::InvalidFeeConfig))
+    );
+    let approver = Address::generate(&env);
+    client.set_fee_approver(&admin, &Some(approver.clone()));
+    assert_eq!(client.get_fee_approver(), Some(approver.clone()));
+
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &None, &token_id, &100, &treasury),
+        Err(Ok(Error::ApprovalRequired))
+    );
+    assert_eq!(
+        client.try_withdraw_fees(
+            &admin,
+            &Some(Address::generate(&env)),
+            &token_id,
+            &100,
+            &treasury
+        ),
+        Err(Ok(Error::ApprovalRequired))
+    );
+
+    let ops = Address::generate(&env);
+    client.withdraw_fees(&admin, &Some(approver.clone()), &token_id, &100, &ops);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 2);
+    assert_eq!(auths[0].0, admin);
+    assert_eq!(auths[1].0, approver);
+    assert_eq!(token_client.balance(&ops), 100);
+
+    client.set_fee_approver(&admin, &None);
+    assert_eq!(client.get_fee_approver(), None);
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &None, &token_id, &100, &ops),
+        Err(Ok(Error::ApprovalRequired))
+    );
+}
+
+/// Creates a funded plan for `beneficiary` with a one-day timelock and
+/// three guardians weighted 1, 1 and 2 (threshold 2).
+pub(super) fn setup_guarded_plan(
+    env: &Env,
+    challenge_window: u64,
+) -> (
+    InheritanceContractClient<'_>,
+    Address,
+    mock_token::MockTokenClient<'_>,
+    Address,
+    Address,
+    [Address; 3],
+) {
+    let contract_id = env.register_contract(None, InheritanceContract);
+    let client = InheritanceContractClient::new(env, &contract_id);
+    let token_id = env.register_contract(None, mock_token::MockToken);
+    let token_client = mock_token::MockTokenClient::new(env, &token_id);
+
+    let owner = Address::generate(env);
+    let beneficiary = Address::generate(env);
+    token_client.mint(&owner, &1000);
+    env.ledger().set_timestamp(1_000_000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &1000,
+        &Vec::from_array(
+            env,
+            [Beneficiary {
+                address: beneficiary.clone(),
+                allocation_bps: 10000,
+                fiat_anchor_info: String::from_str(env, ""),
+            }],
+        ),
+        &3600,
+        &false,
+        &0,
+        &86400,
+        &None,
+    );
+
+    let guardians = [
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+    client.set_guardians(
+        &owner,
+        &vec![
+            env,
+            Guardian {
+                address: guardians[0].clone(),
+                weight: 1,
+            },
+            Guardian {
+                address: guardians[1].clone(),
+                weight: 1,
+            },
+            Guardian {
+                address: guardians[2].clone(),
+                weight: 2,
+            },
+        ],
+        &2,
+        &challenge_window,
+    );
+
+    (
+        client,
+        contract_id,
+        token_client,
+        owner,
+        beneficiary,
+        guardians,
+    )
+}
+
+/// Verifies guardian sets are validated and stored.
+#[test]
+fn test_set_guardians_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, _, owner, _, guardians) = setup_guarded_plan(&env, 0);
+
+    let last_event = env.events().all().last().unwrap();
+    assert_eq!(
+        vec![&env, last_event],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("guardians"), owner.clone()).into_val(&env),
+                (3_u32, 2_u32, 0_u64).into_val(&env),
+            ),
+        ]
+    );
+    let set = client.get_guardians(&owner).unwrap();
+    assert_eq!(set.guardians.len(), 3);
+    assert_eq!(set.threshold, 2);
+
+    let guardian = |address: &Address, weight: u32| Guardian {
+        address: address.clone(),
+        weight,
+    };
+    let invalid = [
+        (Vec::new(&env), 1),
+        (vec![&env, guardian(&guardians[0], 0)], 1),
+        (vec![&env, guardian(&owner, 1)], 1),
+        (
+            vec![&env, guardian(&guardians[0], 1), guardian(&guardians[0], 1)],
+            1,
+        ),
+        (vec![&env, guardian(&guardians[0], 1)], 2),
+        (vec![&env, guardian(&guardians[0], 1)], 0),
+    ];
+    for (set, threshold) in invalid {
+        assert_eq!(
+            client.try_set_guardians(&owner, &set, &threshold, &0),
+            Err(Ok(Error::InvalidGuardianConfig))
+        );
+    }
+    assert_eq!(
+        client.try_set_guardians(
+            &Address::generate(&env),
+            &vec![&env, guardian(&guardians[0], 1)],
+            &1,
+            &0
+        ),
+        Err(Ok(Error::PlanNotFound))
+    );
+
+    client.remove_guardians(&owner);
+    assert_eq!(client.get_guardians(&owner), None);
+    assert_eq!(
+        client.try_remove_guardians(&owner),
+        Err(Ok(Error::GuardiansNotSet))
+    );
+}
+
+/// Verifies a guardian quorum can pause and resume claims and payouts.
+#[test]
+fn test_guardian_pause_blocks_claims() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, _, owner, beneficiary, guardians) = setup_guarded_plan(&env, 0);
+    let [g1, g2, g3] = guardians;
+
+    assert_eq!(
+        client.try_pause_claims(&owner, &vec![&env, g1.clone()]),
+        Err(Ok(Error::QuorumNotMet))
+    );
+    assert_eq!(
+        client.try_pause_claims(&owner, &vec![&env, g1.clone(), g1.clone()]),
+        Err(Ok(Error::InvalidGuardianConfig))
+    );
+    assert_eq!(
+        client.try_pause_claims(&owner, &vec![&env, beneficiary.clone()]),
+        Err(Ok(Error::NotGuardian))
+    );
+
+    client.pause_claims(&owner, &vec![&env, g1.clone(), g2.clone()]);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 2);
+    assert_eq!(auths[0].0, g1);
+    assert_eq!(auths[1].0, g2);
+    assert!(client.is_claims_paused(&owner));
+
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(1_000_000 + 4000);
+    assert_eq!(client.try_claim(&owner), Err(Ok(Error::ClaimsPaused)));
+
+    // A single guardian carrying the threshold weight can act alone.
+    client.resume_claims(&owner, &vec![&env, g3]);
+    assert!(!client.is_claims_paused(&owner));
+    client.claim(&owner);
+}
+
+/// Verifies guardians can move a beneficiary's share to a new wallet.
+#[test]
+fn test_guardians_replace_beneficiary() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, _, owner, beneficiary, guardians) = setup_guarded_plan(&env, 0);
+    let approvers = vec![&env, guardians[2].clone()];
+    let new_wallet = Address::generate(&env);
+
+    assert_eq!(
+        client.try_replace_beneficiary(&owner, &approvers, &Address::generate(&env), &new_wallet),
+        Err(Ok(Error::BeneficiaryNotFound))
+    );
+    assert_eq!(
+        client.try_replace_beneficiary(&owner, &approvers, &beneficiary, &beneficiary),
+        Err(Ok(Error::DuplicateBeneficiary))
+    );
+
+    // The replacement waits out the change delay like an owner's change.
+    let effective_at = client.replace_beneficiary(&owner, &approvers, &beneficiary, &new_wallet);
+    assert_eq!(effective_at, 1_000_000 + DEFAULT_CHANGE_DELAY);
+    assert_eq!(
+        client
+            .get_plan(&owner)
+            .beneficiaries
+            .get(0)
+            .unwrap()
+            .address,
+        beneficiary
+    );
+    assert_eq!(
+        client.try_apply_beneficiary_change(&owner),
+        Err(Ok(Error::ChangeNotDue))
+    );
+
+    env.ledger().set_timestamp(effective_at);
+    client.apply_beneficiary_change(&owner);
+    let plan = client.get_plan(&owner);
+    let replaced = plan.beneficiaries.get(0).unwrap();
+    assert_eq!(replaced.address, new_wallet);
+    assert_eq!(replaced.allocation_bps, 10000);
+}
+
+/// Verifies the owner can cancel a guardian replacement before it applies.
+#[test]
+fn test_owner_cancels_guardian_replacement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, _, owner, beneficiary, guardians) = setup_guarded_plan(&env, 0);
+    let approvers = vec![&env, guardians[2].clone()];
+
+    client.replace_beneficiary(&owner, &approvers, &beneficiary, &Address::generate(&env));
+    client.cancel_beneficiary_change(&owner);
+
+    env.ledger().set_timestamp(1_000_000 + DEFAULT_CHANGE_DELAY);
+    assert_eq!(
+        client.try_apply_beneficiary_change(&owner),
+        Err(Ok(Error::NoPendingChange))
+    );
+    assert_eq!(
+        client
+            .get_plan(&owner)
+            .beneficiaries
+            .get(0)
+            .unwrap()
+            .address,
+        beneficiary
+    );
+}
+
+/// Verifies guardians can veto a claim only within the challenge window,
+/// and that payout waits for the window to close.
+#[test]
+fn test_guardian_veto_within_challenge_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let challenge_window = 2 * 86400;
+    let (client, contract_id, token_client, owner, beneficiary, guardians) =
+        setup_guarded_plan(&env, challenge_window);
+    let approvers = vec![&env, guardians[0].clone(), guardians[1].clone()];
+
+    assert_eq!(
+        client.try_veto_claim(&owner, &approvers),
+        Err(Ok(Error::PayoutNotTriggered))
+    );
+
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    let claimed_at = 1_000_000 + 4000;
+    env.ledger().set_timestamp(claimed_at);
+    client.claim(&owner);
+
+    env.ledger().set_timestamp(claimed_at + 3600);
+    client.veto_claim(&owner, &approvers);
+    let plan = client.get_plan(&owner);
+    assert!(plan.is_active);
+    assert_eq!(plan.last_ping, claimed_at + 3600);
+    assert_eq!(
+        client.try_trigger_payout(&owner),
+        Err(Ok(Error::PayoutNotTriggered))
+    );
+
+    // Claim again; the timelock has passed but the challenge window has not.
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    let claimed_at = claimed_at + 3600 + 4000;
+    env.ledger().set_timestamp(claimed_at);
+    client.claim(&owner);
+    env.ledger().set_timestamp(claimed_at + 86400);
+    assert_eq!(
+        client.try_trigger_payout(&owner),
+        Err(Ok(Error::TimelockNotExpired))
+    );
+
+    env.ledger().set_timestamp(claimed_at + challenge_window);
+    assert_eq!(
+        client.try_veto_claim(&owner, &approvers),
+        Err(Ok(Error::ChallengeWindowClosed))
+    );
+    client.trigger_payout(&owner);
+    assert_eq!(token_client.balance(&beneficiary), 1000);
+    assert_eq!(client.get_guardians(&owner), None);
+}
+
+/// Verifies closing a plan clears its guardians.
+#[test]
+fn test_close_plan_clears_guardians() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, _, owner, _, guardians) = setup_guarded_plan(&env, 0);
+
+    client.pause_claims(&owner, &vec![&env, guardians[2].clone()]);
+    client.close_plan(&owner);
+
+    assert_eq!(client.get_guardians(&owner), None);
+    assert!(!client.is_claims_paused(&owner));
+}
+
+pub(super) fn beneficiary_list(env: &Env, address: &Address) -> Vec<Beneficiary> {
+    Vec::from_array(
+        env,
+        [Beneficiary {
+            address: address.clone(),
+            allocation_bps: 10000,
+            fiat_anchor_info: String::from_str(env, ""),
+        }],
+    )
+}
+
+/// Verifies a queued beneficiary change only applies after the delay and
+/// emits events when queued and applied.
+#[test]
+fn test_beneficiary_change_waits_for_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, _, owner, _, _) = setup_guarded_plan(&env, 0);
+    let heir = Address::generate(&env);
+    let queued_at = 1_000_000;
+
+    assert_eq!(client.get_change_delay(&owner), 7 * 86400);
+    let effective_at = client.queue_beneficiary_change(&owner, &beneficiary_list(&env, &heir));
+    assert_eq!(effective_at, queued_at + 7 * 86400);
+    let last_event = env.events().all().last().unwrap();
+    assert_eq!(
+        vec![&env, last_event],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("chg_queue"), owner.clone()).into_val(&env),
+                (1_u32, effective_at).into_val(&env),
+            ),
+        ]
+    );
+    assert_eq!(
+        client.try_queue_beneficiary_change(&owner, &beneficiary_list(&env, &heir)),
+        Err(Ok(Error::ChangeAlreadyQueued))
+    );
+
+    env.ledger().set_timestamp(effective_at - 1);
+    assert_eq!(
+        client.try_apply_beneficiary_change(&owner),
+        Err(Ok(Error::ChangeNotDue))
+    );
+
+    env.ledger().set_timestamp(effective_at);
+    client.apply_beneficiary_change(&owner);
+    let last_event = env.events().all().last().unwrap();
+    assert_eq!(
+        vec![&env, last_event],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("chg_apply"), owner.clone()).into_val(&env),
+                (queued_at, effective_at).into_val(&env),
+            ),
+        ]
+    );
+    let plan = client.get_plan(&owner);
+    assert_eq!(plan.beneficiaries.get(0).unwrap().address, heir);
+    assert_eq!(client.get_pending_beneficiary_change(&owner), None);
+}
+
+/// Verifies the owner can cancel a queued change during the delay.
+#[test]
+fn test_cancel_beneficiary_change() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, _, owner, beneficiary, _) = setup_guarded_plan(&env, 0);
+    let heir = Address::generate(&env);
+
+    assert_eq!(
+        client.try_cancel_beneficiary_change(&owner),
+        Err(Ok(Error::NoPendingChange))
+    );
+    let effective_at = client.queue_beneficiary_change(&owner, &beneficiary_list(&env, &heir));
+    client.cancel_beneficiary_change(&owner);
+    let last_event = env.events().all().last().unwrap();
+    assert_eq!(
+        vec![&env, last_event],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("chg_cncl"), owner.clone()).into_val(&env),
+                (1_000_000_u64, effective_at).into_val(&env),
+            ),
+        ]
+    );
+
+    env.ledger().set_timestamp(effective_at);
+    assert_eq!(
+        client.try_apply_beneficiary_change(&owner),
+        Err(Ok(Error::NoPendingChange))
+    );
+    let plan = client.get_plan(&owner);
+    assert_eq!(plan.beneficiaries.get(0).unwrap().address, beneficiary);
+}
+
+/// Verifies queued beneficiary lists are validated.
+#[test]
+fn test_queue_beneficiary_change_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, _, owner, _, _) = setup_guarded_plan(&env, 0);
+    let heir = Address::generate(&env);
+
+    let mut short = beneficiary_list(&env, &heir);
+    short.set(
+        0,
+        Beneficiary {
+            address: heir.clone(),
+            allocation_bps: 9000,
+            fiat_anchor_info: String::from_str(&env, ""),
+        },
+    );
+    assert_eq!(
+        client.try_queue_beneficiary_change(&owner, &short),
+        Err(Ok(Error::InvalidBasisPoints))
+    );
+
+    let half = Beneficiary {
+        address: heir.clone(),
+        allocation_bps: 5000,
+        fiat_anchor_info: String::from_str(&env, ""),
+    };
+    assert_eq!(
+        client.try_queue_beneficiary_change(&owner, &vec![&env, half.clone(), half]),
+        Err(Ok(Error::DuplicateBeneficiary))
+    );
+    assert_eq!(
+        client
+            .try_queue_beneficiary_change(&Address::generate(&env), &beneficiary_list(&env, &heir)),
+        Err(Ok(Error::PlanNotFound))
+    );
+}
+
+/// Verifies a longer delay applies at once and a shorter one only after
+/// the current delay.
+#[test]
+fn test_change_delay_lowering_waits_out_current_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, _, owner, _, _) = setup_guarded_plan(&env, 0);
+    let now = 1_000_000;
+
+    assert_eq!(
+        client.try_set_change_delay(&owner, &(91 * 86400)),
+        Err(Ok(Error::InvalidChangeDelay))
+    );
+    assert_eq!(client.set_change_delay(&owner, &(30 * 86400)), now);
+    assert_eq!(client.get_change_delay(&owner), 30 * 86400);
+
+    let lowered_at = client.set_change_delay(&owner, &3600);
+    assert_eq!(lowered_at, now + 30 * 86400);
+    assert_eq!(client.get_change_delay(&owner), 30 * 86400);
+    assert_eq!(
+        client.queue_beneficiary_change(&owner, &beneficiary_list(&env, &Address::generate(&env))),
+        now + 30 * 86400
+    );
+
+    env.ledger().set_timestamp(lowered_at);
+    assert_eq!(client.get_change_delay(&owner), 3600);
+}
+
+/// Verifies changes cannot be queued or applied while a claim is pending.
+#[test]
+fn test_beneficiary_change_blocked_during_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, _, owner, _, _) = setup_guarded_plan(&env, 0);
+    let heir = Address::generate(&env);
+
+    let effective_at = client.queue_beneficiary_change(&owner, &beneficiary_list(&env, &heir));
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(1_000_000 + 4000);
+    client.claim(&owner);
+
+    env.ledger().set_timestamp(effective_at);
+    assert_eq!(
+        client.try_apply_beneficiary_change(&owner),
+        Err(Ok(Error::ClaimInProgress))
+    );
+    client.cancel_beneficiary_change(&owner);
+    assert_eq!(
+        client.try_queue_beneficiary_change(&owner, &beneficiary_list(&env, &heir)),
+        Err(Ok(Error::ClaimInProgress))
+    );
+}
+
+#[test]
+fn test_set_metadata_hash_requires_admin_and_plan() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, token_client, token_id, admin, _) = setup_fee_sharing(&env);
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[7; 32]);
+    assert_eq!(
+        client.try_set_metadata_hash(&owner, &hash),
+        Err(Ok(Error::PlanNotFound))
+    );
+
+    token_client.mint(&owner, &10000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &10000,
+        &single_beneficiary(&env),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &None,
+    );
+    assert_eq!(client.get_metadata_hash(&owner), None);
+
+    client.set_metadata_hash(&owner, &hash);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, admin);
+    assert_eq!(client.get_metadata_hash(&owner), Some(hash));
+
+    let amended = BytesN::from_array(&env, &[9; 32]);
+    client.set_metadata_hash(&owner, &amended);
+    assert_eq!(client.get_metadata_hash(&owner), Some(amended));
+
+    client.close_plan(&owner);
+    assert_eq!(client.get_metadata_hash(&owner), None);
+}
+
+#[test]
+fn test_set_metadata_hash_before_initialize_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, InheritanceContract);
+    let client = InheritanceContractClient::new(&env, &contract_id);
+
+    let result = client.try_set_metadata_hash(
+        &Address::generate(&env),
+        &BytesN::from_array(&env, &[1; 32]),
+    );
+    assert_eq!(result, Err(Ok(Error::NotInitialized)));
+}
+
+#[test]
+fn test_set_attestation_hash_requires_admin_and_plan() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, token_client, token_id, admin, _) = setup_fee_sharing(&env);
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[3; 32]);
+    assert_eq!(
+        client.try_set_attestation_hash(&owner, &hash),
+        Err(Ok(Error::PlanNotFound))
+    );
+
+    token_client.mint(&owner, &10000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &10000,
+        &single_beneficiary(&env),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &None,
+    );
+    assert_eq!(client.get_attestation_hash(&owner), None);
+
+    client.set_attestation_hash(&owner, &hash);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, admin);
+    assert_eq!(client.get_attestation_hash(&owner), Some(hash));
+
+    client.close_plan(&owner);
+    assert_eq!(client.get_attestation_hash(&owner), None);
+}
+
+/// Verifies the claim window is bounded and closes claims once it passes.
+#[test]
+fn test_claim_window_closes_claims() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, admin, _) = setup_fee_sharing(&env);
+
+    assert_eq!(client.get_claim_window(), 365 * 86400);
+    assert_eq!(
+        client.try_set_claim_window(&admin, &(29 * 86400)),
+        Err(Ok(Error::InvalidClaimWindow))
+    );
+    assert_eq!(
+        client.try_set_claim_window(&Address::generate(&env), &(30 * 86400)),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.set_claim_window(&admin, &(30 * 86400));
+
+    let owner = Address::generate(&env);
+    token_client.mint(&owner, &10000);
+    env.ledger().set_timestamp(1_000_000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &10000,
+        &single_beneficiary(&env),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &None,
+    );
+    let expiry = client.get_claim_expiry(&owner);
+    assert_eq!(expiry, 1_000_000 + 3600 + 30 * 86400);
+
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(expiry);
+    assert_eq!(client.try_claim(&owner), Err(Ok(Error::ClaimWindowClosed)));
+}
+
+/// Verifies unclaimed plans go to the fallback beneficiary, or to the
+/// treasury without one, and only once the window has closed unclaimed.
+#[test]
+fn test_escheat_unclaimed_plans() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, _, treasury) = setup_fee_sharing(&env);
+    env.ledger().set_timestamp(1_000_000);
+
+    let mut owners = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    for owner in owners.iter_mut() {
+        token_client.mint(owner, &10000);
+        client.create_plan(
+            owner,
+            &token_id,
+            &10000,
+            &single_beneficiary(&env),
+            &3600,
+            &false,
+            &0,
+            &0,
+            &None,
+        );
+    }
+    let [with_fallback, without_fallback, claimed] = owners;
+    let fallback = Address::generate(&env);
+    client.set_fallback_beneficiary(&with_fallback, &Some(fallback.clone()));
+    assert_eq!(
+        client.get_fallback_beneficiary(&with_fallback),
+        Some(fallback.clone())
+    );
+
+    deactivate_plan_for_testing(&env, &contract_id, &claimed);
+    env.ledger().set_timestamp(1_000_000 + 4000);
+    client.claim(&claimed);
+
+    let expiry = client.get_claim_expiry(&with_fallback);
+    assert_eq!(
+        client.try_escheat(&with_fallback),
+        Err(Ok(Error::ClaimWindowOpen))
+    );
+
+    env.ledger().set_timestamp(expiry);
+    assert_eq!(client.escheat(&with_fallback), fallback);
+    assert_eq!(token_client.balance(&fallback), 9800);
+    assert_eq!(client.get_fallback_beneficiary(&with_fallback), None);
+    assert_eq!(
+        client.try_get_plan(&with_fallback),
+        Err(Ok(Error::PlanNotFound))
+    );
+
+    let treasury_before = token_client.balance(&treasury);
+    assert_eq!(client.escheat(&without_fallback), treasury);
+    assert_eq!(token_client.balance(&treasury), treasury_before + 9800);
+
+    assert_eq!(
+        client.try_escheat(&claimed),
+        Err(Ok(Error::ClaimInProgress))
+    );
+}
+
+/// Verifies tier limits cap plan sizes for owners and hold payouts until the
+/// beneficiary's tier allows their share.
+#[test]
+fn test_kyc_tier_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, admin, _) = setup_fee_sharing(&env);
+    env.ledger().set_timestamp(1_000_000);
+
+    let limits = |max: i128| TierLimits {
+        max_plan_amount: max,
+        max_loan_amount: max / 2,
+        max_claim_payout: max,
+    };
+    assert_eq!(client.get_tier_limits(&KycTier::Basic), None);
+    assert_eq!(
+        client.try_set_tier_limits(&admin, &KycTier::Basic, &limits(-1)),
+        Err(Ok(Error::InvalidTierLimits))
+    );
+    assert_eq!(
+        client.try_set_tier_limits(&Address::generate(&env), &KycTier::Basic, &limits(5000)),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.set_tier_limits(&admin, &KycTier::Basic, &limits(5000));
+    client.set_tier_limits(&admin, &KycTier::Verified, &limits(50_000));
+    assert_eq!(
+        client.get_tier_limits(&KycTier::Verified),
+        Some(limits(50_000))
+    );
+
+    let owner = Address::generate(&env);
+    token_client.mint(&owner, &10000);
+    let beneficiaries = single_beneficiary(&env);
+    let heir = beneficiaries.get(0).unwrap().address;
+    let create = || {
+        client.try_create_plan(
+            &owner,
+            &token_id,
+            &10000,
+            &beneficiaries,
+            &3600,
+            &false,
+            &0,
+            &0,
+            &None,
+        )
+    };
+    assert_eq!(client.get_kyc_tier(&owner), KycTier::Basic);
+    assert_eq!(create(), Err(Ok(Error::TierLimitExceeded)));
+
+    client.set_kyc_tier(&admin, &owner, &KycTier::Verified);
+    assert_eq!(client.get_kyc_tier(&owner), KycTier::Verified);
+    assert_eq!(create(), Ok(Ok(())));
+
+    // The basic-tier heir's share is above their limit until verified.
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(1_000_000 + 4000);
+    client.claim(&owner);
+    assert_eq!(
+        client.try_trigger_payout(&owner),
+        Err(Ok(Error::TierLimitExceeded))
+    );
+    client.set_kyc_tier(&admin, &heir, &KycTier::Verified);
+    client.trigger_payout(&owner);
+    assert_eq!(token_client.balance(&heir), 9800);
+}
+
+/// Verifies the claim fee rate is fixed at plan creation and paid to the
+/// treasury alongside the beneficiaries at payout.
+#[test]
+fn test_claim_fee_paid_to_treasury_at_payout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, admin, treasury) = setup_fee_sharing(&env);
+    env.ledger().set_timestamp(1_000_000);
+
+    assert_eq!(
+        client.try_set_claim_fee(&admin, &10001),
+        Err(Ok(Error::InvalidFeeConfig))
+    );
+    assert_eq!(
+        client.try_set_claim_fee(&Address::generate(&env), &100),
+        Err(Ok(Error::Unauthorized))
+    );
+    // 1% of the plan amount at payout.
+    client.set_claim_fee(&admin, &100);
+    assert_eq!(client.get_claim_fee(), 100);
+
+    let owner = Address::generate(&env);
+    let (first, second) = (Address::generate(&env), Address::generate(&env));
+    token_client.mint(&owner, &10000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &10000,
+        &Vec::from_array(
+            &env,
+            [
+                Beneficiary {
+                    address: first.clone(),
+                    allocation_bps: 5000,
+                    fiat_anchor_info: String::from_str(&env, ""),
+                },
+                Beneficiary {
+                    address: second.clone(),
+                    allocation_bps: 5000,
+                    fiat_anchor_info: String::from_str(&env, ""),
+                },
+            ],
+        ),
+        &3600,
+        &false,
+        &0,
+        &0,
+        &None,
+    );
+    // A later rate change does not reach the existing plan.
+    client.set_claim_fee(&admin, &500);
+    assert_eq!(client.get_plan_claim_fee(&owner), 100);
+
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(1_000_000 + 4000);
+    client.claim(&owner);
+    client.trigger_payout(&owner);
+
+    // 9800 after the platform fee; 98 of it is the claim fee.
+    assert_eq!(token_client.balance(&treasury), 98);
+    assert_eq!(token_client.balance(&first), 4851);
+    assert_eq!(token_client.balance(&second), 4851);
+    let last_event = env.events().all().last().unwrap();
+    assert_eq!(
+        vec![&env, last_event],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("payout"), owner.clone()).into_val(&env),
+                (token_id.clone(), 9702_i128, 98_i128).into_val(&env),
+            ),
+        ]
+    );
+    assert_eq!(
+        client.try_get_plan_claim_fee(&owner),
+        Err(Ok(Error::PlanNotFound))
+    );
+    let fee_key = DataKey::ClaimFee(owner);
+    assert!(!env.as_contract(&contract_id, || env.storage().persistent().has(&fee_key)));
+}
+
+/// A plan is stored as a summary entry and a separate beneficiary list, so
+/// pinging, the timer queries and `get_plan_summary` cost the same however
+/// many beneficiaries the plan has. Only `get_plan` reads the list.
+#[test]
+fn test_hot_paths_do_not_read_beneficiary_list() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, InheritanceContract);
+    let client = InheritanceContractClient::new(&env, &contract_id);
+    let token_id = env.register_contract(None, mock_token::MockToken);
+    let token_client = mock_token::MockTokenClient::new(&env, &token_id);
+
+    let create = |count: u32| {
+        let owner = Address::generate(&env);
+        token_client.mint(&owner, &10000);
+        let mut beneficiaries = Vec::new(&env);
+        for _ in 0..count {
+            beneficiaries.push_back(Beneficiary {
+                address: Address::generate(&env),
+                allocation_bps: 10000 / count,
+                fiat_anchor_info: String::from_str(&env, ""),
+            });
+        }
+        client.create_plan(
+            &owner,
+            &token_id,
+            &10000,
+            &beneficiaries,
+            &3600,
+            &false,
+            &0,
+            &0,
+            &None,
+        );
+        owner
+    };
+    let small = create(1);
+    let large = create(50);
+
+    let (summary, beneficiaries) = env.as_contract(&contract_id, || {
+        let storage = env.storage().persistent();
+        (
+            storage
+                .get::<_, PlanSummary>(&DataKey::Plan(large.clone()))
+                .unwrap(),
+            storage
+                .get::<_, Vec<Beneficiary>>(&DataKey::Beneficiaries(large.clone()))
+                .unwrap(),
+        )
+    });
+    assert_eq!(summary.beneficiary_count, 50);
+    assert_eq!(beneficiaries.len(), 50);
+    assert_eq!(client.get_plan_summary(&large), summary);
+    assert_eq!(client.get_plan(&large).beneficiaries, beneficiaries);
+
+    // Host objects the call visits, which grows with every entry decoded.
+    let visited = |call: &dyn Fn()| {
+        env.budget().reset_unlimited();
+        call();
+        env.budget()
+            .tracker(soroban_sdk::xdr::ContractCostType::VisitObject)
+            .iterations
+    };
+    assert_eq!(
+        visited(&|| client.ping(&small)),
+        visited(&|| client.ping(&large))
+    );
+    assert_eq!(
+        visited(&|| {
+            client.is_plan_timed_out(&small);
+        }),
+        visited(&|| {
+            client.is_plan_timed_out(&large);
+        })
+    );
+    assert_eq!(
+        visited(&|| {
+            client.get_plan_summary(&small);
+        }),
+        visited(&|| {
+            client.get_plan_summary(&large);
+        })
+    );
+    assert!(
+        visited(&|| {
+            client.get_plan(&large);
+        }) > visited(&|| {
+            client.get_plan(&small);
+        })
+    );
+
+    client.close_plan(&large);
+    assert!(!env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Beneficiaries(large.clone()))
+    }));
+}
@@ -0,0 +1,453 @@
+//! Negative paths: every error the contract can return, and every
+//! entrypoint that needs a signature refusing calls without one. Each
+//! rejected call is checked to leave the ledger exactly as it found it.
+
+extern crate std;
+
+use super::test::{
+    beneficiary_list, deactivate_plan_for_testing, setup_fee_sharing, single_beneficiary,
+};
+use super::*;
+use core::fmt::Debug;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::testutils::Ledger;
+use soroban_sdk::{vec, Address, BytesN, Env, String, Vec};
+
+/// Runs each `client.try_*` call and checks it was refused without
+/// touching the ledger.
+///
+/// Without a mode, no signatures are mocked and each call must fail on
+/// authorization rather than validation, so its arguments should be ones
+/// that would succeed if signed. With `wrong_admin`, all signatures are
+/// mocked and each call, made in the name of someone other than the admin,
+/// must fail with `Unauthorized`.
+///
+/// New entrypoints that take a signature belong in one of the matrices
+/// below.
+macro_rules! auth_matrix {
+    ($env:expr, [$($call:expr),+ $(,)?]) => {
+        $(
+            $env.mock_auths(&[]);
+            let before = $env.to_ledger_snapshot().ledger_entries;
+            let result = $call;
+            assert!(
+                matches!(result, Err(Err(_))),
+                "`{}` was not refused for lack of a signature: {:?}",
+                stringify!($call),
+                result,
+            );
+            assert!(
+                $env.to_ledger_snapshot().ledger_entries == before,
+                "`{}` changed the ledger",
+                stringify!($call),
+            );
+        )+
+    };
+    ($env:expr, wrong_admin [$($call:expr),+ $(,)?]) => {
+        $(
+            $env.mock_all_auths();
+            let before = $env.to_ledger_snapshot().ledger_entries;
+            let result = $call;
+            assert!(
+                matches!(result, Err(Ok(Error::Unauthorized))),
+                "`{}` was not refused for a non-admin: {:?}",
+                stringify!($call),
+                result,
+            );
+            assert!(
+                $env.to_ledger_snapshot().ledger_entries == before,
+                "`{}` changed the ledger",
+                stringify!($call),
+            );
+        )+
+    };
+}
+
+/// Collects the errors seen by [`Rejections::expect`].
+struct Rejections<'a> {
+    env: &'a Env,
+    seen: std::vec::Vec<Error>,
+}
+
+impl<'a> Rejections<'a> {
+    fn new(env: &'a Env) -> Self {
+        Self {
+            env,
+            seen: std::vec::Vec::new(),
+        }
+    }
+
+    /// Asserts `call` fails with `expected` and leaves the ledger unchanged.
+    fn expect<T, E>(&mut self, expected: Error, call: impl FnOnce() -> Result<T, Result<Error, E>>)
+    where
+        T: Debug,
+        E: Debug,
+    {
+        let before = self.env.to_ledger_snapshot().ledger_entries;
+        let result = call();
+        match &result {
+            Err(Ok(error)) if *error == expected => {}
+            _ => panic!("expected {expected:?}, got {result:?}"),
+        }
+        assert!(
+            self.env.to_ledger_snapshot().ledger_entries == before,
+            "{expected:?} changed the ledger"
+        );
+        self.seen.push(expected);
+    }
+
+    fn assert_all_seen(&self) {
+        for error in Error::ALL {
+            assert!(
+                self.seen.contains(&error),
+                "{} is never triggered",
+                error.name()
+            );
+        }
+    }
+}
+
+fn weighted_guardians(env: &Env, guardians: &[Address; 3]) -> Vec<Guardian> {
+    vec![
+        env,
+        Guardian {
+            address: guardians[0].clone(),
+            weight: 1,
+        },
+        Guardian {
+            address: guardians[1].clone(),
+            weight: 1,
+        },
+        Guardian {
+            address: guardians[2].clone(),
+            weight: 2,
+        },
+    ]
+}
+
+/// Verifies every error variant is returned somewhere and none of the
+/// failing calls leave a trace in storage or token balances.
+#[test]
+fn test_every_error_variant_leaves_state_unchanged() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, admin, treasury) = setup_fee_sharing(&env);
+    let mut rejections = Rejections::new(&env);
+    env.ledger().set_timestamp(1_000_000);
+
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let heir = Address::generate(&env);
+    token_client.mint(&owner, &10_000);
+    let beneficiaries = beneficiary_list(&env, &heir);
+    let create = |amount: i128, beneficiaries: &Vec<Beneficiary>, referrer: Option<Address>| {
+        client.try_create_plan(
+            &owner,
+            &token_id,
+            &amount,
+            beneficiaries,
+            &3600,
+            &false,
+            &0,
+            &86400,
+            &referrer,
+        )
+    };
+
+    // Admin configuration.
+    rejections.expect(Error::AlreadyInitialized, || client.try_initialize(&admin));
+    rejections.expect(Error::Unauthorized, || {
+        client.try_set_kyc_tier(&stranger, &owner, &KycTier::Verified)
+    });
+    rejections.expect(Error::InvalidFeeConfig, || {
+        client.try_set_fee_config(&admin, &10_001, &0, &treasury)
+    });
+    rejections.expect(Error::InvalidTierLimits, || {
+        client.try_set_tier_limits(
+            &admin,
+            &KycTier::Basic,
+            &TierLimits {
+                max_plan_amount: -1,
+                max_loan_amount: 0,
+                max_claim_payout: 0,
+            },
+        )
+    });
+    rejections.expect(Error::InvalidClaimWindow, || {
+        client.try_set_claim_window(&admin, &0)
+    });
+    let uninitialized =
+        InheritanceContractClient::new(&env, &env.register_contract(None, InheritanceContract));
+    rejections.expect(Error::NotInitialized, || {
+        uninitialized.try_set_kyc_tier(&admin, &owner, &KycTier::Verified)
+    });
+
+    // Plan creation.
+    let mut crowd = Vec::new(&env);
+    for _ in 0..=MAX_BENEFICIARIES {
+        crowd.push_back(Beneficiary {
+            address: Address::generate(&env),
+            allocation_bps: 0,
+            fiat_anchor_info: String::from_str(&env, ""),
+        });
+    }
+    rejections.expect(Error::TooManyBeneficiaries, || create(1000, &crowd, None));
+    rejections.expect(Error::InvalidReferrer, || {
+        create(1000, &beneficiaries, Some(owner.clone()))
+    });
+    rejections.expect(Error::NegativeAmount, || create(0, &beneficiaries, None));
+    let mut half = beneficiaries.clone();
+    half.set(
+        0,
+        Beneficiary {
+            allocation_bps: 5000,
+            ..beneficiaries.get(0).unwrap()
+        },
+    );
+    rejections.expect(Error::InvalidBasisPoints, || create(1000, &half, None));
+    rejections.expect(Error::InsufficientBalance, || {
+        create(20_000, &beneficiaries, None)
+    });
+    client.set_tier_limits(
+        &admin,
+        &KycTier::Basic,
+        &TierLimits {
+            max_plan_amount: 5000,
+            max_loan_amount: 0,
+            max_claim_payout: 5000,
+        },
+    );
+    rejections.expect(Error::TierLimitExceeded, || {
+        create(6000, &beneficiaries, None)
+    });
+    create(1000, &beneficiaries, None).unwrap().unwrap();
+    rejections.expect(Error::PlanAlreadyExists, || {
+        create(1000, &beneficiaries, None)
+    });
+    rejections.expect(Error::PlanNotFound, || client.try_ping(&stranger));
+    rejections.expect(Error::InactivityPeriodNotMet, || client.try_claim(&owner));
+    rejections.expect(Error::PayoutNotTriggered, || {
+        client.try_cancel_claim(&owner)
+    });
+
+    // Fees: the plan paid a 20 token platform fee.
+    rejections.expect(Error::NothingToClaim, || {
+        client.try_claim_referral_fees(&stranger, &token_id)
+    });
+    rejections.expect(Error::InsufficientFees, || {
+        client.try_withdraw_fees(&admin, &None, &token_id, &21, &treasury)
+    });
+    rejections.expect(Error::ApprovalRequired, || {
+        client.try_withdraw_fees(&admin, &None, &token_id, &20, &stranger)
+    });
+
+    // Beneficiary changes.
+    rejections.expect(Error::InvalidChangeDelay, || {
+        client.try_set_change_delay(&owner, &(MAX_CHANGE_DELAY + 1))
+    });
+    rejections.expect(Error::NoPendingChange, || {
+        client.try_cancel_beneficiary_change(&owner)
+    });
+    let mut twice = half.clone();
+    twice.push_back(half.get(0).unwrap());
+    rejections.expect(Error::DuplicateBeneficiary, || {
+        client.try_queue_beneficiary_change(&owner, &twice)
+    });
+    client.queue_beneficiary_change(&owner, &single_beneficiary(&env));
+    rejections.expect(Error::ChangeNotDue, || {
+        client.try_apply_beneficiary_change(&owner)
+    });
+    rejections.expect(Error::ChangeAlreadyQueued, || {
+        client.try_queue_beneficiary_change(&owner, &single_beneficiary(&env))
+    });
+    client.cancel_beneficiary_change(&owner);
+
+    // Guardians.
+    rejections.expect(Error::GuardiansNotSet, || {
+        client.try_remove_guardians(&owner)
+    });
+    rejections.expect(Error::InvalidGuardianConfig, || {
+        client.try_set_guardians(&owner, &Vec::new(&env), &1, &0)
+    });
+    let guardians = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    client.set_guardians(&owner, &weighted_guardians(&env, &guardians), &2, &3600);
+    rejections.expect(Error::NotGuardian, || {
+        client.try_pause_claims(&owner, &vec![&env, stranger.clone()])
+    });
+    rejections.expect(Error::QuorumNotMet, || {
+        client.try_pause_claims(&owner, &vec![&env, guardians[0].clone()])
+    });
+    rejections.expect(Error::BeneficiaryNotFound, || {
+        client.try_replace_beneficiary(
+            &owner,
+            &vec![&env, guardians[2].clone()],
+            &stranger,
+            &Address::generate(&env),
+        )
+    });
+    // A guardian replacement is queued, not applied.
+    let heir = beneficiaries.get(0).unwrap().address;
+    client.replace_beneficiary(
+        &owner,
+        &vec![&env, guardians[2].clone()],
+        &heir,
+        &Address::generate(&env),
+    );
+    rejections.expect(Error::ChangeNotDue, || {
+        client.try_apply_beneficiary_change(&owner)
+    });
+    rejections.expect(Error::ChangeAlreadyQueued, || {
+        client.try_replace_beneficiary(
+            &owner,
+            &vec![&env, guardians[2].clone()],
+            &heir,
+            &Address::generate(&env),
+        )
+    });
+    client.cancel_beneficiary_change(&owner);
+
+    // Claims.
+    let quorum = vec![&env, guardians[2].clone()];
+    client.pause_claims(&owner, &quorum);
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(1_000_000 + 4000);
+    rejections.expect(Error::ClaimsPaused, || client.try_claim(&owner));
+    client.resume_claims(&owner, &quorum);
+    client.claim(&owner);
+    rejections.expect(Error::ClaimInProgress, || client.try_escheat(&owner));
+    rejections.expect(Error::ClaimInProgress, || {
+        client.try_replace_beneficiary(&owner, &quorum, &heir, &Address::generate(&env))
+    });
+    rejections.expect(Error::TimelockNotExpired, || {
+        client.try_trigger_payout(&owner)
+    });
+    env.ledger().set_timestamp(1_000_000 + 4000 + 3600);
+    rejections.expect(Error::ChallengeWindowClosed, || {
+        client.try_veto_claim(&owner, &quorum)
+    });
+    client.cancel_claim(&owner);
+    rejections.expect(Error::ClaimWindowOpen, || client.try_escheat(&owner));
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger()
+        .set_timestamp(1_000_000 + 4000 + 3600 * 2 + DEFAULT_CLAIM_WINDOW);
+    rejections.expect(Error::ClaimWindowClosed, || client.try_claim(&owner));
+
+    assert_eq!(token_client.balance(&owner), 9000);
+    assert_eq!(token_client.balance(&contract_id), 1000);
+    rejections.assert_all_seen();
+}
+
+/// Verifies every entrypoint taking a signature refuses calls without one,
+/// and admin entrypoints refuse anyone but the admin. `claim`,
+/// `trigger_payout`, `apply_beneficiary_change`, `escheat` and
+/// `bump_storage` are open to anyone by design.
+#[test]
+fn test_auth_matrix() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, token_client, token_id, admin, treasury) = setup_fee_sharing(&env);
+    env.ledger().set_timestamp(1_000_000);
+
+    let owner = Address::generate(&env);
+    let referrer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let heir = Address::generate(&env);
+    token_client.mint(&owner, &10_000);
+    client.create_plan(
+        &owner,
+        &token_id,
+        &1000,
+        &beneficiary_list(&env, &heir),
+        &3600,
+        &false,
+        &0,
+        &86400,
+        &Some(referrer.clone()),
+    );
+    let guardians = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    client.set_guardians(&owner, &weighted_guardians(&env, &guardians), &2, &86400);
+    let quorum = vec![&env, guardians[2].clone()];
+    client.queue_beneficiary_change(&owner, &single_beneficiary(&env));
+    client.pause_claims(&owner, &quorum);
+    client.resume_claims(&owner, &quorum);
+    deactivate_plan_for_testing(&env, &contract_id, &owner);
+    env.ledger().set_timestamp(1_000_000 + 4000);
+    client.claim(&owner);
+    let hash = BytesN::from_array(&env, &[7; 32]);
+    let limits = TierLimits {
+        max_plan_amount: 5000,
+        max_loan_amount: 0,
+        max_claim_payout: 5000,
+    };
+    let fresh =
+        InheritanceContractClient::new(&env, &env.register_contract(None, InheritanceContract));
+
+    auth_matrix!(
+        env,
+        [
+            fresh.try_initialize(&admin),
+            client.try_set_fee_config(&admin, &100, &0, &treasury),
+            client.try_set_claim_fee(&admin, &50),
+            client.try_set_treasury(&admin, &stranger),
+            client.try_set_fee_approver(&admin, &Some(stranger.clone())),
+            client.try_withdraw_fees(&admin, &None, &token_id, &10, &treasury),
+            client.try_set_claim_window(&admin, &MIN_CLAIM_WINDOW),
+            client.try_set_tier_limits(&admin, &KycTier::Verified, &limits),
+            client.try_set_kyc_tier(&admin, &owner, &KycTier::Verified),
+            client.try_set_metadata_hash(&owner, &hash),
+            client.try_set_attestation_hash(&owner, &hash),
+            client.try_claim_referral_fees(&referrer, &token_id),
+            client.try_create_plan(
+                &stranger,
+                &token_id,
+                &1000,
+                &single_beneficiary(&env),
+                &3600,
+                &false,
+                &0,
+                &0,
+                &None,
+            ),
+            client.try_ping(&owner),
+            client.try_cancel_claim(&owner),
+            client.try_set_guardians(&owner, &weighted_guardians(&env, &guardians), &1, &0),
+            client.try_remove_guardians(&owner),
+            client.try_pause_claims(&owner, &quorum),
+            client.try_resume_claims(&owner, &quorum),
+            client.try_replace_beneficiary(&owner, &quorum, &heir, &stranger),
+            client.try_veto_claim(&owner, &quorum),
+            client.try_set_change_delay(&owner, &0),
+            client.try_queue_beneficiary_change(&owner, &single_beneficiary(&env)),
+            client.try_cancel_beneficiary_change(&owner),
+            client.try_set_fallback_beneficiary(&owner, &Some(stranger.clone())),
+            client.try_close_plan(&owner),
+            client.try_reclaim(&owner),
+        ]
+    );
+
+    auth_matrix!(
+        env,
+        wrong_admin[
+            client.try_set_fee_config(&stranger, &100, &0, &treasury),
+            client.try_set_claim_fee(&stranger, &50),
+            client.try_set_treasury(&stranger, &stranger),
+            client.try_set_fee_approver(&stranger, &None),
+            client.try_withdraw_fees(&stranger, &None, &token_id, &10, &treasury),
+            client.try_set_claim_window(&stranger, &MIN_CLAIM_WINDOW),
+            client.try_set_tier_limits(&stranger, &KycTier::Verified, &limits),
+            client.try_set_kyc_tier(&stranger, &stranger, &KycTier::Enhanced),
+        ]
+    );
+
+    assert_eq!(client.get_kyc_tier(&owner), KycTier::Basic);
+    assert_eq!(client.get_tier_limits(&KycTier::Verified), None);
+    assert_eq!(token_client.balance(&contract_id), 1000);
+}
@@ -0,0 +1,447 @@
+//! Types shared by the InheritX contracts and the off-chain services.
+//!
+//! Each type is declared once and compiled in one of two forms:
+//!
+//! - with the `soroban` feature, addresses, strings and lists are the
+//!   `soroban-sdk` host types and the types are `#[contracttype]`s (errors
+//!   a `#[contracterror]`), as the contracts store and return them;
+//! - with the `serde` feature, they are `alloc` types that serialize with
+//!   the same field names, for the backend indexer and transaction service.
+//!
+//! Error codes and limits live here too, so a contract change that is not
+//! mirrored off-chain fails to compile rather than drifting.
+
+#![no_std]
+
+#[cfg(not(feature = "soroban"))]
+extern crate alloc;
+
+#[cfg(all(feature = "soroban", feature = "serde"))]
+compile_error!("`soroban` and `serde` are separate representations; enable only one");
+
+#[cfg(feature = "soroban")]
+use soroban_sdk::{contracterror, contracttype};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Host types the shared types are built from.
+pub mod host {
+    #[cfg(feature = "soroban")]
+    pub use soroban_sdk::{Address, String, Vec};
+
+    /// A Stellar strkey (`G...` account or `C...` contract).
+    #[cfg(not(feature = "soroban"))]
+    pub type Address = alloc::string::String;
+    #[cfg(not(feature = "soroban"))]
+    pub use alloc::{string::String, vec::Vec};
+}
+
+use host::{Address, String, Vec};
+
+pub const MAX_BENEFICIARIES: u32 = 100;
+pub const MAX_GUARDIANS: u32 = 10;
+pub const BPS_DENOMINATOR: u32 = 10_000;
+/// Delay on beneficiary changes for plans whose owner has not set one.
+pub const DEFAULT_CHANGE_DELAY: u64 = 7 * 86_400;
+pub const MAX_CHANGE_DELAY: u64 = 90 * 86_400;
+/// How long beneficiaries have to claim a timed-out plan before it can be
+/// escheated, unless the admin has set a different window.
+pub const DEFAULT_CLAIM_WINDOW: u64 = 365 * 86_400;
+pub const MIN_CLAIM_WINDOW: u64 = 30 * 86_400;
+pub const MAX_CLAIM_WINDOW: u64 = 3_650 * 86_400;
+
+/// `inheritance-contract` errors, by their on-chain code.
+#[cfg_attr(feature = "soroban", contracterror)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InheritanceError {
+    PlanAlreadyExists = 1,
+    PlanNotFound = 2,
+    Unauthorized = 3,
+    InactivityPeriodNotMet = 4,
+    InvalidBasisPoints = 5,
+    NegativeAmount = 6,
+    InsufficientBalance = 7,
+    TooManyBeneficiaries = 8,
+    TimelockNotExpired = 9,
+    PayoutNotTriggered = 10,
+    AlreadyInitialized = 11,
+    NotInitialized = 12,
+    InvalidFeeConfig = 13,
+    InvalidReferrer = 14,
+    NothingToClaim = 15,
+    InvalidGuardianConfig = 16,
+    GuardiansNotSet = 17,
+    NotGuardian = 18,
+    QuorumNotMet = 19,
+    ClaimsPaused = 20,
+    ChallengeWindowClosed = 21,
+    BeneficiaryNotFound = 22,
+    DuplicateBeneficiary = 23,
+    ApprovalRequired = 24,
+    InsufficientFees = 25,
+    ChangeAlreadyQueued = 26,
+    NoPendingChange = 27,
+    ChangeNotDue = 28,
+    ClaimInProgress = 29,
+    InvalidChangeDelay = 30,
+    ClaimWindowClosed = 31,
+    ClaimWindowOpen = 32,
+    InvalidClaimWindow = 33,
+    TierLimitExceeded = 34,
+    InvalidTierLimits = 35,
+}
+
+impl InheritanceError {
+    pub const ALL: [Self; 35] = [
+        Self::PlanAlreadyExists,
+        Self::PlanNotFound,
+        Self::Unauthorized,
+        Self::InactivityPeriodNotMet,
+        Self::InvalidBasisPoints,
+        Self::NegativeAmount,
+        Self::InsufficientBalance,
+        Self::TooManyBeneficiaries,
+        Self::TimelockNotExpired,
+        Self::PayoutNotTriggered,
+        Self::AlreadyInitialized,
+        Self::NotInitialized,
+        Self::InvalidFeeConfig,
+        Self::InvalidReferrer,
+        Self::NothingToClaim,
+        Self::InvalidGuardianConfig,
+        Self::GuardiansNotSet,
+        Self::NotGuardian,
+        Self::QuorumNotMet,
+        Self::ClaimsPaused,
+        Self::ChallengeWindowClosed,
+        Self::BeneficiaryNotFound,
+        Self::DuplicateBeneficiary,
+        Self::ApprovalRequired,
+        Self::InsufficientFees,
+        Self::ChangeAlreadyQueued,
+        Self::NoPendingChange,
+        Self::ChangeNotDue,
+        Self::ClaimInProgress,
+        Self::InvalidChangeDelay,
+        Self::ClaimWindowClosed,
+        Self::ClaimWindowOpen,
+        Self::InvalidClaimWindow,
+        Self::TierLimitExceeded,
+        Self::InvalidTierLimits,
+    ];
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| *e as u32 == code)
+    }
+
+    /// Stable snake_case name, safe for clients to match on.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PlanAlreadyExists => "plan_already_exists",
+            Self::PlanNotFound => "plan_not_found",
+            Self::Unauthorized => "unauthorized",
+            Self::InactivityPeriodNotMet => "inactivity_period_not_met",
+            Self::InvalidBasisPoints => "invalid_basis_points",
+            Self::NegativeAmount => "negative_amount",
+            Self::InsufficientBalance => "insufficient_balance",
+            Self::TooManyBeneficiaries => "too_many_beneficiaries",
+            Self::TimelockNotExpired => "timelock_not_expired",
+            Self::PayoutNotTriggered => "payout_not_triggered",
+            Self::AlreadyInitialized => "already_initialized",
+            Self::NotInitialized => "not_initialized",
+            Self::InvalidFeeConfig => "invalid_fee_config",
+            Self::InvalidReferrer => "invalid_referrer",
+            Self::NothingToClaim => "nothing_to_claim",
+            Self::InvalidGuardianConfig => "invalid_guardian_config",
+            Self::GuardiansNotSet => "guardians_not_set",
+            Self::NotGuardian => "not_guardian",
+            Self::QuorumNotMet => "quorum_not_met",
+            Self::ClaimsPaused => "claims_paused",
+            Self::ChallengeWindowClosed => "challenge_window_closed",
+            Self::BeneficiaryNotFound => "beneficiary_not_found",
+            Self::DuplicateBeneficiary => "duplicate_beneficiary",
+            Self::ApprovalRequired => "approval_required",
+            Self::InsufficientFees => "insufficient_fees",
+            Self::ChangeAlreadyQueued => "change_already_queued",
+            Self::NoPendingChange => "no_pending_change",
+            Self::ChangeNotDue => "change_not_due",
+            Self::ClaimInProgress => "claim_in_progress",
+            Self::InvalidChangeDelay => "invalid_change_delay",
+            Self::ClaimWindowClosed => "claim_window_closed",
+            Self::ClaimWindowOpen => "claim_window_open",
+            Self::InvalidClaimWindow => "invalid_claim_window",
+            Self::TierLimitExceeded => "tier_limit_exceeded",
+            Self::InvalidTierLimits => "invalid_tier_limits",
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::PlanAlreadyExists => "This owner already has an inheritance plan",
+            Self::PlanNotFound => "No inheritance plan exists for this owner",
+            Self::Unauthorized => "The caller is not allowed to perform this action",
+            Self::InactivityPeriodNotMet => "The owner's inactivity period has not elapsed yet",
+            Self::InvalidBasisPoints => "Beneficiary allocations must add up to 10000 basis points",
+            Self::NegativeAmount => "Amounts must not be negative",
+            Self::InsufficientBalance => "The plan balance is too low for this amount",
+            Self::TooManyBeneficiaries => "The plan has too many beneficiaries",
+            Self::TimelockNotExpired => "The claim timelock has not expired yet",
+            Self::PayoutNotTriggered => "The payout has not been triggered yet",
+            Self::AlreadyInitialized => "The contract has already been initialized",
+            Self::NotInitialized => "The contract has not been initialized",
+            Self::InvalidFeeConfig => "Fee rates must not exceed 10000 basis points",
+            Self::InvalidReferrer => "A plan owner cannot refer their own plan",
+            Self::NothingToClaim => "There are no referral fees to claim",
+            Self::InvalidGuardianConfig => {
+                "Guardians must be distinct, non-zero weighted and able to meet the threshold"
+            }
+            Self::GuardiansNotSet => "This plan has no guardians",
+            Self::NotGuardian => "An approver is not a guardian of this plan",
+            Self::QuorumNotMet => "The approving guardians do not meet the plan's threshold",
+            Self::ClaimsPaused => "Guardians have paused claims on this plan",
+            Self::ChallengeWindowClosed => {
+                "The guardians' challenge window for this claim has closed"
+            }
+            Self::BeneficiaryNotFound => "The address is not a beneficiary of this plan",
+            Self::DuplicateBeneficiary => "The address is already a beneficiary of this plan",
+            Self::ApprovalRequired => "The fee approver must co-sign this withdrawal",
+            Self::InsufficientFees => "The amount exceeds the collected fee balance",
+            Self::ChangeAlreadyQueued => "A beneficiary change is already queued for this plan",
+            Self::NoPendingChange => "No beneficiary change is queued for this plan",
+            Self::ChangeNotDue => "The beneficiary change delay has not passed yet",
+            Self::ClaimInProgress => "Beneficiaries cannot change while a claim is in progress",
+            Self::InvalidChangeDelay => "The change delay must not exceed 90 days",
+            Self::ClaimWindowClosed => "The claim window for this plan has closed",
+            Self::ClaimWindowOpen => "The claim window for this plan is still open",
+            Self::InvalidClaimWindow => "The claim window must be between 30 days and 10 years",
+            Self::TierLimitExceeded => "The amount exceeds the KYC tier's limit",
+            Self::InvalidTierLimits => "Tier limits must not be negative",
+        }
+    }
+}
+
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Beneficiary {
+    pub address: Address,
+    pub allocation_bps: u32,
+    pub fiat_anchor_info: String,
+}
+
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Plan {
+    pub owner: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub beneficiaries: Vec<Beneficiary>,
+    pub last_ping: u64,
+    pub grace_period: u64,
+    pub earn_yield: bool,
+    pub yield_rate_bps: u32,
+    pub is_active: bool,
+    pub timelock_duration: u64,
+    pub referrer: Option<Address>,
+}
+
+/// A plan without its beneficiary list. The contract stores the list in a
+/// separate entry, so the inactivity timer and other hot paths read and
+/// write only this part.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlanSummary {
+    pub owner: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub beneficiary_count: u32,
+    pub last_ping: u64,
+    pub grace_period: u64,
+    pub earn_yield: bool,
+    pub yield_rate_bps: u32,
+    pub is_active: bool,
+    pub timelock_duration: u64,
+    pub referrer: Option<Address>,
+}
+
+/// Platform fee taken from the deposit at plan creation.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfig {
+    /// Share of each deposit charged as the platform fee.
+    pub platform_fee_bps: u32,
+    /// Share of the platform fee credited to the plan's referrer, if any.
+    pub referral_fee_bps: u32,
+    /// Default destination of fee withdrawals. A single admin may only
+    /// withdraw here.
+    pub treasury: Address,
+}
+
+/// Platform fees held by the contract for one token.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FeeAccount {
+    /// Fees retained from deposits, net of referral shares.
+    pub collected: i128,
+    pub withdrawn: i128,
+}
+
+impl FeeAccount {
+    pub fn balance(&self) -> i128 {
+        self.collected - self.withdrawn
+    }
+}
+
+/// A recovery guardian and the weight its approval carries.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Guardian {
+    pub address: Address,
+    pub weight: u32,
+}
+
+/// Guardians a plan owner trusts to act jointly on the plan.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianSet {
+    pub guardians: Vec<Guardian>,
+    /// Combined weight of approving guardians required for any action.
+    pub threshold: u32,
+    /// Seconds after a claim is triggered during which guardians may veto
+    /// it. Payout waits for this window as well as the plan timelock.
+    pub challenge_window: u64,
+}
+
+/// Beneficiaries the owner queued to replace the plan's current ones.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingBeneficiaryChange {
+    pub beneficiaries: Vec<Beneficiary>,
+    pub queued_at: u64,
+    /// The change can be applied from this time on; until then the owner
+    /// may cancel it.
+    pub effective_at: u64,
+}
+
+/// How long beneficiary changes on a plan wait before they can be applied.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangeDelay {
+    pub delay: u64,
+    /// A shorter delay that replaces `delay` at `lowered_at`, so lowering
+    /// the delay is itself held back by the current one.
+    pub lowered_to: Option<u64>,
+    pub lowered_at: u64,
+}
+
+/// KYC verification level of an address. Higher tiers allow larger plans,
+/// loans and claim payouts.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub enum KycTier {
+    #[default]
+    Basic = 0,
+    Verified = 1,
+    Enhanced = 2,
+}
+
+impl KycTier {
+    pub const ALL: [Self; 3] = [Self::Basic, Self::Verified, Self::Enhanced];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Basic => "basic",
+            Self::Verified => "verified",
+            Self::Enhanced => "enhanced",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|tier| tier.name() == name)
+    }
+}
+
+/// Largest amounts, in token base units, a KYC tier allows.
+#[cfg_attr(feature = "soroban", contracttype)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierLimits {
+    /// Largest plan an owner of the tier can create.
+    pub max_plan_amount: i128,
+    /// Largest loan a borrower of the tier can take.
+    pub max_loan_amount: i128,
+    /// Largest share a beneficiary of the tier can be paid from one plan.
+    pub max_claim_payout: i128,
+}
+
+impl TierLimits {
+    pub fn is_valid(&self) -> bool {
+        self.max_plan_amount >= 0 && self.max_loan_amount >= 0 && self.max_claim_payout >= 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes_round_trip() {
+        for error in InheritanceError::ALL {
+            assert_eq!(InheritanceError::from_code(error as u32), Some(error));
+        }
+        assert_eq!(InheritanceError::from_code(0), None);
+        assert_eq!(
+            InheritanceError::from_code(InheritanceError::ALL.len() as u32 + 1),
+            None
+        );
+    }
+
+    #[test]
+    fn kyc_tiers_round_trip_by_name() {
+        for tier in KycTier::ALL {
+            assert_eq!(KycTier::from_name(tier.name()), Some(tier));
+        }
+        assert_eq!(KycTier::from_name("gold"), None);
+        assert!(KycTier::Basic < KycTier::Enhanced);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn plans_serialize_with_contract_field_names() {
+        use alloc::string::ToString;
+        use alloc::vec;
+
+        let plan = Plan {
+            owner: "GOWNER".to_string(),
+            token: "CTOKEN".to_string(),
+            amount: 1_000,
+            beneficiaries: vec![Beneficiary {
+                address: "GHEIR".to_string(),
+                allocation_bps: BPS_DENOMINATOR,
+                fiat_anchor_info: String::new(),
+            }],
+            last_ping: 1,
+            grace_period: 2,
+            earn_yield: false,
+            yield_rate_bps: 0,
+            is_active: true,
+            timelock_duration: 3,
+            referrer: None,
+        };
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["beneficiaries"][0]["allocation_bps"], 10_000);
+        assert_eq!(json["timelock_duration"], 3);
+        assert_eq!(serde_json::from_value::<Plan>(json).unwrap(), plan);
+    }
+}
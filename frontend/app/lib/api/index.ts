@@ -63,6 +63,7 @@ export type {
   PingRequest,
   PayoutRequest,
   BeneficiaryResponse,
+  Money,
   PlanResponse,
   PingResponse,
   PayoutRow,
@@ -65,6 +65,13 @@ export interface PayoutRequest {
 
 // ─── Response DTOs ───────────────────────────────────────────────────────────
 
+/** Exact token amount; `amount` is a string-encoded Decimal. */
+export interface Money {
+  amount: string;
+  /** Asset the amount is in (e.g. the plan's token address) */
+  currency: string;
+}
+
 export interface BeneficiaryResponse {
   /** Unique beneficiary record ID */
   id: string;
@@ -85,8 +92,8 @@ export interface PlanResponse {
   owner_address: string;
   /** Token contract address */
   token_address: string;
-  /** Locked amount (string-encoded Decimal for precision) */
-  amount: string;
+  /** Locked amount */
+  amount: Money;
   /** Grace period in seconds */
   grace_period: number;
   /** Grace period in seconds (duplicate for backwards compat) */
@@ -102,7 +109,7 @@ export interface PlanResponse {
   /** Yield rate in basis points */
   yield_rate_bps: number;
   /** Accrued yield amount */
-  accrued_yield: number;
+  accrued_yield: Money;
   /** ISO-8601 creation timestamp */
   created_at: string;
   /** Beneficiaries attached to this plan */
@@ -125,8 +132,14 @@ export interface PayoutRow {
   plan_id: string;
   /** Beneficiary wallet address */
   beneficiary_address: string;
-  /** Payout amount (string-encoded Decimal) */
-  amount: string;
+  /** Wallet paid when the beneficiary split their payout; null pays beneficiary_address */
+  destination_address: string | null;
+  /** Payout amount in the plan's token */
+  amount: Money;
+  /** Token the payout is converted to; null pays the plan's token */
+  payout_asset: string | null;
+  /** Amount delivered in payout_asset */
+  converted_amount: Money | null;
   /** Payout type (fiat, crypto, etc.) */
   payout_type: string;
   /** Payout status (PENDING, COMPLETED, FAILED) */
@@ -284,7 +284,8 @@ describe("InheritanceAPI", () => {
         expect(payout.id).toBeDefined();
         expect(payout.plan_id).toBeDefined();
         expect(payout.beneficiary_address).toBeDefined();
-        expect(payout.amount).toBeDefined();
+        expect(typeof payout.amount.amount).toBe("string");
+        expect(payout.amount.currency).toBeDefined();
         expect(payout.status).toBeDefined();
         expect(payout.created_at).toBeDefined();
       }
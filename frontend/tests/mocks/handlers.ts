@@ -108,7 +108,10 @@ export const plansHandlers = [
         id: planId,
         owner_address: body.owner as string,
         token_address: body.token as string,
-        amount: String(body.amount ?? "0"),
+        amount: {
+          amount: String(body.amount ?? "0"),
+          currency: body.token as string,
+        },
         grace_period: body.grace_period as number,
         grace_period_seconds: body.grace_period as number,
         earn_yield: (body.earn_yield as boolean) ?? false,
@@ -116,7 +119,7 @@ export const plansHandlers = [
         is_active: (body.is_active as boolean) ?? true,
         status: "ACTIVE",
         yield_rate_bps: (body.yield_rate_bps as number) ?? 0,
-        accrued_yield: 0,
+        accrued_yield: { amount: "0", currency: body.token as string },
         created_at: now,
         beneficiaries: beneficiaryResponses,
       },
@@ -820,7 +823,10 @@ export const anchorHandlers = [
         id: "payout_001",
         plan_id: "plan_inherit_001",
         beneficiary_address: "GBENEFICIARY1ADDRESS",
-        amount: "500.00",
+        destination_address: null,
+        amount: { amount: "500.00", currency: "USDC" },
+        payout_asset: null,
+        converted_amount: null,
         payout_type: "fiat",
         status: "COMPLETED",
         created_at: new Date(Date.now() - 86400000).toISOString(),
@@ -829,7 +835,10 @@ export const anchorHandlers = [
         id: "payout_002",
         plan_id: "plan_inherit_002",
         beneficiary_address: "GBENEFICIARY2ADDRESS",
-        amount: "1250.50",
+        destination_address: null,
+        amount: { amount: "1250.50", currency: "USDC" },
+        payout_asset: null,
+        converted_amount: null,
         payout_type: "crypto",
         status: "PENDING",
         created_at: new Date(Date.now() - 3600000).toISOString(),
@@ -838,7 +847,10 @@ export const anchorHandlers = [
         id: "payout_003",
         plan_id: "plan_inherit_003",
         beneficiary_address: "GBENEFICIARY1ADDRESS",
-        amount: "300.25",
+        destination_address: null,
+        amount: { amount: "300.25", currency: "USDC" },
+        payout_asset: null,
+        converted_amount: null,
         payout_type: "fiat",
         status: "FAILED",
         created_at: new Date(Date.now() - 172800000).toISOString(),
@@ -847,7 +859,10 @@ export const anchorHandlers = [
         id: "payout_004",
         plan_id: "plan_inherit_004",
         beneficiary_address: "GBENEFICIARY3ADDRESS",
-        amount: "7500.00",
+        destination_address: null,
+        amount: { amount: "7500.00", currency: "USDC" },
+        payout_asset: null,
+        converted_amount: null,
         payout_type: "crypto",
         status: "COMPLETED",
         created_at: new Date(Date.now() - 259200000).toISOString(),